@@ -0,0 +1,57 @@
+//! Minimal editor client for the drift event stream.
+//!
+//! Follows `drift events --follow --all --format jsonlines` and prints one
+//! line per event an editor would surface as virtual text or a diagnostic:
+//!
+//! ```sh
+//! cargo run --example editor_events
+//! ```
+//!
+//! An editor plugin does the same with its own job API, e.g. in neovim
+//! `vim.fn.jobstart({"drift", "events", "-f", "--all", "--format", "jsonlines"}, { on_stdout = ... })`.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+/// Event types worth interrupting an editor for.
+const SHOWN: &[&str] = &["agent.completed", "agent.error", "build.failed", "build.succeeded"];
+
+/// Text to show for one JSON event line, or None for events the editor ignores.
+fn virtual_text(line: &str) -> Option<String> {
+    let event: Value = serde_json::from_str(line).ok()?;
+    let event_type = event.get("type")?.as_str()?;
+    if !SHOWN.contains(&event_type) {
+        return None;
+    }
+    let project = event.get("project").and_then(Value::as_str).unwrap_or("");
+    let title = event.get("title").and_then(Value::as_str).unwrap_or(event_type);
+    Some(format!("[{project}] {title}"))
+}
+
+fn main() -> std::io::Result<()> {
+    let mut child = Command::new("drift")
+        .args(["events", "--follow", "--all", "--format", "jsonlines"])
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("piped stdout");
+
+    for line in BufReader::new(stdout).lines() {
+        if let Some(text) = virtual_text(&line?) {
+            println!("{text}");
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[test]
+fn shows_build_failures_and_skips_noise() {
+    let failed = r#"{"type":"build.failed","project":"myapp","source":"watch","ts":"2026-01-01T00:00:00Z","title":"cargo test failed"}"#;
+    assert_eq!(virtual_text(failed).as_deref(), Some("[myapp] cargo test failed"));
+
+    let focus = r#"{"type":"workspace.activated","project":"myapp","source":"daemon","ts":"2026-01-01T00:00:00Z"}"#;
+    assert_eq!(virtual_text(focus), None);
+    assert_eq!(virtual_text("not json"), None);
+}
@@ -14,6 +14,9 @@ pub enum AddCommand {
         restart: String,
         #[arg(long)]
         cwd: Option<String>,
+        /// Report the open as failed if this service doesn't come up
+        #[arg(long)]
+        required: bool,
         #[arg(long)]
         project: Option<String>,
     },
@@ -78,7 +81,7 @@ fn parse_restart(s: &str) -> anyhow::Result<RestartPolicy> {
 
 pub fn run(cmd: AddCommand) -> anyhow::Result<()> {
     match cmd {
-        AddCommand::Service { name, command, restart, cwd, project } => {
+        AddCommand::Service { name, command, restart, cwd, required, project } => {
             let proj = config::resolve_current_project(project.as_deref())?;
             let mut cfg = config::load_project_config(&proj)?;
             let services = cfg.services.get_or_insert_with(|| ServicesConfig { processes: vec![] });
@@ -90,13 +93,8 @@ pub fn run(cmd: AddCommand) -> anyhow::Result<()> {
                 command,
                 cwd: cwd.unwrap_or_else(|| ".".into()),
                 restart: parse_restart(&restart)?,
-                stop_command: None,
-                agent: None,
-                prompt: None,
-                agent_mode: "oneshot".into(),
-                agent_model: None,
-                agent_permissions: "full".into(),
-                width: None,
+                required,
+                ..Default::default()
             });
             config::save_project_config(&proj, &cfg)?;
             println!("Added service '{name}' to project '{proj}'");
@@ -111,16 +109,13 @@ pub fn run(cmd: AddCommand) -> anyhow::Result<()> {
             }
             services.processes.push(ServiceProcess {
                 name: name.clone(),
-                command: String::new(),
-                cwd: ".".into(),
                 restart: parse_restart(&restart)?,
-                stop_command: None,
                 agent: Some(agent),
                 prompt: Some(prompt),
                 agent_mode: mode,
                 agent_model: model,
                 agent_permissions: permissions,
-                width: None,
+                ..Default::default()
             });
             config::save_project_config(&proj, &cfg)?;
             println!("Added agent '{name}' to project '{proj}'");
@@ -135,9 +130,8 @@ pub fn run(cmd: AddCommand) -> anyhow::Result<()> {
             cfg.windows.push(WindowConfig {
                 name: Some(name.clone()),
                 command,
-                width: None,
                 tmux: if tmux { Some(true) } else { None },
-                app_id: None,
+                ..Default::default()
             });
             config::save_project_config(&proj, &cfg)?;
             println!("Added window '{name}' to project '{proj}'");
@@ -213,15 +207,25 @@ mod tests {
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: None,
             windows: vec![],
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         }
     }
 
@@ -232,15 +236,8 @@ mod tests {
         services.processes.push(ServiceProcess {
             name: "api".into(),
             command: "npm start".into(),
-            cwd: ".".into(),
             restart: RestartPolicy::Never,
-            stop_command: None,
-            agent: None,
-            prompt: None,
-            agent_mode: "oneshot".into(),
-            agent_model: None,
-            agent_permissions: "full".into(),
-            width: None,
+            ..Default::default()
         });
         assert_eq!(cfg.services.as_ref().unwrap().processes.len(), 1);
         assert_eq!(cfg.services.as_ref().unwrap().processes[0].name, "api");
@@ -253,15 +250,8 @@ mod tests {
             processes: vec![ServiceProcess {
                 name: "api".into(),
                 command: "npm start".into(),
-                cwd: ".".into(),
                 restart: RestartPolicy::Never,
-                stop_command: None,
-                agent: None,
-                prompt: None,
-                agent_mode: "oneshot".into(),
-                agent_model: None,
-                agent_permissions: "full".into(),
-                width: None,
+                ..Default::default()
             }],
         });
         let has_dup = cfg.services.as_ref().unwrap().processes.iter().any(|p| p.name == "api");
@@ -274,16 +264,13 @@ mod tests {
         let services = cfg.services.get_or_insert_with(|| ServicesConfig { processes: vec![] });
         services.processes.push(ServiceProcess {
             name: "reviewer".into(),
-            command: String::new(),
-            cwd: ".".into(),
             restart: parse_restart("on-failure").unwrap(),
-            stop_command: None,
             agent: Some("claude".into()),
             prompt: Some("Review code".into()),
             agent_mode: "interactive".into(),
             agent_model: Some("opus".into()),
             agent_permissions: "safe".into(),
-            width: None,
+            ..Default::default()
         });
         let svc = &cfg.services.as_ref().unwrap().processes[0];
         assert_eq!(svc.agent.as_deref(), Some("claude"));
@@ -296,7 +283,7 @@ mod tests {
     #[test]
     fn add_window_duplicate_detection() {
         let mut cfg = minimal_config("test");
-        cfg.windows.push(WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), width: None, tmux: None, app_id: None });
+        cfg.windows.push(WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), ..Default::default() });
         let has_dup = cfg.windows.iter().any(|w| w.name.as_deref() == Some("editor"));
         assert!(has_dup);
     }
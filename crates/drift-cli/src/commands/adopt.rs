@@ -2,19 +2,20 @@ use std::collections::HashSet;
 
 use anyhow::bail;
 use drift_core::config::{ProjectConfig, ProjectMeta, WindowConfig};
-use drift_core::paths;
+use drift_core::{paths, registry};
 use drift_core::sync::generate_window_name;
 
 pub fn run(workspace_name: &str, project_name: Option<&str>) -> anyhow::Result<()> {
     let name = project_name.unwrap_or(workspace_name);
+    registry::check_project_name(name)?;
 
     let dest = paths::project_config_path(name);
     if dest.exists() {
         bail!("Project '{name}' already exists");
     }
 
-    let mut client = drift_core::niri::NiriClient::connect()
-        .map_err(|e| anyhow::anyhow!("Cannot connect to niri: {e}"))?;
+    let mut client = drift_core::compositor::connect()
+        .map_err(|e| anyhow::anyhow!("Cannot connect to the compositor: {e}"))?;
 
     let workspaces = client.workspaces()?;
     let ws = workspaces.iter()
@@ -40,9 +41,7 @@ pub fn run(workspace_name: &str, project_name: Option<&str>) -> anyhow::Result<(
         window_configs.push(WindowConfig {
             name: Some(wname),
             app_id: if is_terminal { None } else { Some(app_id.to_string()) },
-            command: None,
-            width: None,
-            tmux: None,
+            ..Default::default()
         });
     }
 
@@ -60,15 +59,25 @@ pub fn run(workspace_name: &str, project_name: Option<&str>) -> anyhow::Result<(
         },
         auto_close: true,
         persist_windows: None,
+        auto_sync: true,
         env: Default::default(),
         git: None,
+        ssh: None,
         ports: None,
         services: None,
         windows: window_configs,
         tmux: None,
         scratchpad: None,
         verification: None,
+        watch: None,
+        toolchain: None,
         dispatcher: None,
+        hooks: Vec::new(),
+        setup: None,
+        window_rules: Vec::new(),
+        on_open: Vec::new(),
+        on_close: Vec::new(),
+        ignore_windows: Vec::new(),
     };
 
     if let Some(parent) = dest.parent() {
@@ -1,34 +1,57 @@
-use drift_core::{config, kdl, registry};
+use anyhow::bail;
+use drift_core::supervisor::{self, ServiceStatus};
+use drift_core::{config, kdl, registry, tmux};
 
-pub fn archive(name: &str) -> anyhow::Result<()> {
+pub fn archive(name: &str, force: bool, keep_state: bool) -> anyhow::Result<()> {
     let _project = config::load_project_config(name)?;
     let global = config::load_global_config()?;
 
-    // Close workspace if open
-    if let Ok(mut niri_client) = drift_core::niri::NiriClient::connect() {
-        if niri_client.find_workspace_by_name(name)?.is_some() {
-            super::close::close_project(name)?;
-            println!("  Closed workspace");
+    let supervisor_up = supervisor::supervisor_running(name);
+    if supervisor_up && !force {
+        let running: Vec<String> = supervisor::read_state(name)
+            .map(|state| {
+                state
+                    .services
+                    .into_iter()
+                    .filter(|s| s.status == ServiceStatus::Running)
+                    .map(|s| s.name)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !running.is_empty() {
+            bail!(
+                "Project '{name}' has running services ({}).\nClose it first or re-run with --force to stop them.",
+                running.join(", ")
+            );
+        }
+    }
+
+    // Close workspace if open, and stop the supervisor even without one
+    let workspace_open = drift_core::compositor::connect()
+        .ok()
+        .and_then(|mut c| c.find_workspace_by_name(name).ok().flatten())
+        .is_some();
+    if workspace_open || supervisor_up {
+        super::close::close_project(name, super::close::CloseOptions::default())?;
+        println!("  Closed workspace");
+    }
+    // An archived project has no use for its sessions, whatever kill_on_close says
+    for session in tmux::project_session_names(name) {
+        if tmux::kill_session(&session) {
+            println!("  Killed tmux session '{session}'");
         }
     }
 
     registry::archive_project(name)?;
+    if keep_state && registry::pack_state(name)? {
+        println!("  Saved state to {}", registry::archived_state_path(name).display());
+    }
 
-    // Regenerate niri-rules
-    let all_projects = registry::list_projects()?;
-    kdl::write_niri_rules(&all_projects, &global)?;
-
-    drift_core::events::try_emit_event(&drift_core::events::Event {
-        event_type: "drift.project.archived".into(),
-        project: name.to_string(),
-        source: "drift".into(),
-        ts: drift_core::events::iso_now(),
-        level: Some("info".into()),
-        title: Some(format!("Archived project '{name}'")),
-        body: None,
-        meta: None,
-        priority: None,
-    });
+    kdl::refresh_niri_rules(&global)?;
+
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("drift.project.archived", name, "drift")
+        .level("info")
+        .title(format!("Archived project '{name}'")));
 
     println!("Archived project '{name}'");
     Ok(())
@@ -37,23 +60,15 @@ pub fn archive(name: &str) -> anyhow::Result<()> {
 pub fn unarchive(name: &str) -> anyhow::Result<()> {
     let global = config::load_global_config()?;
 
-    registry::unarchive_project(name)?;
-
-    // Regenerate niri-rules
-    let all_projects = registry::list_projects()?;
-    kdl::write_niri_rules(&all_projects, &global)?;
-
-    drift_core::events::try_emit_event(&drift_core::events::Event {
-        event_type: "drift.project.unarchived".into(),
-        project: name.to_string(),
-        source: "drift".into(),
-        ts: drift_core::events::iso_now(),
-        level: Some("info".into()),
-        title: Some(format!("Unarchived project '{name}'")),
-        body: None,
-        meta: None,
-        priority: None,
-    });
+    if registry::unarchive_project(name)? {
+        println!("  Restored saved state");
+    }
+
+    kdl::refresh_niri_rules(&global)?;
+
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("drift.project.unarchived", name, "drift")
+        .level("info")
+        .title(format!("Unarchived project '{name}'")));
 
     println!("Unarchived project '{name}'");
     Ok(())
@@ -1,19 +1,40 @@
 use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
 
 use anyhow::bail;
-use drift_core::{niri, paths};
+use drift_core::lifecycle::{self, DirtyState};
+use drift_core::{compositor, paths, timings};
 use nix::sys::signal::{self, Signal};
 use nix::unistd::Pid;
 
-/// Stop supervisor, close windows, unset workspace name, clean up state.
-/// Does NOT emit events or print summary — callers handle that.
-pub fn close_project(project_name: &str) -> anyhow::Result<()> {
+/// What a close leaves out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CloseOptions {
+    /// Don't run the project's `on_close` commands
+    pub skip_hooks: bool,
+    /// Close the windows but leave the supervisor and its services running
+    pub keep_services: bool,
+}
+
+/// Run `on_close`, stop supervisor, close windows, unset workspace name,
+/// clean up state. Does NOT emit events or print summary — callers handle that.
+pub fn close_project(project_name: &str, options: CloseOptions) -> anyhow::Result<()> {
+    let mut timer = timings::Timer::start("close", project_name);
+    // Before teardown, so the commands can still reach the services
+    if !options.skip_hooks {
+        if let Ok(project) = drift_core::config::load_project_config(project_name) {
+            super::open::run_hooks(&project, drift_core::project_hooks::Stage::Close);
+            timer.step("hooks");
+        }
+    }
+
     // Read supervisor PID before teardown (teardown removes the PID file)
-    let supervisor_pid = read_supervisor_pid(project_name);
+    let supervisor_pid = read_supervisor_pid(project_name).filter(|_| !options.keep_services);
 
     // Non-blocking teardown: save workspace, kill tmux, SIGTERM supervisor,
     // remove state files, remove from session
-    drift_core::lifecycle::teardown_project(project_name);
+    drift_core::lifecycle::teardown_project(project_name, options.keep_services);
+    timer.step("teardown");
 
     // Wait for supervisor to actually die (blocking)
     if let Some(pid) = supervisor_pid {
@@ -30,10 +51,15 @@ pub fn close_project(project_name: &str) -> anyhow::Result<()> {
         }
 
         println!("  Stopped supervisor (PID {pid})");
+        timer.step("supervisor");
     }
 
-    // Close all windows on the workspace
-    let mut niri_client = niri::NiriClient::connect()?;
+    // Close all windows on the workspace (none to close without a
+    // compositor, e.g. over ssh)
+    let Ok(mut niri_client) = compositor::connect() else {
+        timer.finish();
+        return Ok(());
+    };
     if let Some(ws) = niri_client.find_workspace_by_name(project_name)? {
         let ws_id = ws.id;
         let windows = niri_client.windows()?;
@@ -46,7 +72,9 @@ pub fn close_project(project_name: &str) -> anyhow::Result<()> {
 
     // Unset workspace name so it becomes dynamic and gets auto-removed
     let _ = niri_client.unset_workspace_name(project_name);
+    timer.step("windows");
 
+    timer.finish();
     Ok(())
 }
 
@@ -62,27 +90,199 @@ fn read_supervisor_pid(project_name: &str) -> Option<i32> {
     }
 }
 
-pub fn run(name: Option<&str>) -> anyhow::Result<()> {
+pub fn run(name: Option<&str>, all: bool, force: bool, dry_run: bool, options: CloseOptions) -> anyhow::Result<()> {
+    if dry_run {
+        let projects = if all { open_projects()? } else { vec![resolve_project_name(name)?] };
+        return print_plan(&projects, force, options);
+    }
+    if all {
+        return close_many(&open_projects()?, force, options);
+    }
+
     let project_name = resolve_project_name(name)?;
 
-    close_project(&project_name)?;
-
-    drift_core::events::try_emit_event(&drift_core::events::Event {
-        event_type: "drift.project.closed".into(),
-        project: project_name.clone(),
-        source: "drift".into(),
-        ts: drift_core::events::iso_now(),
-        level: Some("info".into()),
-        title: Some(format!("Closed project '{project_name}'")),
-        body: None,
-        meta: None,
-        priority: None,
-    });
-
-    println!("Closed project '{project_name}'");
+    let dirty = if force { DirtyState::default() } else { check_dirty(&project_name, options) };
+    if dirty.needs_confirmation() && !confirm_dirty(&[(project_name.clone(), &dirty)])? {
+        println!("Close cancelled");
+        return Ok(());
+    }
+
+    close_project(&project_name, options)?;
+    emit_closed(&project_name);
+    if dirty.is_dirty() {
+        emit_closed_dirty(&project_name, &dirty);
+    }
+
+    if options.keep_services {
+        println!("Closed project '{project_name}' (services still running)");
+    } else {
+        println!("Closed project '{project_name}'");
+    }
+    Ok(())
+}
+
+/// What closing would lose; services don't count when they're kept.
+fn check_dirty(project_name: &str, options: CloseOptions) -> DirtyState {
+    let mut dirty = lifecycle::dirty_state(project_name);
+    if options.keep_services {
+        dirty.running_services.clear();
+    }
+    dirty
+}
+
+/// Close `open` projects one after another, asking once up front if any
+/// has uncommitted work or running services.
+pub(crate) fn close_many(open: &[String], force: bool, options: CloseOptions) -> anyhow::Result<()> {
+    if open.is_empty() {
+        println!("No open projects");
+        return Ok(());
+    }
+
+    let dirty: Vec<(String, DirtyState)> = if force {
+        Vec::new()
+    } else {
+        open.iter()
+            .map(|p| (p.clone(), check_dirty(p, options)))
+            .filter(|(_, d)| d.needs_confirmation())
+            .collect()
+    };
+    let listed: Vec<(String, &DirtyState)> = dirty.iter().map(|(p, d)| (p.clone(), d)).collect();
+    if !listed.is_empty() && !confirm_dirty(&listed)? {
+        println!("Close cancelled");
+        return Ok(());
+    }
+
+    let mut failures = 0;
+    for project_name in open {
+        println!("Closing '{project_name}'...");
+        match close_project(project_name, options) {
+            Ok(()) => {
+                emit_closed(project_name);
+                if let Some((_, d)) = dirty.iter().find(|(p, d)| p == project_name && d.is_dirty()) {
+                    emit_closed_dirty(project_name, d);
+                }
+            }
+            Err(e) => {
+                eprintln!("  Failed to close '{project_name}': {e}");
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        bail!("Closed {} projects, {failures} failed", open.len() - failures);
+    }
+    println!("Closed {} projects", open.len());
+    Ok(())
+}
+
+/// `--dry-run`: print what closing `projects` would do, touching nothing.
+fn print_plan(projects: &[String], force: bool, options: CloseOptions) -> anyhow::Result<()> {
+    if projects.is_empty() {
+        println!("No open projects");
+        return Ok(());
+    }
+    let mut niri_client = compositor::connect().ok();
+    for project_name in projects {
+        println!("Plan for closing '{project_name}' (dry run, nothing changed)");
+        let config = drift_core::config::load_project_config(project_name);
+        if let (false, Ok(cfg)) = (options.skip_hooks, &config) {
+            for command in &cfg.on_close {
+                println!("  Run on_close: {command}");
+            }
+        }
+        println!("  Save workspace snapshot");
+        if let Ok(cfg) = config {
+            if cfg.tmux.is_some_and(|t| t.kill_on_close) {
+                for session in drift_core::tmux::project_session_names(project_name) {
+                    println!("  Kill tmux session '{session}'");
+                }
+            }
+        }
+        match read_supervisor_pid(project_name) {
+            Some(pid) if options.keep_services => println!("  Leave supervisor (PID {pid}) and its services running"),
+            Some(pid) => println!("  Stop supervisor (PID {pid}) and its services"),
+            None => println!("  No supervisor running"),
+        }
+        let windows = niri_client.as_mut().and_then(|c| {
+            let ws = c.find_workspace_by_name(project_name).ok()??;
+            Some(c.windows().ok()?.iter().filter(|w| w.workspace_id == Some(ws.id)).count())
+        });
+        match windows {
+            Some(n) => println!("  Close {n} windows and release workspace '{project_name}'"),
+            None => println!("  Workspace '{project_name}' is not open"),
+        }
+        if !force {
+            let dirty = check_dirty(project_name, options);
+            if dirty.needs_confirmation() {
+                println!("  Warning: {}; close would ask first", dirty.summary());
+            }
+        }
+    }
     Ok(())
 }
 
+/// Registered projects whose workspace is currently open, in registry order.
+pub(crate) fn open_projects() -> anyhow::Result<Vec<String>> {
+    let mut client = compositor::connect()?;
+    let names: Vec<String> = client
+        .workspaces()?
+        .into_iter()
+        .filter_map(|ws| ws.name)
+        .collect();
+    Ok(drift_core::registry::list_projects()?
+        .into_iter()
+        .map(|p| p.project.name)
+        .filter(|name| names.contains(name))
+        .collect())
+}
+
+pub(crate) fn emit_closed(project_name: &str) {
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("drift.project.closed", project_name, "drift")
+        .level("info")
+        .title(format!("Closed project '{project_name}'")));
+}
+
+/// Warn about projects with unsaved work. Asks for confirmation on a
+/// terminal; from keybindings and scripts it only warns and goes ahead.
+fn confirm_dirty(dirty: &[(String, &DirtyState)]) -> anyhow::Result<bool> {
+    for (project, state) in dirty {
+        eprintln!("Warning: '{project}' has {}", state.summary());
+        for line in state.uncommitted.iter().take(10) {
+            eprintln!("    {line}");
+        }
+        if state.uncommitted.len() > 10 {
+            eprintln!("    ... and {} more", state.uncommitted.len() - 10);
+        }
+        for file in &state.editor_files {
+            eprintln!("    open: {file}");
+        }
+        if !state.running_services.is_empty() {
+            eprintln!("    services: {} (--keep-services leaves them running)", state.running_services.join(", "));
+        }
+    }
+    if !std::io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    eprint!("Close anyway? [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn emit_closed_dirty(project_name: &str, dirty: &DirtyState) {
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("project.closed_dirty", project_name, "drift")
+        .level("warning")
+        .title(format!("Closed '{project_name}' with {}", dirty.summary()))
+        .meta(serde_json::json!({
+            "uncommitted": dirty.uncommitted.len(),
+            "files": dirty.uncommitted,
+            "scratchpad_open": dirty.scratchpad_open,
+            "editor_files": dirty.editor_files,
+        })));
+}
+
 fn resolve_project_name(name: Option<&str>) -> anyhow::Result<String> {
     if let Some(n) = name {
         return Ok(n.to_string());
@@ -95,7 +295,7 @@ fn resolve_project_name(name: Option<&str>) -> anyhow::Result<String> {
     }
 
     // Try to detect from focused workspace
-    if let Ok(mut client) = niri::NiriClient::connect() {
+    if let Ok(mut client) = compositor::connect() {
         if let Ok(Some(win)) = client.focused_window() {
             if let Some(ws_id) = win.workspace_id {
                 if let Ok(workspaces) = client.workspaces() {
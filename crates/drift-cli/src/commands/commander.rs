@@ -3,6 +3,7 @@ use std::process::{Command, Stdio};
 use std::time::Duration;
 
 use anyhow::Context;
+use drift_core::mute::{self, Mute, MuteState};
 use drift_core::paths;
 
 pub fn start() -> anyhow::Result<()> {
@@ -91,7 +92,8 @@ pub fn status() -> anyhow::Result<()> {
         false
     };
 
-    let muted = paths::commander_muted_path().exists();
+    let now = mute::now();
+    let mutes = MuteState::load();
 
     if running {
         let pid = fs::read_to_string(&pid_path)
@@ -103,8 +105,24 @@ pub fn status() -> anyhow::Result<()> {
         println!("Status: stopped");
     }
 
-    if muted {
-        println!("Muted: yes");
+    // Restarts are counted by the daemon, which supervises the commander it launched
+    let supervised = fs::read_to_string(paths::daemon_state_path())
+        .ok()
+        .and_then(|json| serde_json::from_str::<drift_daemon::state::DaemonState>(&json).ok())
+        .and_then(|state| state.commander);
+    if let Some(supervised) = supervised {
+        println!("Supervised by daemon: yes (restarts: {})", supervised.restarts);
+        if let Some(ts) = &supervised.last_crash {
+            println!("Last crash: {ts}");
+        }
+    }
+
+    let until = |m: &Mute| m.until.map(|ts| format!(" until {}", mute::format_clock(ts))).unwrap_or_default();
+    if let Some(all) = mutes.all.filter(|m| m.active(now)) {
+        println!("Muted: yes{}", until(&all));
+    }
+    for (project, m) in mutes.projects.iter().filter(|(_, m)| m.active(now)) {
+        println!("Muted project: {project}{}", until(m));
     }
 
     let config = drift_core::config::load_global_config().unwrap_or_default();
@@ -113,6 +131,9 @@ pub fn status() -> anyhow::Result<()> {
     if let Some(fb) = &config.commander.fallback_engine {
         println!("Fallback: {fb}");
     }
+    if !config.commander.quiet_hours.is_empty() {
+        println!("Quiet hours: {}", config.commander.quiet_hours.join(", "));
+    }
     println!("Voice control: {}", if config.commander.voice_enabled { "enabled" } else { "disabled" });
     if config.commander.voice_enabled {
         println!("Wake word: {}", config.commander.wake_word);
@@ -218,21 +239,56 @@ pub fn say(text: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn mute() -> anyhow::Result<()> {
-    let path = paths::commander_muted_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
+pub fn test_pipeline() -> anyhow::Result<()> {
+    let status = Command::new("drift-commander")
+        .arg("--test-pipeline")
+        .status()
+        .context("running drift-commander (is it installed?)")?;
+    if !status.success() {
+        anyhow::bail!("TTS pipeline check failed");
     }
-    fs::write(&path, "")?;
-    println!("Commander muted");
     Ok(())
 }
 
-pub fn unmute() -> anyhow::Result<()> {
-    let path = paths::commander_muted_path();
-    if path.exists() {
-        fs::remove_file(&path)?;
+pub fn mute(project: Option<&str>, until: Option<&str>) -> anyhow::Result<()> {
+    let now = mute::now();
+    let until = until.map(|t| mute::next_clock(t, now)).transpose()?;
+    let mut state = MuteState::load();
+    let entry = Mute { until };
+    let target = match project {
+        Some(name) => {
+            state.projects.insert(name.to_string(), entry);
+            format!("Muted {name}")
+        }
+        None => {
+            state.all = Some(entry);
+            "Commander muted".to_string()
+        }
+    };
+    state.save(now)?;
+    match until {
+        Some(ts) => println!("{target} until {}", mute::format_clock(ts)),
+        None => println!("{target}"),
+    }
+    Ok(())
+}
+
+pub fn unmute(project: Option<&str>) -> anyhow::Result<()> {
+    let now = mute::now();
+    match project {
+        Some(name) => {
+            let mut state = MuteState::load();
+            if state.projects.remove(name).is_none() {
+                println!("{name} is not muted");
+                return Ok(());
+            }
+            state.save(now)?;
+            println!("Unmuted {name}");
+        }
+        None => {
+            MuteState::default().save(now)?;
+            println!("Commander unmuted");
+        }
     }
-    println!("Commander unmuted");
     Ok(())
 }
@@ -1,3 +1,3 @@
-pub fn run() -> anyhow::Result<()> {
-    drift_daemon::run_daemon()
+pub fn run(autostart: bool) -> anyhow::Result<()> {
+    drift_daemon::run_daemon(autostart)
 }
@@ -0,0 +1,379 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use anyhow::bail;
+use drift_core::events::Event;
+use drift_core::mute::{self, Mute, MuteState};
+use drift_core::shell::{self, build_terminal_args};
+use drift_core::{compositor, config, paths, registry};
+
+use super::status::{services_summary, Overview};
+
+/// Events kept for the feed
+const FEED_SIZE: usize = 200;
+const REFRESH: Duration = Duration::from_secs(2);
+
+enum Msg {
+    Key(Key),
+    Event(Box<Event>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Up,
+    Down,
+    Focus,
+    Mute,
+    Logs,
+    Quit,
+}
+
+struct Dash {
+    projects: Vec<ProjectRow>,
+    active: Option<String>,
+    /// Terminal (rows, cols)
+    size: (usize, usize),
+    /// When projects, active and size were last read
+    refreshed: Instant,
+    selected: usize,
+    feed: VecDeque<Event>,
+    /// Last action's result, shown in the footer
+    status: String,
+}
+
+struct ProjectRow {
+    name: String,
+    workspace: String,
+    services: String,
+    muted: bool,
+}
+
+impl Dash {
+    /// Re-read the project table and terminal size. Done every `REFRESH`
+    /// and after a key, not per event, since it reads daemon.json and
+    /// every project's services and runs `stty`.
+    fn refresh(&mut self) -> anyhow::Result<()> {
+        let overview = Overview::load();
+        let mutes = MuteState::load();
+        let now = mute::now();
+        self.projects = registry::list_projects()?
+            .into_iter()
+            .map(|p| {
+                let name = p.project.name;
+                ProjectRow {
+                    workspace: overview.workspace(&name),
+                    services: services_summary(&name),
+                    muted: mutes.is_muted(&name, now),
+                    name,
+                }
+            })
+            .collect();
+        self.active = overview.daemon().and_then(|s| s.active_project.clone());
+        self.size = terminal_size();
+        self.selected = self.selected.min(self.projects.len().saturating_sub(1));
+        self.refreshed = Instant::now();
+        Ok(())
+    }
+}
+
+/// Raw mode and the alternate screen for as long as it lives. Restoring on
+/// drop covers errors and panics too, which would otherwise leave the
+/// terminal without echo.
+struct Screen {
+    saved: String,
+}
+
+impl Screen {
+    fn enter() -> anyhow::Result<Screen> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+        // Alternate screen, hidden cursor
+        print!("\x1b[?1049h\x1b[?25l");
+        Ok(Screen { saved })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Full-screen view of every project's workspace and services with a live
+/// event feed from the subscribe socket. Keys act on the selected project.
+pub fn run() -> anyhow::Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        bail!("drift dash needs a terminal");
+    }
+    let mut dash = Dash {
+        projects: Vec::new(),
+        active: None,
+        size: (24, 80),
+        refreshed: Instant::now(),
+        selected: 0,
+        feed: VecDeque::new(),
+        status: String::new(),
+    };
+    // Start with what the daemon already buffered
+    if let Some(state) = Overview::load().daemon() {
+        let mut recent: Vec<Event> = state.recent_events.values().flatten().cloned().collect();
+        recent.sort_by(|a, b| a.ts.cmp(&b.ts));
+        dash.feed.extend(recent.into_iter().rev().take(FEED_SIZE).rev());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let events_tx = tx.clone();
+    std::thread::spawn(move || subscribe(events_tx));
+    std::thread::spawn(move || read_keys(tx));
+
+    let _screen = Screen::enter()?;
+    event_loop(&mut dash, &rx)
+}
+
+fn event_loop(dash: &mut Dash, rx: &mpsc::Receiver<Msg>) -> anyhow::Result<()> {
+    dash.refresh()?;
+    loop {
+        draw(dash)?;
+
+        let wait = REFRESH.saturating_sub(dash.refreshed.elapsed());
+        match rx.recv_timeout(wait) {
+            Ok(Msg::Event(event)) => {
+                dash.feed.push_back(*event);
+                if dash.feed.len() > FEED_SIZE {
+                    dash.feed.pop_front();
+                }
+            }
+            Ok(Msg::Key(Key::Quit)) | Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            Ok(Msg::Key(key)) => {
+                handle_key(dash, key)?;
+                dash.refresh()?;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+        if dash.refreshed.elapsed() >= REFRESH {
+            dash.refresh()?;
+        }
+    }
+}
+
+fn handle_key(dash: &mut Dash, key: Key) -> anyhow::Result<()> {
+    match key {
+        Key::Up => dash.selected = dash.selected.saturating_sub(1),
+        Key::Down => dash.selected = (dash.selected + 1).min(dash.projects.len().saturating_sub(1)),
+        _ => {}
+    }
+    let Some(project) = dash.projects.get(dash.selected).map(|p| p.name.clone()) else {
+        return Ok(());
+    };
+    match key {
+        Key::Focus => {
+            dash.status = match compositor::connect().and_then(|mut c| c.focus_workspace(&project)) {
+                Ok(()) => format!("Focused '{project}'"),
+                Err(e) => format!("Could not focus '{project}': {e}"),
+            };
+        }
+        Key::Mute => {
+            let now = mute::now();
+            let mut state = MuteState::load();
+            let muted = state.projects.remove(&project).is_none();
+            if muted {
+                state.projects.insert(project.clone(), Mute { until: None });
+            }
+            state.save(now)?;
+            dash.status = format!("{} '{project}'", if muted { "Muted" } else { "Unmuted" });
+        }
+        Key::Logs => {
+            dash.status = match open_logs(&project) {
+                Ok(()) => format!("Following logs of '{project}' in a new window"),
+                Err(e) => format!("Could not open logs: {e}"),
+            };
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn draw(dash: &Dash) -> anyhow::Result<()> {
+    let (rows, cols) = dash.size;
+    let mut lines = vec![
+        format!("drift dash   active: {}", clean(dash.active.as_deref().unwrap_or("-"))),
+        String::new(),
+        format!("  {:<20} {:<20} {:<26} {}", "PROJECT", "WORKSPACE", "SERVICES", "MUTED"),
+    ];
+    for (i, row) in dash.projects.iter().enumerate() {
+        let marker = if i == dash.selected { ">" } else { " " };
+        let line = format!(
+            "{marker} {:<20} {:<20} {:<26} {}",
+            clean(&row.name),
+            clean(&row.workspace),
+            clean(&row.services),
+            if row.muted { "yes" } else { "" }
+        );
+        lines.push(if i == dash.selected { format!("\x1b[7m{line}\x1b[0m") } else { line });
+    }
+    lines.push(String::new());
+    lines.push("EVENTS".into());
+
+    // The feed gets whatever height is left above the footer
+    let room = rows.saturating_sub(lines.len() + 2);
+    let skip = dash.feed.len().saturating_sub(room);
+    for event in dash.feed.iter().skip(skip) {
+        lines.push(format!(
+            "  {}  {:<16} {:<24} {}",
+            clean(event.ts.get(11..19).unwrap_or(&event.ts)),
+            clean(&event.project),
+            clean(&event.event_type),
+            clean(event.title.as_deref().unwrap_or(""))
+        ));
+    }
+    while lines.len() < rows.saturating_sub(1) {
+        lines.push(String::new());
+    }
+
+    let mut out = String::from("\x1b[H");
+    for line in &lines {
+        out.push_str(&truncate(line, cols));
+        out.push_str("\x1b[K\r\n");
+    }
+    let footer = if dash.status.is_empty() {
+        "j/k select  enter focus  m mute  l logs  q quit".to_string()
+    } else {
+        clean(&dash.status)
+    };
+    out.push_str(&truncate(&footer, cols));
+    out.push_str("\x1b[K");
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(out.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Text from events and configs with control characters (escape sequences,
+/// newlines) turned into spaces, so it can't move the cursor or restyle the
+/// screen.
+fn clean(text: &str) -> String {
+    text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect()
+}
+
+/// Cut to `cols` characters, leaving escape sequences whole.
+fn truncate(line: &str, cols: usize) -> String {
+    let mut out = String::new();
+    let mut visible = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        if c == '\x1b' {
+            in_escape = true;
+        }
+        if in_escape {
+            out.push(c);
+            in_escape = !c.is_ascii_alphabetic();
+            continue;
+        }
+        if visible < cols {
+            out.push(c);
+            visible += 1;
+        }
+    }
+    out
+}
+
+/// `drift logs -f` for the project in a terminal window next to the
+/// dashboard, which keeps this terminal to itself.
+fn open_logs(project: &str) -> anyhow::Result<()> {
+    let logs_dir = paths::logs_dir(project);
+    if !logs_dir.exists() {
+        bail!("no logs for '{project}'");
+    }
+    let global = config::load_global_config()?;
+    let command = format!("drift logs -f --project {}", shell::quote(project));
+    let args = build_terminal_args(
+        &global.defaults.terminal,
+        project,
+        Some("logs"),
+        "",
+        &logs_dir.to_string_lossy(),
+        Some(&command),
+    );
+    compositor::connect()?.spawn(args)
+}
+
+/// Forward daemon events, reconnecting while the daemon is down.
+fn subscribe(tx: mpsc::Sender<Msg>) {
+    loop {
+        if let Ok(stream) = UnixStream::connect(paths::subscribe_socket_path()) {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                let Ok(event) = serde_json::from_str::<Event>(&line) else {
+                    continue;
+                };
+                if tx.send(Msg::Event(Box::new(event))).is_err() {
+                    return;
+                }
+            }
+        }
+        std::thread::sleep(REFRESH);
+    }
+}
+
+fn read_keys(tx: mpsc::Sender<Msg>) {
+    let mut stdin = std::io::stdin().lock();
+    let mut buf = [0u8; 8];
+    loop {
+        let Ok(n) = stdin.read(&mut buf) else { return };
+        if n == 0 {
+            return;
+        }
+        let key = match &buf[..n] {
+            b"q" | b"\x03" => Key::Quit,
+            b"k" | b"\x1b[A" => Key::Up,
+            b"j" | b"\x1b[B" => Key::Down,
+            b"\r" | b"\n" | b"f" => Key::Focus,
+            b"m" => Key::Mute,
+            b"l" => Key::Logs,
+            _ => continue,
+        };
+        if tx.send(Msg::Key(key)).is_err() || key == Key::Quit {
+            return;
+        }
+    }
+}
+
+fn stty(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        bail!("stty {} failed", args.join(" "));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn terminal_size() -> (usize, usize) {
+    stty(&["size"])
+        .ok()
+        .and_then(|s| {
+            let (rows, cols) = s.split_once(' ')?;
+            Some((rows.parse().ok()?, cols.parse().ok()?))
+        })
+        .unwrap_or((24, 80))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_keeps_escapes() {
+        assert_eq!(truncate("abcdef", 3), "abc");
+        assert_eq!(truncate("\x1b[7mabcdef\x1b[0m", 2), "\x1b[7mab\x1b[0m");
+    }
+
+    #[test]
+    fn clean_strips_control_characters() {
+        assert_eq!(clean("build\x1b[2Jfailed\nagain"), "build [2Jfailed again");
+    }
+}
@@ -1,43 +1,87 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use anyhow::bail;
-use drift_core::{config, kdl, registry};
+use drift_core::{config, kdl, registry, trash};
+
+use super::status::format_duration;
 
-pub fn run(name: &str, yes: bool) -> anyhow::Result<()> {
+pub fn run(name: &str, yes: bool, purge: bool) -> anyhow::Result<()> {
     // Verify project exists
     let _project = config::load_project_config(name)?;
     let global = config::load_global_config()?;
 
     if !yes {
-        bail!("Deleting '{name}' will remove its config and all state/logs.\nRe-run with --yes to confirm.");
+        if purge {
+            bail!("Deleting '{name}' will permanently remove its config and all state/logs.\nRe-run with --yes to confirm.");
+        }
+        bail!("Deleting '{name}' will move its config and all state/logs to the trash.\nRe-run with --yes to confirm.");
     }
 
     // Close workspace if open
-    if let Ok(mut niri_client) = drift_core::niri::NiriClient::connect() {
+    if let Ok(mut niri_client) = drift_core::compositor::connect() {
         if niri_client.find_workspace_by_name(name)?.is_some() {
-            super::close::close_project(name)?;
+            super::close::close_project(name, super::close::CloseOptions::default())?;
             println!("  Closed workspace");
         }
     }
 
-    // Remove config + state
-    registry::delete_project(name)?;
-    println!("  Removed config and state");
-
-    // Regenerate niri-rules
-    let all_projects = registry::list_projects()?;
-    kdl::write_niri_rules(&all_projects, &global)?;
-
-    drift_core::events::try_emit_event(&drift_core::events::Event {
-        event_type: "drift.project.deleted".into(),
-        project: name.to_string(),
-        source: "drift".into(),
-        ts: drift_core::events::iso_now(),
-        level: Some("info".into()),
-        title: Some(format!("Deleted project '{name}'")),
-        body: None,
-        meta: None,
-        priority: None,
-    });
-
-    println!("Deleted project '{name}'");
+    if purge {
+        registry::delete_project(name)?;
+        println!("  Removed config and state");
+    } else {
+        let entry = trash::trash_project(name)?;
+        println!("  Moved config and state to {}", entry.path.display());
+        prune_trash(global.defaults.trash_retention_days);
+    }
+
+    kdl::refresh_niri_rules(&global)?;
+
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("drift.project.deleted", name, "drift")
+        .level("info")
+        .title(format!("Deleted project '{name}'")));
+
+    if purge {
+        println!("Deleted project '{name}'");
+    } else {
+        println!("Deleted project '{name}' (undo with `drift restore --deleted {name}`)");
+    }
     Ok(())
 }
+
+/// `drift restore --deleted`: list the trash, or bring a deleted project back.
+pub fn restore(name: Option<&str>) -> anyhow::Result<()> {
+    let Some(name) = name else {
+        let entries = trash::list()?;
+        if entries.is_empty() {
+            println!("Trash is empty");
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        for entry in entries {
+            let ago = format_duration(now.saturating_sub(entry.deleted_at));
+            println!("  {:<20} deleted {ago} ago", entry.project);
+        }
+        return Ok(());
+    };
+
+    trash::restore_project(name)?;
+    let global = config::load_global_config()?;
+    kdl::refresh_niri_rules(&global)?;
+
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("drift.project.restored", name, "drift")
+        .level("info")
+        .title(format!("Restored project '{name}'")));
+
+    println!("Restored project '{name}'");
+    Ok(())
+}
+
+fn prune_trash(retention_days: u32) {
+    match trash::prune(retention_days) {
+        Ok(removed) => {
+            for entry in removed {
+                println!("  Pruned '{}' from the trash", entry.project);
+            }
+        }
+        Err(e) => eprintln!("  Warning: could not prune trash: {e}"),
+    }
+}
@@ -0,0 +1,68 @@
+use drift_core::{config, git_identity, registry, session, tmux};
+
+pub fn run(fix: bool) -> anyhow::Result<()> {
+    let mut problems = 0;
+
+    let open: Vec<String> = session::load_session()?.map(|s| s.projects).unwrap_or_default();
+    let orphans = tmux::orphan_sessions(&tmux::list_sessions(), &open);
+    if orphans.is_empty() {
+        println!("ok    tmux: no orphaned drift sessions");
+    } else {
+        println!("warn  tmux: {} session(s) left over from closed projects", orphans.len());
+        for session in &orphans {
+            if fix && tmux::kill_session(session) {
+                println!("  killed {session}");
+            } else {
+                problems += 1;
+                println!("  {session}");
+            }
+        }
+        if problems > 0 {
+            println!("  run `drift doctor --fix` or `tmux kill-session -t <name>` to remove them");
+        }
+    }
+
+    problems += check_git_identities(fix)?;
+
+    println!();
+    println!("{problems} problem(s) found");
+    Ok(())
+}
+
+/// Repos whose local git identity or signing settings differ from their
+/// project's `[git]`, e.g. after the repo was recloned. `--fix` rewrites them.
+fn check_git_identities(fix: bool) -> anyhow::Result<usize> {
+    let mut problems = 0;
+    let mut checked = 0;
+    for project in registry::list_projects()? {
+        let Some(git) = &project.git else {
+            continue;
+        };
+        let Ok(repo) = config::resolve_repo_path(&project.project.repo) else {
+            continue;
+        };
+        if !repo.join(".git").exists() {
+            continue;
+        }
+        checked += 1;
+        let mismatches = git_identity::mismatches(&repo, git);
+        if mismatches.is_empty() {
+            continue;
+        }
+        println!("warn  git: '{}' identity differs from its config", project.project.name);
+        if fix && git_identity::apply(&repo, git).is_ok() {
+            println!("  fixed");
+            continue;
+        }
+        problems += 1;
+        for (key, expected, actual) in mismatches {
+            println!("  {key} is {} (expected '{expected}')", actual.map_or("unset".into(), |a| format!("'{a}'")));
+        }
+    }
+    if problems > 0 {
+        println!("  run `drift doctor --fix` to apply the configured identities");
+    } else if checked > 0 {
+        println!("ok    git: configured identities match");
+    }
+    Ok(problems)
+}
@@ -0,0 +1,142 @@
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use drift_core::validate::{self, Issue, Severity};
+use drift_core::{config, paths};
+
+use super::validate::print_issues;
+
+enum Target {
+    Project(String),
+    Global,
+    Template(String),
+}
+
+/// Open a config in the editor, then check it on exit. Errors offer to
+/// reopen the file, so a typo is caught here rather than by the next
+/// `drift open`.
+pub fn run(project: Option<&str>, global: bool, template: Option<&str>) -> anyhow::Result<()> {
+    let target = if global {
+        Target::Global
+    } else if let Some(name) = template {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\0']) {
+            bail!("Invalid template name '{name}'");
+        }
+        Target::Template(name.to_string())
+    } else {
+        Target::Project(config::resolve_current_project(project)?)
+    };
+
+    let path = match &target {
+        Target::Project(name) => {
+            let path = paths::project_config_path(name);
+            if !path.exists() {
+                bail!("Project '{name}' not found");
+            }
+            path
+        }
+        Target::Global => paths::global_config_path(),
+        Target::Template(name) => paths::templates_dir().join(format!("{name}.toml")),
+    };
+    if path.exists() {
+        return edit_until_valid(&target, &path, &path);
+    }
+
+    // A new global config or template is written in a scratch file next to
+    // it and only takes its place once it checks out
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let scratch = path.with_extension("toml.new");
+    std::fs::write(&scratch, "")?;
+    let edited = edit_until_valid(&target, &scratch, &path);
+    if scratch.exists() {
+        let _ = std::fs::remove_file(&scratch);
+    }
+    edited
+}
+
+/// Edit `editing` until it checks out, then move it to `path` (where it
+/// already is for an existing file).
+fn edit_until_valid(target: &Target, editing: &Path, path: &Path) -> anyhow::Result<()> {
+    let editor = editor_command();
+    let original = std::fs::read_to_string(editing)?;
+    loop {
+        open_editor(&editor, editing)?;
+        let contents = std::fs::read_to_string(editing)?;
+        if contents == original {
+            println!("No changes to {}", path.display());
+            return Ok(());
+        }
+
+        let issues = check(target, &contents);
+        let (errors, _) = print_issues(&path.display().to_string(), &issues);
+        if errors == 0 {
+            if editing != path {
+                std::fs::rename(editing, path).with_context(|| format!("writing {}", path.display()))?;
+            }
+            println!("Saved {}", path.display());
+            return Ok(());
+        }
+        if !reopen()? {
+            bail!("{} has {errors} error(s); fix it with `drift edit` or `drift validate`", path.display());
+        }
+    }
+}
+
+fn check(target: &Target, contents: &str) -> Vec<Issue> {
+    match target {
+        Target::Global => validate::validate_global(contents),
+        Target::Template(_) => validate::validate_project(contents).0,
+        Target::Project(name) => {
+            let (mut issues, parsed) = validate::validate_project(contents);
+            if let Some(cfg) = parsed.filter(|cfg| cfg.project.name != *name) {
+                issues.push(Issue {
+                    severity: Severity::Warning,
+                    message: format!("project.name '{}' does not match file name '{name}'", cfg.project.name),
+                });
+            }
+            issues
+        }
+    }
+}
+
+/// `defaults.editor`, or `$VISUAL`/`$EDITOR` when the global config itself
+/// doesn't load.
+fn editor_command() -> String {
+    match config::load_global_config() {
+        Ok(global) => global.defaults.editor,
+        Err(_) => std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".into()),
+    }
+}
+
+fn open_editor(editor: &str, path: &Path) -> anyhow::Result<()> {
+    // Through sh so an editor configured with arguments ("code --wait") works
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("drift-edit")
+        .arg(PathBuf::from(path))
+        .status()
+        .with_context(|| format!("running editor '{editor}'"))?;
+    if !status.success() {
+        bail!("editor '{editor}' exited with {status}");
+    }
+    Ok(())
+}
+
+/// Ask whether to go back into the editor. Only on a terminal.
+fn reopen() -> anyhow::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("Reopen in editor? [Y/n] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(!matches!(answer.trim(), "n" | "N" | "no"))
+}
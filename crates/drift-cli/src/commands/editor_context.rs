@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use drift_core::events::Event;
+use drift_core::supervisor::{self, ServiceStatus};
+use drift_core::{config, env, paths, registry};
+use serde::Serialize;
+
+/// Everything an editor plugin needs about the project it is editing, in
+/// one JSON document.
+#[derive(Serialize)]
+struct EditorContext {
+    project: String,
+    repo: String,
+    folder: Option<String>,
+    ports: PortsInfo,
+    /// Project environment with secrets shown as `<secret>`; plugins that
+    /// need the values start processes through drift instead
+    env: BTreeMap<String, String>,
+    /// Names of the project's secrets, without values
+    secrets: Vec<String>,
+    scratchpad: String,
+    /// Where to send events back to drift
+    notify_socket: String,
+    services: Vec<ServiceInfo>,
+    recent_events: Vec<Event>,
+}
+
+#[derive(Serialize)]
+struct PortsInfo {
+    range: Option<[u16; 2]>,
+    named: BTreeMap<String, u16>,
+}
+
+#[derive(Serialize)]
+struct ServiceInfo {
+    name: String,
+    status: String,
+    /// Agent CLI for agent services, so the editor can offer them prompts
+    agent: Option<String>,
+}
+
+pub fn run(project: Option<&str>, last: usize) -> anyhow::Result<()> {
+    let project_name = resolve_project(project)?;
+    let project = registry::find_project(&project_name)?;
+    let repo = config::resolve_repo_path(&project.project.repo)?;
+
+    let ports = PortsInfo {
+        range: project.ports.as_ref().and_then(|p| p.range),
+        named: project
+            .ports
+            .as_ref()
+            .map(|p| p.named.iter().map(|(k, v)| (k.clone(), *v)).collect())
+            .unwrap_or_default(),
+    };
+    let scratchpad_file = project.scratchpad.as_ref().map(|s| s.file.as_str());
+    let scratchpad = paths::scratchpad_path(&project_name, &repo, scratchpad_file);
+
+    let state = supervisor::read_state(&project_name);
+    let services = project
+        .services
+        .iter()
+        .flat_map(|s| s.processes.iter())
+        .map(|process| {
+            let status = state
+                .as_ref()
+                .and_then(|st| st.services.iter().find(|s| s.name == process.name))
+                .map_or("stopped", |s| status_name(&s.status));
+            ServiceInfo { name: process.name.clone(), status: status.into(), agent: process.agent.clone() }
+        })
+        .collect();
+
+    let context = EditorContext {
+        project: project_name.clone(),
+        repo: repo.to_string_lossy().into(),
+        folder: project.project.folder.clone(),
+        ports,
+        env: env::build_env(&project)?.into_iter().collect(),
+        secrets: project.env.secrets.clone(),
+        scratchpad: scratchpad.to_string_lossy().into(),
+        notify_socket: paths::emit_socket_path().to_string_lossy().into(),
+        services,
+        recent_events: super::events::recent_events(&project_name, last),
+    };
+    println!("{}", serde_json::to_string_pretty(&context)?);
+    Ok(())
+}
+
+fn status_name(status: &ServiceStatus) -> &'static str {
+    match status {
+        ServiceStatus::Running => "running",
+        ServiceStatus::Stopped => "stopped",
+        ServiceStatus::Failed => "failed",
+        ServiceStatus::Backoff => "backoff",
+        ServiceStatus::Scheduled => "scheduled",
+    }
+}
+
+/// An editor's working directory says more than the focused window, which
+/// may belong to another workspace by the time the plugin asks.
+fn resolve_project(explicit: Option<&str>) -> anyhow::Result<String> {
+    if let Some(name) = explicit {
+        return Ok(name.to_string());
+    }
+    if let Some(project) = std::env::var("DRIFT_PROJECT").ok().filter(|s| !s.is_empty()) {
+        return Ok(project);
+    }
+    let cwd = std::env::current_dir().context("reading working directory")?;
+    if let Some(project) = registry::project_for_path(&cwd)? {
+        return Ok(project);
+    }
+    config::resolve_current_project(None)
+}
@@ -0,0 +1,48 @@
+use anyhow::Context;
+
+use drift_core::events::{self, Event};
+use drift_core::registry;
+
+/// `drift notify` for editors and scripts: the project defaults to the one
+/// whose repo contains the working directory, and success prints nothing.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    event_type: &str,
+    title: Option<&str>,
+    body: Option<&str>,
+    level: &str,
+    source: &str,
+    project: Option<&str>,
+    meta: Option<&str>,
+    correlation_id: Option<String>,
+) -> anyhow::Result<()> {
+    let project_name = match project {
+        Some(p) => p.to_string(),
+        None => resolve_project()?,
+    };
+    let meta = meta
+        .map(serde_json::from_str::<serde_json::Value>)
+        .transpose()
+        .context("--meta must be valid JSON")?;
+
+    let event = Event {
+        title: title.map(String::from),
+        body: body.map(String::from),
+        meta,
+        correlation_id: correlation_id.or_else(events::inherited_correlation_id),
+        ..Event::new(event_type, project_name, source).level(level)
+    };
+    events::validate(&event)?;
+
+    events::emit_event(&event).context("sending event to drift daemon")
+}
+
+fn resolve_project() -> anyhow::Result<String> {
+    if let Some(project) = std::env::var("DRIFT_PROJECT").ok().filter(|s| !s.is_empty()) {
+        return Ok(project);
+    }
+    let cwd = std::env::current_dir().context("reading working directory")?;
+    registry::project_for_path(&cwd)?.with_context(|| {
+        format!("No drift project contains {}. Use --project or set $DRIFT_PROJECT", cwd.display())
+    })
+}
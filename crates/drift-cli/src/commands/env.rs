@@ -1,5 +1,33 @@
+use std::io::{BufRead, IsTerminal, Write};
+
 use anyhow::bail;
-use drift_core::{env, registry};
+use clap::Subcommand;
+use drift_core::{config, env, registry};
+
+#[derive(Subcommand)]
+pub enum EnvCommand {
+    /// Copy variables from a dotenv file or .envrc into the project's [env]
+    Import {
+        /// File to read, relative to the repo (default: .envrc, then .env)
+        #[arg(long)]
+        file: Option<String>,
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+        /// Don't ask before writing the project config
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Print the project's environment for other tools
+    Export {
+        /// `dotenv`, `direnv` or `fish`
+        #[arg(long, default_value = "dotenv")]
+        format: String,
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+    },
+}
 
 pub fn run(name: Option<&str>) -> anyhow::Result<()> {
     let project_name = match name {
@@ -17,3 +45,71 @@ pub fn run(name: Option<&str>) -> anyhow::Result<()> {
     println!("{}", env::format_env_exports(&env_map));
     Ok(())
 }
+
+pub fn run_command(command: EnvCommand) -> anyhow::Result<()> {
+    match command {
+        EnvCommand::Import { file, project, yes } => import(file.as_deref(), project.as_deref(), yes),
+        EnvCommand::Export { format, project } => {
+            let project = registry::find_project(&config::resolve_current_project(project.as_deref())?)?;
+            println!("{}", env::format_env(&env::build_env(&project)?, &format)?);
+            Ok(())
+        }
+    }
+}
+
+fn import(file: Option<&str>, project: Option<&str>, yes: bool) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let mut cfg = config::load_project_config(&project_name)?;
+    let repo = config::resolve_repo_path(&cfg.project.repo)?;
+
+    let path = match file {
+        Some(f) => repo.join(f),
+        None => match [".envrc", ".env"].iter().map(|f| repo.join(f)).find(|p| p.exists()) {
+            Some(p) => p,
+            None => bail!("No .envrc or .env in {}; pass --file", repo.display()),
+        },
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Cannot read {}: {e}", path.display()))?;
+    let (vars, skipped) = env::parse_env_file(&contents);
+
+    for line in &skipped {
+        eprintln!("  skipping: {line}");
+    }
+    let changes: Vec<&(String, String)> = vars
+        .iter()
+        .filter(|(k, v)| cfg.env.vars.get(k) != Some(v))
+        .collect();
+    if changes.is_empty() {
+        println!("Nothing to import from {}", path.display());
+        return Ok(());
+    }
+    for (key, value) in &changes {
+        match cfg.env.vars.get(key) {
+            Some(old) => println!("  ~ {key} = {value:?} (was {old:?})"),
+            None => println!("  + {key} = {value:?}"),
+        }
+    }
+
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            bail!("Not a terminal; pass --yes to write {project_name}.toml");
+        }
+        eprint!("Write {} variable(s) to '{project_name}'? [y/N] ", changes.len());
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Import cancelled");
+            return Ok(());
+        }
+    }
+
+    let count = changes.len();
+    for (key, value) in vars {
+        cfg.env.vars.insert(key, value);
+    }
+    config::save_project_config(&project_name, &cfg)?;
+    println!("Imported {count} variable(s) into '{project_name}'");
+    Ok(())
+}
@@ -1,19 +1,108 @@
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::UnixStream;
 
 use anyhow::bail;
-use drift_core::events::Event;
-use drift_core::paths;
+use drift_core::events::{self, matches_type_filter, Event};
+use drift_core::{config, paths};
+
+/// Apply the event log retention policy now instead of waiting for the daemon.
+pub fn prune() -> anyhow::Result<()> {
+    let retention_days = config::load_global_config()?.events.retention_days;
+    if retention_days == 0 {
+        println!("Event log retention is disabled (events.retention_days = 0)");
+        return Ok(());
+    }
+    let removed = drift_core::events::prune_event_log(retention_days)?;
+    println!("Removed {} event log archive(s) older than {retention_days} days", removed.len());
+    Ok(())
+}
+
+/// Which events `drift events` shows. Every set field must match.
+#[derive(Debug, Default)]
+pub struct EventFilter {
+    /// Event type, with at most one `*` glob
+    pub event_type: Option<String>,
+    pub levels: Vec<String>,
+    pub sources: Vec<String>,
+    /// Priorities to show; `(rank, true)` also shows anything higher
+    priorities: Vec<(u8, bool)>,
+    pub thread: Option<String>,
+}
+
+impl EventFilter {
+    /// Parse the comma-separated `--level`, `--source` and `--priority`
+    /// lists. A priority ending in `+` means "this or higher".
+    pub fn new(
+        event_type: Option<String>,
+        levels: Option<&str>,
+        sources: Option<&str>,
+        priorities: Option<&str>,
+        thread: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let levels = split_list(levels);
+        if let Some(level) = levels.iter().find(|l| !events::LEVELS.contains(&l.as_str())) {
+            bail!("Invalid level '{level}' (expected one of {})", events::LEVELS.join(", "));
+        }
+        let priorities = split_list(priorities)
+            .iter()
+            .map(|p| {
+                let (name, or_higher) = p.strip_suffix('+').map_or((p.as_str(), false), |n| (n, true));
+                if !events::PRIORITIES.contains(&name) {
+                    bail!("Invalid priority '{p}' (expected one of {}, optionally with a trailing +)", events::PRIORITIES.join(", "));
+                }
+                Ok((events::priority_rank(name), or_higher))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { event_type, levels, sources: split_list(sources), priorities, thread })
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        if self.event_type.as_deref().is_some_and(|f| !matches_type_filter(&event.event_type, f)) {
+            return false;
+        }
+        // Events without a level are informational
+        let level = event.level.as_deref().unwrap_or("info");
+        if !self.levels.is_empty() && !self.levels.iter().any(|l| l == level) {
+            return false;
+        }
+        if !self.sources.is_empty() && !self.sources.contains(&event.source) {
+            return false;
+        }
+        let rank = event.priority.as_deref().map_or(0, events::priority_rank);
+        if !self.priorities.is_empty()
+            && !self.priorities.iter().any(|&(p, or_higher)| rank == p || (or_higher && rank > p))
+        {
+            return false;
+        }
+        self.thread.as_ref().is_none_or(|id| event.correlation_id.as_ref() == Some(id))
+    }
+}
+
+fn split_list(list: Option<&str>) -> Vec<String> {
+    list.into_iter()
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
 
 pub fn run(
-    type_filter: Option<&str>,
+    filter: &EventFilter,
     last: usize,
     all: bool,
     follow: bool,
     project: Option<&str>,
+    format: &str,
+    threaded: bool,
 ) -> anyhow::Result<()> {
+    // `json` is the same one-object-per-line stream as `jsonlines`
+    let format = if format == "json" { "jsonlines" } else { format };
+    if !matches!(format, "text" | "jsonlines" | "msgpack") {
+        bail!("Invalid format '{format}' (expected text, json, jsonlines or msgpack)");
+    }
     if follow {
-        return follow_events(type_filter);
+        return follow_events(filter, format);
     }
 
     let project_name = if all {
@@ -44,17 +133,14 @@ pub fn run(
             .collect()
     };
 
-    // Apply type filter
-    if let Some(filter) = type_filter {
-        events.retain(|e| matches_type_filter(&e.event_type, filter));
-    }
+    events.retain(|e| filter.matches(e));
 
     // Sort by timestamp and take last N
     events.sort_by(|a, b| a.ts.cmp(&b.ts));
     let start = events.len().saturating_sub(last);
     let events = &events[start..];
 
-    if events.is_empty() {
+    if events.is_empty() && format == "text" {
         if let Some(name) = &project_name {
             println!("No events for project '{name}'.");
         } else {
@@ -63,20 +149,58 @@ pub fn run(
         return Ok(());
     }
 
-    for event in events {
-        print_event(event);
+    if threaded && format == "text" {
+        for group in group_threads(events) {
+            print_event(group[0], "");
+            for event in &group[1..] {
+                print_event(event, "  └ ");
+            }
+        }
+        return Ok(());
+    }
+    let ordered: Vec<&Event> = if threaded {
+        group_threads(events).into_iter().flatten().collect()
+    } else {
+        events.iter().collect()
+    };
+    for event in ordered {
+        write_event(event, format)?;
     }
 
     Ok(())
 }
 
-fn follow_events(type_filter: Option<&str>) -> anyhow::Result<()> {
+/// Events grouped by correlation id, groups ordered by their first event.
+/// Events without an id stand alone.
+fn group_threads(events: &[Event]) -> Vec<Vec<&Event>> {
+    let mut groups: Vec<Vec<&Event>> = Vec::new();
+    let mut index: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for event in events {
+        match event.correlation_id.as_deref() {
+            Some(id) => match index.get(id) {
+                Some(&i) => groups[i].push(event),
+                None => {
+                    index.insert(id, groups.len());
+                    groups.push(vec![event]);
+                }
+            },
+            None => groups.push(vec![event]),
+        }
+    }
+    groups
+}
+
+fn follow_events(filter: &EventFilter, format: &str) -> anyhow::Result<()> {
     let socket_path = paths::subscribe_socket_path();
     if !socket_path.exists() {
         bail!("Daemon not running (no subscribe socket). Start it with `drift daemon`.");
     }
 
     let stream = UnixStream::connect(&socket_path)?;
+    // Let the daemon drop other types before they reach us
+    if let Some(event_type) = &filter.event_type {
+        writeln!(&stream, "{}", serde_json::json!({ "types": [event_type] }))?;
+    }
     let reader = BufReader::new(stream);
 
     for line in reader.lines() {
@@ -85,19 +209,35 @@ fn follow_events(type_filter: Option<&str>) -> anyhow::Result<()> {
             continue;
         }
         if let Ok(event) = serde_json::from_str::<Event>(&line) {
-            if let Some(filter) = type_filter {
-                if !matches_type_filter(&event.event_type, filter) {
-                    continue;
-                }
+            if filter.matches(&event) {
+                write_event(&event, format)?;
             }
-            print_event(&event);
         }
     }
 
     Ok(())
 }
 
-fn print_event(event: &Event) {
+/// Print one event in the requested format. Machine formats are flushed per
+/// event so editor plugins reading the pipe see each one immediately.
+fn write_event(event: &Event, format: &str) -> anyhow::Result<()> {
+    let mut out = std::io::stdout().lock();
+    match format {
+        "jsonlines" => writeln!(out, "{}", serde_json::to_string(event)?)?,
+        // One self-delimiting map per event, as read by `vim.mpack` and
+        // msgpack-rpc clients
+        "msgpack" => out.write_all(&rmp_serde::to_vec_named(event)?)?,
+        _ => {
+            drop(out);
+            print_event(event, "");
+            return Ok(());
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn print_event(event: &Event, prefix: &str) {
     // Extract time portion from ISO timestamp (HH:MM:SS)
     let time = if event.ts.len() >= 19 {
         &event.ts[11..19]
@@ -115,24 +255,12 @@ fn print_event(event: &Event) {
     let source = &event.source;
 
     if title.is_empty() {
-        println!("{time}  {etype:<25} {project:<12} {source}");
+        println!("{prefix}{time}  {etype:<25} {project:<12} {source}");
+    } else if let (true, Some(body)) = (etype.starts_with("git."), event.body.as_deref()) {
+        // git hooks put "<sha> on <branch>" (or "<branch> to <remote>") in the body
+        println!("{prefix}{time}  {etype:<25} {project:<12} {body}  \"{title}\"");
     } else {
-        println!("{time}  {etype:<25} {project:<12} \"{title}\"");
-    }
-}
-
-fn matches_type_filter(event_type: &str, filter: &str) -> bool {
-    if filter.contains('*') {
-        // Simple glob: "agent.*" matches "agent.completed", "agent.error", etc.
-        let parts: Vec<&str> = filter.split('*').collect();
-        if parts.len() == 2 {
-            let (prefix, suffix) = (parts[0], parts[1]);
-            event_type.starts_with(prefix) && event_type.ends_with(suffix)
-        } else {
-            event_type == filter
-        }
-    } else {
-        event_type == filter
+        println!("{prefix}{time}  {etype:<25} {project:<12} \"{title}\"");
     }
 }
 
@@ -145,7 +273,7 @@ fn resolve_project(name: Option<&str>) -> anyhow::Result<String> {
             return Ok(project);
         }
     }
-    if let Ok(mut client) = drift_core::niri::NiriClient::connect() {
+    if let Ok(mut client) = drift_core::compositor::connect() {
         if let Ok(Some(win)) = client.focused_window() {
             if let Some(ws_id) = win.workspace_id {
                 if let Ok(workspaces) = client.workspaces() {
@@ -163,9 +291,95 @@ fn resolve_project(name: Option<&str>) -> anyhow::Result<String> {
     bail!("Could not determine project name. Use --project or --all.")
 }
 
+/// The project's last `last` events from the daemon's state file, oldest
+/// first; empty when the daemon isn't running.
+pub fn recent_events(project: &str, last: usize) -> Vec<Event> {
+    let Ok(contents) = std::fs::read_to_string(paths::daemon_state_path()) else {
+        return Vec::new();
+    };
+    let Ok(mut state) = serde_json::from_str::<DaemonStateCompat>(&contents) else {
+        return Vec::new();
+    };
+    let mut events = state.recent_events.remove(project).unwrap_or_default();
+    events.sort_by(|a, b| a.ts.cmp(&b.ts));
+    events.split_off(events.len().saturating_sub(last))
+}
+
 /// Minimal struct to read daemon.json (only the fields we need)
 #[derive(serde::Deserialize)]
 struct DaemonStateCompat {
     #[serde(default)]
     recent_events: std::collections::HashMap<String, Vec<Event>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(event_type: &str, correlation_id: Option<&str>) -> Event {
+        Event { correlation_id: correlation_id.map(String::from), ..Event::new(event_type, "myapp", "test") }
+    }
+
+    #[test]
+    fn msgpack_event_is_a_map_of_its_json_fields() {
+        let mut event = event("build.failed", Some("run-1"));
+        event.meta = Some(json!({ "duration_ms": 1500, "ok": false }));
+        let decoded: serde_json::Value = rmp_serde::from_slice(&rmp_serde::to_vec_named(&event).unwrap()).unwrap();
+        assert_eq!(decoded, serde_json::to_value(&event).unwrap());
+    }
+
+    #[test]
+    fn group_threads_orders_by_first_event() {
+        let events = [
+            event("agent.started", Some("a")),
+            event("service.started", Some("b")),
+            event("drift.notification", None),
+            event("agent.progress", Some("a")),
+            event("service.crashed", Some("b")),
+            event("agent.completed", Some("a")),
+        ];
+        let groups: Vec<Vec<&str>> = group_threads(&events)
+            .iter()
+            .map(|g| g.iter().map(|e| e.event_type.as_str()).collect())
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                vec!["agent.started", "agent.progress", "agent.completed"],
+                vec!["service.started", "service.crashed"],
+                vec!["drift.notification"],
+            ]
+        );
+    }
+
+    fn event_from(source: &str, level: Option<&str>, priority: Option<&str>) -> Event {
+        Event {
+            level: level.map(String::from),
+            priority: priority.map(String::from),
+            ..Event::new("service.crashed", "myapp", source)
+        }
+    }
+
+    #[test]
+    fn filter_by_level_source_and_priority() {
+        let filter = EventFilter::new(None, Some("error,warning"), Some("supervisor"), Some("high+"), None).unwrap();
+        assert!(filter.matches(&event_from("supervisor", Some("error"), Some("critical"))));
+        assert!(filter.matches(&event_from("supervisor", Some("warning"), Some("high"))));
+        assert!(!filter.matches(&event_from("supervisor", Some("error"), Some("medium"))));
+        assert!(!filter.matches(&event_from("supervisor", None, Some("high"))));
+        assert!(!filter.matches(&event_from("agent", Some("error"), Some("high"))));
+
+        let exact = EventFilter::new(None, Some("info"), None, Some("silent"), None).unwrap();
+        assert!(exact.matches(&event_from("drift", None, None)));
+        assert!(!exact.matches(&event_from("drift", None, Some("low"))));
+    }
+
+    #[test]
+    fn filter_rejects_unknown_level_and_priority() {
+        let err = EventFilter::new(None, Some("warn"), None, None, None).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid level 'warn'"));
+        let err = EventFilter::new(None, None, None, Some("urgent+"), None).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid priority 'urgent+'"));
+    }
+}
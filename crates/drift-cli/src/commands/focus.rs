@@ -0,0 +1,29 @@
+use anyhow::bail;
+use drift_core::{compositor, config, shell, workspace};
+
+pub fn run(window: &str, project: Option<&str>) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let title = shell::window_title(&project_name, Some(window));
+
+    let mut client = compositor::connect()?;
+    let win = match client.find_window_by_title(&title)? {
+        Some(win) => Some(win),
+        // The app may have replaced its drift title since it opened
+        None => match workspace::tracked_windows(&project_name).into_iter().find(|(_, name)| name == window) {
+            Some((id, _)) => client.windows()?.into_iter().find(|w| w.id == id),
+            None => None,
+        },
+    };
+    let Some(win) = win else {
+        bail!("No window '{window}' open for project '{project_name}'");
+    };
+
+    if let Some(ws_id) = win.workspace_id {
+        let focused = client.workspaces()?.iter().any(|ws| ws.id == ws_id && ws.is_focused);
+        if !focused {
+            client.focus_workspace_id(ws_id)?;
+        }
+    }
+    client.focus_window(win.id)?;
+    Ok(())
+}
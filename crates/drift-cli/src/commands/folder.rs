@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use clap::Subcommand;
+use drift_core::config::{self, FolderConfig};
+use drift_core::{compositor, registry};
+
+#[derive(Subcommand)]
+pub enum FolderCommand {
+    /// List folders with their projects and defaults
+    List,
+    /// Open every project in a folder
+    Open {
+        /// Folder name
+        name: String,
+    },
+    /// Close every open project in a folder
+    Close {
+        /// Folder name
+        name: String,
+        /// Don't check for uncommitted changes or an open scratchpad
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+pub fn run(command: FolderCommand) -> anyhow::Result<()> {
+    match command {
+        FolderCommand::List => list(),
+        FolderCommand::Open { name } => open(&name),
+        FolderCommand::Close { name, force } => close(&name, force),
+    }
+}
+
+fn list() -> anyhow::Result<()> {
+    let global = config::load_global_config()?;
+    let mut folders: BTreeMap<String, Vec<String>> = global.folders.keys().map(|f| (f.clone(), Vec::new())).collect();
+    for p in registry::list_projects()? {
+        if let Some(folder) = p.project.folder {
+            folders.entry(folder).or_default().push(p.project.name);
+        }
+    }
+    if folders.is_empty() {
+        println!("No folders (set `folder` in a project, or add [folder.<name>] to config.toml)");
+        return Ok(());
+    }
+
+    // Without a compositor (e.g. over ssh) open counts are just left out
+    let open_workspaces: Option<Vec<String>> = compositor::connect()
+        .and_then(|mut c| c.workspaces())
+        .ok()
+        .map(|ws| ws.into_iter().filter_map(|w| w.name).collect());
+
+    for (folder, projects) in &folders {
+        let mut summary = format!("{} project{}", projects.len(), if projects.len() == 1 { "" } else { "s" });
+        if let Some(names) = &open_workspaces {
+            let open = projects.iter().filter(|p| names.contains(p)).count();
+            summary.push_str(&format!(", {open} open"));
+        }
+        println!("{folder}/  ({summary})");
+        if let Some(defaults) = global.folders.get(folder) {
+            let described = describe_defaults(defaults);
+            if !described.is_empty() {
+                println!("  defaults: {described}");
+            }
+        }
+        for project in projects {
+            println!("  {project}");
+        }
+    }
+    Ok(())
+}
+
+fn describe_defaults(defaults: &FolderConfig) -> String {
+    let mut parts = Vec::new();
+    if let Some(output) = &defaults.output {
+        parts.push(format!("output {output}"));
+    }
+    if let Some(icon) = &defaults.icon {
+        parts.push(format!("icon {icon}"));
+    }
+    if let Some(base) = defaults.port_base {
+        parts.push(format!("ports from {base}"));
+    }
+    parts.join(", ")
+}
+
+/// Projects in `folder`, in registry order.
+fn folder_projects(folder: &str) -> anyhow::Result<Vec<String>> {
+    let projects: Vec<String> = registry::list_projects()?
+        .into_iter()
+        .filter(|p| p.project.folder.as_deref() == Some(folder))
+        .map(|p| p.project.name)
+        .collect();
+    if projects.is_empty() {
+        bail!("No projects in folder '{folder}'");
+    }
+    Ok(projects)
+}
+
+fn open(folder: &str) -> anyhow::Result<()> {
+    let projects = folder_projects(folder)?;
+    let mut client = compositor::connect()?;
+
+    let mut opened = 0;
+    let mut failures = 0;
+    for project in &projects {
+        if client.find_workspace_by_name(project)?.is_some() {
+            println!("  '{project}' already open");
+            continue;
+        }
+        println!("Opening '{project}'");
+        match super::open::run(project, None, false, false, false) {
+            Ok(()) => opened += 1,
+            Err(e) => {
+                eprintln!("  Failed to open '{project}': {e}");
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        bail!("Opened {opened} projects in '{folder}', {failures} failed");
+    }
+    println!("Opened {opened} projects in '{folder}'");
+    Ok(())
+}
+
+fn close(folder: &str, force: bool) -> anyhow::Result<()> {
+    let projects = folder_projects(folder)?;
+    let open: Vec<String> = super::close::open_projects()?
+        .into_iter()
+        .filter(|p| projects.contains(p))
+        .collect();
+    super::close::close_many(&open, force, super::close::CloseOptions::default())
+}
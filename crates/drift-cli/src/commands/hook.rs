@@ -0,0 +1,155 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use clap::Subcommand;
+use drift_core::{config, shell};
+
+/// First line after the shebang; marks hooks drift may overwrite or remove.
+const MARKER: &str = "# drift-hook: managed by `drift hook install`";
+
+#[derive(Subcommand)]
+pub enum HookCommand {
+    /// Install git hooks that emit git.* events to the drift bus
+    Install {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+        /// Overwrite existing hooks not installed by drift
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove hooks installed by drift
+    Uninstall {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+    },
+}
+
+pub fn run(command: HookCommand) -> anyhow::Result<()> {
+    match command {
+        HookCommand::Install { project, force } => install(project.as_deref(), force),
+        HookCommand::Uninstall { project } => uninstall(project.as_deref()),
+    }
+}
+
+fn install(project: Option<&str>, force: bool) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let hooks_dir = hooks_dir(&project_name)?;
+    fs::create_dir_all(&hooks_dir)?;
+
+    let scripts = hook_scripts(&project_name);
+    // Check every hook first, so a conflict leaves none of them half-installed
+    if !force {
+        let taken: Vec<String> = scripts
+            .iter()
+            .map(|(hook, _)| hooks_dir.join(hook))
+            .filter(|path| path.exists() && !is_managed(path))
+            .map(|path| path.display().to_string())
+            .collect();
+        if !taken.is_empty() {
+            bail!("Hooks not installed by drift are in the way: {} (use --force to overwrite)", taken.join(", "));
+        }
+    }
+    for (hook, body) in scripts {
+        let path = hooks_dir.join(hook);
+        fs::write(&path, body).with_context(|| format!("writing {}", path.display()))?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        println!("  Installed {hook}");
+    }
+
+    println!("Installed git hooks for '{project_name}' in {}", hooks_dir.display());
+    Ok(())
+}
+
+fn uninstall(project: Option<&str>) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let hooks_dir = hooks_dir(&project_name)?;
+
+    let mut removed = 0;
+    for (hook, _) in hook_scripts(&project_name) {
+        let path = hooks_dir.join(hook);
+        if path.exists() && is_managed(&path) {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    println!("Removed {removed} drift hook(s) for '{project_name}'");
+    Ok(())
+}
+
+/// Hooks directory of the project repo, honouring core.hooksPath.
+fn hooks_dir(project_name: &str) -> anyhow::Result<PathBuf> {
+    let cfg = config::load_project_config(project_name)?;
+    let repo = config::resolve_repo_path(&cfg.project.repo)?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&repo)
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .context("running git")?;
+    if !output.status.success() {
+        bail!("{} is not a git repository", repo.display());
+    }
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(if path.is_absolute() { path } else { repo.join(path) })
+}
+
+fn is_managed(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|s| s.lines().nth(1) == Some(MARKER))
+}
+
+/// Hook name and script. Each runs `drift notify` in the background and
+/// always exits 0, so a missing binary or stopped daemon never blocks git.
+fn hook_scripts(project_name: &str) -> [(&'static str, String); 3] {
+    let notify = |event_type: &str, title: &str, body: &str| {
+        format!(
+            "command -v drift >/dev/null 2>&1 || exit 0\n\
+             drift notify --project {} --type {event_type} --source git -- \"{title}\" \"{body}\" >/dev/null 2>&1 &\n\
+             exit 0\n",
+            shell::quote(project_name)
+        )
+    };
+    let script = |setup: &str, notify: String| format!("#!/bin/sh\n{MARKER}\n{setup}{notify}");
+
+    [
+        (
+            "post-commit",
+            script(
+                "subject=$(git log -1 --format=%s)\nref=\"$(git rev-parse --short HEAD) on $(git rev-parse --abbrev-ref HEAD)\"\n",
+                notify("git.committed", "$subject", "$ref"),
+            ),
+        ),
+        (
+            "post-merge",
+            script(
+                "ref=\"$(git rev-parse --short HEAD) on $(git rev-parse --abbrev-ref HEAD)\"\n",
+                notify("git.merged", "Merged into $(git rev-parse --abbrev-ref HEAD)", "$ref"),
+            ),
+        ),
+        (
+            "pre-push",
+            // $1 is the remote name; stdin (the ref list) is left unread
+            script(
+                "ref=\"$(git rev-parse --abbrev-ref HEAD) to $1\"\n",
+                notify("git.pushed", "Pushing $(git rev-parse --abbrev-ref HEAD)", "$ref"),
+            ),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_positionals_follow_a_double_dash() {
+        // A commit subject like "-fix typo" must not be read as a flag
+        for (name, script) in hook_scripts("myapp") {
+            assert!(script.contains("--source git -- \""), "{name}: {script}");
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use clap::Subcommand;
+use drift_core::import::{self, Imported};
+use drift_core::{config, kdl, paths, registry};
+
+#[derive(Subcommand)]
+pub enum ImportCommand {
+    /// Import a tmuxinator project (file path or name in ~/.config/tmuxinator)
+    Tmuxinator {
+        source: String,
+        /// Project name (default: `name` from the file)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Import a smug session (file path or name in ~/.config/smug)
+    Smug {
+        source: String,
+        /// Project name (default: `session` from the file)
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+pub fn run(command: ImportCommand) -> anyhow::Result<()> {
+    let (path, imported) = match command {
+        ImportCommand::Tmuxinator { source, name } => {
+            let path = resolve_source(&source, &["tmuxinator"], &[".tmuxinator"])?;
+            let yaml = read(&path)?;
+            (path, import::from_tmuxinator(&yaml, name.as_deref())?)
+        }
+        ImportCommand::Smug { source, name } => {
+            let path = resolve_source(&source, &["smug"], &[])?;
+            let yaml = read(&path)?;
+            (path, import::from_smug(&yaml, name.as_deref())?)
+        }
+    };
+    let Imported { config: project, warnings } = imported;
+    let name = project.project.name.clone();
+    registry::check_project_name(&name)?;
+
+    let config_path = paths::project_config_path(&name);
+    if config_path.exists() {
+        bail!("Project '{name}' already exists at {}", config_path.display());
+    }
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    config::save_project_config(&name, &project)?;
+    std::fs::create_dir_all(paths::state_dir(&name))?;
+    std::fs::create_dir_all(paths::logs_dir(&name))?;
+
+    kdl::refresh_niri_rules(&config::load_global_config()?)?;
+
+    println!("Imported '{name}' from {}", path.display());
+    let services = project.services.as_ref().map_or(0, |s| s.processes.len());
+    println!("  {} windows, {services} services", project.windows.len());
+    for warning in &warnings {
+        println!("  warning: {warning}");
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> anyhow::Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))
+}
+
+/// A path that exists is used as-is; otherwise look up `<name>.yml` in the
+/// tool's config directories.
+fn resolve_source(source: &str, config_dirs: &[&str], home_dirs: &[&str]) -> anyhow::Result<PathBuf> {
+    let direct = PathBuf::from(source);
+    if direct.is_file() {
+        return Ok(direct);
+    }
+
+    let mut candidates = Vec::new();
+    if let Some(config) = dirs::config_dir() {
+        candidates.extend(config_dirs.iter().map(|d| config.join(d)));
+    }
+    if let Some(home) = dirs::home_dir() {
+        candidates.extend(home_dirs.iter().map(|d| home.join(d)));
+    }
+    for dir in candidates {
+        for ext in ["yml", "yaml"] {
+            let path = dir.join(format!("{source}.{ext}"));
+            if path.is_file() {
+                return Ok(path);
+            }
+        }
+    }
+    bail!("No file or session named '{source}' found")
+}
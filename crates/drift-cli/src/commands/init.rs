@@ -13,6 +13,7 @@ fn load_template(template_name: &str) -> anyhow::Result<ProjectConfig> {
 }
 
 pub fn run(name: &str, repo: Option<&str>, folder: Option<&str>, template: Option<&str>) -> anyhow::Result<()> {
+    registry::check_project_name(name)?;
     let config_path = paths::project_config_path(name);
     if config_path.exists() {
         bail!("Project '{}' already exists at {}", name, config_path.display());
@@ -25,7 +26,7 @@ pub fn run(name: &str, repo: Option<&str>, folder: Option<&str>, template: Optio
             .to_string(),
     };
 
-    let project = match template {
+    let mut project = match template {
         Some(tmpl) => {
             let mut config = load_template(tmpl)?;
             config.project.name = name.to_string();
@@ -44,18 +45,31 @@ pub fn run(name: &str, repo: Option<&str>, folder: Option<&str>, template: Optio
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: None,
             windows: vec![],
             scratchpad: None,
             tmux: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         },
     };
 
+    let global = config::load_global_config()?;
+    apply_folder_defaults(&mut project, &global)?;
+
     if let Some(parent) = config_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -65,10 +79,34 @@ pub fn run(name: &str, repo: Option<&str>, folder: Option<&str>, template: Optio
     std::fs::create_dir_all(paths::state_dir(name))?;
     std::fs::create_dir_all(paths::logs_dir(name))?;
 
-    let projects = registry::list_projects()?;
-    let global = config::load_global_config()?;
-    kdl::write_niri_rules(&projects, &global)?;
+    kdl::refresh_niri_rules(&global)?;
 
     println!("Initialized project '{name}' at {}", config_path.display());
     Ok(())
 }
+
+/// Fill in the icon and a port range from the project's `[folder.<name>]`
+/// defaults, where the project (or its template) doesn't set them.
+fn apply_folder_defaults(project: &mut ProjectConfig, global: &config::GlobalConfig) -> anyhow::Result<()> {
+    let Some(defaults) = global.folder(project.project.folder.as_deref()) else {
+        return Ok(());
+    };
+    if project.project.icon.is_none() {
+        project.project.icon = defaults.icon.clone();
+    }
+    let Some(base) = defaults.port_base else {
+        return Ok(());
+    };
+    if project.ports.as_ref().is_some_and(|p| p.range.is_some()) {
+        return Ok(());
+    }
+    let taken: Vec<[u16; 2]> = registry::list_projects()?
+        .iter()
+        .filter_map(|p| p.ports.as_ref()?.range)
+        .collect();
+    match config::allocate_port_range(base, global.ports.range_size, &taken) {
+        Some(range) => project.ports.get_or_insert_with(Default::default).range = Some(range),
+        None => eprintln!("Warning: no free port range from {base}; set [ports] range by hand"),
+    }
+    Ok(())
+}
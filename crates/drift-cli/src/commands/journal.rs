@@ -0,0 +1,46 @@
+use anyhow::bail;
+use drift_core::{config, journal};
+use time::OffsetDateTime;
+
+pub fn run(project: Option<&str>, since: Option<&str>, until: Option<&str>, last: usize) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let offset = journal::local_offset();
+    let now = OffsetDateTime::now_utc().to_offset(offset);
+    let since = bound("--since", since, now)?;
+    let until = bound("--until", until, now)?;
+
+    let entries: Vec<_> = journal::collect(&project_name)?
+        .into_iter()
+        .filter(|e| since.is_none_or(|s| e.ts >= s) && until.is_none_or(|u| e.ts <= u))
+        .collect();
+    if entries.is_empty() {
+        println!("No journal entries for '{project_name}'");
+        return Ok(());
+    }
+
+    let skip = if last == 0 { 0 } else { entries.len().saturating_sub(last) };
+    let width = entries[skip..].iter().map(|e| e.source.len()).max().unwrap_or(0);
+    for entry in &entries[skip..] {
+        let ts = entry.ts.to_offset(offset);
+        println!(
+            "{} {:02}:{:02}:{:02}  {:<width$}  {}",
+            ts.date(),
+            ts.hour(),
+            ts.minute(),
+            ts.second(),
+            entry.source,
+            entry.text
+        );
+    }
+    Ok(())
+}
+
+fn bound(flag: &str, value: Option<&str>, now: OffsetDateTime) -> anyhow::Result<Option<OffsetDateTime>> {
+    match value {
+        None => Ok(None),
+        Some(v) => match journal::parse_bound(v, now) {
+            Some(ts) => Ok(Some(ts)),
+            None => bail!("Invalid {flag} '{v}' (expected HH:MM, HH:MM:SS or an RFC 3339 timestamp)"),
+        },
+    }
+}
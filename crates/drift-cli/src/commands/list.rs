@@ -1,17 +1,41 @@
 use std::collections::BTreeMap;
 
+use anyhow::bail;
 use drift_core::config::resolve_repo_path;
-use drift_core::registry;
+use drift_core::{registry, repo_status};
 
-pub fn run(archived: bool) -> anyhow::Result<()> {
-    let projects = if archived {
+use super::status::{services_summary, Overview};
+
+struct Entry {
+    name: String,
+    repo: String,
+    workspace: String,
+    services: String,
+    /// ISO timestamp of the latest event the daemon has seen
+    last_event: Option<String>,
+    /// Branch and changes, with `--verbose`
+    git: Option<String>,
+}
+
+pub fn run(archived: bool, folder: Option<&str>, sort: &str, verbose: bool) -> anyhow::Result<()> {
+    if !matches!(sort, "name" | "activity") {
+        bail!("Invalid sort key '{sort}' (expected name or activity)");
+    }
+    let mut projects = if archived {
         registry::list_archived()?
     } else {
         registry::list_projects()?
     };
+    // Accept the `work/` form list prints
+    let folder = folder.map(|f| f.trim_end_matches('/'));
+    if let Some(folder) = folder {
+        projects.retain(|p| p.project.folder.as_deref() == Some(folder));
+    }
 
     if projects.is_empty() {
-        if archived {
+        if let Some(folder) = folder {
+            println!("No projects in folder '{folder}'.");
+        } else if archived {
             println!("No archived projects.");
         } else {
             println!("No projects configured.");
@@ -19,7 +43,9 @@ pub fn run(archived: bool) -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let mut grouped: BTreeMap<Option<String>, Vec<(&str, String)>> = BTreeMap::new();
+    // Archived projects have nothing running
+    let overview = (!archived).then(Overview::load);
+    let mut grouped: BTreeMap<Option<String>, Vec<Entry>> = BTreeMap::new();
     for p in &projects {
         let folder = p.project.folder.clone();
         let repo = resolve_repo_path(&p.project.repo)?;
@@ -32,10 +58,30 @@ pub fn run(archived: bool) -> anyhow::Result<()> {
         } else {
             repo.display().to_string()
         };
-        grouped
-            .entry(folder)
-            .or_default()
-            .push((&p.project.name, repo_display));
+        let name = &p.project.name;
+        let (workspace, services, last_event) = match &overview {
+            Some(o) => (
+                o.workspace(name),
+                services_summary(name),
+                o.last_event(name).map(|e| e.ts.clone()),
+            ),
+            None => ("-".into(), "-".into(), None),
+        };
+        let git = verbose.then(|| repo_status::inspect(&repo).map_or("-".into(), |s| s.summary()));
+        grouped.entry(folder).or_default().push(Entry {
+            name: p.project.display_name(),
+            repo: repo_display,
+            workspace,
+            services,
+            last_event,
+            git,
+        });
+    }
+    if sort == "activity" {
+        // Most recent first; projects without events keep name order at the end
+        for entries in grouped.values_mut() {
+            entries.sort_by(|a, b| b.last_event.cmp(&a.last_event));
+        }
     }
 
     let mut first = true;
@@ -49,8 +95,20 @@ pub fn run(archived: bool) -> anyhow::Result<()> {
             Some(name) => println!("{name}/"),
             None => println!("(ungrouped)"),
         }
-        for (name, repo) in entries {
-            println!("  {name:<20} {repo}");
+        for e in entries {
+            let last = e
+                .last_event
+                .as_deref()
+                .map(|ts| ts.get(..16).unwrap_or(ts).replace('T', " "))
+                .unwrap_or_else(|| "-".into());
+            let last = match &e.git {
+                Some(git) => format!("{last:<16}  {git}"),
+                None => last,
+            };
+            println!(
+                "  {:<20} {:<30} {:<18} {:<24} {last}",
+                e.name, e.repo, e.workspace, e.services
+            );
         }
     }
 
@@ -3,7 +3,7 @@ use std::io::{self, BufRead};
 use std::process::Command;
 
 use anyhow::{bail, Context};
-use drift_core::{niri, paths};
+use drift_core::{compositor, paths};
 
 pub fn run(service: Option<&str>, follow: bool, project: Option<&str>) -> anyhow::Result<()> {
     let project_name = resolve_project_name(project)?;
@@ -106,7 +106,7 @@ fn resolve_project_name(name: Option<&str>) -> anyhow::Result<String> {
         }
     }
 
-    if let Ok(mut client) = niri::NiriClient::connect() {
+    if let Ok(mut client) = compositor::connect() {
         if let Ok(Some(win)) = client.focused_window() {
             if let Some(ws_id) = win.workspace_id {
                 if let Ok(workspaces) = client.workspaces() {
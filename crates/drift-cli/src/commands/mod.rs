@@ -4,30 +4,54 @@ pub mod archive;
 pub mod close;
 pub mod commander;
 pub mod daemon;
+pub mod dash;
 #[cfg(feature = "dispatch")]
 pub mod dispatch;
 pub mod delete;
+pub mod doctor;
+pub mod edit;
+pub mod editor_context;
+pub mod emit;
 pub mod env;
 pub mod events;
+pub mod focus;
+pub mod folder;
+pub mod hook;
+pub mod import;
 pub mod init;
+pub mod journal;
 pub mod list;
 pub mod logs;
 pub mod niri_rules;
 pub mod notify;
 pub mod open;
+pub mod plugins;
 #[cfg(feature = "dispatch")]
 pub mod post_dispatch;
 pub mod ports;
+pub mod ps;
 pub mod remove;
+pub mod restart;
 pub mod restore;
 #[cfg(feature = "dispatch")]
 pub mod review;
 pub mod save;
+pub mod scratch;
+pub mod secret;
+pub mod setup;
 pub mod shell_data;
+pub mod stats;
 pub mod status;
+pub mod switch;
+pub mod sync;
 #[cfg(feature = "dispatch")]
 pub mod task;
+pub mod time;
+pub mod tmux;
 pub mod to;
+pub mod top;
+pub mod validate;
+pub mod window;
 
 use clap::Subcommand;
 
@@ -44,10 +68,23 @@ pub enum CommanderCommand {
         /// Text to speak
         text: String,
     },
-    /// Temporarily mute announcements
-    Mute,
-    /// Unmute announcements
-    Unmute,
+    /// Check each stage of the TTS pipeline
+    TestPipeline,
+    /// Mute announcements, for everything or one project
+    Mute {
+        /// Only mute this project
+        #[arg(long)]
+        project: Option<String>,
+        /// Unmute automatically at this local time (HH:MM)
+        #[arg(long)]
+        until: Option<String>,
+    },
+    /// Unmute announcements (all mutes, or one project's)
+    Unmute {
+        /// Only unmute this project
+        #[arg(long)]
+        project: Option<String>,
+    },
     /// Download voice control models (VAD + STT)
     Setup,
     /// Record wake word samples and build model
@@ -69,28 +106,113 @@ pub enum Commands {
         /// Attach to an existing workspace instead of creating a new one (piggyback)
         #[arg(long)]
         attach: Option<String>,
+        /// Close the project again if a `required` service fails to start
+        #[arg(long)]
+        strict: bool,
+        /// Print what would be created and spawned without doing it
+        #[arg(long, conflicts_with = "attach")]
+        dry_run: bool,
+        /// Don't run the project's `on_open` commands
+        #[arg(long)]
+        skip_hooks: bool,
     },
     /// Close a project workspace
     Close {
         /// Project name (default: current workspace)
+        #[arg(conflicts_with = "all")]
         name: Option<String>,
+        /// Close every open drift project
+        #[arg(long)]
+        all: bool,
+        /// Don't ask about uncommitted changes, open editors or running services
+        #[arg(long)]
+        force: bool,
+        /// Print what would be stopped and closed without doing it
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't run the projects' `on_close` commands
+        #[arg(long)]
+        skip_hooks: bool,
+        /// Close the windows but leave the services running headless
+        #[arg(long)]
+        keep_services: bool,
     },
     /// Switch to another project (saves current first)
     To {
         /// Project name
         name: String,
+        /// Don't snapshot the current workspace before switching
+        #[arg(long)]
+        no_save: bool,
+    },
+    /// Close the current project and open another in its place
+    Switch {
+        /// Project name
+        name: String,
     },
     /// Save current workspace state
     Save {
         /// Project name (default: current workspace)
         name: Option<String>,
     },
-    /// Show status of current project
-    Status,
-    /// Restore previously-open projects
+    /// Focus a project window by name
+    Focus {
+        /// Window name (as in `[[windows]]`)
+        window: String,
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Show status of a project (default: current), or of all of them
+    Status {
+        /// Project name (default: current)
+        name: Option<String>,
+        /// One-line overview of every project
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+    /// Spawn ad-hoc windows into a project workspace
+    Window {
+        #[command(subcommand)]
+        command: window::WindowCommand,
+    },
+    /// Open the project scratchpad in a floating editor window
+    Scratch {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+        /// Print the scratchpad path instead of opening it
+        #[arg(long)]
+        path: bool,
+    },
+    /// Restart a project service (all services if none given)
+    Restart {
+        /// Service name
+        service: Option<String>,
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Re-read the project config and apply service changes
+    Reload {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Restore previously-open projects, or with --deleted a deleted one
     Restore {
         /// Project name (omit to restore entire session)
         name: Option<String>,
+        /// Bring the project back from the trash (without a name, list the trash)
+        #[arg(long)]
+        deleted: bool,
+    },
+    /// Reopen every project from the last session that isn't already open
+    Resume,
+    /// Work with a project's tmux sessions
+    Tmux {
+        #[command(subcommand)]
+        command: tmux::TmuxCommand,
     },
 
     // ── Project ────────────────────────────────────────────────
@@ -116,16 +238,56 @@ pub enum Commands {
         #[arg(long, short)]
         template: Option<String>,
     },
-    /// List all projects
+    /// Open, close and list projects by folder
+    Folder {
+        #[command(subcommand)]
+        command: folder::FolderCommand,
+    },
+    /// Create a project from a tmuxinator or smug config
+    Import {
+        #[command(subcommand)]
+        command: import::ImportCommand,
+    },
+    /// Store encrypted secrets for project environments
+    Secret {
+        #[command(subcommand)]
+        command: secret::SecretCommand,
+    },
+    /// Keep ~/.config/drift in sync across machines, and review auto-synced windows
+    Sync {
+        #[command(subcommand)]
+        command: sync::SyncCommand,
+    },
+    /// Re-run a project's one-time [setup] commands
+    Setup {
+        #[command(subcommand)]
+        command: setup::SetupCommand,
+    },
+    /// List all projects with workspace, services and last activity
     List {
         /// Show archived projects instead
         #[arg(long)]
         archived: bool,
+        /// Only projects in this folder
+        #[arg(long)]
+        folder: Option<String>,
+        /// Order within each folder: `name` or `activity` (latest event first)
+        #[arg(long, default_value = "name")]
+        sort: String,
+        /// Add each repo's branch, changed files and ahead/behind
+        #[arg(long, short)]
+        verbose: bool,
     },
     /// Archive a project (reversible)
     Archive {
         /// Project name
         name: String,
+        /// Archive even if services are running, stopping them
+        #[arg(long)]
+        force: bool,
+        /// Tar the state and logs directory next to the archived config
+        #[arg(long)]
+        keep_state: bool,
     },
     /// Restore an archived project
     Unarchive {
@@ -139,6 +301,9 @@ pub enum Commands {
         /// Skip confirmation
         #[arg(long)]
         yes: bool,
+        /// Remove permanently instead of moving to the trash
+        #[arg(long)]
+        purge: bool,
     },
     /// Add items to a project (services, windows, env vars, ports)
     Add {
@@ -152,17 +317,59 @@ pub enum Commands {
     },
 
     // ── Inspect ────────────────────────────────────────────────
-    /// Print environment variables for a project
-    #[command(next_help_heading = "Inspect")]
+    /// Print environment variables for a project, or import/export them
+    #[command(next_help_heading = "Inspect", args_conflicts_with_subcommands = true)]
     Env {
         /// Project name (default: current workspace)
         name: Option<String>,
+        #[command(subcommand)]
+        command: Option<env::EnvCommand>,
+    },
+    /// List every drift process: daemon, commander, supervisors and services
+    Ps,
+    /// Full-screen dashboard: projects, services and a live event feed
+    Dash,
+    /// Live CPU/memory usage of running services across all projects
+    Top {
+        /// Sort by `cpu` or `mem`
+        #[arg(long, default_value = "cpu")]
+        sort: String,
+        /// Refresh interval in seconds
+        #[arg(long, default_value = "2")]
+        interval: u64,
+        /// Print one snapshot and exit
+        #[arg(long)]
+        once: bool,
     },
-    /// Show allocated ports for a project
+    /// Show recorded metrics
+    Stats {
+        /// Per-step timings of recent open/close/to runs
+        #[arg(long)]
+        timings: bool,
+        /// Number of runs to show
+        #[arg(long, default_value = "10")]
+        last: usize,
+        /// Only show one operation (open, close, to)
+        #[arg(long)]
+        op: Option<String>,
+    },
+    /// Time each project's workspace had focus, from the daemon's ledger
+    Time {
+        /// Only this project
+        #[arg(long)]
+        project: Option<String>,
+        /// The last seven days, one column per day
+        #[arg(long)]
+        week: bool,
+    },
+    /// Show allocated ports for a project, or check and free them
+    #[command(args_conflicts_with_subcommands = true)]
     Ports {
         /// Project name (default: current)
         #[arg(long)]
         project: Option<String>,
+        #[command(subcommand)]
+        command: Option<ports::PortsCommand>,
     },
     /// View service logs
     Logs {
@@ -175,6 +382,20 @@ pub enum Commands {
         #[arg(long)]
         project: Option<String>,
     },
+    /// Service logs and events of a project, interleaved by time
+    Journal {
+        /// Project name (default: current)
+        project: Option<String>,
+        /// Only entries at or after this time (HH:MM[:SS] today, or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries at or before this time (HH:MM[:SS] today, or RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Show at most this many of the latest entries (0 for all)
+        #[arg(long, default_value = "200")]
+        last: usize,
+    },
     /// View event stream
     Events {
         /// Filter by event type (supports * glob, e.g. "agent.*")
@@ -192,6 +413,27 @@ pub enum Commands {
         /// Project name (default: current)
         #[arg(long)]
         project: Option<String>,
+        /// Delete event log archives older than `events.retention_days`
+        #[arg(long, conflicts_with = "follow")]
+        prune: bool,
+        /// Only these levels, comma-separated (e.g. "error,warning")
+        #[arg(long)]
+        level: Option<String>,
+        /// Only events from these sources, comma-separated (e.g. "supervisor")
+        #[arg(long)]
+        source: Option<String>,
+        /// Only these priorities, comma-separated; a trailing + includes higher ones (e.g. "high+")
+        #[arg(long)]
+        priority: Option<String>,
+        /// Output format: text, json (alias for jsonlines), jsonlines or msgpack (one object per event)
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Only events with this correlation id
+        #[arg(long)]
+        thread: Option<String>,
+        /// Group related events (same correlation id) together
+        #[arg(long, conflicts_with = "follow")]
+        threaded: bool,
     },
     /// Send a notification to the drift event bus
     Notify {
@@ -199,19 +441,104 @@ pub enum Commands {
         #[arg(long)]
         project: Option<String>,
         /// Event type (e.g. agent.completed, build.failed)
-        #[arg(long, default_value = "notification")]
+        #[arg(long, default_value = "drift.notification")]
         r#type: String,
         /// Source identifier
         #[arg(long, default_value = "cli")]
         source: String,
-        /// Event level (info, warn, error, success)
+        /// Event level (info, warning, error, success)
         #[arg(long, default_value = "info")]
         level: String,
         /// Event title
-        title: String,
-        /// Event body
+        #[arg(required_unless_present = "print_snippet")]
+        title: Option<String>,
+        /// Event body; `-` reads it from stdin
         #[arg(default_value = "")]
         body: String,
+        /// Event body, as an option; `-` reads it from stdin
+        #[arg(long = "body", id = "body_option", value_name = "BODY", conflicts_with = "body")]
+        body_option: Option<String>,
+        /// Extra event data as a JSON object
+        #[arg(long)]
+        meta: Option<String>,
+        /// Keep a copy of this file in the project's state dir and link it in the event meta
+        #[arg(long)]
+        file: Option<std::path::PathBuf>,
+        /// Print a helper that sends events without drift installed: python, node or bash
+        #[arg(long, value_name = "LANGUAGE")]
+        print_snippet: Option<String>,
+        /// Wait for the daemon to accept the event; fail if it rejects it or doesn't answer
+        #[arg(long)]
+        wait: bool,
+        /// Link the event to related ones (default: $DRIFT_CORRELATION_ID)
+        #[arg(long)]
+        correlation_id: Option<String>,
+    },
+    /// Emit an event from an editor or script (quiet, project from the working directory)
+    Emit {
+        /// Event type (e.g. build.failed)
+        r#type: String,
+        /// Event title
+        title: Option<String>,
+        /// Event body
+        #[arg(long)]
+        body: Option<String>,
+        /// Event level (info, warning, error, success)
+        #[arg(long, default_value = "info")]
+        level: String,
+        /// Source identifier
+        #[arg(long, default_value = "editor")]
+        source: String,
+        /// Project name (default: $DRIFT_PROJECT, else the project containing the working directory)
+        #[arg(long)]
+        project: Option<String>,
+        /// Extra event data as a JSON object
+        #[arg(long)]
+        meta: Option<String>,
+        /// Link the event to related ones (default: $DRIFT_CORRELATION_ID)
+        #[arg(long)]
+        correlation_id: Option<String>,
+    },
+    /// Print the current project's repo, ports, env, services and recent events as JSON for editor plugins
+    EditorContext {
+        /// Project name (default: $DRIFT_PROJECT, else the project containing the working directory, else the focused workspace)
+        #[arg(long)]
+        project: Option<String>,
+        /// Number of recent events to include
+        #[arg(long, default_value = "20")]
+        last: usize,
+    },
+    /// Manage git hooks that emit git.* events
+    Hook {
+        #[command(subcommand)]
+        command: hook::HookCommand,
+    },
+    /// Check for problems outside the configs (orphaned tmux sessions, git identities)
+    Doctor {
+        /// Clean up what can be fixed automatically
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Open a project, global or template config in the editor and check it on exit
+    Edit {
+        /// Project name (default: current)
+        #[arg(long, conflicts_with_all = ["global", "template"])]
+        project: Option<String>,
+        /// Edit the global config.toml
+        #[arg(long, conflicts_with = "template")]
+        global: bool,
+        /// Edit a template from the templates directory
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// Check project and global configs for errors
+    Validate {
+        /// Project name (default: current, or all if none)
+        #[arg(long, conflicts_with = "all")]
+        project: Option<String>,
+        /// Validate every project and the global config
+        #[arg(long)]
+        all: bool,
     },
 
     // ── Advanced ───────────────────────────────────────────────
@@ -221,6 +548,11 @@ pub enum Commands {
         #[command(subcommand)]
         command: CommanderCommand,
     },
+    /// External `drift-<name>` subcommands
+    Plugins {
+        #[command(subcommand)]
+        command: plugins::PluginsCommand,
+    },
 
     // ── Tasks ──────────────────────────────────────────────────
     /// Manage task queue
@@ -243,21 +575,39 @@ pub enum Commands {
     // ── Hidden (internal) ──────────────────────────────────────
     /// Run the drift daemon (for systemd, runs in foreground)
     #[command(hide = true)]
-    Daemon,
+    Daemon {
+        /// Reopen the last session's projects whenever niri (re)connects
+        #[arg(long)]
+        autostart: bool,
+    },
     /// Regenerate niri-rules.kdl
     #[command(hide = true)]
-    NiriRules,
+    NiriRules {
+        /// Only report whether the file matches the registry; exits non-zero if stale
+        #[arg(long)]
+        check: bool,
+    },
     /// Output project/service/agent state as JSON (for shell integration)
     #[command(hide = true)]
-    ShellData,
+    ShellData {
+        /// Print a new document on every state change (one JSON object per line)
+        #[arg(long)]
+        follow: bool,
+    },
     /// Internal: run service supervisor (not for direct use)
     #[command(name = "_supervisor", hide = true)]
     Supervisor {
         /// Project name
         project: String,
+        /// Inject a fault for testing restart behavior (crash:<service>:<secs>), repeatable
+        #[arg(long)]
+        simulate: Vec<String>,
     },
     /// Internal: process completed dispatch (not for direct use)
     #[cfg(feature = "dispatch")]
     #[command(name = "_post-dispatch", hide = true)]
     PostDispatch(post_dispatch::PostDispatchArgs),
+    /// Run `drift-<name>` from PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
 }
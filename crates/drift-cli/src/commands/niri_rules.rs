@@ -1,11 +1,31 @@
+use anyhow::bail;
 use drift_core::{config, kdl, paths, registry};
 
-pub fn run() -> anyhow::Result<()> {
+pub fn run(check: bool) -> anyhow::Result<()> {
     let projects = registry::list_projects()?;
     let global = config::load_global_config()?;
-    kdl::write_niri_rules(&projects, &global)?;
-
     let path = paths::niri_rules_path();
+
+    if check {
+        let expected = kdl::generate_niri_rules(&projects, &global);
+        let Ok(current) = std::fs::read_to_string(&path) else {
+            bail!("{} does not exist; run 'drift niri-rules'", path.display());
+        };
+        let (missing, outdated) = kdl::niri_rules_diff(&current, &expected);
+        if missing.is_empty() && outdated.is_empty() {
+            println!("{} is up to date", path.display());
+            return Ok(());
+        }
+        for block in &outdated {
+            block.lines().for_each(|line| println!("- {line}"));
+        }
+        for block in &missing {
+            block.lines().for_each(|line| println!("+ {line}"));
+        }
+        bail!("{} is stale; run 'drift niri-rules'", path.display());
+    }
+
+    kdl::write_niri_rules(&projects, &global)?;
     println!("Wrote niri rules to {}", path.display());
     Ok(())
 }
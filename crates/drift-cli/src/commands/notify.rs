@@ -1,7 +1,12 @@
-use anyhow::Context;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
 
 use drift_core::events::{self, Event};
+use drift_core::{paths, registry};
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     project: Option<&str>,
     event_type: &str,
@@ -9,6 +14,10 @@ pub fn run(
     level: &str,
     title: &str,
     body: &str,
+    meta: Option<&str>,
+    file: Option<&Path>,
+    wait: bool,
+    correlation_id: Option<String>,
 ) -> anyhow::Result<()> {
     let project_name = match project {
         Some(p) => p.to_string(),
@@ -18,20 +27,233 @@ pub fn run(
             .context("No project specified. Use --project or set $DRIFT_PROJECT")?,
     };
 
-    let event = Event {
-        event_type: event_type.to_string(),
-        project: project_name,
-        source: source.to_string(),
-        ts: events::iso_now(),
-        level: Some(level.to_string()),
-        title: Some(title.to_string()),
-        body: if body.is_empty() { None } else { Some(body.to_string()) },
-        meta: None,
-        priority: None,
+    let body = if body == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input).context("reading body from stdin")?;
+        let (body, truncated) = tail_of_body(input.trim_end());
+        if truncated {
+            eprintln!("Body truncated to its last {} characters; attach the full output with --file", events::MAX_BODY_CHARS);
+        }
+        body
+    } else {
+        body.to_string()
+    };
+    let mut meta = meta
+        .map(serde_json::from_str::<serde_json::Value>)
+        .transpose()
+        .context("--meta must be valid JSON")?;
+    if meta.as_ref().is_some_and(|m| !m.is_object()) {
+        bail!("--meta must be a JSON object");
+    }
+
+    let mut event = Event {
+        body: if body.is_empty() { None } else { Some(body) },
+        correlation_id: correlation_id.or_else(events::inherited_correlation_id),
+        ..Event::new(event_type, project_name, source).level(level).title(title)
     };
+    // The copy is made only once the event with its link is known to be
+    // valid, so a rejected event doesn't leave an orphaned attachment
+    let attachment = file
+        .map(|file| attachment_path(&event.project, file).map(|dest| (file, dest)))
+        .transpose()?;
+    if let Some((_, dest)) = &attachment {
+        let object = meta.get_or_insert_with(|| serde_json::json!({}));
+        object["attachment"] = dest.to_string_lossy().into();
+    }
+    event.meta = meta;
+    events::validate(&event)?;
+    if let Some((file, dest)) = &attachment {
+        store_attachment(file, dest)?;
+    }
 
+    if wait {
+        let id = events::emit_event_acked(&event).context("sending event to drift daemon")?;
+        println!("Event sent (id {id})");
+        return Ok(());
+    }
     events::emit_event(&event).context("sending event to drift daemon")?;
 
     println!("Event sent");
     Ok(())
 }
+
+const TRUNCATED: &str = "[truncated]\n";
+
+/// Fit a piped body into an event, keeping its end, where a failing build
+/// prints its errors. True when something was cut.
+fn tail_of_body(body: &str) -> (String, bool) {
+    let chars = body.chars().count();
+    if chars <= events::MAX_BODY_CHARS {
+        return (body.to_string(), false);
+    }
+    let keep = events::MAX_BODY_CHARS - TRUNCATED.len();
+    let tail: String = body.chars().skip(chars - keep).collect();
+    (format!("{TRUNCATED}{tail}"), true)
+}
+
+/// Where `file` is kept: `attachments/` in the project's state dir, prefixed
+/// with the time so repeated uploads of the same report don't collide. The
+/// project must be registered, since its name becomes part of the path.
+fn attachment_path(project: &str, file: &Path) -> anyhow::Result<PathBuf> {
+    registry::check_project_name(project)?;
+    registry::find_project(project).with_context(|| format!("Project '{project}' not found"))?;
+    let name = file
+        .file_name()
+        .with_context(|| format!("{} is not a file", file.display()))?;
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    Ok(paths::attachments_dir(project).join(format!("{millis}-{}", name.to_string_lossy())))
+}
+
+fn store_attachment(file: &Path, dest: &Path) -> anyhow::Result<()> {
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    std::fs::copy(file, dest).with_context(|| format!("copying {}", file.display()))?;
+    Ok(())
+}
+
+/// Print a self-contained helper that sends events straight to the emit
+/// socket, for build scripts and test runners that can't call `drift`.
+pub fn print_snippet(language: &str) -> anyhow::Result<()> {
+    println!("{}", snippet(language)?);
+    Ok(())
+}
+
+fn snippet(language: &str) -> anyhow::Result<String> {
+    let template = match language {
+        "python" => PYTHON_SNIPPET,
+        "node" => NODE_SNIPPET,
+        "bash" => BASH_SNIPPET,
+        other => bail!("No snippet for '{other}' (expected python, node or bash)"),
+    };
+    Ok(template.trim().replace("{schema}", &events::SCHEMA_VERSION.to_string()))
+}
+
+// The daemon fills in `ts` and answers each line; these helpers don't wait
+// for the answer, and do nothing when the daemon isn't running.
+
+const PYTHON_SNIPPET: &str = r#"
+# drift event helper (event schema {schema})
+import json, os, socket
+
+def drift_notify(event_type, title, level="info", body=None, meta=None, source="script", project=None):
+    event = {
+        "schema": {schema},
+        "type": event_type,
+        "project": project or os.environ.get("DRIFT_PROJECT", ""),
+        "source": source,
+        "ts": "",
+        "level": level,
+        "title": title,
+    }
+    if body:
+        event["body"] = body
+    if meta:
+        event["meta"] = meta
+    if os.environ.get("DRIFT_CORRELATION_ID"):
+        event["correlation_id"] = os.environ["DRIFT_CORRELATION_ID"]
+    path = os.path.join(os.environ.get("XDG_RUNTIME_DIR", "/tmp"), "drift", "emit.sock")
+    try:
+        with socket.socket(socket.AF_UNIX, socket.SOCK_STREAM) as sock:
+            sock.connect(path)
+            sock.sendall((json.dumps(event) + "\n").encode())
+    except OSError:
+        pass
+
+# drift_notify("build.failed", "cargo test failed", level="error")
+"#;
+
+const NODE_SNIPPET: &str = r#"
+// drift event helper (event schema {schema})
+const net = require("net");
+const path = require("path");
+
+function driftNotify(type, title, { level = "info", body, meta, source = "script", project } = {}) {
+  const event = {
+    schema: {schema},
+    type,
+    project: project || process.env.DRIFT_PROJECT || "",
+    source,
+    ts: "",
+    level,
+    title,
+  };
+  if (body) event.body = body;
+  if (meta) event.meta = meta;
+  if (process.env.DRIFT_CORRELATION_ID) event.correlation_id = process.env.DRIFT_CORRELATION_ID;
+  const socketPath = path.join(process.env.XDG_RUNTIME_DIR || "/tmp", "drift", "emit.sock");
+  return new Promise((resolve) => {
+    const conn = net.createConnection(socketPath, () => conn.end(JSON.stringify(event) + "\n"));
+    conn.on("close", () => resolve());
+    conn.on("error", () => resolve());
+  });
+}
+
+// await driftNotify("build.failed", "npm test failed", { level: "error" });
+"#;
+
+const BASH_SNIPPET: &str = r#"
+# drift event helper (event schema {schema}); needs socat or an nc with -U
+drift_json_escape() {
+  local s=${1//\\/\\\\}
+  s=${s//\"/\\\"}
+  s=${s//$'\n'/\\n}
+  s=${s//$'\t'/\\t}
+  printf '%s' "$s"
+}
+
+# drift_notify <type> <title> [level] [body]
+drift_notify() {
+  local sock="${XDG_RUNTIME_DIR:-/tmp}/drift/emit.sock"
+  local event
+  event=$(printf '{"schema":{schema},"type":"%s","project":"%s","source":"script","ts":"","level":"%s","title":"%s"' \
+    "$(drift_json_escape "$1")" "$(drift_json_escape "${DRIFT_PROJECT:-}")" \
+    "$(drift_json_escape "${3:-info}")" "$(drift_json_escape "$2")")
+  if [ -n "${4:-}" ]; then
+    event+=",\"body\":\"$(drift_json_escape "$4")\""
+  fi
+  if [ -n "${DRIFT_CORRELATION_ID:-}" ]; then
+    event+=",\"correlation_id\":\"$(drift_json_escape "$DRIFT_CORRELATION_ID")\""
+  fi
+  event+="}"
+  if command -v socat >/dev/null 2>&1; then
+    printf '%s\n' "$event" | socat -t 1 - "UNIX-CONNECT:$sock" >/dev/null 2>&1
+  else
+    printf '%s\n' "$event" | nc -NU "$sock" >/dev/null 2>&1
+  fi
+  return 0
+}
+
+# drift_notify build.failed "make check failed" error
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippets_carry_the_current_schema() {
+        for language in ["python", "node", "bash"] {
+            let text = snippet(language).unwrap();
+            assert!(!text.contains("{schema}"), "{language}");
+            assert!(text.contains(&format!("schema {}", events::SCHEMA_VERSION)), "{language}");
+            assert!(text.contains("emit.sock"), "{language}");
+        }
+        assert!(snippet("perl").unwrap_err().to_string().contains("No snippet for 'perl'"));
+    }
+
+    #[test]
+    fn long_body_keeps_its_tail() {
+        assert_eq!(tail_of_body("short"), ("short".to_string(), false));
+
+        let log = format!("{}error: test_login failed", "ok\n".repeat(10_000));
+        let (body, truncated) = tail_of_body(&log);
+        assert!(truncated);
+        assert_eq!(body.chars().count(), events::MAX_BODY_CHARS);
+        assert!(body.starts_with(TRUNCATED));
+        assert!(body.ends_with("error: test_login failed"));
+    }
+}
@@ -1,65 +1,84 @@
 use std::fs;
+use std::net::{SocketAddr, TcpStream};
 use std::process::Command;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
-use drift_core::{config, env, kdl, niri, paths, registry, workspace};
+use drift_core::shell::{self, build_terminal_args};
+use drift_core::compositor::{self, Compositor};
+use drift_core::{config, env, kdl, paths, project_hooks, registry, timings, tmux, workspace};
 
-pub fn run(name: &str, attach: Option<&str>) -> anyhow::Result<()> {
+/// How long required services get to prove they started before open reports them.
+const REQUIRED_GRACE: Duration = Duration::from_secs(2);
+/// How long a window waits for its `after` service before opening anyway.
+const AFTER_TIMEOUT: Duration = Duration::from_secs(30);
+const AFTER_POLL: Duration = Duration::from_millis(250);
+
+pub fn run(name: &str, attach: Option<&str>, strict: bool, dry_run: bool, skip_hooks: bool) -> anyhow::Result<()> {
     let project = config::load_project_config(name)?;
     let global = config::load_global_config()?;
-    let mut niri_client = niri::NiriClient::connect()?;
+    if dry_run {
+        return print_plan(name, &project, &global);
+    }
+    let mut client = compositor::connect()?;
 
     // Piggyback mode: attach agent session to an existing workspace
     if let Some(host_workspace) = attach {
-        return run_attach(name, host_workspace, &project, &global, niri_client);
+        return run_attach(name, host_workspace, &project, &global, client);
     }
 
-    // Hot path: workspace already exists, just focus it
-    if niri_client.find_workspace_by_name(name)?.is_some() {
-        niri_client.focus_workspace(name)?;
+    // Hot path: workspace already exists, focus it and respawn closed windows
+    if let Some(ws) = client.find_workspace_by_name(name)? {
+        client.focus_workspace(name)?;
         println!("Focused existing workspace '{name}'");
+        respawn_missing_windows(name, ws.id, &project, &global, &mut *client)?;
         return Ok(());
     }
 
     // Cold boot
+    let mut timer = timings::Timer::start("open", name);
+
     // Regenerate niri-rules.kdl for persistence across niri restarts
-    let all_projects = registry::list_projects()?;
-    kdl::write_niri_rules(&all_projects, &global)?;
+    if compositor::backend()? == compositor::Backend::Niri {
+        let all_projects = registry::list_projects()?;
+        kdl::write_niri_rules(&all_projects, &global)?;
+    }
 
     // Create a named workspace dynamically via IPC
-    niri_client.create_named_workspace(name)?;
+    client.create_named_workspace(name)?;
+    if let Some(output) = global.folder(project.project.folder.as_deref()).and_then(|f| f.output.as_deref()) {
+        if let Err(e) = client.move_workspace_to_monitor(name, output) {
+            eprintln!("Warning: could not move workspace to {output}: {e}");
+        }
+    }
+    timer.step("workspace");
+
+    // Before the environment, which only points at an agent that is running
+    if let Err(e) = drift_core::ssh::start(&project) {
+        eprintln!("  Warning: project ssh-agent: {e:#}");
+    }
 
     // Build environment
-    let env_vars = env::build_env(&project)?;
+    let env_vars = env::build_process_env(&project)?;
 
     // Port conflict detection
     if let Some(ports) = &project.ports {
-        check_port_conflicts(name, ports, &mut niri_client);
+        check_port_conflicts(name, ports, &mut *client);
     }
 
     let repo_path = config::resolve_repo_path(&project.project.repo)?;
 
-    // Set git identity if configured
+    // Set git identity and signing if configured
     if let Some(git) = &project.git {
-        if let Some(user_name) = &git.user_name {
-            Command::new("git")
-                .args(["config", "--local", "user.name", user_name])
-                .current_dir(&repo_path)
-                .status()
-                .context("setting git user.name")?;
-        }
-        if let Some(user_email) = &git.user_email {
-            Command::new("git")
-                .args(["config", "--local", "user.email", user_email])
-                .current_dir(&repo_path)
-                .status()
-                .context("setting git user.email")?;
+        if let Err(e) = drift_core::git_identity::apply(&repo_path, git) {
+            eprintln!("  Warning: {e:#}");
         }
     }
 
-    // Spawn services via supervisor
-    if project.services.is_some() {
+    timer.step("setup");
+
+    // Spawn services and the build watcher via supervisor
+    if project.services.is_some() || project.watch.is_some() {
         let state_dir = paths::state_dir(name);
         fs::create_dir_all(&state_dir).context("creating state directory")?;
         let logs_dir = paths::logs_dir(name);
@@ -67,21 +86,7 @@ pub fn run(name: &str, attach: Option<&str>) -> anyhow::Result<()> {
 
         // Check for existing supervisor
         let supervisor_pid_path = paths::supervisor_pid_path(name);
-        let supervisor_running = if supervisor_pid_path.exists() {
-            if let Ok(pid_str) = fs::read_to_string(&supervisor_pid_path) {
-                if let Ok(pid) = pid_str.trim().parse::<i32>() {
-                    nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None).is_ok()
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        } else {
-            false
-        };
-
-        if supervisor_running {
+        if drift_core::supervisor::supervisor_running(name) {
             println!("  Supervisor already running");
         } else {
             // Clean up stale PID file
@@ -119,6 +124,17 @@ pub fn run(name: &str, attach: Option<&str>) -> anyhow::Result<()> {
                 eprintln!("  Warning: supervisor may not have started (check logs/supervisor.log)");
             }
         }
+        timer.step("supervisor");
+
+        let required: Vec<String> = project.services.iter()
+            .flat_map(|s| &s.processes)
+            .filter(|p| p.required && !drift_core::agent::is_interactive_agent(p))
+            .map(|p| p.name.clone())
+            .collect();
+        if !required.is_empty() {
+            check_required_services(name, &required, strict)?;
+            timer.step("required");
+        }
     }
 
     // Spawn terminal windows via niri
@@ -134,76 +150,41 @@ pub fn run(name: &str, attach: Option<&str>) -> anyhow::Result<()> {
         None
     };
 
-    let mut windows_to_spawn: Vec<&config::WindowConfig> = match &snapshot_apps {
-        Some(state) => {
-            project.windows.iter()
-                .filter(|w| w.name.as_ref().is_some_and(|n| state.config_names.contains(n.as_str())))
-                .collect()
-        }
-        None => project.windows.iter().collect(),
-    };
-
-    // Sort by snapshot column order so windows reopen in the same positions
-    if let Some(ref state) = snapshot_apps {
-        windows_to_spawn.sort_by_key(|w| {
-            w.name.as_ref()
-                .and_then(|n| state.column_order.get(n.as_str()))
-                .copied()
-                .unwrap_or(usize::MAX)
-        });
-    }
-
-    // Partition windows into tmux and normal
-    let (tmux_windows, normal_windows): (Vec<_>, Vec<_>) = windows_to_spawn
-        .into_iter()
-        .partition(|w| w.tmux == Some(true));
+    let (tmux_windows, normal_windows) = planned_windows(&project, snapshot_apps.as_ref());
 
     // Collect (title, size_change) pairs for windows that need sizing after spawn
-    let mut width_requests: Vec<(String, niri_ipc::SizeChange)> = Vec::new();
-    let mut height_requests: Vec<(String, niri_ipc::SizeChange)> = Vec::new();
+    let mut width_requests: Vec<(String, compositor::SizeChange)> = Vec::new();
+    let mut height_requests: Vec<(String, compositor::SizeChange)> = Vec::new();
+    // Windows with floating/fullscreen/column/output hints, applied after sizing
+    let mut layout_requests: Vec<(String, &config::WindowConfig)> = Vec::new();
 
     if normal_windows.is_empty() && tmux_windows.is_empty() && snapshot_apps.is_none() {
         let args = build_terminal_args(terminal, name, None, &export_str, &repo_str, None);
-        niri_client.spawn(args)?;
+        client.spawn(args)?;
         println!("  Spawned default terminal window");
     } else {
         // Spawn normal windows
         for window in &normal_windows {
-            let wn = window.name.as_deref();
-
-            // app_id-only windows spawn directly (not wrapped in a terminal)
-            if window.app_id.is_some() && window.command.is_none() {
-                let app_id = window.app_id.as_deref().unwrap();
-                let launch_cmd = drift_core::sync::resolve_app_launch_command(app_id);
-                let args: Vec<String> = launch_cmd.split_whitespace().map(String::from).collect();
-                niri_client.spawn(args)?;
-                let label = wn.unwrap_or(app_id);
-                println!("  Spawned app '{label}'");
-                continue;
+            if is_deferred(window) {
+                wait_for_window(name, window, project.ports.as_ref());
             }
+            let title = spawn_window(name, terminal, &export_str, &repo_str, window, &mut *client)?;
 
-            let cmd = window.command.as_deref().filter(|c| !c.is_empty());
-            let args = build_terminal_args(terminal, name, wn, &export_str, &repo_str, cmd);
-            niri_client.spawn(args)?;
-
-            let label = wn
-                .or(window.command.as_deref())
-                .unwrap_or("shell");
-            println!("  Spawned window '{label}'");
-
-            if let Some(wn) = wn {
-                let title = format!("drift:{name}/{wn}");
+            if let (Some(title), Some(wn)) = (title, window.name.as_deref()) {
+                if has_layout_hints(window) {
+                    layout_requests.push((title.clone(), *window));
+                }
                 // Prefer snapshot width (actual size), fall back to config width
                 if let Some(saved_w) = snapshot_apps.as_ref().and_then(|s| s.widths.get(wn)) {
-                    width_requests.push((title.clone(), niri_ipc::SizeChange::SetFixed(*saved_w as i32)));
+                    width_requests.push((title.clone(), compositor::SizeChange::Fixed(*saved_w as i32)));
                 } else if let Some(width_str) = window.width.as_deref() {
-                    if let Some(change) = niri::parse_width(width_str) {
+                    if let Some(change) = compositor::parse_width(width_str) {
                         width_requests.push((title.clone(), change));
                     }
                 }
                 // Restore snapshot height
                 if let Some(saved_h) = snapshot_apps.as_ref().and_then(|s| s.heights.get(wn)) {
-                    height_requests.push((title, niri_ipc::SizeChange::SetFixed(*saved_h as i32)));
+                    height_requests.push((title, compositor::SizeChange::Fixed(*saved_h as i32)));
                 }
             }
         }
@@ -216,7 +197,7 @@ pub fn run(name: &str, attach: Option<&str>) -> anyhow::Result<()> {
                 &export_str,
                 &repo_str,
                 &tmux_windows,
-                &mut niri_client,
+                &mut *client,
             )?;
         }
     }
@@ -226,7 +207,7 @@ pub fn run(name: &str, attach: Option<&str>) -> anyhow::Result<()> {
         for app_id in &state.non_config_apps {
             let launch_cmd = drift_core::sync::resolve_app_launch_command(app_id);
             let args: Vec<String> = launch_cmd.split_whitespace().map(String::from).collect();
-            niri_client.spawn(args)?;
+            client.spawn(args)?;
             println!("  Restored app '{app_id}'");
         }
     }
@@ -249,33 +230,418 @@ pub fn run(name: &str, attach: Option<&str>) -> anyhow::Result<()> {
                 &export_str,
                 &repo_str,
                 &interactive_agents,
-                &mut niri_client,
+                &mut *client,
             )?;
         }
     }
 
+    timer.step("windows");
+
     // Apply window sizes via IPC (windows need time to register with niri)
     if !width_requests.is_empty() || !height_requests.is_empty() {
-        apply_window_sizes(&mut niri_client, &width_requests, &height_requests);
+        apply_window_sizes(&mut *client, &width_requests, &height_requests);
+        timer.step("widths");
+    }
+    if !layout_requests.is_empty() {
+        apply_window_layout(&mut *client, &mut layout_requests);
+        timer.step("layout");
     }
 
-    drift_core::events::try_emit_event(&drift_core::events::Event {
-        event_type: "drift.project.opened".into(),
-        project: name.to_string(),
-        source: "drift".into(),
-        ts: drift_core::events::iso_now(),
-        level: Some("info".into()),
-        title: Some(format!("Opened project '{name}'")),
-        body: None,
-        meta: None,
-        priority: None,
-    });
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("drift.project.opened", name, "drift")
+        .level("info")
+        .title(format!("Opened project '{name}'")));
 
     if let Err(e) = drift_core::session::add_project(name) {
         eprintln!("  Warning: could not update session: {e}");
     }
 
+    timer.finish();
     println!("Opened project '{name}'");
+
+    // Failed setup doesn't undo the open; it runs again next time
+    if drift_core::setup::pending(&project) {
+        if let Err(e) = super::setup::run_setup(&project) {
+            eprintln!("  Warning: {e:#}");
+        }
+    }
+    if !skip_hooks {
+        run_hooks(&project, project_hooks::Stage::Open);
+    }
+    Ok(())
+}
+
+/// Run a project's `on_open`/`on_close` commands. A failure is reported
+/// (and emitted as `project.hook_failed`) but doesn't stop the open or close.
+pub(crate) fn run_hooks(project: &config::ProjectConfig, stage: project_hooks::Stage) {
+    let count = project_hooks::commands(project, stage).len();
+    if count == 0 {
+        return;
+    }
+    println!("  Running {count} {} command{}", stage.key(), if count == 1 { "" } else { "s" });
+    if let Err(e) = project_hooks::run(project, stage) {
+        eprintln!("  Warning: {e:#}");
+    }
+}
+
+/// Spawn one configured non-tmux window. Returns the title of named terminal
+/// windows so callers can size and place them once niri maps them.
+pub(crate) fn spawn_window(
+    project_name: &str,
+    terminal: &str,
+    export_str: &str,
+    repo_path: &str,
+    window: &config::WindowConfig,
+    client: &mut dyn Compositor,
+) -> anyhow::Result<Option<String>> {
+    let wn = window.name.as_deref();
+
+    if let Some(browser) = window.browser.as_deref() {
+        let wn = wn.unwrap_or("browser");
+        client.spawn(drift_core::browser::launch_args(browser, project_name, wn, window.url.as_deref())?)?;
+        println!("  Spawned browser '{wn}'");
+        let titled = drift_core::browser::sets_title(browser);
+        return Ok(titled.then(|| shell::window_title(project_name, Some(wn))));
+    }
+
+    // app_id-only windows spawn directly (not wrapped in a terminal)
+    if let (Some(app_id), None) = (window.app_id.as_deref(), &window.command) {
+        let launch_cmd = drift_core::sync::resolve_app_launch_command(app_id);
+        let args: Vec<String> = launch_cmd.split_whitespace().map(String::from).collect();
+        client.spawn(args)?;
+        let label = wn.unwrap_or(app_id);
+        println!("  Spawned app '{label}'");
+        return Ok(None);
+    }
+
+    let cmd = window.command.as_deref().filter(|c| !c.is_empty());
+    let args = build_terminal_args(terminal, project_name, wn, export_str, repo_path, cmd);
+    client.spawn(args)?;
+
+    let label = wn
+        .or(window.command.as_deref())
+        .unwrap_or("shell");
+    println!("  Spawned window '{label}'");
+    Ok(wn.map(|wn| shell::window_title(project_name, Some(wn))))
+}
+
+/// The configured windows open spawns, split into tmux and normal ones. With
+/// a snapshot, only the windows it had, in its column order; windows that
+/// wait for a service or delay go last so they don't hold up the rest.
+fn planned_windows<'a>(
+    project: &'a config::ProjectConfig,
+    snapshot: Option<&PersistedState>,
+) -> (Vec<&'a config::WindowConfig>, Vec<&'a config::WindowConfig>) {
+    let mut windows: Vec<&config::WindowConfig> = match snapshot {
+        Some(state) => project.windows.iter()
+            .filter(|w| w.name.as_ref().is_some_and(|n| state.config_names.contains(n.as_str())))
+            .collect(),
+        None => project.windows.iter().collect(),
+    };
+
+    // Sort by snapshot column order so windows reopen in the same positions
+    if let Some(state) = snapshot {
+        windows.sort_by_key(|w| {
+            w.name.as_ref()
+                .and_then(|n| state.column_order.get(n.as_str()))
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    let (tmux_windows, mut normal_windows): (Vec<_>, Vec<_>) = windows
+        .into_iter()
+        .partition(|w| w.tmux == Some(true));
+    normal_windows.sort_by_key(|w| is_deferred(w));
+    (tmux_windows, normal_windows)
+}
+
+/// `--dry-run`: print what open would do, touching nothing.
+fn print_plan(name: &str, project: &config::ProjectConfig, global: &config::GlobalConfig) -> anyhow::Result<()> {
+    println!("Plan for opening '{name}' (dry run, nothing changed)");
+
+    let open = compositor::connect()
+        .and_then(|mut c| c.find_workspace_by_name(name))
+        .ok()
+        .flatten()
+        .is_some();
+    if open {
+        println!("\nWorkspace '{name}' is already open: it would be focused and closed windows respawned");
+        return Ok(());
+    }
+    match global.folder(project.project.folder.as_deref()).and_then(|f| f.output.as_deref()) {
+        Some(output) => println!("\nWorkspace: create '{name}' on {output}"),
+        None => println!("\nWorkspace: create '{name}'"),
+    }
+    if compositor::backend()? == compositor::Backend::Niri {
+        println!(
+            "Niri rules: regenerate {} ({} projects)",
+            paths::niri_rules_path().display(),
+            registry::list_projects()?.len()
+        );
+    }
+
+    let repo_path = config::resolve_repo_path(&project.project.repo)?;
+    println!("Repo: {}", repo_path.display());
+    if let Some(git) = &project.git {
+        for (key, value) in drift_core::git_identity::expected(git) {
+            println!("Git: {key} = {value}");
+        }
+    }
+
+    let mut env_vars: Vec<_> = env::build_env(project)?.into_iter().collect();
+    env_vars.sort();
+    println!("\nEnvironment:");
+    for (key, value) in &env_vars {
+        println!("  {key}={value}");
+    }
+
+    let processes: Vec<&config::ServiceProcess> = project.services.iter().flat_map(|s| &s.processes).collect();
+    let (interactive, supervised): (Vec<_>, Vec<_>) = processes
+        .into_iter()
+        .partition(|p| drift_core::agent::is_interactive_agent(p));
+    if !supervised.is_empty() || project.watch.is_some() {
+        println!("\nSupervisor:");
+        for process in &supervised {
+            let mut notes = Vec::new();
+            if process.restart != config::RestartPolicy::Never {
+                notes.push(format!("restart {}", match process.restart {
+                    config::RestartPolicy::OnFailure => "on-failure",
+                    _ => "always",
+                }));
+            }
+            if process.required {
+                notes.push("required".into());
+            }
+            if process.cwd != "." {
+                notes.push(format!("in {}", process.cwd));
+            }
+            let notes = if notes.is_empty() { String::new() } else { format!(" ({})", notes.join(", ")) };
+            match &process.agent {
+                Some(agent) => println!("  agent '{}': {agent} {}{notes}", process.name, process.agent_mode),
+                None => println!("  service '{}': {}{notes}", process.name, process.command),
+            }
+        }
+        if let Some(watch) = &project.watch {
+            println!("  watch {}: {}", watch.globs.join(" "), watch.command);
+        }
+    }
+
+    let snapshot = if project.persist_windows.unwrap_or(global.defaults.persist_windows) {
+        load_persisted_state(name)
+    } else {
+        None
+    };
+    let (tmux_windows, normal_windows) = planned_windows(project, snapshot.as_ref());
+    println!("\nWindows ({}):", global.defaults.terminal);
+    if normal_windows.is_empty() && tmux_windows.is_empty() && snapshot.is_none() {
+        println!("  default terminal");
+    }
+    for window in &normal_windows {
+        println!("  {}", describe_window(window, snapshot.as_ref()));
+    }
+    for window in &tmux_windows {
+        println!("  tmux {}", describe_window(window, snapshot.as_ref()));
+    }
+    if let Some(state) = &snapshot {
+        for app_id in &state.non_config_apps {
+            println!("  app '{app_id}' (from snapshot)");
+        }
+    }
+    if !interactive.is_empty() {
+        println!("  agents: tmux session '{}'", drift_core::agent::tmux_session_name(name));
+        for agent in &interactive {
+            let width = agent.width.as_deref().map(|w| format!(" (width {w})")).unwrap_or_default();
+            println!("    '{}': {}{width}", agent.name, agent.agent.as_deref().unwrap_or("agent"));
+        }
+    }
+    if !project.on_open.is_empty() {
+        println!("\nOn open:");
+        for command in &project.on_open {
+            println!("  {command}");
+        }
+    }
+    Ok(())
+}
+
+fn describe_window(window: &config::WindowConfig, snapshot: Option<&PersistedState>) -> String {
+    let label = window.name.as_deref().unwrap_or("(unnamed)");
+    let what = match (&window.command, &window.app_id) {
+        (Some(cmd), _) if !cmd.is_empty() => cmd.as_str(),
+        (None, Some(app_id)) => app_id.as_str(),
+        _ => "shell",
+    };
+    let mut notes = Vec::new();
+    let saved = window.name.as_deref().and_then(|n| snapshot?.widths.get(n));
+    match (saved, &window.width) {
+        (Some(px), _) => notes.push(format!("width {px}px from snapshot")),
+        (None, Some(width)) => notes.push(format!("width {width}")),
+        (None, None) => {}
+    }
+    if let Some(service) = &window.after {
+        notes.push(format!("after '{service}'"));
+    }
+    if let Some(delay) = &window.delay {
+        notes.push(format!("delay {delay}"));
+    }
+    if window.floating == Some(true) {
+        notes.push("floating".into());
+    }
+    if window.fullscreen == Some(true) {
+        notes.push("fullscreen".into());
+    }
+    if let Some(output) = &window.output {
+        notes.push(format!("on {output}"));
+    }
+    if notes.is_empty() {
+        format!("'{label}': {what}")
+    } else {
+        format!("'{label}': {what} ({})", notes.join(", "))
+    }
+}
+
+fn is_deferred(window: &config::WindowConfig) -> bool {
+    window.after.is_some() || window.delay.is_some()
+}
+
+/// Hold a window back until its `after` service is up, then for its `delay`.
+fn wait_for_window(project_name: &str, window: &config::WindowConfig, ports: Option<&config::ProjectPorts>) {
+    let label = window.name.as_deref().or(window.command.as_deref()).unwrap_or("shell");
+    if let Some(service) = window.after.as_deref() {
+        println!("  Waiting for '{service}' before opening '{label}'");
+        if !wait_for_service(project_name, service, ports) {
+            eprintln!(
+                "  Warning: '{service}' not up after {}s, opening '{label}' anyway",
+                AFTER_TIMEOUT.as_secs()
+            );
+        }
+    }
+    if let Some(delay) = window.delay.as_deref().and_then(config::parse_delay) {
+        std::thread::sleep(delay);
+    }
+}
+
+/// Poll until the service's named port accepts connections, or, if it has no
+/// named port, until the supervisor reports it running.
+fn wait_for_service(project_name: &str, service: &str, ports: Option<&config::ProjectPorts>) -> bool {
+    let port = ports.and_then(|p| p.named.get(service)).copied();
+    let deadline = Instant::now() + AFTER_TIMEOUT;
+    while Instant::now() < deadline {
+        let up = match port {
+            Some(port) => {
+                TcpStream::connect_timeout(&SocketAddr::from(([127, 0, 0, 1], port)), AFTER_POLL).is_ok()
+            }
+            None => drift_core::supervisor::read_state(project_name).is_some_and(|state| {
+                state.services.iter().any(|s| {
+                    s.name == service && s.status == drift_core::supervisor::ServiceStatus::Running
+                })
+            }),
+        };
+        if up {
+            return true;
+        }
+        std::thread::sleep(AFTER_POLL);
+    }
+    false
+}
+
+/// Reopening an open project: spawn configured windows that are no longer on
+/// its workspace. Unnamed terminal windows can't be told apart and are skipped.
+fn respawn_missing_windows(
+    name: &str,
+    ws_id: u64,
+    project: &config::ProjectConfig,
+    global: &config::GlobalConfig,
+    client: &mut dyn Compositor,
+) -> anyhow::Result<()> {
+    let open: Vec<compositor::Window> = client
+        .windows()?
+        .into_iter()
+        .filter(|w| w.workspace_id == Some(ws_id))
+        .collect();
+    let tracked = workspace::tracked_windows(name);
+    let has_title = |title: &str| {
+        open.iter().any(|w| {
+            w.title.as_deref() == Some(title)
+                || tracked.get(&w.id).is_some_and(|wn| shell::window_title(name, Some(wn)) == title)
+        })
+    };
+
+    let missing: Vec<&config::WindowConfig> = project
+        .windows
+        .iter()
+        .filter(|w| w.tmux != Some(true))
+        .filter(|w| match (w.app_id.as_deref(), &w.command, w.name.as_deref()) {
+            (Some(app_id), None, _) => !open.iter().any(|o| o.app_id.as_deref() == Some(app_id)),
+            (_, _, Some(wn)) => !has_title(&shell::window_title(name, Some(wn))),
+            _ => false,
+        })
+        .collect();
+    let tmux_windows: Vec<&config::WindowConfig> =
+        project.windows.iter().filter(|w| w.tmux == Some(true)).collect();
+    let tmux_missing = !tmux_windows.is_empty() && !has_title(&shell::window_title(name, Some("tmux")));
+
+    if missing.is_empty() && !tmux_missing {
+        return Ok(());
+    }
+
+    let env_vars = env::build_process_env(project)?;
+    let export_str = env::format_env_exports(&env_vars);
+    let repo_path = config::resolve_repo_path(&project.project.repo)?;
+    let repo_str = repo_path.to_string_lossy();
+    let terminal = &global.defaults.terminal;
+
+    let mut width_requests: Vec<(String, compositor::SizeChange)> = Vec::new();
+    let mut layout_requests: Vec<(String, &config::WindowConfig)> = Vec::new();
+    for window in &missing {
+        let Some(title) = spawn_window(name, terminal, &export_str, &repo_str, window, client)? else {
+            continue;
+        };
+        if has_layout_hints(window) {
+            layout_requests.push((title.clone(), *window));
+        }
+        if let Some(change) = window.width.as_deref().and_then(compositor::parse_width) {
+            width_requests.push((title, change));
+        }
+    }
+    if tmux_missing {
+        spawn_tmux_windows(name, terminal, &export_str, &repo_str, &tmux_windows, client)?;
+    }
+
+    if !width_requests.is_empty() {
+        apply_window_sizes(client, &width_requests, &[]);
+    }
+    if !layout_requests.is_empty() {
+        apply_window_layout(client, &mut layout_requests);
+    }
+    println!("Respawned {} missing window(s)", missing.len() + usize::from(tmux_missing));
+    Ok(())
+}
+
+/// Give required services a moment to start, then report the ones that
+/// didn't as `project.degraded`. With `strict` the open is undone and fails.
+fn check_required_services(name: &str, required: &[String], strict: bool) -> anyhow::Result<()> {
+    std::thread::sleep(REQUIRED_GRACE);
+    let failed = drift_core::supervisor::read_state(name)
+        .map(|state| drift_core::supervisor::failed_required(&state, required))
+        .unwrap_or_else(|| required.to_vec());
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    let list = failed.join(", ");
+    eprintln!();
+    eprintln!("  ERROR: required service(s) failed to start: {list}");
+    eprintln!("         see `drift logs <service> --project {name}`");
+    eprintln!();
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("project.degraded", name, "drift")
+        .level("error")
+        .title(format!("Required services failed: {list}"))
+        .meta(serde_json::json!({ "services": failed })));
+
+    if strict {
+        super::close::close_project(name, super::close::CloseOptions { skip_hooks: true, ..Default::default() })?;
+        anyhow::bail!("Aborted opening '{name}': required service(s) failed ({list})");
+    }
     Ok(())
 }
 
@@ -285,16 +651,16 @@ fn run_attach(
     host_workspace: &str,
     project: &config::ProjectConfig,
     global: &config::GlobalConfig,
-    mut niri_client: niri::NiriClient,
+    mut client: Box<dyn Compositor>,
 ) -> anyhow::Result<()> {
     // Verify the host workspace exists
-    if niri_client.find_workspace_by_name(host_workspace)?.is_none() {
+    if client.find_workspace_by_name(host_workspace)?.is_none() {
         anyhow::bail!("Host workspace '{host_workspace}' is not open");
     }
 
-    niri_client.focus_workspace(host_workspace)?;
+    client.focus_workspace(host_workspace)?;
 
-    let env_vars = env::build_env(project)?;
+    let env_vars = env::build_process_env(project)?;
     let export_str = env::format_env_exports(&env_vars);
     let repo_path = config::resolve_repo_path(&project.project.repo)?;
     let repo_str = repo_path.to_string_lossy();
@@ -318,19 +684,19 @@ fn run_attach(
                 &export_str,
                 &repo_str,
                 &interactive_agents,
-                &mut niri_client,
+                &mut *client,
             )?;
         } else {
             // No interactive agents — open a plain shell in a scratch session
             let session = drift_core::agent::next_scratch_session_name();
-            let script = format!("{export_str}\ncd {repo_str}\nexec $SHELL");
+            let script = shell::spawn_script(&export_str, &repo_str, None);
             std::process::Command::new("tmux")
                 .args(["new-session", "-d", "-s", &session, "sh", "-c", &script])
                 .status()
                 .context("creating scratch tmux session")?;
-            let attach_cmd = format!("tmux attach -t '{session}'");
+            let attach_cmd = shell::tmux_attach_command(&session);
             let args = build_terminal_args(terminal, name, Some("shell"), &export_str, &repo_str, Some(&attach_cmd));
-            niri_client.spawn(args)?;
+            client.spawn(args)?;
             println!("  Spawned scratch tmux session '{session}' on workspace '{host_workspace}'");
         }
     }
@@ -343,45 +709,30 @@ fn run_attach(
     Ok(())
 }
 
-fn tmux_session_name(project: &str) -> String {
-    format!("drift:{project}")
-}
-
-fn tmux_session_exists(session: &str) -> bool {
-    Command::new("tmux")
-        .args(["has-session", "-t", session])
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false)
-}
-
 fn spawn_tmux_windows(
     project_name: &str,
     terminal: &str,
     export_str: &str,
     repo_path: &str,
     tmux_windows: &[&config::WindowConfig],
-    niri_client: &mut niri::NiriClient,
+    client: &mut dyn Compositor,
 ) -> anyhow::Result<()> {
-    let session = tmux_session_name(project_name);
+    let session = tmux::window_session_name(project_name);
 
-    if tmux_session_exists(&session) {
+    if tmux::session_exists(&session) {
         // Hot restore: session already exists, just attach
-        let args = build_terminal_args(terminal, project_name, Some("tmux"), export_str, repo_path, Some(&format!("tmux attach -t '{session}'")));
-        niri_client.spawn(args)?;
+        let args = build_terminal_args(terminal, project_name, Some("tmux"), export_str, repo_path, Some(&shell::tmux_attach_command(&session)));
+        client.spawn(args)?;
         println!("  Attached to existing tmux session '{session}'");
         return Ok(());
     }
 
     // Cold boot: create new session
     let first_window = &tmux_windows[0];
-    let first_cmd = first_window.command.as_deref().unwrap_or("$SHELL");
     let first_name = first_window.name.as_deref().unwrap_or("shell");
 
     // Build inner script for first window
-    let inner_script = format!("{export_str}\ncd {repo_path}\nexec {first_cmd}");
+    let inner_script = shell::spawn_script(export_str, repo_path, first_window.command.as_deref());
 
     // Create session with first window
     Command::new("tmux")
@@ -401,9 +752,8 @@ fn spawn_tmux_windows(
 
     // Add remaining windows
     for window in &tmux_windows[1..] {
-        let cmd = window.command.as_deref().unwrap_or("$SHELL");
         let name = window.name.as_deref().unwrap_or("shell");
-        let inner_script = format!("{export_str}\ncd {repo_path}\nexec {cmd}");
+        let inner_script = shell::spawn_script(export_str, repo_path, window.command.as_deref());
 
         Command::new("tmux")
             .args([
@@ -421,8 +771,8 @@ fn spawn_tmux_windows(
     }
 
     // Spawn niri terminal that attaches to the session
-    let args = build_terminal_args(terminal, project_name, Some("tmux"), export_str, repo_path, Some(&format!("tmux attach -t '{session}'")));
-    niri_client.spawn(args)?;
+    let args = build_terminal_args(terminal, project_name, Some("tmux"), export_str, repo_path, Some(&shell::tmux_attach_command(&session)));
+    client.spawn(args)?;
     println!("  Created tmux session '{session}' with {} window(s)", tmux_windows.len());
 
     Ok(())
@@ -437,14 +787,14 @@ fn spawn_agent_tmux_session(
     export_str: &str,
     repo_path: &str,
     agents: &[&drift_core::config::ServiceProcess],
-    niri_client: &mut niri::NiriClient,
+    client: &mut dyn Compositor,
 ) -> anyhow::Result<()> {
     let session = drift_core::agent::tmux_session_name(project_name);
 
     if drift_core::agent::tmux_session_exists(&session) {
-        let attach_cmd = format!("tmux attach -t '{session}'");
+        let attach_cmd = shell::tmux_attach_command(&session);
         let args = build_terminal_args(terminal, project_name, Some("agents"), export_str, repo_path, Some(&attach_cmd));
-        niri_client.spawn(args)?;
+        client.spawn(args)?;
         println!("  Attached to existing agent tmux session '{session}'");
         return Ok(());
     }
@@ -452,7 +802,7 @@ fn spawn_agent_tmux_session(
     // Create session with first agent's pane
     let first = agents[0];
     let first_cmd = drift_core::agent::build_agent_command(first, project_name);
-    let first_script = format!("{export_str}\ncd {repo_path}\nexec {first_cmd}");
+    let first_script = shell::spawn_script(export_str, repo_path, Some(&first_cmd));
 
     std::process::Command::new("tmux")
         .args([
@@ -468,7 +818,7 @@ fn spawn_agent_tmux_session(
     // Add remaining agents as new panes (split-window)
     for agent in &agents[1..] {
         let agent_cmd = drift_core::agent::build_agent_command(agent, project_name);
-        let agent_script = format!("{export_str}\ncd {repo_path}\nexec {agent_cmd}");
+        let agent_script = shell::spawn_script(export_str, repo_path, Some(&agent_cmd));
         std::process::Command::new("tmux")
             .args([
                 "split-window",
@@ -486,49 +836,18 @@ fn spawn_agent_tmux_session(
         .context("applying tiled layout to agent session")?;
 
     // Spawn one niri terminal window that attaches to the session
-    let attach_cmd = format!("tmux attach -t '{session}'");
+    let attach_cmd = shell::tmux_attach_command(&session);
     let args = build_terminal_args(terminal, project_name, Some("agents"), export_str, repo_path, Some(&attach_cmd));
-    niri_client.spawn(args)?;
+    client.spawn(args)?;
     println!("  Spawned agent tmux session '{session}' with {} pane(s)", agents.len());
 
     Ok(())
 }
 
-fn build_terminal_args(
-    terminal: &str,
-    project_name: &str,
-    window_name: Option<&str>,
-    export_str: &str,
-    repo_path: &str,
-    command: Option<&str>,
-) -> Vec<String> {
-    let title = match window_name {
-        Some(wn) => format!("drift:{project_name}/{wn}"),
-        None => format!("drift:{project_name}"),
-    };
-    let title_flag = format!("--title={title}");
-
-    // Build the shell script that runs inside the terminal.
-    // This ensures env vars, cwd, and the command all run in a proper shell.
-    let inner_script = match command {
-        Some(cmd) => format!("{export_str}\ncd {repo_path}\nexec {cmd}"),
-        None => format!("{export_str}\ncd {repo_path}\nexec $SHELL"),
-    };
-
-    vec![
-        terminal.into(),
-        title_flag,
-        "-e".into(),
-        "sh".into(),
-        "-c".into(),
-        inner_script,
-    ]
-}
-
 fn check_port_conflicts(
     project_name: &str,
     ports: &drift_core::config::ProjectPorts,
-    niri_client: &mut drift_core::niri::NiriClient,
+    client: &mut dyn drift_core::compositor::Compositor,
 ) {
     let other_projects = match registry::list_projects() {
         Ok(projects) => projects,
@@ -548,7 +867,7 @@ fn check_port_conflicts(
             continue;
         };
         // Only check projects that have an open workspace
-        if niri_client
+        if client
             .find_workspace_by_name(&other.project.name)
             .ok()
             .flatten()
@@ -567,10 +886,83 @@ fn check_port_conflicts(
     }
 }
 
-fn apply_window_sizes(
-    niri_client: &mut niri::NiriClient,
-    width_requests: &[(String, niri_ipc::SizeChange)],
-    height_requests: &[(String, niri_ipc::SizeChange)],
+fn has_layout_hints(window: &config::WindowConfig) -> bool {
+    window.floating == Some(true)
+        || window.fullscreen == Some(true)
+        || window.column.is_some()
+        || window.output.is_some()
+}
+
+/// Apply per-window layout hints over IPC. Column moves go in ascending
+/// order so earlier moves don't shift windows already placed.
+fn apply_window_layout(
+    client: &mut dyn Compositor,
+    requests: &mut [(String, &config::WindowConfig)],
+) {
+    requests.sort_by_key(|(_, w)| w.column.unwrap_or(usize::MAX));
+
+    for (title, window) in requests.iter() {
+        let mut found = None;
+        for attempt in 0..5 {
+            if attempt > 0 {
+                std::thread::sleep(Duration::from_millis(300));
+            }
+            match find_drift_window(client, title) {
+                Ok(Some(w)) => {
+                    found = Some(w);
+                    break;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("  Warning: failed to find window '{title}': {e}");
+                    break;
+                }
+            }
+        }
+        let Some(win) = found else {
+            eprintln!("  Warning: window '{title}' not found for layout");
+            continue;
+        };
+
+        let result = (|| -> anyhow::Result<()> {
+            if window.floating == Some(true) && !win.is_floating {
+                client.move_window_to_floating(win.id)?;
+            } else if let Some(column) = window.column {
+                client.focus_window(win.id)?;
+                client.move_column_to_index(column)?;
+            }
+            if window.fullscreen == Some(true) {
+                client.fullscreen_window(win.id)?;
+            }
+            if let Some(output) = &window.output {
+                client.move_window_to_monitor(win.id, output)?;
+            }
+            Ok(())
+        })();
+        if let Err(e) = result {
+            eprintln!("  Warning: failed to apply layout for '{title}': {e}");
+        }
+    }
+}
+
+/// The window that opened as `title`, or once its program has retitled it,
+/// the one the daemon tied to that `[[windows]]` entry.
+fn find_drift_window(client: &mut dyn Compositor, title: &str) -> anyhow::Result<Option<compositor::Window>> {
+    let windows = client.windows()?;
+    if let Some(i) = windows.iter().position(|w| w.title.as_deref() == Some(title)) {
+        return Ok(windows.into_iter().nth(i));
+    }
+    let Some((project, name)) = workspace::parse_window_title(title) else {
+        return Ok(None);
+    };
+    let tracked = workspace::tracked_windows(project);
+    Ok(windows.into_iter().find(|w| tracked.get(&w.id).is_some_and(|n| n == name)))
+}
+
+pub(crate) fn apply_window_sizes(
+    client: &mut dyn Compositor,
+    width_requests: &[(String, compositor::SizeChange)],
+    height_requests: &[(String, compositor::SizeChange)],
 ) {
     // Collect all unique titles that need sizing
     let all_titles: std::collections::HashSet<&str> = width_requests.iter()
@@ -584,10 +976,10 @@ fn apply_window_sizes(
     let mut pending_titles: Vec<&str> = all_titles.iter().copied().collect();
 
     // Build lookup maps for quick access
-    let width_map: std::collections::HashMap<&str, niri_ipc::SizeChange> = width_requests.iter()
+    let width_map: std::collections::HashMap<&str, compositor::SizeChange> = width_requests.iter()
         .map(|(t, c)| (t.as_str(), *c))
         .collect();
-    let height_map: std::collections::HashMap<&str, niri_ipc::SizeChange> = height_requests.iter()
+    let height_map: std::collections::HashMap<&str, compositor::SizeChange> = height_requests.iter()
         .map(|(t, c)| (t.as_str(), *c))
         .collect();
 
@@ -601,15 +993,15 @@ fn apply_window_sizes(
 
         let mut still_pending = Vec::new();
         for title in &pending_titles {
-            match niri_client.find_window_by_title(title) {
+            match find_drift_window(client, title) {
                 Ok(Some(window)) => {
                     if let Some(change) = width_map.get(title) {
-                        if let Err(e) = niri_client.set_window_width(window.id, *change) {
+                        if let Err(e) = client.set_window_width(window.id, *change) {
                             eprintln!("  Warning: failed to set width for '{title}': {e}");
                         }
                     }
                     if let Some(change) = height_map.get(title) {
-                        if let Err(e) = niri_client.set_window_height(window.id, *change) {
+                        if let Err(e) = client.set_window_height(window.id, *change) {
                             eprintln!("  Warning: failed to set height for '{title}': {e}");
                         }
                     }
@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::bail;
+use clap::{CommandFactory, Subcommand};
+use drift_core::{config, paths};
+
+/// Executables named `drift-<name>` on PATH run as `drift <name>`.
+const PLUGIN_PREFIX: &str = "drift-";
+
+#[derive(Subcommand)]
+pub enum PluginsCommand {
+    /// List `drift-*` executables on PATH usable as subcommands
+    List,
+}
+
+pub fn run(command: PluginsCommand) -> anyhow::Result<()> {
+    match command {
+        PluginsCommand::List => list(),
+    }
+}
+
+fn list() -> anyhow::Result<()> {
+    let plugins = discover(&search_path());
+    if plugins.is_empty() {
+        println!("No plugins found (executables named drift-<name> on PATH)");
+        return Ok(());
+    }
+    let width = plugins.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, path) in &plugins {
+        println!("{name:<width$}  {}", path.display());
+    }
+    Ok(())
+}
+
+/// Replace this process with `drift-<name>`, passing the remaining arguments.
+pub fn exec(args: Vec<String>) -> anyhow::Result<()> {
+    let Some((name, rest)) = args.split_first() else {
+        bail!("No subcommand given");
+    };
+    let Some(path) = find(&search_path(), name) else {
+        bail!("Unknown command '{name}' (no drift-{name} on PATH; see `drift --help` and `drift plugins list`)");
+    };
+
+    let mut cmd = Command::new(&path);
+    cmd.args(rest)
+        .env("DRIFT_NOTIFY_SOCK", paths::emit_socket_path())
+        .env("DRIFT_SUBSCRIBE_SOCK", paths::subscribe_socket_path());
+    if let Ok(project) = config::resolve_current_project(None) {
+        cmd.env("DRIFT_PROJECT", project);
+    }
+    if let Ok(exe) = std::env::current_exe() {
+        cmd.env("DRIFT_BIN", exe);
+    }
+
+    let err = cmd.exec();
+    bail!("Failed to run {}: {err}", path.display())
+}
+
+fn search_path() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|p| std::env::split_paths(&p).collect())
+        .unwrap_or_default()
+}
+
+fn find(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    dirs.iter()
+        .map(|dir| dir.join(format!("{PLUGIN_PREFIX}{name}")))
+        .find(|path| is_executable(path))
+}
+
+/// Plugins by name, sorted. The first match on PATH wins, like the shell, and
+/// names shadowed by a built-in command are left out since they can't be run.
+fn discover(dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let builtins = crate::Cli::command();
+    let mut seen = HashSet::new();
+    let mut plugins = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else { continue };
+        let mut found: Vec<(String, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let name = plugin_name(&file_name)?.to_string();
+                let path = entry.path();
+                is_executable(&path).then_some((name, path))
+            })
+            .collect();
+        found.sort();
+        for (name, path) in found {
+            if builtins.find_subcommand(&name).is_none() && seen.insert(name.clone()) {
+                plugins.push((name, path));
+            }
+        }
+    }
+    plugins.sort();
+    plugins
+}
+
+fn plugin_name(file_name: &str) -> Option<&str> {
+    file_name.strip_prefix(PLUGIN_PREFIX).filter(|n| !n.is_empty() && !n.contains('.'))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_exe(dir: &Path, name: &str, mode: u32) {
+        let path = dir.join(name);
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[test]
+    fn plugin_name_strips_prefix() {
+        assert_eq!(plugin_name("drift-deploy"), Some("deploy"));
+        assert_eq!(plugin_name("drift-"), None);
+        assert_eq!(plugin_name("drift-deploy.sh"), None);
+        assert_eq!(plugin_name("git-deploy"), None);
+    }
+
+    #[test]
+    fn discover_prefers_first_path_entry_and_skips_builtins() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        write_exe(first.path(), "drift-deploy", 0o755);
+        write_exe(second.path(), "drift-deploy", 0o755);
+        write_exe(second.path(), "drift-bench", 0o755);
+        write_exe(second.path(), "drift-notes", 0o644);
+        write_exe(second.path(), "drift-open", 0o755);
+
+        let dirs = vec![first.path().to_path_buf(), second.path().to_path_buf()];
+        let plugins = discover(&dirs);
+        let names: Vec<_> = plugins.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["bench", "deploy"]);
+        assert_eq!(plugins[1].1, first.path().join("drift-deploy"));
+        assert_eq!(find(&dirs, "notes"), None);
+    }
+}
@@ -1,5 +1,35 @@
-use anyhow::bail;
-use drift_core::{config, niri};
+use std::io::{BufRead, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use clap::Subcommand;
+use drift_core::config::ProjectPorts;
+use drift_core::listeners;
+use drift_core::supervisor::{self, ServiceStatus};
+use drift_core::{compositor, config};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+#[derive(Subcommand)]
+pub enum PortsCommand {
+    /// Fail if a configured port is bound by a process that isn't one of the project's services
+    Check {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Stop the process bound to a project port
+    Free {
+        /// Named port, or a port number in the project's range
+        port: String,
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+        /// Don't ask before stopping it
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
 
 pub fn run(project: Option<&str>) -> anyhow::Result<()> {
     let project_name = resolve_project_name(project)?;
@@ -32,6 +62,159 @@ pub fn run(project: Option<&str>) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn run_command(command: PortsCommand) -> anyhow::Result<()> {
+    match command {
+        PortsCommand::Check { project } => check(project.as_deref()),
+        PortsCommand::Free { port, project, yes } => free(&port, project.as_deref(), yes),
+    }
+}
+
+fn check(project: Option<&str>) -> anyhow::Result<()> {
+    let project_name = resolve_project_name(project)?;
+    let project_config = config::load_project_config(&project_name)?;
+    let Some(ports) = &project_config.ports else {
+        println!("No ports configured for '{project_name}'");
+        return Ok(());
+    };
+    let services = service_groups(&project_name);
+
+    let mut foreign = 0;
+    for (label, port) in configured_ports(ports) {
+        let Some(listener) = listeners::find(port) else {
+            if let Some(name) = &label {
+                println!("  {name}: {port} free");
+            }
+            continue;
+        };
+        let owner = services.iter().find(|(_, pgid)| listener.in_groups(&[*pgid]));
+        let label = label.unwrap_or_else(|| port.to_string());
+        match owner {
+            Some((service, _)) => println!("  {label}: {port} in use by service '{service}'"),
+            None => {
+                foreign += 1;
+                println!("  {label}: {port} in use by {}", listener.describe());
+            }
+        }
+    }
+
+    if foreign > 0 {
+        bail!("{foreign} port(s) of '{project_name}' bound by other processes; `drift ports free <port>` stops one");
+    }
+    Ok(())
+}
+
+fn free(target: &str, project: Option<&str>, yes: bool) -> anyhow::Result<()> {
+    let project_name = resolve_project_name(project)?;
+    let project_config = config::load_project_config(&project_name)?;
+    let port = resolve_port(&project_name, project_config.ports.as_ref(), target)?;
+
+    let Some(listener) = listeners::find(port) else {
+        println!("Port {port} is free");
+        return Ok(());
+    };
+    let Some(pid) = listener.pid else {
+        bail!("Port {port} is bound by a process of another user; drift can't stop it");
+    };
+    if let Some((service, _)) = service_groups(&project_name)
+        .iter()
+        .find(|(_, pgid)| listener.in_groups(&[*pgid]))
+    {
+        bail!("Port {port} is used by the '{service}' service; stop or restart it with `drift restart {service}`");
+    }
+
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            bail!("Not a terminal; pass --yes to stop {}", listener.describe());
+        }
+        eprint!("Port {port} is bound by {}. Stop it? [y/N] ", listener.describe());
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Left running");
+            return Ok(());
+        }
+    }
+
+    // Whatever held the port may have exited while we asked, and its pid
+    // been reused; only signal the process bound to it now
+    let Some(current) = listeners::find(port) else {
+        println!("Port {port} is free");
+        return Ok(());
+    };
+    if current.pid != Some(pid) {
+        bail!("Port {port} is now bound by {}; run `drift ports free {target}` again", current.describe());
+    }
+
+    signal::kill(Pid::from_raw(pid as i32), Signal::SIGTERM)
+        .with_context(|| format!("stopping {}", listener.describe()))?;
+    if wait_until_free(port, Duration::from_secs(3)) {
+        println!("Stopped {}; port {port} is free", listener.describe());
+        Ok(())
+    } else {
+        bail!("Sent SIGTERM to {} but port {port} is still bound; try `kill -9 {pid}`", listener.describe())
+    }
+}
+
+/// A named port of the project, or a number inside its range. Anything
+/// else isn't the project's to free.
+fn resolve_port(project: &str, ports: Option<&ProjectPorts>, target: &str) -> anyhow::Result<u16> {
+    let Some(ports) = ports else {
+        bail!("No ports configured for '{project}'");
+    };
+    if let Some(port) = ports.named.get(target) {
+        return Ok(*port);
+    }
+    let Ok(port) = target.parse::<u16>() else {
+        bail!("'{target}' is neither a named port of '{project}' nor a port number");
+    };
+    let in_range = ports.range.is_some_and(|[start, end]| (start..=end).contains(&port));
+    if !in_range && !ports.named.values().any(|p| *p == port) {
+        bail!("Port {port} isn't one of '{project}''s ports");
+    }
+    Ok(port)
+}
+
+/// Named ports (sorted by name), then the rest of the range unlabelled.
+fn configured_ports(ports: &ProjectPorts) -> Vec<(Option<String>, u16)> {
+    let mut named: Vec<(Option<String>, u16)> =
+        ports.named.iter().map(|(name, port)| (Some(name.clone()), *port)).collect();
+    named.sort();
+    if let Some([start, end]) = ports.range {
+        let unnamed: Vec<_> = (start..=end)
+            .filter(|port| !ports.named.values().any(|p| p == port))
+            .map(|port| (None, port))
+            .collect();
+        named.extend(unnamed);
+    }
+    named
+}
+
+/// Running services and the process group each was started in.
+fn service_groups(project: &str) -> Vec<(String, u32)> {
+    supervisor::read_state(project)
+        .map(|state| {
+            state
+                .services
+                .into_iter()
+                .filter(|s| s.status == ServiceStatus::Running)
+                .filter_map(|s| Some((s.name, s.pid?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn wait_until_free(port: u16, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if listeners::find(port).is_none() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    listeners::find(port).is_none()
+}
+
 fn resolve_project_name(name: Option<&str>) -> anyhow::Result<String> {
     if let Some(n) = name {
         return Ok(n.to_string());
@@ -43,7 +226,7 @@ fn resolve_project_name(name: Option<&str>) -> anyhow::Result<String> {
         }
     }
 
-    if let Ok(mut client) = niri::NiriClient::connect() {
+    if let Ok(mut client) = compositor::connect() {
         if let Ok(Some(win)) = client.focused_window() {
             if let Some(ws_id) = win.workspace_id {
                 if let Ok(workspaces) = client.workspaces() {
@@ -61,3 +244,34 @@ fn resolve_project_name(name: Option<&str>) -> anyhow::Result<String> {
 
     bail!("Could not determine project name. Use --project, set $DRIFT_PROJECT, or run from a drift workspace.")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_ports_come_before_the_rest_of_the_range() {
+        let ports = ProjectPorts {
+            range: Some([3000, 3003]),
+            named: [("web".into(), 3001), ("api".into(), 3002)].into_iter().collect(),
+        };
+        assert_eq!(
+            configured_ports(&ports),
+            vec![(Some("api".into()), 3002), (Some("web".into()), 3001), (None, 3000), (None, 3003)]
+        );
+    }
+
+    #[test]
+    fn only_project_ports_resolve() {
+        let ports = ProjectPorts {
+            range: Some([3000, 3003]),
+            named: [("db".into(), 5432)].into_iter().collect(),
+        };
+        assert_eq!(resolve_port("web", Some(&ports), "db").unwrap(), 5432);
+        assert_eq!(resolve_port("web", Some(&ports), "3002").unwrap(), 3002);
+        assert_eq!(resolve_port("web", Some(&ports), "5432").unwrap(), 5432);
+        assert!(resolve_port("web", Some(&ports), "22").is_err());
+        assert!(resolve_port("web", Some(&ports), "ssh").is_err());
+        assert!(resolve_port("web", None, "3000").is_err());
+    }
+}
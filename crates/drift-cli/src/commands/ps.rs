@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::Path;
+
+use drift_core::resources;
+use drift_core::supervisor::{self, ServiceStatus};
+use drift_core::{paths, registry};
+
+use super::status::format_duration;
+
+struct Row {
+    kind: &'static str,
+    project: String,
+    name: String,
+    pid: Option<u32>,
+    status: String,
+}
+
+/// Every drift process on the machine: daemon, commander, and each
+/// project's supervisor and services, checked against /proc.
+pub fn run() -> anyhow::Result<()> {
+    let mut rows = Vec::new();
+    rows.extend(pid_file_row("daemon", &paths::daemon_pid_path()));
+    rows.extend(pid_file_row("commander", &paths::commander_pid_path()));
+
+    for project in registry::list_projects()? {
+        let name = project.project.name;
+        let supervisor_up = supervisor::supervisor_running(&name);
+        if let Some(mut row) = pid_file_row("supervisor", &paths::supervisor_pid_path(&name)) {
+            // A live pid that isn't `drift _supervisor` was reused
+            if !supervisor_up {
+                row.pid = None;
+                row.status = "stale".into();
+            }
+            row.project = name.clone();
+            rows.push(row);
+        }
+
+        let Some(state) = supervisor::read_state(&name) else {
+            continue;
+        };
+        for svc in state.services {
+            let alive = svc.pid.is_some_and(alive);
+            let status = match svc.status {
+                // Without a supervisor nobody is watching these
+                ServiceStatus::Running if !supervisor_up && alive => "orphaned".into(),
+                ServiceStatus::Running if !alive => "dead".into(),
+                ServiceStatus::Running if state.suspended => "suspended".into(),
+                ServiceStatus::Running => "running".into(),
+                ServiceStatus::Stopped => "stopped".into(),
+                ServiceStatus::Failed => "failed".into(),
+                ServiceStatus::Backoff => "restarting".into(),
+                ServiceStatus::Scheduled => "scheduled".into(),
+            };
+            rows.push(Row {
+                kind: if svc.is_agent { "agent" } else { "service" },
+                project: name.clone(),
+                name: svc.name,
+                pid: svc.pid.filter(|_| alive),
+                status,
+            });
+        }
+    }
+
+    if rows.is_empty() {
+        println!("No drift processes");
+        return Ok(());
+    }
+    println!("{:<11} {:<16} {:<16} {:>8} {:<11} UPTIME", "KIND", "PROJECT", "NAME", "PID", "STATUS");
+    for row in rows {
+        let pid = row.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into());
+        let uptime = row
+            .pid
+            .and_then(resources::process_uptime)
+            .map(|d| format_duration(d.as_secs()))
+            .unwrap_or_else(|| "-".into());
+        let project = if row.project.is_empty() { "-" } else { &row.project };
+        println!(
+            "{:<11} {:<16} {:<16} {:>8} {:<11} {uptime}",
+            row.kind, project, row.name, pid, row.status
+        );
+    }
+    Ok(())
+}
+
+/// A process known by its pid file; a file naming a dead pid shows as stale.
+fn pid_file_row(kind: &'static str, path: &Path) -> Option<Row> {
+    let pid = fs::read_to_string(path).ok()?.trim().parse::<u32>().ok()?;
+    let running = alive(pid);
+    Some(Row {
+        kind,
+        project: String::new(),
+        name: kind.to_string(),
+        pid: running.then_some(pid),
+        status: if running { "running" } else { "stale" }.into(),
+    })
+}
+
+fn alive(pid: u32) -> bool {
+    nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid as i32), None).is_ok()
+}
@@ -1,36 +1,38 @@
+use std::io::{BufRead, Write};
+
 use anyhow::bail;
 use clap::Subcommand;
-use drift_core::config;
+use drift_core::config::{self, ProjectConfig};
 
 #[derive(Subcommand)]
 pub enum RemoveCommand {
-    /// Remove a service process
+    /// Remove a service process (omit the name to pick interactively)
     Service {
-        name: String,
+        name: Option<String>,
         #[arg(long)]
         project: Option<String>,
     },
-    /// Remove an agent service
+    /// Remove an agent service (omit the name to pick interactively)
     Agent {
-        name: String,
+        name: Option<String>,
         #[arg(long)]
         project: Option<String>,
     },
-    /// Remove a terminal window
+    /// Remove a terminal window (omit the name to pick interactively)
     Window {
-        name: String,
+        name: Option<String>,
         #[arg(long)]
         project: Option<String>,
     },
-    /// Remove an environment variable
+    /// Remove an environment variable (omit the key to pick interactively)
     Env {
-        key: String,
+        key: Option<String>,
         #[arg(long)]
         project: Option<String>,
     },
-    /// Remove a named port
+    /// Remove a named port (omit the name to pick interactively)
     Port {
-        name: String,
+        name: Option<String>,
         #[arg(long)]
         project: Option<String>,
     },
@@ -46,60 +48,56 @@ pub fn run(cmd: RemoveCommand) -> anyhow::Result<()> {
         RemoveCommand::Service { name, project } | RemoveCommand::Agent { name, project } => {
             let proj = config::resolve_current_project(project.as_deref())?;
             let mut cfg = config::load_project_config(&proj)?;
-            if let Some(services) = &mut cfg.services {
-                let before = services.processes.len();
-                services.processes.retain(|p| p.name != name);
-                if services.processes.len() == before {
-                    bail!("Service '{name}' not found in project '{proj}'");
-                }
-                if services.processes.is_empty() {
-                    cfg.services = None;
-                }
-            } else {
+            let Some(services) = &cfg.services else {
                 bail!("No services in project '{proj}'");
+            };
+            let available = services.processes.iter().map(|p| p.name.clone()).collect();
+            let names = selected(name, "service", available)?;
+            for name in &names {
+                remove_service(&mut cfg, &proj, name)?;
             }
-            config::save_project_config(&proj, &cfg)?;
-            println!("Removed service '{name}' from project '{proj}'");
-            Ok(())
+            save_and_report(&proj, &cfg, "service", &names)
         }
         RemoveCommand::Window { name, project } => {
             let proj = config::resolve_current_project(project.as_deref())?;
             let mut cfg = config::load_project_config(&proj)?;
-            let before = cfg.windows.len();
-            cfg.windows.retain(|w| w.name.as_deref() != Some(&name));
-            if cfg.windows.len() == before {
-                bail!("Window '{name}' not found in project '{proj}'");
+            let available = cfg.windows.iter().filter_map(|w| w.name.clone()).collect();
+            let names = selected(name, "window", available)?;
+            for name in &names {
+                let before = cfg.windows.len();
+                cfg.windows.retain(|w| w.name.as_deref() != Some(name));
+                if cfg.windows.len() == before {
+                    bail!("Window '{name}' not found in project '{proj}'");
+                }
             }
-            config::save_project_config(&proj, &cfg)?;
-            println!("Removed window '{name}' from project '{proj}'");
-            Ok(())
+            save_and_report(&proj, &cfg, "window", &names)
         }
         RemoveCommand::Env { key, project } => {
             let proj = config::resolve_current_project(project.as_deref())?;
             let mut cfg = config::load_project_config(&proj)?;
-            if cfg.env.vars.remove(&key).is_none() {
-                bail!("Env var '{key}' not found in project '{proj}'");
+            let mut available: Vec<String> = cfg.env.vars.keys().cloned().collect();
+            available.sort();
+            let keys = selected(key, "env var", available)?;
+            for key in &keys {
+                if cfg.env.vars.remove(key).is_none() {
+                    bail!("Env var '{key}' not found in project '{proj}'");
+                }
             }
-            config::save_project_config(&proj, &cfg)?;
-            println!("Removed env '{key}' from project '{proj}'");
-            Ok(())
+            save_and_report(&proj, &cfg, "env", &keys)
         }
         RemoveCommand::Port { name, project } => {
             let proj = config::resolve_current_project(project.as_deref())?;
             let mut cfg = config::load_project_config(&proj)?;
-            if let Some(ports) = &mut cfg.ports {
-                if ports.named.remove(&name).is_none() {
-                    bail!("Port '{name}' not found in project '{proj}'");
-                }
-                if ports.range.is_none() && ports.named.is_empty() {
-                    cfg.ports = None;
-                }
-            } else {
+            let Some(ports) = &cfg.ports else {
                 bail!("No ports in project '{proj}'");
+            };
+            let mut available: Vec<String> = ports.named.keys().cloned().collect();
+            available.sort();
+            let names = selected(name, "port", available)?;
+            for name in &names {
+                remove_port(&mut cfg, &proj, name)?;
             }
-            config::save_project_config(&proj, &cfg)?;
-            println!("Removed port '{name}' from project '{proj}'");
-            Ok(())
+            save_and_report(&proj, &cfg, "port", &names)
         }
         RemoveCommand::PortRange { project } => {
             let proj = config::resolve_current_project(project.as_deref())?;
@@ -122,6 +120,109 @@ pub fn run(cmd: RemoveCommand) -> anyhow::Result<()> {
     }
 }
 
+fn remove_service(cfg: &mut ProjectConfig, proj: &str, name: &str) -> anyhow::Result<()> {
+    let Some(services) = &mut cfg.services else {
+        bail!("No services in project '{proj}'");
+    };
+    let before = services.processes.len();
+    services.processes.retain(|p| p.name != name);
+    if services.processes.len() == before {
+        bail!("Service '{name}' not found in project '{proj}'");
+    }
+    if services.processes.is_empty() {
+        cfg.services = None;
+    }
+    Ok(())
+}
+
+fn remove_port(cfg: &mut ProjectConfig, proj: &str, name: &str) -> anyhow::Result<()> {
+    let Some(ports) = &mut cfg.ports else {
+        bail!("No ports in project '{proj}'");
+    };
+    if ports.named.remove(name).is_none() {
+        bail!("Port '{name}' not found in project '{proj}'");
+    }
+    if ports.range.is_none() && ports.named.is_empty() {
+        cfg.ports = None;
+    }
+    Ok(())
+}
+
+fn save_and_report(proj: &str, cfg: &ProjectConfig, kind: &str, names: &[String]) -> anyhow::Result<()> {
+    if names.is_empty() {
+        println!("Nothing removed");
+        return Ok(());
+    }
+    config::save_project_config(proj, cfg)?;
+    for name in names {
+        println!("Removed {kind} '{name}' from project '{proj}'");
+    }
+    Ok(())
+}
+
+/// The explicit name, or the items picked from a numbered list on stdin.
+fn selected(explicit: Option<String>, kind: &str, available: Vec<String>) -> anyhow::Result<Vec<String>> {
+    if let Some(name) = explicit {
+        return Ok(vec![name]);
+    }
+    if available.is_empty() {
+        bail!("No {kind}s to remove");
+    }
+
+    for (i, item) in available.iter().enumerate() {
+        println!("{:>3}) {item}", i + 1);
+    }
+    print!("Remove which {kind}s? (numbers, ranges like 2-4, text to match, `all`; empty to cancel): ");
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().lock().read_line(&mut input)?;
+    let picked = parse_selection(&input, &available)?;
+    Ok(picked.into_iter().map(|i| available[i].clone()).collect())
+}
+
+/// Indices selected by a picker answer. Tokens are separated by spaces or
+/// commas: `3`, `2-4`, `all`, or text that fuzzy-matches item names (its
+/// characters appear in order, case-insensitive).
+fn parse_selection(input: &str, items: &[String]) -> anyhow::Result<Vec<usize>> {
+    let mut picked = Vec::new();
+    for token in input.split([' ', ',', '\t', '\n']).map(str::trim).filter(|t| !t.is_empty()) {
+        if token == "all" || token == "*" {
+            picked.extend(0..items.len());
+        } else if let Some((a, b)) = token.split_once('-').filter(|(a, b)| is_number(a) && is_number(b)) {
+            let (a, b): (usize, usize) = (a.parse()?, b.parse()?);
+            if a == 0 || b < a || b > items.len() {
+                bail!("Invalid range '{token}' (1-{})", items.len());
+            }
+            picked.extend(a - 1..b);
+        } else if is_number(token) {
+            let n: usize = token.parse()?;
+            if n == 0 || n > items.len() {
+                bail!("No item {n} (1-{})", items.len());
+            }
+            picked.push(n - 1);
+        } else {
+            let matches: Vec<usize> = (0..items.len()).filter(|&i| fuzzy_match(token, &items[i])).collect();
+            if matches.is_empty() {
+                bail!("Nothing matches '{token}'");
+            }
+            picked.extend(matches);
+        }
+    }
+    picked.sort_unstable();
+    picked.dedup();
+    Ok(picked)
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn fuzzy_match(pattern: &str, item: &str) -> bool {
+    let mut chars = item.chars().flat_map(char::to_lowercase);
+    pattern.chars().flat_map(char::to_lowercase).all(|p| chars.any(|c| c == p))
+}
+
 #[cfg(test)]
 mod tests {
     use drift_core::config::*;
@@ -136,44 +237,40 @@ mod tests {
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
             services: Some(ServicesConfig {
                 processes: vec![
                     ServiceProcess {
                         name: "api".into(),
                         command: "npm start".into(),
-                        cwd: ".".into(),
                         restart: RestartPolicy::Never,
-                        stop_command: None,
-                        agent: None,
-                        prompt: None,
-                        agent_mode: "oneshot".into(),
-                        agent_model: None,
-                        agent_permissions: "full".into(),
-                        width: None,
+                        ..Default::default()
                     },
                     ServiceProcess {
                         name: "worker".into(),
                         command: "npm run worker".into(),
-                        cwd: ".".into(),
                         restart: RestartPolicy::Always,
-                        stop_command: None,
-                        agent: None,
-                        prompt: None,
-                        agent_mode: "oneshot".into(),
-                        agent_model: None,
-                        agent_permissions: "full".into(),
-                        width: None,
+                        ..Default::default()
                     },
                 ],
             }),
             windows: vec![
-                WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), width: None, tmux: None, app_id: None },
-                WindowConfig { name: Some("shell".into()), command: None, width: None, tmux: None, app_id: None },
+                WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), ..Default::default() },
+                WindowConfig { name: Some("shell".into()), ..Default::default() },
             ],
             tmux: None,
             scratchpad: None,
@@ -305,4 +402,35 @@ mod tests {
         }
         assert!(cfg.ports.is_none());
     }
+
+    fn items(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn selection_numbers_and_ranges() {
+        let list = items(&["a", "b", "c", "d", "e"]);
+        assert_eq!(super::parse_selection("1 3-4", &list).unwrap(), [0, 2, 3]);
+        assert_eq!(super::parse_selection("2,2, 1-2\n", &list).unwrap(), [0, 1]);
+        assert_eq!(super::parse_selection("all", &list).unwrap(), [0, 1, 2, 3, 4]);
+        assert!(super::parse_selection("\n", &list).unwrap().is_empty());
+    }
+
+    #[test]
+    fn selection_rejects_out_of_range() {
+        let list = items(&["a", "b"]);
+        assert!(super::parse_selection("0", &list).is_err());
+        assert!(super::parse_selection("3", &list).is_err());
+        assert!(super::parse_selection("2-1", &list).is_err());
+        assert!(super::parse_selection("1-3", &list).is_err());
+    }
+
+    #[test]
+    fn selection_fuzzy_matches_names() {
+        let list = items(&["api-server", "web", "worker", "DB_URL"]);
+        assert_eq!(super::parse_selection("w", &list).unwrap(), [1, 2]);
+        assert_eq!(super::parse_selection("apsv", &list).unwrap(), [0]);
+        assert_eq!(super::parse_selection("db", &list).unwrap(), [3]);
+        assert!(super::parse_selection("zzz", &list).is_err());
+    }
 }
@@ -0,0 +1,63 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use anyhow::{bail, Context};
+use drift_core::{config, paths};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+/// Ask the project's supervisor to restart one service (or all of them).
+pub fn run(service: Option<&str>, project: Option<&str>) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let pid = running_supervisor(&project_name)?;
+
+    if let Some(name) = service {
+        let cfg = config::load_project_config(&project_name)?;
+        let known = cfg
+            .services
+            .as_ref()
+            .is_some_and(|s| s.processes.iter().any(|p| p.name == name));
+        if !known {
+            bail!("Service '{name}' not found in project '{project_name}'");
+        }
+    }
+
+    // Requests accumulate until the supervisor picks them up; an empty line
+    // means "all". One appended write per request, so concurrent restarts
+    // can't overwrite each other
+    let path = paths::supervisor_restart_request_path(&project_name);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(format!("{}\n", service.unwrap_or("")).as_bytes()))
+        .context("writing restart request")?;
+
+    signal::kill(pid, Signal::SIGUSR1).context("signalling supervisor")?;
+    match service {
+        Some(name) => println!("Restarting '{name}' in project '{project_name}'"),
+        None => println!("Restarting all services in project '{project_name}'"),
+    }
+    Ok(())
+}
+
+/// Ask the supervisor to re-read the project config and apply service changes.
+pub fn reload(project: Option<&str>) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let pid = running_supervisor(&project_name)?;
+    signal::kill(pid, Signal::SIGHUP).context("signalling supervisor")?;
+    println!("Reloading services for project '{project_name}'");
+    Ok(())
+}
+
+fn running_supervisor(project_name: &str) -> anyhow::Result<Pid> {
+    let pid_path = paths::supervisor_pid_path(project_name);
+    let pid = fs::read_to_string(&pid_path)
+        .ok()
+        .and_then(|s| s.trim().parse::<i32>().ok())
+        .map(Pid::from_raw);
+    match pid {
+        Some(pid) if signal::kill(pid, None).is_ok() => Ok(pid),
+        _ => bail!("No supervisor running for project '{project_name}'"),
+    }
+}
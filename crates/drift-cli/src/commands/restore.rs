@@ -1,4 +1,4 @@
-use drift_core::{config, session, workspace};
+use drift_core::{compositor, config, paths, session, workspace};
 
 pub fn run(name: Option<&str>) -> anyhow::Result<()> {
     match name {
@@ -18,7 +18,7 @@ fn restore_single(name: &str) -> anyhow::Result<()> {
         println!("Restoring '{name}' (no saved snapshot)");
     }
 
-    super::open::run(name, None)
+    super::open::run(name, None, false, false, false)
 }
 
 fn restore_session() -> anyhow::Result<()> {
@@ -54,3 +54,47 @@ fn restore_session() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Reopen the last session after a niri or machine restart. Unlike
+/// `restore`, projects whose workspace already exists are left alone and
+/// projects that were deleted or archived are dropped from the session.
+pub fn resume() -> anyhow::Result<()> {
+    let session = match session::load_session()? {
+        Some(s) if !s.projects.is_empty() => s,
+        _ => {
+            println!("No session to resume");
+            return Ok(());
+        }
+    };
+
+    let mut client = compositor::connect()?;
+
+    let mut opened = 0;
+    let mut failures = 0;
+    for project in &session.projects {
+        if !paths::project_config_path(project).exists() {
+            println!("  Skipping '{project}' (no longer exists)");
+            session::remove_project(project)?;
+            continue;
+        }
+        if client.find_workspace_by_name(project)?.is_some() {
+            println!("  '{project}' already open");
+            continue;
+        }
+        println!("Resuming '{project}'");
+        match super::open::run(project, None, false, false, false) {
+            Ok(()) => opened += 1,
+            Err(e) => {
+                eprintln!("  Failed to resume '{project}': {e}");
+                failures += 1;
+            }
+        }
+    }
+
+    if failures == 0 {
+        println!("Resumed {opened} projects");
+    } else {
+        println!("Resumed {opened} projects, {failures} failed");
+    }
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use anyhow::{bail, Context};
-use drift_core::events::{iso_now, try_emit_event, Event};
+use drift_core::events::{try_emit_event, Event};
 use drift_core::handoff::read_handoff;
 use drift_core::paths;
 use drift_core::registry::list_projects;
@@ -141,17 +141,9 @@ fn approve(task_id: &str) -> anyhow::Result<()> {
     queue.complete(task_id, None, None)?;
     queue.save(&project)?;
 
-    try_emit_event(&Event {
-        event_type: "task.completed".into(),
-        project: project.clone(),
-        source: "review".into(),
-        ts: iso_now(),
-        level: Some("success".into()),
-        title: Some(format!("Task {task_id} approved")),
-        body: None,
-        meta: None,
-        priority: None,
-    });
+    try_emit_event(&Event::new("task.completed", project.clone(), "review")
+        .level("success")
+        .title(format!("Task {task_id} approved")));
 
     println!("Approved task {task_id} in project {project}");
     Ok(())
@@ -170,15 +162,10 @@ fn reject(task_id: &str, reason: Option<&str>) -> anyhow::Result<()> {
     queue.save(&project)?;
 
     try_emit_event(&Event {
-        event_type: "task.failed".into(),
-        project: project.clone(),
-        source: "review".into(),
-        ts: iso_now(),
-        level: Some("error".into()),
-        title: Some(format!("Task {task_id} rejected")),
         body: reason.map(|s| s.to_string()),
-        meta: None,
-        priority: None,
+        ..Event::new("task.failed", project.clone(), "review")
+            .level("error")
+            .title(format!("Task {task_id} rejected"))
     });
 
     println!("Rejected task {task_id} in project {project}");
@@ -1,5 +1,5 @@
 use anyhow::bail;
-use drift_core::{niri, workspace};
+use drift_core::{compositor, workspace};
 
 pub fn run(name: Option<&str>) -> anyhow::Result<()> {
     let project_name = resolve_project_name(name)?;
@@ -9,17 +9,9 @@ pub fn run(name: Option<&str>) -> anyhow::Result<()> {
         println!("Saved {} windows for '{project_name}'", snapshot.windows.len());
     }
 
-    drift_core::events::try_emit_event(&drift_core::events::Event {
-        event_type: "drift.save.completed".into(),
-        project: project_name.clone(),
-        source: "drift".into(),
-        ts: drift_core::events::iso_now(),
-        level: Some("info".into()),
-        title: Some(format!("Saved workspace for '{project_name}'")),
-        body: None,
-        meta: None,
-        priority: None,
-    });
+    drift_core::events::try_emit_event(&drift_core::events::Event::new("drift.save.completed", project_name.clone(), "drift")
+        .level("info")
+        .title(format!("Saved workspace for '{project_name}'")));
 
     Ok(())
 }
@@ -35,7 +27,7 @@ fn resolve_project_name(name: Option<&str>) -> anyhow::Result<String> {
         }
     }
 
-    if let Ok(mut client) = niri::NiriClient::connect() {
+    if let Ok(mut client) = compositor::connect() {
         if let Ok(Some(win)) = client.focused_window() {
             if let Some(ws_id) = win.workspace_id {
                 if let Ok(workspaces) = client.workspaces() {
@@ -0,0 +1,51 @@
+use std::fs;
+
+use anyhow::{bail, Context};
+use drift_core::shell::{self, build_terminal_args};
+use drift_core::{compositor, config, env, paths};
+
+pub fn run(project: Option<&str>, print_path: bool) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let project_config = config::load_project_config(&project_name)?;
+    let global = config::load_global_config()?;
+
+    let repo_path = config::resolve_repo_path(&project_config.project.repo)?;
+    let file = project_config.scratchpad.as_ref().map(|s| s.file.as_str());
+    let path = paths::scratchpad_path(&project_name, &repo_path, file);
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating {}", parent.display()))?;
+        }
+        fs::write(&path, format!("# {project_name} scratchpad\n\n"))
+            .with_context(|| format!("creating {}", path.display()))?;
+    }
+
+    if print_path {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    let mut client = compositor::connect()?;
+    if client.find_workspace_by_name(&project_name)?.is_none() {
+        bail!("Project '{project_name}' is not open. Run 'drift open {project_name}' first.");
+    }
+    client.focus_workspace(&project_name)?;
+
+    let env_vars = env::build_process_env(&project_config)?;
+    let export_str = env::format_env_exports(&env_vars);
+    let editor_cmd = format!("{} {}", global.defaults.editor, shell::quote(&path.to_string_lossy()));
+
+    let args = build_terminal_args(
+        &global.defaults.terminal,
+        &project_name,
+        Some("scratchpad"),
+        &export_str,
+        &repo_path.to_string_lossy(),
+        Some(&editor_cmd),
+    );
+    client.spawn(args)?;
+
+    println!("Opened scratchpad for '{project_name}'");
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::process::Command;
+
+use anyhow::bail;
+use clap::Subcommand;
+use drift_core::{paths, secrets};
+
+#[derive(Subcommand)]
+pub enum SecretCommand {
+    /// Store a secret, read from stdin (prompted for on a terminal)
+    Set {
+        /// Environment variable name
+        key: String,
+        /// Store it for this project instead of globally
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// List stored secret names (values are never printed)
+    List {
+        /// List this project's secrets instead of the global ones
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Remove a stored secret
+    Rm {
+        /// Environment variable name
+        key: String,
+        /// Remove it from this project instead of globally
+        #[arg(long)]
+        project: Option<String>,
+    },
+}
+
+pub fn run(command: SecretCommand) -> anyhow::Result<()> {
+    match command {
+        SecretCommand::Set { key, project } => set(&key, project.as_deref()),
+        SecretCommand::List { project } => list(project.as_deref()),
+        SecretCommand::Rm { key, project } => rm(&key, project.as_deref()),
+    }
+}
+
+fn check_project(project: Option<&str>) -> anyhow::Result<()> {
+    if let Some(name) = project {
+        if !paths::project_config_path(name).exists() {
+            bail!("Project '{name}' not found");
+        }
+    }
+    Ok(())
+}
+
+fn scope(project: Option<&str>) -> String {
+    match project {
+        Some(name) => format!("project '{name}'"),
+        None => "global secrets".into(),
+    }
+}
+
+fn set(key: &str, project: Option<&str>) -> anyhow::Result<()> {
+    check_project(project)?;
+    let value = read_value(key)?;
+    if value.is_empty() {
+        bail!("Empty value, nothing stored");
+    }
+    secrets::set(project, key, &value)?;
+    println!("Stored {key} in {}", scope(project));
+    if let Some(name) = project {
+        println!("Add \"{key}\" to `secrets` under [env] in {name}.toml to use it");
+    }
+    Ok(())
+}
+
+/// One line from a terminal (echo off), or all of piped stdin minus the
+/// trailing newline.
+fn read_value(key: &str) -> anyhow::Result<String> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        let mut value = String::new();
+        stdin.lock().read_to_string(&mut value)?;
+        return Ok(value.trim_end_matches(['\n', '\r']).to_string());
+    }
+    eprint!("Value for {key}: ");
+    std::io::stderr().flush()?;
+    let _ = Command::new("stty").arg("-echo").status();
+    let mut value = String::new();
+    let read = stdin.lock().read_line(&mut value);
+    let _ = Command::new("stty").arg("echo").status();
+    eprintln!();
+    read?;
+    Ok(value.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn list(project: Option<&str>) -> anyhow::Result<()> {
+    check_project(project)?;
+    let values = secrets::load(project)?;
+    if values.is_empty() {
+        println!("No secrets in {}", scope(project));
+        return Ok(());
+    }
+    for key in values.keys() {
+        println!("{key}");
+    }
+    Ok(())
+}
+
+fn rm(key: &str, project: Option<&str>) -> anyhow::Result<()> {
+    check_project(project)?;
+    if !secrets::remove(project, key)? {
+        bail!("No secret {key} in {}", scope(project));
+    }
+    println!("Removed {key} from {}", scope(project));
+    Ok(())
+}
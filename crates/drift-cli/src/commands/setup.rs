@@ -0,0 +1,39 @@
+use anyhow::bail;
+use clap::Subcommand;
+use drift_core::{config, setup};
+
+#[derive(Subcommand)]
+pub enum SetupCommand {
+    /// Run the project's [setup] commands again now
+    Rerun {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+    },
+}
+
+pub fn run(command: SetupCommand) -> anyhow::Result<()> {
+    match command {
+        SetupCommand::Rerun { project } => rerun(project.as_deref()),
+    }
+}
+
+fn rerun(project: Option<&str>) -> anyhow::Result<()> {
+    let name = config::resolve_current_project(project)?;
+    let cfg = config::load_project_config(&name)?;
+    if !setup::has_commands(&cfg) {
+        bail!("Project '{name}' has no [setup] commands");
+    }
+    setup::reset(&name)?;
+    run_setup(&cfg)
+}
+
+/// Run setup with progress on stdout; shared with `drift open`.
+pub(crate) fn run_setup(cfg: &config::ProjectConfig) -> anyhow::Result<()> {
+    let name = &cfg.project.name;
+    let count = cfg.setup.as_ref().map_or(0, |s| s.commands.len());
+    println!("Running setup for '{name}' ({count} command{})", if count == 1 { "" } else { "s" });
+    setup::run(cfg)?;
+    println!("Setup complete for '{name}'");
+    Ok(())
+}
@@ -2,8 +2,12 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc;
+use std::time::Duration;
 
-use drift_core::niri::NiriClient;
+use drift_core::compositor;
 use drift_core::supervisor::{ServiceStatus, ServicesState};
 #[cfg(feature = "dispatch")]
 use drift_core::tasks::{TaskQueue, TaskStatus};
@@ -58,6 +62,9 @@ struct ProjectData {
     is_active: bool,
     workspaces: Vec<WorkspaceData>,
     services: Vec<ServiceData>,
+    /// Sum over all services, for bars ranking projects by usage
+    cpu_percent: f32,
+    rss_bytes: u64,
     project_state: Option<ProjectStateData>,
     #[cfg(feature = "dispatch")]
     tasks: Option<TaskSummary>,
@@ -85,6 +92,8 @@ struct ServiceData {
     name: String,
     status: String,
     is_agent: bool,
+    cpu_percent: Option<f32>,
+    rss_bytes: Option<u64>,
 }
 
 #[cfg(feature = "dispatch")]
@@ -126,7 +135,57 @@ struct GlobalSummary {
     total_reviews_pending: usize,
 }
 
-pub fn run() -> anyhow::Result<()> {
+/// Quiet period after a daemon event before the document is rebuilt, so a
+/// burst (e.g. a project opening) produces one update.
+const FOLLOW_DEBOUNCE: Duration = Duration::from_millis(150);
+/// Rebuild this often without events too, for changes the daemon doesn't
+/// announce (window counts, service CPU). Only changed documents are printed.
+const FOLLOW_REFRESH: Duration = Duration::from_secs(2);
+
+pub fn run(follow: bool) -> anyhow::Result<()> {
+    if !follow {
+        println!("{}", build()?);
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || watch_daemon_events(tx));
+
+    let mut last = String::new();
+    loop {
+        let doc = build()?;
+        if doc != last {
+            // A closed pipe (bar reloaded) ends the stream
+            let mut out = std::io::stdout().lock();
+            writeln!(out, "{doc}")?;
+            out.flush()?;
+            last = doc;
+        }
+
+        if rx.recv_timeout(FOLLOW_REFRESH).is_ok() {
+            while rx.recv_timeout(FOLLOW_DEBOUNCE).is_ok() {}
+        }
+    }
+}
+
+/// Signal every daemon event on `tx`, reconnecting while the daemon is down.
+fn watch_daemon_events(tx: mpsc::Sender<()>) {
+    loop {
+        if let Ok(stream) = UnixStream::connect(paths::subscribe_socket_path()) {
+            for line in BufReader::new(stream).lines() {
+                if line.is_err() {
+                    break;
+                }
+                if tx.send(()).is_err() {
+                    return;
+                }
+            }
+        }
+        std::thread::sleep(FOLLOW_REFRESH);
+    }
+}
+
+fn build() -> anyhow::Result<String> {
     let projects = registry::list_projects()?;
     let known_project_names: HashSet<String> = projects
         .iter()
@@ -297,12 +356,16 @@ pub fn run() -> anyhow::Result<()> {
             }
         }
 
+        let cpu_percent = services.iter().filter_map(|s| s.cpu_percent).sum();
+        let rss_bytes = services.iter().filter_map(|s| s.rss_bytes).sum();
         folders.entry(folder_key).or_default().push(ProjectData {
             name: name.clone(),
             icon: project.project.icon.clone(),
             is_active,
             workspaces,
             services,
+            cpu_percent,
+            rss_bytes,
             project_state: ps,
             #[cfg(feature = "dispatch")]
             tasks: task_summary,
@@ -327,8 +390,7 @@ pub fn run() -> anyhow::Result<()> {
         },
     };
 
-    println!("{}", serde_json::to_string(&data)?);
-    Ok(())
+    Ok(serde_json::to_string(&data)?)
 }
 
 fn read_daemon_state() -> Option<drift_daemon::state::DaemonState> {
@@ -338,8 +400,8 @@ fn read_daemon_state() -> Option<drift_daemon::state::DaemonState> {
 }
 
 /// Query niri directly for workspaces and windows. Returns empty vecs if niri isn't available.
-fn query_niri() -> (Vec<niri_ipc::Workspace>, Vec<niri_ipc::Window>) {
-    let mut client = match NiriClient::connect() {
+fn query_niri() -> (Vec<compositor::Workspace>, Vec<compositor::Window>) {
+    let mut client = match compositor::connect() {
         Ok(c) => c,
         Err(_) => return (Vec::new(), Vec::new()),
     };
@@ -438,8 +500,11 @@ fn read_services(project: &str) -> Vec<ServiceData> {
                 ServiceStatus::Stopped => "stopped".into(),
                 ServiceStatus::Failed => "failed".into(),
                 ServiceStatus::Backoff => "backoff".into(),
+                ServiceStatus::Scheduled => "scheduled".into(),
             },
             is_agent: svc.is_agent,
+            cpu_percent: svc.cpu_percent,
+            rss_bytes: svc.rss_bytes,
         })
         .collect()
 }
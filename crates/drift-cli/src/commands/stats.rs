@@ -0,0 +1,40 @@
+use anyhow::bail;
+use drift_core::timings::{self, Run};
+
+pub fn run(show_timings: bool, last: usize, op: Option<&str>) -> anyhow::Result<()> {
+    if !show_timings {
+        bail!("Nothing selected; try `drift stats --timings`");
+    }
+
+    let runs: Vec<Run> = timings::recent(usize::MAX)?
+        .into_iter()
+        .filter(|r| op.is_none_or(|o| r.op == o))
+        .collect();
+    if runs.is_empty() {
+        println!("No timings recorded yet (they are written by open, close and to)");
+        return Ok(());
+    }
+
+    let skip = runs.len().saturating_sub(last);
+    println!("{:<20} {:<6} {:<16} {:>8}  STEPS", "WHEN", "OP", "PROJECT", "TOTAL");
+    for run in &runs[skip..] {
+        let steps: Vec<String> = run.steps.iter().map(|s| format!("{} {}", s.name, format_ms(s.ms))).collect();
+        println!(
+            "{:<20} {:<6} {:<16} {:>8}  {}",
+            run.ts.get(..19).unwrap_or(&run.ts),
+            run.op,
+            run.project,
+            format_ms(run.total_ms),
+            steps.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn format_ms(ms: u64) -> String {
+    if ms >= 1000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        format!("{ms}ms")
+    }
+}
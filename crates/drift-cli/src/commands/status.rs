@@ -3,14 +3,16 @@ use std::fs;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use drift_core::events::Event;
-use drift_core::{config, niri, paths, project_state};
+use drift_core::supervisor::{self, ServiceStatus};
+use drift_core::{compositor, config, paths, project_state, registry};
+use drift_daemon::state::DaemonState;
 use nix::sys::signal;
 use nix::unistd::Pid;
 
-pub fn run() -> anyhow::Result<()> {
+pub fn run(name: Option<&str>) -> anyhow::Result<()> {
     show_daemon_status();
 
-    let project_name = detect_project();
+    let project_name = name.map(str::to_string).or_else(detect_project);
 
     let Some(project_name) = project_name else {
         println!("No active drift project detected.");
@@ -19,7 +21,6 @@ pub fn run() -> anyhow::Result<()> {
     };
 
     let project = config::load_project_config(&project_name)?;
-    let mut niri_client = niri::NiriClient::connect()?;
 
     // Project header: name (folder/)
     let folder_suffix = project
@@ -34,19 +35,26 @@ pub fn run() -> anyhow::Result<()> {
     let repo_path = config::resolve_repo_path(&project.project.repo)?;
     let repo_display = abbreviate_home(&repo_path.to_string_lossy());
     println!("  Repo: {repo_display}");
+    if let Some(git) = drift_core::repo_status::inspect(&repo_path) {
+        println!("  Git: {}", git.summary());
+    }
 
-    // Workspace status with window count
-    let ws = niri_client.find_workspace_by_name(&project_name)?;
-    let window_count = if let Some(ref ws) = ws {
-        let ws_id = ws.id;
-        let windows = niri_client.windows()?;
-        windows.iter().filter(|w| w.workspace_id == Some(ws_id)).count()
-    } else {
-        0
+    // Workspace status with window count (unknown without a compositor, e.g. over ssh)
+    let workspace = match compositor::connect() {
+        Ok(mut client) => {
+            let ws = client.find_workspace_by_name(&project_name)?;
+            let window_count = match &ws {
+                Some(ws) => client.windows()?.iter().filter(|w| w.workspace_id == Some(ws.id)).count(),
+                None => 0,
+            };
+            Some((ws, window_count))
+        }
+        Err(_) => None,
     };
 
-    match &ws {
-        Some(ws) => {
+    match workspace {
+        None => println!("  Workspace: unknown (compositor not reachable)"),
+        Some((Some(ws), window_count)) => {
             let mut parts = Vec::new();
             if ws.is_focused {
                 parts.push("focused".to_string());
@@ -54,7 +62,7 @@ pub fn run() -> anyhow::Result<()> {
             parts.push(format!("{window_count} window{}", if window_count == 1 { "" } else { "s" }));
             println!("  Workspace: active ({})", parts.join(", "));
         }
-        None => {
+        Some((None, _)) => {
             println!("  Workspace: not open");
         }
     }
@@ -74,6 +82,107 @@ pub fn run() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// One line per project: workspace, services and the latest event.
+pub fn run_all() -> anyhow::Result<()> {
+    show_daemon_status();
+
+    let projects = registry::list_projects()?;
+    if projects.is_empty() {
+        println!("No projects");
+        return Ok(());
+    }
+    let overview = Overview::load();
+    println!("{:<18} {:<22} {:<26} LAST EVENT", "PROJECT", "WORKSPACE", "SERVICES");
+    for p in projects {
+        let name = p.project.name;
+        let last_event = overview
+            .last_event(&name)
+            .map(|e| format!("{}  {}", e.ts.get(11..16).unwrap_or(&e.ts), e.event_type))
+            .unwrap_or_else(|| "-".into());
+        println!(
+            "{name:<18} {:<22} {:<26} {last_event}",
+            overview.workspace(&name),
+            services_summary(&name)
+        );
+    }
+    Ok(())
+}
+
+/// What's live across projects, from the daemon's state file when the
+/// daemon is up, otherwise from the compositor (which gives no counts).
+pub(crate) struct Overview {
+    daemon: Option<DaemonState>,
+    open_names: Vec<String>,
+}
+
+impl Overview {
+    pub(crate) fn load() -> Overview {
+        let daemon = fs::read_to_string(paths::daemon_state_path())
+            .ok()
+            .and_then(|json| serde_json::from_str::<DaemonState>(&json).ok())
+            .filter(|s| signal::kill(Pid::from_raw(s.pid as i32), None).is_ok());
+        let open_names = if daemon.is_some() {
+            Vec::new()
+        } else {
+            compositor::connect()
+                .and_then(|mut c| c.workspaces())
+                .map(|ws| ws.into_iter().filter_map(|w| w.name).collect())
+                .unwrap_or_default()
+        };
+        Overview { daemon, open_names }
+    }
+
+    /// "open (3 win, focused)", "open" or "-".
+    pub(crate) fn workspace(&self, project: &str) -> String {
+        match &self.daemon {
+            Some(state) => match state.workspace_projects.iter().find(|w| w.project == project) {
+                Some(w) => format!(
+                    "open ({} win{})",
+                    w.window_count,
+                    if w.is_focused { ", focused" } else { "" }
+                ),
+                None => "-".into(),
+            },
+            None if self.open_names.iter().any(|n| n == project) => "open".into(),
+            None => "-".into(),
+        }
+    }
+
+    /// The daemon's state, when it is running.
+    pub(crate) fn daemon(&self) -> Option<&DaemonState> {
+        self.daemon.as_ref()
+    }
+
+    pub(crate) fn last_event(&self, project: &str) -> Option<&Event> {
+        self.daemon
+            .as_ref()?
+            .recent_events
+            .get(project)?
+            .iter()
+            .max_by(|a, b| a.ts.cmp(&b.ts))
+    }
+}
+
+pub(crate) fn services_summary(project_name: &str) -> String {
+    let Some(state) = supervisor::read_state(project_name) else {
+        return "-".into();
+    };
+    if state.services.is_empty() {
+        return "-".into();
+    }
+    let count = |f: fn(&ServiceStatus) -> bool| state.services.iter().filter(|s| f(&s.status)).count();
+    let running = count(|s| *s == ServiceStatus::Running);
+    let failing = count(|s| matches!(s, ServiceStatus::Failed | ServiceStatus::Backoff));
+    let mut summary = format!("{running}/{} running", state.services.len());
+    if failing > 0 {
+        summary.push_str(&format!(", {failing} failing"));
+    }
+    if state.suspended {
+        summary.push_str(", suspended");
+    }
+    summary
+}
+
 fn show_services(project_name: &str, project: &config::ProjectConfig) {
     let state_path = paths::services_state_path(project_name);
     if state_path.exists() {
@@ -85,7 +194,11 @@ fn show_services(project_name: &str, project: &config::ProjectConfig) {
                             return;
                         }
                         println!();
-                        println!("  Services:");
+                        if state.suspended {
+                            println!("  Services: (suspended while idle)");
+                        } else {
+                            println!("  Services:");
+                        }
                         let now_epoch = SystemTime::now()
                             .duration_since(UNIX_EPOCH)
                             .unwrap_or_default()
@@ -129,13 +242,14 @@ fn show_services(project_name: &str, project: &config::ProjectConfig) {
 }
 
 fn print_service_line(svc: &drift_core::supervisor::ServiceState, now_epoch: u64) {
-    use drift_core::supervisor::ServiceStatus;
+    use drift_core::supervisor::StartReason;
 
     let status_str = match svc.status {
         ServiceStatus::Running => "running",
         ServiceStatus::Stopped => "stopped",
         ServiceStatus::Failed => "failed",
         ServiceStatus::Backoff => "restarting",
+        ServiceStatus::Scheduled => "scheduled",
     };
 
     let mut parts = vec![format!("    {:<12} {:<10}", svc.name, status_str)];
@@ -157,6 +271,23 @@ fn print_service_line(svc: &drift_core::supervisor::ServiceState, now_epoch: u64
         }
     }
 
+    if svc.status == ServiceStatus::Scheduled {
+        if let Some(next) = svc.next_run.as_deref().and_then(|t| t.parse::<i64>().ok()) {
+            parts.push(format!("next run {}", drift_core::mute::format_clock(next)));
+        }
+        if let Some(code) = svc.exit_code {
+            parts.push(format!("last exit {code}"));
+        }
+    }
+
+    // Resource usage (sampled by the supervisor every few seconds)
+    if let Some(cpu) = svc.cpu_percent {
+        parts.push(format!("cpu {cpu:.1}%"));
+    }
+    if let Some(rss) = svc.rss_bytes {
+        parts.push(format!("mem {}", drift_core::resources::format_bytes(rss)));
+    }
+
     // Agent info
     if svc.is_agent {
         if let Some(ref agent_type) = svc.agent_type {
@@ -170,6 +301,15 @@ fn print_service_line(svc: &drift_core::supervisor::ServiceState, now_epoch: u64
         parts.push(format!("[{} {label}]", svc.restart_count));
     }
 
+    // Why the current run was started, when it wasn't the initial open
+    if svc.start_reason != StartReason::Initial {
+        let mut why = format!("started: {}", svc.start_reason.as_str());
+        if let Some(code) = svc.previous_exit_code {
+            why.push_str(&format!(", prev exit {code}"));
+        }
+        parts.push(format!("({why})"));
+    }
+
     // Join: first part is already formatted with padding, rest separated by two spaces
     let line = if parts.len() == 1 {
         parts.remove(0)
@@ -340,7 +480,7 @@ fn detect_project() -> Option<String> {
     }
 
     // Try to detect from focused workspace
-    let mut client = niri::NiriClient::connect().ok()?;
+    let mut client = compositor::connect().ok()?;
     let win = client.focused_window().ok()??;
     let ws_id = win.workspace_id?;
     let workspaces = client.workspaces().ok()?;
@@ -351,7 +491,7 @@ fn detect_project() -> Option<String> {
 }
 
 /// Format seconds into human-readable duration: <1m, 5m, 2h13m, 1d4h
-fn format_duration(secs: u64) -> String {
+pub(crate) fn format_duration(secs: u64) -> String {
     let minutes = secs / 60;
     let hours = minutes / 60;
     let days = hours / 24;
@@ -0,0 +1,28 @@
+use anyhow::bail;
+use drift_core::{config, paths};
+
+use super::close::{close_project, emit_closed, CloseOptions};
+use super::to::detect_current_project;
+
+/// Close the current project (services, windows) and open another in its
+/// place. Unlike `to`, only one project's dev stack is running afterwards.
+pub fn run(name: &str) -> anyhow::Result<()> {
+    // Fail before tearing anything down if the target doesn't exist
+    config::load_project_config(name)?;
+
+    // Named workspaces that aren't drift projects are left alone
+    let current = detect_current_project().filter(|p| paths::project_config_path(p).exists());
+    match current {
+        Some(current) if current == name => {
+            bail!("Already in project '{name}'");
+        }
+        Some(current) => {
+            println!("Closing '{current}'...");
+            close_project(&current, CloseOptions::default())?;
+            emit_closed(&current);
+        }
+        None => {}
+    }
+
+    super::open::run(name, None, false, false, false)
+}
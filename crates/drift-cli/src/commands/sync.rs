@@ -0,0 +1,271 @@
+use std::collections::BTreeSet;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context};
+use clap::Subcommand;
+use drift_core::{config, kdl, paths, sync as window_sync};
+
+#[derive(Subcommand)]
+pub enum SyncCommand {
+    /// Make ~/.config/drift a git repo pushing to this remote
+    Setup {
+        /// Git remote URL
+        remote: String,
+    },
+    /// Commit local config changes and push them
+    Push,
+    /// Fetch and apply config changes made on other machines
+    Pull,
+    /// Accept or reject windows the daemon staged for a project with `auto_sync = false`
+    Review {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+        /// Add every pending window to the project config without asking
+        #[arg(long, conflicts_with = "reject_all")]
+        accept_all: bool,
+        /// Reject every pending window without asking
+        #[arg(long)]
+        reject_all: bool,
+    },
+}
+
+const BRANCH: &str = "main";
+
+/// Generated or tied to this machine; never synced. Secret stores are
+/// encrypted to this machine's identity, so no other machine could read them.
+const GITIGNORE: &str = "\
+# Written by `drift sync setup`: machine-specific files stay local
+niri-rules.kdl
+secrets/
+*.tmp
+";
+
+pub fn run(command: SyncCommand) -> anyhow::Result<()> {
+    match command {
+        SyncCommand::Setup { remote } => setup(&remote),
+        SyncCommand::Push => push(),
+        SyncCommand::Pull => pull(),
+        SyncCommand::Review { project, accept_all, reject_all } => review(project.as_deref(), accept_all, reject_all),
+    }
+}
+
+fn review(project: Option<&str>, accept_all: bool, reject_all: bool) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let pending = window_sync::load_pending_windows(&project_name)?;
+    if pending.windows.is_empty() {
+        println!("No pending windows for '{project_name}'");
+        return Ok(());
+    }
+
+    let all: Vec<usize> = (0..pending.windows.len()).collect();
+    if accept_all || reject_all {
+        let (accept, reject) = if accept_all { (all, Vec::new()) } else { (Vec::new(), all) };
+        window_sync::review_pending_windows(&project_name, &accept, &reject)?;
+        let verb = if accept_all { "Added" } else { "Rejected" };
+        println!("{verb} {} window(s) for '{project_name}'", pending.windows.len());
+        return Ok(());
+    }
+
+    if !std::io::stdin().is_terminal() {
+        for entry in &pending.windows {
+            println!("  {}", describe(entry));
+        }
+        bail!("Not a terminal; pass --accept-all or --reject-all");
+    }
+    let (mut accept, mut reject) = (Vec::new(), Vec::new());
+    for (i, entry) in pending.windows.iter().enumerate() {
+        eprint!("  {}  [a]ccept, [r]eject, [s]kip? ", describe(entry));
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        match answer.trim() {
+            "a" | "accept" => accept.push(i),
+            "r" | "reject" => reject.push(i),
+            _ => {}
+        }
+    }
+    window_sync::review_pending_windows(&project_name, &accept, &reject)?;
+    let skipped = pending.windows.len() - accept.len() - reject.len();
+    println!("Added {}, rejected {}, {skipped} still pending", accept.len(), reject.len());
+    Ok(())
+}
+
+/// `firefox-2  org.mozilla.firefox  "Docs"`
+fn describe(entry: &window_sync::PendingWindow) -> String {
+    let name = entry.window.name.as_deref().unwrap_or("?");
+    match &entry.title {
+        Some(title) => format!("{name}  {}  {title:?}", entry.app_id),
+        None => format!("{name}  {}", entry.app_id),
+    }
+}
+
+fn setup(remote: &str) -> anyhow::Result<()> {
+    let dir = paths::config_dir();
+    std::fs::create_dir_all(&dir)?;
+    if !dir.join(".git").exists() {
+        git(&dir, &["init", "--quiet"])?;
+        git(&dir, &["symbolic-ref", "HEAD", &format!("refs/heads/{BRANCH}")])?;
+        println!("  Initialized git repo in {}", dir.display());
+    }
+    std::fs::write(dir.join(".gitignore"), GITIGNORE)?;
+    // Files committed before the .gitignore existed would still be tracked
+    let _ = git(&dir, &["rm", "--cached", "--quiet", "--ignore-unmatch", "-r", "niri-rules.kdl", "secrets"]);
+
+    if git(&dir, &["remote", "get-url", "origin"]).is_ok() {
+        git(&dir, &["remote", "set-url", "origin", remote])?;
+    } else {
+        git(&dir, &["remote", "add", "origin", remote])?;
+    }
+    // Joining configs already pushed from another machine
+    let fresh = git(&dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_err();
+    if fresh && fetch(&dir)? {
+        adopt_remote(&dir)?;
+    }
+    commit_local(&dir)?;
+    if fetch(&dir)? {
+        integrate(&dir)?;
+    }
+    git(&dir, &["push", "--quiet", "-u", "origin", BRANCH])?;
+    println!("Syncing {} with {remote}", dir.display());
+    Ok(())
+}
+
+fn push() -> anyhow::Result<()> {
+    let dir = repo_dir()?;
+    let committed = commit_local(&dir)?;
+    if fetch(&dir)? {
+        integrate(&dir)?;
+    }
+    git(&dir, &["push", "--quiet", "origin", BRANCH])?;
+    if committed {
+        println!("Pushed config changes");
+    } else {
+        println!("No local changes; remote is up to date");
+    }
+    Ok(())
+}
+
+fn pull() -> anyhow::Result<()> {
+    let dir = repo_dir()?;
+    // Local edits are committed first so a pull never loses them
+    commit_local(&dir)?;
+    if !fetch(&dir)? {
+        println!("Nothing to pull");
+        return Ok(());
+    }
+    let before = git(&dir, &["rev-parse", "HEAD"])?;
+    integrate(&dir)?;
+    let after = git(&dir, &["rev-parse", "HEAD"])?;
+    if before == after {
+        println!("Already up to date");
+        return Ok(());
+    }
+
+    let changed = git(&dir, &["diff", "--name-only", &before, &after])?;
+    for file in changed.lines() {
+        println!("  {file}");
+    }
+    let global = config::load_global_config()?;
+    kdl::refresh_niri_rules(&global)?;
+    println!("Pulled config changes; run `drift validate --all` to check them");
+    Ok(())
+}
+
+fn repo_dir() -> anyhow::Result<std::path::PathBuf> {
+    let dir = paths::config_dir();
+    if !dir.join(".git").exists() {
+        bail!("Config sync isn't set up; run `drift sync setup <git-remote>` first");
+    }
+    Ok(dir)
+}
+
+/// Commit everything not ignored. Returns whether there was anything to commit.
+fn commit_local(dir: &Path) -> anyhow::Result<bool> {
+    git(dir, &["add", "--all"])?;
+    if git(dir, &["status", "--porcelain"])?.is_empty() {
+        return Ok(false);
+    }
+    let message = format!("drift sync from {}", hostname());
+    git(dir, &["commit", "--quiet", "-m", &message])?;
+    Ok(true)
+}
+
+/// Fetch the remote branch. False when the remote doesn't have it yet.
+fn fetch(dir: &Path) -> anyhow::Result<bool> {
+    git(dir, &["fetch", "--quiet", "origin"])?;
+    Ok(git(dir, &["rev-parse", "--verify", "--quiet", &format!("origin/{BRANCH}")]).is_ok())
+}
+
+/// Base a new repo on the remote's history, keeping local files. Files
+/// only the remote has are checked out; files both have but with
+/// different contents are left for the user to pick.
+fn adopt_remote(dir: &Path) -> anyhow::Result<()> {
+    git(dir, &["reset", "--quiet", &format!("origin/{BRANCH}")])?;
+    for file in git(dir, &["ls-files", "--deleted"])?.lines() {
+        git(dir, &["checkout", "--", file])?;
+    }
+    let differing: Vec<String> = git(dir, &["diff", "--name-only"])?
+        .lines()
+        .filter(|f| *f != ".gitignore")
+        .map(|f| format!("  {f}"))
+        .collect();
+    if !differing.is_empty() {
+        bail!(
+            "These files differ from the copies on the remote:\n{}\nKeep the remote version with `git checkout -- <file>` in {}, or keep yours, then run `drift sync push`",
+            differing.join("\n"),
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Rebase local commits onto the remote branch, refusing up front when
+/// both sides changed the same file rather than leaving a half-applied
+/// rebase in the config directory.
+fn integrate(dir: &Path) -> anyhow::Result<()> {
+    let remote = format!("origin/{BRANCH}");
+    let Ok(base) = git(dir, &["merge-base", "HEAD", &remote]) else {
+        bail!("Local config and {remote} have no shared history; move one of them aside and run setup again");
+    };
+
+    let ours = changed_files(dir, &base, "HEAD")?;
+    let theirs = changed_files(dir, &base, &remote)?;
+    let conflicts: Vec<&String> = ours.intersection(&theirs).collect();
+    if !conflicts.is_empty() {
+        let list = conflicts.iter().map(|f| format!("  {f}")).collect::<Vec<_>>().join("\n");
+        bail!(
+            "These files changed both here and on another machine:\n{list}\nResolve them with git in {}, then run `drift sync push`",
+            dir.display()
+        );
+    }
+    git(dir, &["rebase", "--quiet", &remote]).inspect_err(|_| {
+        let _ = git(dir, &["rebase", "--abort"]);
+    })?;
+    Ok(())
+}
+
+fn changed_files(dir: &Path, from: &str, to: &str) -> anyhow::Result<BTreeSet<String>> {
+    Ok(git(dir, &["diff", "--name-only", from, to])?.lines().map(str::to_string).collect())
+}
+
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed: {}", args.first().unwrap_or(&""), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|h| h.trim().to_string())
+        .unwrap_or_else(|_| "unknown host".into())
+}
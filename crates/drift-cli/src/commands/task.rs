@@ -138,20 +138,14 @@ pub fn run(cmd: TaskCommand) -> anyhow::Result<()> {
             queue.add(task);
             queue.save(&project)?;
 
-            events::try_emit_event(&Event {
-                event_type: "task.queued".into(),
-                project: project.clone(),
-                source: "cli".into(),
-                ts: events::iso_now(),
-                level: Some("info".into()),
-                title: Some("Task queued".into()),
-                body: Some(format!("{task_id}: {}", truncate(&description, 60))),
-                meta: Some(serde_json::json!({
+            events::try_emit_event(&Event::new("task.queued", project.clone(), "cli")
+                .level("info")
+                .title("Task queued")
+                .body(format!("{task_id}: {}", truncate(&description, 60)))
+                .meta(serde_json::json!({
                     "task_id": task_id,
                     "priority": priority,
-                })),
-                priority: None,
-            });
+                })));
 
             println!("{task_id}");
             Ok(())
@@ -242,17 +236,11 @@ pub fn run(cmd: TaskCommand) -> anyhow::Result<()> {
             queue.complete(&task_id, None, None)?;
             queue.save(&project)?;
 
-            events::try_emit_event(&Event {
-                event_type: "task.completed".into(),
-                project: project.clone(),
-                source: "cli".into(),
-                ts: events::iso_now(),
-                level: Some("info".into()),
-                title: Some("Task completed".into()),
-                body: Some(task_id.clone()),
-                meta: Some(serde_json::json!({ "task_id": task_id })),
-                priority: None,
-            });
+            events::try_emit_event(&Event::new("task.completed", project.clone(), "cli")
+                .level("info")
+                .title("Task completed")
+                .body(task_id.clone())
+                .meta(serde_json::json!({ "task_id": task_id })));
 
             println!("Task {task_id} marked as completed");
             Ok(())
@@ -263,17 +251,11 @@ pub fn run(cmd: TaskCommand) -> anyhow::Result<()> {
             queue.fail(&task_id, reason.as_deref())?;
             queue.save(&project)?;
 
-            events::try_emit_event(&Event {
-                event_type: "task.failed".into(),
-                project: project.clone(),
-                source: "cli".into(),
-                ts: events::iso_now(),
-                level: Some("warning".into()),
-                title: Some("Task failed".into()),
-                body: Some(task_id.clone()),
-                meta: Some(serde_json::json!({ "task_id": task_id })),
-                priority: None,
-            });
+            events::try_emit_event(&Event::new("task.failed", project.clone(), "cli")
+                .level("warning")
+                .title("Task failed")
+                .body(task_id.clone())
+                .meta(serde_json::json!({ "task_id": task_id })));
 
             println!("Task {task_id} marked as failed");
             Ok(())
@@ -379,20 +361,14 @@ pub fn run(cmd: TaskCommand) -> anyhow::Result<()> {
                 prev_id = Some(task.id.clone());
                 ids.push(task.id.clone());
 
-                events::try_emit_event(&Event {
-                    event_type: "task.queued".into(),
-                    project: project.clone(),
-                    source: "cli".into(),
-                    ts: events::iso_now(),
-                    level: Some("info".into()),
-                    title: Some("Task queued".into()),
-                    body: Some(format!("{}: {}", task.id, truncate(desc, 60))),
-                    meta: Some(serde_json::json!({
+                events::try_emit_event(&Event::new("task.queued", project.clone(), "cli")
+                    .level("info")
+                    .title("Task queued")
+                    .body(format!("{}: {}", task.id, truncate(desc, 60)))
+                    .meta(serde_json::json!({
                         "task_id": &task.id,
                         "priority": priority,
-                    })),
-                    priority: None,
-                });
+                    })));
 
                 queue.add(task);
             }
@@ -0,0 +1,76 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use drift_core::focus_time;
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Focus time per project from the daemon's ledger: today, or the last
+/// seven days with a column per day.
+pub fn run(project: Option<&str>, week: bool) -> anyhow::Result<()> {
+    let ledger = focus_time::load();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+    let days = focus_time::recent_days(now, if week { 7 } else { 1 });
+    let names: Vec<String> = days.iter().map(|(d, _)| d.clone()).collect();
+
+    let mut totals: Vec<(String, u64)> = ledger
+        .totals(&names)
+        .into_iter()
+        .filter(|(p, _)| project.is_none_or(|name| p == name))
+        .collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let period = if week { "the last 7 days" } else { "today" };
+    if totals.is_empty() {
+        println!("No focus time recorded {period} (tracked by `drift daemon`)");
+        return Ok(());
+    }
+
+    if !week {
+        println!("Today ({})", names[0]);
+        for (name, secs) in &totals {
+            println!("  {name:<20} {:>8}", format_hm(*secs));
+        }
+        if totals.len() > 1 {
+            let sum: u64 = totals.iter().map(|(_, s)| s).sum();
+            println!("  {:<20} {:>8}", "total", format_hm(sum));
+        }
+        return Ok(());
+    }
+
+    let header: String = days.iter().map(|(_, wd)| format!(" {:>7}", WEEKDAYS[*wd as usize])).collect();
+    println!("{:<20}{header} {:>8}", "PROJECT", "TOTAL");
+    for (name, total) in &totals {
+        let cells: String = names
+            .iter()
+            .map(|day| {
+                let secs = ledger.days.get(day).and_then(|d| d.get(name)).copied().unwrap_or(0);
+                format!(" {:>7}", format_hm(secs))
+            })
+            .collect();
+        println!("{name:<20}{cells} {:>8}", format_hm(*total));
+    }
+    Ok(())
+}
+
+/// `2h 05m`, `35m`, or `-` for nothing.
+fn format_hm(secs: u64) -> String {
+    let minutes = secs / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, 0) if secs == 0 => "-".into(),
+        (0, m) => format!("{m}m"),
+        (h, m) => format!("{h}h {m:02}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hours_and_minutes() {
+        assert_eq!(format_hm(0), "-");
+        assert_eq!(format_hm(59), "0m");
+        assert_eq!(format_hm(35 * 60), "35m");
+        assert_eq!(format_hm(2 * 3600 + 5 * 60 + 10), "2h 05m");
+    }
+}
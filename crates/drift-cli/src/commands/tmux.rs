@@ -0,0 +1,42 @@
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+use anyhow::bail;
+use clap::Subcommand;
+use drift_core::{agent, config, tmux};
+
+#[derive(Subcommand)]
+pub enum TmuxCommand {
+    /// Attach to a project's tmux session
+    Attach {
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+        /// Attach to the agent pane session instead of the windows session
+        #[arg(long)]
+        agents: bool,
+    },
+}
+
+pub fn run(command: TmuxCommand) -> anyhow::Result<()> {
+    match command {
+        TmuxCommand::Attach { project, agents } => attach(project.as_deref(), agents),
+    }
+}
+
+fn attach(project: Option<&str>, agents: bool) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let candidates = if agents {
+        vec![agent::tmux_session_name(&project_name)]
+    } else {
+        tmux::project_session_names(&project_name).to_vec()
+    };
+    let Some(session) = candidates.into_iter().find(|s| tmux::session_exists(s)) else {
+        bail!("No tmux session for '{project_name}' (is it open with tmux windows or interactive agents?)");
+    };
+
+    // Inside tmux, attaching would nest; switch the current client instead
+    let verb = if std::env::var_os("TMUX").is_some() { "switch-client" } else { "attach-session" };
+    let err = Command::new("tmux").args([verb, "-t", &format!("={session}")]).exec();
+    bail!("running tmux: {err}")
+}
@@ -1,7 +1,12 @@
-use drift_core::{niri, workspace};
+use drift_core::{compositor, config, timings, workspace};
 
-pub fn run(name: &str) -> anyhow::Result<()> {
-    if let Some(current) = detect_current_project() {
+pub fn run(name: &str, no_save: bool) -> anyhow::Result<()> {
+    let mut timer = timings::Timer::start("to", name);
+    let save = !no_save
+        && config::load_global_config()
+            .map(|g| g.defaults.save_on_switch)
+            .unwrap_or(true);
+    if let Some(current) = detect_current_project().filter(|_| save) {
         match workspace::save_workspace(&current) {
             Ok(()) => {
                 if let Ok(Some(snapshot)) = workspace::load_workspace_snapshot(&current) {
@@ -12,17 +17,22 @@ pub fn run(name: &str) -> anyhow::Result<()> {
         }
     }
 
-    super::open::run(name, None)
+    timer.step("save");
+
+    super::open::run(name, None, false, false, false)?;
+    timer.step("open");
+    timer.finish();
+    Ok(())
 }
 
-fn detect_current_project() -> Option<String> {
+pub(crate) fn detect_current_project() -> Option<String> {
     if let Ok(project) = std::env::var("DRIFT_PROJECT") {
         if !project.is_empty() {
             return Some(project);
         }
     }
 
-    let mut client = niri::NiriClient::connect().ok()?;
+    let mut client = compositor::connect().ok()?;
     let win = client.focused_window().ok()??;
     let ws_id = win.workspace_id?;
     let workspaces = client.workspaces().ok()?;
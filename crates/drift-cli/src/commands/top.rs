@@ -0,0 +1,102 @@
+use std::fs;
+use std::io::Write;
+use std::time::Duration;
+
+use anyhow::bail;
+use drift_core::resources::format_bytes;
+use drift_core::supervisor::{ServiceStatus, ServicesState};
+use drift_core::{paths, registry};
+
+struct Row {
+    project: String,
+    service: String,
+    pid: Option<u32>,
+    cpu_percent: f32,
+    rss_bytes: u64,
+}
+
+/// Live table of per-service CPU/memory across every project, from the
+/// samples supervisors write into services.json.
+pub fn run(sort: &str, interval: u64, once: bool) -> anyhow::Result<()> {
+    if !matches!(sort, "cpu" | "mem") {
+        bail!("Invalid sort key '{sort}' (expected cpu or mem)");
+    }
+
+    loop {
+        let rows = collect_rows(sort)?;
+        let mut out = String::new();
+        if !once {
+            // Clear screen and home the cursor between refreshes
+            out.push_str("\x1b[2J\x1b[H");
+        }
+        out.push_str(&render(&rows));
+        print!("{out}");
+        std::io::stdout().flush()?;
+
+        if once {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_secs(interval.max(1)));
+    }
+}
+
+fn collect_rows(sort: &str) -> anyhow::Result<Vec<Row>> {
+    let mut rows = Vec::new();
+    for project in registry::list_projects()? {
+        let name = project.project.name;
+        let Ok(json) = fs::read_to_string(paths::services_state_path(&name)) else {
+            continue;
+        };
+        let Ok(state) = serde_json::from_str::<ServicesState>(&json) else {
+            continue;
+        };
+        for svc in state.services {
+            if svc.status != ServiceStatus::Running {
+                continue;
+            }
+            rows.push(Row {
+                project: name.clone(),
+                service: svc.name,
+                pid: svc.pid,
+                cpu_percent: svc.cpu_percent.unwrap_or(0.0),
+                rss_bytes: svc.rss_bytes.unwrap_or(0),
+            });
+        }
+    }
+
+    match sort {
+        "mem" => rows.sort_by_key(|r| std::cmp::Reverse(r.rss_bytes)),
+        _ => rows.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent)),
+    }
+    Ok(rows)
+}
+
+fn render(rows: &[Row]) -> String {
+    if rows.is_empty() {
+        return "No running services\n".into();
+    }
+
+    let mut out = format!(
+        "{:<16} {:<16} {:>8} {:>7} {:>8}\n",
+        "PROJECT", "SERVICE", "PID", "CPU%", "MEM"
+    );
+    for row in rows {
+        let pid = row.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into());
+        out.push_str(&format!(
+            "{:<16} {:<16} {:>8} {:>7.1} {:>8}\n",
+            row.project,
+            row.service,
+            pid,
+            row.cpu_percent,
+            format_bytes(row.rss_bytes)
+        ));
+    }
+
+    let cpu: f32 = rows.iter().map(|r| r.cpu_percent).sum();
+    let mem: u64 = rows.iter().map(|r| r.rss_bytes).sum();
+    out.push_str(&format!(
+        "{:<16} {:<16} {:>8} {:>7.1} {:>8}\n",
+        "TOTAL", "", "", cpu, format_bytes(mem)
+    ));
+    out
+}
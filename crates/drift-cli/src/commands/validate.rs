@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use drift_core::config;
+use drift_core::paths;
+use drift_core::validate::{self, Issue, Severity};
+
+pub fn run(project: Option<&str>, all: bool) -> anyhow::Result<()> {
+    let selected = if all {
+        None
+    } else {
+        config::resolve_current_project(project).ok()
+    };
+
+    if let Some(name) = &selected {
+        if !paths::project_config_path(name).exists() {
+            bail!("Project '{name}' not found");
+        }
+    }
+
+    let mut report: BTreeMap<String, Vec<Issue>> = BTreeMap::new();
+
+    let global_path = paths::global_config_path();
+    if selected.is_none() && global_path.exists() {
+        let contents = std::fs::read_to_string(&global_path)?;
+        report.insert(global_path.display().to_string(), validate::validate_global(&contents));
+    }
+
+    // Every project is parsed so port overlaps are caught even when only one is selected
+    let mut ranges = Vec::new();
+    let dir = paths::projects_dir();
+    if dir.exists() {
+        let mut entries: Vec<_> = std::fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let contents = std::fs::read_to_string(&path)?;
+            let (mut issues, parsed) = validate::validate_project(&contents);
+            if let Some(cfg) = parsed {
+                if cfg.project.name != name {
+                    issues.push(Issue {
+                        severity: Severity::Warning,
+                        message: format!("project.name '{}' does not match file name '{name}'", cfg.project.name),
+                    });
+                }
+                if let Some([start, end]) = cfg.ports.as_ref().and_then(|p| p.range) {
+                    ranges.push((name.clone(), [start, end]));
+                }
+            }
+            if selected.as_deref().is_none_or(|s| s == name) {
+                report.insert(name, issues);
+            }
+        }
+    }
+
+    for (a, b) in validate::find_port_overlaps(&ranges) {
+        for (this, other) in [(&a, &b), (&b, &a)] {
+            if let Some(issues) = report.get_mut(this.as_str()) {
+                issues.push(Issue {
+                    severity: Severity::Error,
+                    message: format!("ports.range overlaps with project '{other}'"),
+                });
+            }
+        }
+    }
+
+    if report.is_empty() {
+        println!("No configs to validate");
+        return Ok(());
+    }
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    for (name, issues) in &report {
+        let (e, w) = print_issues(name, issues);
+        errors += e;
+        warnings += w;
+    }
+
+    println!();
+    println!("{} checked, {errors} error(s), {warnings} warning(s)", report.len());
+    if errors > 0 {
+        bail!("validation failed with {errors} error(s)");
+    }
+    Ok(())
+}
+
+/// Print one config's issues under an ok/warn/FAIL line. Returns the
+/// number of errors and warnings.
+pub(crate) fn print_issues(name: &str, issues: &[Issue]) -> (usize, usize) {
+    if issues.is_empty() {
+        println!("ok    {name}");
+        return (0, 0);
+    }
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    println!("{}  {name}", if errors > 0 { "FAIL" } else { "warn" });
+    for issue in issues {
+        match issue.severity {
+            Severity::Error => println!("  error: {}", issue.message),
+            Severity::Warning => println!("  warning: {}", issue.message),
+        }
+    }
+    (errors, issues.len() - errors)
+}
@@ -0,0 +1,93 @@
+use anyhow::bail;
+use clap::Subcommand;
+use drift_core::config::{self, WindowConfig};
+use drift_core::{compositor, env, shell};
+
+#[derive(Subcommand)]
+pub enum WindowCommand {
+    /// Spawn an extra terminal window on the project workspace
+    Spawn {
+        /// Window name (title becomes drift:<project>/<name>)
+        name: String,
+        /// Command to run (empty for shell)
+        command: Option<String>,
+        /// Column width: "30%", "800px" or "0.3"
+        #[arg(long)]
+        width: Option<String>,
+        /// Project name (default: current)
+        #[arg(long)]
+        project: Option<String>,
+        /// Also add the window to the project config
+        #[arg(long)]
+        save: bool,
+    },
+}
+
+pub fn run(command: WindowCommand) -> anyhow::Result<()> {
+    match command {
+        WindowCommand::Spawn { name, command, width, project, save } => {
+            spawn(&name, command, width, project.as_deref(), save)
+        }
+    }
+}
+
+fn spawn(
+    name: &str,
+    command: Option<String>,
+    width: Option<String>,
+    project: Option<&str>,
+    save: bool,
+) -> anyhow::Result<()> {
+    let project_name = config::resolve_current_project(project)?;
+    let mut project_config = config::load_project_config(&project_name)?;
+    let global = config::load_global_config()?;
+
+    let size = match width.as_deref() {
+        Some(w) => match compositor::parse_width(w) {
+            Some(change) => Some(change),
+            None => bail!("Invalid width '{w}' (expected e.g. \"30%\", \"800px\" or \"0.3\")"),
+        },
+        None => None,
+    };
+    if save && project_config.windows.iter().any(|w| w.name.as_deref() == Some(name)) {
+        bail!("Window '{name}' already exists in project '{project_name}'");
+    }
+
+    let mut client = compositor::connect()?;
+    if client.find_workspace_by_name(&project_name)?.is_none() {
+        bail!("Project '{project_name}' is not open. Run 'drift open {project_name}' first.");
+    }
+    let title = shell::window_title(&project_name, Some(name));
+    if client.find_window_by_title(&title)?.is_some() {
+        bail!("Window '{name}' is already open; use 'drift focus {name}'");
+    }
+    client.focus_workspace(&project_name)?;
+
+    let window = WindowConfig {
+        name: Some(name.to_string()),
+        command,
+        width,
+        ..Default::default()
+    };
+    let env_vars = env::build_process_env(&project_config)?;
+    let export_str = env::format_env_exports(&env_vars);
+    let repo_path = config::resolve_repo_path(&project_config.project.repo)?;
+    super::open::spawn_window(
+        &project_name,
+        &global.defaults.terminal,
+        &export_str,
+        &repo_path.to_string_lossy(),
+        &window,
+        &mut *client,
+    )?;
+    if let Some(change) = size {
+        super::open::apply_window_sizes(&mut *client, &[(title, change)], &[]);
+    }
+
+    if save {
+        project_config.windows.push(window);
+        config::save_project_config(&project_name, &project_config)?;
+        println!("Added window '{name}' to project '{project_name}'");
+    }
+    Ok(())
+}
@@ -20,52 +20,143 @@ fn main() -> anyhow::Result<()> {
         Commands::Init { name, repo, folder, template } => {
             commands::init::run(&name, repo.as_deref(), folder.as_deref(), template.as_deref())
         }
-        Commands::List { archived } => commands::list::run(archived),
-        Commands::Open { name, attach } => commands::open::run(&name, attach.as_deref()),
-        Commands::Close { name } => commands::close::run(name.as_deref()),
-        Commands::Archive { name } => commands::archive::archive(&name),
+        Commands::Folder { command } => commands::folder::run(command),
+        Commands::Import { command } => commands::import::run(command),
+        Commands::Secret { command } => commands::secret::run(command),
+        Commands::Sync { command } => commands::sync::run(command),
+        Commands::Setup { command } => commands::setup::run(command),
+        Commands::List { archived, folder, sort, verbose } => commands::list::run(archived, folder.as_deref(), &sort, verbose),
+        Commands::Open { name, attach, strict, dry_run, skip_hooks } => commands::open::run(&name, attach.as_deref(), strict, dry_run, skip_hooks),
+        Commands::Close { name, all, force, dry_run, skip_hooks, keep_services } => {
+            commands::close::run(name.as_deref(), all, force, dry_run, commands::close::CloseOptions { skip_hooks, keep_services })
+        }
+        Commands::Archive { name, force, keep_state } => commands::archive::archive(&name, force, keep_state),
         Commands::Unarchive { name } => commands::archive::unarchive(&name),
-        Commands::Delete { name, yes } => commands::delete::run(&name, yes),
+        Commands::Delete { name, yes, purge } => commands::delete::run(&name, yes, purge),
         Commands::Save { name } => commands::save::run(name.as_deref()),
-        Commands::ShellData => commands::shell_data::run(),
-        Commands::Status => commands::status::run(),
-        Commands::To { name } => commands::to::run(&name),
-        Commands::Env { name } => commands::env::run(name.as_deref()),
-        Commands::Events { r#type, last, all, follow, project } => {
-            commands::events::run(r#type.as_deref(), last, all, follow, project.as_deref())
+        Commands::ShellData { follow } => commands::shell_data::run(follow),
+        Commands::Status { name, all } => {
+            if all {
+                commands::status::run_all()
+            } else {
+                commands::status::run(name.as_deref())
+            }
+        }
+        Commands::Window { command } => commands::window::run(command),
+        Commands::Focus { window, project } => commands::focus::run(&window, project.as_deref()),
+        Commands::To { name, no_save } => commands::to::run(&name, no_save),
+        Commands::Switch { name } => commands::switch::run(&name),
+        Commands::Scratch { project, path } => commands::scratch::run(project.as_deref(), path),
+        Commands::Env { name, command } => match command {
+            Some(command) => commands::env::run_command(command),
+            None => commands::env::run(name.as_deref()),
+        },
+        Commands::Events { prune: true, .. } => commands::events::prune(),
+        Commands::Events { r#type, last, all, follow, project, prune: false, level, source, priority, format, thread, threaded } => {
+            commands::events::EventFilter::new(r#type, level.as_deref(), source.as_deref(), priority.as_deref(), thread)
+                .and_then(|filter| {
+                    commands::events::run(&filter, last, all, follow, project.as_deref(), &format, threaded)
+                })
         }
-        Commands::NiriRules => commands::niri_rules::run(),
-        Commands::Daemon => commands::daemon::run(),
+        Commands::NiriRules { check } => commands::niri_rules::run(check),
+        Commands::Daemon { autostart } => commands::daemon::run(autostart),
         Commands::Logs { service, follow, project } => {
             commands::logs::run(service.as_deref(), follow, project.as_deref())
         }
+        Commands::Journal { project, since, until, last } => {
+            commands::journal::run(project.as_deref(), since.as_deref(), until.as_deref(), last)
+        }
         Commands::Add { command } => commands::add::run(command),
         Commands::Remove { command } => commands::remove::run(command),
-        Commands::Restore { name } => commands::restore::run(name.as_deref()),
-        Commands::Ports { project } => commands::ports::run(project.as_deref()),
-        Commands::Notify { project, r#type, source, level, title, body } => {
-            commands::notify::run(project.as_deref(), &r#type, &source, &level, &title, &body)
+        Commands::Restart { service, project } => {
+            commands::restart::run(service.as_deref(), project.as_deref())
+        }
+        Commands::Reload { project } => commands::restart::reload(project.as_deref()),
+        Commands::Restore { name, deleted: true } => commands::delete::restore(name.as_deref()),
+        Commands::Restore { name, deleted: false } => commands::restore::run(name.as_deref()),
+        Commands::Resume => commands::restore::resume(),
+        Commands::Tmux { command } => commands::tmux::run(command),
+        Commands::Ps => commands::ps::run(),
+        Commands::Top { sort, interval, once } => commands::top::run(&sort, interval, once),
+        Commands::Dash => commands::dash::run(),
+        Commands::Stats { timings, last, op } => commands::stats::run(timings, last, op.as_deref()),
+        Commands::Time { project, week } => commands::time::run(project.as_deref(), week),
+        Commands::Ports { project, command } => match command {
+            Some(command) => commands::ports::run_command(command),
+            None => commands::ports::run(project.as_deref()),
+        },
+        Commands::Notify { print_snippet: Some(language), .. } => commands::notify::print_snippet(&language),
+        Commands::Notify {
+            project,
+            r#type,
+            source,
+            level,
+            title,
+            body,
+            body_option,
+            meta,
+            file,
+            print_snippet: None,
+            wait,
+            correlation_id,
+        } => commands::notify::run(
+            project.as_deref(),
+            &r#type,
+            &source,
+            &level,
+            title.as_deref().unwrap_or_default(),
+            body_option.as_deref().unwrap_or(&body),
+            meta.as_deref(),
+            file.as_deref(),
+            wait,
+            correlation_id,
+        ),
+        Commands::Emit { r#type, title, body, level, source, project, meta, correlation_id } => commands::emit::run(
+            &r#type,
+            title.as_deref(),
+            body.as_deref(),
+            &level,
+            &source,
+            project.as_deref(),
+            meta.as_deref(),
+            correlation_id,
+        ),
+        Commands::EditorContext { project, last } => commands::editor_context::run(project.as_deref(), last),
+        Commands::Hook { command } => commands::hook::run(command),
+        Commands::Doctor { fix } => commands::doctor::run(fix),
+        Commands::Edit { project, global, template } => {
+            commands::edit::run(project.as_deref(), global, template.as_deref())
         }
+        Commands::Validate { project, all } => commands::validate::run(project.as_deref(), all),
         Commands::Commander { command } => match command {
             commands::CommanderCommand::Start => commands::commander::start(),
             commands::CommanderCommand::Stop => commands::commander::stop(),
             commands::CommanderCommand::Status => commands::commander::status(),
             commands::CommanderCommand::Say { text } => commands::commander::say(&text),
-            commands::CommanderCommand::Mute => commands::commander::mute(),
-            commands::CommanderCommand::Unmute => commands::commander::unmute(),
+            commands::CommanderCommand::TestPipeline => commands::commander::test_pipeline(),
+            commands::CommanderCommand::Mute { project, until } => {
+                commands::commander::mute(project.as_deref(), until.as_deref())
+            }
+            commands::CommanderCommand::Unmute { project } => commands::commander::unmute(project.as_deref()),
             commands::CommanderCommand::Setup => commands::commander::setup(),
             commands::CommanderCommand::Train { word } => commands::commander::train(word.as_deref()),
         },
+        Commands::Plugins { command } => commands::plugins::run(command),
         #[cfg(feature = "dispatch")]
         Commands::Task { command } => commands::task::run(command),
         #[cfg(feature = "dispatch")]
         Commands::Review { command } => commands::review::run(command),
         #[cfg(feature = "dispatch")]
         Commands::Dispatch(args) => commands::dispatch::run(args),
-        Commands::Supervisor { project } => {
-            drift_core::supervisor::run_supervisor(&project)
+        Commands::Supervisor { project, simulate } => {
+            let faults = simulate
+                .iter()
+                .map(|spec| drift_core::supervisor::parse_fault(spec))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            drift_core::supervisor::run_supervisor_with_faults(&project, &faults)
         }
         #[cfg(feature = "dispatch")]
         Commands::PostDispatch(args) => commands::post_dispatch::run(args),
+        Commands::External(args) => commands::plugins::exec(args),
     }
 }
@@ -130,6 +130,16 @@ fn init_duplicate_fails() {
     assert!(err.contains("already exists"));
 }
 
+#[test]
+fn init_rejects_names_of_drift_state() {
+    let t = TestEnv::new();
+    for name in ["trash", "events", "spool"] {
+        let err = t.stderr_fail(&["init", name]);
+        assert!(err.contains("reserved for drift's own state"), "{err}");
+    }
+    assert!(!t.project_config_path("trash").exists());
+}
+
 #[test]
 fn init_with_template() {
     let t = TestEnv::new();
@@ -215,6 +225,284 @@ fn init_missing_template_fails() {
     assert!(err.contains("not found"));
 }
 
+#[test]
+fn list_shows_project_icon() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp", "/tmp/myrepo"]);
+    t.run_ok(&["init", "plain", "/tmp/plain"]);
+    let cfg = t.read_config("myapp").replace("name = \"myapp\"", "name = \"myapp\"\nicon = \"🚀\"");
+    std::fs::write(t.project_config_path("myapp"), cfg).unwrap();
+
+    let out = t.stdout(&["list"]);
+    assert!(out.contains("🚀 myapp"));
+    assert!(out.contains("  plain"));
+}
+
+#[test]
+fn list_filters_by_folder_and_shows_services() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "api", "/tmp/api", "--folder", "work"]);
+    t.run_ok(&["init", "notes", "/tmp/notes"]);
+    std::fs::create_dir_all(t.state_dir("api")).unwrap();
+    std::fs::write(
+        t.state_dir("api").join("services.json"),
+        r#"{"supervisor_pid":1,"project":"api","services":[
+  {"name":"web","pid":10,"status":"running","restart_count":0,"started_at":null,"exit_code":null}
+]}"#,
+    )
+    .unwrap();
+
+    let out = t.stdout(&["list", "--folder", "work/"]);
+    assert!(out.contains("work/"));
+    assert!(out.lines().any(|l| l.contains("api") && l.contains("1/1 running")), "{out}");
+    assert!(!out.contains("notes"));
+
+    assert!(t.stdout(&["list", "--sort", "activity"]).contains("notes"));
+    t.run_fail(&["list", "--sort", "size"]);
+}
+
+#[test]
+fn list_verbose_shows_git_branch_and_changes() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(repo.path()).output().unwrap();
+    assert!(git(&["init", "-q", "-b", "trunk"]).status.success());
+    std::fs::write(repo.path().join("notes.txt"), "todo\n").unwrap();
+    t.run_ok(&["init", "api", repo.path().to_str().unwrap()]);
+
+    let out = t.stdout(&["list", "--verbose"]);
+    assert!(out.lines().any(|l| l.contains("api") && l.ends_with("trunk (1 changed)")), "{out}");
+    assert!(!t.stdout(&["list"]).contains("trunk"));
+    assert!(t.stdout(&["status", "api"]).contains("  Git: trunk (1 changed)"));
+}
+
+#[test]
+fn archive_keep_state_round_trips() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp", "/tmp"]);
+    std::fs::create_dir_all(t.state_dir("myapp").join("logs")).unwrap();
+    std::fs::write(t.state_dir("myapp").join("logs/web.log"), "hello\n").unwrap();
+
+    t.run_ok(&["archive", "myapp", "--keep-state"]);
+    assert!(!t.state_dir("myapp").exists());
+    assert!(t.config_dir.path().join("drift/projects/archived/myapp.state.tar.gz").exists());
+    assert!(t.stdout(&["list", "--archived"]).contains("myapp"));
+
+    t.run_ok(&["unarchive", "myapp"]);
+    let log = std::fs::read_to_string(t.state_dir("myapp").join("logs/web.log")).unwrap();
+    assert_eq!(log, "hello\n");
+    assert!(!t.config_dir.path().join("drift/projects/archived/myapp.state.tar.gz").exists());
+}
+
+#[test]
+fn unarchive_leaves_project_archived_when_state_cannot_be_restored() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp", "/tmp"]);
+    std::fs::create_dir_all(t.state_dir("myapp").join("logs")).unwrap();
+    t.run_ok(&["archive", "myapp", "--keep-state"]);
+    std::fs::create_dir_all(t.state_dir("myapp")).unwrap();
+
+    let err = t.stderr_fail(&["unarchive", "myapp"]);
+    assert!(err.contains("already exists"), "stderr: {err}");
+    assert!(!t.project_config_path("myapp").exists());
+    assert!(t.stdout(&["list", "--archived"]).contains("myapp"));
+    assert!(t.config_dir.path().join("drift/projects/archived/myapp.state.tar.gz").exists());
+}
+
+#[test]
+fn delete_moves_to_trash_and_restore_brings_it_back() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "my-app", "/tmp"]);
+    std::fs::create_dir_all(t.state_dir("my-app").join("logs")).unwrap();
+    std::fs::write(t.state_dir("my-app").join("logs/web.log"), "hello\n").unwrap();
+
+    t.run_fail(&["delete", "my-app"]);
+    t.run_ok(&["delete", "my-app", "--yes"]);
+    assert!(!t.project_config_path("my-app").exists());
+    assert!(!t.state_dir("my-app").exists());
+    assert!(t.stdout(&["restore", "--deleted"]).contains("my-app"));
+
+    t.run_ok(&["restore", "--deleted", "my-app"]);
+    assert!(t.project_config_path("my-app").exists());
+    let log = std::fs::read_to_string(t.state_dir("my-app").join("logs/web.log")).unwrap();
+    assert_eq!(log, "hello\n");
+    assert!(t.stdout(&["restore", "--deleted"]).contains("Trash is empty"));
+    t.run_fail(&["restore", "--deleted", "my-app"]);
+
+    t.run_ok(&["delete", "my-app", "--yes", "--purge"]);
+    assert!(t.stdout(&["restore", "--deleted"]).contains("Trash is empty"));
+}
+
+// ── Edit ──
+
+#[test]
+fn edit_validates_after_the_editor_exits() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp", "/tmp"]);
+    // The "editor" appends whatever $DRIFT_TEST_APPEND holds
+    let editor = t.config_dir.path().join("editor.sh");
+    std::fs::write(&editor, "#!/bin/sh\nprintf '%s\\n' \"$DRIFT_TEST_APPEND\" >> \"$1\"\n").unwrap();
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let global = t.config_dir.path().join("drift/config.toml");
+    std::fs::write(&global, format!("[defaults]\neditor = \"{}\"\n", editor.display())).unwrap();
+
+    let out = t.cmd().args(["edit", "--project", "myapp"]).env("DRIFT_TEST_APPEND", "# note").output().unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Saved"));
+
+    let out = t.cmd().args(["edit", "--project", "myapp"]).env("DRIFT_TEST_APPEND", "[bogus").output().unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("invalid TOML"));
+    assert!(t.read_config("myapp").contains("[bogus"));
+}
+
+#[test]
+fn edit_template_writes_only_a_valid_new_template() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let t = TestEnv::new();
+    let editor = t.config_dir.path().join("editor.sh");
+    std::fs::write(&editor, "#!/bin/sh\nprintf '%s\\n' \"$DRIFT_TEST_APPEND\" >> \"$1\"\n").unwrap();
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let global = t.config_dir.path().join("drift/config.toml");
+    std::fs::create_dir_all(global.parent().unwrap()).unwrap();
+    std::fs::write(&global, format!("[defaults]\neditor = \"{}\"\n", editor.display())).unwrap();
+    let templates = t.config_dir.path().join("drift/templates");
+
+    let out = t.cmd().args(["edit", "--template", "../escape"]).output().unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Invalid template name"));
+    assert!(!t.config_dir.path().join("drift/escape.toml").exists());
+
+    let out = t.cmd().args(["edit", "--template", "web"]).env("DRIFT_TEST_APPEND", "[bogus").output().unwrap();
+    assert!(!out.status.success());
+    assert!(std::fs::read_dir(&templates).unwrap().next().is_none());
+
+    let template = "[project]\nname = \"web\"\nrepo = \"/tmp\"";
+    let out = t.cmd().args(["edit", "--template", "web"]).env("DRIFT_TEST_APPEND", template).output().unwrap();
+    assert!(out.status.success());
+    assert!(std::fs::read_to_string(templates.join("web.toml")).unwrap().contains("name = \"web\""));
+}
+
+// ── Sync ──
+
+#[test]
+fn sync_shares_projects_and_stops_on_conflicts() {
+    let remote = TempDir::new().unwrap();
+    let ok = std::process::Command::new("git")
+        .args(["init", "--quiet", "--bare"])
+        .arg(remote.path())
+        .status()
+        .unwrap();
+    assert!(ok.success());
+    let url = remote.path().to_str().unwrap();
+    let run = |t: &TestEnv, args: &[&str]| {
+        t.cmd()
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "drift")
+            .env("GIT_AUTHOR_EMAIL", "drift@localhost")
+            .env("GIT_COMMITTER_NAME", "drift")
+            .env("GIT_COMMITTER_EMAIL", "drift@localhost")
+            .output()
+            .unwrap()
+    };
+
+    let a = TestEnv::new();
+    a.run_ok(&["init", "myapp", "/tmp"]);
+    let store = a.config_dir.path().join("drift/secrets/projects/myapp.age");
+    std::fs::create_dir_all(store.parent().unwrap()).unwrap();
+    std::fs::write(&store, "encrypted").unwrap();
+    assert!(run(&a, &["sync", "setup", url]).status.success());
+
+    let b = TestEnv::new();
+    assert!(run(&b, &["sync", "setup", url]).status.success());
+    assert!(b.project_config_path("myapp").exists());
+    // Encrypted to a's identity: useless on b
+    assert!(!b.config_dir.path().join("drift/secrets").exists());
+
+    let edit = |t: &TestEnv, line: &str| {
+        let cfg = t.read_config("myapp") + line;
+        std::fs::write(t.project_config_path("myapp"), cfg).unwrap();
+    };
+    edit(&b, "# from b\n");
+    assert!(run(&b, &["sync", "push"]).status.success());
+    edit(&a, "# from a\n");
+    let out = run(&a, &["sync", "pull"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("projects/myapp.toml"));
+}
+
+// ── Time ──
+
+#[test]
+fn time_reports_focus_ledger() {
+    let t = TestEnv::new();
+    assert!(t.stdout(&["time"]).contains("No focus time recorded today"));
+
+    let today = std::process::Command::new("date").arg("+%F").output().unwrap();
+    let today = String::from_utf8_lossy(&today.stdout).trim().to_string();
+    std::fs::create_dir_all(t.state_dir.path().join("drift")).unwrap();
+    std::fs::write(
+        t.state_dir.path().join("drift/focus-time.json"),
+        format!(r#"{{"days":{{"{today}":{{"api":7500,"web":600}},"2001-01-01":{{"old":60}}}}}}"#),
+    )
+    .unwrap();
+
+    let out = t.stdout(&["time"]);
+    assert!(out.lines().any(|l| l.contains("api") && l.contains("2h 05m")), "{out}");
+    assert!(out.lines().any(|l| l.contains("total") && l.contains("2h 15m")), "{out}");
+    assert!(!out.contains("old"));
+
+    let out = t.stdout(&["time", "--week", "--project", "web"]);
+    assert!(out.starts_with("PROJECT"));
+    assert!(out.contains("10m") && !out.contains("api"), "{out}");
+}
+
+// ── Import ──
+
+#[test]
+fn import_tmuxinator_by_name() {
+    let t = TestEnv::new();
+    let dir = t.config_dir.path().join("tmuxinator");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("blog.yml"),
+        r#"
+name: blog
+root: /tmp/blog
+on_project_start: docker compose up -d
+windows:
+  - editor: vim
+  - server: npm run dev
+"#,
+    )
+    .unwrap();
+
+    let out = t.stdout(&["import", "tmuxinator", "blog"]);
+    assert!(out.contains("2 windows, 1 services"));
+    let cfg = t.read_config("blog");
+    assert!(cfg.contains("repo = \"/tmp/blog\""));
+    assert!(cfg.contains("command = \"npm run dev\""));
+    assert!(cfg.contains("command = \"docker compose up -d\""));
+
+    // Refuses to overwrite an existing project
+    t.run_fail(&["import", "tmuxinator", "blog"]);
+}
+
+#[test]
+fn import_smug_file_with_name_override() {
+    let t = TestEnv::new();
+    let file = t.config_dir.path().join("api.yml");
+    std::fs::write(&file, "session: api\nroot: /tmp/api\nwindows:\n  - name: code\n    commands: [nvim]\n").unwrap();
+
+    t.run_ok(&["import", "smug", file.to_str().unwrap(), "--name", "backend"]);
+    let cfg = t.read_config("backend");
+    assert!(cfg.contains("name = \"backend\""));
+    assert!(cfg.contains("command = \"nvim\""));
+}
+
 // ── Add service ──
 
 #[test]
@@ -433,6 +721,37 @@ fn remove_nonexistent_env_fails() {
     assert!(err.contains("not found"));
 }
 
+#[test]
+fn remove_env_picks_interactively() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp"]);
+    for key in ["ALPHA", "BETA", "GAMMA"] {
+        t.run_ok(&["add", "env", key, "1", "--project", "myapp"]);
+    }
+
+    let mut child = t
+        .cmd()
+        .args(["remove", "env", "--project", "myapp"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"1 3\n").unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("  2) BETA"));
+    assert!(stdout.contains("Removed env 'ALPHA'"));
+
+    let cfg = t.read_config("myapp");
+    assert!(!cfg.contains("ALPHA"));
+    assert!(cfg.contains("BETA"));
+    assert!(!cfg.contains("GAMMA"));
+}
+
 // ── Remove port ──
 
 #[test]
@@ -545,6 +864,519 @@ fn full_lifecycle() {
     assert!(cfg.contains("range = [") && cfg.contains("3000") && cfg.contains("3010"));
 }
 
+// ── Validate ──
+
+#[test]
+fn validate_clean_project() {
+    let t = TestEnv::new();
+    let repo = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    let out = t.stdout(&["validate", "--all"]);
+    assert!(out.contains("ok    myapp"), "unexpected output: {out}");
+    assert!(out.contains("0 error(s)"));
+}
+
+#[test]
+fn validate_reports_unknown_keys_and_bad_restart() {
+    let t = TestEnv::new();
+    let repo = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    let mut cfg = t.read_config("myapp");
+    cfg.push_str("\n[services]\nprocesses = [{ name = \"api\", command = \"run\", restart = \"sometimes\", retsart = \"always\" }]\n");
+    std::fs::write(t.project_config_path("myapp"), cfg).unwrap();
+
+    let out = t.run_fail(&["validate", "--project", "myapp"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("unknown key 'retsart'"), "unexpected output: {stdout}");
+    assert!(stdout.contains("invalid restart policy 'sometimes'"), "unexpected output: {stdout}");
+}
+
+#[test]
+fn validate_reports_port_overlap_and_missing_repo() {
+    let t = TestEnv::new();
+    let repo = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "a", repo.path().to_str().unwrap()]);
+    t.run_ok(&["init", "b", "/nonexistent/drift-validate"]);
+    t.run_ok(&["add", "port-range", "3000", "3010", "--project", "a"]);
+    t.run_ok(&["add", "port-range", "3005", "3015", "--project", "b"]);
+
+    let out = t.run_fail(&["validate", "--all"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("overlaps with project 'b'"), "unexpected output: {stdout}");
+    assert!(stdout.contains("overlaps with project 'a'"), "unexpected output: {stdout}");
+    assert!(stdout.contains("repo does not exist"), "unexpected output: {stdout}");
+}
+
+// ── Supervisor fault injection ──
+
+#[test]
+fn supervisor_simulated_crash_marks_service_failed() {
+    let t = TestEnv::new();
+    let repo = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    t.run_ok(&["add", "service", "api", "sleep 30", "--project", "myapp"]);
+
+    // restart = never, so the supervisor exits once the crash is observed
+    t.run_ok(&["_supervisor", "myapp", "--simulate", "crash:api:0.2"]);
+
+    let state = std::fs::read_to_string(t.state_dir("myapp").join("services.json")).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&state).unwrap();
+    assert_eq!(parsed["services"][0]["status"], "failed");
+
+    let log = std::fs::read_to_string(t.state_dir("myapp").join("logs").join("api.log")).unwrap();
+    assert!(log.contains("simulated crash"), "log: {log}");
+}
+
+fn wait_for_services_json(t: &TestEnv, project: &str, pred: impl Fn(&serde_json::Value) -> bool) -> serde_json::Value {
+    let path = t.state_dir(project).join("services.json");
+    for _ in 0..100 {
+        if let Ok(json) = std::fs::read_to_string(&path) {
+            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&json) {
+                if pred(&parsed) {
+                    return parsed;
+                }
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    panic!("services.json never reached expected state");
+}
+
+#[test]
+fn supervisor_records_manual_restart_reason() {
+    let t = TestEnv::new();
+    let repo = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    t.run_ok(&["add", "service", "api", "sleep 30", "--project", "myapp"]);
+
+    let mut supervisor = t.cmd().args(["_supervisor", "myapp"]).spawn().unwrap();
+    let state = wait_for_services_json(&t, "myapp", |s| s["services"][0]["status"] == "running");
+    assert_eq!(state["services"][0]["start_reason"], "initial");
+
+    t.run_ok(&["restart", "api", "--project", "myapp"]);
+    let state = wait_for_services_json(&t, "myapp", |s| s["services"][0]["start_reason"] == "manual");
+    assert_eq!(state["services"][0]["restart_count"], 1);
+
+    let _ = Command::new("kill").arg(supervisor.id().to_string()).status();
+    let _ = supervisor.wait();
+}
+
+#[test]
+fn restart_without_supervisor_fails() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp"]);
+    let err = t.stderr_fail(&["restart", "--project", "myapp"]);
+    assert!(err.contains("No supervisor running"), "stderr: {err}");
+}
+
+#[test]
+fn supervisor_rejects_invalid_simulate_spec() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp"]);
+    let err = t.stderr_fail(&["_supervisor", "myapp", "--simulate", "crash:api"]);
+    assert!(err.contains("invalid fault spec"), "stderr: {err}");
+}
+
+// ── Scratchpad ──
+
+#[test]
+fn scratch_path_creates_default_in_state_dir() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp"]);
+    let out = t.stdout(&["scratch", "--project", "myapp", "--path"]);
+    let path = PathBuf::from(out.trim());
+    assert_eq!(path, t.state_dir("myapp").join("scratchpad.md"));
+    assert!(std::fs::read_to_string(&path).unwrap().contains("myapp scratchpad"));
+}
+
+#[test]
+fn scratch_path_uses_configured_file_in_repo() {
+    let t = TestEnv::new();
+    let repo = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    let mut cfg = t.read_config("myapp");
+    cfg.push_str("\n[scratchpad]\nfile = \"notes/todo.md\"\n");
+    std::fs::write(t.project_config_path("myapp"), cfg).unwrap();
+
+    let out = t.stdout(&["scratch", "--project", "myapp", "--path"]);
+    assert_eq!(PathBuf::from(out.trim()), repo.path().join("notes/todo.md"));
+    assert!(repo.path().join("notes/todo.md").exists());
+}
+
+// ── Window spawn ──
+
+#[test]
+fn window_spawn_rejects_invalid_width() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp"]);
+    let err = t.stderr_fail(&["window", "spawn", "logs", "--width", "wide", "--project", "myapp"]);
+    assert!(err.contains("Invalid width 'wide'"));
+}
+
+// ── Resume ──
+
+#[test]
+fn resume_without_session_is_noop() {
+    let t = TestEnv::new();
+    let out = t.stdout(&["resume"]);
+    assert!(out.contains("No session to resume"));
+}
+
+// ── Switch / close --all ──
+
+#[test]
+fn switch_to_unknown_project_fails() {
+    let t = TestEnv::new();
+    t.run_fail(&["switch", "nope"]);
+}
+
+#[test]
+fn close_all_conflicts_with_name() {
+    let t = TestEnv::new();
+    t.run_fail(&["close", "myapp", "--all"]);
+}
+
+// ── Open / close --dry-run ──
+
+#[test]
+fn open_dry_run_prints_plan() {
+    let t = TestEnv::new();
+    let repo_dir = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "myapp", repo_dir.path().to_str().unwrap()]);
+    t.run_ok(&["add", "service", "api", "npm start", "--restart", "on-failure", "--project", "myapp"]);
+    t.run_ok(&["add", "window", "editor", "nvim .", "--project", "myapp"]);
+
+    let out = t.stdout(&["open", "myapp", "--dry-run"]);
+    assert!(out.contains("Workspace: create 'myapp'"));
+    assert!(out.contains("DRIFT_PROJECT=myapp"));
+    assert!(out.contains("service 'api': npm start (restart on-failure)"));
+    assert!(out.contains("'editor': nvim ."));
+    assert!(!t.state_dir("myapp").join("supervisor.pid").exists());
+}
+
+#[test]
+fn printed_environments_mask_secrets() {
+    let t = TestEnv::new();
+    let repo_dir = tempfile::tempdir().unwrap();
+    t.run_ok(&["init", "myapp", repo_dir.path().to_str().unwrap()]);
+    let config = t.read_config("myapp");
+    let env = "[env]\nsecrets = [\"API_KEY\"]\nAPI_URL = \"https://${API_KEY}@api.example.com\"\n";
+    let config = match config.contains("[env]\n") {
+        true => config.replacen("[env]\n", env, 1),
+        false => format!("{config}\n{env}"),
+    };
+    std::fs::write(t.project_config_path("myapp"), config).unwrap();
+
+    // Store a real value when age is available; without it, printing must
+    // still work since nothing is decrypted
+    let have_age = Command::new("age").arg("--version").output().is_ok_and(|o| o.status.success());
+    if have_age {
+        let mut child = t
+            .cmd()
+            .args(["secret", "set", "API_KEY", "--project", "myapp"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        use std::io::Write;
+        child.stdin.take().unwrap().write_all(b"hunter2\n").unwrap();
+        assert!(child.wait().unwrap().success());
+    }
+
+    let dry_run = t.stdout(&["open", "myapp", "--dry-run"]);
+    assert!(dry_run.contains("API_KEY=<secret>"), "{dry_run}");
+    assert!(dry_run.contains("API_URL=https://<secret>@api.example.com"), "{dry_run}");
+    let export = t.stdout(&["env", "export", "--project", "myapp"]);
+    assert!(export.contains("API_KEY=\"<secret>\""), "{export}");
+    assert!(!dry_run.contains("hunter2") && !export.contains("hunter2"));
+
+    let context: serde_json::Value =
+        serde_json::from_str(&t.stdout(&["editor-context", "--project", "myapp"])).unwrap();
+    assert_eq!(context["env"]["API_KEY"], "<secret>");
+    assert_eq!(context["secrets"][0], "API_KEY");
+}
+
+#[test]
+fn close_dry_run_changes_nothing() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp"]);
+    let out = t.stdout(&["close", "myapp", "--dry-run"]);
+    assert!(out.contains("Plan for closing 'myapp'"));
+    assert!(out.contains("No supervisor running"));
+}
+
+#[test]
+fn close_runs_on_close_commands_unless_skipped() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    let cfg = t.read_config("myapp").replacen("[project]", "on_close = [\"echo $DRIFT_PROJECT >> closed.txt\"]\n\n[project]", 1);
+    std::fs::write(t.project_config_path("myapp"), cfg).unwrap();
+
+    let out = t.stdout(&["close", "myapp", "--dry-run"]);
+    assert!(out.contains("Run on_close: echo $DRIFT_PROJECT >> closed.txt"), "{out}");
+    assert!(!t.stdout(&["close", "myapp", "--dry-run", "--skip-hooks"]).contains("on_close"));
+
+    t.run_ok(&["close", "myapp", "--force", "--skip-hooks"]);
+    assert!(!repo.path().join("closed.txt").exists());
+    t.run_ok(&["close", "myapp", "--force"]);
+    assert_eq!(std::fs::read_to_string(repo.path().join("closed.txt")).unwrap(), "myapp\n");
+}
+
+#[test]
+fn close_warns_about_files_open_in_neovim() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    let swap_dir = t.state_dir.path().join("nvim").join("swap");
+    std::fs::create_dir_all(&swap_dir).unwrap();
+    let swap_name = format!("{}%main.rs.swp", repo.path().to_str().unwrap().replace('/', "%"));
+    std::fs::write(swap_dir.join(swap_name), "").unwrap();
+
+    let out = t.stdout(&["close", "myapp", "--dry-run"]);
+    assert!(out.contains("Warning: 1 file open in an editor; close would ask first"), "{out}");
+
+    // Without a terminal it only warns
+    let output = t.run_ok(&["close", "myapp", "--skip-hooks"]);
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Warning: 'myapp' has 1 file open in an editor"), "{err}");
+    assert!(err.contains("open: main.rs"), "{err}");
+}
+
+// ── Stats ──
+
+#[test]
+fn stats_timings_shows_recent_runs() {
+    let t = TestEnv::new();
+    let base = t.state_dir.path().join("drift");
+    std::fs::create_dir_all(&base).unwrap();
+    std::fs::write(
+        base.join("timings.jsonl"),
+        r#"{"op":"close","project":"old","ts":"2026-01-01T10:00:00Z","total_ms":90,"steps":[]}
+{"op":"open","project":"myapp","ts":"2026-01-02T10:00:00Z","total_ms":1500,"steps":[{"name":"workspace","ms":40},{"name":"windows","ms":1410}]}
+"#,
+    )
+    .unwrap();
+
+    let out = t.stdout(&["stats", "--timings", "--last", "1"]);
+    assert!(out.contains("myapp"));
+    assert!(out.contains("1.5s"));
+    assert!(out.contains("workspace 40ms, windows 1.4s"));
+    assert!(!out.contains("old"));
+
+    let out = t.stdout(&["stats", "--timings", "--op", "close"]);
+    assert!(out.contains("old"));
+    assert!(!out.contains("myapp"));
+
+    t.run_fail(&["stats"]);
+}
+
+// ── Event log retention ──
+
+#[test]
+fn events_prune_removes_expired_archives() {
+    let t = TestEnv::new();
+    let dir = t.state_dir.path().join("drift").join("events");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("2000-01-01.jsonl"), "{}\n").unwrap();
+    std::fs::write(dir.join("2999-01-01.jsonl"), "{}\n").unwrap();
+
+    let out = t.stdout(&["events", "--prune"]);
+    assert!(out.contains("Removed 1 event log archive"));
+    assert!(!dir.join("2000-01-01.jsonl").exists());
+    assert!(dir.join("2999-01-01.jsonl").exists());
+}
+
+// ── Editor event stream ──
+
+#[test]
+fn events_rejects_unknown_format() {
+    let t = TestEnv::new();
+    let err = t.stderr_fail(&["events", "--all", "--format", "xml"]);
+    assert!(err.contains("Invalid format 'xml'"));
+}
+
+#[test]
+fn events_filter_by_level_source_and_priority() {
+    let t = TestEnv::new();
+    let state = t.state_dir.path().join("drift");
+    std::fs::create_dir_all(&state).unwrap();
+    let event = |ty: &str, source: &str, level: &str, priority: &str, ts: &str| {
+        serde_json::json!({
+            "type": ty, "project": "myapp", "source": source, "ts": ts,
+            "level": level, "priority": priority,
+        })
+    };
+    let state_json = serde_json::json!({ "recent_events": { "myapp": [
+        event("service.started", "supervisor", "info", "low", "2026-01-01T10:00:00Z"),
+        event("service.crashed", "supervisor", "error", "critical", "2026-01-01T10:01:00Z"),
+        event("build.failed", "ci", "error", "high", "2026-01-01T10:02:00Z"),
+        event("service.restarted", "supervisor", "warning", "medium", "2026-01-01T10:03:00Z"),
+    ]}});
+    std::fs::write(state.join("daemon.json"), state_json.to_string()).unwrap();
+
+    let out = t.stdout(&["events", "--all", "--level", "error,warning", "--source", "supervisor", "--format", "json"]);
+    let types: Vec<String> = out
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["type"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(types, ["service.crashed", "service.restarted"]);
+
+    let out = t.stdout(&["events", "--all", "--priority", "high+"]);
+    assert!(out.contains("service.crashed") && out.contains("build.failed"), "{out}");
+    assert!(!out.contains("service.started") && !out.contains("service.restarted"), "{out}");
+
+    let err = t.stderr_fail(&["events", "--all", "--priority", "urgent"]);
+    assert!(err.contains("Invalid priority 'urgent'"), "{err}");
+}
+
+#[test]
+fn emit_resolves_project_from_working_directory() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    let sub = repo.path().join("src");
+    std::fs::create_dir_all(&sub).unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+
+    // No daemon here: a resolved project fails at delivery, not at lookup
+    let out = t.cmd().args(["emit", "build.failed"]).current_dir(&sub).output().unwrap();
+    let err = String::from_utf8_lossy(&out.stderr);
+    assert!(!out.status.success());
+    assert!(err.contains("sending event"), "{err}");
+
+    let elsewhere = TempDir::new().unwrap();
+    let out = t.cmd().args(["emit", "build.failed"]).current_dir(elsewhere.path()).output().unwrap();
+    assert!(String::from_utf8_lossy(&out.stderr).contains("No drift project contains"));
+}
+
+#[test]
+fn notify_rejects_malformed_event_before_sending() {
+    let t = TestEnv::new();
+    let err = t.stderr_fail(&["notify", "--project", "myapp", "--type", "Build", "done"]);
+    assert!(err.contains("invalid event type 'Build'"), "{err}");
+    let err = t.stderr_fail(&["notify", "--project", "myapp", "--level", "warn", "done"]);
+    assert!(err.contains("invalid level 'warn'"), "{err}");
+}
+
+// ── Git hooks ──
+
+#[test]
+fn hook_install_and_uninstall() {
+    let t = TestEnv::new();
+    let repo_dir = tempfile::tempdir().unwrap();
+    std::process::Command::new("git")
+        .args(["init", repo_dir.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    t.run_ok(&["init", "myapp", repo_dir.path().to_str().unwrap()]);
+
+    let hooks = repo_dir.path().join(".git").join("hooks");
+    std::fs::write(hooks.join("pre-push"), "#!/bin/sh\nexit 0\n").unwrap();
+    let err = t.stderr_fail(&["hook", "install", "--project", "myapp"]);
+    assert!(err.contains("--force"));
+    // Nothing is installed when one hook is in the way
+    assert!(!hooks.join("post-commit").exists());
+
+    t.run_ok(&["hook", "install", "--project", "myapp", "--force"]);
+    let post_commit = std::fs::read_to_string(hooks.join("post-commit")).unwrap();
+    assert!(post_commit.contains("--type git.committed"));
+    assert!(post_commit.contains("--project 'myapp'"));
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(hooks.join("post-commit")).unwrap().permissions().mode();
+    assert_eq!(mode & 0o111, 0o111);
+
+    // Reinstalling over drift's own hooks needs no --force
+    t.run_ok(&["hook", "install", "--project", "myapp"]);
+
+    t.run_ok(&["hook", "uninstall", "--project", "myapp"]);
+    assert!(!hooks.join("post-commit").exists());
+    assert!(!hooks.join("pre-push").exists());
+}
+
+// ── Top ──
+
+#[test]
+fn top_once_sorts_services_by_memory() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp", "/tmp"]);
+    std::fs::create_dir_all(t.state_dir("myapp")).unwrap();
+    std::fs::write(
+        t.state_dir("myapp").join("services.json"),
+        r#"{"supervisor_pid":1,"project":"myapp","services":[
+  {"name":"small","pid":10,"status":"running","restart_count":0,"started_at":null,"exit_code":null,"cpu_percent":50.0,"rss_bytes":1048576},
+  {"name":"big","pid":11,"status":"running","restart_count":0,"started_at":null,"exit_code":null,"cpu_percent":1.0,"rss_bytes":209715200},
+  {"name":"dead","pid":null,"status":"stopped","restart_count":0,"started_at":null,"exit_code":0}
+]}"#,
+    )
+    .unwrap();
+
+    let out = t.stdout(&["top", "--once", "--sort", "mem"]);
+    let big = out.find("big").unwrap();
+    let small = out.find("small").unwrap();
+    assert!(big < small, "expected big before small:\n{out}");
+    assert!(out.contains("200M"));
+    assert!(!out.contains("dead"));
+
+    let out = t.stdout(&["top", "--once"]);
+    assert!(out.find("small").unwrap() < out.find("big").unwrap());
+}
+
+// ── Status ──
+
+#[test]
+fn status_named_and_all() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp", "/tmp"]);
+    t.run_ok(&["init", "notes", "/tmp"]);
+    std::fs::create_dir_all(t.state_dir("myapp")).unwrap();
+    std::fs::write(
+        t.state_dir("myapp").join("services.json"),
+        r#"{"supervisor_pid":1,"project":"myapp","services":[
+  {"name":"api","pid":10,"status":"running","restart_count":0,"started_at":null,"exit_code":null},
+  {"name":"db","pid":null,"status":"failed","restart_count":0,"started_at":null,"exit_code":1}
+]}"#,
+    )
+    .unwrap();
+
+    let out = t.stdout(&["status", "--all"]);
+    let line = |name: &str| out.lines().find(|l| l.starts_with(name)).unwrap().to_string();
+    assert!(line("myapp").contains("1/2 running, 1 failing"), "{out}");
+    assert!(line("notes").contains(" - "), "{out}");
+
+    let out = t.stdout(&["status", "myapp"]);
+    assert!(out.contains("myapp"));
+    assert!(out.contains("api"));
+    t.run_fail(&["status", "nope"]);
+    t.run_fail(&["status", "myapp", "--all"]);
+}
+
+// ── Ps ──
+
+#[test]
+fn ps_checks_recorded_pids() {
+    let t = TestEnv::new();
+    assert!(t.stdout(&["ps"]).contains("No drift processes"));
+
+    t.run_ok(&["init", "myapp", "/tmp"]);
+    std::fs::create_dir_all(t.state_dir("myapp")).unwrap();
+    std::fs::write(t.state_dir("myapp").join("supervisor.pid"), "2147483647").unwrap();
+    let alive = std::process::id();
+    std::fs::write(
+        t.state_dir("myapp").join("services.json"),
+        format!(r#"{{"supervisor_pid":2147483647,"project":"myapp","services":[
+  {{"name":"api","pid":{alive},"status":"running","restart_count":0,"started_at":null,"exit_code":null}},
+  {{"name":"gone","pid":2147483647,"status":"running","restart_count":0,"started_at":null,"exit_code":null}}
+]}}"#),
+    )
+    .unwrap();
+
+    let out = t.stdout(&["ps"]);
+    let line = |name: &str| out.lines().find(|l| l.contains(name)).unwrap().to_string();
+    assert!(line("supervisor").contains("stale"), "{out}");
+    assert!(line("api").contains("orphaned") && line("api").contains(&alive.to_string()), "{out}");
+    assert!(line("gone").contains("dead"), "{out}");
+}
+
 // ── Tmux ──
 
 #[test]
@@ -584,6 +1416,42 @@ fn tmux_config_roundtrip() {
     assert!(cfg.contains("tmux = true"));
 }
 
+#[test]
+fn tmux_attach_without_session_fails() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "drift-test-no-session"]);
+    let err = t.stderr_fail(&["tmux", "attach", "--project", "drift-test-no-session"]);
+    assert!(err.contains("No tmux session"));
+}
+
+#[test]
+fn doctor_runs_without_tmux_sessions() {
+    let t = TestEnv::new();
+    let out = t.stdout(&["doctor"]);
+    assert!(out.contains("problem(s) found"));
+}
+
+#[test]
+fn doctor_checks_and_fixes_git_identity() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(repo.path()).output().unwrap();
+    assert!(git(&["init", "-q"]).status.success());
+    t.run_ok(&["init", "client", repo.path().to_str().unwrap()]);
+    let cfg = t.read_config("client") + "\n[git]\nuser_email = \"me@client.example\"\nsigning_key = \"ABCD1234\"\ncommit_sign = true\n";
+    std::fs::write(t.project_config_path("client"), cfg).unwrap();
+
+    let out = t.stdout(&["doctor"]);
+    assert!(out.contains("warn  git: 'client' identity differs from its config"), "{out}");
+    assert!(out.contains("user.email is unset (expected 'me@client.example')"), "{out}");
+
+    t.run_ok(&["doctor", "--fix"]);
+    let signing = String::from_utf8_lossy(&git(&["config", "--local", "user.signingkey"]).stdout).to_string();
+    assert_eq!(signing.trim(), "ABCD1234");
+    let out = t.stdout(&["doctor"]);
+    assert!(out.contains("ok    git: configured identities match"), "{out}");
+}
+
 // ── Task Queue ──
 
 fn extract_task_id(output: &str) -> String {
@@ -1169,3 +2037,431 @@ fn shell_data_review_queue_includes_needs_review_tasks() {
     assert_eq!(reviews[0]["project"], "myapp");
     assert_eq!(parsed["global"]["total_reviews_pending"], 1);
 }
+
+// ── Plugins ──
+
+#[test]
+fn unknown_command_runs_plugin_from_path() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let env = TestEnv::new();
+    let bin = TempDir::new().unwrap();
+    let plugin = bin.path().join("drift-hello");
+    std::fs::write(&plugin, "#!/bin/sh\necho \"hello $1 $DRIFT_PROJECT\"\necho \"$DRIFT_NOTIFY_SOCK\"\n").unwrap();
+    std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.path().display(), std::env::var("PATH").unwrap_or_default());
+
+    let output = env.cmd().env("PATH", &path).env("DRIFT_PROJECT", "myapp").args(["hello", "world"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("hello world myapp"));
+    assert!(stdout.contains("emit.sock"));
+
+    let output = env.cmd().env("PATH", &path).args(["plugins", "list"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("hello"));
+}
+
+#[test]
+fn unknown_command_without_plugin_fails() {
+    let env = TestEnv::new();
+    let stderr = env.stderr_fail(&["no-such-plugin-xyz"]);
+    assert!(stderr.contains("drift-no-such-plugin-xyz"));
+}
+
+// ── Notify --wait ──
+
+#[test]
+fn notify_wait_fails_without_daemon() {
+    let t = TestEnv::new();
+    let runtime = TempDir::new().unwrap();
+    let out = t
+        .cmd()
+        .env("XDG_RUNTIME_DIR", runtime.path())
+        .args(["notify", "--wait", "--project", "myapp", "done"])
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("sending event to drift daemon"));
+}
+
+// ── Notify body, meta and attachments ──
+
+#[test]
+fn notify_reads_stdin_body_and_attaches_file() {
+    use std::io::{BufRead, Write};
+
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    let runtime = TempDir::new().unwrap();
+    std::fs::create_dir_all(runtime.path().join("drift")).unwrap();
+    let listener = std::os::unix::net::UnixListener::bind(runtime.path().join("drift/emit.sock")).unwrap();
+    let report = t.state_dir.path().join("report.txt");
+    std::fs::write(&report, "test_login failed\n").unwrap();
+
+    let mut child = t
+        .cmd()
+        .env("XDG_RUNTIME_DIR", runtime.path())
+        .args(["notify", "--project", "myapp", "--level", "error", "--body", "-"])
+        .args(["--meta", r#"{"pr":42}"#, "--file", report.to_str().unwrap(), "CI failed"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"3 tests failed\n").unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut line = String::new();
+    std::io::BufReader::new(stream).read_line(&mut line).unwrap();
+    assert!(child.wait().unwrap().success());
+
+    let event: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(event["body"], "3 tests failed");
+    assert_eq!(event["meta"]["pr"], 42);
+    let attachment = PathBuf::from(event["meta"]["attachment"].as_str().unwrap());
+    assert!(attachment.starts_with(t.state_dir("myapp").join("attachments")));
+    assert_eq!(std::fs::read_to_string(attachment).unwrap(), "test_login failed\n");
+
+    // Only registered projects get a directory
+    let err = t.stderr_fail(&["notify", "--project", "../elsewhere", "--file", report.to_str().unwrap(), "CI failed"]);
+    assert!(err.contains("Invalid project name"), "{err}");
+    let err = t.stderr_fail(&["notify", "--project", "ghost", "--file", report.to_str().unwrap(), "CI failed"]);
+    assert!(err.contains("Project 'ghost' not found"), "{err}");
+}
+
+#[test]
+fn notify_truncates_an_over_long_stdin_body() {
+    use std::io::{BufRead, Write};
+
+    let t = TestEnv::new();
+    let runtime = TempDir::new().unwrap();
+    std::fs::create_dir_all(runtime.path().join("drift")).unwrap();
+    let listener = std::os::unix::net::UnixListener::bind(runtime.path().join("drift/emit.sock")).unwrap();
+
+    let mut child = t
+        .cmd()
+        .env("XDG_RUNTIME_DIR", runtime.path())
+        .args(["notify", "--project", "myapp", "--level", "error", "--body", "-", "CI failed"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let log = format!("{}error: test_login failed\n", "compiling...\n".repeat(2_000));
+    child.stdin.take().unwrap().write_all(log.as_bytes()).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut line = String::new();
+    std::io::BufReader::new(stream).read_line(&mut line).unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Body truncated"));
+
+    let event: serde_json::Value = serde_json::from_str(&line).unwrap();
+    let body = event["body"].as_str().unwrap();
+    assert!(body.starts_with("[truncated]"));
+    assert!(body.ends_with("error: test_login failed"));
+}
+
+#[test]
+fn notify_rejects_non_object_meta() {
+    let t = TestEnv::new();
+    let err = t.stderr_fail(&["notify", "--project", "myapp", "--meta", "[1]", "done"]);
+    assert!(err.contains("--meta must be a JSON object"), "{err}");
+    let err = t.stderr_fail(&["notify", "--project", "myapp", "--meta", "{", "done"]);
+    assert!(err.contains("--meta must be valid JSON"), "{err}");
+}
+
+// ── Notify --print-snippet ──
+
+#[test]
+fn notify_prints_client_snippets() {
+    let t = TestEnv::new();
+    let python = t.stdout(&["notify", "--print-snippet", "python"]);
+    assert!(python.contains("def drift_notify("));
+    assert!(python.contains("emit.sock"));
+    assert!(t.stdout(&["notify", "--print-snippet", "node"]).contains("function driftNotify("));
+    assert!(t.stdout(&["notify", "--print-snippet", "bash"]).contains("drift_notify() {"));
+    assert!(t.stderr_fail(&["notify", "--print-snippet", "ruby"]).contains("expected python, node or bash"));
+}
+
+// ── Editor context ──
+
+#[test]
+fn editor_context_resolves_project_from_working_directory() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    t.run_ok(&["add", "port", "api", "3001", "--project", "myapp"]);
+    let daemon = t.state_dir.path().join("drift");
+    std::fs::create_dir_all(&daemon).unwrap();
+    std::fs::write(
+        daemon.join("daemon.json"),
+        r#"{"recent_events":{"myapp":[
+            {"type":"build.failed","project":"myapp","source":"watch","ts":"2026-01-01T00:00:02Z","title":"second"},
+            {"type":"agent.completed","project":"myapp","source":"agent","ts":"2026-01-01T00:00:01Z","title":"first"}
+        ]}}"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(repo.path().join("src")).unwrap();
+
+    let out = t.cmd().current_dir(repo.path().join("src")).args(["editor-context", "--last", "1"]).output().unwrap();
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let context: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(context["project"], "myapp");
+    assert_eq!(context["ports"]["named"]["api"], 3001);
+    assert_eq!(context["env"]["DRIFT_PROJECT"], "myapp");
+    assert!(context["scratchpad"].as_str().unwrap().ends_with("scratchpad.md"));
+    let events = context["recent_events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["title"], "second");
+}
+
+// ── Ports check / free ──
+
+#[test]
+fn ports_check_reports_foreign_listeners() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    let socket = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port().to_string();
+    t.run_ok(&["add", "port", "api", &port, "--project", "myapp"]);
+
+    let out = t.run_fail(&["ports", "check", "--project", "myapp"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains(&format!("api: {port} in use by")), "{stdout}");
+    assert!(stdout.contains(&format!("PID {}", std::process::id())), "{stdout}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("bound by other processes"));
+
+    let err = t.stderr_fail(&["ports", "free", "api", "--project", "myapp"]);
+    assert!(err.contains("pass --yes"), "{err}");
+    let err = t.stderr_fail(&["ports", "free", "db", "--project", "myapp"]);
+    assert!(err.contains("neither a named port of 'myapp' nor a port number"), "{err}");
+    let err = t.stderr_fail(&["ports", "free", "22", "--project", "myapp"]);
+    assert!(err.contains("Port 22 isn't one of 'myapp''s ports"), "{err}");
+
+    drop(socket);
+    assert!(t.stdout(&["ports", "check", "--project", "myapp"]).contains(&format!("api: {port} free")));
+    assert!(t.stdout(&["ports", "free", "api", "--project", "myapp"]).contains("is free"));
+}
+
+// ── Journal ──
+
+#[test]
+fn journal_interleaves_logs_and_events() {
+    let t = TestEnv::new();
+    let logs = t.state_dir("myapp").join("logs");
+    std::fs::create_dir_all(&logs).unwrap();
+    // 2026-01-01T00:00:00Z and 00:10:00Z
+    std::fs::write(logs.join("api.log"), "--- service 'api' started at 1767225600 ---\nlistening\n--- service 'api' started at 1767226200 ---\nlistening again\n").unwrap();
+    std::fs::write(logs.join("supervisor.log"), "supervisor chatter\n").unwrap();
+    let events = t.state_dir.path().join("drift").join("events");
+    std::fs::create_dir_all(&events).unwrap();
+    std::fs::write(
+        events.join("2026-01-01.jsonl"),
+        "{\"type\":\"service.crashed\",\"project\":\"myapp\",\"source\":\"supervisor\",\"ts\":\"2026-01-01T00:05:00Z\",\"title\":\"api crashed\"}\n",
+    )
+    .unwrap();
+
+    let out = t.stdout(&["journal", "myapp"]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 5, "{out}");
+    assert!(lines[1].ends_with("listening"));
+    assert!(lines[2].contains("event") && lines[2].ends_with("service.crashed: api crashed"));
+    assert!(lines[4].ends_with("listening again"));
+    assert!(!out.contains("supervisor chatter"));
+
+    let out = t.stdout(&["journal", "myapp", "--since", "2026-01-01T00:04:00Z"]);
+    assert_eq!(out.lines().count(), 3, "{out}");
+}
+
+// ── Commander mute ──
+
+#[test]
+fn commander_mute_state_is_structured() {
+    let t = TestEnv::new();
+    let state_path = t.state_dir.path().join("drift").join("commander.json");
+
+    t.run_ok(&["commander", "mute", "--project", "api", "--until", "18:00"]);
+    t.run_ok(&["commander", "mute", "--project", "web"]);
+    let state: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&state_path).unwrap()).unwrap();
+    assert!(state["projects"]["api"]["until"].is_i64());
+    assert!(state["projects"]["web"].get("until").is_none());
+    assert!(state.get("all").is_none());
+
+    let status = t.stdout(&["commander", "status"]);
+    assert!(status.contains("Muted project: api until 18:00"), "{status}");
+    assert!(status.contains("Muted project: web\n"), "{status}");
+
+    t.run_ok(&["commander", "unmute", "--project", "api"]);
+    let state = std::fs::read_to_string(&state_path).unwrap();
+    assert!(!state.contains("\"api\"") && state.contains("\"web\""));
+
+    t.run_ok(&["commander", "mute"]);
+    assert!(t.stdout(&["commander", "status"]).contains("Muted: yes\n"));
+    t.run_ok(&["commander", "unmute"]);
+    assert_eq!(std::fs::read_to_string(&state_path).unwrap().trim(), "{}");
+
+    t.run_fail(&["commander", "mute", "--until", "25:00"]);
+}
+
+// ── Niri rules --check ──
+
+#[test]
+fn niri_rules_check_detects_stale_file() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "myapp"]);
+    assert!(t.stdout(&["niri-rules", "--check"]).contains("is up to date"));
+
+    std::fs::write(
+        t.config_dir.path().join("drift").join("niri-rules.kdl"),
+        "// Auto-generated by drift. Do not edit.\n",
+    )
+    .unwrap();
+    let out = t.run_fail(&["niri-rules", "--check"]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("+ workspace \"myapp\""));
+
+    t.run_ok(&["niri-rules"]);
+    t.run_ok(&["niri-rules", "--check"]);
+}
+
+// ── Folders ──
+
+#[test]
+fn folder_init_defaults_and_list() {
+    let t = TestEnv::new();
+    std::fs::create_dir_all(t.config_dir.path().join("drift")).unwrap();
+    std::fs::write(
+        t.config_dir.path().join("drift").join("config.toml"),
+        "[folder.work]\nicon = \"W\"\nport_base = 4000\n",
+    )
+    .unwrap();
+    t.run_ok(&["init", "api", "--folder", "work"]);
+    t.run_ok(&["init", "web", "--folder", "work"]);
+    t.run_ok(&["init", "blog"]);
+
+    assert!(t.read_config("api").contains("icon = \"W\""));
+    let api = t.read_config("api");
+    assert!(api.contains("4000") && api.contains("4009"));
+    assert!(t.read_config("web").contains("4010"));
+    assert!(!t.read_config("blog").contains("range"));
+
+    let out = t.stdout(&["folder", "list"]);
+    assert!(out.contains("work/  (2 projects"));
+    assert!(out.contains("defaults: icon W, ports from 4000"));
+    assert!(!out.contains("blog"));
+}
+
+#[test]
+fn folder_open_unknown_fails() {
+    let t = TestEnv::new();
+    t.run_ok(&["init", "blog"]);
+    assert!(t.stderr_fail(&["folder", "open", "nope"]).contains("No projects in folder 'nope'"));
+}
+
+// ── Secrets ──
+
+#[test]
+fn secret_rejects_bad_names_and_projects() {
+    let t = TestEnv::new();
+    let err = t.stderr_fail(&["secret", "set", "API_KEY", "--project", "nope"]);
+    assert!(err.contains("Project 'nope' not found"));
+
+    let mut child = t
+        .cmd()
+        .args(["secret", "set", "API-KEY"])
+        .stdin(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    use std::io::Write;
+    child.stdin.take().unwrap().write_all(b"hunter2\n").unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("not a valid environment variable name"));
+
+    assert!(t.stdout(&["secret", "list"]).contains("No secrets in global secrets"));
+}
+
+// ── Env import / export ──
+
+#[test]
+fn env_import_and_export() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    std::fs::write(
+        repo.path().join(".envrc"),
+        "use nix\nexport API_URL=http://localhost:8080\nNAME='my app'\n",
+    )
+    .unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+
+    let out = t.stdout(&["env", "import", "--project", "myapp", "--yes"]);
+    assert!(out.contains("+ API_URL = \"http://localhost:8080\""));
+    assert!(out.contains("Imported 2 variable(s)"));
+    let cfg = t.read_config("myapp");
+    assert!(cfg.contains("API_URL = \"http://localhost:8080\""));
+    assert!(cfg.contains("NAME = \"my app\""));
+
+    let fish = t.stdout(&["env", "export", "--format", "fish", "--project", "myapp"]);
+    assert!(fish.contains("set -gx NAME 'my app'"));
+    let dotenv = t.stdout(&["env", "export", "--project", "myapp"]);
+    assert!(dotenv.contains("API_URL=\"http://localhost:8080\""));
+    t.run_fail(&["env", "export", "--format", "yaml", "--project", "myapp"]);
+}
+
+// ── Sync review ──
+
+#[test]
+fn sync_review_accepts_and_rejects_pending_windows() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    assert!(t.stdout(&["sync", "review", "--project", "myapp"]).contains("No pending windows"));
+
+    let pending = r#"{"windows":[
+        {"app_id":"org.mozilla.firefox","title":"Docs","window":{"name":"firefox","app_id":"org.mozilla.firefox"}}
+    ]}"#;
+    std::fs::create_dir_all(t.state_dir("myapp")).unwrap();
+    std::fs::write(t.state_dir("myapp").join("pending_windows.json"), pending).unwrap();
+
+    let out = t.run_fail(&["sync", "review", "--project", "myapp"]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("firefox  org.mozilla.firefox  \"Docs\""));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--accept-all or --reject-all"));
+
+    t.run_ok(&["sync", "review", "--project", "myapp", "--reject-all"]);
+    assert!(!t.read_config("myapp").contains("org.mozilla.firefox"));
+    let state = std::fs::read_to_string(t.state_dir("myapp").join("pending_windows.json")).unwrap();
+    assert!(state.contains("\"rejected\""));
+
+    std::fs::write(t.state_dir("myapp").join("pending_windows.json"), pending).unwrap();
+    assert!(t.stdout(&["sync", "review", "--project", "myapp", "--accept-all"]).contains("Added 1 window(s)"));
+    assert!(t.read_config("myapp").contains("app_id = \"org.mozilla.firefox\""));
+    assert!(!t.state_dir("myapp").join("pending_windows.json").exists());
+}
+
+// ── Setup ──
+
+#[test]
+fn setup_rerun_runs_commands_and_marks_done() {
+    let t = TestEnv::new();
+    let repo = TempDir::new().unwrap();
+    t.run_ok(&["init", "myapp", repo.path().to_str().unwrap()]);
+    t.stderr_fail(&["setup", "rerun", "--project", "myapp"]);
+
+    let mut cfg = t.read_config("myapp");
+    cfg.push_str("\n[setup]\ncommands = [\"echo one >> setup.txt\", \"echo $DRIFT_PROJECT >> setup.txt\"]\n");
+    std::fs::write(t.project_config_path("myapp"), cfg).unwrap();
+
+    let out = t.stdout(&["setup", "rerun", "--project", "myapp"]);
+    assert!(out.contains("Running setup for 'myapp' (2 commands)"));
+    assert_eq!(std::fs::read_to_string(repo.path().join("setup.txt")).unwrap(), "one\nmyapp\n");
+    assert!(t.state_dir("myapp").join("setup-done").exists());
+    let log = std::fs::read_to_string(t.state_dir("myapp").join("logs/setup.log")).unwrap();
+    assert!(log.contains("$ echo one >> setup.txt"));
+
+    let cfg = t.read_config("myapp").replace("echo one >> setup.txt", "exit 3");
+    std::fs::write(t.project_config_path("myapp"), cfg).unwrap();
+    let err = t.stderr_fail(&["setup", "rerun", "--project", "myapp"]);
+    assert!(err.contains("setup command `exit 3` failed"));
+    assert!(!t.state_dir("myapp").join("setup-done").exists());
+}
@@ -0,0 +1,48 @@
+//! One status line per change for a bar or widget (eww `deflisten`, waybar
+//! `exec`): the active project and its last warning or error.
+//!
+//! ```sh
+//! cargo run -p drift-client --example status_line
+//! ```
+
+use drift_client::{DaemonState, Event, Subscription};
+
+fn status_line(active: Option<&str>, last_problem: Option<&Event>) -> String {
+    let project = active.unwrap_or("-");
+    match last_problem {
+        Some(event) => format!("{project}  {}", event.title.as_deref().unwrap_or(&event.event_type)),
+        None => project.to_string(),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut active = drift_client::daemon_state().ok().and_then(|s: DaemonState| s.active_project);
+    let mut last_problem: Option<Event> = None;
+    println!("{}", status_line(active.as_deref(), None));
+
+    let filter = Subscription::default()
+        .event_type("workspace.activated")
+        .event_type("build.*")
+        .event_type("service.*");
+    for event in drift_client::subscribe(&filter)? {
+        let event = event?;
+        match event.event_type.as_str() {
+            "daemon.heartbeat" => continue,
+            "workspace.activated" => active = Some(event.project.clone()),
+            _ if matches!(event.level.as_deref(), Some("warning" | "error")) => last_problem = Some(event),
+            _ => continue,
+        }
+        println!("{}", status_line(active.as_deref(), last_problem.as_ref()));
+    }
+    Ok(())
+}
+
+#[test]
+fn shows_project_and_last_problem() {
+    assert_eq!(status_line(None, None), "-");
+    let event: Event = serde_json::from_str(
+        r#"{"type":"build.failed","project":"web","source":"ci","ts":"","title":"cargo test failed"}"#,
+    )
+    .unwrap();
+    assert_eq!(status_line(Some("web"), Some(&event)), "web  cargo test failed");
+}
@@ -0,0 +1,50 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use crate::{paths, Event};
+
+/// How long `emit_event` waits for the daemon's reply.
+const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Send an event and wait for the daemon's reply, so a rejected event or an
+/// unresponsive daemon is an error. Returns the id the daemon assigned.
+/// An empty `ts` is filled in by the daemon.
+pub fn emit_event(event: &Event) -> anyhow::Result<u64> {
+    let path = paths::emit_socket_path();
+    let mut stream = UnixStream::connect(&path)?;
+    stream.set_read_timeout(Some(ACK_TIMEOUT))?;
+    writeln!(stream, "{}", serde_json::to_string(event)?)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| anyhow::anyhow!("no reply from drift daemon: {e}"))?;
+    parse_ack(&reply)
+}
+
+fn parse_ack(reply: &str) -> anyhow::Result<u64> {
+    if reply.trim().is_empty() {
+        anyhow::bail!("drift daemon closed the connection without a reply (is it up to date?)");
+    }
+    let ack: serde_json::Value = serde_json::from_str(reply)?;
+    if ack["ok"] == true {
+        return ack["id"].as_u64().ok_or_else(|| anyhow::anyhow!("malformed reply: {reply}"));
+    }
+    let error = ack["error"].as_str().unwrap_or("unknown error");
+    anyhow::bail!("drift daemon rejected the event: {error}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ack_replies() {
+        assert_eq!(parse_ack("{\"ok\":true,\"id\":7}\n").unwrap(), 7);
+        let err = parse_ack("{\"ok\":false,\"error\":\"unknown field 'x'\"}").unwrap_err();
+        assert_eq!(err.to_string(), "drift daemon rejected the event: unknown field 'x'");
+        assert!(parse_ack("").unwrap_err().to_string().contains("without a reply"));
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// Version of the event wire format. Events without a `schema` field are
+/// from before versioning and are read as version 1.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Lowest to highest; events without a priority rank as `silent`.
+pub const PRIORITIES: &[&str] = &["silent", "low", "medium", "high", "critical"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub project: String,
+    pub source: String,
+    pub ts: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Value>,
+    /// Shared by related events, e.g. one service run from start to crash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default = "legacy_schema")]
+    pub schema: u32,
+}
+
+fn legacy_schema() -> u32 {
+    1
+}
+
+impl Event {
+    /// An event stamped with the current time and schema version; the
+    /// optional fields are set with the builder methods below.
+    pub fn new(event_type: impl Into<String>, project: impl Into<String>, source: impl Into<String>) -> Self {
+        Event {
+            event_type: event_type.into(),
+            project: project.into(),
+            source: source.into(),
+            ts: OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default(),
+            level: None,
+            title: None,
+            body: None,
+            meta: None,
+            correlation_id: None,
+            priority: None,
+            schema: SCHEMA_VERSION,
+        }
+    }
+
+    pub fn level(mut self, level: impl Into<String>) -> Self {
+        self.level = Some(level.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn meta(mut self, meta: serde_json::Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+}
+
+/// Position of `priority` in [`PRIORITIES`]; unknown values rank as `silent`.
+pub fn priority_rank(priority: &str) -> u8 {
+    PRIORITIES.iter().position(|p| *p == priority).unwrap_or(0) as u8
+}
+
+/// Match an event type against a filter with at most one `*` glob,
+/// e.g. "agent.*" matches "agent.completed".
+pub fn matches_type_filter(event_type: &str, filter: &str) -> bool {
+    if filter.contains('*') {
+        let parts: Vec<&str> = filter.split('*').collect();
+        if parts.len() == 2 {
+            let (prefix, suffix) = (parts[0], parts[1]);
+            event_type.starts_with(prefix) && event_type.ends_with(suffix)
+        } else {
+            event_type == filter
+        }
+    } else {
+        event_type == filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_filter_exact_and_glob() {
+        assert!(matches_type_filter("workspace.activated", "workspace.activated"));
+        assert!(matches_type_filter("workspace.activated", "workspace.*"));
+        assert!(matches_type_filter("agent.completed", "*.completed"));
+        assert!(!matches_type_filter("service.crashed", "workspace.*"));
+        assert!(matches_type_filter("anything", "*"));
+    }
+
+    #[test]
+    fn new_event_is_stamped_and_built_up() {
+        let event = Event::new("build.failed", "web", "ci").level("error").title("cargo test").body("1 failed");
+        assert_eq!(event.schema, SCHEMA_VERSION);
+        assert!(event.ts.ends_with('Z'), "{}", event.ts);
+        assert_eq!(event.level.as_deref(), Some("error"));
+        assert_eq!(event.title.as_deref(), Some("cargo test"));
+        assert_eq!(event.body.as_deref(), Some("1 failed"));
+        assert!(event.meta.is_none() && event.correlation_id.is_none() && event.priority.is_none());
+    }
+
+    #[test]
+    fn priorities_ranked_in_order() {
+        assert!(priority_rank("critical") > priority_rank("high"));
+        assert_eq!(priority_rank("silent"), 0);
+        assert_eq!(priority_rank("whatever"), 0);
+    }
+}
@@ -0,0 +1,22 @@
+//! Talk to a running drift daemon without shelling out to the CLI: emit
+//! events, follow the event stream, and read the daemon's state.
+//!
+//! ```no_run
+//! let filter = drift_client::Subscription::default().project("myapp").event_type("build.*");
+//! for event in drift_client::subscribe(&filter)? {
+//!     let event = event?;
+//!     println!("{} {}", event.event_type, event.title.unwrap_or_default());
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+mod emit;
+mod event;
+pub mod paths;
+pub mod state;
+mod subscribe;
+
+pub use emit::emit_event;
+pub use event::{matches_type_filter, priority_rank, Event, PRIORITIES, SCHEMA_VERSION};
+pub use state::{daemon_state, DaemonState, TrackedWindow};
+pub use subscribe::{subscribe, Events, Subscription};
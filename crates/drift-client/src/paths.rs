@@ -0,0 +1,24 @@
+//! Where the daemon's sockets and state file live.
+
+use std::path::PathBuf;
+
+fn runtime_dir() -> PathBuf {
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".into());
+    PathBuf::from(runtime_dir).join("drift")
+}
+
+/// Events written here, one JSON object per line, go onto the bus.
+pub fn emit_socket_path() -> PathBuf {
+    runtime_dir().join("emit.sock")
+}
+
+/// Every event on the bus, one JSON object per line.
+pub fn subscribe_socket_path() -> PathBuf {
+    runtime_dir().join("subscribe.sock")
+}
+
+/// The daemon's view of workspaces, focus and recent events, rewritten as
+/// it changes. None if the platform has no state directory.
+pub fn daemon_state_path() -> Option<PathBuf> {
+    Some(dirs::state_dir()?.join("drift").join("daemon.json"))
+}
@@ -0,0 +1,362 @@
+//! daemon.json: what the daemon knows about workspaces, focus and recent
+//! events, as read by the CLI, bars and widgets.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{paths, Event};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusState {
+    pub mode: String,
+    pub active_project: Option<String>,
+    pub niri_workspace_id: Option<u64>,
+}
+
+impl Default for FocusState {
+    fn default() -> Self {
+        Self {
+            mode: "overview".into(),
+            active_project: None,
+            niri_workspace_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NiriWorkspace {
+    pub workspace_id: u64,
+    pub idx: u64,
+    pub name: Option<String>,
+    pub is_active: bool,
+    pub is_focused: bool,
+    pub window_count: u32,
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DaemonState {
+    pub pid: u32,
+    pub active_project: Option<String>,
+    pub workspace_projects: Vec<WorkspaceProject>,
+    #[serde(default)]
+    pub all_workspaces: Vec<NiriWorkspace>,
+    pub recent_events: HashMap<String, Vec<Event>>,
+    #[serde(default)]
+    pub focus: FocusState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commander: Option<CommanderState>,
+    #[serde(default)]
+    pub subscribers: SubscriberStats,
+    #[serde(default)]
+    pub tracked_windows: Vec<TrackedWindow>,
+    /// Projects whose services the daemon froze for idling
+    #[serde(default)]
+    pub suspended_projects: Vec<String>,
+}
+
+impl DaemonState {
+    /// The open window the daemon tied to a project's `[[windows]]` entry.
+    pub fn tracked_window(&self, project: &str, name: &str) -> Option<&TrackedWindow> {
+        self.tracked_windows.iter().find(|w| w.project == project && w.name == name)
+    }
+}
+
+/// A window drift spawned, tied to its `[[windows]]` entry by the title (or
+/// browser app_id) it opened with, so the tie survives the app retitling
+/// itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackedWindow {
+    pub window_id: u64,
+    pub project: String,
+    pub name: String,
+    pub pid: Option<i32>,
+}
+
+/// Event stream subscribers and the ones dropped for falling behind.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubscriberStats {
+    pub connected: usize,
+    pub slow_disconnects: u64,
+    pub dropped_events: u64,
+}
+
+/// The commander as supervised by the daemon.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommanderState {
+    /// None while it waits to be restarted, or if the daemon didn't start it
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub last_crash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceProject {
+    pub workspace_id: u64,
+    pub workspace_name: String,
+    pub project: String,
+    pub is_active: bool,
+    pub is_focused: bool,
+    pub window_count: u32,
+}
+
+/// Read the state the daemon last wrote. Errors when the daemon has never
+/// run; check `pid` to tell whether it still is.
+pub fn daemon_state() -> anyhow::Result<DaemonState> {
+    let path = paths::daemon_state_path().ok_or_else(|| anyhow::anyhow!("no state directory"))?;
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("cannot read {} (has the drift daemon run?): {e}", path.display()))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daemon_state_default() {
+        let state = DaemonState::default();
+        assert_eq!(state.pid, 0);
+        assert!(state.active_project.is_none());
+        assert!(state.workspace_projects.is_empty());
+        assert!(state.all_workspaces.is_empty());
+        assert!(state.recent_events.is_empty());
+    }
+
+    #[test]
+    fn daemon_state_roundtrip() {
+        let mut events = HashMap::new();
+        events.insert(
+            "myapp".into(),
+            vec![Event {
+                ts: "2024-01-01T00:00:00Z".into(),
+                ..Event::new("notification", "myapp", "build").level("info").title("Build succeeded")
+            }],
+        );
+
+        let state = DaemonState {
+            pid: 12345,
+            active_project: Some("myapp".into()),
+            workspace_projects: vec![
+                WorkspaceProject {
+                    workspace_id: 1,
+                    workspace_name: "myapp".into(),
+                    project: "myapp".into(),
+                    is_active: true,
+                    is_focused: true,
+                    window_count: 3,
+                },
+                WorkspaceProject {
+                    workspace_id: 2,
+                    workspace_name: "other".into(),
+                    project: "other".into(),
+                    is_active: false,
+                    is_focused: false,
+                    window_count: 1,
+                },
+            ],
+            all_workspaces: vec![
+                NiriWorkspace {
+                    workspace_id: 1,
+                    idx: 1,
+                    name: Some("myapp".into()),
+                    is_active: true,
+                    is_focused: true,
+                    window_count: 3,
+                    project: Some("myapp".into()),
+                },
+                NiriWorkspace {
+                    workspace_id: 2,
+                    idx: 2,
+                    name: Some("other".into()),
+                    is_active: false,
+                    is_focused: false,
+                    window_count: 1,
+                    project: Some("other".into()),
+                },
+                NiriWorkspace {
+                    workspace_id: 3,
+                    idx: 3,
+                    name: Some("browser".into()),
+                    is_active: false,
+                    is_focused: false,
+                    window_count: 2,
+                    project: None,
+                },
+            ],
+            recent_events: events,
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string_pretty(&state).unwrap();
+        let parsed: DaemonState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.pid, 12345);
+        assert_eq!(parsed.active_project.as_deref(), Some("myapp"));
+        assert_eq!(parsed.workspace_projects.len(), 2);
+        assert_eq!(parsed.workspace_projects[0].workspace_id, 1);
+        assert_eq!(parsed.workspace_projects[0].workspace_name, "myapp");
+        assert!(parsed.workspace_projects[0].is_active);
+        assert!(parsed.workspace_projects[0].is_focused);
+        assert_eq!(parsed.workspace_projects[0].window_count, 3);
+        assert_eq!(parsed.workspace_projects[1].workspace_id, 2);
+        assert!(!parsed.workspace_projects[1].is_active);
+        assert!(!parsed.workspace_projects[1].is_focused);
+        assert_eq!(parsed.all_workspaces.len(), 3);
+        let browser_ws = parsed.all_workspaces.iter().find(|ws| ws.name.as_deref() == Some("browser")).unwrap();
+        assert!(browser_ws.project.is_none());
+        assert_eq!(browser_ws.window_count, 2);
+        let evts = parsed.recent_events.get("myapp").unwrap();
+        assert_eq!(evts.len(), 1);
+        assert_eq!(evts[0].title.as_deref(), Some("Build succeeded"));
+    }
+
+    #[test]
+    fn daemon_state_no_active_project() {
+        let state = DaemonState {
+            pid: 999,
+            active_project: None,
+            workspace_projects: vec![],
+            recent_events: HashMap::new(),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed: DaemonState = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.pid, 999);
+        assert!(parsed.active_project.is_none());
+    }
+
+    #[test]
+    fn suspended_projects_survive_a_roundtrip() {
+        let old: DaemonState =
+            serde_json::from_str(r#"{"pid":1,"active_project":null,"workspace_projects":[],"recent_events":{}}"#).unwrap();
+        assert!(old.suspended_projects.is_empty());
+
+        let state = DaemonState { suspended_projects: vec!["idle".into()], ..Default::default() };
+        let parsed: DaemonState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(parsed.suspended_projects, ["idle"]);
+    }
+
+    #[test]
+    fn workspace_project_roundtrip() {
+        let wp = WorkspaceProject {
+            workspace_id: 42,
+            workspace_name: "test-ws".into(),
+            project: "test-proj".into(),
+            is_active: true,
+            is_focused: false,
+            window_count: 5,
+        };
+        let json = serde_json::to_string(&wp).unwrap();
+        let parsed: WorkspaceProject = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.workspace_id, 42);
+        assert_eq!(parsed.workspace_name, "test-ws");
+        assert_eq!(parsed.project, "test-proj");
+        assert!(parsed.is_active);
+        assert!(!parsed.is_focused);
+        assert_eq!(parsed.window_count, 5);
+    }
+
+    #[test]
+    fn all_workspaces_includes_non_project() {
+        let state = DaemonState {
+            pid: 1,
+            all_workspaces: vec![
+                NiriWorkspace {
+                    workspace_id: 10,
+                    idx: 1,
+                    name: Some("myproject".into()),
+                    is_active: true,
+                    is_focused: true,
+                    window_count: 2,
+                    project: Some("myproject".into()),
+                },
+                NiriWorkspace {
+                    workspace_id: 11,
+                    idx: 2,
+                    name: Some("random-ws".into()),
+                    is_active: false,
+                    is_focused: false,
+                    window_count: 5,
+                    project: None,
+                },
+                NiriWorkspace {
+                    workspace_id: 12,
+                    idx: 3,
+                    name: None,
+                    is_active: false,
+                    is_focused: false,
+                    window_count: 0,
+                    project: None,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string_pretty(&state).unwrap();
+        let parsed: DaemonState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.all_workspaces.len(), 3);
+        assert!(parsed.all_workspaces[0].project.is_some());
+        assert!(parsed.all_workspaces[1].project.is_none());
+        assert_eq!(parsed.all_workspaces[1].window_count, 5);
+        assert!(parsed.all_workspaces[2].name.is_none());
+    }
+
+    #[test]
+    fn daemon_state_with_events() {
+        let mut events = HashMap::new();
+        events.insert("proj-a".into(), vec![
+            Event {
+                ts: "2026-01-01T00:00:00Z".into(),
+                priority: Some("low".into()),
+                ..Event::new("build.started", "proj-a", "ci").level("info").title("Build started")
+            },
+            Event {
+                ts: "2026-01-01T00:01:00Z".into(),
+                priority: Some("high".into()),
+                ..Event::new("build.complete", "proj-a", "ci")
+                    .level("success")
+                    .title("Build succeeded")
+                    .body("42 tests passed")
+                    .meta(serde_json::json!({"duration_ms": 5000}))
+            },
+        ]);
+        events.insert("proj-b".into(), vec![
+            Event {
+                ts: "2026-01-01T00:02:00Z".into(),
+                priority: Some("critical".into()),
+                ..Event::new("deploy.failed", "proj-b", "cd")
+                    .level("error")
+                    .title("Deploy failed")
+                    .body("Connection timeout")
+            },
+        ]);
+
+        let state = DaemonState {
+            pid: 54321,
+            active_project: Some("proj-a".into()),
+            workspace_projects: vec![],
+            recent_events: events,
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string_pretty(&state).unwrap();
+        let parsed: DaemonState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.pid, 54321);
+        assert_eq!(parsed.active_project.as_deref(), Some("proj-a"));
+
+        let a_events = parsed.recent_events.get("proj-a").unwrap();
+        assert_eq!(a_events.len(), 2);
+        assert_eq!(a_events[0].event_type, "build.started");
+        assert_eq!(a_events[1].event_type, "build.complete");
+        assert_eq!(a_events[1].meta.as_ref().unwrap()["duration_ms"], 5000);
+
+        let b_events = parsed.recent_events.get("proj-b").unwrap();
+        assert_eq!(b_events.len(), 1);
+        assert_eq!(b_events[0].level.as_deref(), Some("error"));
+        assert_eq!(b_events[0].priority.as_deref(), Some("critical"));
+    }
+}
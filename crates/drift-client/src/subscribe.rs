@@ -0,0 +1,148 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::{matches_type_filter, priority_rank, PRIORITIES};
+use crate::{paths, Event};
+
+/// Which events a subscriber wants, sent as the first line on the
+/// subscribe socket and applied by the daemon. Empty lists match
+/// everything; `daemon.heartbeat` always gets through.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Subscription {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// Event types, each with at most one `*` glob
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_priority: Option<String>,
+}
+
+impl Subscription {
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.projects.push(project.into());
+        self
+    }
+
+    pub fn event_type(mut self, filter: impl Into<String>) -> Self {
+        self.types.push(filter.into());
+        self
+    }
+
+    pub fn min_priority(mut self, priority: impl Into<String>) -> Self {
+        self.min_priority = Some(priority.into());
+        self
+    }
+
+    pub fn parse(line: &str) -> anyhow::Result<Self> {
+        let subscription: Subscription = serde_json::from_str(line)?;
+        if let Some(p) = subscription.min_priority.as_deref() {
+            if !PRIORITIES.contains(&p) {
+                anyhow::bail!("invalid min_priority '{p}' (expected one of {})", PRIORITIES.join(", "));
+            }
+        }
+        Ok(subscription)
+    }
+
+    /// Heartbeats always pass so filtered clients can still tell the
+    /// daemon is alive.
+    pub fn matches(&self, event: &Event) -> bool {
+        if event.event_type == "daemon.heartbeat" {
+            return true;
+        }
+        let min = self.min_priority.as_deref().map_or(0, priority_rank);
+        event.priority.as_deref().map_or(0, priority_rank) >= min
+            && (self.projects.is_empty() || self.projects.contains(&event.project))
+            && (self.types.is_empty() || self.types.iter().any(|t| matches_type_filter(&event.event_type, t)))
+    }
+}
+
+/// Events as the daemon sends them, starting with its replay of recent
+/// ones. Ends when the daemon goes away.
+pub struct Events {
+    reader: BufReader<UnixStream>,
+    line: String,
+}
+
+impl Iterator for Events {
+    type Item = anyhow::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line.clear();
+        match self.reader.read_line(&mut self.line) {
+            Ok(0) => None,
+            Ok(_) => Some(parse_line(&self.line)),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
+
+/// Connect to the subscribe socket and follow the events `filter` matches.
+pub fn subscribe(filter: &Subscription) -> anyhow::Result<Events> {
+    let path = paths::subscribe_socket_path();
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|e| anyhow::anyhow!("cannot connect to {} (is the drift daemon running?): {e}", path.display()))?;
+    // Sent even when empty, so the daemon doesn't wait to see if one comes
+    writeln!(stream, "{}", serde_json::to_string(filter)?)?;
+    Ok(Events { reader: BufReader::new(stream), line: String::new() })
+}
+
+/// An event, or the daemon's `{"ok":false,"error":...}` if it refused the
+/// subscription.
+fn parse_line(line: &str) -> anyhow::Result<Event> {
+    let value: serde_json::Value = serde_json::from_str(line)?;
+    if value["ok"] == false {
+        let error = value["error"].as_str().unwrap_or("unknown error");
+        anyhow::bail!("drift daemon refused the subscription: {error}");
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(project: &str, event_type: &str, priority: Option<&str>) -> Event {
+        Event { priority: priority.map(String::from), ..Event::new(event_type, project, "test") }
+    }
+
+    #[test]
+    fn builder_serializes_only_what_is_set() {
+        let filter = Subscription::default().project("web").event_type("agent.*").min_priority("medium");
+        assert_eq!(
+            serde_json::to_string(&filter).unwrap(),
+            r#"{"projects":["web"],"types":["agent.*"],"min_priority":"medium"}"#
+        );
+        assert_eq!(serde_json::to_string(&Subscription::default()).unwrap(), "{}");
+        assert_eq!(Subscription::parse(&serde_json::to_string(&filter).unwrap()).unwrap(), filter);
+    }
+
+    #[test]
+    fn subscription_filters_project_type_and_priority() {
+        let sub = Subscription::parse(r#"{"projects":["web"],"types":["agent.*"],"min_priority":"medium"}"#).unwrap();
+        assert!(sub.matches(&event("web", "agent.completed", Some("high"))));
+        assert!(!sub.matches(&event("web", "agent.completed", Some("low"))));
+        assert!(!sub.matches(&event("api", "agent.completed", Some("high"))));
+        assert!(!sub.matches(&event("web", "service.crashed", Some("critical"))));
+        assert!(sub.matches(&event("", "daemon.heartbeat", Some("silent"))));
+        assert!(Subscription::default().matches(&event("api", "service.crashed", None)));
+    }
+
+    #[test]
+    fn subscription_rejects_unknown_fields_and_priorities() {
+        assert!(Subscription::parse(r#"{"project":"web"}"#).is_err());
+        let err = Subscription::parse(r#"{"min_priority":"urgent"}"#).unwrap_err();
+        assert!(err.to_string().starts_with("invalid min_priority 'urgent'"));
+    }
+
+    #[test]
+    fn refused_subscription_is_an_error() {
+        let err = parse_line(r#"{"ok":false,"error":"invalid subscription: bad"}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid subscription: bad"));
+        let event = parse_line(r#"{"type":"build.failed","project":"web","source":"ci","ts":""}"#).unwrap();
+        assert_eq!(event.event_type, "build.failed");
+    }
+}
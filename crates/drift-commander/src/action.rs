@@ -1,10 +1,9 @@
-use std::fs;
 use std::process::{Command, Stdio};
 
 use crate::command::VoiceCommand;
 use drift_core::events::{self, Event};
-use drift_core::niri::NiriClient;
-use drift_core::paths;
+use drift_core::mute::{self, Mute, MuteState};
+use drift_core::compositor;
 use drift_core::registry;
 
 pub struct ActionResult {
@@ -109,12 +108,12 @@ fn run_drift(args: &[&str], success_msg: &str) -> ActionResult {
 }
 
 fn switch_to_project(name: &str) -> ActionResult {
-    let mut client = match NiriClient::connect() {
+    let mut client = match compositor::connect() {
         Ok(c) => c,
         Err(e) => {
             return ActionResult {
                 success: false,
-                message: format!("cannot connect to niri: {e}"),
+                message: format!("cannot connect to compositor: {e}"),
             }
         }
     };
@@ -155,17 +154,8 @@ fn close_project(name: Option<&str>) -> ActionResult {
         },
     };
 
-    let event = Event {
-        event_type: "drift.voice.close".into(),
-        project: project_name.clone(),
-        source: "commander".into(),
-        ts: events::iso_now(),
-        level: None,
-        title: Some(format!("voice close request: {project_name}")),
-        body: None,
-        meta: None,
-        priority: None,
-    };
+    let event = Event::new("drift.voice.close", project_name.clone(), "commander")
+        .title(format!("voice close request: {project_name}"));
 
     events::try_emit_event(&event);
 
@@ -176,7 +166,7 @@ fn close_project(name: Option<&str>) -> ActionResult {
 }
 
 fn detect_current_project() -> Option<String> {
-    let mut client = NiriClient::connect().ok()?;
+    let mut client = compositor::connect().ok()?;
     let win = client.focused_window().ok()??;
     let ws_id = win.workspace_id?;
     let workspaces = client.workspaces().ok()?;
@@ -226,11 +216,9 @@ fn list_projects() -> ActionResult {
 }
 
 fn mute() -> ActionResult {
-    let path = paths::commander_muted_path();
-    if let Some(parent) = path.parent() {
-        let _ = fs::create_dir_all(parent);
-    }
-    match fs::write(&path, "") {
+    let mut state = MuteState::load();
+    state.all = Some(Mute { until: None });
+    match state.save(mute::now()) {
         Ok(()) => ActionResult {
             success: true,
             message: "muted".into(),
@@ -243,14 +231,15 @@ fn mute() -> ActionResult {
 }
 
 fn unmute() -> ActionResult {
-    let path = paths::commander_muted_path();
-    if !path.exists() {
+    let now = mute::now();
+    let state = MuteState::load();
+    if !state.all_muted(now) && state.projects.values().all(|m| !m.active(now)) {
         return ActionResult {
             success: true,
             message: "already unmuted".into(),
         };
     }
-    match fs::remove_file(&path) {
+    match MuteState::default().save(now) {
         Ok(()) => ActionResult {
             success: true,
             message: "unmuted".into(),
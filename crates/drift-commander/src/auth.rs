@@ -0,0 +1,69 @@
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context};
+
+/// Where the bearer token for an HTTP endpoint comes from.
+#[derive(Debug, PartialEq)]
+pub enum EndpointAuth<'a> {
+    /// `keyring:<service>/<account>`, looked up in the secret service
+    Keyring { service: &'a str, account: &'a str },
+    /// Anything else is used as the token verbatim
+    Token(&'a str),
+}
+
+pub fn parse_endpoint_auth(spec: &str) -> anyhow::Result<EndpointAuth<'_>> {
+    let Some(rest) = spec.strip_prefix("keyring:") else {
+        return Ok(EndpointAuth::Token(spec));
+    };
+    match rest.split_once('/') {
+        Some((service, account)) if !service.is_empty() && !account.is_empty() => {
+            Ok(EndpointAuth::Keyring { service, account })
+        }
+        _ => bail!("invalid endpoint_auth '{spec}', expected keyring:<service>/<account>"),
+    }
+}
+
+/// Resolve `endpoint_auth` to a bearer token. Keyring entries are read on
+/// every call so rotating the secret doesn't need a commander restart.
+pub fn resolve_bearer(spec: &str) -> anyhow::Result<String> {
+    match parse_endpoint_auth(spec)? {
+        EndpointAuth::Token(token) => Ok(token.to_string()),
+        EndpointAuth::Keyring { service, account } => {
+            let output = Command::new("secret-tool")
+                .args(["lookup", "service", service, "account", account])
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .context("running secret-tool (is libsecret installed?)")?;
+            let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !output.status.success() || token.is_empty() {
+                bail!("no secret found for service={service} account={account}");
+            }
+            Ok(token)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keyring_reference() {
+        assert_eq!(
+            parse_endpoint_auth("keyring:drift/tts").unwrap(),
+            EndpointAuth::Keyring { service: "drift", account: "tts" }
+        );
+    }
+
+    #[test]
+    fn plain_value_is_token() {
+        assert_eq!(parse_endpoint_auth("sk-123").unwrap(), EndpointAuth::Token("sk-123"));
+    }
+
+    #[test]
+    fn rejects_incomplete_keyring_reference() {
+        assert!(parse_endpoint_auth("keyring:drift").is_err());
+        assert!(parse_endpoint_auth("keyring:/tts").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Audio ducking: other streams are turned down while the commander speaks,
+//! through `pactl` (PulseAudio, or PipeWire's pulse server).
+
+use std::process::{Command, Stdio};
+
+/// Streams turned down by `start`, with the per-channel volumes to put back.
+/// Dropping it restores them.
+pub struct Duck {
+    restore: Vec<(u32, Vec<u32>)>,
+}
+
+impl Duck {
+    /// Lower every playing stream by `percent`. Best effort: without pactl
+    /// or a sound server, nothing happens.
+    pub fn start(percent: u8) -> Self {
+        let restore = sink_inputs().unwrap_or_default();
+        for (id, volumes) in &restore {
+            set_volume(*id, &scaled(volumes, percent));
+        }
+        Self { restore }
+    }
+}
+
+impl Drop for Duck {
+    fn drop(&mut self) {
+        for (id, volumes) in &self.restore {
+            set_volume(*id, volumes);
+        }
+    }
+}
+
+fn sink_inputs() -> Option<Vec<(u32, Vec<u32>)>> {
+    let output = Command::new("pactl")
+        .args(["list", "sink-inputs"])
+        .env("LC_ALL", "C")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_sink_inputs(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Stream ids and raw channel volumes from `pactl list sink-inputs`.
+fn parse_sink_inputs(text: &str) -> Vec<(u32, Vec<u32>)> {
+    let mut inputs = Vec::new();
+    let mut current = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(id) = line.strip_prefix("Sink Input #") {
+            current = id.trim().parse().ok();
+        } else if let Some(channels) = line.strip_prefix("Volume:") {
+            let Some(id) = current.take() else { continue };
+            // "front-left: 65536 /  100% / 0.00 dB,   front-right: ..."
+            let volumes: Vec<u32> = channels
+                .split(',')
+                .filter_map(|ch| ch.split(':').nth(1)?.split_whitespace().next()?.parse().ok())
+                .collect();
+            if !volumes.is_empty() {
+                inputs.push((id, volumes));
+            }
+        }
+    }
+    inputs
+}
+
+fn scaled(volumes: &[u32], percent: u8) -> Vec<u32> {
+    let keep = 100 - u64::from(percent.min(100));
+    volumes.iter().map(|v| (u64::from(*v) * keep / 100) as u32).collect()
+}
+
+fn set_volume(id: u32, volumes: &[u32]) {
+    let _ = Command::new("pactl")
+        .arg("set-sink-input-volume")
+        .arg(id.to_string())
+        .args(volumes.iter().map(u32::to_string))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = "\
+Sink Input #42
+\tDriver: protocol-native.c
+\tSink: 1
+\tVolume: front-left: 65536 / 100% / 0.00 dB,   front-right: 32768 /  50% / -18.06 dB
+\t        balance -1.00
+\tProperties:
+\t\tapplication.name = \"Firefox\"
+
+Sink Input #57
+\tVolume: mono: 52429 /  80% / -5.81 dB
+";
+
+    #[test]
+    fn parses_stream_volumes() {
+        assert_eq!(
+            parse_sink_inputs(LISTING),
+            [(42, vec![65536, 32768]), (57, vec![52429])]
+        );
+        assert!(parse_sink_inputs("").is_empty());
+    }
+
+    #[test]
+    fn scales_each_channel() {
+        assert_eq!(scaled(&[65536, 32768], 75), [16384, 8192]);
+        assert_eq!(scaled(&[65536], 0), [65536]);
+        assert_eq!(scaled(&[65536], 150), [0]);
+    }
+}
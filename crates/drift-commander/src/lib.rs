@@ -1,18 +1,19 @@
 pub mod action;
 pub mod audio;
+pub mod auth;
 pub mod command;
 pub mod command_llm;
+pub mod duck;
 pub mod models;
+pub mod pipeline;
 pub mod post_process;
 pub mod stt;
 pub mod vad;
 pub mod voice;
 pub mod wakeword;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::os::unix::net::UnixStream;
-use std::os::unix::process::CommandExt;
-use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
@@ -21,7 +22,8 @@ use std::{fs, io, thread};
 use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
 
 use drift_core::config::{self, CommanderConfig};
-use drift_core::events::Event;
+use drift_core::events::{matches_type_filter, Event};
+use drift_core::mute::{self, MuteState};
 use drift_core::paths;
 
 static SHUTDOWN: AtomicBool = AtomicBool::new(false);
@@ -44,28 +46,67 @@ fn install_signal_handlers() {
 
 // --- Speakable event types ---
 
-const SPEAKABLE_EVENTS: &[&str] = &[
-    "agent.completed",
-    "agent.error",
-    "agent.needs_review",
-    "service.crashed",
-    "build.failed",
-];
+fn should_speak(event: &Event, config: &CommanderConfig) -> bool {
+    config
+        .speak_events
+        .iter()
+        .any(|pattern| matches_type_filter(&event.event_type, pattern))
+}
 
-fn is_speakable_event(event_type: &str) -> bool {
-    SPEAKABLE_EVENTS.contains(&event_type)
+fn is_critical(event: &Event) -> bool {
+    event.priority.as_deref() == Some("critical")
 }
 
-fn should_speak(event: &Event) -> bool {
-    is_speakable_event(&event.event_type)
+// --- Focused project ---
+
+/// The project whose workspace has focus, followed from the daemon's
+/// `workspace.activated` / `workspace.deactivated` events.
+#[derive(Default)]
+struct FocusTracker {
+    active: Option<String>,
 }
 
-fn is_critical(event: &Event) -> bool {
-    event.priority.as_deref() == Some("critical")
+impl FocusTracker {
+    /// Start from the daemon's last saved state, for focus changes that
+    /// happened before we subscribed.
+    fn from_daemon_state() -> Self {
+        #[derive(serde::Deserialize)]
+        struct State {
+            active_project: Option<String>,
+        }
+        let active = fs::read_to_string(paths::daemon_state_path())
+            .ok()
+            .and_then(|json| serde_json::from_str::<State>(&json).ok())
+            .and_then(|state| state.active_project);
+        Self { active }
+    }
+
+    fn observe(&mut self, event: &Event) {
+        match event.event_type.as_str() {
+            "workspace.activated" => self.active = Some(event.project.clone()),
+            "workspace.deactivated" if self.active.as_deref() == Some(&event.project) => {
+                self.active = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn is_focused(&self, project: &str) -> bool {
+        self.active.as_deref() == Some(project)
+    }
 }
 
 // --- Speech rendering ---
 
+/// Used when `speech_templates` has nothing for an event type.
+const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    ("agent.completed", "{project}: agent finished — {title}"),
+    ("agent.error", "{project}: agent error — {title}"),
+    ("agent.needs_review", "{project}: agent needs review — {title}"),
+    ("service.crashed", "{project}: {source} crashed"),
+    ("build.failed", "{project}: build failed — {title}"),
+];
+
 fn title_or_type(event: &Event) -> &str {
     event
         .title
@@ -74,17 +115,32 @@ fn title_or_type(event: &Event) -> &str {
         .unwrap_or(&event.event_type)
 }
 
-fn render_speech(event: &Event) -> String {
-    match event.event_type.as_str() {
-        "agent.completed" => format!("{}: agent finished — {}", event.project, title_or_type(event)),
-        "agent.error" => format!("{}: agent error — {}", event.project, title_or_type(event)),
-        "agent.needs_review" => {
-            format!("{}: agent needs review — {}", event.project, title_or_type(event))
-        }
-        "service.crashed" => format!("{}: {} crashed", event.project, event.source),
-        "build.failed" => format!("{}: build failed — {}", event.project, title_or_type(event)),
-        _ => format!("{}: {}", event.project, title_or_type(event)),
+/// The configured template for an event type: an exact key wins, then the
+/// longest matching glob, then the built-in default.
+fn speech_template<'a>(event_type: &str, templates: &'a HashMap<String, String>) -> &'a str {
+    if let Some(template) = templates.get(event_type) {
+        return template;
     }
+    let glob = templates
+        .iter()
+        .filter(|(pattern, _)| pattern.contains('*') && matches_type_filter(event_type, pattern))
+        .max_by_key(|(pattern, _)| pattern.len());
+    if let Some((_, template)) = glob {
+        return template;
+    }
+    DEFAULT_TEMPLATES
+        .iter()
+        .find(|(t, _)| *t == event_type)
+        .map(|(_, template)| *template)
+        .unwrap_or("{project}: {title}")
+}
+
+fn render_speech(event: &Event, config: &CommanderConfig) -> String {
+    speech_template(&event.event_type, &config.speech_templates)
+        .replace("{project}", &event.project)
+        .replace("{title}", title_or_type(event))
+        .replace("{source}", &event.source)
+        .replace("{type}", &event.event_type)
 }
 
 // --- Cooldown batching ---
@@ -173,7 +229,48 @@ enum TtsEngine {
 pub(crate) struct SpeechMessage {
     pub(crate) text: String,
     pub(crate) instruct: String,
-    pub(crate) _critical: bool,
+    pub(crate) critical: bool,
+}
+
+/// Announcements waiting to be spoken: critical ones first, each group in
+/// arrival order, never more than `max` of them.
+struct SpeechQueue {
+    items: VecDeque<SpeechMessage>,
+    max: usize,
+}
+
+impl SpeechQueue {
+    fn new(max: usize) -> Self {
+        Self {
+            items: VecDeque::new(),
+            max: max.max(1),
+        }
+    }
+
+    fn push(&mut self, msg: SpeechMessage) {
+        if msg.critical {
+            let at = self.items.iter().take_while(|m| m.critical).count();
+            self.items.insert(at, msg);
+        } else {
+            self.items.push_back(msg);
+        }
+        // Over the limit, the oldest of the lowest priority goes first: a
+        // stale routine announcement is worth less than a fresh one.
+        while self.items.len() > self.max {
+            let at = self.items.iter().position(|m| !m.critical).unwrap_or(0);
+            if let Some(dropped) = self.items.remove(at) {
+                eprintln!("commander: queue full, dropping \"{}\"", dropped.text);
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<SpeechMessage> {
+        self.items.pop_front()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
 }
 
 struct TtsState {
@@ -184,7 +281,7 @@ struct TtsState {
 
 impl TtsState {
     fn new(config: CommanderConfig) -> Self {
-        let engine = if check_http_tts(&config.endpoint) {
+        let engine = if check_http_tts(&config) {
             eprintln!("commander: using HTTP TTS at {}", config.endpoint);
             TtsEngine::Http
         } else if config.fallback_engine.is_some() || config.fallback_command.is_some() {
@@ -208,27 +305,28 @@ impl TtsState {
         }
         if self.last_http_check.elapsed() >= Duration::from_secs(60) {
             self.last_http_check = Instant::now();
-            if check_http_tts(&self.config.endpoint) {
+            if check_http_tts(&self.config) {
                 eprintln!("commander: HTTP TTS recovered");
                 self.engine = TtsEngine::Http;
             }
         }
     }
 
-    fn speak(&mut self, msg: &SpeechMessage) {
+    /// Speak `msg`, stopping early once `interrupted` returns true.
+    fn speak(&mut self, msg: &SpeechMessage, interrupted: &mut dyn FnMut() -> bool) {
         self.maybe_recheck_http();
 
         match self.engine {
             TtsEngine::Http => {
-                if let Err(e) = speak_http(&self.config, &msg.text, &msg.instruct) {
+                if let Err(e) = speak_http(&self.config, &msg.text, &msg.instruct, interrupted) {
                     eprintln!("commander: HTTP TTS failed: {e}, trying fallback");
                     self.engine = TtsEngine::Fallback;
                     self.last_http_check = Instant::now();
-                    let _ = speak_fallback(&self.config, &msg.text);
+                    let _ = speak_fallback(&self.config, &msg.text, interrupted);
                 }
             }
             TtsEngine::Fallback => {
-                if let Err(e) = speak_fallback(&self.config, &msg.text) {
+                if let Err(e) = speak_fallback(&self.config, &msg.text, interrupted) {
                     eprintln!("commander: fallback TTS failed: {e}");
                 }
             }
@@ -244,8 +342,8 @@ pub(crate) fn make_agent(timeout_secs: u64) -> ureq::Agent {
         .new_agent()
 }
 
-fn check_http_tts(endpoint: &str) -> bool {
-    let url = format!("{endpoint}/v1/audio/speech");
+fn check_http_tts(config: &CommanderConfig) -> bool {
+    let url = format!("{}/v1/audio/speech", config.endpoint);
     let body = serde_json::json!({
         "model": "qwen3-tts",
         "voice": "Vivian",
@@ -253,14 +351,28 @@ fn check_http_tts(endpoint: &str) -> bool {
         "response_format": "wav",
     });
 
-    let agent = make_agent(5);
-    match agent.post(&url).send_json(&body) {
+    let mut req = make_agent(5).post(&url);
+    if let Some(spec) = &config.endpoint_auth {
+        match auth::resolve_bearer(spec) {
+            Ok(token) => req = req.header("Authorization", format!("Bearer {token}")),
+            Err(e) => {
+                eprintln!("commander: endpoint_auth: {e}");
+                return false;
+            }
+        }
+    }
+    match req.send_json(&body) {
         Ok(resp) => resp.status() == 200,
         Err(_) => false,
     }
 }
 
-fn speak_http(config: &CommanderConfig, text: &str, instruct: &str) -> anyhow::Result<()> {
+fn speak_http(
+    config: &CommanderConfig,
+    text: &str,
+    instruct: &str,
+    interrupted: &mut dyn FnMut() -> bool,
+) -> anyhow::Result<()> {
     let url = format!("{}/v1/audio/speech", config.endpoint);
     let mut body = serde_json::json!({
         "model": "qwen3-tts",
@@ -272,121 +384,81 @@ fn speak_http(config: &CommanderConfig, text: &str, instruct: &str) -> anyhow::R
         body["instruct"] = serde_json::Value::String(instruct.to_string());
     }
 
-    let agent = make_agent(30);
-    let mut resp = agent.post(&url).send_json(&body)?;
+    let mut req = make_agent(30).post(&url);
+    if let Some(spec) = &config.endpoint_auth {
+        let token = auth::resolve_bearer(spec)?;
+        req = req.header("Authorization", format!("Bearer {token}"));
+    }
+    let mut resp = req.send_json(&body)?;
 
     if resp.status() != 200 {
         anyhow::bail!("HTTP TTS returned status {}", resp.status());
     }
 
     let audio_data = resp.body_mut().read_to_vec()?;
-
-    play_audio(&audio_data, config.audio_filter.as_deref())
-}
-
-fn speak_fallback(config: &CommanderConfig, text: &str) -> anyhow::Result<()> {
-    let cmd = if let Some(custom) = &config.fallback_command {
-        custom.replace("{text}", text)
-    } else {
-        match config.fallback_engine.as_deref() {
-            Some("piper") => {
-                let voice = config
-                    .fallback_voice
-                    .as_deref()
-                    .unwrap_or("en_US-lessac-medium");
-                if let Some(filter) = &config.audio_filter {
-                    format!(
-                        "echo {} | piper --model {} --output-raw | {} | aplay -r 22050 -f S16_LE",
-                        shell_escape(text),
-                        shell_escape(voice),
-                        filter,
-                    )
-                } else {
-                    format!(
-                        "echo {} | piper --model {} --output-raw | aplay -r 22050 -f S16_LE",
-                        shell_escape(text),
-                        shell_escape(voice),
-                    )
-                }
-            }
-            Some("espeak-ng") | Some("espeak") => {
-                format!("echo {} | espeak-ng", shell_escape(text))
-            }
-            _ => {
-                anyhow::bail!("no fallback engine configured");
-            }
-        }
-    };
-
-    let status = unsafe {
-        Command::new("sh")
-            .args(["-c", &cmd])
-            .stdin(Stdio::null())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .pre_exec(|| {
-                libc::setsid();
-                Ok(())
-            })
-            .status()?
-    };
-
-    if !status.success() {
-        anyhow::bail!("fallback TTS exited with {status}");
+    if interrupted() {
+        return Ok(());
     }
-    Ok(())
-}
 
-fn play_audio(data: &[u8], audio_filter: Option<&str>) -> anyhow::Result<()> {
-    let cmd = if let Some(filter) = audio_filter {
-        format!("{filter} | aplay -r 22050 -f S16_LE")
-    } else {
-        "aplay -r 22050 -f S16_LE".into()
-    };
-
-    let mut child = unsafe {
-        Command::new("sh")
-            .args(["-c", &cmd])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .pre_exec(|| {
-                libc::setsid();
-                Ok(())
-            })
-            .spawn()?
-    };
-
-    if let Some(stdin) = child.stdin.as_mut() {
-        use std::io::Write;
-        let _ = stdin.write_all(data);
-    }
-    drop(child.stdin.take());
-    child.wait()?;
-    Ok(())
+    pipeline::run(&pipeline::playback(config.audio_filter.as_deref())?, audio_data, interrupted)?.check()
 }
 
-fn shell_escape(s: &str) -> String {
-    format!("'{}'", s.replace('\'', "'\\''"))
+fn speak_fallback(
+    config: &CommanderConfig,
+    text: &str,
+    interrupted: &mut dyn FnMut() -> bool,
+) -> anyhow::Result<()> {
+    let (stages, input) = pipeline::fallback(config, text)?;
+    pipeline::run(&stages, input, interrupted)?.check()
 }
 
 // --- Speech worker thread ---
 
-fn speech_worker(
-    rx: mpsc::Receiver<SpeechMessage>,
-    interrupt: &AtomicBool,
-    config: CommanderConfig,
-) {
+fn speech_worker(rx: mpsc::Receiver<SpeechMessage>, config: CommanderConfig) {
+    let mut queue = SpeechQueue::new(config.max_queue);
+    let duck_percent = config.duck_percent;
     let mut tts = TtsState::new(config);
+    // Held from the first announcement until the queue runs dry, so other
+    // audio doesn't bounce back up between back-to-back messages.
+    let mut duck: Option<duck::Duck> = None;
 
     while !SHUTDOWN.load(Ordering::Relaxed) {
-        match rx.recv_timeout(Duration::from_millis(500)) {
-            Ok(msg) => {
-                interrupt.store(false, Ordering::Relaxed);
-                tts.speak(&msg);
+        if queue.is_empty() {
+            match rx.recv_timeout(Duration::from_millis(500)) {
+                Ok(msg) => queue.push(msg),
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        while let Ok(msg) = rx.try_recv() {
+            queue.push(msg);
+        }
+        let Some(msg) = queue.pop() else { continue };
+
+        // Messages arriving mid-speech are queued; a critical one cuts off
+        // anything that isn't critical itself.
+        let speaking_critical = msg.critical;
+        let mut interrupted = || {
+            let mut urgent = false;
+            while let Ok(next) = rx.try_recv() {
+                urgent |= next.critical;
+                queue.push(next);
+            }
+            if urgent && !speaking_critical {
+                eprintln!("commander: interrupted by a critical announcement");
+                return true;
             }
-            Err(mpsc::RecvTimeoutError::Timeout) => {}
-            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            false
+        };
+        if duck.is_none() && duck_percent > 0 && tts.engine != TtsEngine::None {
+            duck = Some(duck::Duck::start(duck_percent));
+        }
+        tts.speak(&msg, &mut interrupted);
+        while let Ok(next) = rx.try_recv() {
+            queue.push(next);
+        }
+        if queue.is_empty() {
+            duck = None;
         }
     }
 }
@@ -410,14 +482,11 @@ pub fn run_commander() -> anyhow::Result<()> {
 
     // Speech worker channel
     let (speech_tx, speech_rx) = mpsc::channel::<SpeechMessage>();
-    let interrupt = std::sync::Arc::new(AtomicBool::new(false));
-
-    let worker_interrupt = std::sync::Arc::clone(&interrupt);
     let worker_config = commander_config.clone();
 
     let speech_thread = thread::Builder::new()
         .name("speech-worker".into())
-        .spawn(move || speech_worker(speech_rx, &worker_interrupt, worker_config))?;
+        .spawn(move || speech_worker(speech_rx, worker_config))?;
 
     // Voice control thread (if enabled)
     let voice_config = commander_config.clone();
@@ -464,26 +533,38 @@ pub fn run_commander() -> anyhow::Result<()> {
 
         let mut reader = io::BufReader::new(stream);
         let mut line_buf = String::new();
+        let mut focus = FocusTracker::from_daemon_state();
 
         loop {
             if SHUTDOWN.load(Ordering::Relaxed) {
                 break 'outer;
             }
 
-            // Check muted
-            if paths::commander_muted_path().exists() {
+            // Muted or in quiet hours: keep following focus, but say nothing
+            let now = mute::now();
+            let mutes = MuteState::load();
+            let project_muted = |project: &str| {
+                mutes.is_muted(project, now) || commander_config.muted_projects.iter().any(|p| p == project)
+            };
+            if mutes.all_muted(now) || mute::in_quiet_hours(&commander_config.quiet_hours, mute::local_minute(now)) {
                 line_buf.clear();
                 let _ = io::BufRead::read_line(&mut reader, &mut line_buf);
+                if let Ok(event) = serde_json::from_str::<Event>(line_buf.trim()) {
+                    focus.observe(&event);
+                }
                 continue;
             }
 
             // Flush expired cooldowns
             for (project, event_type, count) in cooldown.flush_expired() {
+                if project_muted(&project) {
+                    continue;
+                }
                 let batch_text = format!("{project}: {count} more {event_type} events");
                 let _ = speech_tx.send(SpeechMessage {
                     text: batch_text,
                     instruct: commander_config.instruct.clone(),
-                    _critical: false,
+                    critical: false,
                 });
             }
 
@@ -508,7 +589,15 @@ pub fn run_commander() -> anyhow::Result<()> {
                         }
                     };
 
-                    if !should_speak(&event) {
+                    focus.observe(&event);
+
+                    if !should_speak(&event, &commander_config) {
+                        continue;
+                    }
+                    if commander_config.speak_background_only && focus.is_focused(&event.project) {
+                        continue;
+                    }
+                    if project_muted(&event.project) {
                         continue;
                     }
 
@@ -525,27 +614,23 @@ pub fn run_commander() -> anyhow::Result<()> {
                             let _ = speech_tx.send(SpeechMessage {
                                 text: batch_text,
                                 instruct: commander_config.instruct.clone(),
-                                _critical: false,
+                                critical: false,
                             });
                         }
                         CooldownAction::Speak => {}
                     }
 
-                    let text = render_speech(&event);
+                    let text = render_speech(&event, &commander_config);
                     let instruct = commander_config
                         .event_instructs
                         .get(&event.event_type)
                         .cloned()
                         .unwrap_or_else(|| commander_config.instruct.clone());
 
-                    if critical {
-                        interrupt.store(true, Ordering::Relaxed);
-                    }
-
                     let _ = speech_tx.send(SpeechMessage {
                         text,
                         instruct,
-                        _critical: critical,
+                        critical,
                     });
                 }
                 Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
@@ -578,11 +663,71 @@ pub fn say_text(text: &str) -> anyhow::Result<()> {
     let config = global_config.commander;
 
     let mut tts = TtsState::new(config.clone());
-    tts.speak(&SpeechMessage {
+    let msg = SpeechMessage {
         text: text.to_string(),
         instruct: config.instruct.clone(),
-        _critical: false,
-    });
+        critical: false,
+    };
+    let _duck = (config.duck_percent > 0).then(|| duck::Duck::start(config.duck_percent));
+    tts.speak(&msg, &mut || false);
+    Ok(())
+}
+
+/// Diagnose the TTS setup: whether the HTTP endpoint answers, whether each
+/// stage of the fallback pipeline is installed, and how each stage fares
+/// speaking a test sentence.
+pub fn test_pipeline() -> anyhow::Result<()> {
+    let config = config::load_global_config().unwrap_or_default().commander;
+
+    if check_http_tts(&config) {
+        println!("[ok] HTTP TTS at {}", config.endpoint);
+    } else {
+        println!("[unavailable] HTTP TTS at {}", config.endpoint);
+    }
+    let playback = pipeline::playback(config.audio_filter.as_deref())?;
+    let names: Vec<_> = playback.iter().map(pipeline::Stage::name).collect();
+    println!("  playback: {}", names.join(" | "));
+
+    let (stages, input) = match pipeline::fallback(&config, "drift pipeline test") {
+        Ok(fallback) => fallback,
+        Err(e) => {
+            println!("[unavailable] fallback: {e}");
+            return Ok(());
+        }
+    };
+    let mut missing = false;
+    for stage in stages.iter().chain(&playback) {
+        match stage.resolve() {
+            Some(path) => println!("[ok] {} ({})", stage.name(), path.display()),
+            None => {
+                println!("[missing] {}", stage.name());
+                missing = true;
+            }
+        }
+    }
+    if missing {
+        anyhow::bail!("some pipeline programs are not installed");
+    }
+
+    println!("Speaking a test sentence with the fallback pipeline...");
+    let pipeline::Finished::Completed(outcomes) = pipeline::run(&stages, input, &mut || false)? else {
+        anyhow::bail!("interrupted");
+    };
+    let mut failed = false;
+    for outcome in &outcomes {
+        if outcome.status.success() {
+            println!("[ok] {}", outcome.name);
+        } else {
+            println!("[failed] {} ({})", outcome.name, outcome.status);
+            for line in outcome.stderr.lines().filter(|l| !l.trim().is_empty()) {
+                println!("  {line}");
+            }
+            failed = true;
+        }
+    }
+    if failed {
+        anyhow::bail!("fallback pipeline failed");
+    }
     Ok(())
 }
 
@@ -592,80 +737,73 @@ mod tests {
 
     #[test]
     fn render_speech_agent_completed() {
-        let event = Event {
-            event_type: "agent.completed".into(),
-            project: "myapp".into(),
-            source: "claude".into(),
-            ts: String::new(),
-            level: None,
-            title: Some("Implemented auth".into()),
-            body: None,
-            meta: None,
-            priority: None,
-        };
-        assert_eq!(render_speech(&event), "myapp: agent finished — Implemented auth");
+        let event = Event::new("agent.completed", "myapp", "claude").title("Implemented auth");
+        assert_eq!(render_speech(&event, &CommanderConfig::default()), "myapp: agent finished — Implemented auth");
     }
 
     #[test]
     fn render_speech_service_crashed() {
-        let event = Event {
-            event_type: "service.crashed".into(),
-            project: "myapp".into(),
-            source: "api-server".into(),
-            ts: String::new(),
-            level: None,
-            title: None,
-            body: None,
-            meta: None,
-            priority: None,
-        };
-        assert_eq!(render_speech(&event), "myapp: api-server crashed");
+        let event = Event::new("service.crashed", "myapp", "api-server");
+        assert_eq!(render_speech(&event, &CommanderConfig::default()), "myapp: api-server crashed");
     }
 
     #[test]
     fn render_speech_fallback_to_type() {
-        let event = Event {
-            event_type: "build.failed".into(),
-            project: "myapp".into(),
-            source: "ci".into(),
-            ts: String::new(),
-            level: None,
-            title: None,
-            body: None,
-            meta: None,
-            priority: None,
-        };
-        assert_eq!(render_speech(&event), "myapp: build failed — build.failed");
+        let event = Event::new("build.failed", "myapp", "ci");
+        assert_eq!(render_speech(&event, &CommanderConfig::default()), "myapp: build failed — build.failed");
     }
 
     #[test]
-    fn speakable_events() {
-        assert!(is_speakable_event("agent.completed"));
-        assert!(is_speakable_event("service.crashed"));
-        assert!(!is_speakable_event("workspace.created"));
-        assert!(!is_speakable_event("random.event"));
+    fn render_speech_uses_configured_templates() {
+        let mut config = CommanderConfig::default();
+        config.speech_templates.insert("build.*".into(), "{project} build: {title}".into());
+        config.speech_templates.insert("build.failed".into(), "{source} broke {project}".into());
+        let event = Event::new("build.failed", "myapp", "ci").title("cargo check");
+        assert_eq!(render_speech(&event, &config), "ci broke myapp");
+        let succeeded = Event {
+            event_type: "build.succeeded".into(),
+            ..event.clone()
+        };
+        assert_eq!(render_speech(&succeeded, &config), "myapp build: cargo check");
+        let other = Event {
+            event_type: "deploy.done".into(),
+            ..event
+        };
+        assert_eq!(render_speech(&other, &config), "myapp: cargo check");
     }
 
     #[test]
     fn should_speak_filters_by_event_type() {
-        let speakable = Event {
-            event_type: "agent.error".into(),
-            project: "p".into(),
-            source: "s".into(),
-            ts: String::new(),
-            level: None,
-            title: None,
-            body: None,
-            meta: None,
-            priority: None,
-        };
-        assert!(should_speak(&speakable));
+        let speakable = Event::new("agent.error", "p", "s");
+        assert!(should_speak(&speakable, &CommanderConfig::default()));
 
         let not_speakable = Event {
             event_type: "workspace.created".into(),
             ..speakable.clone()
         };
-        assert!(!should_speak(&not_speakable));
+        assert!(!should_speak(&not_speakable, &CommanderConfig::default()));
+
+        let config = CommanderConfig {
+            speak_events: vec!["workspace.*".into()],
+            ..CommanderConfig::default()
+        };
+        assert!(should_speak(&not_speakable, &config));
+        assert!(!should_speak(&speakable, &config));
+    }
+
+    #[test]
+    fn focus_tracker_follows_workspace_events() {
+        let event = |event_type: &str, project: &str| Event::new(event_type, project, "daemon");
+        let mut focus = FocusTracker::default();
+        focus.observe(&event("workspace.activated", "api"));
+        assert!(focus.is_focused("api"));
+        focus.observe(&event("workspace.deactivated", "api"));
+        focus.observe(&event("workspace.activated", "web"));
+        assert!(!focus.is_focused("api"));
+        assert!(focus.is_focused("web"));
+        // A late deactivation of another project doesn't clear focus
+        focus.observe(&event("workspace.deactivated", "api"));
+        assert!(focus.is_focused("web"));
     }
 
     #[test]
@@ -697,9 +835,41 @@ mod tests {
         ));
     }
 
+    fn msg(text: &str, critical: bool) -> SpeechMessage {
+        SpeechMessage {
+            text: text.into(),
+            instruct: String::new(),
+            critical,
+        }
+    }
+
+    fn drain(queue: &mut SpeechQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.pop()).map(|m| m.text).collect()
+    }
+
+    #[test]
+    fn speech_queue_puts_critical_first() {
+        let mut queue = SpeechQueue::new(5);
+        queue.push(msg("a", false));
+        queue.push(msg("crash 1", true));
+        queue.push(msg("b", false));
+        queue.push(msg("crash 2", true));
+        assert_eq!(drain(&mut queue), ["crash 1", "crash 2", "a", "b"]);
+    }
+
     #[test]
-    fn shell_escape_basic() {
-        assert_eq!(shell_escape("hello"), "'hello'");
-        assert_eq!(shell_escape("it's"), "'it'\\''s'");
+    fn speech_queue_drops_oldest_lowest_priority() {
+        let mut queue = SpeechQueue::new(3);
+        queue.push(msg("a", false));
+        queue.push(msg("b", false));
+        queue.push(msg("crash", true));
+        queue.push(msg("c", false));
+        assert_eq!(drain(&mut queue), ["crash", "b", "c"]);
+
+        let mut queue = SpeechQueue::new(2);
+        queue.push(msg("crash 1", true));
+        queue.push(msg("crash 2", true));
+        queue.push(msg("crash 3", true));
+        assert_eq!(drain(&mut queue), ["crash 2", "crash 3"]);
     }
 }
@@ -6,12 +6,17 @@ struct Cli {
     /// One-shot TTS: speak this text and exit
     #[arg(long)]
     say: Option<String>,
+    /// Check the TTS endpoint and run the fallback pipeline stage by stage
+    #[arg(long)]
+    test_pipeline: bool,
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
     if let Some(text) = cli.say {
         drift_commander::say_text(&text)
+    } else if cli.test_pipeline {
+        drift_commander::test_pipeline()
     } else {
         drift_commander::run_commander()
     }
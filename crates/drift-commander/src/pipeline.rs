@@ -0,0 +1,376 @@
+//! TTS audio pipelines (`piper | filter | aplay`) run as separate processes
+//! joined by pipes, without a shell in between.
+
+use std::io::{Read, Write};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Stdio};
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use drift_core::config::CommanderConfig;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl Stage {
+    fn new(program: &str, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// The program's file name, for messages.
+    pub fn name(&self) -> &str {
+        Path::new(&self.program)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(&self.program)
+    }
+
+    /// Where the program would be run from, if it can be found.
+    pub fn resolve(&self) -> Option<PathBuf> {
+        if self.program.contains('/') {
+            let path = PathBuf::from(&self.program);
+            return path.is_file().then_some(path);
+        }
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(&self.program))
+            .find(|candidate| candidate.is_file())
+    }
+
+    fn substitute(mut self, placeholder: &str, value: &str) -> Self {
+        for arg in &mut self.args {
+            *arg = arg.replace(placeholder, value);
+        }
+        self
+    }
+}
+
+/// Split a command line into stages: whitespace-separated words, `'…'` and
+/// `"…"` quoting, `\` escapes, and unquoted `|` between stages. Nothing else
+/// is special, so config values can't smuggle in shell syntax.
+pub fn parse(cmd: &str) -> anyhow::Result<Vec<Stage>> {
+    let mut stages = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = cmd.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => w.push(c),
+                        None => anyhow::bail!("unterminated ' in {cmd:?}"),
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => w.push(c),
+                            Some(c) => {
+                                w.push('\\');
+                                w.push(c);
+                            }
+                            None => anyhow::bail!("unterminated \" in {cmd:?}"),
+                        },
+                        Some(c) => w.push(c),
+                        None => anyhow::bail!("unterminated \" in {cmd:?}"),
+                    }
+                }
+            }
+            '\\' => {
+                let Some(next) = chars.next() else {
+                    anyhow::bail!("trailing \\ in {cmd:?}");
+                };
+                word.get_or_insert_with(String::new).push(next);
+            }
+            '|' => {
+                words.extend(word.take());
+                stages.push(stage_from(std::mem::take(&mut words), cmd)?);
+            }
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word.take());
+    stages.push(stage_from(words, cmd)?);
+    Ok(stages)
+}
+
+fn stage_from(mut words: Vec<String>, cmd: &str) -> anyhow::Result<Stage> {
+    if words.is_empty() {
+        anyhow::bail!("empty command in {cmd:?}");
+    }
+    let program = words.remove(0);
+    Ok(Stage { program, args: words })
+}
+
+/// `audio_filter` (if any) followed by aplay, for audio on stdin.
+pub fn playback(audio_filter: Option<&str>) -> anyhow::Result<Vec<Stage>> {
+    let mut stages = match audio_filter {
+        Some(filter) => parse(filter)?,
+        None => Vec::new(),
+    };
+    stages.push(Stage::new("aplay", &["-r", "22050", "-f", "S16_LE"]));
+    Ok(stages)
+}
+
+/// The fallback engine's stages for `text`, and what to feed the first one.
+/// A `fallback_command` gets the text as its `{text}` argument if it has
+/// one, and on stdin otherwise.
+pub fn fallback(config: &CommanderConfig, text: &str) -> anyhow::Result<(Vec<Stage>, Vec<u8>)> {
+    let line = format!("{text}\n").into_bytes();
+    if let Some(custom) = &config.fallback_command {
+        let stages = parse(custom)?
+            .into_iter()
+            .map(|stage| stage.substitute("{text}", text))
+            .collect();
+        let input = if custom.contains("{text}") { Vec::new() } else { line };
+        return Ok((stages, input));
+    }
+    match config.fallback_engine.as_deref() {
+        Some("piper") => {
+            let voice = config
+                .fallback_voice
+                .as_deref()
+                .unwrap_or("en_US-lessac-medium");
+            let mut stages = vec![Stage::new("piper", &["--model", voice, "--output-raw"])];
+            stages.extend(playback(config.audio_filter.as_deref())?);
+            Ok((stages, line))
+        }
+        Some("espeak-ng") | Some("espeak") => Ok((vec![Stage::new("espeak-ng", &[])], line)),
+        _ => anyhow::bail!("no fallback engine configured"),
+    }
+}
+
+pub struct StageOutcome {
+    pub name: String,
+    pub status: ExitStatus,
+    pub stderr: String,
+}
+
+pub enum Finished {
+    Completed(Vec<StageOutcome>),
+    Interrupted,
+}
+
+impl Finished {
+    /// An error naming each stage that failed, with the last line it logged.
+    pub fn check(self) -> anyhow::Result<()> {
+        let Finished::Completed(outcomes) = self else {
+            return Ok(());
+        };
+        let failures: Vec<String> = outcomes
+            .iter()
+            .filter(|o| !o.status.success())
+            .map(|o| match o.stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+                Some(line) => format!("{} {}: {}", o.name, o.status, line.trim()),
+                None => format!("{} {}", o.name, o.status),
+            })
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!("{}", failures.join("; "))
+        }
+    }
+}
+
+/// Run `stages` with `input` on the first one's stdin, polling `interrupted`
+/// while they play. The stages share a process group, so an interruption
+/// stops every one of them at once.
+pub fn run(
+    stages: &[Stage],
+    input: Vec<u8>,
+    interrupted: &mut dyn FnMut() -> bool,
+) -> anyhow::Result<Finished> {
+    let mut children: Vec<(String, Child)> = Vec::new();
+    let mut stderr_readers = Vec::new();
+    let mut upstream: Option<ChildStdout> = None;
+    let mut pgid = 0;
+
+    for (i, stage) in stages.iter().enumerate() {
+        let stdin = match upstream.take() {
+            Some(out) => Stdio::from(out),
+            None => Stdio::piped(),
+        };
+        let stdout = if i + 1 < stages.len() { Stdio::piped() } else { Stdio::null() };
+        let spawned = Command::new(&stage.program)
+            .args(&stage.args)
+            .stdin(stdin)
+            .stdout(stdout)
+            .stderr(Stdio::piped())
+            .process_group(pgid)
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                stop(pgid, &mut children);
+                anyhow::bail!("{}: failed to start: {e}", stage.name());
+            }
+        };
+        if pgid == 0 {
+            pgid = child.id() as i32;
+        }
+        upstream = child.stdout.take();
+        stderr_readers.push(child.stderr.take().map(|mut err| {
+            thread::spawn(move || {
+                let mut text = String::new();
+                let _ = err.read_to_string(&mut text);
+                text
+            })
+        }));
+        children.push((stage.name().to_string(), child));
+    }
+
+    // Feed the first stage from its own thread so long audio can't block
+    // the interrupt check; the write fails once the stage is gone.
+    if let Some(mut stdin) = children.first_mut().and_then(|(_, c)| c.stdin.take()) {
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+
+    let mut statuses: Vec<Option<ExitStatus>> = vec![None; children.len()];
+    loop {
+        for ((_, child), status) in children.iter_mut().zip(&mut statuses) {
+            if status.is_none() {
+                *status = child.try_wait()?;
+            }
+        }
+        if statuses.iter().all(Option::is_some) {
+            break;
+        }
+        if interrupted() || crate::SHUTDOWN.load(Ordering::Relaxed) {
+            stop(pgid, &mut children);
+            return Ok(Finished::Interrupted);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let outcomes = children
+        .into_iter()
+        .zip(statuses)
+        .zip(stderr_readers)
+        .map(|(((name, _), status), reader)| StageOutcome {
+            name,
+            status: status.expect("every stage exited"),
+            stderr: reader.and_then(|r| r.join().ok()).unwrap_or_default(),
+        })
+        .collect();
+    Ok(Finished::Completed(outcomes))
+}
+
+fn stop(pgid: i32, children: &mut [(String, Child)]) {
+    if pgid != 0 {
+        unsafe {
+            libc::killpg(pgid, libc::SIGTERM);
+        }
+    }
+    for (_, child) in children {
+        let _ = child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(stage: &Stage) -> Vec<&str> {
+        std::iter::once(stage.program.as_str())
+            .chain(stage.args.iter().map(String::as_str))
+            .collect()
+    }
+
+    #[test]
+    fn parse_splits_words_and_stages() {
+        let stages = parse("sox -t raw - -t raw - pitch -300 | tee '/tmp/tts out.raw'").unwrap();
+        assert_eq!(stages.len(), 2);
+        assert_eq!(words(&stages[0]), ["sox", "-t", "raw", "-", "-t", "raw", "-", "pitch", "-300"]);
+        assert_eq!(words(&stages[1]), ["tee", "/tmp/tts out.raw"]);
+    }
+
+    #[test]
+    fn parse_keeps_shell_syntax_literal() {
+        let stages = parse(r#"say "a | b; $(rm -rf ~)" \| ''"#).unwrap();
+        assert_eq!(stages.len(), 1);
+        assert_eq!(words(&stages[0]), ["say", "a | b; $(rm -rf ~)", "|", ""]);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_commands() {
+        assert!(parse("say 'open").is_err());
+        assert!(parse("a | | b").is_err());
+        assert!(parse("a |").is_err());
+        assert!(parse("   ").is_err());
+    }
+
+    #[test]
+    fn fallback_command_takes_text_as_argument_or_stdin() {
+        let config = CommanderConfig {
+            fallback_command: Some("say -v Alex {text}".into()),
+            ..CommanderConfig::default()
+        };
+        let (stages, input) = fallback(&config, "it's $HOME").unwrap();
+        assert_eq!(words(&stages[0]), ["say", "-v", "Alex", "it's $HOME"]);
+        assert!(input.is_empty());
+
+        let config = CommanderConfig {
+            fallback_command: Some("festival --tts".into()),
+            ..CommanderConfig::default()
+        };
+        let (_, input) = fallback(&config, "hello").unwrap();
+        assert_eq!(input, b"hello\n");
+    }
+
+    #[test]
+    fn piper_pipeline_includes_filter() {
+        let config = CommanderConfig {
+            fallback_engine: Some("piper".into()),
+            audio_filter: Some("sox -t raw - -t raw - gain -3".into()),
+            ..CommanderConfig::default()
+        };
+        let (stages, _) = fallback(&config, "hi").unwrap();
+        let names: Vec<_> = stages.iter().map(Stage::name).collect();
+        assert_eq!(names, ["piper", "sox", "aplay"]);
+    }
+
+    #[test]
+    fn run_reports_failing_stage() {
+        let stages = parse("cat | sh -c 'cat >/dev/null; echo broken >&2; exit 3'").unwrap();
+        let finished = run(&stages, b"audio".to_vec(), &mut || false).unwrap();
+        let err = finished.check().unwrap_err().to_string();
+        assert!(err.starts_with("sh exit status: 3"), "{err}");
+        assert!(err.ends_with("broken"), "{err}");
+    }
+
+    #[test]
+    fn run_stops_when_interrupted() {
+        let stages = parse("sleep 5 | cat").unwrap();
+        let started = std::time::Instant::now();
+        let finished = run(&stages, Vec::new(), &mut || true).unwrap();
+        assert!(matches!(finished, Finished::Interrupted));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn run_reports_missing_program() {
+        let stages = parse("drift-no-such-tts-engine").unwrap();
+        let err = run(&stages, Vec::new(), &mut || false).err().unwrap();
+        assert!(err.to_string().starts_with("drift-no-such-tts-engine: failed to start"));
+    }
+}
@@ -322,15 +322,8 @@ fn debug_save_wav(path: &str, samples: &[f32], sample_rate: u32) {
 fn emit_voice_event(event_type: &str, title: &str) {
     use drift_core::events::{self, Event};
     events::try_emit_event(&Event {
-        event_type: event_type.into(),
-        project: String::new(),
-        source: "commander".into(),
-        ts: events::iso_now(),
-        level: Some("info".into()),
-        title: Some(title.into()),
-        body: None,
-        meta: None,
         priority: Some("low".into()),
+        ..Event::new(event_type, "", "commander").level("info").title(title)
     });
 }
 
@@ -343,7 +336,7 @@ fn speak_feedback(
         let _ = tx.send(crate::SpeechMessage {
             text: text.into(),
             instruct: instruct.into(),
-            _critical: false,
+            critical: false,
         });
     }
 }
@@ -1,6 +1,5 @@
-use std::process::Command;
-
 use crate::config::ServiceProcess;
+use crate::shell;
 
 const FULL_TOOLS: &str = "Bash,Read,Edit,Write,Glob,Grep,WebFetch,WebSearch,NotebookEdit,Task";
 const SAFE_TOOLS: &str = "Read,Glob,Grep,WebFetch,WebSearch";
@@ -12,13 +11,7 @@ pub fn tmux_session_name(project: &str) -> String {
 
 /// Return true if a tmux session with this name already exists.
 pub fn tmux_session_exists(session: &str) -> bool {
-    Command::new("tmux")
-        .args(["has-session", "-t", session])
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false)
+    crate::tmux::session_exists(session)
 }
 
 /// Find an unused scratch session name of the form `drift-scratch-<n>`.
@@ -46,13 +39,13 @@ pub fn build_agent_command(svc: &ServiceProcess, project_name: &str) -> String {
          Use `drift notify --type agent.error --title \"<summary>\"` when you hit errors."
     );
 
-    let escaped_context = shell_escape(&system_context);
-    let escaped_task = shell_escape(raw_prompt);
+    let escaped_context = shell::quote(&system_context);
+    let escaped_task = shell::quote(raw_prompt);
 
     match (agent, svc.agent_mode.as_str()) {
         ("claude", "oneshot") => {
             // Oneshot: -p mode, task goes as positional arg
-            let escaped_full = shell_escape(&format!("{system_context}\n\n{raw_prompt}"));
+            let escaped_full = shell::quote(&format!("{system_context}\n\n{raw_prompt}"));
             let mut cmd = String::from("claude -p");
             if full {
                 cmd.push_str(" --dangerously-skip-permissions");
@@ -76,7 +69,7 @@ pub fn build_agent_command(svc: &ServiceProcess, project_name: &str) -> String {
             format!("{cmd} --append-system-prompt {escaped_context} {escaped_task}")
         }
         ("codex", "oneshot") => {
-            let escaped_full = shell_escape(&format!("{system_context}\n\n{raw_prompt}"));
+            let escaped_full = shell::quote(&format!("{system_context}\n\n{raw_prompt}"));
             let mut cmd = String::from("codex exec");
             if full {
                 cmd.push_str(" -s danger-full-access");
@@ -87,7 +80,7 @@ pub fn build_agent_command(svc: &ServiceProcess, project_name: &str) -> String {
             format!("{cmd} {escaped_full}")
         }
         ("codex", "interactive") => {
-            let escaped_full = shell_escape(&format!("{system_context}\n\n{raw_prompt}"));
+            let escaped_full = shell::quote(&format!("{system_context}\n\n{raw_prompt}"));
             let mut cmd = String::from("codex");
             if full {
                 cmd.push_str(" -s danger-full-access");
@@ -98,7 +91,7 @@ pub fn build_agent_command(svc: &ServiceProcess, project_name: &str) -> String {
             format!("{cmd} {escaped_full}")
         }
         _ => {
-            let escaped_full = shell_escape(&format!("{system_context}\n\n{raw_prompt}"));
+            let escaped_full = shell::quote(&format!("{system_context}\n\n{raw_prompt}"));
             format!("{agent} {escaped_full}")
         }
     }
@@ -109,10 +102,6 @@ pub fn is_interactive_agent(svc: &ServiceProcess) -> bool {
     svc.agent.is_some() && svc.agent_mode == "interactive"
 }
 
-fn shell_escape(s: &str) -> String {
-    format!("'{}'", s.replace('\'', "'\\''"))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,16 +110,10 @@ mod tests {
     fn make_agent(agent: &str, prompt: &str) -> ServiceProcess {
         ServiceProcess {
             name: "test".into(),
-            command: String::new(),
-            cwd: ".".into(),
             restart: RestartPolicy::Never,
-            stop_command: None,
             agent: Some(agent.into()),
             prompt: Some(prompt.into()),
-            agent_mode: "oneshot".into(),
-            agent_model: None,
-            agent_permissions: "full".into(),
-            width: None,
+            ..Default::default()
         }
     }
 
@@ -200,15 +183,8 @@ mod tests {
         let svc = ServiceProcess {
             name: "api".into(),
             command: "bash server.sh".into(),
-            cwd: ".".into(),
             restart: RestartPolicy::Never,
-            stop_command: None,
-            agent: None,
-            prompt: None,
-            agent_mode: "oneshot".into(),
-            agent_model: None,
-            agent_permissions: "full".into(),
-            width: None,
+            ..Default::default()
         };
         assert!(!is_interactive_agent(&svc));
     }
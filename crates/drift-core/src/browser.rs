@@ -0,0 +1,119 @@
+//! `browser` windows: Firefox or a Chromium-based browser started with a
+//! profile under the project's state dir, so each project keeps its own
+//! logins, cookies and tabs. The window gets an app_id naming the project
+//! and window, which niri rules and workspace snapshots match on.
+
+use std::fs;
+
+use anyhow::bail;
+
+use crate::{paths, shell};
+
+pub const BROWSERS: &[&str] = &["firefox", "chromium", "chrome", "brave"];
+
+/// app_id of a project's browser window, e.g. `drift-browser-web-docs`.
+pub fn app_id(project: &str, window_name: &str) -> String {
+    format!("drift-browser-{project}-{window_name}")
+}
+
+/// The window name encoded in a browser window's app_id, if it is one of
+/// this project's. A longer registered project name that also matches
+/// wins, so "web" doesn't claim "web-admin"'s browsers.
+pub fn window_name_from_app_id(app_id: &str, project: &str) -> Option<String> {
+    strip_project(app_id, project)?;
+    window_name_among(app_id, project, &project_names())
+}
+
+fn window_name_among(app_id: &str, project: &str, projects: &[String]) -> Option<String> {
+    let name = strip_project(app_id, project)?;
+    let longer = projects.iter().any(|p| p.len() > project.len() && strip_project(app_id, p).is_some());
+    (!longer).then(|| name.to_string())
+}
+
+fn strip_project<'a>(app_id: &'a str, project: &str) -> Option<&'a str> {
+    app_id
+        .strip_prefix("drift-browser-")?
+        .strip_prefix(project)?
+        .strip_prefix('-')
+        .filter(|name| !name.is_empty())
+}
+
+/// Registered project names, from the config file names.
+fn project_names() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(paths::projects_dir()) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect()
+}
+
+/// argv that starts `browser` on its project profile, creating the profile
+/// dir on first use. Firefox can't set its window title; Chromium-based
+/// browsers get the usual `drift:<project>/<name>` title.
+pub fn launch_args(browser: &str, project: &str, window_name: &str, url: Option<&str>) -> anyhow::Result<Vec<String>> {
+    let bin = match browser {
+        "firefox" => "firefox",
+        "chromium" => "chromium",
+        "chrome" => "google-chrome-stable",
+        "brave" => "brave",
+        other => bail!("unknown browser '{other}' (use: {})", BROWSERS.join(", ")),
+    };
+    let profile = paths::browser_profile_dir(project, browser);
+    fs::create_dir_all(&profile)?;
+    let profile = profile.to_string_lossy().into_owned();
+    let app_id = app_id(project, window_name);
+
+    let mut args: Vec<String> = if browser == "firefox" {
+        // A separate instance: without it Firefox hands the URL to one
+        // already running on the default profile
+        vec![bin.into(), "--new-instance".into(), "--profile".into(), profile, "--name".into(), app_id]
+    } else {
+        vec![
+            bin.into(),
+            format!("--user-data-dir={profile}"),
+            format!("--class={app_id}"),
+            format!("--window-name={}", shell::window_title(project, Some(window_name))),
+        ]
+    };
+    args.extend(url.map(String::from));
+    Ok(args)
+}
+
+/// Whether the browser sets the drift window title itself.
+pub fn sets_title(browser: &str) -> bool {
+    browser != "firefox"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_id_round_trips_window_name() {
+        let id = app_id("web", "docs");
+        assert_eq!(window_name_from_app_id(&id, "web"), Some("docs".into()));
+        assert_eq!(window_name_from_app_id(&id, "api"), None);
+        assert_eq!(window_name_from_app_id("firefox", "web"), None);
+    }
+
+    #[test]
+    fn longest_project_name_claims_the_app_id() {
+        let projects = ["web".to_string(), "web-admin".to_string()];
+        let id = app_id("web-admin", "docs");
+        assert_eq!(window_name_among(&id, "web", &projects), None);
+        assert_eq!(window_name_among(&id, "web-admin", &projects), Some("docs".into()));
+        assert_eq!(window_name_among(&app_id("web", "docs"), "web", &projects), Some("docs".into()));
+        // Without a "web-admin" project, the window is web's "admin-docs"
+        assert_eq!(window_name_among(&id, "web", &projects[..1]), Some("admin-docs".into()));
+    }
+
+    #[test]
+    fn unknown_browser_rejected() {
+        let err = launch_args("netscape", "web", "docs", None).unwrap_err();
+        assert_eq!(err.to_string(), "unknown browser 'netscape' (use: firefox, chromium, chrome, brave)");
+    }
+}
@@ -0,0 +1,106 @@
+//! Per-service cgroups. A service with `memory_max`, `cpu_max` or
+//! `cgroup = true` runs in its own transient systemd scope
+//! (`systemd-run --user --scope`): the limits apply to everything it
+//! starts, and stopping it kills the whole scope, including processes that
+//! left its process group.
+
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::ServiceProcess;
+
+pub fn wants_scope(svc: &ServiceProcess) -> bool {
+    svc.cgroup || svc.memory_max.is_some() || svc.cpu_max.is_some()
+}
+
+/// Whether the user's systemd manager can create scopes, probed once.
+pub fn available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new("systemd-run")
+            .args(["--user", "--scope", "--quiet", "--collect", "true"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    })
+}
+
+/// A unit name unique to this start of the service.
+pub fn unit_name(project: &str, service: &str) -> String {
+    let clean = |s: &str| -> String {
+        s.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+            .collect()
+    };
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    format!("drift-{}-{}-{nanos:x}.scope", clean(project), clean(service))
+}
+
+/// `systemd-run` arguments that put the command following them in `unit`.
+/// systemd-run execs the command, so the service keeps its pid.
+pub fn scope_args(unit: &str, svc: &ServiceProcess) -> Vec<String> {
+    let mut args: Vec<String> = ["--user", "--scope", "--quiet", "--collect", "--unit", unit]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    if let Some(memory) = &svc.memory_max {
+        args.push("-p".into());
+        args.push(format!("MemoryMax={}", memory.trim()));
+    }
+    if let Some(cpu) = &svc.cpu_max {
+        args.push("-p".into());
+        args.push(format!("CPUQuota={}", cpu.trim()));
+    }
+    args.push("--".into());
+    args
+}
+
+/// SIGKILL everything left in the scope. A scope that already ended is
+/// fine; systemd removes it once empty.
+pub fn kill(unit: &str) {
+    let _ = Command::new("systemctl")
+        .args(["--user", "kill", "--signal=SIGKILL", unit])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// `memory_max`: bytes, or a number with a K, M, G or T suffix.
+pub fn valid_memory(s: &str) -> bool {
+    let s = s.trim();
+    let digits = s.strip_suffix(['K', 'M', 'G', 'T']).unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `cpu_max`: a percentage of one CPU, above 100% for more than one.
+pub fn valid_cpu(s: &str) -> bool {
+    s.trim().strip_suffix('%').and_then(|n| n.parse::<u32>().ok()).is_some_and(|n| n > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_validated() {
+        assert!(valid_memory("2G"));
+        assert!(valid_memory("512M"));
+        assert!(valid_memory("1048576"));
+        assert!(!valid_memory("2GB"));
+        assert!(!valid_memory("G"));
+        assert!(valid_cpu("50%"));
+        assert!(valid_cpu("200%"));
+        assert!(!valid_cpu("0%"));
+        assert!(!valid_cpu("1.5"));
+    }
+
+    #[test]
+    fn unit_names_are_safe() {
+        let unit = unit_name("my app", "web/api");
+        assert!(unit.starts_with("drift-my_app-web_api-"));
+        assert!(unit.ends_with(".scope"));
+    }
+}
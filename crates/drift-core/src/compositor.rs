@@ -0,0 +1,162 @@
+//! The window manager drift drives, behind one trait: niri (the default)
+//! or Hyprland. Commands talk to `connect()`; only the daemon's live event
+//! tracking still speaks niri directly.
+
+use anyhow::bail;
+
+use crate::config;
+
+/// Values accepted for `defaults.compositor`.
+pub const BACKENDS: &[&str] = &["auto", "niri", "hyprland"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub id: u64,
+    /// Position on its output, 1-based
+    pub idx: u8,
+    pub name: Option<String>,
+    pub output: Option<String>,
+    /// Shown on its output
+    pub is_active: bool,
+    /// Shown on the focused output
+    pub is_focused: bool,
+    pub active_window_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    pub id: u64,
+    pub title: Option<String>,
+    pub app_id: Option<String>,
+    pub pid: Option<i32>,
+    pub workspace_id: Option<u64>,
+    pub is_focused: bool,
+    pub is_floating: bool,
+    /// Width and height in logical pixels
+    pub size: Option<(f64, f64)>,
+    /// Column in a scrolling layout (niri only)
+    pub column: Option<usize>,
+    /// Position within that column, from the top (niri only)
+    pub tile: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeChange {
+    /// Percent of the output
+    Proportion(f64),
+    /// Logical pixels
+    Fixed(i32),
+}
+
+/// Parse a width config string ("60%", "800px", "0.6") into a size.
+pub fn parse_width(width: &str) -> Option<SizeChange> {
+    let trimmed = width.trim();
+    if let Some(pct) = trimmed.strip_suffix('%') {
+        let val: f64 = pct.trim().parse().ok()?;
+        Some(SizeChange::Proportion(val))
+    } else if let Some(px) = trimmed.strip_suffix("px") {
+        let val: i32 = px.trim().parse().ok()?;
+        Some(SizeChange::Fixed(val))
+    } else if let Ok(val) = trimmed.parse::<f64>() {
+        // Bare float treated as proportion (0.6 → 60%)
+        Some(SizeChange::Proportion(val * 100.0))
+    } else {
+        None
+    }
+}
+
+pub trait Compositor {
+    fn workspaces(&mut self) -> anyhow::Result<Vec<Workspace>>;
+    fn windows(&mut self) -> anyhow::Result<Vec<Window>>;
+    fn focused_window(&mut self) -> anyhow::Result<Option<Window>>;
+
+    fn focus_workspace(&mut self, name: &str) -> anyhow::Result<()>;
+    fn focus_workspace_id(&mut self, id: u64) -> anyhow::Result<()>;
+    /// Create a workspace called `name` and focus it.
+    fn create_named_workspace(&mut self, name: &str) -> anyhow::Result<()>;
+    /// Drop a workspace's name so the compositor can clean it up once empty.
+    fn unset_workspace_name(&mut self, name: &str) -> anyhow::Result<()>;
+    /// Rename a project's workspace, whether it carries the bare project
+    /// name or a "{project} · status" name from a prior rename.
+    fn rename_workspace(&mut self, project: &str, new_name: &str) -> anyhow::Result<()>;
+
+    /// Run a program on the focused workspace.
+    fn spawn(&mut self, command: Vec<String>) -> anyhow::Result<()>;
+    fn close_window(&mut self, id: u64) -> anyhow::Result<()>;
+    fn focus_window(&mut self, id: u64) -> anyhow::Result<()>;
+    fn set_window_width(&mut self, id: u64, change: SizeChange) -> anyhow::Result<()>;
+    fn set_window_height(&mut self, id: u64, change: SizeChange) -> anyhow::Result<()>;
+    fn move_window_to_floating(&mut self, id: u64) -> anyhow::Result<()>;
+    fn fullscreen_window(&mut self, id: u64) -> anyhow::Result<()>;
+    /// Move the focused column; a no-op on layouts without columns.
+    fn move_column_to_index(&mut self, index: usize) -> anyhow::Result<()>;
+    fn move_window_to_monitor(&mut self, id: u64, output: &str) -> anyhow::Result<()>;
+    fn move_workspace_to_monitor(&mut self, name: &str, output: &str) -> anyhow::Result<()>;
+
+    fn find_workspace_by_name(&mut self, name: &str) -> anyhow::Result<Option<Workspace>> {
+        Ok(self.workspaces()?.into_iter().find(|ws| ws.name.as_deref() == Some(name)))
+    }
+
+    fn find_window_by_title(&mut self, title: &str) -> anyhow::Result<Option<Window>> {
+        Ok(self.windows()?.into_iter().find(|w| w.title.as_deref() == Some(title)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Niri,
+    Hyprland,
+}
+
+/// The backend `defaults.compositor` asks for. "auto" goes by the session's
+/// environment, preferring niri when both look present.
+pub fn backend() -> anyhow::Result<Backend> {
+    let configured = config::load_global_config()
+        .map(|g| g.defaults.compositor)
+        .unwrap_or_else(|_| "auto".into());
+    resolve_backend(
+        &configured,
+        std::env::var_os("NIRI_SOCKET").is_some(),
+        std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some(),
+    )
+}
+
+fn resolve_backend(configured: &str, niri: bool, hyprland: bool) -> anyhow::Result<Backend> {
+    match configured {
+        "niri" => Ok(Backend::Niri),
+        "hyprland" => Ok(Backend::Hyprland),
+        "auto" if hyprland && !niri => Ok(Backend::Hyprland),
+        "auto" => Ok(Backend::Niri),
+        other => bail!("unknown compositor '{other}' (expected one of {})", BACKENDS.join(", ")),
+    }
+}
+
+/// Connect to the configured compositor.
+pub fn connect() -> anyhow::Result<Box<dyn Compositor>> {
+    Ok(match backend()? {
+        Backend::Niri => Box::new(crate::niri::NiriClient::connect()?),
+        Backend::Hyprland => Box::new(crate::hyprland::HyprlandClient::connect()?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths_parse() {
+        assert_eq!(parse_width("60%"), Some(SizeChange::Proportion(60.0)));
+        assert_eq!(parse_width("800px"), Some(SizeChange::Fixed(800)));
+        assert_eq!(parse_width("0.5"), Some(SizeChange::Proportion(50.0)));
+        assert_eq!(parse_width("wide"), None);
+    }
+
+    #[test]
+    fn auto_prefers_niri() {
+        assert_eq!(resolve_backend("auto", true, true).unwrap(), Backend::Niri);
+        assert_eq!(resolve_backend("auto", false, true).unwrap(), Backend::Hyprland);
+        assert_eq!(resolve_backend("auto", false, false).unwrap(), Backend::Niri);
+        assert_eq!(resolve_backend("hyprland", true, false).unwrap(), Backend::Hyprland);
+        assert!(resolve_backend("sway", true, false).is_err());
+    }
+}
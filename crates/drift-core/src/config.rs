@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -17,6 +17,147 @@ pub struct GlobalConfig {
     pub commander: CommanderConfig,
     #[serde(default)]
     pub features: FeaturesConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookConfig>,
+    /// Variables for every project, beneath each project's own `[env]`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Per-folder defaults, `[folder.<name>]`
+    #[serde(default, rename = "folder", skip_serializing_if = "BTreeMap::is_empty")]
+    pub folders: BTreeMap<String, FolderConfig>,
+    /// Windows no project's sync or snapshots should keep
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_windows: Vec<WindowPattern>,
+}
+
+impl GlobalConfig {
+    /// Defaults for a project's folder, if it has any configured.
+    pub fn folder(&self, folder: Option<&str>) -> Option<&FolderConfig> {
+        self.folders.get(folder?)
+    }
+}
+
+/// Defaults shared by the projects in one folder.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct FolderConfig {
+    /// Monitor (niri output name) the folder's workspaces open on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Icon for new projects in the folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// First port for new projects' ranges, `ports.range_size` wide each
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port_base: Option<u16>,
+}
+
+/// Prometheus metrics served by the daemon.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    /// Address for the HTTP listener, e.g. "127.0.0.1:9977" (off when unset)
+    #[serde(default)]
+    pub listen: Option<String>,
+    /// Required on every request when set, as `?token=` or a bearer header
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// An external service the daemon publishes matching events to.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SinkConfig {
+    #[serde(rename = "type")]
+    pub kind: SinkKind,
+    /// ntfy topic, or MQTT topic prefix (events go to `<topic>/<project>/<type>`)
+    pub topic: String,
+    /// ntfy server URL (default https://ntfy.sh) or MQTT broker `host[:port]` (default localhost)
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Lowest event priority published: silent, low, medium, high or critical
+    #[serde(default)]
+    pub min_priority: Option<String>,
+    /// Event types to publish, `*` globs allowed (default: all)
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Only publish events of this project
+    #[serde(default)]
+    pub project: Option<String>,
+    /// ntfy access token
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    Ntfy,
+    Mqtt,
+}
+
+/// POST matching events as JSON to HTTP endpoints (e.g. Home Assistant).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WebhooksConfig {
+    /// Default URL for rules that don't set their own
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<WebhookRule>,
+}
+
+/// A command the daemon runs when a matching event arrives.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HookConfig {
+    /// Event type, supports a single `*` glob (e.g. "build.*")
+    pub event: String,
+    /// Run with `sh -c`; the event is in `DRIFT_EVENT_*` variables
+    pub command: String,
+    /// Only fire for this project (global hooks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_hook_timeout() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookRule {
+    /// Event type, supports a single `*` glob (e.g. "workspace.*")
+    pub event: String,
+    /// Only fire for this project
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Overrides `webhooks.url` for this rule
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl WebhooksConfig {
+    /// URLs to POST the event to, one per matching rule, deduplicated.
+    pub fn urls_for(&self, event_type: &str, project: &str) -> Vec<&str> {
+        let mut urls: Vec<&str> = Vec::new();
+        for rule in &self.rules {
+            if !crate::events::matches_type_filter(event_type, &rule.event) {
+                continue;
+            }
+            if rule.project.as_deref().is_some_and(|p| p != project) {
+                continue;
+            }
+            if let Some(url) = rule.url.as_deref().or(self.url.as_deref()) {
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+        urls
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -55,6 +196,39 @@ pub struct Defaults {
     pub shell: String,
     #[serde(default)]
     pub persist_windows: bool,
+    /// Suspend a project's services after its workspace has been unfocused
+    /// this many minutes (unset = never)
+    #[serde(default)]
+    pub idle_suspend_minutes: Option<u64>,
+    /// Regenerate niri-rules.kdl on init/delete/archive and when the daemon
+    /// sees project configs change
+    #[serde(default = "default_true")]
+    pub auto_niri_rules: bool,
+    /// Snapshot the current workspace before `drift to` switches away
+    #[serde(default = "default_true")]
+    pub save_on_switch: bool,
+    /// Window manager to drive: "auto", "niri" or "hyprland"
+    #[serde(default = "default_compositor")]
+    pub compositor: String,
+    /// Hand services a crashed supervisor left running to a new supervisor
+    /// when the daemon starts, instead of killing them
+    #[serde(default)]
+    pub adopt_orphans: bool,
+    /// Days `drift delete` keeps projects in the trash (0 = forever)
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// Stop counting focus time after this many minutes without input
+    /// (needs swayidle; 0 counts all focused time)
+    #[serde(default = "default_focus_idle_minutes")]
+    pub focus_idle_minutes: u32,
+    /// Fetch every project's repo this often in the daemon and report ones
+    /// that fell behind upstream (unset = never)
+    #[serde(default)]
+    pub git_poll_minutes: Option<u64>,
+    /// Snapshot every open project workspace this often in the daemon, not
+    /// only when it loses focus (unset = never)
+    #[serde(default)]
+    pub auto_save_minutes: Option<u64>,
 }
 
 impl Default for Defaults {
@@ -64,10 +238,28 @@ impl Default for Defaults {
             editor: default_editor(),
             shell: default_shell(),
             persist_windows: false,
+            idle_suspend_minutes: None,
+            auto_niri_rules: true,
+            save_on_switch: true,
+            compositor: default_compositor(),
+            adopt_orphans: false,
+            trash_retention_days: default_trash_retention_days(),
+            focus_idle_minutes: default_focus_idle_minutes(),
+            git_poll_minutes: None,
+            auto_save_minutes: None,
         }
     }
 }
 
+fn default_compositor() -> String {
+    "auto".into()
+}
+fn default_trash_retention_days() -> u32 {
+    30
+}
+fn default_focus_idle_minutes() -> u32 {
+    5
+}
 fn default_terminal() -> String {
     "ghostty".into()
 }
@@ -93,6 +285,20 @@ fn default_range_size() -> u16 {
     10
 }
 
+/// The first `size`-port range at or above `base` that overlaps none of
+/// `taken`.
+pub fn allocate_port_range(base: u16, size: u16, taken: &[[u16; 2]]) -> Option<[u16; 2]> {
+    let size = size.max(1);
+    let mut start = base;
+    loop {
+        let end = start.checked_add(size - 1)?;
+        match taken.iter().filter(|[s, e]| *s <= end && start <= *e).map(|[_, e]| *e).max() {
+            Some(blocked_until) => start = blocked_until.checked_add(1)?,
+            None => return Some([start, end]),
+        }
+    }
+}
+
 impl Default for PortDefaults {
     fn default() -> Self {
         Self {
@@ -108,16 +314,35 @@ pub struct EventsConfig {
     pub buffer_size: usize,
     #[serde(default = "default_replay_on_subscribe")]
     pub replay_on_subscribe: usize,
+    /// Days of event log archives to keep (0 = forever)
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+    /// Seconds between daemon heartbeats and niri state reconciles (0 = off)
+    #[serde(default = "default_heartbeat_secs")]
+    pub heartbeat_secs: u64,
+    /// Seconds during which repeats of an event (same project, type and
+    /// title) are folded into the first one (0 = off)
+    #[serde(default)]
+    pub dedup_secs: u64,
+    /// Emitted events allowed per source, e.g. `"ci" = "10/min"`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub limits: HashMap<String, String>,
 }
 
 fn default_buffer_size() -> usize { 200 }
 fn default_replay_on_subscribe() -> usize { 20 }
+fn default_retention_days() -> u32 { 14 }
+fn default_heartbeat_secs() -> u64 { 30 }
 
 impl Default for EventsConfig {
     fn default() -> Self {
         Self {
             buffer_size: default_buffer_size(),
             replay_on_subscribe: default_replay_on_subscribe(),
+            retention_days: default_retention_days(),
+            heartbeat_secs: default_heartbeat_secs(),
+            dedup_secs: 0,
+            limits: HashMap::new(),
         }
     }
 }
@@ -129,6 +354,9 @@ pub struct CommanderConfig {
     pub enabled: bool,
     #[serde(default = "default_endpoint")]
     pub endpoint: String,
+    /// Bearer token for `endpoint`: `keyring:<service>/<account>` or a literal token
+    #[serde(default)]
+    pub endpoint_auth: Option<String>,
     #[serde(default = "default_voice")]
     pub voice: String,
     #[serde(default)]
@@ -143,12 +371,28 @@ pub struct CommanderConfig {
     pub audio_filter: Option<String>,
     #[serde(default)]
     pub speak_background_only: bool,
+    /// Local `"HH:MM-HH:MM"` ranges with no announcements
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
+    /// Projects never announced
+    #[serde(default)]
+    pub muted_projects: Vec<String>,
     #[serde(default = "default_cooldown_sec")]
     pub cooldown_sec: u64,
     #[serde(default = "default_max_queue")]
     pub max_queue: usize,
+    /// How much to turn other audio down while speaking, in percent (0 = off)
+    #[serde(default)]
+    pub duck_percent: u8,
     #[serde(default)]
     pub event_instructs: HashMap<String, String>,
+    /// Event types to announce; `*` globs as in `drift events --type`
+    #[serde(default = "default_speak_events")]
+    pub speak_events: Vec<String>,
+    /// Announcement per event type (or glob), with `{project}`, `{title}`,
+    /// `{source}` and `{type}` placeholders
+    #[serde(default)]
+    pub speech_templates: HashMap<String, String>,
     #[serde(default)]
     pub voice_enabled: bool,
     #[serde(default = "default_wake_word")]
@@ -173,6 +417,11 @@ fn default_endpoint() -> String { "http://localhost:8880".into() }
 fn default_voice() -> String { "Vivian".into() }
 fn default_cooldown_sec() -> u64 { 5 }
 fn default_max_queue() -> usize { 3 }
+fn default_speak_events() -> Vec<String> {
+    ["agent.completed", "agent.error", "agent.needs_review", "service.crashed", "build.failed"]
+        .map(String::from)
+        .to_vec()
+}
 fn default_wake_word() -> String { "drift".into() }
 fn default_stt_model() -> String { "parakeet-tdt-0.6b-v3".into() }
 fn default_vad_threshold() -> f32 { 0.2 }
@@ -184,6 +433,7 @@ impl Default for CommanderConfig {
         Self {
             enabled: false,
             endpoint: default_endpoint(),
+            endpoint_auth: None,
             voice: default_voice(),
             instruct: String::new(),
             fallback_engine: None,
@@ -191,9 +441,14 @@ impl Default for CommanderConfig {
             fallback_command: None,
             audio_filter: None,
             speak_background_only: false,
+            quiet_hours: Vec::new(),
+            muted_projects: Vec::new(),
             cooldown_sec: default_cooldown_sec(),
             max_queue: default_max_queue(),
+            duck_percent: 0,
             event_instructs: HashMap::new(),
+            speak_events: default_speak_events(),
+            speech_templates: HashMap::new(),
             voice_enabled: false,
             wake_word: default_wake_word(),
             stt_model: default_stt_model(),
@@ -230,10 +485,16 @@ pub struct ProjectConfig {
     pub auto_close: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub persist_windows: Option<bool>,
+    /// Add windows the daemon finds in the workspace to `[[windows]]`
+    /// directly; when false they wait for `drift sync review`
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub auto_sync: bool,
     #[serde(default)]
     pub env: EnvConfig,
     #[serde(default)]
     pub git: Option<GitConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<SshConfig>,
     #[serde(default)]
     pub ports: Option<ProjectPorts>,
     #[serde(default)]
@@ -247,7 +508,26 @@ pub struct ProjectConfig {
     #[serde(default)]
     pub verification: Option<VerificationConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<WatchConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<ToolchainConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dispatcher: Option<DispatcherConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<SetupConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub window_rules: Vec<WindowRule>,
+    /// Shell commands run at the end of every cold `drift open`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_open: Vec<String>,
+    /// Shell commands run at the start of every close, while services are up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_close: Vec<String>,
+    /// Windows window sync and workspace snapshots leave out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_windows: Vec<WindowPattern>,
 }
 
 fn default_true() -> bool {
@@ -264,10 +544,23 @@ pub struct ProjectMeta {
     pub icon: Option<String>,
 }
 
+impl ProjectMeta {
+    /// Name prefixed with the project icon, e.g. "🌐 website".
+    pub fn display_name(&self) -> String {
+        match self.icon.as_deref().map(str::trim) {
+            Some(icon) if !icon.is_empty() => format!("{icon} {}", self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct EnvConfig {
     #[serde(default)]
     pub env_file: Option<String>,
+    /// Names of `drift secret` values to decrypt into the environment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
     #[serde(flatten)]
     pub vars: HashMap<String, String>,
 }
@@ -276,9 +569,21 @@ pub struct EnvConfig {
 pub struct GitConfig {
     pub user_name: Option<String>,
     pub user_email: Option<String>,
+    /// GPG key id (or SSH key path with `gpg.format = ssh`) for user.signingkey
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+    /// commit.gpgsign for the repo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_sign: Option<bool>,
 }
 
+/// A key loaded into the project's own ssh-agent while it is open
 #[derive(Debug, Deserialize, Serialize)]
+pub struct SshConfig {
+    pub key: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct ProjectPorts {
     pub range: Option<[u16; 2]>,
     #[serde(flatten)]
@@ -291,7 +596,7 @@ pub struct ServicesConfig {
     pub processes: Vec<ServiceProcess>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ServiceProcess {
     pub name: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -314,6 +619,53 @@ pub struct ServiceProcess {
     pub agent_permissions: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub width: Option<String>,
+    /// A failed start or early crash marks the project degraded on open
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    /// Memory limit for the service's cgroup, e.g. "2G"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_max: Option<String>,
+    /// CPU limit as a percentage of one CPU, e.g. "150%"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_max: Option<String>,
+    /// Run in its own systemd scope even without limits
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub cgroup: bool,
+    /// Cron expression; the command runs on schedule instead of staying up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+}
+
+fn is_true(b: &bool) -> bool {
+    *b
+}
+
+pub(crate) fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+/// The values an entry with only a `name` gets from serde.
+impl Default for ServiceProcess {
+    fn default() -> Self {
+        ServiceProcess {
+            name: String::new(),
+            command: String::new(),
+            cwd: default_cwd(),
+            restart: RestartPolicy::default(),
+            stop_command: None,
+            agent: None,
+            prompt: None,
+            agent_mode: default_agent_mode(),
+            agent_model: None,
+            agent_permissions: default_agent_permissions(),
+            width: None,
+            required: false,
+            memory_max: None,
+            cpu_max: None,
+            cgroup: false,
+            schedule: None,
+        }
+    }
 }
 
 fn default_cwd() -> String {
@@ -344,7 +696,7 @@ fn is_default_agent_permissions(s: &str) -> bool {
     s == "full"
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum RestartPolicy {
     #[default]
@@ -353,7 +705,7 @@ pub enum RestartPolicy {
     Always,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct WindowConfig {
     #[serde(default)]
     pub name: Option<String>,
@@ -365,6 +717,66 @@ pub struct WindowConfig {
     pub tmux: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub app_id: Option<String>,
+    /// Open as a floating window instead of a column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floating: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fullscreen: Option<bool>,
+    /// 0.0 (transparent) to 1.0, set through niri-rules.kdl
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+    /// 1-based column position on the workspace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Monitor (niri output name, e.g. "DP-1") to move the window to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Service to wait for on `drift open`: its named port must accept
+    /// connections, or without one the service must be running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    /// Extra wait before opening, e.g. "2s" or "500ms"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<String>,
+    /// Open a browser ("firefox", "chromium", "chrome", "brave") with its
+    /// own profile for this project instead of a terminal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser: Option<String>,
+    /// Page a `browser` window opens on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Claims windows drift didn't spawn for the project: a browser profile, a
+/// database GUI. Both patterns are regexes; a rule needs at least one.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct WindowRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Move a matching window to the project's workspace when it opens
+    #[serde(default)]
+    pub move_to_workspace: bool,
+}
+
+/// `[[ignore_windows]]`: app_id and/or title regexes for transient
+/// windows (file pickers, video players) that shouldn't be persisted.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct WindowPattern {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Parse a window `delay`: whole seconds ("2s", "2") or milliseconds ("500ms").
+pub fn parse_delay(s: &str) -> Option<std::time::Duration> {
+    let s = s.trim();
+    if let Some(ms) = s.strip_suffix("ms") {
+        return ms.trim().parse().ok().map(std::time::Duration::from_millis);
+    }
+    s.strip_suffix('s').unwrap_or(s).trim().parse().ok().map(std::time::Duration::from_secs)
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -385,6 +797,50 @@ pub struct VerificationConfig {
     pub timeout_sec: Option<u64>,
 }
 
+/// Commands run once, the first time the project opens (`drift setup rerun`
+/// runs them again).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SetupConfig {
+    #[serde(default)]
+    pub commands: Vec<String>,
+}
+
+/// Run `command` whenever a file matching `globs` changes (polled by the supervisor).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Globs relative to the repo root, e.g. "src/**/*.rs"
+    pub globs: Vec<String>,
+    pub command: String,
+    /// Directories (globs) not descended into while scanning
+    #[serde(default = "default_watch_ignore")]
+    pub ignore: Vec<String>,
+    /// Quiet period after a change before the command runs
+    #[serde(default = "default_watch_debounce_ms")]
+    pub debounce_ms: u64,
+}
+
+fn default_watch_ignore() -> Vec<String> {
+    vec![".git".into(), "target".into(), "node_modules".into()]
+}
+
+fn default_watch_debounce_ms() -> u64 {
+    500
+}
+
+/// Interpreter versions activated in every spawned window and service.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolchainConfig {
+    /// Virtualenv directory, relative to the repo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub python: Option<String>,
+    /// Node version prefix ("20", "20.11") installed with fnm or nvm
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// rustup toolchain name ("stable", "1.78", "nightly-2024-05-01")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust: Option<String>,
+}
+
 pub fn load_global_config() -> anyhow::Result<GlobalConfig> {
     let path = paths::global_config_path();
     if !path.exists() {
@@ -422,7 +878,7 @@ pub fn resolve_current_project(explicit: Option<&str>) -> anyhow::Result<String>
         }
     }
 
-    if let Ok(mut client) = crate::niri::NiriClient::connect() {
+    if let Ok(mut client) = crate::compositor::connect() {
         if let Ok(Some(win)) = client.focused_window() {
             if let Some(ws_id) = win.workspace_id {
                 if let Ok(workspaces) = client.workspaces() {
@@ -458,6 +914,12 @@ pub fn resolve_repo_path(raw: &str) -> anyhow::Result<PathBuf> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn default_service_matches_a_parsed_bare_entry() {
+        let parsed: ServiceProcess = toml::from_str(r#"name = "api""#).unwrap();
+        assert_eq!(parsed, ServiceProcess { name: "api".into(), ..Default::default() });
+    }
+
     #[test]
     fn parse_minimal_project_config() {
         let toml_str = r#"
@@ -470,6 +932,7 @@ repo = "~/code/myapp"
         assert_eq!(config.project.repo, "~/code/myapp");
         assert!(config.project.folder.is_none());
         assert!(config.project.icon.is_none());
+        assert_eq!(config.project.display_name(), config.project.name);
         assert!(config.env.vars.is_empty());
         assert!(config.env.env_file.is_none());
         assert!(config.git.is_none());
@@ -490,6 +953,7 @@ icon = "🌐"
 
 [env]
 env_file = ".env"
+secrets = ["API_KEY"]
 NODE_ENV = "development"
 PORT = "3000"
 
@@ -522,9 +986,12 @@ file = "notes.md"
         assert_eq!(config.project.name, "webapp");
         assert_eq!(config.project.folder.as_deref(), Some("web"));
         assert_eq!(config.project.icon.as_deref(), Some("🌐"));
+        assert_eq!(config.project.display_name(), "🌐 webapp");
         assert_eq!(config.env.env_file.as_deref(), Some(".env"));
         assert_eq!(config.env.vars.get("NODE_ENV").unwrap(), "development");
         assert_eq!(config.env.vars.get("PORT").unwrap(), "3000");
+        assert_eq!(config.env.secrets, vec!["API_KEY"]);
+        assert!(!config.env.vars.contains_key("secrets"));
         let git = config.git.unwrap();
         assert_eq!(git.user_name.as_deref(), Some("Dev User"));
         assert_eq!(git.user_email.as_deref(), Some("dev@example.com"));
@@ -566,6 +1033,31 @@ range_size = 20
         assert_eq!(config.ports.range_size, 20);
     }
 
+    #[test]
+    fn parse_global_config_folders() {
+        let toml_str = r#"
+[folder.work]
+output = "DP-1"
+icon = "💼"
+port_base = 4000
+"#;
+        let config: GlobalConfig = toml::from_str(toml_str).unwrap();
+        let work = config.folder(Some("work")).unwrap();
+        assert_eq!(work.output.as_deref(), Some("DP-1"));
+        assert_eq!(work.port_base, Some(4000));
+        assert!(config.folder(Some("play")).is_none());
+        assert!(config.folder(None).is_none());
+    }
+
+    #[test]
+    fn port_ranges_skip_taken() {
+        assert_eq!(allocate_port_range(4000, 10, &[]), Some([4000, 4009]));
+        assert_eq!(allocate_port_range(4000, 10, &[[4000, 4009], [4005, 4014]]), Some([4015, 4024]));
+        assert_eq!(allocate_port_range(4000, 10, &[[4008, 4020]]), Some([4021, 4030]));
+        assert_eq!(allocate_port_range(4000, 10, &[[3000, 3009]]), Some([4000, 4009]));
+        assert_eq!(allocate_port_range(65530, 10, &[]), None);
+    }
+
     #[test]
     fn parse_global_config_empty_uses_defaults() {
         let config: GlobalConfig = toml::from_str("").unwrap();
@@ -659,11 +1151,47 @@ processes = [
         assert_eq!(config.ports.range_size, 10);
     }
 
+    #[test]
+    fn webhook_rules_match_and_override_url() {
+        let toml_str = r#"
+[webhooks]
+url = "http://ha.local/api/webhook/drift"
+
+[[webhooks.rules]]
+event = "workspace.activated"
+project = "oncall"
+url = "http://ha.local/api/webhook/oncall"
+
+[[webhooks.rules]]
+event = "workspace.*"
+"#;
+        let config: GlobalConfig = toml::from_str(toml_str).unwrap();
+        let hooks = &config.webhooks;
+        assert_eq!(
+            hooks.urls_for("workspace.activated", "oncall"),
+            vec!["http://ha.local/api/webhook/oncall", "http://ha.local/api/webhook/drift"]
+        );
+        assert_eq!(hooks.urls_for("workspace.deactivated", "oncall"), vec!["http://ha.local/api/webhook/drift"]);
+        assert!(hooks.urls_for("service.crashed", "oncall").is_empty());
+    }
+
+    #[test]
+    fn webhook_rule_without_any_url_is_skipped() {
+        let toml_str = r#"
+[[webhooks.rules]]
+event = "workspace.activated"
+"#;
+        let config: GlobalConfig = toml::from_str(toml_str).unwrap();
+        assert!(config.webhooks.urls_for("workspace.activated", "x").is_empty());
+    }
+
     #[test]
     fn events_config_defaults() {
         let config = EventsConfig::default();
         assert_eq!(config.buffer_size, 200);
         assert_eq!(config.replay_on_subscribe, 20);
+        assert_eq!(config.retention_days, 14);
+        assert_eq!(config.heartbeat_secs, 30);
     }
 
     #[test]
@@ -706,29 +1234,32 @@ replay_on_subscribe = 10
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: Some(ServicesConfig {
                 processes: vec![ServiceProcess {
                     name: "api".into(),
                     command: "npm start".into(),
-                    cwd: ".".into(),
                     restart: RestartPolicy::OnFailure,
-                    stop_command: None,
-                    agent: None,
-                    prompt: None,
-                    agent_mode: "oneshot".into(),
-                    agent_model: None,
-                    agent_permissions: "full".into(),
-                    width: None,
+                    ..Default::default()
                 }],
             }),
-            windows: vec![WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), width: None, tmux: None, app_id: None }],
+            windows: vec![WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), ..Default::default() }],
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -753,15 +1284,25 @@ replay_on_subscribe = 10
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: None,
             windows: vec![],
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         };
         config.env.vars.insert("NODE_ENV".into(), "development".into());
         config.env.vars.insert("PORT".into(), "3000".into());
@@ -784,8 +1325,10 @@ replay_on_subscribe = 10
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: Some(ProjectPorts {
                 range: Some([3000, 3010]),
                 named: [("api".into(), 3001), ("web".into(), 3002)].into_iter().collect(),
@@ -795,7 +1338,15 @@ replay_on_subscribe = 10
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -807,6 +1358,16 @@ replay_on_subscribe = 10
         assert_eq!(*ports.named.get("web").unwrap(), 3002);
     }
 
+    #[test]
+    fn parse_delay_units() {
+        use std::time::Duration;
+        assert_eq!(parse_delay("2s"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_delay("3"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_delay("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_delay("soon"), None);
+        assert_eq!(parse_delay("1.5s"), None);
+    }
+
     #[test]
     fn resolve_current_project_explicit() {
         let result = resolve_current_project(Some("myapp")).unwrap();
@@ -824,29 +1385,32 @@ replay_on_subscribe = 10
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: Some(ServicesConfig {
                 processes: vec![ServiceProcess {
                     name: "api".into(),
                     command: "run".into(),
-                    cwd: ".".into(),
                     restart: RestartPolicy::Never,
-                    stop_command: None,
-                    agent: None,
-                    prompt: None,
-                    agent_mode: "oneshot".into(),
-                    agent_model: None,
-                    agent_permissions: "full".into(),
-                    width: None,
+                    ..Default::default()
                 }],
             }),
             windows: vec![],
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         };
 
         // Remove the service
@@ -870,29 +1434,35 @@ replay_on_subscribe = 10
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: Some(ServicesConfig {
                 processes: vec![ServiceProcess {
                     name: "reviewer".into(),
-                    command: String::new(),
-                    cwd: ".".into(),
                     restart: RestartPolicy::OnFailure,
-                    stop_command: None,
                     agent: Some("claude".into()),
                     prompt: Some("Review code".into()),
-                    agent_mode: "oneshot".into(),
                     agent_model: Some("opus".into()),
                     agent_permissions: "safe".into(),
-                    width: None,
+                    ..Default::default()
                 }],
             }),
             windows: vec![],
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -6,7 +6,7 @@ use crate::handoff::Handoff;
 use crate::project_state::ProjectState;
 use crate::registry;
 use crate::tasks::{Task, TaskQueue, TaskStatus};
-use crate::{agent, config, env, handoff, paths, project_state};
+use crate::{agent, config, env, handoff, paths, project_state, shell};
 
 /// Indicates the source of a previous handoff for prompt construction.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -192,27 +192,22 @@ pub fn prepare_dispatch(
 
     let svc = ServiceProcess {
         name: format!("dispatch-{}", task.id),
-        command: String::new(),
-        cwd: ".".into(),
         restart: RestartPolicy::Never,
-        stop_command: None,
         agent: Some(agent_type.clone()),
         prompt: Some(dispatch_prompt.clone()),
-        agent_mode: "oneshot".into(),
         agent_model: model.clone(),
-        agent_permissions: "full".into(),
-        width: None,
+        ..Default::default()
     };
 
     let agent_cmd = agent::build_agent_command(&svc, project_name);
     let wrapped_cmd = format!(
-        "{}; drift _post-dispatch '{}' '{}'",
+        "{}; drift _post-dispatch {} {}",
         agent_cmd,
-        project_name.replace('\'', "'\\''"),
-        task.id.replace('\'', "'\\''"),
+        shell::quote(project_name),
+        shell::quote(&task.id),
     );
 
-    let mut env_vars = env::build_env(&project_config)?;
+    let mut env_vars = env::build_process_env(&project_config)?;
     let task_env = env::dispatch_env(&task.id, &handoff_file_path);
     env_vars.extend(task_env);
 
@@ -270,20 +265,14 @@ pub fn execute_dispatch(plan: &DispatchPlan) -> anyhow::Result<PathBuf> {
         }
     }
 
-    crate::events::try_emit_event(&crate::events::Event {
-        event_type: "task.running".into(),
-        project: plan.project_name.clone(),
-        source: "dispatch".into(),
-        ts: crate::events::iso_now(),
-        level: Some("info".into()),
-        title: Some(format!("Dispatched task {}", plan.task.id)),
-        body: Some(plan.task.description.clone()),
-        meta: Some(serde_json::json!({
+    crate::events::try_emit_event(&crate::events::Event::new("task.running", plan.project_name.clone(), "dispatch")
+        .level("info")
+        .title(format!("Dispatched task {}", plan.task.id))
+        .body(plan.task.description.clone())
+        .meta(serde_json::json!({
             "task_id": plan.task.id,
             "agent_type": plan.agent_type,
-        })),
-        priority: None,
-    });
+        })));
 
     Ok(log_path)
 }
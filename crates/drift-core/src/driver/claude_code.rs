@@ -74,7 +74,7 @@ impl ClaudeCodeDriver {
                 Some((meta.modified().ok()?, e.path()))
             })
             .collect();
-        files.sort_by(|a, b| b.0.cmp(&a.0));
+        files.sort_by_key(|f| std::cmp::Reverse(f.0));
 
         let path = files.into_iter().next()?.1;
         let content = fs::read_to_string(&path).ok()?;
@@ -1,8 +1,41 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use crate::config::{self, ProjectConfig};
+use crate::config::{self, ProjectConfig, ToolchainConfig};
 
+/// Stands in for a secret's value in [`build_env`], so printed environments
+/// never show one.
+pub const SECRET_PLACEHOLDER: &str = "<secret>";
+
+/// The project environment as it may be shown to the user or other tools.
+/// Secrets are not decrypted; each is set to [`SECRET_PLACEHOLDER`].
 pub fn build_env(project: &ProjectConfig) -> anyhow::Result<HashMap<String, String>> {
+    let global = config::load_global_config()?;
+    build_env_with_global(project, &global.env)
+}
+
+/// The environment handed to processes drift starts for the project, with
+/// secrets decrypted. Never print it.
+pub fn build_process_env(project: &ProjectConfig) -> anyhow::Result<HashMap<String, String>> {
+    let global = config::load_global_config()?;
+    build(project, &global.env, true)
+}
+
+pub fn build_env_with_global(
+    project: &ProjectConfig,
+    global_env: &HashMap<String, String>,
+) -> anyhow::Result<HashMap<String, String>> {
+    build(project, global_env, false)
+}
+
+/// Layers, lowest first: drift's own variables and ports, the global
+/// `[env]`, the project's `env_file`, its `[env]` and its secrets. `${VAR}`
+/// in `[env]` values (global or project) is then expanded.
+fn build(
+    project: &ProjectConfig,
+    global_env: &HashMap<String, String>,
+    reveal_secrets: bool,
+) -> anyhow::Result<HashMap<String, String>> {
     let mut env = HashMap::new();
 
     let repo_path = config::resolve_repo_path(&project.project.repo)?;
@@ -25,6 +58,39 @@ pub fn build_env(project: &ProjectConfig) -> anyhow::Result<HashMap<String, Stri
         crate::paths::emit_socket_path().to_string_lossy().to_string(),
     );
 
+    if project.ssh.is_some() && crate::ssh::agent_running(&project.project.name) {
+        env.insert(
+            "SSH_AUTH_SOCK".into(),
+            crate::paths::ssh_agent_socket_path(&project.project.name).to_string_lossy().into(),
+        );
+    }
+
+    // Set PROJECT.md path if it exists
+    let ps_path = crate::paths::project_state_path(&repo_path);
+    if ps_path.exists() {
+        env.insert(
+            "DRIFT_PROJECT_STATE".into(),
+            ps_path.to_string_lossy().into(),
+        );
+    }
+
+    if let Some(ports) = &project.ports {
+        if let Some([start, end]) = ports.range {
+            env.insert("DRIFT_PORT_RANGE_START".into(), start.to_string());
+            env.insert("DRIFT_PORT_RANGE_END".into(), end.to_string());
+        }
+        for (name, port) in &ports.named {
+            env.insert(
+                format!("DRIFT_PORT_{}", name.to_uppercase()),
+                port.to_string(),
+            );
+        }
+    }
+
+    for (key, value) in global_env {
+        env.insert(key.clone(), value.clone());
+    }
+
     if let Some(env_file) = &project.env.env_file {
         let env_path = repo_path.join(env_file);
         if env_path.exists() {
@@ -44,30 +110,189 @@ pub fn build_env(project: &ProjectConfig) -> anyhow::Result<HashMap<String, Stri
     for (key, value) in &project.env.vars {
         env.insert(key.clone(), value.clone());
     }
+    if reveal_secrets {
+        env.extend(crate::secrets::resolve(&project.project.name, &project.env.secrets)?);
+    } else {
+        for key in &project.env.secrets {
+            env.insert(key.clone(), SECRET_PLACEHOLDER.into());
+        }
+    }
 
-    // Set PROJECT.md path if it exists
-    let ps_path = crate::paths::project_state_path(&repo_path);
-    if ps_path.exists() {
-        env.insert(
-            "DRIFT_PROJECT_STATE".into(),
-            ps_path.to_string_lossy().into(),
-        );
+    // Only values from a config file are templates; env_file and secret
+    // values are taken as they are
+    let templates: HashSet<String> = global_env
+        .keys()
+        .chain(project.env.vars.keys())
+        .filter(|k| env.get(*k).is_some_and(|v| v.contains('$')))
+        .cloned()
+        .collect();
+    interpolate(&mut env, &templates)?;
+
+    if let Some(toolchain) = &project.toolchain {
+        apply_toolchain(&mut env, toolchain, &repo_path, &node_install_roots());
     }
 
-    if let Some(ports) = &project.ports {
-        if let Some([start, end]) = ports.range {
-            env.insert("DRIFT_PORT_RANGE_START".into(), start.to_string());
-            env.insert("DRIFT_PORT_RANGE_END".into(), end.to_string());
+    Ok(env)
+}
+
+/// Expand `${VAR}` in the `templates` values, from other variables in any
+/// order and then the process environment. A variable naming itself
+/// (`PATH = "${HOME}/bin:${PATH}"`) gets the inherited value; `$${` is a
+/// literal `${`. An unset variable expands to nothing, with a warning.
+fn interpolate(env: &mut HashMap<String, String>, templates: &HashSet<String>) -> anyhow::Result<()> {
+    let mut done = HashMap::new();
+    for key in templates {
+        expand_var(key, env, templates, &mut done, &mut Vec::new())?;
+    }
+    env.extend(done);
+    Ok(())
+}
+
+fn expand_var(
+    key: &str,
+    env: &HashMap<String, String>,
+    templates: &HashSet<String>,
+    done: &mut HashMap<String, String>,
+    stack: &mut Vec<String>,
+) -> anyhow::Result<String> {
+    if let Some(value) = done.get(key) {
+        return Ok(value.clone());
+    }
+    if stack.iter().any(|k| k == key) {
+        stack.push(key.to_string());
+        anyhow::bail!("env variables refer to each other: {}", stack.join(" -> "));
+    }
+    stack.push(key.to_string());
+
+    let raw = &env[key];
+    let mut out = String::new();
+    let mut rest = raw.as_str();
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        if let Some(tail) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = tail;
+        } else if let Some(tail) = after.strip_prefix('{') {
+            let Some(end) = tail.find('}') else {
+                anyhow::bail!("env {key}: unclosed ${{");
+            };
+            let name = &tail[..end];
+            let value = if name == key {
+                std::env::var(name).unwrap_or_default()
+            } else if templates.contains(name) {
+                expand_var(name, env, templates, done, stack).map_err(|e| anyhow::anyhow!("env {key}: {e}"))?
+            } else {
+                env.get(name).cloned().or_else(|| std::env::var(name).ok()).unwrap_or_else(|| {
+                    eprintln!("warning: env {key}: ${{{name}}} is not set, using an empty value");
+                    String::new()
+                })
+            };
+            out.push_str(&value);
+            rest = &tail[end + 1..];
+        } else {
+            out.push('$');
+            rest = after;
         }
-        for (name, port) in &ports.named {
-            env.insert(
-                format!("DRIFT_PORT_{}", name.to_uppercase()),
-                port.to_string(),
-            );
+    }
+    out.push_str(rest);
+
+    stack.pop();
+    done.insert(key.to_string(), out.clone());
+    Ok(out)
+}
+
+/// Activate the project's toolchain: a virtualenv and node install go first
+/// on PATH, the rust toolchain is selected through `RUSTUP_TOOLCHAIN`.
+/// A node version that isn't installed is left out rather than failing the open.
+fn apply_toolchain(
+    env: &mut HashMap<String, String>,
+    toolchain: &ToolchainConfig,
+    repo_path: &Path,
+    node_roots: &[PathBuf],
+) {
+    let mut path_prefix: Vec<PathBuf> = Vec::new();
+
+    if let Some(venv) = &toolchain.python {
+        let venv_path = repo_path.join(venv);
+        env.insert("VIRTUAL_ENV".into(), venv_path.to_string_lossy().into());
+        path_prefix.push(venv_path.join("bin"));
+    }
+    if let Some(bin) = toolchain.node.as_deref().and_then(|v| find_node_bin(node_roots, v)) {
+        path_prefix.push(bin);
+    }
+    if let Some(rust) = &toolchain.rust {
+        env.insert("RUSTUP_TOOLCHAIN".into(), rust.clone());
+    }
+
+    if !path_prefix.is_empty() {
+        let base = env
+            .get("PATH")
+            .cloned()
+            .or_else(|| std::env::var("PATH").ok())
+            .unwrap_or_default();
+        let mut parts: Vec<String> = path_prefix.iter().map(|p| p.to_string_lossy().into()).collect();
+        if !base.is_empty() {
+            parts.push(base);
         }
+        env.insert("PATH".into(), parts.join(":"));
     }
+}
 
-    Ok(env)
+/// Directories holding one `v<version>` directory per installed node (fnm, then nvm).
+fn node_install_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    match std::env::var_os("FNM_DIR") {
+        Some(dir) => roots.push(PathBuf::from(dir).join("node-versions")),
+        None => {
+            if let Some(data) = dirs::data_dir() {
+                roots.push(data.join("fnm").join("node-versions"));
+            }
+        }
+    }
+    match std::env::var_os("NVM_DIR") {
+        Some(dir) => roots.push(PathBuf::from(dir).join("versions").join("node")),
+        None => {
+            if let Some(home) = dirs::home_dir() {
+                roots.push(home.join(".nvm").join("versions").join("node"));
+            }
+        }
+    }
+    roots
+}
+
+/// `bin` directory of the newest installed node matching a version prefix
+/// ("20" matches v20.11.1 but not v200.0.0).
+fn find_node_bin(roots: &[PathBuf], version: &str) -> Option<PathBuf> {
+    let wanted: Vec<u64> = version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        let newest = entries
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                let parts: Vec<u64> = name
+                    .strip_prefix('v')?
+                    .split('.')
+                    .map(|p| p.parse().ok())
+                    .collect::<Option<_>>()?;
+                parts.starts_with(&wanted).then_some((parts, e.path()))
+            })
+            .max_by(|a, b| a.0.cmp(&b.0));
+        if let Some((_, dir)) = newest {
+            // fnm nests the tree under installation/, nvm doesn't
+            let fnm_bin = dir.join("installation").join("bin");
+            return Some(if fnm_bin.is_dir() { fnm_bin } else { dir.join("bin") });
+        }
+    }
+    None
 }
 
 /// Build additional env vars for dispatched tasks.
@@ -98,6 +323,85 @@ pub fn format_env_exports(env: &HashMap<String, String>) -> String {
         .join("\n")
 }
 
+/// Output formats for `drift env export`.
+pub const EXPORT_FORMATS: &[&str] = &["dotenv", "direnv", "fish"];
+
+/// Render `env` as a dotenv file, an `.envrc`, or fish `set -gx` lines,
+/// sorted by name.
+pub fn format_env(env: &HashMap<String, String>, format: &str) -> anyhow::Result<String> {
+    let line: fn(&str, &str) -> String = match format {
+        "dotenv" => |k, v| {
+            let escaped = v.replace('\\', "\\\\").replace('"', "\\\"").replace('$', "\\$").replace('\n', "\\n");
+            format!("{k}=\"{escaped}\"")
+        },
+        "direnv" => |k, v| format!("export {k}='{}'", v.replace('\'', "'\"'\"'")),
+        "fish" => |k, v| format!("set -gx {k} '{}'", v.replace('\\', "\\\\").replace('\'', "\\'")),
+        _ => anyhow::bail!("unknown format '{format}' (expected one of {})", EXPORT_FORMATS.join(", ")),
+    };
+    let mut keys: Vec<&String> = env.keys().collect();
+    keys.sort();
+    Ok(keys.iter().map(|k| line(k, &env[*k])).collect::<Vec<_>>().join("\n"))
+}
+
+/// Variables from a dotenv file or an `.envrc`, in file order, plus the
+/// lines that aren't plain assignments (direnv's `use nix`, `PATH_add`,
+/// `dotenv` ...), which have no `[env]` equivalent.
+pub fn parse_env_file(contents: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let mut vars = Vec::new();
+    let mut skipped = Vec::new();
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let assignment = trimmed.strip_prefix("export ").unwrap_or(trimmed).trim_start();
+        let parsed = assignment.split_once('=').and_then(|(key, value)| {
+            let key = key.trim();
+            let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return None;
+            }
+            unquote(value.trim()).map(|value| (key.to_string(), value))
+        });
+        match parsed {
+            Some(var) => vars.push(var),
+            _ => skipped.push(trimmed.to_string()),
+        }
+    }
+    (vars, skipped)
+}
+
+/// A value as the shell would see it: single quotes literal, double quotes
+/// with backslash escapes, bare values up to a ` #` comment. None for
+/// anything that needs a shell to evaluate (`$VAR`, `$(cmd)`).
+fn unquote(value: &str) -> Option<String> {
+    if let Some(inner) = value.strip_prefix('\'') {
+        return inner.strip_suffix('\'').map(str::to_string);
+    }
+    if let Some(inner) = value.strip_prefix('"') {
+        let inner = inner.strip_suffix('"')?;
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next()? {
+                    'n' => out.push('\n'),
+                    other => out.push(other),
+                },
+                '$' => return None,
+                c => out.push(c),
+            }
+        }
+        return Some(out);
+    }
+    let bare = value.split(" #").next().unwrap_or("").trim();
+    if bare.contains('$') || bare.contains('`') {
+        return None;
+    }
+    Some(bare.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,15 +417,25 @@ mod tests {
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: None,
             windows: vec![],
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         }
     }
 
@@ -135,6 +449,14 @@ mod tests {
         assert!(env.contains_key("DRIFT_NOTIFY_SOCK"));
     }
 
+    #[test]
+    fn build_env_leaves_ssh_alone_without_a_running_agent() {
+        let mut project = minimal_project("drift-test-no-agent", "/tmp/myapp");
+        assert!(!build_env(&project).unwrap().contains_key("SSH_AUTH_SOCK"));
+        project.ssh = Some(crate::config::SshConfig { key: "~/.ssh/id_client".into() });
+        assert!(!build_env(&project).unwrap().contains_key("SSH_AUTH_SOCK"));
+    }
+
     #[test]
     fn build_env_with_folder() {
         let mut project = minimal_project("myapp", "/tmp/myapp");
@@ -227,4 +549,120 @@ mod tests {
         assert_eq!(env.get("DRIFT_PORT_RANGE_END").unwrap(), "3010");
         assert_eq!(env.get("DRIFT_PORT_API").unwrap(), "3001");
     }
+
+    #[test]
+    fn toolchain_sets_venv_rust_and_path() {
+        let mut env = HashMap::new();
+        env.insert("PATH".to_string(), "/usr/bin".to_string());
+        let toolchain = ToolchainConfig {
+            python: Some(".venv".into()),
+            node: None,
+            rust: Some("nightly".into()),
+        };
+        apply_toolchain(&mut env, &toolchain, Path::new("/code/app"), &[]);
+        assert_eq!(env["VIRTUAL_ENV"], "/code/app/.venv");
+        assert_eq!(env["RUSTUP_TOOLCHAIN"], "nightly");
+        assert_eq!(env["PATH"], "/code/app/.venv/bin:/usr/bin");
+    }
+
+    #[test]
+    fn find_node_bin_picks_newest_matching_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let fnm = tmp.path().join("fnm");
+        let nvm = tmp.path().join("nvm");
+        for v in ["v18.19.0", "v20.9.0", "v20.11.1", "v200.0.0"] {
+            std::fs::create_dir_all(fnm.join(v).join("installation").join("bin")).unwrap();
+        }
+        std::fs::create_dir_all(nvm.join("v22.1.0").join("bin")).unwrap();
+        let roots = [fnm.clone(), nvm.clone()];
+
+        assert_eq!(find_node_bin(&roots, "20"), Some(fnm.join("v20.11.1/installation/bin")));
+        assert_eq!(find_node_bin(&roots, "v20.9"), Some(fnm.join("v20.9.0/installation/bin")));
+        assert_eq!(find_node_bin(&roots, "22"), Some(nvm.join("v22.1.0/bin")));
+        assert_eq!(find_node_bin(&roots, "16"), None);
+        assert_eq!(find_node_bin(&roots, "lts"), None);
+    }
+
+    #[test]
+    fn env_files_parse_like_a_shell() {
+        let contents = r#"
+# comment
+export API_URL=https://api.example.com
+NAME='single $quoted'
+GREETING="hello \"world\"\nbye"
+DEBUG=1 # inline comment
+use nix
+PATH_add bin
+HOME_DIR=$HOME
+"#;
+        let (vars, skipped) = parse_env_file(contents);
+        assert_eq!(
+            vars,
+            vec![
+                ("API_URL".to_string(), "https://api.example.com".to_string()),
+                ("NAME".to_string(), "single $quoted".to_string()),
+                ("GREETING".to_string(), "hello \"world\"\nbye".to_string()),
+                ("DEBUG".to_string(), "1".to_string()),
+            ]
+        );
+        assert_eq!(skipped, vec!["use nix", "PATH_add bin", "HOME_DIR=$HOME"]);
+    }
+
+    #[test]
+    fn env_formats_round_trip() {
+        let env = HashMap::from([
+            ("B".to_string(), "it's \"x\"".to_string()),
+            ("A".to_string(), "1".to_string()),
+        ]);
+        assert_eq!(format_env(&env, "dotenv").unwrap(), "A=\"1\"\nB=\"it's \\\"x\\\"\"");
+        assert_eq!(format_env(&env, "direnv").unwrap(), "export A='1'\nexport B='it'\"'\"'s \"x\"'");
+        assert_eq!(format_env(&env, "fish").unwrap(), "set -gx A '1'\nset -gx B 'it\\'s \"x\"'");
+        assert!(format_env(&env, "toml").is_err());
+
+        let (vars, _) = parse_env_file(&format_env(&env, "dotenv").unwrap());
+        assert_eq!(vars.into_iter().collect::<HashMap<_, _>>(), env);
+    }
+
+    #[test]
+    fn env_interpolates_ports_and_other_vars() {
+        let mut project = minimal_project("myapp", "/tmp/myapp");
+        project.ports = Some(crate::config::ProjectPorts {
+            range: None,
+            named: HashMap::from([("db".to_string(), 5433)]),
+        });
+        project.env.vars.insert("DATABASE_URL".into(), "postgres://${DB_HOST}:${DRIFT_PORT_DB}/app".into());
+        project.env.vars.insert("LITERAL".into(), "$${NOT_EXPANDED} costs $5".into());
+        let global = HashMap::from([
+            ("DB_HOST".to_string(), "localhost".to_string()),
+            ("EDITOR".to_string(), "vim".to_string()),
+        ]);
+        let env = build_env_with_global(&project, &global).unwrap();
+        assert_eq!(env["DATABASE_URL"], "postgres://localhost:5433/app");
+        assert_eq!(env["LITERAL"], "${NOT_EXPANDED} costs $5");
+        assert_eq!(env["EDITOR"], "vim");
+    }
+
+    #[test]
+    fn project_env_overrides_global() {
+        let mut project = minimal_project("myapp", "/tmp/myapp");
+        project.env.vars.insert("LOG".into(), "debug".into());
+        let global = HashMap::from([("LOG".to_string(), "info".to_string())]);
+        assert_eq!(build_env_with_global(&project, &global).unwrap()["LOG"], "debug");
+    }
+
+    #[test]
+    fn unset_variable_interpolates_empty() {
+        let mut project = minimal_project("myapp", "/tmp/myapp");
+        project.env.vars.insert("URL".into(), "http://${DRIFT_TEST_SURELY_UNSET}/".into());
+        assert_eq!(build_env_with_global(&project, &HashMap::new()).unwrap()["URL"], "http:///");
+    }
+
+    #[test]
+    fn env_interpolation_errors() {
+        let mut project = minimal_project("myapp", "/tmp/myapp");
+        project.env.vars.insert("A".into(), "${B}".into());
+        project.env.vars.insert("B".into(), "${A}".into());
+        let err = build_env_with_global(&project, &HashMap::new()).unwrap_err().to_string();
+        assert!(err.contains("refer to each other"), "{err}");
+    }
 }
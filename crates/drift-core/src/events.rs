@@ -1,27 +1,48 @@
+use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::os::unix::net::UnixStream;
-use serde::{Deserialize, Serialize};
-use time::OffsetDateTime;
+use std::path::{Path, PathBuf};
+use time::{Date, Duration, Month, OffsetDateTime};
 use time::format_description::well_known::Rfc3339;
 use crate::paths;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Event {
-    #[serde(rename = "type")]
-    pub event_type: String,
-    pub project: String,
-    pub source: String,
-    pub ts: String,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub level: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub title: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub body: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub meta: Option<serde_json::Value>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub priority: Option<String>,
+pub use drift_client::{matches_type_filter, priority_rank, Event, PRIORITIES, SCHEMA_VERSION};
+
+/// Longest line the emit socket accepts, in bytes.
+pub const MAX_EVENT_BYTES: usize = 64 * 1024;
+const MAX_TITLE_CHARS: usize = 256;
+pub const MAX_BODY_CHARS: usize = 8 * 1024;
+/// Beyond this the spool drops new events rather than fill the disk while
+/// the daemon stays down.
+const MAX_SPOOLED_EVENTS: usize = 1000;
+
+pub const LEVELS: &[&str] = &["info", "success", "warning", "error"];
+
+const FIELDS: &[&str] = &[
+    "schema", "type", "project", "source", "ts", "level", "title", "body", "meta", "correlation_id",
+    "priority",
+];
+
+/// Set for processes drift starts on behalf of a run (services, agents), so
+/// events they emit with `drift notify` join the run's thread.
+pub const CORRELATION_ENV: &str = "DRIFT_CORRELATION_ID";
+const MAX_CORRELATION_ID_CHARS: usize = 128;
+
+/// A new id for a group of related events: milliseconds since the epoch and
+/// the process id, with a counter for ids made in the same millisecond.
+pub fn new_correlation_id() -> String {
+    static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    format!("{millis:x}-{:x}-{n:x}", std::process::id())
+}
+
+/// The correlation id inherited from the environment, if drift set one.
+pub fn inherited_correlation_id() -> Option<String> {
+    std::env::var(CORRELATION_ENV).ok().filter(|id| !id.is_empty())
 }
 
 pub fn iso_now() -> String {
@@ -38,8 +59,238 @@ pub fn emit_event(event: &Event) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Send an event and wait for the daemon's reply, so a rejected event or an
+/// unresponsive daemon is an error. Returns the id the daemon assigned.
+pub fn emit_event_acked(event: &Event) -> anyhow::Result<u64> {
+    drift_client::emit_event(event)
+}
+
+/// Emit without failing the caller. When the daemon isn't listening the
+/// event goes to the spool, to be replayed when it next starts.
 pub fn try_emit_event(event: &Event) {
-    let _ = emit_event(event);
+    if emit_event(event).is_err() {
+        let _ = spool_event(event);
+    }
+}
+
+/// Persist an event for the daemon to pick up later. Written under a
+/// temporary name and renamed so a drain never sees half a file.
+pub fn spool_event(event: &Event) -> anyhow::Result<()> {
+    spool_to(&paths::event_spool_dir(), event)
+}
+
+fn spool_to(dir: &Path, event: &Event) -> anyhow::Result<()> {
+    static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+    fs::create_dir_all(dir)?;
+    if spooled_files(dir)?.len() >= MAX_SPOOLED_EVENTS {
+        anyhow::bail!("event spool is full ({MAX_SPOOLED_EVENTS} events)");
+    }
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    // Zero-padded so file names sort in emit order
+    let name = format!("{nanos:020}-{}-{n}", std::process::id());
+    let tmp = dir.join(format!("{name}.tmp"));
+    fs::write(&tmp, serde_json::to_string(event)?)?;
+    fs::rename(&tmp, dir.join(format!("{name}.json")))?;
+    Ok(())
+}
+
+/// Take every spooled event, oldest first, removing the files. Each keeps
+/// its original `ts` and gets `meta.spooled = true`, so consumers can tell
+/// it arrived late. Events that no longer parse are dropped.
+pub fn drain_spool() -> Vec<Event> {
+    drain_spool_dir(&paths::event_spool_dir())
+}
+
+fn drain_spool_dir(dir: &Path) -> Vec<Event> {
+    let Ok(files) = spooled_files(dir) else {
+        return Vec::new();
+    };
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let event = fs::read_to_string(&path).ok().and_then(|line| parse_emitted(&line).ok());
+            let _ = fs::remove_file(&path);
+            let mut event = event?;
+            if let Some(meta) = event.meta.get_or_insert_with(|| serde_json::json!({})).as_object_mut() {
+                meta.insert("spooled".into(), true.into());
+            }
+            Some(event)
+        })
+        .collect()
+}
+
+fn spooled_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Append an event to today's archive in the event log directory.
+pub fn append_to_log(event: &Event) -> anyhow::Result<()> {
+    let dir = paths::event_log_dir();
+    fs::create_dir_all(&dir)?;
+    let today = OffsetDateTime::now_utc().date();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(format!("{today}.jsonl")))?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Delete event log archives older than `retention_days`. 0 keeps everything.
+pub fn prune_event_log(retention_days: u32) -> anyhow::Result<Vec<PathBuf>> {
+    let dir = paths::event_log_dir();
+    if retention_days == 0 || !dir.exists() {
+        return Ok(Vec::new());
+    }
+    prune_log_dir(&dir, OffsetDateTime::now_utc().date(), retention_days)
+}
+
+fn prune_log_dir(dir: &Path, today: Date, retention_days: u32) -> anyhow::Result<Vec<PathBuf>> {
+    let cutoff = today - Duration::days(i64::from(retention_days));
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "jsonl") {
+            continue;
+        }
+        let Some(date) = path.file_stem().and_then(|s| s.to_str()).and_then(parse_log_date) else {
+            continue;
+        };
+        if date < cutoff {
+            fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+/// Delete `drift notify --file` attachments older than `retention_days`,
+/// across every project's state dir. 0 keeps everything.
+pub fn prune_attachments(retention_days: u32) -> anyhow::Result<Vec<PathBuf>> {
+    let base = paths::state_base_dir();
+    if retention_days == 0 || !base.exists() {
+        return Ok(Vec::new());
+    }
+    let max_age = std::time::Duration::from_secs(u64::from(retention_days) * 86_400);
+    let cutoff = std::time::SystemTime::now() - max_age;
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(&base)? {
+        let dir = entry?.path().join("attachments");
+        if dir.is_dir() {
+            removed.extend(prune_attachment_dir(&dir, cutoff)?);
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+fn prune_attachment_dir(dir: &Path, cutoff: std::time::SystemTime) -> anyhow::Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let modified = entry.metadata()?.modified()?;
+        if entry.file_type()?.is_file() && modified < cutoff {
+            fs::remove_file(entry.path())?;
+            removed.push(entry.path());
+        }
+    }
+    Ok(removed)
+}
+
+fn parse_log_date(stem: &str) -> Option<Date> {
+    let mut parts = stem.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// Events allowed and the period they refill over, from an `[events.limits]`
+/// value like `"10/min"` (periods: `sec`, `min`, `hour`).
+pub fn parse_rate_limit(spec: &str) -> Option<(u32, std::time::Duration)> {
+    let (count, period) = spec.split_once('/')?;
+    let count: u32 = count.trim().parse().ok().filter(|n| *n > 0)?;
+    let secs = match period.trim() {
+        "s" | "sec" | "second" => 1,
+        "m" | "min" | "minute" => 60,
+        "h" | "hour" => 3600,
+        _ => return None,
+    };
+    Some((count, std::time::Duration::from_secs(secs)))
+}
+
+/// Parse one line received on the emit socket, rejecting anything the
+/// daemon would otherwise have to guess about: unknown fields, a newer
+/// schema, malformed types and levels, oversized text.
+pub fn parse_emitted(line: &str) -> anyhow::Result<Event> {
+    if line.len() > MAX_EVENT_BYTES {
+        anyhow::bail!("event is {} bytes, limit is {MAX_EVENT_BYTES}", line.len());
+    }
+    let value: serde_json::Value = serde_json::from_str(line)?;
+    let Some(object) = value.as_object() else {
+        anyhow::bail!("event must be a JSON object");
+    };
+    if let Some(key) = object.keys().find(|k| !FIELDS.contains(&k.as_str())) {
+        anyhow::bail!("unknown field '{key}'");
+    }
+    let event: Event = serde_json::from_value(value)?;
+    validate(&event)?;
+    Ok(event)
+}
+
+pub fn validate(event: &Event) -> anyhow::Result<()> {
+    if event.schema == 0 || event.schema > SCHEMA_VERSION {
+        anyhow::bail!("unsupported schema version {} (this daemon speaks {SCHEMA_VERSION})", event.schema);
+    }
+    if !is_valid_type(&event.event_type) {
+        anyhow::bail!(
+            "invalid event type '{}' (expected namespace.verb, lowercase, e.g. build.failed)",
+            event.event_type
+        );
+    }
+    if let Some(level) = event.level.as_deref() {
+        if !LEVELS.contains(&level) {
+            anyhow::bail!("invalid level '{level}' (expected one of {})", LEVELS.join(", "));
+        }
+    }
+    if event.title.as_deref().is_some_and(|t| t.chars().count() > MAX_TITLE_CHARS) {
+        anyhow::bail!("title is longer than {MAX_TITLE_CHARS} characters");
+    }
+    if event.body.as_deref().is_some_and(|b| b.chars().count() > MAX_BODY_CHARS) {
+        anyhow::bail!("body is longer than {MAX_BODY_CHARS} characters");
+    }
+    if event.correlation_id.as_deref().is_some_and(|id| id.is_empty() || id.chars().count() > MAX_CORRELATION_ID_CHARS) {
+        anyhow::bail!("correlation_id must be 1 to {MAX_CORRELATION_ID_CHARS} characters");
+    }
+    if event.meta.as_ref().is_some_and(|m| !m.is_object()) {
+        anyhow::bail!("meta must be a JSON object");
+    }
+    Ok(())
+}
+
+/// `namespace.verb`: two or more dot-separated segments of `[a-z0-9_-]`,
+/// starting with a letter.
+fn is_valid_type(event_type: &str) -> bool {
+    let mut segments = event_type.split('.');
+    let starts_with_letter = event_type.starts_with(|c: char| c.is_ascii_lowercase());
+    starts_with_letter
+        && event_type.contains('.')
+        && segments.all(|s| {
+            !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        })
 }
 
 #[cfg(test)]
@@ -48,29 +299,20 @@ mod tests {
 
     fn minimal_event() -> Event {
         Event {
-            event_type: "build.complete".into(),
-            project: "myapp".into(),
-            source: "ci".into(),
             ts: "2026-01-15T10:30:00Z".into(),
-            level: None,
-            title: None,
-            body: None,
-            meta: None,
-            priority: None,
+            ..Event::new("build.complete", "myapp", "ci")
         }
     }
 
     fn full_event() -> Event {
         Event {
-            event_type: "build.complete".into(),
-            project: "myapp".into(),
-            source: "ci".into(),
             ts: "2026-01-15T10:30:00Z".into(),
-            level: Some("info".into()),
-            title: Some("Build succeeded".into()),
-            body: Some("All 42 tests passed".into()),
-            meta: Some(serde_json::json!({"duration_ms": 1234})),
             priority: Some("high".into()),
+            ..Event::new("build.complete", "myapp", "ci")
+                .level("info")
+                .title("Build succeeded")
+                .body("All 42 tests passed")
+                .meta(serde_json::json!({"duration_ms": 1234}))
         }
     }
 
@@ -155,4 +397,118 @@ mod tests {
         assert_eq!(meta["tags"][0], "v1.0");
         assert_eq!(meta["tags"][1], "latest");
     }
+
+    #[test]
+    fn prune_removes_only_expired_archives() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["2026-01-01.jsonl", "2026-01-10.jsonl", "2026-01-15.jsonl", "notes.jsonl", "2026-01-01.txt"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let today = Date::from_calendar_date(2026, Month::January, 15).unwrap();
+
+        let removed = prune_log_dir(dir.path(), today, 7).unwrap();
+        assert_eq!(removed, vec![dir.path().join("2026-01-01.jsonl")]);
+        assert!(dir.path().join("2026-01-10.jsonl").exists());
+        assert!(dir.path().join("notes.jsonl").exists());
+        assert!(dir.path().join("2026-01-01.txt").exists());
+    }
+
+    #[test]
+    fn old_attachments_pruned() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("1-report.txt"), "x").unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        assert!(prune_attachment_dir(dir.path(), std::time::SystemTime::UNIX_EPOCH).unwrap().is_empty());
+        assert_eq!(prune_attachment_dir(dir.path(), later).unwrap(), vec![dir.path().join("1-report.txt")]);
+        assert!(!dir.path().join("1-report.txt").exists());
+    }
+
+    #[test]
+    fn spool_drains_in_emit_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for event_type in ["service.crashed", "service.restarted"] {
+            let event = Event { event_type: event_type.into(), ..minimal_event() };
+            spool_to(dir.path(), &event).unwrap();
+        }
+        std::fs::write(dir.path().join("00-garbage.json"), "not json").unwrap();
+        std::fs::write(dir.path().join("99-partial.tmp"), "{").unwrap();
+
+        let drained = drain_spool_dir(dir.path());
+        let types: Vec<_> = drained.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["service.crashed", "service.restarted"]);
+        assert_eq!(drained[0].meta.as_ref().unwrap()["spooled"], true);
+        assert_eq!(drained[0].ts, minimal_event().ts);
+        assert!(drain_spool_dir(dir.path()).is_empty());
+        assert!(dir.path().join("99-partial.tmp").exists());
+    }
+
+    #[test]
+    fn rate_limits_parsed() {
+        assert_eq!(parse_rate_limit("10/min"), Some((10, std::time::Duration::from_secs(60))));
+        assert_eq!(parse_rate_limit("1 / hour"), Some((1, std::time::Duration::from_secs(3600))));
+        assert_eq!(parse_rate_limit("0/sec"), None);
+        assert_eq!(parse_rate_limit("10/day"), None);
+        assert_eq!(parse_rate_limit("lots"), None);
+    }
+
+    #[test]
+    fn parse_log_date_rejects_garbage() {
+        assert!(parse_log_date("2026-02-30").is_none());
+        assert!(parse_log_date("latest").is_none());
+        assert_eq!(
+            parse_log_date("2026-03-04"),
+            Some(Date::from_calendar_date(2026, Month::March, 4).unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_emitted_accepts_valid_event() {
+        let event = parse_emitted(r#"{"type":"build.failed","project":"myapp","source":"ci","ts":"","level":"error"}"#)
+            .unwrap();
+        assert_eq!(event.event_type, "build.failed");
+        assert_eq!(event.schema, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn parse_emitted_rejects_malformed_events() {
+        let base = r#""project":"myapp","source":"ci","ts":"""#;
+        let err = |line: String| parse_emitted(&line).unwrap_err().to_string();
+
+        assert_eq!(err(format!(r#"{{"type":"build.failed",{base},"tite":"x"}}"#)), "unknown field 'tite'");
+        assert!(err(format!(r#"{{"type":"Build",{base}}}"#)).starts_with("invalid event type 'Build'"));
+        assert!(err(format!(r#"{{"type":"build.failed",{base},"level":"warn"}}"#)).starts_with("invalid level 'warn'"));
+        assert!(err(format!(r#"{{"type":"build.failed",{base},"schema":2}}"#)).starts_with("unsupported schema version 2"));
+        assert!(err(format!(r#"{{"type":"build.failed",{base},"meta":[1]}}"#)).starts_with("meta must be"));
+        assert!(err("[1]".into()).starts_with("event must be"));
+
+        let long_title = "x".repeat(MAX_TITLE_CHARS + 1);
+        assert!(err(format!(r#"{{"type":"build.failed",{base},"title":"{long_title}"}}"#)).starts_with("title is longer"));
+        assert!(err("x".repeat(MAX_EVENT_BYTES + 1)).contains("limit is"));
+    }
+
+    #[test]
+    fn event_type_naming() {
+        assert!(is_valid_type("build.failed"));
+        assert!(is_valid_type("drift.project.opened"));
+        assert!(is_valid_type("project.closed_dirty"));
+        assert!(!is_valid_type("notification"));
+        assert!(!is_valid_type("build."));
+        assert!(!is_valid_type(".failed"));
+        assert!(!is_valid_type("Build.Failed"));
+        assert!(!is_valid_type("1build.failed"));
+    }
+
+    #[test]
+    fn missing_schema_reads_as_version_one() {
+        let event: Event = serde_json::from_str(r#"{"type":"a.b","project":"p","source":"s","ts":""}"#).unwrap();
+        assert_eq!(event.schema, 1);
+    }
+
+    #[test]
+    fn correlation_ids_are_unique() {
+        let a = new_correlation_id();
+        let b = new_correlation_id();
+        assert_ne!(a, b);
+        assert!(a.len() <= MAX_CORRELATION_ID_CHARS);
+    }
 }
@@ -0,0 +1,126 @@
+//! How long each project's workspace had focus, per local day. The daemon
+//! adds to the ledger as focus moves; `drift time` reads it.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// Seconds of focus by day (`YYYY-MM-DD`, local time), then project.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ledger {
+    pub days: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl Ledger {
+    pub fn add(&mut self, day: &str, project: &str, secs: u64) {
+        *self.days.entry(day.to_string()).or_default().entry(project.to_string()).or_default() += secs;
+    }
+
+    /// Focus time per project over `days`.
+    pub fn totals(&self, days: &[String]) -> BTreeMap<String, u64> {
+        let mut totals = BTreeMap::new();
+        for day in days {
+            for (project, secs) in self.days.get(day).into_iter().flatten() {
+                *totals.entry(project.clone()).or_default() += secs;
+            }
+        }
+        totals
+    }
+}
+
+pub fn load() -> Ledger {
+    fs::read_to_string(paths::focus_time_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Add `secs` of focus to `project` on the local day of `ts`.
+pub fn record(project: &str, secs: u64, ts: i64) -> anyhow::Result<()> {
+    let mut ledger = load();
+    ledger.add(&local_date(ts).0, project, secs);
+    save(&ledger)
+}
+
+/// Remove `secs` already recorded for `project` on the local day of `ts`,
+/// for time that turned out to be idle.
+pub fn take_back(project: &str, secs: u64, ts: i64) -> anyhow::Result<()> {
+    let mut ledger = load();
+    if let Some(total) = ledger.days.get_mut(&local_date(ts).0).and_then(|d| d.get_mut(project)) {
+        *total = total.saturating_sub(secs);
+        save(&ledger)?;
+    }
+    Ok(())
+}
+
+fn save(ledger: &Ledger) -> anyhow::Result<()> {
+    let path = paths::focus_time_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string(ledger)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// The local day of `ts` as `YYYY-MM-DD`, with its weekday (0 = Sunday).
+pub fn local_date(ts: i64) -> (String, u32) {
+    let t = ts as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+        unsafe { libc::gmtime_r(&t, &mut tm) };
+    }
+    let date = format!("{:04}-{:02}-{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday);
+    (date, tm.tm_wday as u32)
+}
+
+/// The last `n` local days up to the one containing `now`, oldest first.
+pub fn recent_days(now: i64, n: usize) -> Vec<(String, u32)> {
+    // Half-day steps never skip a day, even one shortened by DST
+    let mut days = Vec::with_capacity(n);
+    let mut ts = now;
+    while days.len() < n {
+        let day = local_date(ts);
+        if days.last().is_none_or(|(d, _): &(String, u32)| *d != day.0) {
+            days.push(day);
+        }
+        ts -= 12 * 3600;
+    }
+    days.reverse();
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_cover_only_the_given_days() {
+        let mut ledger = Ledger::default();
+        ledger.add("2026-03-02", "api", 600);
+        ledger.add("2026-03-02", "api", 60);
+        ledger.add("2026-03-03", "api", 100);
+        ledger.add("2026-03-03", "web", 30);
+        ledger.add("2026-03-09", "web", 999);
+
+        let totals = ledger.totals(&["2026-03-02".into(), "2026-03-03".into()]);
+        assert_eq!(totals.get("api"), Some(&760));
+        assert_eq!(totals.get("web"), Some(&30));
+    }
+
+    #[test]
+    fn recent_days_are_distinct_and_end_today() {
+        let now = 1_700_000_000;
+        let days = recent_days(now, 7);
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[6].0, local_date(now).0);
+        let mut names: Vec<&String> = days.iter().map(|(d, _)| d).collect();
+        names.dedup();
+        assert_eq!(names.len(), 7);
+        assert!(names.windows(2).all(|w| w[0] < w[1]));
+    }
+}
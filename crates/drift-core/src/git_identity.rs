@@ -0,0 +1,90 @@
+//! The `[git]` identity a project's repo commits with: user, email and
+//! signing key, written to the repo's local git config on `drift open` and
+//! checked by `drift doctor`.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context};
+
+use crate::config::GitConfig;
+
+/// `git config` keys and values the project wants set locally.
+pub fn expected(git: &GitConfig) -> Vec<(&'static str, String)> {
+    let mut settings = Vec::new();
+    if let Some(name) = &git.user_name {
+        settings.push(("user.name", name.clone()));
+    }
+    if let Some(email) = &git.user_email {
+        settings.push(("user.email", email.clone()));
+    }
+    if let Some(key) = &git.signing_key {
+        settings.push(("user.signingkey", key.clone()));
+    }
+    // Set either way, so a global `commit.gpgsign` can't sign with the wrong key
+    if let Some(sign) = git.commit_sign {
+        settings.push(("commit.gpgsign", sign.to_string()));
+    }
+    settings
+}
+
+pub fn apply(repo: &Path, git: &GitConfig) -> anyhow::Result<()> {
+    for (key, value) in expected(git) {
+        let status = Command::new("git")
+            .args(["config", "--local", key, &value])
+            .current_dir(repo)
+            .status()
+            .with_context(|| format!("setting git {key}"))?;
+        if !status.success() {
+            bail!("git config {key} failed in {}", repo.display());
+        }
+    }
+    Ok(())
+}
+
+/// Settings whose local value differs from the config: key, expected,
+/// and what the repo has (None when unset).
+pub fn mismatches(repo: &Path, git: &GitConfig) -> Vec<(&'static str, String, Option<String>)> {
+    expected(git)
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let actual = local_value(repo, key);
+            (actual.as_deref() != Some(value.as_str())).then_some((key, value, actual))
+        })
+        .collect()
+}
+
+fn local_value(repo: &Path, key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "--local", "--get", key])
+        .current_dir(repo)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_configured_settings_expected() {
+        let git = GitConfig {
+            user_name: None,
+            user_email: Some("me@client.example".into()),
+            signing_key: Some("ABCD1234".into()),
+            commit_sign: Some(false),
+        };
+        assert_eq!(
+            expected(&git),
+            vec![
+                ("user.email", "me@client.example".to_string()),
+                ("user.signingkey", "ABCD1234".to_string()),
+                ("commit.gpgsign", "false".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Hyprland backend: requests over `.socket.sock` in the instance's runtime
+//! directory, one connection per request, as `hyprctl` does.
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+
+use crate::compositor::{Compositor, SizeChange, Window, Workspace};
+use crate::shell;
+
+pub struct HyprlandClient {
+    socket: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct HyprWorkspace {
+    id: i64,
+    name: String,
+    monitor: String,
+    #[serde(default, rename = "lastwindow")]
+    last_window: String,
+    #[serde(default)]
+    windows: u32,
+}
+
+#[derive(Deserialize)]
+struct HyprMonitor {
+    name: String,
+    focused: bool,
+    #[serde(rename = "activeWorkspace")]
+    active_workspace: WorkspaceRef,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceRef {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+struct HyprClient {
+    address: String,
+    #[serde(default = "default_true")]
+    mapped: bool,
+    size: (f64, f64),
+    workspace: WorkspaceRef,
+    floating: bool,
+    class: String,
+    title: String,
+    pid: i32,
+    #[serde(rename = "focusHistoryID")]
+    focus_history_id: i32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl HyprlandClient {
+    pub fn connect() -> anyhow::Result<Self> {
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+            .context("HYPRLAND_INSTANCE_SIGNATURE is not set; is Hyprland running?")?;
+        let runtime = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+        // Hyprland moved its sockets from /tmp to the runtime dir in 0.40
+        let candidates = runtime
+            .map(|dir| dir.join("hypr"))
+            .into_iter()
+            .chain([PathBuf::from("/tmp/hypr")])
+            .map(|dir| dir.join(&signature).join(".socket.sock"));
+        for socket in candidates {
+            if socket.exists() {
+                return Ok(Self { socket });
+            }
+        }
+        bail!("no Hyprland socket found for instance '{signature}'")
+    }
+
+    fn request(&self, request: &str) -> anyhow::Result<String> {
+        let mut stream = UnixStream::connect(&self.socket).context("connecting to Hyprland socket")?;
+        stream.write_all(request.as_bytes())?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)?;
+        Ok(reply)
+    }
+
+    fn query<T: serde::de::DeserializeOwned>(&self, what: &str) -> anyhow::Result<T> {
+        let reply = self.request(&format!("j/{what}"))?;
+        serde_json::from_str(&reply).with_context(|| format!("parsing Hyprland {what}"))
+    }
+
+    fn dispatch(&self, args: &str) -> anyhow::Result<()> {
+        match self.request(&format!("dispatch {args}"))?.trim() {
+            "ok" => Ok(()),
+            err => bail!("hyprland error: {err}"),
+        }
+    }
+
+    fn clients(&self) -> anyhow::Result<Vec<HyprClient>> {
+        let clients: Vec<HyprClient> = self.query("clients")?;
+        Ok(clients.into_iter().filter(|c| c.mapped).collect())
+    }
+
+    fn size_of(&self, id: u64) -> anyhow::Result<(i64, i64)> {
+        let client = self
+            .clients()?
+            .into_iter()
+            .find(|c| parse_address(&c.address) == Some(id))
+            .with_context(|| format!("window {} not found", address(id)))?;
+        Ok((client.size.0 as i64, client.size.1 as i64))
+    }
+}
+
+impl Compositor for HyprlandClient {
+    fn workspaces(&mut self) -> anyhow::Result<Vec<Workspace>> {
+        let workspaces: Vec<HyprWorkspace> = self.query("workspaces")?;
+        let monitors: Vec<HyprMonitor> = self.query("monitors")?;
+        Ok(convert_workspaces(workspaces, &monitors))
+    }
+
+    fn windows(&mut self) -> anyhow::Result<Vec<Window>> {
+        Ok(self.clients()?.into_iter().filter_map(convert_client).collect())
+    }
+
+    fn focused_window(&mut self) -> anyhow::Result<Option<Window>> {
+        Ok(self.windows()?.into_iter().find(|w| w.is_focused))
+    }
+
+    fn focus_workspace(&mut self, name: &str) -> anyhow::Result<()> {
+        self.dispatch(&format!("workspace name:{name}"))
+    }
+
+    fn focus_workspace_id(&mut self, id: u64) -> anyhow::Result<()> {
+        let id = id as i64;
+        let name = if id > 0 {
+            None
+        } else {
+            let workspaces: Vec<HyprWorkspace> = self.query("workspaces")?;
+            let ws = workspaces
+                .into_iter()
+                .find(|ws| ws.id == id)
+                .with_context(|| format!("workspace {id} not found"))?;
+            Some(ws.name)
+        };
+        self.dispatch(&workspace_target(id, name.as_deref()))
+    }
+
+    fn create_named_workspace(&mut self, name: &str) -> anyhow::Result<()> {
+        // Switching to a named workspace that doesn't exist creates it, on
+        // the focused monitor
+        self.focus_workspace(name)
+    }
+
+    fn unset_workspace_name(&mut self, _name: &str) -> anyhow::Result<()> {
+        // Hyprland removes a workspace by itself once it is empty and unfocused
+        Ok(())
+    }
+
+    fn rename_workspace(&mut self, project: &str, new_name: &str) -> anyhow::Result<()> {
+        let separator = format!("{project} \u{00b7} ");
+        let Some(ws) = self
+            .workspaces()?
+            .into_iter()
+            .find(|ws| ws.name.as_deref().is_some_and(|n| n == project || n.starts_with(&separator)))
+        else {
+            return Ok(());
+        };
+        self.dispatch(&format!("renameworkspace {} {new_name}", ws.id as i64))
+    }
+
+    fn spawn(&mut self, command: Vec<String>) -> anyhow::Result<()> {
+        let line: Vec<String> = command.iter().map(|arg| shell::quote(arg)).collect();
+        self.dispatch(&format!("exec {}", line.join(" ")))
+    }
+
+    fn close_window(&mut self, id: u64) -> anyhow::Result<()> {
+        self.dispatch(&format!("closewindow address:{}", address(id)))
+    }
+
+    fn focus_window(&mut self, id: u64) -> anyhow::Result<()> {
+        self.dispatch(&format!("focuswindow address:{}", address(id)))
+    }
+
+    fn set_window_width(&mut self, id: u64, change: SizeChange) -> anyhow::Result<()> {
+        let height = self.size_of(id)?.1;
+        let width = match change {
+            SizeChange::Proportion(p) => format!("{p}%"),
+            SizeChange::Fixed(px) => px.to_string(),
+        };
+        self.dispatch(&format!("resizewindowpixel exact {width} {height},address:{}", address(id)))
+    }
+
+    fn set_window_height(&mut self, id: u64, change: SizeChange) -> anyhow::Result<()> {
+        let width = self.size_of(id)?.0;
+        let height = match change {
+            SizeChange::Proportion(p) => format!("{p}%"),
+            SizeChange::Fixed(px) => px.to_string(),
+        };
+        self.dispatch(&format!("resizewindowpixel exact {width} {height},address:{}", address(id)))
+    }
+
+    fn move_window_to_floating(&mut self, id: u64) -> anyhow::Result<()> {
+        self.dispatch(&format!("setfloating address:{}", address(id)))
+    }
+
+    fn fullscreen_window(&mut self, id: u64) -> anyhow::Result<()> {
+        self.focus_window(id)?;
+        self.dispatch("fullscreen 0")
+    }
+
+    fn move_column_to_index(&mut self, _index: usize) -> anyhow::Result<()> {
+        // Dwindle and master layouts have no columns to order
+        Ok(())
+    }
+
+    fn move_window_to_monitor(&mut self, id: u64, output: &str) -> anyhow::Result<()> {
+        self.focus_window(id)?;
+        self.dispatch(&format!("movewindow mon:{output}"))
+    }
+
+    fn move_workspace_to_monitor(&mut self, name: &str, output: &str) -> anyhow::Result<()> {
+        self.dispatch(&format!("moveworkspacetomonitor name:{name} {output}"))
+    }
+}
+
+/// Window addresses are hex pointers, "0x55d1c2a0"; as ids they're plain numbers.
+fn parse_address(address: &str) -> Option<u64> {
+    u64::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+}
+
+fn address(id: u64) -> String {
+    format!("0x{id:x}")
+}
+
+/// Hyprland workspace ids are signed (named workspaces count down from
+/// -1337); they're carried bit-for-bit in the u64 id. Special (scratchpad)
+/// workspaces are left out, and numbered ones have no name.
+fn convert_workspaces(workspaces: Vec<HyprWorkspace>, monitors: &[HyprMonitor]) -> Vec<Workspace> {
+    let mut workspaces: Vec<HyprWorkspace> = workspaces
+        .into_iter()
+        .filter(|ws| !ws.name.starts_with("special"))
+        .collect();
+    // Grouped by monitor like niri lists them: numbered workspaces in
+    // order, then named ones in creation order
+    workspaces.sort_by_key(|ws| {
+        let monitor = monitors.iter().position(|m| m.name == ws.monitor).unwrap_or(usize::MAX);
+        (monitor, if ws.id > 0 { ws.id } else { i64::MAX / 2 - ws.id })
+    });
+
+    let mut position: std::collections::HashMap<String, u8> = std::collections::HashMap::new();
+    workspaces
+        .into_iter()
+        .map(|ws| {
+            let idx = position.entry(ws.monitor.clone()).or_insert(0);
+            *idx = idx.saturating_add(1);
+            let monitor = monitors.iter().find(|m| m.name == ws.monitor);
+            let is_active = monitor.is_some_and(|m| m.active_workspace.id == ws.id);
+            Workspace {
+                id: ws.id as u64,
+                idx: *idx,
+                name: (ws.name != ws.id.to_string()).then_some(ws.name),
+                output: Some(ws.monitor),
+                is_active,
+                is_focused: is_active && monitor.is_some_and(|m| m.focused),
+                active_window_id: (ws.windows > 0).then(|| parse_address(&ws.last_window)).flatten(),
+            }
+        })
+        .collect()
+}
+
+fn convert_client(c: HyprClient) -> Option<Window> {
+    Some(Window {
+        id: parse_address(&c.address)?,
+        title: Some(c.title),
+        app_id: Some(c.class).filter(|class| !class.is_empty()),
+        pid: Some(c.pid).filter(|pid| *pid > 0),
+        workspace_id: Some(c.workspace.id as u64),
+        is_focused: c.focus_history_id == 0,
+        is_floating: c.floating,
+        size: Some(c.size),
+        column: None,
+        tile: None,
+    })
+}
+
+/// `workspace` dispatch for a workspace id. Named workspaces have negative
+/// ids, and a signed number is a relative move (`-1337` goes back 1337
+/// workspaces), so those are addressed by name.
+fn workspace_target(id: i64, name: Option<&str>) -> String {
+    match name {
+        Some(name) if id <= 0 => format!("workspace name:{name}"),
+        _ => format!("workspace {id}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKSPACES: &str = r#"[
+        {"id": 1, "name": "1", "monitor": "DP-1", "monitorID": 0, "windows": 2, "lastwindow": "0x5a1", "lastwindowtitle": "nvim"},
+        {"id": -1337, "name": "myapp", "monitor": "DP-1", "monitorID": 0, "windows": 0, "lastwindow": "0x0", "lastwindowtitle": ""},
+        {"id": -98, "name": "special:scratch", "monitor": "DP-1", "monitorID": 0, "windows": 1, "lastwindow": "0x0", "lastwindowtitle": ""},
+        {"id": 2, "name": "2", "monitor": "HDMI-A-1", "monitorID": 1, "windows": 0, "lastwindow": "0x0", "lastwindowtitle": ""}
+    ]"#;
+
+    const MONITORS: &str = r#"[
+        {"id": 0, "name": "DP-1", "focused": true, "activeWorkspace": {"id": -1337, "name": "myapp"}},
+        {"id": 1, "name": "HDMI-A-1", "focused": false, "activeWorkspace": {"id": 2, "name": "2"}}
+    ]"#;
+
+    #[test]
+    fn named_workspaces_focused_by_name() {
+        assert_eq!(workspace_target(2, None), "workspace 2");
+        assert_eq!(workspace_target(-1337, Some("myapp")), "workspace name:myapp");
+    }
+
+    #[test]
+    fn workspaces_convert() {
+        let workspaces = convert_workspaces(
+            serde_json::from_str(WORKSPACES).unwrap(),
+            &serde_json::from_str::<Vec<HyprMonitor>>(MONITORS).unwrap(),
+        );
+        let names: Vec<Option<&str>> = workspaces.iter().map(|ws| ws.name.as_deref()).collect();
+        assert_eq!(names, [None, Some("myapp"), None]);
+
+        let myapp = &workspaces[1];
+        assert_eq!((myapp.idx, myapp.id as i64), (2, -1337));
+        assert!(myapp.is_focused && myapp.is_active);
+        assert_eq!(myapp.active_window_id, None);
+        assert_eq!(workspaces[0].active_window_id, Some(0x5a1));
+        assert!(workspaces[2].is_active && !workspaces[2].is_focused);
+    }
+
+    #[test]
+    fn clients_convert() {
+        let client: HyprClient = serde_json::from_str(
+            r#"{"address": "0x55d1c2a0", "mapped": true, "hidden": false, "at": [0, 0], "size": [960, 1080],
+                "workspace": {"id": -1337, "name": "myapp"}, "floating": false, "monitor": 0,
+                "class": "com.mitchellh.ghostty", "title": "drift:myapp/editor", "pid": 4242,
+                "focusHistoryID": 0}"#,
+        )
+        .unwrap();
+        let window = convert_client(client).unwrap();
+        assert_eq!(window.id, 0x55d1c2a0);
+        assert_eq!(address(window.id), "0x55d1c2a0");
+        assert_eq!(window.workspace_id.map(|id| id as i64), Some(-1337));
+        assert_eq!(window.app_id.as_deref(), Some("com.mitchellh.ghostty"));
+        assert!(window.is_focused);
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::{bail, Context};
+use serde_yaml::Value;
+
+use crate::config::{
+    EnvConfig, ProjectConfig, ProjectMeta, RestartPolicy, ServiceProcess, ServicesConfig,
+    TmuxConfig, WindowConfig,
+};
+
+/// A project converted from another session manager, plus anything that
+/// couldn't be carried over.
+pub struct Imported {
+    pub config: ProjectConfig,
+    pub warnings: Vec<String>,
+}
+
+/// Convert a tmuxinator project. Windows and panes become tmux-backed drift
+/// windows; `on_project_start` (or the older `pre`) becomes one-shot services.
+pub fn from_tmuxinator(yaml: &str, name: Option<&str>) -> anyhow::Result<Imported> {
+    let doc: Value = serde_yaml::from_str(yaml).context("parsing tmuxinator YAML")?;
+    let mut warnings = Vec::new();
+
+    let name = project_name(name, &doc, "name")?;
+    let repo = str_field(&doc, "root").unwrap_or("~").to_string();
+    let pre_window = commands(doc.get("pre_window")).join("; ");
+
+    let mut windows = Vec::new();
+    for entry in seq(doc.get("windows")) {
+        // Each entry is a single-key map: `- editor: <spec>`
+        let Some((win_name, spec)) = entry.as_mapping().and_then(|m| m.iter().next()) else {
+            warnings.push("skipped a window entry that isn't a `name: spec` map".into());
+            continue;
+        };
+        let win_name = scalar(win_name);
+
+        let (root, panes) = match spec {
+            Value::Mapping(_) => {
+                if spec.get("layout").is_some() {
+                    warnings.push(format!("window '{win_name}': layout is not supported, panes become separate windows"));
+                }
+                let mut panes: Vec<String> = seq(spec.get("panes")).iter().map(pane_command).collect();
+                if panes.is_empty() {
+                    panes.push(String::new());
+                }
+                let pre = commands(spec.get("pre")).join("; ");
+                if !pre.is_empty() {
+                    for pane in &mut panes {
+                        *pane = join_commands(&pre, pane);
+                    }
+                }
+                (str_field(spec, "root"), panes)
+            }
+            other => (None, vec![commands(Some(other)).join("; ")]),
+        };
+
+        push_windows(&mut windows, &win_name, root, &pre_window, panes);
+    }
+
+    let pre = doc.get("on_project_start").or_else(|| doc.get("pre"));
+    let processes = one_shot_services(commands(pre));
+
+    for key in ["on_project_stop", "on_project_exit", "tmux_options", "socket_name", "startup_window", "startup_pane"] {
+        if doc.get(key).is_some() {
+            warnings.push(format!("'{key}' has no drift equivalent and was ignored"));
+        }
+    }
+
+    Ok(Imported { config: build_config(name, repo, windows, processes), warnings })
+}
+
+/// Convert a smug session. `before_start` becomes one-shot services and
+/// each window's panes become tmux-backed drift windows.
+pub fn from_smug(yaml: &str, name: Option<&str>) -> anyhow::Result<Imported> {
+    let doc: Value = serde_yaml::from_str(yaml).context("parsing smug YAML")?;
+    let mut warnings = Vec::new();
+
+    let name = project_name(name, &doc, "session")?;
+    let repo = str_field(&doc, "root").unwrap_or("~").to_string();
+
+    let mut windows = Vec::new();
+    for (i, win) in seq(doc.get("windows")).iter().enumerate() {
+        let win_name = str_field(win, "name")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("window-{}", i + 1));
+        if win.get("layout").is_some() {
+            warnings.push(format!("window '{win_name}': layout is not supported, panes become separate windows"));
+        }
+        if win.get("manual").and_then(Value::as_bool) == Some(true) {
+            warnings.push(format!("window '{win_name}': manual windows are imported but open with the project"));
+        }
+
+        let mut panes = vec![commands(win.get("commands")).join("; ")];
+        for pane in seq(win.get("panes")) {
+            let cmd = commands(pane.get("commands")).join("; ");
+            panes.push(match str_field(pane, "root") {
+                Some(root) if root != "." => join_commands(&format!("cd {root}"), &cmd),
+                _ => cmd,
+            });
+        }
+
+        push_windows(&mut windows, &win_name, str_field(win, "root"), "", panes);
+    }
+
+    let processes = one_shot_services(commands(doc.get("before_start")));
+
+    if doc.get("stop").is_some() {
+        warnings.push("'stop' commands were ignored; add a stop_command to a service if needed".into());
+    }
+
+    Ok(Imported { config: build_config(name, repo, windows, processes), warnings })
+}
+
+fn build_config(name: String, repo: String, windows: Vec<WindowConfig>, processes: Vec<ServiceProcess>) -> ProjectConfig {
+    ProjectConfig {
+        project: ProjectMeta { name, repo, folder: None, icon: None },
+        auto_close: true,
+        persist_windows: None,
+        auto_sync: true,
+        env: EnvConfig::default(),
+        git: None,
+        ssh: None,
+        ports: None,
+        services: if processes.is_empty() { None } else { Some(ServicesConfig { processes }) },
+        windows,
+        tmux: Some(TmuxConfig { kill_on_close: false }),
+        scratchpad: None,
+        verification: None,
+        watch: None,
+        toolchain: None,
+        dispatcher: None,
+        hooks: Vec::new(),
+        setup: None,
+        window_rules: Vec::new(),
+        on_open: Vec::new(),
+        on_close: Vec::new(),
+        ignore_windows: Vec::new(),
+    }
+}
+
+/// One drift window per pane: `editor`, `editor-2`, `editor-3`, ...
+fn push_windows(
+    windows: &mut Vec<WindowConfig>,
+    name: &str,
+    root: Option<&str>,
+    pre_window: &str,
+    panes: Vec<String>,
+) {
+    for (i, pane) in panes.into_iter().enumerate() {
+        let mut cmd = join_commands(pre_window, &pane);
+        if let Some(root) = root.filter(|r| *r != ".") {
+            cmd = join_commands(&format!("cd {root}"), &cmd);
+        }
+        windows.push(WindowConfig {
+            name: Some(if i == 0 { name.to_string() } else { format!("{name}-{}", i + 1) }),
+            command: if cmd.is_empty() { None } else { Some(cmd) },
+            tmux: Some(true),
+            ..Default::default()
+        });
+    }
+}
+
+fn one_shot_services(cmds: Vec<String>) -> Vec<ServiceProcess> {
+    cmds.into_iter()
+        .enumerate()
+        .map(|(i, command)| ServiceProcess {
+            name: format!("pre-{}", i + 1),
+            command,
+            restart: RestartPolicy::Never,
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn project_name(explicit: Option<&str>, doc: &Value, key: &str) -> anyhow::Result<String> {
+    match explicit.or_else(|| str_field(doc, key)) {
+        Some(name) if !name.is_empty() => Ok(name.to_string()),
+        _ => bail!("no '{key}' in config; pass --name"),
+    }
+}
+
+fn join_commands(first: &str, second: &str) -> String {
+    match (first.is_empty(), second.is_empty()) {
+        (true, _) => second.to_string(),
+        (false, true) => first.to_string(),
+        (false, false) => format!("{first} && {second}"),
+    }
+}
+
+/// tmuxinator panes are either a command string, a list of commands, or a
+/// single-key map naming the pane.
+fn pane_command(pane: &Value) -> String {
+    match pane {
+        Value::Mapping(m) => m.iter().next().map(|(_, v)| commands(Some(v)).join("; ")).unwrap_or_default(),
+        other => commands(Some(other)).join("; "),
+    }
+}
+
+/// A command field may be missing, a string, or a list of strings.
+fn commands(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Sequence(items)) => items.iter().map(scalar).filter(|s| !s.is_empty()).collect(),
+        Some(Value::Null) | None => Vec::new(),
+        Some(other) => {
+            let s = scalar(other);
+            if s.is_empty() { Vec::new() } else { vec![s] }
+        }
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn seq(value: Option<&Value>) -> &[Value] {
+    value.and_then(Value::as_sequence).map(Vec::as_slice).unwrap_or(&[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tmuxinator_windows_panes_and_pre() {
+        let yaml = r#"
+name: blog
+root: ~/code/blog
+on_project_start: docker compose up -d
+pre_window: nvm use
+windows:
+  - editor:
+      layout: main-vertical
+      panes:
+        - vim
+        - guard
+  - server: bundle exec rails s
+  - shell:
+"#;
+        let imported = from_tmuxinator(yaml, None).unwrap();
+        let cfg = imported.config;
+        assert_eq!(cfg.project.name, "blog");
+        assert_eq!(cfg.project.repo, "~/code/blog");
+
+        let names: Vec<_> = cfg.windows.iter().map(|w| w.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["editor", "editor-2", "server", "shell"]);
+        assert_eq!(cfg.windows[0].command.as_deref(), Some("nvm use && vim"));
+        assert_eq!(cfg.windows[2].command.as_deref(), Some("nvm use && bundle exec rails s"));
+        assert_eq!(cfg.windows[3].command.as_deref(), Some("nvm use"));
+        assert!(cfg.windows.iter().all(|w| w.tmux == Some(true)));
+
+        let services = cfg.services.unwrap().processes;
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].command, "docker compose up -d");
+        assert_eq!(services[0].restart, RestartPolicy::Never);
+        assert!(imported.warnings.iter().any(|w| w.contains("layout")));
+    }
+
+    #[test]
+    fn tmuxinator_requires_name() {
+        assert!(from_tmuxinator("windows: []", None).is_err());
+        let imported = from_tmuxinator("windows: []", Some("x")).unwrap();
+        assert_eq!(imported.config.project.name, "x");
+    }
+
+    #[test]
+    fn smug_session_with_panes_and_before_start() {
+        let yaml = r#"
+session: api
+root: ~/code/api
+before_start:
+  - docker compose up -d
+  - make migrate
+stop:
+  - docker compose stop
+windows:
+  - name: code
+    commands:
+      - nvim
+    panes:
+      - type: horizontal
+        root: web
+        commands:
+          - npm start
+  - name: logs
+    root: log
+    commands:
+      - tail -f app.log
+"#;
+        let imported = from_smug(yaml, None).unwrap();
+        let cfg = imported.config;
+        assert_eq!(cfg.project.name, "api");
+
+        let cmds: Vec<_> = cfg.windows.iter().map(|w| w.command.as_deref().unwrap()).collect();
+        assert_eq!(cmds, ["nvim", "cd web && npm start", "cd log && tail -f app.log"]);
+
+        let services = cfg.services.unwrap().processes;
+        assert_eq!(services.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["pre-1", "pre-2"]);
+        assert!(imported.warnings.iter().any(|w| w.contains("stop")));
+    }
+}
@@ -0,0 +1,199 @@
+//! A project's service logs and drift events merged into one chronological
+//! stream, for `drift journal`.
+//!
+//! Service output is written to the logs unmodified, so log lines have no
+//! timestamp of their own. Each one takes the time of the closest preceding
+//! line that has one: a supervisor start marker
+//! (`--- service 'api' started at <unix secs> ---`) or a line beginning with
+//! an RFC 3339 timestamp, as many loggers print.
+
+use std::fs;
+
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, Time, UtcOffset};
+
+use crate::events::Event;
+use crate::paths;
+
+/// Logs that aren't service output and carry no timestamps.
+const SKIPPED_LOGS: &[&str] = &["supervisor"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub ts: OffsetDateTime,
+    /// Service name for log lines, `event` for drift events
+    pub source: String,
+    pub text: String,
+}
+
+/// Every entry for `project`, oldest first.
+pub fn collect(project: &str) -> anyhow::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    let logs_dir = paths::logs_dir(project);
+    if logs_dir.exists() {
+        for entry in fs::read_dir(&logs_dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "log") {
+                continue;
+            }
+            let Some(source) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if SKIPPED_LOGS.contains(&source) {
+                continue;
+            }
+            let contents = String::from_utf8_lossy(&fs::read(&path)?).to_string();
+            entries.extend(parse_log(source, &contents));
+        }
+    }
+
+    let events_dir = paths::event_log_dir();
+    if events_dir.exists() {
+        for entry in fs::read_dir(&events_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "jsonl") {
+                entries.extend(parse_events(project, &fs::read_to_string(&path)?));
+            }
+        }
+    }
+
+    // Stable, so lines sharing an inherited timestamp keep their file order
+    entries.sort_by_key(|e| e.ts);
+    Ok(entries)
+}
+
+/// Log lines with their inferred timestamps. Lines before the first
+/// timestamp can't be placed and are left out.
+pub fn parse_log(source: &str, contents: &str) -> Vec<Entry> {
+    let mut current = None;
+    let mut entries = Vec::new();
+    for line in contents.lines() {
+        if let Some(ts) = line_timestamp(line) {
+            current = Some(ts);
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(ts) = current {
+            entries.push(Entry {
+                ts,
+                source: source.to_string(),
+                text: line.to_string(),
+            });
+        }
+    }
+    entries
+}
+
+/// Events of `project` from one event log archive.
+pub fn parse_events(project: &str, contents: &str) -> Vec<Entry> {
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Event>(line).ok())
+        .filter(|event| event.project == project)
+        .filter_map(|event| {
+            let ts = OffsetDateTime::parse(&event.ts, &Rfc3339).ok()?;
+            let mut text = event.event_type.clone();
+            if let Some(title) = event.title.as_deref().filter(|t| !t.is_empty()) {
+                text.push_str(": ");
+                text.push_str(title);
+            }
+            Some(Entry {
+                ts,
+                source: "event".into(),
+                text,
+            })
+        })
+        .collect()
+}
+
+fn line_timestamp(line: &str) -> Option<OffsetDateTime> {
+    let trimmed = line.trim();
+    if let Some(marker) = trimmed.strip_prefix("--- ").and_then(|l| l.strip_suffix(" ---")) {
+        let secs: i64 = marker.rsplit_once(" at ")?.1.parse().ok()?;
+        return OffsetDateTime::from_unix_timestamp(secs).ok();
+    }
+    let first = trimmed.split_whitespace().next()?;
+    let first = first.trim_start_matches('[').trim_end_matches(']');
+    OffsetDateTime::parse(first, &Rfc3339).ok()
+}
+
+/// Parse a `--since`/`--until` bound: RFC 3339, or `HH:MM[:SS]` on the day of
+/// `now` in its offset.
+pub fn parse_bound(s: &str, now: OffsetDateTime) -> Option<OffsetDateTime> {
+    if let Ok(ts) = OffsetDateTime::parse(s, &Rfc3339) {
+        return Some(ts);
+    }
+    let mut parts = s.split(':').map(|p| p.parse::<u8>());
+    let hour = parts.next()?.ok()?;
+    let minute = parts.next()?.ok()?;
+    let second = match parts.next() {
+        Some(p) => p.ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(now.replace_time(Time::from_hms(hour, minute, second).ok()?))
+}
+
+/// The local UTC offset, or UTC if it can't be determined.
+pub fn local_offset() -> UtcOffset {
+    UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_lines_inherit_marker_and_leading_timestamps() {
+        let contents = "\
+orphan line before any marker
+
+--- service 'api' started at 1767225600 ---
+listening on :3000
+2026-01-01T00:05:00Z GET /health
+request handled
+";
+        let entries = parse_log("api", contents);
+        let texts: Vec<_> = entries.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "--- service 'api' started at 1767225600 ---",
+                "listening on :3000",
+                "2026-01-01T00:05:00Z GET /health",
+                "request handled",
+            ]
+        );
+        assert_eq!(entries[1].ts.unix_timestamp(), 1767225600);
+        assert_eq!(entries[3].ts.unix_timestamp(), 1767225600 + 300);
+        assert!(entries.iter().all(|e| e.source == "api"));
+    }
+
+    #[test]
+    fn events_filtered_by_project() {
+        let contents = r#"{"type":"service.crashed","project":"myapp","source":"supervisor","ts":"2026-01-01T00:03:00Z","title":"api crashed"}
+{"type":"drift.project.opened","project":"other","source":"drift","ts":"2026-01-01T00:04:00Z"}
+not json"#;
+        let entries = parse_events("myapp", contents);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].source, "event");
+        assert_eq!(entries[0].text, "service.crashed: api crashed");
+    }
+
+    #[test]
+    fn bounds_accept_clock_time_and_rfc3339() {
+        let now = OffsetDateTime::parse("2026-01-01T18:00:00+01:00", &Rfc3339).unwrap();
+        let since = parse_bound("14:32", now).unwrap();
+        assert_eq!(since, OffsetDateTime::parse("2026-01-01T14:32:00+01:00", &Rfc3339).unwrap());
+        assert_eq!(
+            parse_bound("2026-01-01T13:00:00Z", now),
+            OffsetDateTime::parse("2026-01-01T13:00:00Z", &Rfc3339).ok()
+        );
+        assert_eq!(parse_bound("25:00", now), None);
+        assert_eq!(parse_bound("yesterday", now), None);
+    }
+}
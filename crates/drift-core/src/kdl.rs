@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 
 use crate::config::{GlobalConfig, ProjectConfig};
-use crate::paths;
+use crate::{browser, paths, registry};
 
 /// Parse a width string like "60%", "0.6", or "800px" into a KDL `default-column-width` line.
 fn format_column_width(width: &str) -> Option<String> {
@@ -27,6 +27,18 @@ fn format_column_width(width: &str) -> Option<String> {
     }
 }
 
+/// Escape regex metacharacters so a project or window name matches literally.
+fn escape_regex(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
 /// Map a terminal command name to its niri app-id regex.
 fn terminal_app_id(terminal: &str) -> &str {
     match terminal {
@@ -68,28 +80,51 @@ pub fn generate_niri_rules(projects: &[ProjectConfig], global: &GlobalConfig) ->
             // Base rule: routes all drift windows for this project to its workspace.
             // The regex matches both "drift:name" and "drift:name/windowname".
             out.push_str(&format!(
-                "\nwindow-rule {{\n    match app-id=r#\"^{terminal}$\"# title=r#\"^drift:{name}(/.*)?$\"#\n    open-on-workspace \"{name}\"\n}}\n"
+                "\nwindow-rule {{\n    match app-id=r#\"^{terminal}$\"# title=r#\"^drift:{}(/.*)?$\"#\n    open-on-workspace \"{name}\"\n}}\n",
+                escape_regex(name)
             ));
 
-            // Per-window rules with column width
+            // Per-window rules: column width, floating, fullscreen, opacity
+            // and the workspace, so a window niri restores on its own lands
+            // where `drift open` would put it. Column position and output
+            // have no rule equivalent and are applied over IPC.
             for window in &config.windows {
-                let width_str = match &window.width {
-                    Some(w) => w,
-                    None => continue,
-                };
-                let width_line = match format_column_width(width_str) {
-                    Some(line) => line,
-                    None => continue,
+                let wn = match (&window.name, &window.browser) {
+                    (Some(n), _) => n.as_str(),
+                    (None, Some(_)) => "browser",
+                    (None, None) => continue,
                 };
-                let wn = match &window.name {
-                    Some(n) => n,
-                    None => continue,
+                // app_id-only windows aren't terminals and don't carry a drift title
+                if window.tmux == Some(true) || (window.app_id.is_some() && window.command.is_none()) {
+                    continue;
+                }
+                let mut lines = String::new();
+                if let Some(width_line) = window.width.as_deref().and_then(format_column_width) {
+                    lines.push_str(&width_line);
+                }
+                if window.floating == Some(true) {
+                    lines.push_str("    open-floating true\n");
+                }
+                if window.fullscreen == Some(true) {
+                    lines.push_str("    open-fullscreen true\n");
+                }
+                if let Some(opacity) = window.opacity {
+                    lines.push_str(&format!("    opacity {:.2}\n", opacity.clamp(0.0, 1.0)));
+                }
+                lines.push_str(&format!("    open-on-workspace \"{name}\"\n"));
+                // Browser windows are known by app_id; Firefox can't set a title
+                let matcher = match window.browser {
+                    Some(_) => format!("app-id=r#\"^{}$\"#", escape_regex(&browser::app_id(name, wn))),
+                    None => format!("app-id=r#\"^{terminal}$\"# title=r#\"^drift:{}/{}$\"#", escape_regex(name), escape_regex(wn)),
                 };
-                out.push_str(&format!(
-                    "\nwindow-rule {{\n    match app-id=r#\"^{terminal}$\"# title=r#\"^drift:{name}/{wn}$\"#\n{width_line}}}\n"
-                ));
+                out.push_str(&format!("\nwindow-rule {{\n    match {matcher}\n{lines}}}\n"));
             }
 
+            // Scratchpad (`drift scratch`) opens floating over the project's columns
+            out.push_str(&format!(
+                "\nwindow-rule {{\n    match app-id=r#\"^{terminal}$\"# title=r#\"^drift:{name}/scratchpad$\"#\n    open-floating true\n}}\n"
+            ));
+
             // Per-agent rules with column width
             if let Some(services) = &config.services {
                 for svc in &services.processes {
@@ -116,19 +151,76 @@ pub fn generate_niri_rules(projects: &[ProjectConfig], global: &GlobalConfig) ->
     out
 }
 
+/// Write niri-rules.kdl. Returns whether its contents changed; an
+/// unchanged file only has its mtime bumped, so niri doesn't reload it.
 pub fn write_niri_rules(
     projects: &[ProjectConfig],
     global: &GlobalConfig,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<bool> {
     let content = generate_niri_rules(projects, global);
     let path = paths::niri_rules_path();
+    if std::fs::read_to_string(&path).is_ok_and(|current| current == content) {
+        std::fs::File::options()
+            .append(true)
+            .open(&path)?
+            .set_modified(std::time::SystemTime::now())?;
+        return Ok(false);
+    }
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    std::fs::write(&path, content)?;
+    let tmp = path.with_extension("kdl.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(true)
+}
+
+/// Rule blocks (separated by blank lines) that differ between the rules
+/// file and what would be generated now: `(missing, outdated)`.
+pub fn niri_rules_diff<'a>(current: &'a str, expected: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
+    let blocks = |s: &'a str| -> Vec<&'a str> {
+        s.split("\n\n").map(str::trim).filter(|b| !b.is_empty()).collect()
+    };
+    let (current, expected) = (blocks(current), blocks(expected));
+    let missing = expected.iter().filter(|b| !current.contains(b)).copied().collect();
+    let outdated = current.iter().filter(|b| !expected.contains(b)).copied().collect();
+    (missing, outdated)
+}
+
+/// Regenerate niri-rules.kdl after a registry change (init, import, delete,
+/// archive) unless `defaults.auto_niri_rules` is off.
+pub fn refresh_niri_rules(global: &GlobalConfig) -> anyhow::Result<()> {
+    if !global.defaults.auto_niri_rules {
+        return Ok(());
+    }
+    let projects = registry::list_projects()?;
+    write_niri_rules(&projects, global)?;
     Ok(())
 }
 
+/// Whether niri-rules.kdl is missing or older than the global config, any
+/// project config, or the projects directory itself (which changes on
+/// delete/archive).
+pub fn niri_rules_stale() -> bool {
+    let Ok(rules_mtime) = std::fs::metadata(paths::niri_rules_path()).and_then(|m| m.modified()) else {
+        return true;
+    };
+    let projects_dir = paths::projects_dir();
+    let mut sources = vec![paths::global_config_path(), projects_dir.clone()];
+    if let Ok(entries) = std::fs::read_dir(&projects_dir) {
+        sources.extend(
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "toml")),
+        );
+    }
+    sources
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+        .any(|mtime| mtime > rules_mtime)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,11 +236,27 @@ mod tests {
                 editor: "nvim".into(),
                 shell: "zsh".into(),
                 persist_windows: false,
+                idle_suspend_minutes: None,
+                auto_niri_rules: true,
+                save_on_switch: true,
+                compositor: "auto".into(),
+                adopt_orphans: false,
+                trash_retention_days: 30,
+                focus_idle_minutes: 5,
+                git_poll_minutes: None,
+                auto_save_minutes: None,
             },
             ports: PortDefaults::default(),
             events: crate::config::EventsConfig::default(),
             commander: crate::config::CommanderConfig::default(),
             features: crate::config::FeaturesConfig::default(),
+            webhooks: crate::config::WebhooksConfig::default(),
+            metrics: crate::config::MetricsConfig::default(),
+            sinks: Vec::new(),
+            hooks: Vec::new(),
+            env: Default::default(),
+            folders: Default::default(),
+            ignore_windows: Vec::new(),
         }
     }
 
@@ -162,15 +270,25 @@ mod tests {
             },
             auto_close: true,
             persist_windows: None,
+            auto_sync: true,
             env: EnvConfig::default(),
             git: None,
+            ssh: None,
             ports: None,
             services: None,
             windows: vec![],
             tmux: None,
             scratchpad: None,
             verification: None,
+            watch: None,
+            toolchain: None,
             dispatcher: None,
+            hooks: Vec::new(),
+            setup: None,
+            window_rules: Vec::new(),
+            on_open: Vec::new(),
+            on_close: Vec::new(),
+            ignore_windows: Vec::new(),
         }
     }
 
@@ -263,8 +381,8 @@ mod tests {
         let global = make_global("ghostty");
         let mut proj = make_project("myapp", Some("dev"));
         proj.windows = vec![
-            WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), width: Some("60%".into()), tmux: None, app_id: None },
-            WindowConfig { name: Some("shell".into()), command: None, width: Some("40%".into()), tmux: None, app_id: None },
+            WindowConfig { name: Some("editor".into()), command: Some("nvim .".into()), width: Some("60%".into()), ..Default::default() },
+            WindowConfig { name: Some("shell".into()), width: Some("40%".into()), ..Default::default() },
         ];
         let result = generate_niri_rules(&[proj], &global);
         assert!(result.contains(r##"match app-id=r#"^com\.mitchellh\.ghostty$"# title=r#"^drift:myapp/editor$"#"##));
@@ -278,18 +396,69 @@ mod tests {
         let global = make_global("ghostty");
         let mut proj = make_project("myapp", None);
         proj.windows = vec![
-            WindowConfig { name: Some("editor".into()), command: None, width: Some("800px".into()), tmux: None, app_id: None },
+            WindowConfig { name: Some("editor".into()), width: Some("800px".into()), ..Default::default() },
         ];
         let result = generate_niri_rules(&[proj], &global);
         assert!(result.contains("default-column-width { fixed 800; }"));
     }
 
+    #[test]
+    fn window_layout_hints_emit_rules() {
+        let global = make_global("foot");
+        let mut proj = make_project("myapp", None);
+        proj.windows = vec![
+            WindowConfig { name: Some("docs".into()), floating: Some(true), ..Default::default() },
+            WindowConfig { name: Some("logs".into()), width: Some("50%".into()), fullscreen: Some(true), column: Some(2), ..Default::default() },
+        ];
+        let result = generate_niri_rules(&[proj], &global);
+        assert!(result.contains("title=r#\"^drift:myapp/docs$\"#\n    open-floating true\n    open-on-workspace \"myapp\"\n}"));
+        assert!(result.contains("title=r#\"^drift:myapp/logs$\"#\n    default-column-width { proportion 0.5; }\n    open-fullscreen true\n    open-on-workspace \"myapp\"\n}"));
+    }
+
+    #[test]
+    fn window_rules_carry_opacity_and_workspace() {
+        let global = make_global("foot");
+        let mut proj = make_project("my.app", None);
+        proj.windows = vec![
+            WindowConfig { name: Some("notes".into()), opacity: Some(0.9), ..Default::default() },
+            WindowConfig { name: Some("browser".into()), app_id: Some("firefox".into()), opacity: Some(0.9), ..Default::default() },
+        ];
+        let result = generate_niri_rules(&[proj], &global);
+        assert!(result.contains("title=r#\"^drift:my\\.app/notes$\"#\n    opacity 0.90\n    open-on-workspace \"my.app\"\n}"));
+        assert!(!result.contains("drift:my\\.app/browser"));
+    }
+
+    #[test]
+    fn browser_windows_matched_by_app_id() {
+        let global = make_global("foot");
+        let mut proj = make_project("web", None);
+        proj.windows = vec![
+            WindowConfig { name: Some("docs".into()), width: Some("50%".into()), browser: Some("firefox".into()), ..Default::default() },
+        ];
+        let result = generate_niri_rules(&[proj], &global);
+        assert!(result.contains("match app-id=r#\"^drift-browser-web-docs$\"#\n    default-column-width { proportion 0.5; }\n    open-on-workspace \"web\"\n}"));
+    }
+
+    #[test]
+    fn escape_regex_quotes_metacharacters() {
+        assert_eq!(escape_regex("api"), "api");
+        assert_eq!(escape_regex("a.b+(c)"), "a\\.b\\+\\(c\\)");
+    }
+
+    #[test]
+    fn scratchpad_gets_floating_rule() {
+        let global = make_global("ghostty");
+        let result = generate_niri_rules(&[make_project("myapp", None)], &global);
+        assert!(result.contains(r##"match app-id=r#"^com\.mitchellh\.ghostty$"# title=r#"^drift:myapp/scratchpad$"#"##));
+        assert!(result.contains("    open-floating true\n"));
+    }
+
     #[test]
     fn window_without_width_no_column_rule() {
         let global = make_global("ghostty");
         let mut proj = make_project("myapp", None);
         proj.windows = vec![
-            WindowConfig { name: Some("shell".into()), command: None, width: None, tmux: None, app_id: None },
+            WindowConfig { name: Some("shell".into()), ..Default::default() },
         ];
         let result = generate_niri_rules(&[proj], &global);
         assert!(!result.contains("default-column-width"));
@@ -304,16 +473,12 @@ mod tests {
         proj.services = Some(ServicesConfig {
             processes: vec![ServiceProcess {
                 name: "assistant".into(),
-                command: String::new(),
-                cwd: ".".into(),
                 restart: RestartPolicy::OnFailure,
-                stop_command: None,
                 agent: Some("claude".into()),
                 prompt: Some("Help me".into()),
                 agent_mode: "interactive".into(),
-                agent_model: None,
-                agent_permissions: "full".into(),
                 width: Some("50%".into()),
+                ..Default::default()
             }],
         });
         let result = generate_niri_rules(&[proj], &global);
@@ -328,16 +493,11 @@ mod tests {
         proj.services = Some(ServicesConfig {
             processes: vec![ServiceProcess {
                 name: "worker".into(),
-                command: String::new(),
-                cwd: ".".into(),
                 restart: RestartPolicy::OnFailure,
-                stop_command: None,
                 agent: Some("claude".into()),
                 prompt: Some("Do stuff".into()),
-                agent_mode: "oneshot".into(),
-                agent_model: None,
-                agent_permissions: "full".into(),
                 width: Some("50%".into()),
+                ..Default::default()
             }],
         });
         let result = generate_niri_rules(&[proj], &global);
@@ -345,6 +505,20 @@ mod tests {
         assert!(!result.contains("drift:myapp/worker"));
     }
 
+    #[test]
+    fn rules_diff_reports_changed_blocks() {
+        let global = make_global("foot");
+        let before = generate_niri_rules(&[make_project("api", None)], &global);
+        let after = generate_niri_rules(&[make_project("api", None), make_project("web", None)], &global);
+        assert_eq!(niri_rules_diff(&after, &after), (vec![], vec![]));
+
+        let (missing, outdated) = niri_rules_diff(&before, &after);
+        assert!(missing.iter().all(|b| b.contains("web")));
+        assert!(missing.iter().any(|b| b.contains("open-on-workspace \"web\"")));
+        // The folder block lists both workspaces now, so the old one is outdated
+        assert_eq!(outdated, vec!["// (ungrouped)\nworkspace \"api\""]);
+    }
+
     #[test]
     fn format_column_width_percentage() {
         assert_eq!(
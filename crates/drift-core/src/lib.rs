@@ -1,27 +1,51 @@
 pub mod agent;
+pub mod browser;
+pub mod cgroup;
 pub mod claude_trust;
 #[cfg(feature = "drivers")]
 pub mod driver;
+pub mod compositor;
 pub mod config;
 #[cfg(feature = "dispatch")]
 pub mod dispatch;
 pub mod env;
 pub mod events;
+pub mod focus_time;
+pub mod git_identity;
 #[cfg(feature = "handoff")]
 pub mod handoff;
+pub mod hyprland;
+pub mod import;
+pub mod journal;
 pub mod kdl;
 pub mod lifecycle;
+pub mod listeners;
+pub mod mute;
 pub mod niri;
 pub mod paths;
 #[cfg(feature = "post-dispatch")]
 pub mod post_dispatch;
+pub mod project_hooks;
 pub mod project_state;
 pub mod registry;
+pub mod repo_status;
+pub mod resources;
+pub mod schedule;
+pub mod secrets;
 pub mod session;
+pub mod setup;
+pub mod shell;
+pub mod ssh;
 pub mod supervisor;
 pub mod sync;
 #[cfg(feature = "tasks")]
 pub mod tasks;
+pub mod timings;
+pub mod tmux;
+pub mod trash;
+pub mod validate;
+pub mod watch;
+pub mod window_rules;
 pub mod workspace;
 pub mod workspace_names;
 #[cfg(feature = "worktree")]
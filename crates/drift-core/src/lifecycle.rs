@@ -1,42 +1,176 @@
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use nix::sys::signal::{self, Signal};
 use nix::unistd::Pid;
 
-use crate::{config, paths};
+use crate::{compositor, config, paths, shell};
+
+/// Work a close could leave behind unnoticed.
+#[derive(Debug, Default, PartialEq)]
+pub struct DirtyState {
+    /// `git status --porcelain` lines of the repo
+    pub uncommitted: Vec<String>,
+    /// The scratchpad is open in an editor (drift window or vim swap file)
+    pub scratchpad_open: bool,
+    /// Repo files open in neovim (they have a swap file), relative to the repo
+    pub editor_files: Vec<String>,
+    /// Services the supervisor is running that close would stop
+    pub running_services: Vec<String>,
+}
+
+impl DirtyState {
+    /// Work a close would leave behind. Running services aren't work, but
+    /// still warrant asking first (see [`DirtyState::needs_confirmation`]).
+    pub fn is_dirty(&self) -> bool {
+        !self.uncommitted.is_empty() || self.scratchpad_open || !self.editor_files.is_empty()
+    }
+
+    pub fn needs_confirmation(&self) -> bool {
+        self.is_dirty() || !self.running_services.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        match self.uncommitted.len() {
+            0 => {}
+            1 => parts.push("1 uncommitted change".to_string()),
+            n => parts.push(format!("{n} uncommitted changes")),
+        }
+        if self.scratchpad_open {
+            parts.push("scratchpad open".to_string());
+        }
+        match self.editor_files.len() {
+            0 => {}
+            1 => parts.push("1 file open in an editor".to_string()),
+            n => parts.push(format!("{n} files open in an editor")),
+        }
+        match self.running_services.len() {
+            0 => {}
+            1 => parts.push("1 service running".to_string()),
+            n => parts.push(format!("{n} services running")),
+        }
+        parts.join(", ")
+    }
+}
+
+/// Check a project for uncommitted git changes, an open scratchpad or
+/// editor buffers, and running services. Best effort: anything that can't
+/// be checked counts as clean.
+pub fn dirty_state(project_name: &str) -> DirtyState {
+    let Ok(cfg) = config::load_project_config(project_name) else {
+        return DirtyState::default();
+    };
+    let Ok(repo_path) = config::resolve_repo_path(&cfg.project.repo) else {
+        return DirtyState::default();
+    };
+
+    let uncommitted = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(&repo_path)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let file = cfg.scratchpad.as_ref().map(|s| s.file.as_str());
+    let scratchpad = paths::scratchpad_path(project_name, &repo_path, file);
+    let title = shell::window_title(project_name, Some("scratchpad"));
+    let scratchpad_open = vim_swap_path(&scratchpad).exists()
+        || compositor::connect()
+            .and_then(|mut c| c.find_window_by_title(&title))
+            .is_ok_and(|w| w.is_some());
+
+    let running_services = if crate::supervisor::supervisor_running(project_name) {
+        crate::supervisor::read_state(project_name)
+            .map(|state| {
+                state.services.into_iter()
+                    .filter(|s| s.status == crate::supervisor::ServiceStatus::Running)
+                    .map(|s| s.name)
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    DirtyState {
+        uncommitted,
+        scratchpad_open,
+        editor_files: nvim_open_files(&repo_path),
+        running_services,
+    }
+}
+
+/// Repo files with a neovim swap file, i.e. open in some neovim. Its swap
+/// files are named after the full path, with `/` written as `%`.
+fn nvim_open_files(repo: &Path) -> Vec<String> {
+    let Some(swap_dir) = dirs::state_dir().map(|d| d.join("nvim").join("swap")) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(swap_dir) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}/", repo.display());
+    let mut files: Vec<String> = entries
+        .flatten()
+        .filter_map(|e| swap_file_target(&e.file_name().to_string_lossy()))
+        .filter_map(|path| path.strip_prefix(&prefix).map(str::to_string))
+        .collect();
+    files.sort();
+    files
+}
+
+/// `%home%me%repo%src%main.rs.swp` -> `/home/me/repo/src/main.rs`
+/// (`.swo`, `.swn`, ... when several editors had the file open).
+fn swap_file_target(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    (ext.len() == 3 && ext.starts_with("sw") && stem.starts_with('%')).then(|| stem.replace('%', "/"))
+}
+
+/// Where vim/neovim keep the swap file of an open buffer (`.name.swp` alongside it).
+fn vim_swap_path(file: &Path) -> PathBuf {
+    let name = file.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    file.with_file_name(format!(".{name}.swp"))
+}
 
 /// Non-blocking project teardown: save workspace, kill tmux, SIGTERM supervisor,
-/// clean up state files, remove from session tracking.
+/// clean up state files, remove from session tracking. With `keep_services`
+/// the supervisor (and the project's ssh-agent) stay up, running headless.
 /// Does NOT wait for supervisor to die — callers handle that if needed.
-pub fn teardown_project(project_name: &str) {
+pub fn teardown_project(project_name: &str, keep_services: bool) {
     // Best-effort workspace save
     if let Err(e) = crate::workspace::save_workspace(project_name) {
         eprintln!("  Warning: could not save workspace: {e}");
     }
 
-    // Kill tmux session if configured
+    // Kill tmux sessions (windows and agent panes) if configured
     if let Ok(cfg) = config::load_project_config(project_name) {
-        if let Some(tmux_cfg) = cfg.tmux {
-            if tmux_cfg.kill_on_close {
-                let session_name = format!("drift:{project_name}");
-                let has_session = Command::new("tmux")
-                    .args(["has-session", "-t", &session_name])
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .status()
-                    .map(|s| s.success())
-                    .unwrap_or(false);
-
-                if has_session {
-                    let _ = Command::new("tmux")
-                        .args(["kill-session", "-t", &session_name])
-                        .status();
+        if cfg.tmux.is_some_and(|t| t.kill_on_close) {
+            for session in crate::tmux::project_session_names(project_name) {
+                if crate::tmux::kill_session(&session) {
+                    println!("  Killed tmux session '{session}'");
                 }
             }
         }
     }
 
+    if !keep_services {
+        stop_services(project_name);
+    }
+
+    // Remove from session tracking
+    if let Err(e) = crate::session::remove_project(project_name) {
+        eprintln!("  Warning: could not update session: {e}");
+    }
+}
+
+/// SIGTERM the supervisor (non-blocking), stop the ssh-agent and drop the
+/// services state.
+fn stop_services(project_name: &str) {
     // Send SIGTERM to supervisor (non-blocking)
     let supervisor_pid_path = paths::supervisor_pid_path(project_name);
     if supervisor_pid_path.exists() {
@@ -50,11 +184,47 @@ pub fn teardown_project(project_name: &str) {
         let _ = fs::remove_file(&supervisor_pid_path);
     }
 
+    crate::ssh::stop(project_name);
+
     // Clean up state files
     let _ = fs::remove_file(paths::services_state_path(project_name));
+}
 
-    // Remove from session tracking
-    if let Err(e) = crate::session::remove_project(project_name) {
-        eprintln!("  Warning: could not update session: {e}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_summary() {
+        assert!(!DirtyState::default().is_dirty());
+        let state = DirtyState {
+            uncommitted: vec![" M src/main.rs".into(), "?? notes.md".into()],
+            scratchpad_open: true,
+            editor_files: vec!["src/lib.rs".into()],
+            running_services: Vec::new(),
+        };
+        assert!(state.is_dirty());
+        assert_eq!(state.summary(), "2 uncommitted changes, scratchpad open, 1 file open in an editor");
+
+        let services = DirtyState { running_services: vec!["web".into(), "db".into()], ..Default::default() };
+        assert!(!services.is_dirty());
+        assert!(services.needs_confirmation());
+        assert_eq!(services.summary(), "2 services running");
+    }
+
+    #[test]
+    fn nvim_swap_names_decoded() {
+        assert_eq!(swap_file_target("%home%me%repo%src%main.rs.swp"), Some("/home/me/repo/src/main.rs".into()));
+        assert_eq!(swap_file_target("%home%me%notes.md.swo"), Some("/home/me/notes.md".into()));
+        assert_eq!(swap_file_target(".main.rs.swp"), None);
+        assert_eq!(swap_file_target("%home%me%file.txt"), None);
+    }
+
+    #[test]
+    fn swap_path_sits_next_to_file() {
+        assert_eq!(
+            vim_swap_path(Path::new("/repo/NOTES.md")),
+            PathBuf::from("/repo/.NOTES.md.swp")
+        );
     }
 }
@@ -0,0 +1,109 @@
+//! Who is listening on a TCP port, from /proc/net/tcp{,6} and the socket
+//! links in /proc/<pid>/fd. Processes of other users can't be inspected,
+//! so their listeners come back without a pid.
+
+use std::fs;
+
+use crate::resources;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listener {
+    pub port: u16,
+    pub pid: Option<u32>,
+    /// Process name from /proc/<pid>/comm
+    pub command: Option<String>,
+}
+
+impl Listener {
+    /// "node (PID 1234)", or "an unknown process" when it belongs to
+    /// another user.
+    pub fn describe(&self) -> String {
+        match (self.pid, &self.command) {
+            (Some(pid), Some(command)) => format!("{command} (PID {pid})"),
+            (Some(pid), None) => format!("PID {pid}"),
+            (None, _) => "an unknown process".into(),
+        }
+    }
+
+    /// Whether the listener runs in one of the given process groups, i.e.
+    /// belongs to a service started with that pid.
+    pub fn in_groups(&self, pgids: &[u32]) -> bool {
+        self.pid
+            .and_then(resources::process_group)
+            .is_some_and(|pgid| pgids.contains(&pgid))
+    }
+}
+
+/// The process listening on `port`, if any.
+pub fn find(port: u16) -> Option<Listener> {
+    let inode = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|table| listening_sockets(&table))
+        .find(|(p, _)| *p == port)
+        .map(|(_, inode)| inode)?;
+    let pid = socket_owner(inode);
+    let command = pid.and_then(|pid| {
+        fs::read_to_string(format!("/proc/{pid}/comm")).ok().map(|c| c.trim().to_string())
+    });
+    Some(Listener { port, pid, command })
+}
+
+/// Port and socket inode of every socket in LISTEN state in a
+/// /proc/net/tcp-format table.
+fn listening_sockets(table: &str) -> Vec<(u16, u64)> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // 0A is TCP_LISTEN
+            if fields.get(3) != Some(&"0A") {
+                return None;
+            }
+            let (_, port) = fields.get(1)?.rsplit_once(':')?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some((port, inode))
+        })
+        .collect()
+}
+
+fn socket_owner(inode: u64) -> Option<u32> {
+    let target = format!("socket:[{inode}]");
+    fs::read_dir("/proc").ok()?.flatten().find_map(|entry| {
+        let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+        let fds = fs::read_dir(entry.path().join("fd")).ok()?;
+        fds.flatten()
+            .any(|fd| fs::read_link(fd.path()).is_ok_and(|link| link.as_os_str() == target.as_str()))
+            .then_some(pid)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_listening_sockets_parsed() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:A2C4 0100007F:0BB8 01 00000000:00000000 00:00000000 00000000  1000        0 41299 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(listening_sockets(table), vec![(3000, 41234)]);
+        let table6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 5150 1 0000000000000000 100 0 0 10 0
+";
+        assert_eq!(listening_sockets(table6), vec![(8080, 5150)]);
+    }
+
+    #[test]
+    fn own_listener_found() {
+        let socket = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = socket.local_addr().unwrap().port();
+        let listener = find(port).unwrap();
+        assert_eq!(listener.pid, Some(std::process::id()));
+        let own_group = resources::process_group(std::process::id()).unwrap();
+        assert!(listener.in_groups(&[own_group]));
+    }
+}
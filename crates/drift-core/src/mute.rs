@@ -0,0 +1,185 @@
+//! Commander mutes, kept in `commander.json`: everything or single projects,
+//! each until unmuted or until a given time, plus the configured quiet hours.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Mute {
+    /// Unix seconds the mute ends at; None lasts until unmuted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<i64>,
+}
+
+impl Mute {
+    pub fn active(&self, now: i64) -> bool {
+        !matches!(self.until, Some(until) if now >= until)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MuteState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all: Option<Mute>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub projects: BTreeMap<String, Mute>,
+}
+
+impl MuteState {
+    /// The saved state; missing or unreadable counts as nothing muted.
+    pub fn load() -> Self {
+        fs::read_to_string(paths::commander_state_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save, dropping mutes that have run out.
+    pub fn save(mut self, now: i64) -> anyhow::Result<()> {
+        self.prune(now);
+        let path = paths::commander_state_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&self)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn all_muted(&self, now: i64) -> bool {
+        self.all.is_some_and(|m| m.active(now))
+    }
+
+    pub fn is_muted(&self, project: &str, now: i64) -> bool {
+        self.all_muted(now) || self.projects.get(project).is_some_and(|m| m.active(now))
+    }
+
+    fn prune(&mut self, now: i64) {
+        if self.all.is_some_and(|m| !m.active(now)) {
+            self.all = None;
+        }
+        self.projects.retain(|_, m| m.active(now));
+    }
+}
+
+/// Minutes since midnight for `"HH:MM"`.
+pub fn parse_clock(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// Start and end minute of a `"22:00-08:00"` range.
+pub fn parse_range(s: &str) -> Option<(u32, u32)> {
+    let (start, end) = s.split_once('-')?;
+    Some((parse_clock(start)?, parse_clock(end)?))
+}
+
+/// Whether `minute` (since midnight) falls in any of the ranges. A range
+/// whose end is before its start runs over midnight.
+pub fn in_quiet_hours(ranges: &[String], minute: u32) -> bool {
+    ranges.iter().filter_map(|r| parse_range(r)).any(|(start, end)| {
+        if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        }
+    })
+}
+
+pub fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Local minutes since midnight at `ts`. Uses `localtime_r` rather than the
+/// time crate, which refuses to read the local offset once a process has
+/// more than one thread (the commander always does).
+pub fn local_minute(ts: i64) -> u32 {
+    let t = ts as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+        return (ts.rem_euclid(86_400) / 60) as u32;
+    }
+    tm.tm_hour as u32 * 60 + tm.tm_min as u32
+}
+
+/// Unix seconds of the next local `"HH:MM"` after `now`.
+pub fn next_clock(s: &str, now: i64) -> anyhow::Result<i64> {
+    let target = parse_clock(s).ok_or_else(|| anyhow::anyhow!("invalid time '{s}' (expected HH:MM)"))?;
+    Ok(next_minute(target, local_minute(now), now))
+}
+
+fn next_minute(target: u32, current: u32, now: i64) -> i64 {
+    let ahead = match (target + 1440 - current) % 1440 {
+        0 => 1440,
+        n => n,
+    };
+    now - now.rem_euclid(60) + i64::from(ahead) * 60
+}
+
+/// `"HH:MM"` in local time, for showing when a mute ends.
+pub fn format_clock(ts: i64) -> String {
+    let minute = local_minute(ts);
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutes_expire() {
+        let mut state = MuteState {
+            all: Some(Mute { until: Some(100) }),
+            ..Default::default()
+        };
+        state.projects.insert("api".into(), Mute { until: None });
+        state.projects.insert("web".into(), Mute { until: Some(50) });
+        assert!(state.is_muted("other", 99));
+        assert!(!state.is_muted("other", 100));
+        assert!(state.is_muted("api", 1_000));
+        assert!(!state.is_muted("web", 100));
+
+        state.prune(60);
+        assert_eq!(state.projects.keys().collect::<Vec<_>>(), ["api"]);
+        assert!(state.all.is_some());
+    }
+
+    #[test]
+    fn clock_parsing() {
+        assert_eq!(parse_clock("18:00"), Some(1080));
+        assert_eq!(parse_clock("7:05"), Some(425));
+        assert_eq!(parse_clock("24:00"), None);
+        assert_eq!(parse_clock("18"), None);
+        assert_eq!(parse_range("22:00-08:00"), Some((1320, 480)));
+        assert_eq!(parse_range("22:00"), None);
+    }
+
+    #[test]
+    fn quiet_hours_wrap_midnight() {
+        let ranges = vec!["22:00-08:00".to_string(), "12:00-13:00".to_string()];
+        assert!(in_quiet_hours(&ranges, 23 * 60));
+        assert!(in_quiet_hours(&ranges, 7 * 60 + 59));
+        assert!(!in_quiet_hours(&ranges, 8 * 60));
+        assert!(in_quiet_hours(&ranges, 12 * 60 + 30));
+        assert!(!in_quiet_hours(&ranges, 13 * 60));
+        assert!(!in_quiet_hours(&[], 0));
+    }
+
+    #[test]
+    fn next_minute_rolls_over_to_tomorrow() {
+        // 17:30:20 local, in seconds since some midnight
+        let now = 17 * 3600 + 30 * 60 + 20;
+        assert_eq!(next_minute(18 * 60, 17 * 60 + 30, now), 18 * 3600);
+        assert_eq!(next_minute(9 * 60, 17 * 60 + 30, now), 86_400 + 9 * 3600);
+        assert_eq!(next_minute(17 * 60 + 30, 17 * 60 + 30, now), 86_400 + 17 * 3600 + 30 * 60);
+    }
+}
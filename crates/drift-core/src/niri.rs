@@ -2,22 +2,7 @@ use anyhow::{bail, Context};
 use niri_ipc::socket::Socket;
 use niri_ipc::{Action, Request, Response, SizeChange, Window, Workspace, WorkspaceReferenceArg};
 
-/// Parse a width config string ("60%", "800px", "0.6") into a niri SizeChange.
-pub fn parse_width(width: &str) -> Option<SizeChange> {
-    let trimmed = width.trim();
-    if let Some(pct) = trimmed.strip_suffix('%') {
-        let val: f64 = pct.trim().parse().ok()?;
-        Some(SizeChange::SetProportion(val))
-    } else if let Some(px) = trimmed.strip_suffix("px") {
-        let val: i32 = px.trim().parse().ok()?;
-        Some(SizeChange::SetFixed(val))
-    } else if let Ok(val) = trimmed.parse::<f64>() {
-        // Bare float treated as proportion (0.6 → 60%)
-        Some(SizeChange::SetProportion(val * 100.0))
-    } else {
-        None
-    }
-}
+use crate::compositor::{self, Compositor};
 
 pub struct NiriClient {
     socket: Socket,
@@ -132,27 +117,35 @@ impl NiriClient {
         }
     }
 
-    pub fn focus_workspace_down(&mut self) -> anyhow::Result<()> {
-        let reply = self
-            .socket
-            .send(Request::Action(Action::FocusWorkspaceDown {}))?;
-        match reply {
-            Ok(Response::Handled) => Ok(()),
-            Ok(other) => bail!("unexpected response: {other:?}"),
-            Err(msg) => bail!("niri error: {msg}"),
-        }
-    }
-
+    /// Create a workspace called `name` and focus it. niri keeps an empty
+    /// workspace at the end of each monitor; the one on the focused monitor
+    /// is named by id, so the user moving focus while a project opens can't
+    /// get some other workspace renamed.
     pub fn create_named_workspace(&mut self, name: &str) -> anyhow::Result<()> {
-        // Focus past the last workspace to create a new empty one, then name it
-        let workspaces = self.workspaces()?;
-        let max_idx = workspaces.len();
-        // Focus workspace beyond the last to create a new one
-        for _ in 0..max_idx {
-            self.focus_workspace_down()?;
+        for _ in 0..3 {
+            let workspaces = self.workspaces()?;
+            let id = trailing_empty_workspace(&workspaces)
+                .context("no empty workspace on the focused monitor")?;
+            self.action(Action::SetWorkspaceName {
+                name: name.to_string(),
+                workspace: Some(WorkspaceReferenceArg::Id(id)),
+            })?;
+
+            // A window may have landed on it between listing and naming
+            let named = self.workspaces()?.into_iter().find(|ws| ws.id == id);
+            match named {
+                Some(ws) if ws.name.as_deref() == Some(name) && ws.active_window_id.is_none() => {
+                    return self.focus_workspace_id(id);
+                }
+                Some(_) => {
+                    let _ = self.action(Action::UnsetWorkspaceName {
+                        reference: Some(WorkspaceReferenceArg::Id(id)),
+                    });
+                }
+                None => {}
+            }
         }
-        self.set_workspace_name(name)?;
-        Ok(())
+        bail!("could not create workspace '{name}': workspaces kept changing")
     }
 
     pub fn find_window_by_title(&mut self, title: &str) -> anyhow::Result<Option<Window>> {
@@ -188,6 +181,60 @@ impl NiriClient {
         }
     }
 
+    pub fn focus_window(&mut self, id: u64) -> anyhow::Result<()> {
+        self.action(Action::FocusWindow { id })
+    }
+
+    pub fn focus_workspace_id(&mut self, id: u64) -> anyhow::Result<()> {
+        self.action(Action::FocusWorkspace {
+            reference: WorkspaceReferenceArg::Id(id),
+        })
+    }
+
+    pub fn move_window_to_floating(&mut self, id: u64) -> anyhow::Result<()> {
+        self.action(Action::MoveWindowToFloating { id: Some(id) })
+    }
+
+    pub fn fullscreen_window(&mut self, id: u64) -> anyhow::Result<()> {
+        self.action(Action::FullscreenWindow { id: Some(id) })
+    }
+
+    /// Move a window to another workspace without following it there.
+    pub fn move_window_to_workspace(&mut self, id: u64, workspace_id: u64) -> anyhow::Result<()> {
+        self.action(Action::MoveWindowToWorkspace {
+            window_id: Some(id),
+            reference: WorkspaceReferenceArg::Id(workspace_id),
+            focus: false,
+        })
+    }
+
+    /// Move the focused window's column to a 1-based position.
+    pub fn move_column_to_index(&mut self, index: usize) -> anyhow::Result<()> {
+        self.action(Action::MoveColumnToIndex { index })
+    }
+
+    pub fn move_window_to_monitor(&mut self, id: u64, output: &str) -> anyhow::Result<()> {
+        self.action(Action::MoveWindowToMonitor {
+            id: Some(id),
+            output: output.to_string(),
+        })
+    }
+
+    pub fn move_workspace_to_monitor(&mut self, name: &str, output: &str) -> anyhow::Result<()> {
+        self.action(Action::MoveWorkspaceToMonitor {
+            output: output.to_string(),
+            reference: Some(WorkspaceReferenceArg::Name(name.to_string())),
+        })
+    }
+
+    fn action(&mut self, action: Action) -> anyhow::Result<()> {
+        match self.socket.send(Request::Action(action))? {
+            Ok(Response::Handled) => Ok(()),
+            Ok(other) => bail!("unexpected response: {other:?}"),
+            Err(msg) => bail!("niri error: {msg}"),
+        }
+    }
+
     pub fn unset_workspace_name(&mut self, name: &str) -> anyhow::Result<()> {
         let reply =
             self.socket
@@ -201,3 +248,122 @@ impl NiriClient {
         }
     }
 }
+
+impl Compositor for NiriClient {
+    fn workspaces(&mut self) -> anyhow::Result<Vec<compositor::Workspace>> {
+        Ok(NiriClient::workspaces(self)?.into_iter().map(into_workspace).collect())
+    }
+
+    fn windows(&mut self) -> anyhow::Result<Vec<compositor::Window>> {
+        Ok(NiriClient::windows(self)?.into_iter().map(into_window).collect())
+    }
+
+    fn focused_window(&mut self) -> anyhow::Result<Option<compositor::Window>> {
+        Ok(NiriClient::focused_window(self)?.map(into_window))
+    }
+
+    fn focus_workspace(&mut self, name: &str) -> anyhow::Result<()> {
+        NiriClient::focus_workspace(self, name)
+    }
+
+    fn focus_workspace_id(&mut self, id: u64) -> anyhow::Result<()> {
+        NiriClient::focus_workspace_id(self, id)
+    }
+
+    fn create_named_workspace(&mut self, name: &str) -> anyhow::Result<()> {
+        NiriClient::create_named_workspace(self, name)
+    }
+
+    fn unset_workspace_name(&mut self, name: &str) -> anyhow::Result<()> {
+        NiriClient::unset_workspace_name(self, name)
+    }
+
+    fn rename_workspace(&mut self, project: &str, new_name: &str) -> anyhow::Result<()> {
+        NiriClient::rename_workspace(self, project, new_name)
+    }
+
+    fn spawn(&mut self, command: Vec<String>) -> anyhow::Result<()> {
+        NiriClient::spawn(self, command)
+    }
+
+    fn close_window(&mut self, id: u64) -> anyhow::Result<()> {
+        NiriClient::close_window(self, id)
+    }
+
+    fn focus_window(&mut self, id: u64) -> anyhow::Result<()> {
+        NiriClient::focus_window(self, id)
+    }
+
+    fn set_window_width(&mut self, id: u64, change: compositor::SizeChange) -> anyhow::Result<()> {
+        NiriClient::set_window_width(self, id, into_size_change(change))
+    }
+
+    fn set_window_height(&mut self, id: u64, change: compositor::SizeChange) -> anyhow::Result<()> {
+        NiriClient::set_window_height(self, id, into_size_change(change))
+    }
+
+    fn move_window_to_floating(&mut self, id: u64) -> anyhow::Result<()> {
+        NiriClient::move_window_to_floating(self, id)
+    }
+
+    fn fullscreen_window(&mut self, id: u64) -> anyhow::Result<()> {
+        NiriClient::fullscreen_window(self, id)
+    }
+
+    fn move_column_to_index(&mut self, index: usize) -> anyhow::Result<()> {
+        NiriClient::move_column_to_index(self, index)
+    }
+
+    fn move_window_to_monitor(&mut self, id: u64, output: &str) -> anyhow::Result<()> {
+        NiriClient::move_window_to_monitor(self, id, output)
+    }
+
+    fn move_workspace_to_monitor(&mut self, name: &str, output: &str) -> anyhow::Result<()> {
+        NiriClient::move_workspace_to_monitor(self, name, output)
+    }
+}
+
+fn into_workspace(ws: Workspace) -> compositor::Workspace {
+    compositor::Workspace {
+        id: ws.id,
+        idx: ws.idx,
+        name: ws.name,
+        output: ws.output,
+        is_active: ws.is_active,
+        is_focused: ws.is_focused,
+        active_window_id: ws.active_window_id,
+    }
+}
+
+fn into_window(w: Window) -> compositor::Window {
+    compositor::Window {
+        id: w.id,
+        title: w.title,
+        app_id: w.app_id,
+        pid: w.pid,
+        workspace_id: w.workspace_id,
+        is_focused: w.is_focused,
+        is_floating: w.is_floating,
+        size: Some(w.layout.tile_size),
+        column: w.layout.pos_in_scrolling_layout.map(|(col, _)| col),
+        tile: w.layout.pos_in_scrolling_layout.map(|(_, tile)| tile),
+    }
+}
+
+fn into_size_change(change: compositor::SizeChange) -> SizeChange {
+    match change {
+        compositor::SizeChange::Proportion(p) => SizeChange::SetProportion(p),
+        compositor::SizeChange::Fixed(px) => SizeChange::SetFixed(px),
+    }
+}
+
+/// The empty workspace niri keeps last on the focused monitor.
+fn trailing_empty_workspace(workspaces: &[Workspace]) -> Option<u64> {
+    let output = workspaces.iter().find(|ws| ws.is_focused)?.output.as_deref();
+    workspaces
+        .iter()
+        .filter(|ws| ws.output.as_deref() == output)
+        .max_by_key(|ws| ws.idx)
+        .filter(|ws| ws.name.is_none() && ws.active_window_id.is_none())
+        .map(|ws| ws.id)
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn config_dir() -> PathBuf {
     dirs::config_dir()
@@ -25,6 +25,11 @@ pub fn logs_dir(project: &str) -> PathBuf {
     state_dir(project).join("logs")
 }
 
+/// Files attached to events with `drift notify --file`
+pub fn attachments_dir(project: &str) -> PathBuf {
+    state_dir(project).join("attachments")
+}
+
 pub fn handoff_dir(project: &str) -> PathBuf {
     state_dir(project).join("handoffs")
 }
@@ -45,6 +50,11 @@ pub fn templates_dir() -> PathBuf {
     config_dir().join("templates")
 }
 
+/// Encrypted `drift secret` stores and the age identity that opens them.
+pub fn secrets_dir() -> PathBuf {
+    config_dir().join("secrets")
+}
+
 pub fn global_config_path() -> PathBuf {
     config_dir().join("config.toml")
 }
@@ -53,10 +63,45 @@ pub fn project_config_path(name: &str) -> PathBuf {
     projects_dir().join(format!("{name}.toml"))
 }
 
+/// Scratchpad location: a configured `[scratchpad] file` is relative to the
+/// repo (absolute and `~/` paths are used as-is); without one it lives in the
+/// project state dir.
+pub fn scratchpad_path(project: &str, repo_path: &Path, file: Option<&str>) -> PathBuf {
+    let Some(file) = file else {
+        return state_dir(project).join("scratchpad.md");
+    };
+    if let Some(rest) = file.strip_prefix("~/") {
+        dirs::home_dir()
+            .expect("could not determine home directory")
+            .join(rest)
+    } else {
+        repo_path.join(file)
+    }
+}
+
 pub fn supervisor_pid_path(project: &str) -> PathBuf {
     state_dir(project).join("supervisor.pid")
 }
 
+pub fn supervisor_restart_request_path(project: &str) -> PathBuf {
+    state_dir(project).join("restart-request")
+}
+
+/// Present while the project's services are suspended for idleness
+pub fn services_suspended_path(project: &str) -> PathBuf {
+    state_dir(project).join("suspended")
+}
+
+/// Written once a project's `[setup]` commands have all succeeded
+pub fn setup_done_path(project: &str) -> PathBuf {
+    state_dir(project).join("setup-done")
+}
+
+/// Profile of a `browser` window, one per project and browser
+pub fn browser_profile_dir(project: &str, browser: &str) -> PathBuf {
+    state_dir(project).join("browser").join(browser)
+}
+
 pub fn services_state_path(project: &str) -> PathBuf {
     state_dir(project).join("services.json")
 }
@@ -65,10 +110,23 @@ pub fn workspace_state_path(project: &str) -> PathBuf {
     state_dir(project).join("workspace.json")
 }
 
+/// Windows the daemon detected in a project with `auto_sync = false`,
+/// waiting for `drift sync review`
+pub fn pending_windows_path(project: &str) -> PathBuf {
+    state_dir(project).join("pending_windows.json")
+}
+
 pub fn task_queue_path(project: &str) -> PathBuf {
     state_dir(project).join("tasks.json")
 }
 
+/// Entries of `state_base_dir` that aren't project state. A project with one
+/// of these names would share its state dir with them.
+pub const RESERVED_PROJECT_NAMES: &[&str] = &[
+    "events", "spool", "trash", "daemon.pid", "daemon.json", "commander.pid", "commander.json",
+    "session.json", "focus-time.json", "timings.jsonl",
+];
+
 pub fn state_base_dir() -> PathBuf {
     dirs::state_dir()
         .expect("could not determine state directory")
@@ -83,14 +141,38 @@ pub fn daemon_state_path() -> PathBuf {
     state_base_dir().join("daemon.json")
 }
 
+/// Daily JSONL archives of every event the daemon has seen
+pub fn event_log_dir() -> PathBuf {
+    state_base_dir().join("events")
+}
+
+/// Events emitted while the daemon was down, one JSON file each, replayed
+/// when it next starts
+pub fn event_spool_dir() -> PathBuf {
+    state_base_dir().join("spool")
+}
+
+/// Deleted projects, one `<name>-<unix secs>/` directory each
+pub fn trash_dir() -> PathBuf {
+    state_base_dir().join("trash")
+}
+
 pub fn emit_socket_path() -> PathBuf {
-    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".into());
-    PathBuf::from(runtime_dir).join("drift").join("emit.sock")
+    drift_client::paths::emit_socket_path()
 }
 
 pub fn subscribe_socket_path() -> PathBuf {
+    drift_client::paths::subscribe_socket_path()
+}
+
+/// Socket of the project's own ssh-agent (`[ssh] key`)
+pub fn ssh_agent_socket_path(project: &str) -> PathBuf {
     let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".into());
-    PathBuf::from(runtime_dir).join("drift").join("subscribe.sock")
+    PathBuf::from(runtime_dir).join("drift").join(format!("ssh-{project}.sock"))
+}
+
+pub fn ssh_agent_pid_path(project: &str) -> PathBuf {
+    state_dir(project).join("ssh-agent.pid")
 }
 
 pub fn notify_socket_path() -> PathBuf {
@@ -101,10 +183,6 @@ pub fn commander_pid_path() -> PathBuf {
     state_base_dir().join("commander.pid")
 }
 
-pub fn commander_muted_path() -> PathBuf {
-    state_base_dir().join("commander.muted")
-}
-
 pub fn commander_state_path() -> PathBuf {
     state_base_dir().join("commander.json")
 }
@@ -117,6 +195,16 @@ pub fn session_path() -> PathBuf {
     state_base_dir().join("session.json")
 }
 
+/// Seconds each project had focus, per day (`drift time`)
+pub fn focus_time_path() -> PathBuf {
+    state_base_dir().join("focus-time.json")
+}
+
+/// Rolling log of open/close/to step timings
+pub fn timings_path() -> PathBuf {
+    state_base_dir().join("timings.jsonl")
+}
+
 pub fn models_dir() -> PathBuf {
     dirs::cache_dir()
         .expect("could not determine cache directory")
@@ -192,4 +280,17 @@ mod tests {
         let p = templates_dir();
         assert_eq!(p, config_dir().join("templates"));
     }
+
+    #[test]
+    fn scratchpad_path_defaults_to_state_dir() {
+        let repo = Path::new("/code/myapp");
+        assert_eq!(scratchpad_path("myapp", repo, None), state_dir("myapp").join("scratchpad.md"));
+    }
+
+    #[test]
+    fn scratchpad_path_relative_to_repo() {
+        let repo = Path::new("/code/myapp");
+        assert_eq!(scratchpad_path("myapp", repo, Some("notes.md")), repo.join("notes.md"));
+        assert_eq!(scratchpad_path("myapp", repo, Some("/tmp/notes.md")), PathBuf::from("/tmp/notes.md"));
+    }
 }
@@ -126,24 +126,18 @@ pub fn process_completed_dispatch(project: &str, task_id: &str) -> Result<()> {
             queue.save(project)?;
         }
 
-        events::try_emit_event(&events::Event {
-            event_type: "task.needs_review".into(),
-            project: project.into(),
-            source: "post-dispatch".into(),
-            ts: events::iso_now(),
-            level: Some("warning".into()),
-            title: Some(format!("Task {} has file conflicts", task_id)),
-            body: Some(format!("Overlapping files with: {}", conflict_details.join("; "))),
-            meta: Some(serde_json::json!({
+        events::try_emit_event(&events::Event::new("task.needs_review", project, "post-dispatch")
+            .level("warning")
+            .title(format!("Task {} has file conflicts", task_id))
+            .body(format!("Overlapping files with: {}", conflict_details.join("; ")))
+            .meta(serde_json::json!({
                 "task_id": task_id,
                 "reason": "file-conflict",
                 "conflicts": conflicts.iter().map(|c| serde_json::json!({
                     "task": c.other_task_id,
                     "files": c.overlapping_files,
                 })).collect::<Vec<_>>(),
-            })),
-            priority: None,
-        });
+            })));
         return Ok(());
     }
 
@@ -159,17 +153,11 @@ pub fn process_completed_dispatch(project: &str, task_id: &str) -> Result<()> {
                 if !result.passed {
                     queue.needs_review(task_id)?;
                     queue.save(project)?;
-                    events::try_emit_event(&events::Event {
-                        event_type: "task.needs_review".into(),
-                        project: project.into(),
-                        source: "post-dispatch".into(),
-                        ts: events::iso_now(),
-                        level: Some("warning".into()),
-                        title: Some(format!("Task {} verification failed", task_id)),
-                        body: Some(result.output),
-                        meta: Some(serde_json::json!({"task_id": task_id, "reason": "verification-failed"})),
-                        priority: None,
-                    });
+                    events::try_emit_event(&events::Event::new("task.needs_review", project, "post-dispatch")
+                        .level("warning")
+                        .title(format!("Task {} verification failed", task_id))
+                        .body(result.output)
+                        .meta(serde_json::json!({"task_id": task_id, "reason": "verification-failed"})));
                     return Ok(());
                 }
                 Some(true)
@@ -208,23 +196,16 @@ pub fn process_completed_dispatch(project: &str, task_id: &str) -> Result<()> {
     queue.save(project)?;
 
     // Emit completion event
-    events::try_emit_event(&events::Event {
-        event_type: "task.completed".into(),
-        project: project.into(),
-        source: "post-dispatch".into(),
-        ts: events::iso_now(),
-        level: Some("success".into()),
-        title: Some(format!("Task {} completed", task_id)),
-        body: None,
-        meta: Some(serde_json::json!({
+    events::try_emit_event(&events::Event::new("task.completed", project, "post-dispatch")
+        .level("success")
+        .title(format!("Task {} completed", task_id))
+        .meta(serde_json::json!({
             "task_id": task_id,
             "agent": ho.agent,
             "files_changed": ho.files_changed.len(),
             "tests_passed": ho.tests_passed,
             "verification_passed": verification_passed,
-        })),
-        priority: None,
-    });
+        })));
 
     Ok(())
 }
@@ -253,17 +234,10 @@ fn task_component(project: &str, task_id: &str) -> Option<String> {
 }
 
 fn emit_task_event(event_type: &str, project: &str, task_id: &str, reason: &str) {
-    events::try_emit_event(&events::Event {
-        event_type: event_type.into(),
-        project: project.into(),
-        source: "post-dispatch".into(),
-        ts: events::iso_now(),
-        level: Some(if event_type.contains("failed") { "error" } else { "warning" }.into()),
-        title: Some(format!("Task {}", task_id)),
-        body: None,
-        meta: Some(serde_json::json!({"task_id": task_id, "reason": reason})),
-        priority: None,
-    });
+    events::try_emit_event(&events::Event::new(event_type, project, "post-dispatch")
+        .level(if event_type.contains("failed") { "error" } else { "warning" })
+        .title(format!("Task {}", task_id))
+        .meta(serde_json::json!({"task_id": task_id, "reason": reason})));
 }
 
 #[cfg(test)]
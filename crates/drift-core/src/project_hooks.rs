@@ -0,0 +1,96 @@
+//! `on_open` / `on_close` commands: scripts a project needs run each time
+//! it opens or closes that don't fit the service model (bringing up a VPN,
+//! seeding a database, tearing down containers). They run in order from
+//! the repo root with the project env; output goes to logs/hooks.log.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::bail;
+
+use crate::config::{self, ProjectConfig};
+use crate::events::{self, Event};
+use crate::{env, paths, resources};
+
+/// How long one command may run before it and everything it started are
+/// killed, so a hung script can't hold up open or close.
+pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Open,
+    Close,
+}
+
+impl Stage {
+    pub fn key(self) -> &'static str {
+        match self {
+            Stage::Open => "on_open",
+            Stage::Close => "on_close",
+        }
+    }
+}
+
+pub fn commands(project: &ProjectConfig, stage: Stage) -> &[String] {
+    match stage {
+        Stage::Open => &project.on_open,
+        Stage::Close => &project.on_close,
+    }
+}
+
+/// Run the stage's commands, stopping at the first failure, which is
+/// emitted as `project.hook_failed`.
+pub fn run(project: &ProjectConfig, stage: Stage) -> anyhow::Result<()> {
+    let commands = commands(project, stage);
+    if commands.is_empty() {
+        return Ok(());
+    }
+    let name = &project.project.name;
+    let env_vars = env::build_process_env(project)?;
+    let repo_path = config::resolve_repo_path(&project.project.repo)?;
+
+    let logs_dir = paths::logs_dir(name);
+    fs::create_dir_all(&logs_dir)?;
+    let log_path = logs_dir.join("hooks.log");
+    let mut log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+
+    for command in commands {
+        writeln!(log, "\n[{}] $ {command}", stage.key())?;
+        let child = Command::new("sh")
+            .args(["-c", command])
+            .envs(&env_vars)
+            .current_dir(&repo_path)
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log.try_clone()?)
+            // Own process group, so a timeout also kills whatever it started
+            .process_group(0)
+            .spawn();
+        let status = child.and_then(|mut c| resources::wait_group_timeout(&mut c, COMMAND_TIMEOUT));
+        let (exit_code, timed_out) = match status {
+            Ok(Some(s)) if s.success() => continue,
+            Ok(Some(s)) => (s.code(), false),
+            Ok(None) => (None, true),
+            Err(_) => (None, false),
+        };
+        if timed_out {
+            writeln!(log, "--- timed out after {}s ---", COMMAND_TIMEOUT.as_secs())?;
+        } else {
+            writeln!(log, "--- failed (exit {}) ---", exit_code.map_or("?".into(), |c| c.to_string()))?;
+        }
+        events::try_emit_event(&Event::new("project.hook_failed", name.clone(), "drift")
+            .level("error")
+            .title(format!("{} hook failed: {command}", stage.key()))
+            .meta(serde_json::json!({
+                "stage": stage.key(),
+                "command": command,
+                "exit_code": exit_code,
+                "timed_out": timed_out,
+            })));
+        bail!("{} command `{command}` failed; see {}", stage.key(), log_path.display());
+    }
+    Ok(())
+}
@@ -1,8 +1,23 @@
+use std::path::Path;
+
 use anyhow::Context;
 
 use crate::config::{self, ProjectConfig};
 use crate::paths;
 
+/// Refuse names a new project can't have: ones that aren't a single path
+/// component, and ones whose state dir would be drift's own (see
+/// [`paths::RESERVED_PROJECT_NAMES`]).
+pub fn check_project_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\0') {
+        anyhow::bail!("Invalid project name '{name}'");
+    }
+    if paths::RESERVED_PROJECT_NAMES.contains(&name) {
+        anyhow::bail!("'{name}' is reserved for drift's own state; pick another project name");
+    }
+    Ok(())
+}
+
 pub fn list_projects() -> anyhow::Result<Vec<ProjectConfig>> {
     let dir = paths::projects_dir();
     if !dir.exists() {
@@ -33,6 +48,21 @@ pub fn list_projects() -> anyhow::Result<Vec<ProjectConfig>> {
     Ok(projects)
 }
 
+/// Project whose repo contains `path`, preferring the most deeply nested repo.
+pub fn project_for_path(path: &std::path::Path) -> anyhow::Result<Option<String>> {
+    let mut best: Option<(usize, String)> = None;
+    for project in list_projects()? {
+        let Ok(repo) = config::resolve_repo_path(&project.project.repo) else {
+            continue;
+        };
+        let depth = repo.components().count();
+        if path.starts_with(&repo) && best.as_ref().is_none_or(|(d, _)| depth > *d) {
+            best = Some((depth, project.project.name));
+        }
+    }
+    Ok(best.map(|(_, name)| name))
+}
+
 pub fn find_project(name: &str) -> anyhow::Result<ProjectConfig> {
     config::load_project_config(name)
 }
@@ -76,7 +106,9 @@ pub fn archive_project(name: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn unarchive_project(name: &str) -> anyhow::Result<()> {
+/// Restore an archived project: its packed state first, so a failure
+/// leaves it fully archived, then its config. True when state was restored.
+pub fn unarchive_project(name: &str) -> anyhow::Result<bool> {
     let archived = paths::archived_projects_dir().join(format!("{name}.toml"));
     if !archived.exists() {
         anyhow::bail!("Archived project '{name}' not found");
@@ -87,9 +119,82 @@ pub fn unarchive_project(name: &str) -> anyhow::Result<()> {
         anyhow::bail!("Active project '{name}' already exists");
     }
 
+    let restored = unpack_state(name)?;
     std::fs::rename(&archived, &dest)
         .with_context(|| format!("restoring config to {}", dest.display()))?;
 
+    Ok(restored)
+}
+
+/// Where `archive --keep-state` packs a project's state and logs.
+pub fn archived_state_path(name: &str) -> std::path::PathBuf {
+    paths::archived_projects_dir().join(format!("{name}.state.tar.gz"))
+}
+
+/// Tar the state directory next to the archived config, then remove it.
+pub fn pack_state(name: &str) -> anyhow::Result<bool> {
+    let state = paths::state_dir(name);
+    if !state.exists() {
+        return Ok(false);
+    }
+    tar_dir(&state, &archived_state_path(name))?;
+    std::fs::remove_dir_all(&state)
+        .with_context(|| format!("removing state directory {}", state.display()))?;
+    Ok(true)
+}
+
+/// Unpack state saved by `pack_state`, if there is any.
+pub fn unpack_state(name: &str) -> anyhow::Result<bool> {
+    let archive = archived_state_path(name);
+    if !archive.exists() {
+        return Ok(false);
+    }
+    untar_state(name, &archive)?;
+    std::fs::remove_file(&archive)?;
+    Ok(true)
+}
+
+/// Write `dir` into a gzipped tarball at `dest`, with paths relative to
+/// its parent.
+pub(crate) fn tar_dir(dir: &Path, dest: &Path) -> anyhow::Result<()> {
+    let parent = dir.parent().context("directory has no parent")?;
+    let name = dir.file_name().context("directory has no name")?;
+    let status = std::process::Command::new("tar")
+        .arg("-czf")
+        .arg(dest)
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .status()
+        .context("running tar")?;
+    if !status.success() {
+        let _ = std::fs::remove_file(dest);
+        anyhow::bail!("tar failed packing {}", dir.display());
+    }
+    Ok(())
+}
+
+/// Unpack a tarball made by `tar_dir` from a project's state directory
+/// back into place, refusing to overwrite existing state.
+pub(crate) fn untar_state(name: &str, archive: &Path) -> anyhow::Result<()> {
+    let state = paths::state_dir(name);
+    if state.exists() {
+        anyhow::bail!("State directory {} already exists; not overwriting it", state.display());
+    }
+    let parent = state.parent().context("state directory has no parent")?;
+    std::fs::create_dir_all(parent)?;
+    let status = std::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(archive)
+        .arg("-C")
+        .arg(parent)
+        .status()
+        .context("running tar")?;
+    if !status.success() {
+        // Don't leave a partial state directory in the way of the next try
+        let _ = std::fs::remove_dir_all(&state);
+        anyhow::bail!("tar failed unpacking {}", archive.display());
+    }
     Ok(())
 }
 
@@ -122,3 +227,17 @@ pub fn list_archived() -> anyhow::Result<Vec<ProjectConfig>> {
 
     Ok(projects)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_and_path_like_names_rejected() {
+        for name in ["trash", "events", "spool", "daemon.json", "", "..", "a/b"] {
+            assert!(check_project_name(name).is_err(), "{name}");
+        }
+        assert!(check_project_name("myapp").is_ok());
+        assert!(check_project_name("trash-panda").is_ok());
+    }
+}
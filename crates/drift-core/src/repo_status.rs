@@ -0,0 +1,128 @@
+//! A quick look at a project's repo: branch, changed files and how far it
+//! is ahead of or behind its upstream, from one `git status` call.
+
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// How long a background fetch may take before it's killed.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoStatus {
+    /// None on a detached HEAD
+    pub branch: Option<String>,
+    /// Changed, staged and untracked files
+    pub changed: usize,
+    pub ahead: u32,
+    pub behind: u32,
+    pub has_upstream: bool,
+}
+
+impl RepoStatus {
+    /// "main (3 changed, 1 ahead, 2 behind)", or just "main" when clean and
+    /// up to date.
+    pub fn summary(&self) -> String {
+        let branch = self.branch.as_deref().unwrap_or("(detached)");
+        let mut parts = Vec::new();
+        if self.changed > 0 {
+            parts.push(format!("{} changed", self.changed));
+        }
+        if self.ahead > 0 {
+            parts.push(format!("{} ahead", self.ahead));
+        }
+        if self.behind > 0 {
+            parts.push(format!("{} behind", self.behind));
+        }
+        if parts.is_empty() {
+            branch.to_string()
+        } else {
+            format!("{branch} ({})", parts.join(", "))
+        }
+    }
+}
+
+/// None when `repo` isn't a git repo or git isn't installed.
+pub fn inspect(repo: &Path) -> Option<RepoStatus> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain=v2", "--branch"])
+        .current_dir(repo)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then(|| parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Update the remote-tracking refs so ahead/behind are current. Never
+/// prompts for credentials, passphrases or host keys, and gives up after
+/// `FETCH_TIMEOUT`; a failed fetch leaves the old refs.
+pub fn fetch(repo: &Path) -> bool {
+    let ssh = match std::env::var("GIT_SSH_COMMAND") {
+        Ok(cmd) if !cmd.trim().is_empty() => format!("{cmd} -o BatchMode=yes"),
+        _ => "ssh -o BatchMode=yes".to_string(),
+    };
+    let child = Command::new("git")
+        .args(["fetch", "--quiet"])
+        .current_dir(repo)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_SSH_COMMAND", ssh)
+        .env("SSH_ASKPASS_REQUIRE", "never")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Own process group, so a timeout also takes down ssh
+        .process_group(0)
+        .spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+    matches!(crate::resources::wait_group_timeout(&mut child, FETCH_TIMEOUT), Ok(Some(status)) if status.success())
+}
+
+fn parse(porcelain: &str) -> RepoStatus {
+    let mut status = RepoStatus::default();
+    for line in porcelain.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            status.branch = (head != "(detached)").then(|| head.to_string());
+        } else if line.starts_with("# branch.upstream ") {
+            status.has_upstream = true;
+        } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            for part in ab.split_whitespace() {
+                if let Some(n) = part.strip_prefix('+') {
+                    status.ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = part.strip_prefix('-') {
+                    status.behind = n.parse().unwrap_or(0);
+                }
+            }
+        } else if !line.starts_with('#') && !line.is_empty() {
+            status.changed += 1;
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_v2_parsed() {
+        let out = "# branch.oid 1234\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +1 -2\n1 .M N... 100644 100644 100644 a b src/lib.rs\n? notes.txt\n";
+        let status = parse(out);
+        assert_eq!(
+            status,
+            RepoStatus { branch: Some("main".into()), changed: 2, ahead: 1, behind: 2, has_upstream: true }
+        );
+        assert_eq!(status.summary(), "main (2 changed, 1 ahead, 2 behind)");
+    }
+
+    #[test]
+    fn clean_detached_head() {
+        let status = parse("# branch.oid 1234\n# branch.head (detached)\n");
+        assert_eq!(status.branch, None);
+        assert!(!status.has_upstream);
+        assert_eq!(status.summary(), "(detached)");
+    }
+}
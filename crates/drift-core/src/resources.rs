@@ -0,0 +1,182 @@
+use std::fs;
+use std::io;
+use std::process::{Child, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// CPU time and resident memory of a whole process group, read from /proc.
+#[derive(Debug, Clone, Copy)]
+pub struct GroupSample {
+    /// utime + stime of every process in the group, in clock ticks
+    pub cpu_ticks: u64,
+    pub rss_bytes: u64,
+    pub taken_at: Instant,
+}
+
+/// Sum usage over every process whose process group is `pgid`. Services are
+/// started with setsid, so this covers the shell and everything it spawned.
+pub fn sample_group(pgid: u32) -> Option<GroupSample> {
+    let page_size = sysconf(libc::_SC_PAGESIZE)?;
+    let mut cpu_ticks = 0;
+    let mut rss_pages = 0;
+    let mut found = false;
+
+    for entry in fs::read_dir("/proc").ok()?.flatten() {
+        let name = entry.file_name();
+        if !name.to_string_lossy().bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        let Ok(stat) = fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        let Some(fields) = parse_stat(&stat) else {
+            continue;
+        };
+        if fields.pgrp == pgid {
+            found = true;
+            cpu_ticks += fields.utime + fields.stime;
+            rss_pages += fields.rss_pages;
+        }
+    }
+
+    found.then(|| GroupSample {
+        cpu_ticks,
+        rss_bytes: rss_pages * page_size,
+        taken_at: Instant::now(),
+    })
+}
+
+/// CPU usage between two samples, as a percentage of one core.
+pub fn cpu_percent(prev: &GroupSample, now: &GroupSample) -> Option<f32> {
+    let ticks_per_sec = sysconf(libc::_SC_CLK_TCK)? as f64;
+    let elapsed = now.taken_at.duration_since(prev.taken_at).as_secs_f64();
+    if elapsed <= 0.0 {
+        return None;
+    }
+    let ticks = now.cpu_ticks.saturating_sub(prev.cpu_ticks) as f64;
+    Some((ticks / ticks_per_sec / elapsed * 100.0) as f32)
+}
+
+/// When `pid` started, in clock ticks after boot. Stays the same for the
+/// life of the process, so a reused pid shows up as a different value.
+pub fn start_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    parse_stat(&stat).map(|fields| fields.start_ticks)
+}
+
+/// The process group `pid` belongs to.
+pub fn process_group(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    parse_stat(&stat).map(|fields| fields.pgrp)
+}
+
+/// Wait for a child spawned as its own process group leader
+/// (`process_group(0)`), killing the whole group once `timeout` passes so
+/// nothing it started lingers. None when it timed out.
+pub fn wait_group_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            unsafe {
+                libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+            }
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// How long `pid` has been running, from its start time and /proc/uptime.
+pub fn process_uptime(pid: u32) -> Option<Duration> {
+    let ticks = start_ticks(pid)?;
+    let uptime = fs::read_to_string("/proc/uptime").ok()?;
+    let since_boot: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    let started = ticks as f64 / sysconf(libc::_SC_CLK_TCK)? as f64;
+    Some(Duration::from_secs_f64((since_boot - started).max(0.0)))
+}
+
+/// Human-readable size, e.g. "512K", "145M", "1.2G".
+pub fn format_bytes(bytes: u64) -> String {
+    const K: u64 = 1024;
+    match bytes {
+        b if b >= K * K * K => format!("{:.1}G", b as f64 / (K * K * K) as f64),
+        b if b >= K * K => format!("{}M", b / (K * K)),
+        b => format!("{}K", b / K),
+    }
+}
+
+struct StatFields {
+    pgrp: u32,
+    utime: u64,
+    stime: u64,
+    rss_pages: u64,
+    start_ticks: u64,
+}
+
+/// Parse /proc/<pid>/stat. The command name can contain spaces and parens,
+/// so fields are counted from the last ')'.
+fn parse_stat(stat: &str) -> Option<StatFields> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // `rest` starts at field 3 (state), so field N is at index N - 3
+    Some(StatFields {
+        pgrp: fields.get(2)?.parse().ok()?,
+        utime: fields.get(11)?.parse().ok()?,
+        stime: fields.get(12)?.parse().ok()?,
+        start_ticks: fields.get(19)?.parse().ok()?,
+        rss_pages: fields.get(21)?.parse().ok()?,
+    })
+}
+
+fn sysconf(name: libc::c_int) -> Option<u64> {
+    let value = unsafe { libc::sysconf(name) };
+    (value > 0).then_some(value as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stat_handles_parens_in_comm() {
+        let stat = "1234 (my (weird) cmd) S 1 1234 1234 0 -1 4194560 100 0 0 0 250 50 0 0 20 0 1 0 5000 10000000 2048 18446744073709551615";
+        let fields = parse_stat(stat).unwrap();
+        assert_eq!(fields.pgrp, 1234);
+        assert_eq!(fields.utime, 250);
+        assert_eq!(fields.stime, 50);
+        assert_eq!(fields.rss_pages, 2048);
+        assert_eq!(fields.start_ticks, 5000);
+    }
+
+    #[test]
+    fn start_ticks_of_own_process_are_stable() {
+        let pid = std::process::id();
+        let ticks = start_ticks(pid).unwrap();
+        assert_eq!(start_ticks(pid), Some(ticks));
+    }
+
+    #[test]
+    fn sample_own_process_group() {
+        let pgid = unsafe { libc::getpgrp() } as u32;
+        assert_eq!(process_group(std::process::id()), Some(pgid));
+        let sample = sample_group(pgid).unwrap();
+        assert!(sample.rss_bytes > 0);
+    }
+
+    #[test]
+    fn own_uptime_is_short() {
+        let uptime = process_uptime(std::process::id()).unwrap();
+        assert!(uptime < Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn format_bytes_units() {
+        assert_eq!(format_bytes(512 * 1024), "512K");
+        assert_eq!(format_bytes(145 * 1024 * 1024), "145M");
+        assert_eq!(format_bytes(1536 * 1024 * 1024), "1.5G");
+    }
+}
@@ -0,0 +1,183 @@
+//! Cron schedules for services that run periodically instead of staying up
+//! (`schedule = "*/15 * * * *"`). Standard five fields in local time:
+//! minute, hour, day of month, month, day of week. Each field takes `*`,
+//! numbers, ranges `a-b`, steps `*/n` or `a-b/n`, and comma lists. As in
+//! cron, when both day fields are restricted a day matching either runs.
+
+use anyhow::{bail, Context};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+/// Local calendar fields of a point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LocalTime {
+    minute: u32,
+    hour: u32,
+    day: u32,
+    month: u32,
+    weekday: u32,
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> anyhow::Result<Schedule> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            bail!("invalid schedule '{expr}' (expected 5 fields: minute hour day month weekday, or @daily etc.)");
+        };
+        let field = |value: &str, min, max, name: &str| {
+            parse_field(value, min, max).map_err(|e| anyhow::anyhow!("invalid schedule '{expr}': {name} {e}"))
+        };
+        let mut weekdays = field(weekday, 0, 7, "weekday")?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Schedule {
+            minutes: field(minute, 0, 59, "minute")?,
+            hours: field(hour, 0, 23, "hour")?,
+            days: field(day, 1, 31, "day")?,
+            months: field(month, 1, 12, "month")?,
+            weekdays,
+            days_restricted: *day != "*",
+            weekdays_restricted: *weekday != "*",
+        })
+    }
+
+    fn matches(&self, t: LocalTime) -> bool {
+        let day = self.days & (1 << t.day) != 0;
+        let weekday = self.weekdays & (1 << t.weekday) != 0;
+        let day_ok = match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        };
+        self.minutes & (1 << t.minute) != 0
+            && self.hours & (1 << t.hour) != 0
+            && self.months & (1 << t.month) != 0
+            && day_ok
+    }
+
+    /// Unix seconds of the first matching minute after `now`, looking at
+    /// most a year ahead (`0 0 31 2 *` never matches).
+    pub fn next_after(&self, now: i64) -> Option<i64> {
+        let start = now - now.rem_euclid(60) + 60;
+        (0..366 * 24 * 60)
+            .map(|i| start + i * 60)
+            .find(|&ts| self.matches(local_time(ts)))
+    }
+}
+
+/// One field as a bitmask of the values it allows.
+fn parse_field(field: &str, min: u32, max: u32) -> anyhow::Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().ok().filter(|s| *s > 0).with_context(|| format!("'{part}' has an invalid step"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => {
+                let (lo, hi) = range.split_once('-').unwrap_or((range, range));
+                let parse = |s: &str| s.parse::<u32>().with_context(|| format!("'{part}' is not a number or range"));
+                let (lo, hi) = (parse(lo)?, parse(hi)?);
+                // `5/15` means from 5 to the end, stepping 15
+                let hi = if step > 1 && !range.contains('-') { max } else { hi };
+                (lo, hi)
+            }
+        };
+        if lo < min || hi > max || lo > hi {
+            bail!("'{part}' is outside {min}-{max}");
+        }
+        for value in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn local_time(ts: i64) -> LocalTime {
+    let t = ts as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+        unsafe { libc::gmtime_r(&t, &mut tm) };
+    }
+    LocalTime {
+        minute: tm.tm_min as u32,
+        hour: tm.tm_hour as u32,
+        day: tm.tm_mday as u32,
+        month: tm.tm_mon as u32 + 1,
+        weekday: tm.tm_wday as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minute: u32, hour: u32, day: u32, month: u32, weekday: u32) -> LocalTime {
+        LocalTime { minute, hour, day, month, weekday }
+    }
+
+    #[test]
+    fn steps_and_ranges() {
+        let s = Schedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert!(s.matches(at(0, 9, 3, 6, 1)));
+        assert!(s.matches(at(45, 17, 3, 6, 5)));
+        assert!(!s.matches(at(10, 9, 3, 6, 1)));
+        assert!(!s.matches(at(0, 18, 3, 6, 1)));
+        assert!(!s.matches(at(0, 9, 3, 6, 0)));
+    }
+
+    #[test]
+    fn either_day_field_matches_when_both_restricted() {
+        let s = Schedule::parse("0 0 1 * 0").unwrap();
+        assert!(s.matches(at(0, 0, 1, 6, 3)));
+        assert!(s.matches(at(0, 0, 9, 6, 0)));
+        assert!(!s.matches(at(0, 0, 9, 6, 3)));
+    }
+
+    #[test]
+    fn aliases_and_sunday_as_seven() {
+        assert_eq!(Schedule::parse("@daily").unwrap(), Schedule::parse("0 0 * * *").unwrap());
+        assert!(Schedule::parse("0 3 * * 7").unwrap().matches(at(0, 3, 9, 6, 0)));
+    }
+
+    #[test]
+    fn invalid_schedules_rejected() {
+        assert!(Schedule::parse("* * * *").is_err());
+        assert_eq!(
+            Schedule::parse("60 * * * *").unwrap_err().to_string(),
+            "invalid schedule '60 * * * *': minute '60' is outside 0-59"
+        );
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("x * * * *").is_err());
+    }
+
+    #[test]
+    fn next_run_is_a_later_whole_minute() {
+        let s = Schedule::parse("* * * * *").unwrap();
+        assert_eq!(s.next_after(1_000_000_000), Some(1_000_000_020));
+        assert_eq!(Schedule::parse("0 0 31 2 *").unwrap().next_after(1_000_000_000), None);
+    }
+}
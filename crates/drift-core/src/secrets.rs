@@ -0,0 +1,213 @@
+//! `drift secret`: values stored age-encrypted under the config dir, one
+//! store per project plus a global one, and decrypted only when a project's
+//! environment is built. Uses the `age` and `age-keygen` binaries; the
+//! identity is generated on first use.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context};
+
+use crate::paths;
+
+pub fn identity_path() -> PathBuf {
+    paths::secrets_dir().join("identity.txt")
+}
+
+/// The store for `project`, or the global store for None.
+pub fn store_path(project: Option<&str>) -> PathBuf {
+    match project {
+        Some(name) => paths::secrets_dir().join("projects").join(format!("{name}.age")),
+        None => paths::secrets_dir().join("global.age"),
+    }
+}
+
+/// Whether `key` can be used as an environment variable name.
+pub fn valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Decrypt a store. A missing store is empty.
+pub fn load(project: Option<&str>) -> anyhow::Result<BTreeMap<String, String>> {
+    let path = store_path(project);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let output = Command::new("age")
+        .arg("--decrypt")
+        .arg("--identity")
+        .arg(identity_path())
+        .arg(&path)
+        .stderr(Stdio::piped())
+        .output()
+        .context("could not run age (is it installed?)")?;
+    if !output.status.success() {
+        bail!(
+            "could not decrypt {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+pub fn set(project: Option<&str>, key: &str, value: &str) -> anyhow::Result<()> {
+    if !valid_key(key) {
+        bail!("'{key}' is not a valid environment variable name");
+    }
+    let mut values = load(project)?;
+    values.insert(key.to_string(), value.to_string());
+    save(project, &values)
+}
+
+/// Remove `key`; returns whether it was there.
+pub fn remove(project: Option<&str>, key: &str) -> anyhow::Result<bool> {
+    let mut values = load(project)?;
+    if values.remove(key).is_none() {
+        return Ok(false);
+    }
+    save(project, &values)?;
+    Ok(true)
+}
+
+/// Values for a project's `secrets` list, from its own store or else the
+/// global one. Nothing is decrypted when the list is empty.
+pub fn resolve(project: &str, keys: &[String]) -> anyhow::Result<HashMap<String, String>> {
+    if keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    pick(keys, &load(Some(project))?, &load(None)?).map_err(|missing| {
+        anyhow::anyhow!(
+            "secret{} not set: {} (use `drift secret set <KEY> --project {project}`)",
+            if missing.len() == 1 { "" } else { "s" },
+            missing.join(", ")
+        )
+    })
+}
+
+/// Look up `keys`, project values first; the error lists the missing ones.
+fn pick(
+    keys: &[String],
+    project: &BTreeMap<String, String>,
+    global: &BTreeMap<String, String>,
+) -> Result<HashMap<String, String>, Vec<String>> {
+    let mut found = HashMap::new();
+    let mut missing = Vec::new();
+    for key in keys {
+        match project.get(key).or_else(|| global.get(key)) {
+            Some(value) => {
+                found.insert(key.clone(), value.clone());
+            }
+            None => missing.push(key.clone()),
+        }
+    }
+    if missing.is_empty() {
+        Ok(found)
+    } else {
+        Err(missing)
+    }
+}
+
+fn save(project: Option<&str>, values: &BTreeMap<String, String>) -> anyhow::Result<()> {
+    let path = store_path(project);
+    if values.is_empty() {
+        let _ = fs::remove_file(&path);
+        return Ok(());
+    }
+    let recipient = recipient()?;
+    let mut child = Command::new("age")
+        .args(["--encrypt", "--recipient", &recipient])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("could not run age (is it installed?)")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&serde_json::to_vec(values)?)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("age failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)?;
+    }
+    let tmp = path.with_extension("age.tmp");
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&tmp)?
+        .write_all(&output.stdout)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// The public key for the identity, generating the identity if needed.
+fn recipient() -> anyhow::Result<String> {
+    let identity = identity_path();
+    if !identity.exists() {
+        create_private_dir(&paths::secrets_dir())?;
+        let status = Command::new("age-keygen")
+            .arg("-o")
+            .arg(&identity)
+            .stderr(Stdio::null())
+            .status()
+            .context("could not run age-keygen (is age installed?)")?;
+        if !status.success() {
+            bail!("age-keygen failed to create {}", identity.display());
+        }
+    }
+    let output = Command::new("age-keygen")
+        .arg("-y")
+        .arg(&identity)
+        .output()
+        .context("could not run age-keygen (is age installed?)")?;
+    if !output.status.success() {
+        bail!("could not read the public key from {}", identity.display());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn create_private_dir(dir: &Path) -> anyhow::Result<()> {
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_names_validated() {
+        assert!(valid_key("API_KEY"));
+        assert!(valid_key("_token2"));
+        assert!(!valid_key("2FA"));
+        assert!(!valid_key("API-KEY"));
+        assert!(!valid_key(""));
+    }
+
+    #[test]
+    fn project_values_win_over_global() {
+        let project = BTreeMap::from([("API_KEY".to_string(), "project".to_string())]);
+        let global = BTreeMap::from([
+            ("API_KEY".to_string(), "global".to_string()),
+            ("DB_URL".to_string(), "postgres://".to_string()),
+        ]);
+        let keys = vec!["API_KEY".to_string(), "DB_URL".to_string()];
+        let found = pick(&keys, &project, &global).unwrap();
+        assert_eq!(found["API_KEY"], "project");
+        assert_eq!(found["DB_URL"], "postgres://");
+
+        let keys = vec!["API_KEY".to_string(), "NOPE".to_string(), "ALSO".to_string()];
+        assert_eq!(pick(&keys, &project, &global).unwrap_err(), vec!["NOPE", "ALSO"]);
+    }
+}
@@ -0,0 +1,88 @@
+//! One-shot `[setup]` commands (`npm install`, `docker compose pull`, ...),
+//! run the first time a project opens. Output goes to logs/setup.log; a
+//! marker in the state dir records that they all succeeded.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Instant, SystemTime};
+
+use anyhow::bail;
+
+use crate::config::{self, ProjectConfig};
+use crate::events::{self, Event};
+use crate::{env, paths};
+
+/// Setup commands still to run for this project, if any.
+pub fn pending(project: &ProjectConfig) -> bool {
+    has_commands(project) && !paths::setup_done_path(&project.project.name).exists()
+}
+
+pub fn has_commands(project: &ProjectConfig) -> bool {
+    project.setup.as_ref().is_some_and(|s| !s.commands.is_empty())
+}
+
+/// Forget that setup ran, so the next open runs it again.
+pub fn reset(project: &str) -> anyhow::Result<()> {
+    match fs::remove_file(paths::setup_done_path(project)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Run every setup command in order from the repo root, stopping at the
+/// first failure. Emits `setup.completed` or `setup.failed`, and marks the
+/// project done only when all commands succeeded.
+pub fn run(project: &ProjectConfig) -> anyhow::Result<()> {
+    let name = &project.project.name;
+    let commands = project.setup.as_ref().map(|s| s.commands.as_slice()).unwrap_or_default();
+    let env_vars = env::build_process_env(project)?;
+    let repo_path = config::resolve_repo_path(&project.project.repo)?;
+
+    let logs_dir = paths::logs_dir(name);
+    fs::create_dir_all(&logs_dir)?;
+    let log_path = logs_dir.join("setup.log");
+    let mut log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+
+    let correlation_id = events::new_correlation_id();
+    let started = Instant::now();
+    for command in commands {
+        writeln!(log, "\n$ {command}")?;
+        let status = Command::new("sh")
+            .args(["-c", command])
+            .envs(&env_vars)
+            .env(events::CORRELATION_ENV, &correlation_id)
+            .current_dir(&repo_path)
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log.try_clone()?)
+            .status();
+        let exit_code = match status {
+            Ok(s) if s.success() => continue,
+            Ok(s) => s.code(),
+            Err(_) => None,
+        };
+        writeln!(log, "--- failed (exit {}) ---", exit_code.map_or("?".into(), |c| c.to_string()))?;
+        emit(name, &correlation_id, "setup.failed", "error", format!("Setup failed: {command}"), serde_json::json!({
+            "command": command,
+            "exit_code": exit_code,
+        }));
+        bail!("setup command `{command}` failed; see {}", log_path.display());
+    }
+
+    let secs = started.elapsed().as_secs();
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+    fs::write(paths::setup_done_path(name), now.to_string())?;
+    emit(name, &correlation_id, "setup.completed", "success", format!("Setup finished in {secs}s"), serde_json::json!({
+        "commands": commands.len(),
+        "duration_secs": secs,
+    }));
+    Ok(())
+}
+
+fn emit(project: &str, correlation_id: &str, event_type: &str, level: &str, title: String, meta: serde_json::Value) {
+    events::try_emit_event(&Event {
+        correlation_id: Some(correlation_id.to_string()),
+        ..Event::new(event_type, project, "setup").level(level).title(title).meta(meta)
+    });
+}
@@ -0,0 +1,144 @@
+//! Building `sh` scripts and terminal argv for spawned windows and tmux panes.
+
+/// Quote `s` as a single POSIX shell word. Safe for any input, including
+/// spaces, quotes, `$`, backticks and newlines.
+pub fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Script for a terminal or tmux pane: export the project environment, cd
+/// into `cwd`, then replace the shell with `command` (or the user's $SHELL).
+///
+/// `cwd` is a literal path and gets quoted. `command` is shell syntax from
+/// the project config, so it runs under its own `sh -c` where compound
+/// commands (`a && b`, pipes) behave as written.
+pub fn spawn_script(export_str: &str, cwd: &str, command: Option<&str>) -> String {
+    let exec = match command {
+        Some(cmd) => format!("exec sh -c {}", quote(cmd)),
+        None => "exec \"${SHELL:-sh}\"".to_string(),
+    };
+    let mut script = String::new();
+    if !export_str.is_empty() {
+        script.push_str(export_str);
+        script.push('\n');
+    }
+    script.push_str(&format!("cd {} || exit 1\n{exec}", quote(cwd)));
+    script
+}
+
+/// Window title drift uses to match its windows: `drift:<project>[/<window>]`.
+pub fn window_title(project_name: &str, window_name: Option<&str>) -> String {
+    match window_name {
+        Some(wn) => format!("drift:{project_name}/{wn}"),
+        None => format!("drift:{project_name}"),
+    }
+}
+
+/// argv for spawning `terminal` with a drift title running [`spawn_script`].
+pub fn build_terminal_args(
+    terminal: &str,
+    project_name: &str,
+    window_name: Option<&str>,
+    export_str: &str,
+    repo_path: &str,
+    command: Option<&str>,
+) -> Vec<String> {
+    vec![
+        terminal.into(),
+        format!("--title={}", window_title(project_name, window_name)),
+        "-e".into(),
+        "sh".into(),
+        "-c".into(),
+        spawn_script(export_str, repo_path, command),
+    ]
+}
+
+/// `tmux attach` command for a session name that may contain any characters.
+pub fn tmux_attach_command(session: &str) -> String {
+    format!("tmux attach -t {}", quote(session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::process::Command;
+
+    const HOSTILE: &[&str] = &[
+        "plain",
+        "with space",
+        "it's",
+        "double\"quote",
+        "$HOME",
+        "`id`",
+        "$(id)",
+        "semi;colon",
+        "back\\slash",
+        "new\nline",
+        "glob*?[x]",
+        "-leading-dash",
+        "'; rm -rf / #",
+        "",
+    ];
+
+    fn run_sh(script: &str) -> String {
+        let out = Command::new("sh").args(["-c", script]).output().unwrap();
+        assert!(out.status.success(), "script failed: {script}\n{}", String::from_utf8_lossy(&out.stderr));
+        String::from_utf8(out.stdout).unwrap()
+    }
+
+    #[test]
+    fn quote_round_trips_through_sh() {
+        for s in HOSTILE {
+            let out = run_sh(&format!("printf '%s' {}", quote(s)));
+            assert_eq!(&out, s, "quoting {s:?}");
+        }
+    }
+
+    #[test]
+    fn spawn_script_cds_into_hostile_paths() {
+        let base = tempfile::TempDir::new().unwrap();
+        for name in HOSTILE.iter().filter(|n| !n.is_empty()) {
+            let dir = base.path().join(name.replace('/', "_"));
+            std::fs::create_dir_all(&dir).unwrap();
+            let script = spawn_script("", &dir.to_string_lossy(), Some("pwd -P"));
+            let out = run_sh(&script);
+            assert_eq!(out.trim_end_matches('\n'), dir.canonicalize().unwrap().to_string_lossy(), "dir {name:?}");
+        }
+    }
+
+    #[test]
+    fn spawn_script_runs_compound_commands_and_exports() {
+        let mut env = HashMap::new();
+        env.insert("GREETING".to_string(), "it's \"here\"".to_string());
+        let exports = crate::env::format_env_exports(&env);
+        let script = spawn_script(&exports, "/", Some("printf '%s' \"$GREETING\" && printf ' ok'"));
+        assert_eq!(run_sh(&script), "it's \"here\" ok");
+    }
+
+    #[test]
+    fn spawn_script_missing_cwd_fails_instead_of_running_elsewhere() {
+        let script = spawn_script("", "/nonexistent/drift-test", Some("echo ran"));
+        let out = Command::new("sh").args(["-c", &script]).output().unwrap();
+        assert!(!out.status.success());
+        assert!(!String::from_utf8_lossy(&out.stdout).contains("ran"));
+    }
+
+    #[test]
+    fn spawn_script_defaults_to_shell() {
+        let script = spawn_script("", "/", None);
+        assert!(script.ends_with("exec \"${SHELL:-sh}\""));
+    }
+
+    #[test]
+    fn terminal_args_shape() {
+        let args = build_terminal_args("foot", "my app", Some("editor"), "", "/tmp/a b", Some("nvim"));
+        assert_eq!(args[..5], ["foot", "--title=drift:my app/editor", "-e", "sh", "-c"]);
+        assert_eq!(args[5], "cd '/tmp/a b' || exit 1\nexec sh -c 'nvim'");
+    }
+
+    #[test]
+    fn tmux_attach_quotes_session() {
+        assert_eq!(tmux_attach_command("drift:it's"), "tmux attach -t 'drift:it'\\''s'");
+    }
+}
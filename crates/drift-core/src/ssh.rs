@@ -0,0 +1,100 @@
+//! A project's own ssh-agent (`[ssh] key = "~/.ssh/id_client"`), started
+//! on `drift open` with just that key loaded and killed on close. While it
+//! runs, the project env points SSH_AUTH_SOCK at it, so git and ssh in its
+//! windows and services only offer the project's key.
+
+use std::fs;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context};
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+use crate::config::{self, ProjectConfig};
+use crate::paths;
+
+/// Start the project's agent unless it is already running, and load its
+/// key. ssh-add prompts on the terminal for a passphrase if it needs one.
+pub fn start(project: &ProjectConfig) -> anyhow::Result<()> {
+    let Some(ssh) = &project.ssh else {
+        return Ok(());
+    };
+    let name = &project.project.name;
+    let socket = paths::ssh_agent_socket_path(name);
+    if agent_running(name) {
+        return Ok(());
+    }
+
+    let key = config::resolve_repo_path(&ssh.key)?;
+    if !key.exists() {
+        bail!("ssh key {} does not exist", key.display());
+    }
+    if let Some(parent) = socket.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let _ = fs::remove_file(&socket);
+
+    let output = Command::new("ssh-agent")
+        .arg("-a")
+        .arg(&socket)
+        .stdin(Stdio::null())
+        .output()
+        .context("starting ssh-agent")?;
+    if !output.status.success() {
+        bail!("ssh-agent failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let pid = parse_agent_pid(&String::from_utf8_lossy(&output.stdout)).context("ssh-agent did not report its pid")?;
+    fs::create_dir_all(paths::state_dir(name))?;
+    fs::write(paths::ssh_agent_pid_path(name), pid.to_string())?;
+
+    let added = Command::new("ssh-add").arg(&key).env("SSH_AUTH_SOCK", &socket).status();
+    match added {
+        Ok(status) if status.success() => Ok(()),
+        // An agent without the key would only make ssh fail differently
+        Ok(_) => {
+            stop(name);
+            bail!("ssh-add could not load {}", key.display())
+        }
+        Err(e) => {
+            stop(name);
+            Err(e).context("running ssh-add")
+        }
+    }
+}
+
+/// Whether the project's agent is up and listening on its socket.
+pub fn agent_running(project: &str) -> bool {
+    running_pid(project).is_some() && paths::ssh_agent_socket_path(project).exists()
+}
+
+/// Kill the project's agent, if it has one, and remove its socket.
+pub fn stop(project: &str) {
+    if let Some(pid) = running_pid(project) {
+        let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
+    }
+    let _ = fs::remove_file(paths::ssh_agent_pid_path(project));
+    let _ = fs::remove_file(paths::ssh_agent_socket_path(project));
+}
+
+fn running_pid(project: &str) -> Option<i32> {
+    let pid: i32 = fs::read_to_string(paths::ssh_agent_pid_path(project)).ok()?.trim().parse().ok()?;
+    signal::kill(Pid::from_raw(pid), None).is_ok().then_some(pid)
+}
+
+/// `SSH_AGENT_PID=1234; export SSH_AGENT_PID;` from ssh-agent's output.
+fn parse_agent_pid(output: &str) -> Option<i32> {
+    let rest = &output[output.find("SSH_AGENT_PID=")? + "SSH_AGENT_PID=".len()..];
+    rest.split(';').next()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_pid_parsed_from_shell_output() {
+        let out = "SSH_AUTH_SOCK=/run/user/1000/drift/ssh-web.sock; export SSH_AUTH_SOCK;\nSSH_AGENT_PID=4242; export SSH_AGENT_PID;\necho Agent pid 4242;\n";
+        assert_eq!(parse_agent_pid(out), Some(4242));
+        assert_eq!(parse_agent_pid("garbage"), None);
+    }
+}
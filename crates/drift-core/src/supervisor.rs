@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::os::unix::process::CommandExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
@@ -14,7 +14,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::config::{self, RestartPolicy, ServiceProcess};
 use crate::events::{self, Event};
-use crate::{agent, env, paths};
+use crate::resources::{self, GroupSample};
+use crate::schedule::Schedule;
+use crate::{agent, cgroup, env, mute, paths, watch};
 
 // --- Public types (serialized to services.json) ---
 
@@ -23,6 +25,9 @@ pub struct ServicesState {
     pub supervisor_pid: u32,
     pub project: String,
     pub services: Vec<ServiceState>,
+    /// Services are SIGSTOPped because the workspace has been idle
+    #[serde(default)]
+    pub suspended: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +41,57 @@ pub struct ServiceState {
     #[serde(default)]
     pub is_agent: bool,
     pub agent_type: Option<String>,
+    #[serde(default)]
+    pub start_reason: StartReason,
+    /// Exit code of the run that preceded the current start
+    #[serde(default)]
+    pub previous_exit_code: Option<i32>,
+    /// CPU usage of the service's process group (% of one core)
+    #[serde(default)]
+    pub cpu_percent: Option<f32>,
+    /// Resident memory of the service's process group
+    #[serde(default)]
+    pub rss_bytes: Option<u64>,
+    /// Kernel start time of `pid` (clock ticks since boot), which tells the
+    /// service apart from a later process that reused its pid
+    #[serde(default)]
+    pub start_ticks: Option<u64>,
+    /// systemd scope of the current run, when it has one
+    #[serde(default)]
+    pub scope_unit: Option<String>,
+    /// When a scheduled service runs next (unix seconds)
+    #[serde(default)]
+    pub next_run: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartReason {
+    #[default]
+    Initial,
+    /// Restarted by policy after a non-zero exit or signal
+    Crash,
+    /// Restarted by `restart = "always"` after a clean exit
+    Exit,
+    /// Restarted via `drift restart`
+    Manual,
+    /// Started or restarted by `drift reload` after a config change
+    ConfigReload,
+    /// A scheduled run of a service with `schedule`
+    Schedule,
+}
+
+impl StartReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StartReason::Initial => "initial",
+            StartReason::Crash => "crash",
+            StartReason::Exit => "exit",
+            StartReason::Manual => "manual",
+            StartReason::ConfigReload => "config-reload",
+            StartReason::Schedule => "schedule",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -45,6 +101,50 @@ pub enum ServiceStatus {
     Stopped,
     Failed,
     Backoff,
+    /// A scheduled service waiting for its next run
+    Scheduled,
+}
+
+/// A fault injected into a supervised service, for exercising restart
+/// policies and event emission without a purpose-built crashing script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultInjection {
+    pub service: String,
+    pub kind: FaultKind,
+    /// Delay after each (re)start before the fault fires
+    pub after: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultKind {
+    /// SIGKILL the service's process group, producing a non-zero exit
+    Crash,
+}
+
+/// Parse a `--simulate` spec of the form `crash:<service>:<secs>`.
+pub fn parse_fault(spec: &str) -> anyhow::Result<FaultInjection> {
+    let parts: Vec<&str> = spec.splitn(3, ':').collect();
+    let [kind, service, secs] = parts.as_slice() else {
+        anyhow::bail!("invalid fault spec '{spec}' (expected crash:<service>:<secs>)");
+    };
+    let kind = match *kind {
+        "crash" => FaultKind::Crash,
+        other => anyhow::bail!("unknown fault kind '{other}' (supported: crash)"),
+    };
+    if service.is_empty() {
+        anyhow::bail!("invalid fault spec '{spec}': missing service name");
+    }
+    let secs: f64 = secs
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid fault delay '{secs}' in '{spec}'"))?;
+    if !secs.is_finite() || secs < 0.0 {
+        anyhow::bail!("invalid fault delay '{secs}' in '{spec}'");
+    }
+    Ok(FaultInjection {
+        service: service.to_string(),
+        kind,
+        after: Duration::from_secs_f64(secs),
+    })
 }
 
 // --- Internal types ---
@@ -60,14 +160,128 @@ struct ManagedService {
     last_exit: Option<Instant>,
     exit_code: Option<i32>,
     backoff: Duration,
+    start_reason: StartReason,
+    previous_exit_code: Option<i32>,
+    last_sample: Option<GroupSample>,
+    cpu_percent: Option<f32>,
+    rss_bytes: Option<u64>,
+    /// Shared by the events of the current run, and exported to it
+    correlation_id: Option<String>,
+    /// systemd scope of the current run, when it has one
+    scope_unit: Option<String>,
+    /// /proc start time of `pid`
+    start_ticks: Option<u64>,
+    /// Parsed `schedule`, and when it fires next (unix seconds)
+    schedule: Option<Schedule>,
+    next_run: Option<i64>,
+}
+
+impl ManagedService {
+    fn new(config: ServiceProcess) -> Self {
+        Self {
+            config,
+            child: None,
+            pid: None,
+            status: ServiceStatus::Stopped,
+            restart_count: 0,
+            started_at: None,
+            started_at_system: None,
+            last_exit: None,
+            exit_code: None,
+            backoff: Duration::ZERO,
+            start_reason: StartReason::Initial,
+            previous_exit_code: None,
+            last_sample: None,
+            cpu_percent: None,
+            rss_bytes: None,
+            correlation_id: None,
+            scope_unit: None,
+            start_ticks: None,
+            schedule: None,
+            next_run: None,
+        }
+    }
+
+    /// Refresh CPU/RSS from /proc; cleared when the service isn't running.
+    fn sample_usage(&mut self) {
+        let sample = match (&self.status, self.pid) {
+            (ServiceStatus::Running, Some(pid)) => resources::sample_group(pid),
+            _ => None,
+        };
+        self.cpu_percent = match (&self.last_sample, &sample) {
+            (Some(prev), Some(now)) => resources::cpu_percent(prev, now),
+            _ => None,
+        };
+        self.rss_bytes = sample.map(|s| s.rss_bytes);
+        self.last_sample = sample;
+    }
+
+    /// Take over a process a previous supervisor started, keeping its
+    /// start time and restart count. It isn't our child, so its exit is
+    /// noticed by polling and its exit code is lost.
+    fn adopt(&mut self, previous: &ServiceState, pid: u32) {
+        let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGCONT);
+        let started = previous
+            .started_at
+            .as_deref()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap_or_else(SystemTime::now);
+        let running_for = SystemTime::now().duration_since(started).unwrap_or_default();
+        self.pid = Some(pid);
+        self.start_ticks = previous.start_ticks;
+        self.scope_unit = previous.scope_unit.clone();
+        self.status = ServiceStatus::Running;
+        self.restart_count = previous.restart_count;
+        self.start_reason = previous.start_reason;
+        self.started_at = Some(Instant::now().checked_sub(running_for).unwrap_or_else(Instant::now));
+        self.started_at_system = Some(started);
+        self.correlation_id = Some(events::new_correlation_id());
+    }
+
+    fn load_schedule(&mut self) {
+        self.schedule = self.config.schedule.as_deref().and_then(|expr| match Schedule::parse(expr) {
+            Ok(schedule) => Some(schedule),
+            Err(e) => {
+                eprintln!("service '{}': {e}", self.config.name);
+                None
+            }
+        });
+    }
+
+    /// `Some(exit code)` once the service has exited, `None` while it runs.
+    fn poll_exit(&mut self) -> std::io::Result<Option<Option<i32>>> {
+        match (self.child.as_mut(), self.pid) {
+            (Some(child), _) => Ok(child.try_wait()?.map(|status| status.code())),
+            (None, Some(pid)) if signal::kill(Pid::from_raw(pid as i32), None).is_ok() => Ok(None),
+            _ => Ok(Some(None)),
+        }
+    }
+}
+
+/// Project-level context shared by every service start.
+struct SpawnContext {
+    project: String,
+    env_vars: HashMap<String, String>,
+    repo_path: PathBuf,
 }
 
 // --- Signal handling ---
 
+const USAGE_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
 static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+static RELOAD: AtomicBool = AtomicBool::new(false);
+static RESTART_REQUESTED: AtomicBool = AtomicBool::new(false);
+static SUSPEND_CHANGED: AtomicBool = AtomicBool::new(false);
 
-extern "C" fn handle_signal(_: libc::c_int) {
-    SHUTDOWN.store(true, Ordering::Relaxed);
+extern "C" fn handle_signal(sig: libc::c_int) {
+    match sig {
+        libc::SIGHUP => RELOAD.store(true, Ordering::Relaxed),
+        libc::SIGUSR1 => RESTART_REQUESTED.store(true, Ordering::Relaxed),
+        libc::SIGUSR2 => SUSPEND_CHANGED.store(true, Ordering::Relaxed),
+        _ => SHUTDOWN.store(true, Ordering::Relaxed),
+    }
 }
 
 fn install_signal_handlers() {
@@ -79,15 +293,180 @@ fn install_signal_handlers() {
         );
         signal::sigaction(Signal::SIGTERM, &action).expect("install SIGTERM handler");
         signal::sigaction(Signal::SIGINT, &action).expect("install SIGINT handler");
+        signal::sigaction(Signal::SIGHUP, &action).expect("install SIGHUP handler");
+        signal::sigaction(Signal::SIGUSR1, &action).expect("install SIGUSR1 handler");
+        signal::sigaction(Signal::SIGUSR2, &action).expect("install SIGUSR2 handler");
+    }
+}
+
+/// Ask a project's supervisor to freeze (SIGSTOP) or thaw (SIGCONT) its
+/// services. The desired state lives in a flag file so repeated requests
+/// are idempotent; SIGUSR2 just tells the supervisor to look at it.
+pub fn request_suspend(project: &str, suspend: bool) -> anyhow::Result<()> {
+    let pid = fs::read_to_string(paths::supervisor_pid_path(project))
+        .ok()
+        .and_then(|s| s.trim().parse::<i32>().ok())
+        .map(Pid::from_raw)
+        .filter(|pid| signal::kill(*pid, None).is_ok())
+        .ok_or_else(|| anyhow::anyhow!("no supervisor running for '{project}'"))?;
+
+    let flag = paths::services_suspended_path(project);
+    if suspend {
+        fs::write(&flag, "")?;
+    } else if flag.exists() {
+        fs::remove_file(&flag)?;
+    }
+    signal::kill(pid, Signal::SIGUSR2)?;
+    Ok(())
+}
+
+/// The state the project's supervisor last wrote, if any.
+pub fn read_state(project: &str) -> Option<ServicesState> {
+    let json = fs::read_to_string(paths::services_state_path(project)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Required services that didn't come up: failed to spawn, exited non-zero,
+/// already restarted or waiting in backoff, or missing from the state.
+pub fn failed_required(state: &ServicesState, required: &[String]) -> Vec<String> {
+    required
+        .iter()
+        .filter(|name| match state.services.iter().find(|s| &s.name == *name) {
+            Some(svc) => match svc.status {
+                ServiceStatus::Failed | ServiceStatus::Backoff => true,
+                ServiceStatus::Stopped => svc.exit_code != Some(0),
+                ServiceStatus::Running => svc.restart_count > 0,
+                ServiceStatus::Scheduled => svc.exit_code.is_some_and(|c| c != 0),
+            },
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+// --- Leftovers of a dead supervisor ---
+
+/// What a supervisor that died without shutting down left behind.
+#[derive(Debug, Default, PartialEq)]
+pub struct Leftovers {
+    /// supervisor.pid or services.json outlived the supervisor
+    pub stale_files: bool,
+    /// Services still running in the project's environment
+    pub orphans: Vec<Orphan>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orphan {
+    pub service: String,
+    pub pid: u32,
+    pub scope_unit: Option<String>,
+}
+
+impl Orphan {
+    fn of(svc: &ServiceState, pid: u32) -> Self {
+        Self { service: svc.name.clone(), pid, scope_unit: svc.scope_unit.clone() }
+    }
+}
+
+/// `None` while the project's supervisor is running.
+pub fn find_leftovers(project: &str) -> Option<Leftovers> {
+    if supervisor_running(project) {
+        return None;
+    }
+    Some(Leftovers {
+        stale_files: paths::supervisor_pid_path(project).exists() || paths::services_state_path(project).exists(),
+        orphans: read_state(project)
+            .map(|state| {
+                live_services(&state, project)
+                    .into_iter()
+                    .map(|(svc, pid)| Orphan::of(svc, pid))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// Whether supervisor.pid names a live `drift _supervisor <project>`,
+/// rather than a dead pid or one since reused by another command.
+pub fn supervisor_running(project: &str) -> bool {
+    fs::read_to_string(paths::supervisor_pid_path(project))
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok())
+        .and_then(|pid| fs::read(format!("/proc/{pid}/cmdline")).ok())
+        .is_some_and(|cmdline| is_supervisor_cmdline(&cmdline, project))
+}
+
+fn is_supervisor_cmdline(cmdline: &[u8], project: &str) -> bool {
+    let mut args = cmdline.split(|b| *b == 0);
+    args.any(|a| a == b"_supervisor") && args.any(|a| a == project.as_bytes())
+}
+
+/// Running services from a previous state that are still alive: the pid
+/// has the recorded start time or, for state written without one, still
+/// carries the project's environment.
+fn live_services<'a>(state: &'a ServicesState, project: &str) -> Vec<(&'a ServiceState, u32)> {
+    state
+        .services
+        .iter()
+        .filter(|s| s.status == ServiceStatus::Running)
+        .filter_map(|s| Some((s, s.pid?)))
+        .filter(|(s, pid)| match s.start_ticks {
+            Some(ticks) => resources::start_ticks(*pid) == Some(ticks),
+            None => fs::read(format!("/proc/{pid}/environ"))
+                .is_ok_and(|environ| environ_has(&environ, "DRIFT_PROJECT", project)),
+        })
+        .collect()
+}
+
+fn environ_has(environ: &[u8], key: &str, value: &str) -> bool {
+    let entry = format!("{key}={value}");
+    environ.split(|b| *b == 0).any(|e| e == entry.as_bytes())
+}
+
+/// SIGTERM the process groups of orphaned services, then SIGKILL those
+/// still around after 3s, however many there are, and clear their scopes.
+pub fn terminate_orphans(orphans: &[Orphan]) {
+    for orphan in orphans {
+        let group = Pid::from_raw(-(orphan.pid as i32));
+        let _ = signal::kill(group, Signal::SIGCONT);
+        let _ = signal::kill(group, Signal::SIGTERM);
+    }
+    let deadline = Instant::now() + Duration::from_secs(3);
+    let alive = |o: &Orphan| signal::kill(Pid::from_raw(o.pid as i32), None).is_ok();
+    while orphans.iter().any(alive) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
     }
+    for orphan in orphans {
+        if alive(orphan) {
+            let _ = signal::kill(Pid::from_raw(-(orphan.pid as i32)), Signal::SIGKILL);
+        }
+        if let Some(unit) = &orphan.scope_unit {
+            cgroup::kill(unit);
+        }
+    }
+}
+
+/// Remove the pid, state and suspend files of a supervisor that is gone.
+pub fn clear_stale_files(project: &str) {
+    let _ = fs::remove_file(paths::supervisor_pid_path(project));
+    let _ = fs::remove_file(paths::services_state_path(project));
+    let _ = fs::remove_file(paths::services_suspended_path(project));
 }
 
 // --- Main entry point ---
 
 pub fn run_supervisor(project_name: &str) -> anyhow::Result<()> {
+    run_supervisor_with_faults(project_name, &[])
+}
+
+/// Run the supervisor with injected faults (`drift _supervisor --simulate`).
+pub fn run_supervisor_with_faults(project_name: &str, faults: &[FaultInjection]) -> anyhow::Result<()> {
     let project_config = config::load_project_config(project_name)?;
-    let env_vars = env::build_env(&project_config)?;
-    let repo_path = config::resolve_repo_path(&project_config.project.repo)?;
+    let mut ctx = SpawnContext {
+        project: project_name.to_string(),
+        env_vars: env::build_process_env(&project_config)?,
+        repo_path: config::resolve_repo_path(&project_config.project.repo)?,
+    };
 
     install_signal_handlers();
 
@@ -96,100 +475,112 @@ pub fn run_supervisor(project_name: &str) -> anyhow::Result<()> {
     fs::create_dir_all(&logs_dir)?;
     fs::create_dir_all(&state_dir)?;
 
+    // Services a dead supervisor left running are taken over, not started
+    // twice, or stopped when `defaults.adopt_orphans` is off
+    let previous = read_state(project_name);
+    let mut orphans = previous
+        .as_ref()
+        .map(|state| live_services(state, project_name))
+        .unwrap_or_default();
+    let adopt = config::load_global_config().is_ok_and(|c| c.defaults.adopt_orphans);
+    if !adopt && !orphans.is_empty() {
+        let stopping: Vec<Orphan> = orphans.iter().map(|&(svc, pid)| Orphan::of(svc, pid)).collect();
+        eprintln!("stopping {} service(s) left running by a previous supervisor", stopping.len());
+        terminate_orphans(&stopping);
+        orphans.clear();
+    }
+
     fs::write(
         paths::supervisor_pid_path(project_name),
         std::process::id().to_string(),
     )?;
+    let _ = fs::remove_file(paths::services_suspended_path(project_name));
 
-    let processes = match project_config.services {
-        Some(svc_config) if !svc_config.processes.is_empty() => svc_config.processes,
-        _ => return Ok(()),
-    };
-
-    // Filter out interactive agents (they get spawned as windows, not services)
-    let processes: Vec<_> = processes.into_iter()
-        .filter(|s| !agent::is_interactive_agent(s))
-        .collect();
-
-    if processes.is_empty() {
+    let watch_config = project_config.watch.clone();
+    let processes = supervised_processes(project_config);
+    if processes.is_empty() && watch_config.is_none() {
         return Ok(());
     }
 
+    for fault in faults {
+        if !processes.iter().any(|p| p.name == fault.service) {
+            eprintln!("warning: --simulate targets unknown service '{}'", fault.service);
+        }
+    }
+
     let mut services: Vec<ManagedService> = Vec::with_capacity(processes.len());
     for proc in processes {
-        match spawn_service(&proc, &env_vars, &repo_path, project_name) {
-            Ok(child) => {
-                let pid = child.id();
-                events::try_emit_event(&Event {
-                    event_type: "service.started".into(),
-                    project: project_name.to_string(),
-                    source: proc.name.clone(),
-                    ts: events::iso_now(),
-                    level: Some("info".into()),
-                    title: Some(format!("Service '{}' started", proc.name)),
-                    body: None,
-                    meta: Some(serde_json::json!({ "pid": pid })),
-                    priority: None,
-                });
-                services.push(ManagedService {
-                    config: proc,
-                    pid: Some(pid),
-                    child: Some(child),
-                    status: ServiceStatus::Running,
-                    restart_count: 0,
-                    started_at: Some(Instant::now()),
-                    started_at_system: Some(SystemTime::now()),
-                    last_exit: None,
-                    exit_code: None,
-                    backoff: Duration::ZERO,
-                });
-            }
-            Err(e) => {
-                eprintln!("failed to spawn service '{}': {e}", proc.name);
-                services.push(ManagedService {
-                    config: proc,
-                    child: None,
-                    pid: None,
-                    status: ServiceStatus::Failed,
-                    restart_count: 0,
-                    started_at: None,
-                    started_at_system: None,
-                    last_exit: None,
-                    exit_code: None,
-                    backoff: Duration::ZERO,
-                });
+        let mut svc = ManagedService::new(proc);
+        match orphans.iter().find(|(prev, _)| prev.name == svc.config.name) {
+            Some(&(prev, pid)) => {
+                eprintln!("adopted service '{}' (PID {pid})", svc.config.name);
+                svc.adopt(prev, pid);
+                svc.load_schedule();
             }
+            None => launch(&mut svc, StartReason::Initial, &ctx),
         }
+        services.push(svc);
     }
 
-    write_state(&services, project_name);
+    let watcher = watch_config.map(|cfg| {
+        let (project, repo_path, env_vars) = (ctx.project.clone(), ctx.repo_path.clone(), ctx.env_vars.clone());
+        thread::spawn(move || watch::run_watcher(project, repo_path, env_vars, cfg, &SHUTDOWN))
+    });
+
+    let mut suspended = false;
+    let mut last_usage_sample = Instant::now();
+    write_state(&services, project_name, suspended);
 
     loop {
         if SHUTDOWN.load(Ordering::Relaxed) {
-            graceful_shutdown(&mut services, &env_vars, &repo_path, project_name);
+            graceful_shutdown(&mut services, &ctx.env_vars, &ctx.repo_path, project_name);
             break;
         }
 
         let mut state_changed = false;
 
+        if SUSPEND_CHANGED.swap(false, Ordering::Relaxed) {
+            let wanted = paths::services_suspended_path(project_name).exists();
+            if wanted != suspended {
+                suspended = wanted;
+                set_suspended(&services, suspended, project_name);
+                state_changed = true;
+            }
+        }
+
+        if RELOAD.swap(false, Ordering::Relaxed) {
+            reload_config(&mut services, &mut ctx);
+            state_changed = true;
+        }
+
+        if RESTART_REQUESTED.swap(false, Ordering::Relaxed) {
+            handle_restart_requests(&mut services, &ctx);
+            state_changed = true;
+        }
+
         for svc in &mut services {
             match svc.status {
                 ServiceStatus::Running => {
-                    if let Some(child) = svc.child.as_mut() {
-                        match child.try_wait() {
-                            Ok(Some(exit_status)) => {
-                                svc.exit_code = exit_status.code();
+                    inject_faults(svc, faults, project_name);
+                    if svc.pid.is_some() {
+                        match svc.poll_exit() {
+                            Ok(Some(exit_code)) => {
+                                let success = exit_code == Some(0);
+                                svc.exit_code = exit_code;
                                 svc.child = None;
                                 svc.pid = None;
+                                release_scope(svc);
                                 svc.last_exit = Some(Instant::now());
 
                                 let should_restart = match svc.config.restart {
                                     RestartPolicy::Always => true,
-                                    RestartPolicy::OnFailure => !exit_status.success(),
+                                    RestartPolicy::OnFailure => !success,
                                     RestartPolicy::Never => false,
                                 };
 
-                                if should_restart {
+                                if svc.config.schedule.is_some() {
+                                    finish_run(svc, project_name);
+                                } else if should_restart {
                                     let ran_for = svc
                                         .started_at
                                         .map(|s| s.elapsed())
@@ -201,62 +592,25 @@ pub fn run_supervisor(project_name: &str) -> anyhow::Result<()> {
                                         svc.status = ServiceStatus::Backoff;
                                     } else {
                                         svc.backoff = Duration::ZERO;
-                                        match spawn_service(
-                                            &svc.config,
-                                            &env_vars,
-                                            &repo_path,
-                                            project_name,
-                                        ) {
-                                            Ok(child) => {
-                                                svc.restart_count += 1;
-                                                let new_pid = child.id();
-                                                svc.pid = Some(new_pid);
-                                                svc.child = Some(child);
-                                                svc.status = ServiceStatus::Running;
-                                                svc.started_at = Some(Instant::now());
-                                                svc.started_at_system = Some(SystemTime::now());
-                                                events::try_emit_event(&Event {
-                                                    event_type: "service.restarted".into(),
-                                                    project: project_name.to_string(),
-                                                    source: svc.config.name.clone(),
-                                                    ts: events::iso_now(),
-                                                    level: Some("warning".into()),
-                                                    title: Some(format!("Service '{}' restarted", svc.config.name)),
-                                                    body: None,
-                                                    meta: Some(serde_json::json!({ "pid": new_pid, "restart_count": svc.restart_count })),
-                                                    priority: None,
-                                                });
-                                            }
-                                            Err(_) => {
-                                                svc.status = ServiceStatus::Failed;
-                                            }
-                                        }
+                                        start_service(svc, policy_restart_reason(svc.exit_code), &ctx);
                                     }
-                                } else if exit_status.success() {
+                                } else if success {
                                     svc.status = ServiceStatus::Stopped;
                                     events::try_emit_event(&Event {
-                                        event_type: "service.stopped".into(),
-                                        project: project_name.to_string(),
-                                        source: svc.config.name.clone(),
-                                        ts: events::iso_now(),
-                                        level: Some("info".into()),
-                                        title: Some(format!("Service '{}' stopped", svc.config.name)),
-                                        body: None,
-                                        meta: Some(serde_json::json!({ "exit_code": 0 })),
-                                        priority: None,
+                                        correlation_id: svc.correlation_id.clone(),
+                                        ..Event::new("service.stopped", project_name, svc.config.name.clone())
+                                            .level("info")
+                                            .title(format!("Service '{}' stopped", svc.config.name))
+                                            .meta(serde_json::json!({ "exit_code": 0 }))
                                     });
                                 } else {
                                     svc.status = ServiceStatus::Failed;
                                     events::try_emit_event(&Event {
-                                        event_type: "service.crashed".into(),
-                                        project: project_name.to_string(),
-                                        source: svc.config.name.clone(),
-                                        ts: events::iso_now(),
-                                        level: Some("error".into()),
-                                        title: Some(format!("Service '{}' crashed", svc.config.name)),
-                                        body: None,
-                                        meta: Some(serde_json::json!({ "exit_code": svc.exit_code })),
-                                        priority: None,
+                                        correlation_id: svc.correlation_id.clone(),
+                                        ..Event::new("service.crashed", project_name, svc.config.name.clone())
+                                            .level("error")
+                                            .title(format!("Service '{}' crashed", svc.config.name))
+                                            .meta(serde_json::json!({ "exit_code": svc.exit_code }))
                                     });
                                 }
                                 state_changed = true;
@@ -265,43 +619,29 @@ pub fn run_supervisor(project_name: &str) -> anyhow::Result<()> {
                             Err(_) => {
                                 svc.child = None;
                                 svc.pid = None;
+                                release_scope(svc);
                                 svc.status = ServiceStatus::Failed;
                                 state_changed = true;
                             }
                         }
                     }
                 }
+                // Don't bring services back while the project is frozen
+                ServiceStatus::Backoff if suspended => {}
                 ServiceStatus::Backoff => {
                     if svc
                         .last_exit
                         .map(|t| t.elapsed() >= svc.backoff)
                         .unwrap_or(true)
                     {
-                        match spawn_service(&svc.config, &env_vars, &repo_path, project_name) {
-                            Ok(child) => {
-                                svc.restart_count += 1;
-                                let new_pid = child.id();
-                                svc.pid = Some(new_pid);
-                                svc.child = Some(child);
-                                svc.status = ServiceStatus::Running;
-                                svc.started_at = Some(Instant::now());
-                                svc.started_at_system = Some(SystemTime::now());
-                                events::try_emit_event(&Event {
-                                    event_type: "service.restarted".into(),
-                                    project: project_name.to_string(),
-                                    source: svc.config.name.clone(),
-                                    ts: events::iso_now(),
-                                    level: Some("warning".into()),
-                                    title: Some(format!("Service '{}' restarted", svc.config.name)),
-                                    body: None,
-                                    meta: Some(serde_json::json!({ "pid": new_pid, "restart_count": svc.restart_count })),
-                                    priority: None,
-                                });
-                            }
-                            Err(_) => {
-                                svc.status = ServiceStatus::Failed;
-                            }
-                        }
+                        start_service(svc, policy_restart_reason(svc.exit_code), &ctx);
+                        state_changed = true;
+                    }
+                }
+                ServiceStatus::Scheduled if suspended => {}
+                ServiceStatus::Scheduled => {
+                    if svc.next_run.is_some_and(|t| mute::now() >= t) {
+                        start_service(svc, StartReason::Schedule, &ctx);
                         state_changed = true;
                     }
                 }
@@ -309,13 +649,22 @@ pub fn run_supervisor(project_name: &str) -> anyhow::Result<()> {
             }
         }
 
+        if last_usage_sample.elapsed() >= USAGE_SAMPLE_INTERVAL {
+            for svc in &mut services {
+                svc.sample_usage();
+            }
+            last_usage_sample = Instant::now();
+            state_changed = true;
+        }
+
         if state_changed {
-            write_state(&services, project_name);
+            write_state(&services, project_name, suspended);
         }
 
-        if services
-            .iter()
-            .all(|s| matches!(s.status, ServiceStatus::Stopped | ServiceStatus::Failed))
+        if watcher.is_none()
+            && services
+                .iter()
+                .all(|s| matches!(s.status, ServiceStatus::Stopped | ServiceStatus::Failed))
         {
             break;
         }
@@ -323,9 +672,322 @@ pub fn run_supervisor(project_name: &str) -> anyhow::Result<()> {
         thread::sleep(Duration::from_millis(500));
     }
 
+    if let Some(handle) = watcher {
+        let _ = handle.join();
+    }
+
     Ok(())
 }
 
+/// Services the supervisor owns: interactive agents are spawned as windows instead.
+fn supervised_processes(project_config: config::ProjectConfig) -> Vec<ServiceProcess> {
+    match project_config.services {
+        Some(svc_config) => svc_config
+            .processes
+            .into_iter()
+            .filter(|s| !agent::is_interactive_agent(s))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn policy_restart_reason(exit_code: Option<i32>) -> StartReason {
+    if exit_code == Some(0) {
+        StartReason::Exit
+    } else {
+        StartReason::Crash
+    }
+}
+
+/// Spawn a service and record why it was started. Emits `service.started`
+/// for a first start and `service.restarted` for every later one.
+fn start_service(svc: &mut ManagedService, reason: StartReason, ctx: &SpawnContext) -> bool {
+    let scheduled = svc.config.schedule.is_some();
+    let is_restart = svc.started_at.is_some() && !scheduled;
+    let correlation_id = events::new_correlation_id();
+    let scope = scope_for(&svc.config, &ctx.project);
+    match spawn_service(&svc.config, &ctx.env_vars, &ctx.repo_path, &ctx.project, &correlation_id, scope.as_deref()) {
+        Ok(child) => {
+            svc.correlation_id = Some(correlation_id);
+            svc.scope_unit = scope;
+            let pid = child.id();
+            svc.start_ticks = resources::start_ticks(pid);
+            svc.previous_exit_code = if is_restart { svc.exit_code } else { None };
+            svc.start_reason = reason;
+            svc.pid = Some(pid);
+            svc.child = Some(child);
+            svc.status = ServiceStatus::Running;
+            svc.started_at = Some(Instant::now());
+            svc.started_at_system = Some(SystemTime::now());
+            if scheduled {
+                events::try_emit_event(&Event {
+                    correlation_id: svc.correlation_id.clone(),
+                    ..Event::new("service.run.started", ctx.project.clone(), svc.config.name.clone())
+                        .level("info")
+                        .title(format!("Scheduled run of '{}' started", svc.config.name))
+                        .meta(serde_json::json!({ "pid": pid, "reason": reason.as_str() }))
+                });
+            } else if is_restart {
+                svc.restart_count += 1;
+                events::try_emit_event(&Event {
+                    correlation_id: svc.correlation_id.clone(),
+                    ..Event::new("service.restarted", ctx.project.clone(), svc.config.name.clone())
+                        .level("warning")
+                        .title(format!("Service '{}' restarted", svc.config.name))
+                        .meta(serde_json::json!({
+                            "pid": pid,
+                            "restart_count": svc.restart_count,
+                            "reason": reason.as_str(),
+                            "previous_exit_code": svc.previous_exit_code,
+                        }))
+                });
+            } else {
+                events::try_emit_event(&Event {
+                    correlation_id: svc.correlation_id.clone(),
+                    ..Event::new("service.started", ctx.project.clone(), svc.config.name.clone())
+                        .level("info")
+                        .title(format!("Service '{}' started", svc.config.name))
+                        .meta(serde_json::json!({ "pid": pid, "reason": reason.as_str() }))
+                });
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("failed to spawn service '{}': {e}", svc.config.name);
+            svc.child = None;
+            svc.pid = None;
+            svc.status = ServiceStatus::Failed;
+            if scheduled {
+                schedule_next_run(svc);
+            }
+            false
+        }
+    }
+}
+
+/// Start a service, or for a scheduled one wait for its next run.
+fn launch(svc: &mut ManagedService, reason: StartReason, ctx: &SpawnContext) {
+    if svc.config.schedule.is_none() {
+        start_service(svc, reason, ctx);
+        return;
+    }
+    svc.load_schedule();
+    schedule_next_run(svc);
+}
+
+fn schedule_next_run(svc: &mut ManagedService) {
+    svc.next_run = svc.schedule.as_ref().and_then(|s| s.next_after(mute::now()));
+    svc.status = if svc.next_run.is_some() { ServiceStatus::Scheduled } else { ServiceStatus::Failed };
+}
+
+/// A scheduled run exited: report it and wait for the next one. Restart
+/// policies don't apply; a failed run just waits like a successful one.
+fn finish_run(svc: &mut ManagedService, project: &str) {
+    let success = svc.exit_code == Some(0);
+    let ran_for = svc.started_at.map(|s| s.elapsed()).unwrap_or(Duration::ZERO);
+    schedule_next_run(svc);
+    let verb = if success { "finished" } else { "failed" };
+    events::try_emit_event(&Event {
+        correlation_id: svc.correlation_id.clone(),
+        ..Event::new("service.run.finished", project, svc.config.name.clone())
+            .level(if success { "success" } else { "error" })
+            .title(format!("Scheduled run of '{}' {verb}", svc.config.name))
+            .meta(serde_json::json!({
+                "exit_code": svc.exit_code,
+                "duration_secs": ran_for.as_secs(),
+                "next_run": svc.next_run,
+            }))
+    });
+}
+
+/// Stop a single service: stop_command or SIGTERM, then SIGKILL after 5s.
+fn stop_service(svc: &mut ManagedService, ctx: &SpawnContext) {
+    if svc.pid.is_none() {
+        return;
+    }
+    // A suspended service can't act on SIGTERM until it is continued
+    if let Some(pid) = svc.pid {
+        let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGCONT);
+    }
+    if let Some(stop_cmd) = &svc.config.stop_command {
+        let _ = Command::new("sh")
+            .args(["-c", stop_cmd])
+            .current_dir(&ctx.repo_path)
+            .envs(&ctx.env_vars)
+            .status();
+    } else if let Some(pid) = svc.pid {
+        let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGTERM);
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        match svc.poll_exit() {
+            Ok(Some(exit_code)) => {
+                svc.exit_code = exit_code;
+                break;
+            }
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(100)),
+            Ok(None) => {
+                if let Some(pid) = svc.pid {
+                    let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGKILL);
+                }
+                svc.exit_code = svc.child.as_mut().and_then(|c| c.wait().ok()).and_then(|s| s.code());
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+
+    svc.child = None;
+    svc.pid = None;
+    release_scope(svc);
+    svc.last_exit = Some(Instant::now());
+    svc.status = ServiceStatus::Stopped;
+}
+
+/// A fresh scope unit when the service asks for one and systemd can make
+/// it; otherwise the service runs as a plain process group.
+fn scope_for(svc: &ServiceProcess, project: &str) -> Option<String> {
+    if !cgroup::wants_scope(svc) {
+        return None;
+    }
+    if !cgroup::available() {
+        eprintln!(
+            "service '{}': systemd-run --user is unavailable, starting without cgroup limits",
+            svc.name
+        );
+        return None;
+    }
+    Some(cgroup::unit_name(project, &svc.name))
+}
+
+/// Kill whatever the service left in its scope, e.g. daemons that escaped
+/// the process group.
+fn release_scope(svc: &mut ManagedService) {
+    if let Some(unit) = svc.scope_unit.take() {
+        cgroup::kill(&unit);
+    }
+}
+
+/// SIGHUP: re-read the project config, restarting services whose definition
+/// (or the project environment) changed, starting new ones, and stopping
+/// removed ones.
+fn reload_config(services: &mut Vec<ManagedService>, ctx: &mut SpawnContext) {
+    let project_config = match config::load_project_config(&ctx.project) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("config reload failed, keeping current services: {e}");
+            return;
+        }
+    };
+    let (new_env, new_repo) = match (
+        env::build_process_env(&project_config),
+        config::resolve_repo_path(&project_config.project.repo),
+    ) {
+        (Ok(env), Ok(repo)) => (env, repo),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("config reload failed, keeping current services: {e}");
+            return;
+        }
+    };
+    let env_changed = new_env != ctx.env_vars || new_repo != ctx.repo_path;
+    ctx.env_vars = new_env;
+    ctx.repo_path = new_repo;
+
+    let mut processes = supervised_processes(project_config);
+
+    services.retain_mut(|svc| {
+        let Some(idx) = processes.iter().position(|p| p.name == svc.config.name) else {
+            stop_service(svc, ctx);
+            return false;
+        };
+        let new_config = processes.remove(idx);
+        if env_changed || new_config != svc.config {
+            stop_service(svc, ctx);
+            svc.config = new_config;
+            svc.backoff = Duration::ZERO;
+            launch(svc, StartReason::ConfigReload, ctx);
+        }
+        true
+    });
+
+    for proc in processes {
+        let mut svc = ManagedService::new(proc);
+        launch(&mut svc, StartReason::ConfigReload, ctx);
+        services.push(svc);
+    }
+}
+
+/// SIGUSR1: restart the services named in the restart request file (one per
+/// line), or every service when a line is empty or the file lists none.
+fn handle_restart_requests(services: &mut [ManagedService], ctx: &SpawnContext) {
+    // Moved aside first, so requests appended meanwhile wait for their signal
+    let path = paths::supervisor_restart_request_path(&ctx.project);
+    let taken = path.with_extension("taken");
+    let pending = match fs::rename(&path, &taken) {
+        Ok(()) => fs::read_to_string(&taken).unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+    let _ = fs::remove_file(&taken);
+    let requested: Vec<&str> = pending.lines().map(str::trim).collect();
+    let all = requested.is_empty() || requested.contains(&"");
+
+    for svc in services.iter_mut() {
+        if !all && !requested.contains(&svc.config.name.as_str()) {
+            continue;
+        }
+        // Restarting everything shouldn't fire scheduled runs; naming one does
+        if all && svc.config.schedule.is_some() && svc.pid.is_none() {
+            continue;
+        }
+        stop_service(svc, ctx);
+        svc.backoff = Duration::ZERO;
+        start_service(svc, StartReason::Manual, ctx);
+    }
+}
+
+// --- Idle suspension ---
+
+fn set_suspended(services: &[ManagedService], suspended: bool, project: &str) {
+    let sig = if suspended { Signal::SIGSTOP } else { Signal::SIGCONT };
+    let mut count = 0;
+    for svc in services {
+        if let (ServiceStatus::Running, Some(pid)) = (&svc.status, svc.pid) {
+            let _ = signal::kill(Pid::from_raw(-(pid as i32)), sig);
+            count += 1;
+        }
+    }
+
+    let (event_type, title) = if suspended {
+        ("services.suspended", format!("Suspended {count} idle service(s)"))
+    } else {
+        ("services.resumed", format!("Resumed {count} service(s)"))
+    };
+    events::try_emit_event(&Event::new(event_type, project, "supervisor").level("info").title(title));
+}
+
+// --- Fault injection ---
+
+fn inject_faults(svc: &ManagedService, faults: &[FaultInjection], project: &str) {
+    let (Some(pid), Some(started_at)) = (svc.pid, svc.started_at) else {
+        return;
+    };
+    for fault in faults.iter().filter(|f| f.service == svc.config.name) {
+        if started_at.elapsed() < fault.after {
+            continue;
+        }
+        match fault.kind {
+            FaultKind::Crash => {
+                let log_path = paths::logs_dir(project).join(format!("{}.log", svc.config.name));
+                if let Ok(mut log) = OpenOptions::new().append(true).open(&log_path) {
+                    let _ = writeln!(log, "--- simulated crash after {:?} ---", fault.after);
+                }
+                let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGKILL);
+            }
+        }
+    }
+}
+
 // --- Spawn ---
 
 fn spawn_service(
@@ -333,6 +995,8 @@ fn spawn_service(
     env_vars: &HashMap<String, String>,
     repo_path: &Path,
     project: &str,
+    correlation_id: &str,
+    scope: Option<&str>,
 ) -> anyhow::Result<Child> {
     let svc_cwd = if svc.cwd.is_empty() || svc.cwd == "." {
         repo_path.to_path_buf()
@@ -362,10 +1026,23 @@ fn spawn_service(
 
     let stderr_file = log_file.try_clone()?;
 
+    // systemd-run --scope execs the command, so the pid (and the session
+    // from setsid) carry over to the service
+    let mut cmd = match scope {
+        Some(unit) => {
+            let mut cmd = Command::new("systemd-run");
+            cmd.args(cgroup::scope_args(unit, svc)).args(["sh", "-c", &command]);
+            cmd
+        }
+        None => {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", &command]);
+            cmd
+        }
+    };
     let child = unsafe {
-        Command::new("sh")
-            .args(["-c", &command])
-            .envs(env_vars)
+        cmd.envs(env_vars)
+            .env(events::CORRELATION_ENV, correlation_id)
             .current_dir(&svc_cwd)
             .stdout(log_file)
             .stderr(stderr_file)
@@ -388,9 +1065,12 @@ fn graceful_shutdown(
     repo_path: &Path,
     project: &str,
 ) {
-    // Phase 1: SIGTERM or stop_command
+    // Phase 1: SIGTERM or stop_command (continuing suspended services first)
     for svc in services.iter_mut() {
-        if svc.child.is_some() {
+        if let Some(pid) = svc.pid {
+            let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGCONT);
+        }
+        if svc.pid.is_some() {
             if let Some(stop_cmd) = &svc.config.stop_command {
                 let _ = Command::new("sh")
                     .args(["-c", stop_cmd])
@@ -406,13 +1086,7 @@ fn graceful_shutdown(
     // Phase 2: Wait up to 5 seconds
     let deadline = Instant::now() + Duration::from_secs(5);
     loop {
-        let all_exited = services.iter_mut().all(|svc| {
-            if let Some(child) = svc.child.as_mut() {
-                matches!(child.try_wait(), Ok(Some(_)))
-            } else {
-                true
-            }
-        });
+        let all_exited = services.iter_mut().all(|svc| !matches!(svc.poll_exit(), Ok(None)));
         if all_exited || Instant::now() >= deadline {
             break;
         }
@@ -421,25 +1095,25 @@ fn graceful_shutdown(
 
     // Phase 3: SIGKILL stragglers
     for svc in services.iter_mut() {
-        if svc.child.is_some() {
-            if let Some(pid) = svc.pid {
-                let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGKILL);
-            }
+        if let Some(pid) = svc.pid.take() {
+            let _ = signal::kill(Pid::from_raw(-(pid as i32)), Signal::SIGKILL);
             if let Some(child) = svc.child.as_mut() {
                 let _ = child.wait();
             }
             svc.child = None;
         }
+        release_scope(svc);
         svc.status = ServiceStatus::Stopped;
     }
 
-    write_state(services, project);
+    write_state(services, project, false);
     let _ = fs::remove_file(paths::supervisor_pid_path(project));
+    let _ = fs::remove_file(paths::services_suspended_path(project));
 }
 
 // --- State writing (atomic) ---
 
-fn write_state(services: &[ManagedService], project: &str) {
+fn write_state(services: &[ManagedService], project: &str, suspended: bool) {
     let state = ServicesState {
         supervisor_pid: std::process::id(),
         project: project.to_string(),
@@ -454,8 +1128,16 @@ fn write_state(services: &[ManagedService], project: &str) {
                 exit_code: s.exit_code,
                 is_agent: s.config.agent.is_some(),
                 agent_type: s.config.agent.clone(),
+                start_reason: s.start_reason,
+                previous_exit_code: s.previous_exit_code,
+                cpu_percent: s.cpu_percent,
+                rss_bytes: s.rss_bytes,
+                start_ticks: s.pid.and(s.start_ticks),
+                scope_unit: s.pid.and(s.scope_unit.clone()),
+                next_run: s.next_run.map(|t| t.to_string()),
             })
             .collect(),
+        suspended,
     };
     let path = paths::services_state_path(project);
     let tmp = path.with_extension("json.tmp");
@@ -472,3 +1154,132 @@ fn format_time(time: SystemTime) -> String {
     let secs = duration.as_secs();
     format!("{secs}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, status: ServiceStatus, exit_code: Option<i32>, restart_count: u32) -> ServiceState {
+        ServiceState {
+            name: name.into(),
+            pid: None,
+            status,
+            restart_count,
+            started_at: None,
+            exit_code,
+            is_agent: false,
+            agent_type: None,
+            start_reason: StartReason::Initial,
+            previous_exit_code: None,
+            cpu_percent: None,
+            rss_bytes: None,
+            start_ticks: None,
+            scope_unit: None,
+            next_run: None,
+        }
+    }
+
+    #[test]
+    fn supervisor_cmdline_matched_by_project() {
+        assert!(is_supervisor_cmdline(b"drift\0_supervisor\0myapp\0", "myapp"));
+        assert!(is_supervisor_cmdline(b"/bin/drift\0_supervisor\0myapp\0--simulate\0crash:api:1\0", "myapp"));
+        assert!(!is_supervisor_cmdline(b"drift\0_supervisor\0other\0", "myapp"));
+        assert!(!is_supervisor_cmdline(b"vim\0myapp\0", "myapp"));
+    }
+
+    #[test]
+    fn live_services_skip_a_reused_pid() {
+        let pid = std::process::id();
+        let ticks = resources::start_ticks(pid).unwrap();
+        let mut ours = service("api", ServiceStatus::Running, None, 0);
+        ours.pid = Some(pid);
+        ours.start_ticks = Some(ticks);
+        let mut reused = ours.clone();
+        reused.name = "worker".into();
+        reused.start_ticks = Some(ticks + 1);
+        let state = ServicesState {
+            supervisor_pid: 1,
+            project: "myapp".into(),
+            services: vec![ours, reused],
+            suspended: false,
+        };
+
+        let live: Vec<&str> = live_services(&state, "myapp").iter().map(|(s, _)| s.name.as_str()).collect();
+        assert_eq!(live, ["api"]);
+    }
+
+    #[test]
+    fn environ_entries_match_exactly() {
+        let environ = b"HOME=/home/me\0DRIFT_PROJECT=myapp\0";
+        assert!(environ_has(environ, "DRIFT_PROJECT", "myapp"));
+        assert!(!environ_has(environ, "DRIFT_PROJECT", "my"));
+        assert!(!environ_has(b"DRIFT_PROJECT=myapp2\0", "DRIFT_PROJECT", "myapp"));
+    }
+
+    #[test]
+    fn failed_required_flags_services_that_did_not_come_up() {
+        let state = ServicesState {
+            supervisor_pid: 1,
+            project: "myapp".into(),
+            services: vec![
+                service("api", ServiceStatus::Running, None, 0),
+                service("db", ServiceStatus::Failed, None, 0),
+                service("worker", ServiceStatus::Backoff, Some(1), 0),
+                service("flaky", ServiceStatus::Running, None, 1),
+                service("migrate", ServiceStatus::Stopped, Some(0), 0),
+                service("seed", ServiceStatus::Stopped, Some(2), 0),
+            ],
+            suspended: false,
+        };
+        let all: Vec<String> = ["api", "db", "worker", "flaky", "migrate", "seed", "ghost"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(failed_required(&state, &all), ["db", "worker", "flaky", "seed", "ghost"]);
+        assert!(failed_required(&state, &["api".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn policy_restart_reason_from_exit_code() {
+        assert_eq!(policy_restart_reason(Some(0)), StartReason::Exit);
+        assert_eq!(policy_restart_reason(Some(1)), StartReason::Crash);
+        // Killed by a signal: no exit code
+        assert_eq!(policy_restart_reason(None), StartReason::Crash);
+    }
+
+    #[test]
+    fn service_state_without_start_reason_defaults_to_initial() {
+        let json = r#"{"name":"api","pid":1,"status":"running","restart_count":0,"started_at":null,"exit_code":null,"agent_type":null}"#;
+        let state: ServiceState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.start_reason, StartReason::Initial);
+        assert!(state.previous_exit_code.is_none());
+    }
+
+    #[test]
+    fn start_reason_serde_kebab_case() {
+        let json = serde_json::to_string(&StartReason::ConfigReload).unwrap();
+        assert_eq!(json, "\"config-reload\"");
+    }
+
+    #[test]
+    fn parse_fault_crash() {
+        let fault = parse_fault("crash:api:3").unwrap();
+        assert_eq!(fault.service, "api");
+        assert_eq!(fault.kind, FaultKind::Crash);
+        assert_eq!(fault.after, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn parse_fault_fractional_secs() {
+        let fault = parse_fault("crash:worker:0.5").unwrap();
+        assert_eq!(fault.after, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn parse_fault_rejects_bad_specs() {
+        assert!(parse_fault("crash:api").is_err());
+        assert!(parse_fault("hang:api:3").is_err());
+        assert!(parse_fault("crash::3").is_err());
+        assert!(parse_fault("crash:api:soon").is_err());
+        assert!(parse_fault("crash:api:-1").is_err());
+    }
+}
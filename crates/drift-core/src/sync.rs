@@ -1,6 +1,11 @@
 use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use serde::{Deserialize, Serialize};
 
 use crate::config::{self, WindowConfig};
+use crate::window_rules::IgnoreList;
+use crate::{browser, paths};
 
 fn infer_terminal_app_id<'a>(
     running_windows: &'a [(String, Option<String>)],
@@ -13,37 +18,112 @@ fn infer_terminal_app_id<'a>(
         .find(|app_id| app_id.to_lowercase().contains(&term_lower))
 }
 
+/// A window the daemon found in a project's workspace that its config
+/// doesn't account for.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingWindow {
+    /// App id it was detected under (a terminal's own, not blank)
+    pub app_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The `[[windows]]` entry accepting it would add
+    pub window: WindowConfig,
+}
+
+/// The `auto_sync = false` staging area, in the project's state dir.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PendingWindows {
+    #[serde(default)]
+    pub windows: Vec<PendingWindow>,
+    /// App ids rejected in review, which are never staged again
+    #[serde(default)]
+    pub rejected: Vec<String>,
+}
+
+/// Add running windows the project config doesn't account for to its
+/// `[[windows]]`, or with `auto_sync = false` stage them for `drift sync
+/// review`. `[[ignore_windows]]` matches are skipped. True when the config
+/// or the staging area changed.
 pub fn sync_windows_to_config(
     project: &str,
     running_windows: &[(String, Option<String>)],
     terminal_name: &str,
 ) -> anyhow::Result<bool> {
     let mut config = config::load_project_config(project)?;
+    let global_ignore = config::load_global_config().map(|g| g.ignore_windows).unwrap_or_default();
+    let ignore = IgnoreList::new(&global_ignore, &config.ignore_windows);
+    let running_windows: Vec<(String, Option<String>)> = running_windows
+        .iter()
+        .filter(|(app_id, title)| !ignore.is_ignored(Some(app_id), title.as_deref()))
+        .cloned()
+        .collect();
+    let running_windows = running_windows.as_slice();
+
+    if !config.auto_sync {
+        let mut pending = load_pending_windows(project)?;
+        let known: Vec<&WindowConfig> = config.windows.iter().chain(pending.windows.iter().map(|p| &p.window)).collect();
+        let new_windows: Vec<PendingWindow> = detect_new_windows(project, &known, running_windows, terminal_name)
+            .into_iter()
+            .filter(|w| !pending.rejected.contains(&w.app_id))
+            .collect();
+        if new_windows.is_empty() {
+            return Ok(false);
+        }
+        pending.windows.extend(new_windows);
+        save_pending_windows(project, &pending)?;
+        return Ok(true);
+    }
+
+    let known: Vec<&WindowConfig> = config.windows.iter().collect();
+    let new_windows = detect_new_windows(project, &known, running_windows, terminal_name);
+    if new_windows.is_empty() {
+        return Ok(false);
+    }
+    config.windows.extend(new_windows.into_iter().map(|w| w.window));
+    config::save_project_config(project, &config)?;
+    Ok(true)
+}
 
-    let mut terminal_budget: usize = config
-        .windows
+/// Running windows beyond what `known` accounts for, each as a config entry
+/// with a name not taken by `known`.
+fn detect_new_windows(
+    project: &str,
+    known: &[&WindowConfig],
+    running_windows: &[(String, Option<String>)],
+    terminal_name: &str,
+) -> Vec<PendingWindow> {
+    let mut terminal_budget: usize = known
         .iter()
-        .filter(|w| w.app_id.is_none())
+        .filter(|w| w.app_id.is_none() && w.browser.is_none())
         .count();
 
     let mut gui_budget: HashMap<String, usize> = HashMap::new();
-    for w in &config.windows {
-        if let Some(app_id) = &w.app_id {
-            *gui_budget.entry(app_id.clone()).or_insert(0) += 1;
+    for w in known {
+        let app_id = match &w.browser {
+            Some(_) => Some(browser::app_id(project, w.name.as_deref().unwrap_or("browser"))),
+            None => w.app_id.clone(),
+        };
+        if let Some(app_id) = app_id {
+            *gui_budget.entry(app_id).or_insert(0) += 1;
         }
     }
 
     let terminal_app_id = infer_terminal_app_id(running_windows, terminal_name);
 
-    let mut existing_names: HashSet<String> = config
-        .windows
+    let mut existing_names: HashSet<String> = known
         .iter()
         .filter_map(|w| w.name.clone())
         .collect();
 
-    let mut new_windows: Vec<(String, bool)> = Vec::new();
+    let mut new_windows = Vec::new();
+
+    let scratchpad_title = format!("drift:{project}/scratchpad");
 
-    for (app_id, _title) in running_windows {
+    for (app_id, title) in running_windows {
+        // The scratchpad is opened on demand by `drift scratch`, not on open
+        if title.as_deref() == Some(scratchpad_title.as_str()) {
+            continue;
+        }
         if let Some(term_id) = terminal_app_id {
             if app_id == term_id && terminal_budget > 0 {
                 terminal_budget -= 1;
@@ -57,26 +137,80 @@ pub fn sync_windows_to_config(
             }
         }
         let is_terminal = terminal_app_id.is_some_and(|t| app_id == t);
-        new_windows.push((app_id.clone(), is_terminal));
+        let name = generate_window_name(app_id, is_terminal, &mut existing_names);
+        new_windows.push(PendingWindow {
+            app_id: app_id.clone(),
+            title: title.clone(),
+            window: new_window_config(name, (!is_terminal).then(|| app_id.clone())),
+        });
     }
+    new_windows
+}
 
-    if new_windows.is_empty() {
-        return Ok(false);
+fn new_window_config(name: String, app_id: Option<String>) -> WindowConfig {
+    WindowConfig {
+        name: Some(name),
+        app_id,
+        ..Default::default()
     }
+}
 
-    for (app_id, is_terminal) in &new_windows {
-        let name = generate_window_name(app_id, *is_terminal, &mut existing_names);
-        config.windows.push(WindowConfig {
-            name: Some(name),
-            app_id: if *is_terminal { None } else { Some(app_id.clone()) },
-            command: None,
-            width: None,
-            tmux: None,
-        });
+pub fn load_pending_windows(project: &str) -> anyhow::Result<PendingWindows> {
+    let path = paths::pending_windows_path(project);
+    if !path.exists() {
+        return Ok(PendingWindows::default());
     }
+    let data = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&data)?)
+}
 
-    config::save_project_config(project, &config)?;
-    Ok(true)
+fn save_pending_windows(project: &str, pending: &PendingWindows) -> anyhow::Result<()> {
+    let path = paths::pending_windows_path(project);
+    if pending.windows.is_empty() && pending.rejected.is_empty() {
+        let _ = fs::remove_file(&path);
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(pending)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Settle a review: pending windows at `accept` (indices into
+/// [`PendingWindows::windows`]) join the project's `[[windows]]`, renamed if
+/// their name has been taken since; those at `reject` are dropped and their
+/// app ids remembered. The rest stay pending.
+pub fn review_pending_windows(project: &str, accept: &[usize], reject: &[usize]) -> anyhow::Result<()> {
+    let mut pending = load_pending_windows(project)?;
+    let mut config = config::load_project_config(project)?;
+    let mut existing_names: HashSet<String> = config.windows.iter().filter_map(|w| w.name.clone()).collect();
+
+    let mut kept = Vec::new();
+    for (i, mut entry) in std::mem::take(&mut pending.windows).into_iter().enumerate() {
+        if accept.contains(&i) {
+            let taken = entry.window.name.as_ref().is_none_or(|n| !existing_names.insert(n.clone()));
+            if taken {
+                let is_terminal = entry.window.app_id.is_none();
+                entry.window.name = Some(generate_window_name(&entry.app_id, is_terminal, &mut existing_names));
+            }
+            config.windows.push(entry.window);
+        } else if reject.contains(&i) {
+            if !pending.rejected.contains(&entry.app_id) {
+                pending.rejected.push(entry.app_id);
+            }
+        } else {
+            kept.push(entry);
+        }
+    }
+    pending.windows = kept;
+
+    if !accept.is_empty() {
+        config::save_project_config(project, &config)?;
+    }
+    save_pending_windows(project, &pending)
 }
 
 pub fn generate_window_name(
@@ -187,23 +321,16 @@ mod tests {
             WindowConfig {
                 name: Some("editor".into()),
                 command: Some("nvim".into()),
-                width: None,
-                tmux: None,
-                app_id: None,
+                ..Default::default()
             },
             WindowConfig {
                 name: Some("shell".into()),
-                command: None,
-                width: None,
-                tmux: None,
-                app_id: None,
+                ..Default::default()
             },
             WindowConfig {
                 name: Some("browser".into()),
-                command: None,
-                width: None,
-                tmux: None,
                 app_id: Some("org.mozilla.firefox".into()),
+                ..Default::default()
             },
         ];
 
@@ -251,9 +378,7 @@ mod tests {
         let windows = vec![WindowConfig {
             name: Some("editor".into()),
             command: Some("nvim".into()),
-            width: None,
-            tmux: None,
-            app_id: None,
+            ..Default::default()
         }];
 
         let mut terminal_budget: usize = windows.iter().filter(|w| w.app_id.is_none()).count();
@@ -294,6 +419,28 @@ mod tests {
         assert_eq!(new_windows[1], "org.mozilla.firefox");
     }
 
+    #[test]
+    fn new_windows_named_around_known_ones() {
+        let editor = new_window_config("editor".into(), None);
+        let firefox = new_window_config("firefox".into(), Some("org.mozilla.firefox".into()));
+        let running: Vec<(String, Option<String>)> = vec![
+            ("ghostty".to_string(), Some("nvim".into())),
+            ("ghostty".to_string(), None),
+            ("org.mozilla.firefox".to_string(), None),
+            ("org.mozilla.firefox".to_string(), Some("Docs".into())),
+            ("ghostty".to_string(), Some("drift:myapp/scratchpad".into())),
+        ];
+
+        let new_windows = detect_new_windows("myapp", &[&editor, &firefox], &running, "ghostty");
+        assert_eq!(new_windows.len(), 2);
+        assert_eq!(new_windows[0].app_id, "ghostty");
+        assert_eq!(new_windows[0].window.name.as_deref(), Some("shell"));
+        assert_eq!(new_windows[0].window.app_id, None);
+        assert_eq!(new_windows[1].title.as_deref(), Some("Docs"));
+        assert_eq!(new_windows[1].window.name.as_deref(), Some("firefox-2"));
+        assert_eq!(new_windows[1].window.app_id.as_deref(), Some("org.mozilla.firefox"));
+    }
+
     #[test]
     fn name_generation_unique() {
         let mut existing = HashSet::new();
@@ -356,16 +503,12 @@ mod tests {
             WindowConfig {
                 name: Some("editor".into()),
                 command: Some("nvim".into()),
-                width: None,
-                tmux: None,
-                app_id: None,
+                ..Default::default()
             },
             WindowConfig {
                 name: Some("browser".into()),
-                command: None,
-                width: None,
-                tmux: None,
                 app_id: Some("org.mozilla.firefox".into()),
+                ..Default::default()
             },
         ];
 
@@ -112,7 +112,7 @@ impl TaskQueue {
                 if let Some(ref parent_id) = t.parent_task {
                     self.tasks.iter()
                         .find(|p| p.id == *parent_id)
-                        .map_or(true, |p| p.status == TaskStatus::Completed)
+                        .is_none_or(|p| p.status == TaskStatus::Completed)
                 } else {
                     true
                 }
@@ -0,0 +1,137 @@
+//! Per-step timings of `drift open` / `close` / `to`, kept as a rolling
+//! JSONL file so slow steps can be compared across runs.
+
+use std::fs;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{events, paths};
+
+/// Runs kept in the timings file; older ones are dropped on write.
+const MAX_RUNS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub name: String,
+    pub ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub op: String,
+    pub project: String,
+    pub ts: String,
+    pub total_ms: u64,
+    pub steps: Vec<Step>,
+}
+
+/// Measures consecutive steps of one operation. Each `step` covers the time
+/// since the previous one (or since `start`).
+pub struct Timer {
+    op: String,
+    project: String,
+    started: Instant,
+    last: Instant,
+    steps: Vec<Step>,
+}
+
+impl Timer {
+    pub fn start(op: &str, project: &str) -> Self {
+        let now = Instant::now();
+        Self {
+            op: op.to_string(),
+            project: project.to_string(),
+            started: now,
+            last: now,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn step(&mut self, name: &str) {
+        let now = Instant::now();
+        self.steps.push(Step {
+            name: name.to_string(),
+            ms: now.duration_since(self.last).as_millis() as u64,
+        });
+        self.last = now;
+    }
+
+    pub fn into_run(self) -> Run {
+        Run {
+            op: self.op,
+            project: self.project,
+            ts: events::iso_now(),
+            total_ms: self.started.elapsed().as_millis() as u64,
+            steps: self.steps,
+        }
+    }
+
+    /// Record the run. Best effort: timing must never fail the operation.
+    pub fn finish(self) {
+        let _ = record(&self.into_run());
+    }
+}
+
+pub fn record(run: &Run) -> anyhow::Result<()> {
+    let path = paths::timings_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let contents = append_trimmed(&existing, &serde_json::to_string(run)?, MAX_RUNS);
+    let tmp = path.with_extension("jsonl.tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// The last `limit` recorded runs, oldest first.
+pub fn recent(limit: usize) -> anyhow::Result<Vec<Run>> {
+    let path = paths::timings_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let runs: Vec<Run> = fs::read_to_string(&path)?
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    let skip = runs.len().saturating_sub(limit);
+    Ok(runs.into_iter().skip(skip).collect())
+}
+
+fn append_trimmed(existing: &str, line: &str, max: usize) -> String {
+    let mut lines: Vec<&str> = existing.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines.push(line);
+    let skip = lines.len().saturating_sub(max);
+    let mut out = lines[skip..].join("\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_records_steps_in_order() {
+        let mut timer = Timer::start("open", "myapp");
+        timer.step("workspace");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        timer.step("windows");
+        let run = timer.into_run();
+        assert_eq!(run.op, "open");
+        assert_eq!(run.project, "myapp");
+        let names: Vec<_> = run.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["workspace", "windows"]);
+        assert!(run.steps[1].ms >= 5);
+        assert!(run.total_ms >= run.steps.iter().map(|s| s.ms).sum::<u64>());
+    }
+
+    #[test]
+    fn append_trimmed_keeps_newest() {
+        let out = append_trimmed("a\nb\n\nc\n", "d", 3);
+        assert_eq!(out, "b\nc\nd\n");
+        assert_eq!(append_trimmed("", "a", 3), "a\n");
+    }
+}
@@ -0,0 +1,103 @@
+//! tmux sessions drift creates: `drift:<project>` for tmux-backed windows and
+//! `drift-<project>` for interactive agent panes.
+
+use std::process::{Command, Stdio};
+
+use crate::agent;
+
+/// Session holding a project's `tmux = true` windows.
+pub fn window_session_name(project: &str) -> String {
+    format!("drift:{project}")
+}
+
+/// Every session drift may create for `project`, windows first.
+pub fn project_session_names(project: &str) -> [String; 2] {
+    [window_session_name(project), agent::tmux_session_name(project)]
+}
+
+/// Return true if a tmux session with this name exists.
+pub fn session_exists(session: &str) -> bool {
+    Command::new("tmux")
+        .args(["has-session", "-t", &exact_target(session)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Kill a session if it exists. Returns true if one was killed.
+pub fn kill_session(session: &str) -> bool {
+    session_exists(session)
+        && Command::new("tmux")
+            .args(["kill-session", "-t", &exact_target(session)])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+}
+
+/// Names of all sessions on the default tmux server (empty if none is running).
+pub fn list_sessions() -> Vec<String> {
+    Command::new("tmux")
+        .args(["list-sessions", "-F", "#{session_name}"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Project a drift session belongs to, or None for foreign and scratch sessions.
+pub fn session_project(session: &str) -> Option<&str> {
+    if let Some(project) = session.strip_prefix("drift:") {
+        return Some(project);
+    }
+    session
+        .strip_prefix("drift-")
+        .filter(|p| !p.starts_with("scratch-"))
+}
+
+/// Drift sessions whose project isn't open.
+pub fn orphan_sessions(sessions: &[String], open_projects: &[String]) -> Vec<String> {
+    sessions
+        .iter()
+        .filter(|s| session_project(s).is_some_and(|p| !open_projects.iter().any(|o| o == p)))
+        .cloned()
+        .collect()
+}
+
+/// `=name` makes tmux match the session name exactly instead of by prefix,
+/// so closing `api` can't kill `drift:api-v2`.
+fn exact_target(session: &str) -> String {
+    format!("={session}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_names() {
+        assert_eq!(project_session_names("myapp"), ["drift:myapp", "drift-myapp"]);
+    }
+
+    #[test]
+    fn session_project_parses_drift_sessions() {
+        assert_eq!(session_project("drift:myapp"), Some("myapp"));
+        assert_eq!(session_project("drift-myapp"), Some("myapp"));
+        assert_eq!(session_project("drift-scratch-2"), None);
+        assert_eq!(session_project("main"), None);
+    }
+
+    #[test]
+    fn orphans_are_drift_sessions_of_closed_projects() {
+        let sessions: Vec<String> = ["drift:open", "drift-open", "drift:closed", "drift-scratch-1", "work"]
+            .map(String::from)
+            .to_vec();
+        let orphans = orphan_sessions(&sessions, &["open".to_string()]);
+        assert_eq!(orphans, ["drift:closed"]);
+    }
+}
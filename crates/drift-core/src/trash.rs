@@ -0,0 +1,139 @@
+//! `drift delete` moves a project here instead of removing it: the config
+//! and a tarball of its state go into `trash/<name>-<unix secs>/`, where
+//! `drift restore` finds them until they are pruned.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+
+use crate::{paths, registry};
+
+const STATE_ARCHIVE: &str = "state.tar.gz";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    pub project: String,
+    /// Unix seconds when the project was deleted
+    pub deleted_at: u64,
+    pub path: PathBuf,
+}
+
+/// Move a project's config and state into the trash.
+pub fn trash_project(name: &str) -> anyhow::Result<TrashEntry> {
+    let config_path = paths::project_config_path(name);
+    if !config_path.exists() {
+        bail!("Project '{name}' not found");
+    }
+    // Its state dir is drift's own (e.g. the trash itself); trashing it
+    // would take everything else with it
+    if paths::RESERVED_PROJECT_NAMES.contains(&name) {
+        bail!("Project '{name}' uses a reserved name; rename its config before deleting it");
+    }
+    let deleted_at = now();
+    let path = paths::trash_dir().join(format!("{name}-{deleted_at}"));
+    fs::create_dir_all(&path).with_context(|| format!("creating {}", path.display()))?;
+
+    let state = paths::state_dir(name);
+    if state.exists() {
+        registry::tar_dir(&state, &path.join(STATE_ARCHIVE))?;
+    }
+    fs::rename(&config_path, path.join(format!("{name}.toml")))
+        .with_context(|| format!("moving config to {}", path.display()))?;
+    if state.exists() {
+        fs::remove_dir_all(&state).with_context(|| format!("removing state directory {}", state.display()))?;
+    }
+    Ok(TrashEntry { project: name.to_string(), deleted_at, path })
+}
+
+/// Put the most recently deleted copy of a project back.
+pub fn restore_project(name: &str) -> anyhow::Result<TrashEntry> {
+    let Some(entry) = list()?.into_iter().filter(|e| e.project == name).max_by_key(|e| e.deleted_at) else {
+        bail!("No deleted project '{name}' in the trash");
+    };
+    let dest = paths::project_config_path(name);
+    if dest.exists() {
+        bail!("Active project '{name}' already exists");
+    }
+
+    let archive = entry.path.join(STATE_ARCHIVE);
+    if archive.exists() {
+        registry::untar_state(name, &archive)?;
+    }
+    fs::create_dir_all(paths::projects_dir())?;
+    fs::rename(entry.path.join(format!("{name}.toml")), &dest)
+        .with_context(|| format!("restoring config to {}", dest.display()))?;
+    fs::remove_dir_all(&entry.path)?;
+    Ok(entry)
+}
+
+/// Everything in the trash, newest first.
+pub fn list() -> anyhow::Result<Vec<TrashEntry>> {
+    let dir = paths::trash_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if let Some(entry) = parse_entry(&path) {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then_with(|| a.project.cmp(&b.project)));
+    Ok(entries)
+}
+
+/// Remove entries deleted more than `retention_days` ago. 0 keeps everything.
+pub fn prune(retention_days: u32) -> anyhow::Result<Vec<TrashEntry>> {
+    if retention_days == 0 {
+        return Ok(Vec::new());
+    }
+    let cutoff = now().saturating_sub(u64::from(retention_days) * 86_400);
+    let mut removed = Vec::new();
+    for entry in list()? {
+        if entry.deleted_at < cutoff {
+            fs::remove_dir_all(&entry.path).with_context(|| format!("removing {}", entry.path.display()))?;
+            removed.push(entry);
+        }
+    }
+    Ok(removed)
+}
+
+/// `myapp-1700000000` → ("myapp", 1700000000). Project names may contain `-`.
+fn parse_entry(path: &Path) -> Option<TrashEntry> {
+    if !path.is_dir() {
+        return None;
+    }
+    let dir_name = path.file_name()?.to_str()?;
+    let (project, ts) = dir_name.rsplit_once('-')?;
+    Some(TrashEntry {
+        project: project.to_string(),
+        deleted_at: ts.parse().ok()?,
+        path: path.to_path_buf(),
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_names_split_at_last_dash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("my-app-1700000000");
+        fs::create_dir(&path).unwrap();
+        let entry = parse_entry(&path).unwrap();
+        assert_eq!(entry.project, "my-app");
+        assert_eq!(entry.deleted_at, 1_700_000_000);
+
+        let junk = dir.path().join("notes");
+        fs::create_dir(&junk).unwrap();
+        assert!(parse_entry(&junk).is_none());
+    }
+}
@@ -0,0 +1,840 @@
+use toml::Value;
+
+use crate::browser;
+use crate::cgroup;
+use crate::config::{self, ProjectConfig, RestartPolicy};
+use crate::events;
+use crate::schedule::Schedule;
+
+// Known keys per config section. Sections that use `#[serde(flatten)]`
+// (`[env]`, `[ports]`, and the global `[env]`) accept arbitrary keys and are not listed here.
+const GLOBAL_KEYS: &[&str] = &["defaults", "ports", "events", "commander", "features", "webhooks", "metrics", "sinks", "hooks", "folder", "env", "ignore_windows"];
+const DEFAULTS_KEYS: &[&str] = &["terminal", "editor", "shell", "persist_windows", "idle_suspend_minutes", "auto_niri_rules", "save_on_switch", "compositor", "adopt_orphans", "trash_retention_days", "focus_idle_minutes", "git_poll_minutes", "auto_save_minutes"];
+const PORT_DEFAULTS_KEYS: &[&str] = &["base", "range_size"];
+const EVENTS_KEYS: &[&str] = &["buffer_size", "replay_on_subscribe", "retention_days", "heartbeat_secs", "dedup_secs", "limits"];
+const COMMANDER_KEYS: &[&str] = &[
+    "enabled", "endpoint", "endpoint_auth", "voice", "instruct", "fallback_engine", "fallback_voice",
+    "fallback_command", "audio_filter", "speak_background_only", "quiet_hours", "muted_projects", "cooldown_sec", "max_queue", "duck_percent",
+    "event_instructs", "speak_events", "speech_templates", "voice_enabled", "wake_word", "stt_model", "vad_threshold",
+    "max_listen_sec", "speak_feedback", "audio_device", "llm_endpoint", "llm_model",
+];
+const FEATURES_KEYS: &[&str] = &["dispatch", "commander", "drivers"];
+const WEBHOOKS_KEYS: &[&str] = &["url", "rules"];
+const WEBHOOK_RULE_KEYS: &[&str] = &["event", "project", "url"];
+const SINK_KEYS: &[&str] = &["type", "topic", "server", "min_priority", "events", "project", "token"];
+const METRICS_KEYS: &[&str] = &["listen", "token"];
+const HOOK_KEYS: &[&str] = &["event", "command", "project", "timeout_secs"];
+const FOLDER_KEYS: &[&str] = &["output", "icon", "port_base"];
+
+const PROJECT_KEYS: &[&str] = &[
+    "project", "auto_close", "persist_windows", "auto_sync", "env", "git", "ports", "services", "windows",
+    "tmux", "scratchpad", "verification", "watch", "toolchain", "dispatcher", "hooks",
+    "setup", "window_rules", "ssh", "on_open", "on_close", "ignore_windows",
+];
+const PROJECT_META_KEYS: &[&str] = &["name", "repo", "folder", "icon"];
+const GIT_KEYS: &[&str] = &["user_name", "user_email", "signing_key", "commit_sign"];
+const SSH_KEYS: &[&str] = &["key"];
+const SERVICES_KEYS: &[&str] = &["processes"];
+const PROCESS_KEYS: &[&str] = &[
+    "name", "command", "cwd", "restart", "stop_command", "agent", "prompt", "agent_mode",
+    "agent_model", "agent_permissions", "width", "required", "memory_max", "cpu_max", "cgroup",
+    "schedule",
+];
+const WINDOW_KEYS: &[&str] = &[
+    "name", "command", "width", "tmux", "app_id", "floating", "fullscreen", "opacity", "column", "output", "after",
+    "delay", "browser", "url",
+];
+const WINDOW_RULE_KEYS: &[&str] = &["app_id", "title", "move_to_workspace"];
+const IGNORE_WINDOW_KEYS: &[&str] = &["app_id", "title"];
+const TMUX_KEYS: &[&str] = &["kill_on_close"];
+const SCRATCHPAD_KEYS: &[&str] = &["file"];
+const VERIFICATION_KEYS: &[&str] = &["command", "timeout_sec"];
+const WATCH_KEYS: &[&str] = &["globs", "command", "ignore", "debounce_ms"];
+const SETUP_KEYS: &[&str] = &["commands"];
+const TOOLCHAIN_KEYS: &[&str] = &["python", "node", "rust"];
+const DISPATCHER_KEYS: &[&str] = &[
+    "auto_dispatch", "max_concurrent_agents", "review_gate_blocks", "preferred_agent",
+    "preferred_model",
+];
+
+const RESTART_POLICIES: &[&str] = &["never", "on-failure", "always"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: impl Into<String>) -> Self {
+        Self { severity: Severity::Error, message: message.into() }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self { severity: Severity::Warning, message: message.into() }
+    }
+}
+
+/// Validate the contents of the global config.toml.
+pub fn validate_global(contents: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let value: Value = match toml::from_str(contents) {
+        Ok(v) => v,
+        Err(e) => {
+            issues.push(Issue::error(format!("invalid TOML: {}", e.message())));
+            return issues;
+        }
+    };
+
+    check_keys(&value, "", GLOBAL_KEYS, &mut issues);
+    check_table(&value, "defaults", DEFAULTS_KEYS, &mut issues);
+    check_table(&value, "ports", PORT_DEFAULTS_KEYS, &mut issues);
+    check_table(&value, "events", EVENTS_KEYS, &mut issues);
+    check_table(&value, "commander", COMMANDER_KEYS, &mut issues);
+    check_table(&value, "features", FEATURES_KEYS, &mut issues);
+    check_table(&value, "webhooks", WEBHOOKS_KEYS, &mut issues);
+    check_table(&value, "metrics", METRICS_KEYS, &mut issues);
+    if let Some(rules) = value
+        .get("webhooks")
+        .and_then(|w| w.get("rules"))
+        .and_then(Value::as_array)
+    {
+        for (i, rule) in rules.iter().enumerate() {
+            check_keys(rule, &format!("webhooks.rules[{i}]"), WEBHOOK_RULE_KEYS, &mut issues);
+        }
+    }
+    if let Some(sinks) = value.get("sinks").and_then(Value::as_array) {
+        for (i, sink) in sinks.iter().enumerate() {
+            check_keys(sink, &format!("sinks[{i}]"), SINK_KEYS, &mut issues);
+            if let Some(p) = sink.get("min_priority").and_then(Value::as_str) {
+                if !events::PRIORITIES.contains(&p) {
+                    issues.push(Issue::error(format!(
+                        "sinks[{i}].min_priority: '{p}' is not one of {}",
+                        events::PRIORITIES.join(", ")
+                    )));
+                }
+            }
+        }
+    }
+    if let Some(limits) = value.get("events").and_then(|e| e.get("limits")).and_then(Value::as_table) {
+        for (source, limit) in limits {
+            if limit.as_str().and_then(events::parse_rate_limit).is_none() {
+                issues.push(Issue::error(format!(
+                    "events.limits.{source}: invalid limit {limit} (expected e.g. \"10/min\")"
+                )));
+            }
+        }
+    }
+    if let Some(ranges) = value.get("commander").and_then(|c| c.get("quiet_hours")).and_then(Value::as_array) {
+        for range in ranges.iter().filter_map(Value::as_str) {
+            if crate::mute::parse_range(range).is_none() {
+                issues.push(Issue::error(format!(
+                    "commander.quiet_hours: invalid range '{range}' (expected e.g. \"22:00-08:00\")"
+                )));
+            }
+        }
+    }
+    if let Some(duck) = value.get("commander").and_then(|c| c.get("duck_percent")).and_then(Value::as_integer) {
+        if !(0..=100).contains(&duck) {
+            issues.push(Issue::error(format!("commander.duck_percent: {duck} is not between 0 and 100")));
+        }
+    }
+    if let Some(compositor) = value.get("defaults").and_then(|d| d.get("compositor")).and_then(Value::as_str) {
+        if !crate::compositor::BACKENDS.contains(&compositor) {
+            issues.push(Issue::error(format!(
+                "defaults.compositor: '{compositor}' is not one of {}",
+                crate::compositor::BACKENDS.join(", ")
+            )));
+        }
+    }
+    check_hooks(&value, &mut issues);
+    check_ignore_windows(&value, &mut issues);
+    if let Some(folders) = value.get("folder").and_then(Value::as_table) {
+        for (name, folder) in folders {
+            check_keys(folder, &format!("folder.{name}"), FOLDER_KEYS, &mut issues);
+        }
+    }
+    if let Some(listen) = value.get("metrics").and_then(|m| m.get("listen")).and_then(Value::as_str) {
+        match listen.parse::<std::net::SocketAddr>() {
+            Err(_) => issues.push(Issue::error(format!(
+                "metrics.listen: '{listen}' is not an address (expected e.g. \"127.0.0.1:9977\")"
+            ))),
+            Ok(addr) if !addr.ip().is_loopback() && value["metrics"].get("token").is_none() => {
+                issues.push(Issue::warning(format!(
+                    "metrics.listen: '{listen}' is reachable from other machines and no metrics.token is set"
+                )));
+            }
+            Ok(_) => {}
+        }
+    }
+
+    if let Err(e) = toml::from_str::<config::GlobalConfig>(contents) {
+        issues.push(Issue::error(e.message().to_string()));
+    }
+    issues
+}
+
+/// Validate the contents of a project TOML. Returns the parsed config when it
+/// deserializes, so callers can run cross-project checks on it.
+pub fn validate_project(contents: &str) -> (Vec<Issue>, Option<ProjectConfig>) {
+    let mut issues = Vec::new();
+    let value: Value = match toml::from_str(contents) {
+        Ok(v) => v,
+        Err(e) => {
+            issues.push(Issue::error(format!("invalid TOML: {}", e.message())));
+            return (issues, None);
+        }
+    };
+
+    check_keys(&value, "", PROJECT_KEYS, &mut issues);
+    check_table(&value, "project", PROJECT_META_KEYS, &mut issues);
+    check_table(&value, "git", GIT_KEYS, &mut issues);
+    check_table(&value, "ssh", SSH_KEYS, &mut issues);
+    check_table(&value, "services", SERVICES_KEYS, &mut issues);
+    check_table(&value, "tmux", TMUX_KEYS, &mut issues);
+    check_table(&value, "scratchpad", SCRATCHPAD_KEYS, &mut issues);
+    check_table(&value, "verification", VERIFICATION_KEYS, &mut issues);
+    check_table(&value, "watch", WATCH_KEYS, &mut issues);
+    check_table(&value, "setup", SETUP_KEYS, &mut issues);
+    check_table(&value, "toolchain", TOOLCHAIN_KEYS, &mut issues);
+    check_table(&value, "dispatcher", DISPATCHER_KEYS, &mut issues);
+    check_hooks(&value, &mut issues);
+
+    if let Some(processes) = value
+        .get("services")
+        .and_then(|s| s.get("processes"))
+        .and_then(Value::as_array)
+    {
+        for (i, proc) in processes.iter().enumerate() {
+            let label = proc
+                .get("name")
+                .and_then(Value::as_str)
+                .map(|n| format!("services.processes '{n}'"))
+                .unwrap_or_else(|| format!("services.processes[{i}]"));
+            check_keys(proc, &label, PROCESS_KEYS, &mut issues);
+            if let Some(restart) = proc.get("restart").and_then(Value::as_str) {
+                if !RESTART_POLICIES.contains(&restart) {
+                    issues.push(Issue::error(format!(
+                        "{label}: invalid restart policy '{restart}' (use: {})",
+                        RESTART_POLICIES.join(", ")
+                    )));
+                }
+            }
+        }
+    }
+
+    if let Some(windows) = value.get("windows").and_then(Value::as_array) {
+        for (i, win) in windows.iter().enumerate() {
+            check_keys(win, &format!("windows[{i}]"), WINDOW_KEYS, &mut issues);
+        }
+    }
+
+    if let Some(rules) = value.get("window_rules").and_then(Value::as_array) {
+        for (i, rule) in rules.iter().enumerate() {
+            check_keys(rule, &format!("window_rules[{i}]"), WINDOW_RULE_KEYS, &mut issues);
+        }
+    }
+    check_ignore_windows(&value, &mut issues);
+
+    let config = match toml::from_str::<ProjectConfig>(contents) {
+        Ok(c) => c,
+        Err(e) => {
+            // Restart policy errors were already reported with more context
+            if !issues.iter().any(|i| i.message.contains("invalid restart policy")) {
+                issues.push(Issue::error(e.message().to_string()));
+            }
+            return (issues, None);
+        }
+    };
+
+    check_project_semantics(&config, &mut issues);
+    (issues, Some(config))
+}
+
+fn check_project_semantics(config: &ProjectConfig, issues: &mut Vec<Issue>) {
+    if let Err(e) = crate::registry::check_project_name(&config.project.name) {
+        issues.push(Issue::error(format!("project.name: {e}")));
+    }
+    match config::resolve_repo_path(&config.project.repo) {
+        Ok(path) if !path.exists() => {
+            issues.push(Issue::error(format!("repo does not exist: {}", path.display())));
+        }
+        Ok(path) if !path.is_dir() => {
+            issues.push(Issue::error(format!("repo is not a directory: {}", path.display())));
+        }
+        Ok(_) => {}
+        Err(e) => issues.push(Issue::error(format!("repo: {e}"))),
+    }
+
+    if let Some(ssh) = &config.ssh {
+        match config::resolve_repo_path(&ssh.key) {
+            Ok(key) if !key.exists() => issues.push(Issue::error(format!("ssh.key does not exist: {}", key.display()))),
+            Ok(_) => {}
+            Err(e) => issues.push(Issue::error(format!("ssh.key: {e}"))),
+        }
+    }
+
+    if let Some(ports) = &config.ports {
+        if let Some([start, end]) = ports.range {
+            if start > end {
+                issues.push(Issue::error(format!("ports.range: start {start} is greater than end {end}")));
+            }
+        }
+    }
+
+    if let Some(services) = &config.services {
+        for svc in &services.processes {
+            if svc.agent.is_some() && svc.prompt.as_deref().is_none_or(|p| p.trim().is_empty()) {
+                issues.push(Issue::error(format!("agent service '{}' has no prompt", svc.name)));
+            }
+            if svc.agent.is_none() && svc.command.trim().is_empty() {
+                issues.push(Issue::warning(format!("service '{}' has an empty command", svc.name)));
+            }
+            if let Some(memory) = svc.memory_max.as_deref() {
+                if !cgroup::valid_memory(memory) {
+                    issues.push(Issue::error(format!(
+                        "service '{}': invalid memory_max '{memory}' (expected e.g. \"512M\" or \"2G\")",
+                        svc.name
+                    )));
+                }
+            }
+            if let Some(expr) = svc.schedule.as_deref() {
+                match Schedule::parse(expr) {
+                    Ok(_) if svc.restart != RestartPolicy::Never => issues.push(Issue::warning(format!(
+                        "service '{}': restart is ignored for a scheduled service",
+                        svc.name
+                    ))),
+                    Ok(_) => {}
+                    Err(e) => issues.push(Issue::error(format!("service '{}': {e}", svc.name))),
+                }
+            }
+            if let Some(cpu) = svc.cpu_max.as_deref() {
+                if !cgroup::valid_cpu(cpu) {
+                    issues.push(Issue::error(format!(
+                        "service '{}': invalid cpu_max '{cpu}' (expected a percentage, e.g. \"150%\")",
+                        svc.name
+                    )));
+                }
+            }
+        }
+    }
+
+    for (i, win) in config.windows.iter().enumerate() {
+        let label = win.name.as_deref().map(|n| format!("window '{n}'")).unwrap_or_else(|| format!("windows[{i}]"));
+        let has_layout = win.floating.is_some() || win.fullscreen.is_some() || win.opacity.is_some() || win.column.is_some() || win.output.is_some();
+        if has_layout && win.name.is_none() {
+            issues.push(Issue::warning(format!("{label}: layout hints need a window name to match the window")));
+        }
+        if has_layout && win.tmux == Some(true) {
+            issues.push(Issue::warning(format!("{label}: layout hints are ignored for tmux windows")));
+        }
+        if let Some(opacity) = win.opacity {
+            if !(0.0..=1.0).contains(&opacity) {
+                issues.push(Issue::error(format!("{label}: opacity {opacity} is not between 0.0 and 1.0")));
+            }
+        }
+        if win.column == Some(0) {
+            issues.push(Issue::error(format!("{label}: column is 1-based")));
+        }
+        if win.floating == Some(true) && win.column.is_some() {
+            issues.push(Issue::warning(format!("{label}: column has no effect on a floating window")));
+        }
+        if let Some(delay) = win.delay.as_deref() {
+            if config::parse_delay(delay).is_none() {
+                issues.push(Issue::error(format!("{label}: invalid delay '{delay}' (expected e.g. \"2s\" or \"500ms\")")));
+            }
+        }
+        if let Some(after) = win.after.as_deref() {
+            let known = config.services.iter().flat_map(|s| &s.processes).any(|p| p.name == after);
+            if !known {
+                issues.push(Issue::error(format!("{label}: after names unknown service '{after}'")));
+            }
+        }
+        if (win.after.is_some() || win.delay.is_some()) && win.tmux == Some(true) {
+            issues.push(Issue::warning(format!("{label}: after/delay are ignored for tmux windows")));
+        }
+        match win.browser.as_deref() {
+            Some(b) if !browser::BROWSERS.contains(&b) => issues.push(Issue::error(format!(
+                "{label}: unknown browser '{b}' (use: {})",
+                browser::BROWSERS.join(", ")
+            ))),
+            Some(_) if win.command.is_some() || win.app_id.is_some() || win.tmux == Some(true) => {
+                issues.push(Issue::warning(format!("{label}: command, app_id and tmux are ignored for a browser window")));
+            }
+            None if win.url.is_some() => issues.push(Issue::warning(format!("{label}: url only applies to browser windows"))),
+            _ => {}
+        }
+    }
+
+    for (i, rule) in config.window_rules.iter().enumerate() {
+        if rule.app_id.is_none() && rule.title.is_none() {
+            issues.push(Issue::error(format!("window_rules[{i}]: needs app_id or title")));
+        }
+        for (field, pattern) in [("app_id", &rule.app_id), ("title", &rule.title)] {
+            if let Some(Err(e)) = pattern.as_deref().map(regex::Regex::new) {
+                issues.push(Issue::error(format!("window_rules[{i}]: invalid {field} pattern: {e}")));
+            }
+        }
+    }
+}
+
+/// `[[ignore_windows]]`, in the global or a project config.
+fn check_ignore_windows(value: &Value, issues: &mut Vec<Issue>) {
+    let Some(patterns) = value.get("ignore_windows").and_then(Value::as_array) else {
+        return;
+    };
+    for (i, pattern) in patterns.iter().enumerate() {
+        let label = format!("ignore_windows[{i}]");
+        check_keys(pattern, &label, IGNORE_WINDOW_KEYS, issues);
+        let (app_id, title) = (pattern.get("app_id"), pattern.get("title"));
+        if app_id.is_none() && title.is_none() {
+            issues.push(Issue::error(format!("{label}: needs app_id or title")));
+        }
+        for (field, p) in [("app_id", app_id), ("title", title)] {
+            if let Some(Err(e)) = p.and_then(Value::as_str).map(regex::Regex::new) {
+                issues.push(Issue::error(format!("{label}: invalid {field} pattern: {e}")));
+            }
+        }
+    }
+}
+
+/// Report every pair of projects whose `[ports] range` overlaps.
+pub fn find_port_overlaps(ranges: &[(String, [u16; 2])]) -> Vec<(String, String)> {
+    let mut overlaps = Vec::new();
+    for (i, (a, [a_start, a_end])) in ranges.iter().enumerate() {
+        for (b, [b_start, b_end]) in &ranges[i + 1..] {
+            if a_start <= b_end && b_start <= a_end {
+                overlaps.push((a.clone(), b.clone()));
+            }
+        }
+    }
+    overlaps
+}
+
+fn check_table(root: &Value, key: &str, known: &[&str], issues: &mut Vec<Issue>) {
+    if let Some(table) = root.get(key) {
+        check_keys(table, key, known, issues);
+    }
+}
+
+fn check_hooks(value: &Value, issues: &mut Vec<Issue>) {
+    let Some(hooks) = value.get("hooks").and_then(Value::as_array) else {
+        return;
+    };
+    for (i, hook) in hooks.iter().enumerate() {
+        check_keys(hook, &format!("hooks[{i}]"), HOOK_KEYS, issues);
+        if hook.get("command").and_then(Value::as_str).is_some_and(|c| c.trim().is_empty()) {
+            issues.push(Issue::warning(format!("hooks[{i}]: empty command")));
+        }
+    }
+}
+
+fn check_keys(value: &Value, section: &str, known: &[&str], issues: &mut Vec<Issue>) {
+    let Some(table) = value.as_table() else {
+        return;
+    };
+    for key in table.keys() {
+        if !known.contains(&key.as_str()) {
+            if section.is_empty() {
+                issues.push(Issue::error(format!("unknown key '{key}'")));
+            } else {
+                issues.push(Issue::error(format!("{section}: unknown key '{key}'")));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(issues: &[Issue]) -> Vec<&str> {
+        issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .map(|i| i.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn valid_project_has_no_issues() {
+        let toml_str = r#"
+[project]
+name = "ok"
+repo = "/tmp"
+
+[env]
+ANY_KEY = "allowed"
+
+[ports]
+range = [3000, 3010]
+api = 3001
+
+[services]
+processes = [{ name = "api", command = "npm start", restart = "on-failure" }]
+
+[[windows]]
+name = "editor"
+
+[[hooks]]
+event = "build.failed"
+command = "notify-send 'build broke'"
+timeout_secs = 10
+"#;
+        let (issues, config) = validate_project(toml_str);
+        assert!(issues.is_empty(), "unexpected issues: {issues:?}");
+        assert!(config.is_some());
+    }
+
+    #[test]
+    fn unknown_keys_reported_per_section() {
+        let toml_str = r#"
+auto_clsoe = false
+
+[project]
+name = "x"
+repo = "/tmp"
+colour = "red"
+
+[[windows]]
+name = "editor"
+widht = "50%"
+"#;
+        let (issues, _) = validate_project(toml_str);
+        let errs = errors(&issues);
+        assert!(errs.contains(&"unknown key 'auto_clsoe'"), "{errs:?}");
+        assert!(errs.contains(&"project: unknown key 'colour'"), "{errs:?}");
+        assert!(errs.contains(&"windows[0]: unknown key 'widht'"), "{errs:?}");
+    }
+
+    #[test]
+    fn invalid_restart_policy_reported_once() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[services]
+processes = [{ name = "api", command = "run", restart = "sometimes" }]
+"#;
+        let (issues, config) = validate_project(toml_str);
+        assert!(config.is_none());
+        let errs = errors(&issues);
+        assert_eq!(errs.len(), 1, "{errs:?}");
+        assert!(errs[0].contains("invalid restart policy 'sometimes'"));
+        assert!(errs[0].contains("'api'"));
+    }
+
+    #[test]
+    fn missing_repo_reported() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/nonexistent/drift-validate-test"
+"#;
+        let (issues, _) = validate_project(toml_str);
+        assert!(errors(&issues).iter().any(|e| e.starts_with("repo does not exist")));
+    }
+
+    #[test]
+    fn agent_without_prompt_reported() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[services]
+processes = [{ name = "reviewer", agent = "claude" }]
+"#;
+        let (issues, _) = validate_project(toml_str);
+        assert!(errors(&issues).contains(&"agent service 'reviewer' has no prompt"));
+    }
+
+    #[test]
+    fn service_limits_checked() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[services]
+processes = [
+  { name = "api", command = "run", memory_max = "2G", cpu_max = "150%" },
+  { name = "db", command = "run", memory_max = "lots", cpu_max = "1.5" },
+]
+"#;
+        let (issues, _) = validate_project(toml_str);
+        assert_eq!(
+            errors(&issues),
+            vec![
+                "service 'db': invalid memory_max 'lots' (expected e.g. \"512M\" or \"2G\")",
+                "service 'db': invalid cpu_max '1.5' (expected a percentage, e.g. \"150%\")",
+            ]
+        );
+    }
+
+    #[test]
+    fn service_schedule_checked() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[services]
+processes = [
+  { name = "snapshot", command = "pg_dump", schedule = "*/15 * * * *", restart = "always" },
+  { name = "report", command = "run", schedule = "0 25 * * *" },
+]
+"#;
+        let (issues, _) = validate_project(toml_str);
+        assert_eq!(errors(&issues), vec!["service 'report': invalid schedule '0 25 * * *': hour '25' is outside 0-23"]);
+        assert!(issues.iter().any(|i| i.message == "service 'snapshot': restart is ignored for a scheduled service"));
+    }
+
+    #[test]
+    fn window_layout_hints_checked() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[[windows]]
+name = "docs"
+floating = true
+column = 2
+
+[[windows]]
+name = "editor"
+column = 0
+output = "DP-1"
+"#;
+        let (issues, config) = validate_project(toml_str);
+        assert!(config.is_some());
+        assert_eq!(errors(&issues), vec!["window 'editor': column is 1-based"]);
+        assert!(issues.iter().any(|i| i.message == "window 'docs': column has no effect on a floating window"));
+    }
+
+    #[test]
+    fn window_opacity_checked() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[[windows]]
+name = "notes"
+opacity = 0.9
+
+[[windows]]
+name = "editor"
+opacity = 1.5
+"#;
+        let (issues, _) = validate_project(toml_str);
+        assert_eq!(errors(&issues), vec!["window 'editor': opacity 1.5 is not between 0.0 and 1.0"]);
+    }
+
+    #[test]
+    fn window_after_and_delay_checked() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[services]
+processes = [{ name = "frontend", command = "npm run dev" }]
+
+[[windows]]
+name = "browser"
+after = "frontend"
+delay = "2s"
+
+[[windows]]
+name = "docs"
+after = "backend"
+delay = "soon"
+"#;
+        let (issues, _) = validate_project(toml_str);
+        assert_eq!(
+            errors(&issues),
+            vec![
+                "window 'docs': invalid delay 'soon' (expected e.g. \"2s\" or \"500ms\")",
+                "window 'docs': after names unknown service 'backend'",
+            ]
+        );
+    }
+
+    #[test]
+    fn browser_windows_checked() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[[windows]]
+name = "docs"
+browser = "firefox"
+url = "https://docs.rs"
+
+[[windows]]
+name = "old"
+browser = "netscape"
+
+[[windows]]
+name = "shell"
+url = "https://example.com"
+"#;
+        let (issues, _) = validate_project(toml_str);
+        assert_eq!(errors(&issues), vec!["window 'old': unknown browser 'netscape' (use: firefox, chromium, chrome, brave)"]);
+        assert!(issues.iter().any(|i| i.message == "window 'shell': url only applies to browser windows"));
+    }
+
+    #[test]
+    fn window_rules_checked() {
+        let toml_str = r#"
+[project]
+name = "x"
+repo = "/tmp"
+
+[[window_rules]]
+app_id = "^firefox$"
+title = "localhost:3000"
+move_to_workspace = true
+
+[[window_rules]]
+move_to_workspace = true
+
+[[window_rules]]
+title = "(unclosed"
+class = "x"
+"#;
+        let (issues, _) = validate_project(toml_str);
+        let errors = errors(&issues);
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], "window_rules[2]: unknown key 'class'");
+        assert_eq!(errors[1], "window_rules[1]: needs app_id or title");
+        assert!(errors[2].starts_with("window_rules[2]: invalid title pattern"));
+    }
+
+    #[test]
+    fn ignore_windows_checked() {
+        let toml_str = r#"
+[[ignore_windows]]
+app_id = "^mpv$"
+
+[[ignore_windows]]
+title = "(unclosed"
+
+[[ignore_windows]]
+class = "x"
+"#;
+        let issues = validate_global(toml_str);
+        let errors = errors(&issues);
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors[0].starts_with("ignore_windows[1]: invalid title pattern"));
+        assert_eq!(errors[1], "ignore_windows[2]: unknown key 'class'");
+        assert_eq!(errors[2], "ignore_windows[2]: needs app_id or title");
+    }
+
+    #[test]
+    fn global_unknown_keys_reported() {
+        let toml_str = r#"
+[defaults]
+terminal = "foot"
+termnial = "foot"
+
+[commander]
+voice = "Vivian"
+"#;
+        let issues = validate_global(toml_str);
+        assert_eq!(errors(&issues), vec!["defaults: unknown key 'termnial'"]);
+    }
+
+    #[test]
+    fn global_sink_priority_checked() {
+        let toml_str = r#"
+[[sinks]]
+type = "ntfy"
+topic = "drift-alerts"
+min_priority = "urgent"
+"#;
+        let issues = validate_global(toml_str);
+        assert_eq!(
+            errors(&issues),
+            vec!["sinks[0].min_priority: 'urgent' is not one of silent, low, medium, high, critical"]
+        );
+    }
+
+    #[test]
+    fn global_duck_percent_checked() {
+        let issues = validate_global("[commander]\nduck_percent = 120\n");
+        assert_eq!(errors(&issues), vec!["commander.duck_percent: 120 is not between 0 and 100"]);
+    }
+
+    #[test]
+    fn global_folder_keys_checked() {
+        let issues = validate_global("[folder.work]\noutput = \"DP-1\"\nmonitor = \"DP-2\"\n");
+        assert_eq!(errors(&issues), vec!["folder.work: unknown key 'monitor'"]);
+    }
+
+    #[test]
+    fn global_open_metrics_listener_needs_token() {
+        let warned = |toml: &str| validate_global(toml).iter().any(|i| i.message.contains("no metrics.token"));
+        assert!(warned("[metrics]\nlisten = \"0.0.0.0:9977\"\n"));
+        assert!(!warned("[metrics]\nlisten = \"0.0.0.0:9977\"\ntoken = \"s3cret\"\n"));
+        assert!(!warned("[metrics]\nlisten = \"127.0.0.1:9977\"\n"));
+    }
+
+    #[test]
+    fn global_compositor_checked() {
+        assert!(errors(&validate_global("[defaults]\ncompositor = \"hyprland\"\n")).is_empty());
+        let issues = validate_global("[defaults]\ncompositor = \"sway\"\n");
+        assert_eq!(errors(&issues), vec!["defaults.compositor: 'sway' is not one of auto, niri, hyprland"]);
+    }
+
+    #[test]
+    fn global_quiet_hours_checked() {
+        let issues = validate_global("[commander]\nquiet_hours = [\"22:00-08:00\", \"late\"]\n");
+        assert_eq!(
+            errors(&issues),
+            vec!["commander.quiet_hours: invalid range 'late' (expected e.g. \"22:00-08:00\")"]
+        );
+    }
+
+    #[test]
+    fn global_event_limits_checked() {
+        let issues = validate_global("[events.limits]\nci = \"10/min\"\nwatcher = \"10/day\"\n");
+        assert_eq!(
+            errors(&issues),
+            vec!["events.limits.watcher: invalid limit \"10/day\" (expected e.g. \"10/min\")"]
+        );
+    }
+
+    #[test]
+    fn global_hooks_checked() {
+        let toml_str = r#"
+[[hooks]]
+event = "service.crashed"
+command = "systemctl --user restart tunnel"
+timout_secs = 5
+"#;
+        let issues = validate_global(toml_str);
+        assert_eq!(errors(&issues), vec!["hooks[0]: unknown key 'timout_secs'"]);
+    }
+
+    #[test]
+    fn port_overlaps_detected() {
+        let ranges = vec![
+            ("a".to_string(), [3000, 3010]),
+            ("b".to_string(), [3010, 3020]),
+            ("c".to_string(), [4000, 4010]),
+        ];
+        let overlaps = find_port_overlaps(&ranges);
+        assert_eq!(overlaps, vec![("a".to_string(), "b".to_string())]);
+    }
+}
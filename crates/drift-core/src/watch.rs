@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+use crate::config::WatchConfig;
+use crate::events::{self, Event};
+use crate::paths;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Match a `/`-separated relative path against a glob. `*` and `?` stay
+/// within one path segment; a `**` segment matches any number of segments.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pat: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let segs: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&pat, &segs)
+}
+
+fn match_segments(pat: &[&str], segs: &[&str]) -> bool {
+    match pat.split_first() {
+        None => segs.is_empty(),
+        Some((&"**", rest)) => (0..=segs.len()).any(|i| match_segments(rest, &segs[i..])),
+        Some((p, rest)) => match segs.split_first() {
+            Some((s, seg_rest)) => match_segment(p.as_bytes(), s.as_bytes()) && match_segments(rest, seg_rest),
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pat: &[u8], s: &[u8]) -> bool {
+    match pat.split_first() {
+        None => s.is_empty(),
+        Some((b'*', rest)) => (0..=s.len()).any(|i| match_segment(rest, &s[i..])),
+        Some((b'?', rest)) => !s.is_empty() && match_segment(rest, &s[1..]),
+        Some((c, rest)) => s.first() == Some(c) && match_segment(rest, &s[1..]),
+    }
+}
+
+/// Modification times of every file under `root` matching one of `globs`,
+/// keyed by path relative to `root`.
+pub fn scan(root: &Path, globs: &[String], ignore: &[String]) -> HashMap<PathBuf, SystemTime> {
+    let mut files = HashMap::new();
+    let mut stack = vec![PathBuf::new()];
+    while let Some(rel_dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(root.join(&rel_dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let rel = rel_dir.join(entry.file_name());
+            let rel_str = rel.to_string_lossy();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if !ignore.iter().any(|g| glob_match(g, &rel_str)) {
+                    stack.push(rel);
+                }
+            } else if globs.iter().any(|g| glob_match(g, &rel_str)) {
+                if let Ok(mtime) = entry.metadata().and_then(|m| m.modified()) {
+                    files.insert(rel, mtime);
+                }
+            }
+        }
+    }
+    files
+}
+
+/// First path that was added, removed or modified between two scans.
+fn first_change(old: &HashMap<PathBuf, SystemTime>, new: &HashMap<PathBuf, SystemTime>) -> Option<PathBuf> {
+    new.iter()
+        .find(|(path, mtime)| old.get(*path) != Some(*mtime))
+        .map(|(path, _)| path.clone())
+        .or_else(|| old.keys().find(|p| !new.contains_key(*p)).cloned())
+}
+
+/// Poll the repo and run the watch command after each burst of changes,
+/// emitting build.started / build.succeeded / build.failed. Runs until
+/// `shutdown` is set. Output goes to logs/watch.log.
+pub fn run_watcher(
+    project: String,
+    repo_path: PathBuf,
+    env_vars: HashMap<String, String>,
+    config: WatchConfig,
+    shutdown: &'static AtomicBool,
+) {
+    let mut snapshot = scan(&repo_path, &config.globs, &config.ignore);
+    let debounce = Duration::from_millis(config.debounce_ms);
+
+    while !shutdown.load(Ordering::Relaxed) {
+        thread::sleep(POLL_INTERVAL);
+
+        let current = scan(&repo_path, &config.globs, &config.ignore);
+        let Some(changed) = first_change(&snapshot, &current) else {
+            continue;
+        };
+
+        // Wait for the burst (save-all, git checkout) to settle
+        let mut settled = current;
+        loop {
+            thread::sleep(debounce);
+            let next = scan(&repo_path, &config.globs, &config.ignore);
+            if first_change(&settled, &next).is_none() || shutdown.load(Ordering::Relaxed) {
+                break;
+            }
+            settled = next;
+        }
+        snapshot = settled;
+
+        if !shutdown.load(Ordering::Relaxed) {
+            run_build(&project, &repo_path, &env_vars, &config.command, &changed, shutdown);
+        }
+    }
+}
+
+fn run_build(
+    project: &str,
+    repo_path: &Path,
+    env_vars: &HashMap<String, String>,
+    command: &str,
+    changed: &Path,
+    shutdown: &AtomicBool,
+) {
+    let changed = changed.to_string_lossy().to_string();
+    let run_id = events::new_correlation_id();
+    emit(project, &run_id, "build.started", "info", format!("Build started ({changed})"), serde_json::json!({
+        "command": command,
+        "changed": changed,
+    }));
+
+    let log_path = paths::logs_dir(project).join("watch.log");
+    let log = OpenOptions::new().create(true).append(true).open(&log_path);
+    let (stdout, stderr) = match log.and_then(|mut f| {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+        writeln!(f, "\n--- {command} ({changed} changed) at {now} ---")?;
+        let err = f.try_clone()?;
+        Ok((Stdio::from(f), Stdio::from(err)))
+    }) {
+        Ok(pair) => pair,
+        Err(_) => (Stdio::null(), Stdio::null()),
+    };
+
+    let started = Instant::now();
+    let spawned = unsafe {
+        Command::new("sh")
+            .args(["-c", command])
+            .envs(env_vars)
+            .env(events::CORRELATION_ENV, &run_id)
+            .current_dir(repo_path)
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
+            .pre_exec(|| {
+                libc::setsid();
+                Ok(())
+            })
+            .spawn()
+    };
+    let mut child = match spawned {
+        Ok(c) => c,
+        Err(e) => {
+            emit(project, &run_id, "build.failed", "error", format!("Build could not start: {e}"), serde_json::json!({
+                "command": command,
+            }));
+            return;
+        }
+    };
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if shutdown.load(Ordering::Relaxed) => {
+                let _ = signal::kill(Pid::from_raw(-(child.id() as i32)), Signal::SIGTERM);
+                let _ = child.wait();
+                return;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(_) => break None,
+        }
+    };
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let exit_code = status.and_then(|s| s.code());
+    let meta = serde_json::json!({
+        "command": command,
+        "changed": changed,
+        "duration_ms": duration_ms,
+        "exit_code": exit_code,
+    });
+    let secs = duration_ms as f64 / 1000.0;
+    if status.is_some_and(|s| s.success()) {
+        emit(project, &run_id, "build.succeeded", "success", format!("Build succeeded in {secs:.1}s"), meta);
+    } else {
+        emit(project, &run_id, "build.failed", "error", format!("Build failed in {secs:.1}s"), meta);
+    }
+}
+
+fn emit(project: &str, run_id: &str, event_type: &str, level: &str, title: String, meta: serde_json::Value) {
+    events::try_emit_event(&Event {
+        correlation_id: Some(run_id.to_string()),
+        ..Event::new(event_type, project, "watch").level(level).title(title).meta(meta)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_single_segment_wildcards() {
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(!glob_match("*.rs", "src/main.rs"));
+        assert!(glob_match("src/?ain.rs", "src/main.rs"));
+        assert!(!glob_match("src/*.rs", "src/main.ts"));
+    }
+
+    #[test]
+    fn glob_double_star_spans_directories() {
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/a/b/c.rs"));
+        assert!(glob_match("**/Cargo.toml", "Cargo.toml"));
+        assert!(glob_match("**/Cargo.toml", "crates/x/Cargo.toml"));
+        assert!(!glob_match("src/**/*.rs", "tests/a.rs"));
+        assert!(glob_match("target", "target"));
+    }
+
+    #[test]
+    fn scan_respects_globs_and_ignored_dirs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("src/nested/lib.rs"), "").unwrap();
+        fs::write(dir.path().join("src/notes.md"), "").unwrap();
+        fs::write(dir.path().join("target/debug/gen.rs"), "").unwrap();
+
+        let files = scan(dir.path(), &["**/*.rs".into()], &["target".into()]);
+        let mut names: Vec<_> = files.keys().map(|p| p.to_string_lossy().to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["src/main.rs", "src/nested/lib.rs"]);
+    }
+
+    #[test]
+    fn first_change_detects_add_modify_remove() {
+        let t0 = SystemTime::UNIX_EPOCH;
+        let t1 = t0 + Duration::from_secs(1);
+        let old: HashMap<PathBuf, SystemTime> = [(PathBuf::from("a"), t0), (PathBuf::from("b"), t0)].into();
+
+        assert_eq!(first_change(&old, &old), None);
+        let modified: HashMap<_, _> = [(PathBuf::from("a"), t1), (PathBuf::from("b"), t0)].into();
+        assert_eq!(first_change(&old, &modified), Some(PathBuf::from("a")));
+        let removed: HashMap<_, _> = [(PathBuf::from("a"), t0)].into();
+        assert_eq!(first_change(&old, &removed), Some(PathBuf::from("b")));
+    }
+}
@@ -0,0 +1,142 @@
+//! `[[window_rules]]`: tie windows drift didn't spawn to a project by
+//! app_id and/or title regex, so the daemon can claim (and optionally
+//! move) them when they open. `[[ignore_windows]]` uses the same kind of
+//! patterns to keep windows out of sync and snapshots.
+
+use regex::Regex;
+
+use crate::config::{self, ProjectConfig, WindowPattern};
+
+pub struct WindowMatcher {
+    pub project: String,
+    app_id: Option<Regex>,
+    title: Option<Regex>,
+    pub move_to_workspace: bool,
+}
+
+impl WindowMatcher {
+    /// Every pattern the rule has must match; a missing app_id or title
+    /// never matches a pattern for it.
+    pub fn matches(&self, app_id: Option<&str>, title: Option<&str>) -> bool {
+        patterns_match(&self.app_id, &self.title, app_id, title)
+    }
+}
+
+fn patterns_match(app_id_re: &Option<Regex>, title_re: &Option<Regex>, app_id: Option<&str>, title: Option<&str>) -> bool {
+    let field = |re: &Option<Regex>, value: Option<&str>| match re {
+        Some(re) => value.is_some_and(|v| re.is_match(v)),
+        None => true,
+    };
+    (app_id_re.is_some() || title_re.is_some()) && field(app_id_re, app_id) && field(title_re, title)
+}
+
+/// The global and a project's `[[ignore_windows]]`, compiled.
+pub struct IgnoreList(Vec<(Option<Regex>, Option<Regex>)>);
+
+impl IgnoreList {
+    /// Invalid patterns are skipped with a warning, as for window rules.
+    pub fn new(global: &[WindowPattern], project: &[WindowPattern]) -> Self {
+        let mut patterns = Vec::new();
+        for (source, list) in [("config.toml", global), ("project", project)] {
+            for (i, p) in list.iter().enumerate() {
+                let pattern = |p: &Option<String>| p.as_deref().map(Regex::new).transpose();
+                match (pattern(&p.app_id), pattern(&p.title)) {
+                    (Ok(app_id), Ok(title)) => patterns.push((app_id, title)),
+                    (Err(e), _) | (_, Err(e)) => eprintln!("{source}: ignore_windows[{i}] skipped: {e}"),
+                }
+            }
+        }
+        Self(patterns)
+    }
+
+    /// From the global config and the project's own; a config that doesn't
+    /// load contributes nothing.
+    pub fn load(project: &str) -> Self {
+        let global = config::load_global_config().map(|g| g.ignore_windows).unwrap_or_default();
+        let own = config::load_project_config(project).map(|p| p.ignore_windows).unwrap_or_default();
+        Self::new(&global, &own)
+    }
+
+    pub fn is_ignored(&self, app_id: Option<&str>, title: Option<&str>) -> bool {
+        self.0.iter().any(|(a, t)| patterns_match(a, t, app_id, title))
+    }
+}
+
+/// Compile every project's rules, in project order. Rules with an invalid
+/// pattern are skipped with a warning (`drift validate` reports them).
+pub fn compile(projects: &[ProjectConfig]) -> Vec<WindowMatcher> {
+    let mut matchers = Vec::new();
+    for project in projects {
+        let name = &project.project.name;
+        for (i, rule) in project.window_rules.iter().enumerate() {
+            let pattern = |p: &Option<String>| p.as_deref().map(Regex::new).transpose();
+            match (pattern(&rule.app_id), pattern(&rule.title)) {
+                (Ok(app_id), Ok(title)) => matchers.push(WindowMatcher {
+                    project: name.clone(),
+                    app_id,
+                    title,
+                    move_to_workspace: rule.move_to_workspace,
+                }),
+                (Err(e), _) | (_, Err(e)) => eprintln!("{name}: window_rules[{i}] skipped: {e}"),
+            }
+        }
+    }
+    matchers
+}
+
+/// The first rule matching a window.
+pub fn find<'a>(matchers: &'a [WindowMatcher], app_id: Option<&str>, title: Option<&str>) -> Option<&'a WindowMatcher> {
+    matchers.iter().find(|m| m.matches(app_id, title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WindowRule;
+
+    fn project(name: &str, rules: Vec<WindowRule>) -> ProjectConfig {
+        let mut cfg: ProjectConfig = toml::from_str(&format!("[project]\nname = \"{name}\"\nrepo = \"/tmp\"\n")).unwrap();
+        cfg.window_rules = rules;
+        cfg
+    }
+
+    #[test]
+    fn all_given_patterns_must_match() {
+        let matchers = compile(&[
+            project("api", vec![WindowRule {
+                app_id: Some("^firefox$".into()),
+                title: Some("localhost:8080".into()),
+                move_to_workspace: true,
+            }]),
+            project("db", vec![WindowRule { app_id: Some("dbeaver".into()), ..Default::default() }]),
+        ]);
+        assert_eq!(find(&matchers, Some("firefox"), Some("App - localhost:8080")).unwrap().project, "api");
+        assert!(find(&matchers, Some("firefox"), Some("News")).is_none());
+        assert!(find(&matchers, Some("firefox"), None).is_none());
+        assert_eq!(find(&matchers, Some("DBeaver.dbeaver"), None).unwrap().project, "db");
+    }
+
+    #[test]
+    fn ignore_list_combines_global_and_project_patterns() {
+        let global = [WindowPattern { app_id: Some("^mpv$".into()), title: None }];
+        let project = [
+            WindowPattern { app_id: None, title: Some("^(Open|Save) File".into()) },
+            WindowPattern::default(),
+        ];
+        let ignore = IgnoreList::new(&global, &project);
+        assert!(ignore.is_ignored(Some("mpv"), Some("clip.mp4")));
+        assert!(ignore.is_ignored(Some("org.mozilla.firefox"), Some("Open File")));
+        assert!(!ignore.is_ignored(Some("org.mozilla.firefox"), Some("Docs")));
+        assert!(!ignore.is_ignored(None, None));
+    }
+
+    #[test]
+    fn empty_and_invalid_rules_never_match() {
+        let matchers = compile(&[project("api", vec![
+            WindowRule::default(),
+            WindowRule { title: Some("(".into()), ..Default::default() },
+        ])]);
+        assert_eq!(matchers.len(), 1);
+        assert!(find(&matchers, Some("x"), Some("y")).is_none());
+    }
+}
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
 use std::fs;
 use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{niri::NiriClient, paths};
+use crate::config::is_false;
+use crate::window_rules::IgnoreList;
+use crate::{browser, compositor, paths};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WorkspaceSnapshot {
@@ -24,17 +27,72 @@ pub struct SavedWindow {
     pub height: Option<f64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub column_index: Option<usize>,
+    /// Position within the column, from the top, for stacked windows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tile_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub floating: bool,
+    /// The window that had focus when the workspace was saved
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub focused: bool,
 }
 
-/// Extract window config name from drift title pattern "drift:project/name"
-pub fn extract_config_name(title: Option<&str>, project: &str) -> Option<String> {
+/// Extract window config name from drift title pattern "drift:project/name",
+/// or from the app_id of a `browser` window
+pub fn extract_config_name(app_id: Option<&str>, title: Option<&str>, project: &str) -> Option<String> {
+    if let Some(name) = app_id.and_then(|id| browser::window_name_from_app_id(id, project)) {
+        return Some(name);
+    }
     let title = title?;
     let prefix = format!("drift:{project}/");
     title.strip_prefix(&prefix).map(|s| s.to_string())
 }
 
+/// `(project, name)` from a `drift:<project>/<name>` title.
+pub fn parse_window_title(title: &str) -> Option<(&str, &str)> {
+    let (project, name) = title.strip_prefix("drift:")?.split_once('/')?;
+    (!project.is_empty() && !name.is_empty()).then_some((project, name))
+}
+
+/// The `(project, [[windows]] name)` a window opened as: its drift title,
+/// or for a browser window its app_id. `projects` are the known project
+/// names; the longest match wins, so "web" doesn't claim "web-admin"'s
+/// browsers.
+pub fn identify_window<'a>(
+    app_id: Option<&str>,
+    title: Option<&str>,
+    projects: impl IntoIterator<Item = &'a str>,
+) -> Option<(String, String)> {
+    let projects: Vec<&str> = projects.into_iter().collect();
+    if let Some((project, name)) = title.and_then(parse_window_title) {
+        if projects.contains(&project) {
+            return Some((project.to_string(), name.to_string()));
+        }
+    }
+    let app_id = app_id?;
+    projects
+        .into_iter()
+        .filter_map(|p| browser::window_name_from_app_id(app_id, p).map(|name| (p, name)))
+        .max_by_key(|(p, _)| p.len())
+        .map(|(p, name)| (p.to_string(), name))
+}
+
+/// Window ids the daemon has tied to the project's `[[windows]]` entries,
+/// with the entry names. Empty when the daemon isn't running.
+pub fn tracked_windows(project: &str) -> HashMap<u64, String> {
+    let Ok(state) = drift_client::daemon_state() else {
+        return HashMap::new();
+    };
+    state
+        .tracked_windows
+        .into_iter()
+        .filter(|w| w.project == project)
+        .map(|w| (w.window_id, w.name))
+        .collect()
+}
+
 pub fn save_workspace(project: &str) -> anyhow::Result<()> {
-    let mut client = NiriClient::connect()?;
+    let mut client = compositor::connect()?;
     let ws = client.find_workspace_by_name(project)?;
     let ws_id = match ws {
         Some(ws) => ws.id,
@@ -42,16 +100,23 @@ pub fn save_workspace(project: &str) -> anyhow::Result<()> {
     };
 
     let all_windows = client.windows()?;
+    let tracked = tracked_windows(project);
     let windows: Vec<SavedWindow> = all_windows
         .into_iter()
         .filter(|w| w.workspace_id == Some(ws_id))
         .map(|w| SavedWindow {
             app_id: w.app_id.clone(),
             title: w.title.clone(),
-            config_name: extract_config_name(w.title.as_deref(), project),
-            width: Some(w.layout.tile_size.0),
-            height: Some(w.layout.tile_size.1),
-            column_index: w.layout.pos_in_scrolling_layout.map(|(col, _)| col),
+            config_name: tracked
+                .get(&w.id)
+                .cloned()
+                .or_else(|| extract_config_name(w.app_id.as_deref(), w.title.as_deref(), project)),
+            width: w.size.map(|(width, _)| width),
+            height: w.size.map(|(_, height)| height),
+            column_index: w.column,
+            tile_index: w.tile,
+            floating: w.is_floating,
+            focused: w.is_focused,
         })
         .collect();
 
@@ -68,7 +133,11 @@ pub fn load_workspace_snapshot(project: &str) -> anyhow::Result<Option<Workspace
     Ok(Some(snapshot))
 }
 
-pub fn write_snapshot(project: &str, windows: Vec<SavedWindow>) -> anyhow::Result<()> {
+/// Write the snapshot, leaving out windows matching `[[ignore_windows]]`.
+pub fn write_snapshot(project: &str, mut windows: Vec<SavedWindow>) -> anyhow::Result<()> {
+    let ignore = IgnoreList::load(project);
+    windows.retain(|w| !ignore.is_ignored(w.app_id.as_deref(), w.title.as_deref()));
+
     let secs = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap_or_default()
@@ -108,6 +177,9 @@ mod tests {
                     width: None,
                     height: None,
                     column_index: None,
+                    tile_index: None,
+                    floating: false,
+                    focused: false,
                 },
                 SavedWindow {
                     app_id: Some("com.mitchellh.ghostty".into()),
@@ -116,6 +188,9 @@ mod tests {
                     width: None,
                     height: None,
                     column_index: None,
+                    tile_index: None,
+                    floating: false,
+                    focused: false,
                 },
             ],
         };
@@ -137,6 +212,9 @@ mod tests {
             width: None,
             height: None,
             column_index: None,
+            tile_index: None,
+            floating: false,
+            focused: false,
         };
         let json = serde_json::to_string(&window).unwrap();
         let parsed: SavedWindow = serde_json::from_str(&json).unwrap();
@@ -153,6 +231,9 @@ mod tests {
             width: None,
             height: None,
             column_index: None,
+            tile_index: None,
+            floating: false,
+            focused: false,
         };
         let json = serde_json::to_string(&window).unwrap();
         let parsed: SavedWindow = serde_json::from_str(&json).unwrap();
@@ -160,6 +241,31 @@ mod tests {
         assert_eq!(parsed.title.as_deref(), Some("terminal"));
     }
 
+    #[test]
+    fn saved_window_layout_roundtrip() {
+        let window = SavedWindow {
+            app_id: Some("kitty".into()),
+            title: Some("drift:myapp/logs".into()),
+            config_name: Some("logs".into()),
+            width: Some(960.0),
+            height: Some(540.0),
+            column_index: Some(2),
+            tile_index: Some(2),
+            floating: false,
+            focused: true,
+        };
+        let json = serde_json::to_string(&window).unwrap();
+        assert!(!json.contains("floating"));
+        let parsed: SavedWindow = serde_json::from_str(&json).unwrap();
+        assert_eq!((parsed.column_index, parsed.tile_index), (Some(2), Some(2)));
+        assert!(parsed.focused && !parsed.floating);
+
+        // Snapshots from before the layout fields still load
+        let old: SavedWindow = serde_json::from_str(r#"{"app_id":"kitty","title":null,"column_index":1}"#).unwrap();
+        assert_eq!(old.tile_index, None);
+        assert!(!old.focused && !old.floating);
+    }
+
     #[test]
     fn load_workspace_snapshot_nonexistent_path() {
         let result = load_workspace_snapshot("nonexistent_project_that_does_not_exist_xyz_12345").unwrap();
@@ -179,6 +285,9 @@ mod tests {
                     width: None,
                     height: None,
                     column_index: None,
+                    tile_index: None,
+                    floating: false,
+                    focused: false,
                 },
             ],
         };
@@ -190,11 +299,32 @@ mod tests {
         assert_eq!(parsed.windows[0].app_id.as_deref(), Some("app1"));
     }
 
+    #[test]
+    fn window_title_parsed() {
+        assert_eq!(parse_window_title("drift:myapp/editor"), Some(("myapp", "editor")));
+        assert_eq!(parse_window_title("drift:myapp"), None);
+        assert_eq!(parse_window_title("drift:/editor"), None);
+        assert_eq!(parse_window_title("vim README.md"), None);
+    }
+
+    #[test]
+    fn window_identified_by_title_or_browser_app_id() {
+        let projects = ["web", "web-admin"];
+        assert_eq!(identify_window(Some("foot"), Some("drift:web/editor"), projects), Some(("web".into(), "editor".into())));
+        assert_eq!(identify_window(Some("foot"), Some("drift:gone/editor"), projects), None);
+        assert_eq!(
+            identify_window(Some("drift-browser-web-admin-docs"), Some("GitHub"), projects),
+            Some(("web-admin".into(), "docs".into()))
+        );
+        assert_eq!(identify_window(Some("foot"), Some("vim"), projects), None);
+    }
+
     #[test]
     fn extract_config_name_from_title() {
-        assert_eq!(extract_config_name(Some("drift:myapp/editor"), "myapp"), Some("editor".into()));
-        assert_eq!(extract_config_name(Some("drift:myapp"), "myapp"), None);
-        assert_eq!(extract_config_name(Some("random title"), "myapp"), None);
-        assert_eq!(extract_config_name(None, "myapp"), None);
+        assert_eq!(extract_config_name(None, Some("drift:myapp/editor"), "myapp"), Some("editor".into()));
+        assert_eq!(extract_config_name(None, Some("drift:myapp"), "myapp"), None);
+        assert_eq!(extract_config_name(None, Some("random title"), "myapp"), None);
+        assert_eq!(extract_config_name(None, None, "myapp"), None);
+        assert_eq!(extract_config_name(Some("drift-browser-myapp-docs"), Some("GitHub"), "myapp"), Some("docs".into()));
     }
 }
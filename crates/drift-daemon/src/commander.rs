@@ -0,0 +1,190 @@
+//! The commander as a child of the daemon: launched at startup when
+//! `commander.enabled` is set, and restarted with backoff when it crashes.
+
+use std::fs;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+use drift_core::{events, paths};
+
+use crate::state::CommanderState;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A commander that ran this long before crashing restarts without delay
+/// build-up, as if it had never crashed.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+pub struct CommanderWatch {
+    /// None when it wasn't ours to start (already running, or failed to
+    /// spawn) or after a clean exit
+    child: Option<Child>,
+    started: Instant,
+    backoff: Duration,
+    restart_at: Option<Instant>,
+    restarts: u32,
+    last_crash: Option<String>,
+}
+
+impl CommanderWatch {
+    pub fn start() -> Self {
+        Self {
+            child: spawn(),
+            started: Instant::now(),
+            backoff: MIN_BACKOFF,
+            restart_at: None,
+            restarts: 0,
+            last_crash: None,
+        }
+    }
+
+    /// Reap a dead commander and restart a crashed one once its backoff has
+    /// passed. Returns the exit status when it has just crashed. A clean
+    /// exit (`drift commander stop`) is left alone.
+    pub fn poll(&mut self) -> Option<String> {
+        if let Some(at) = self.restart_at {
+            if Instant::now() >= at {
+                self.restart_at = None;
+                self.restarts += 1;
+                self.started = Instant::now();
+                self.child = spawn();
+            }
+            return None;
+        }
+
+        let status = self.child.as_mut()?.try_wait().ok()??;
+        self.child = None;
+        if status.success() {
+            eprintln!("commander exited");
+            return None;
+        }
+
+        if self.started.elapsed() >= STABLE_AFTER {
+            self.backoff = MIN_BACKOFF;
+        }
+        eprintln!("commander crashed ({status}), restarting in {}s", self.backoff.as_secs());
+        self.restart_at = Some(Instant::now() + self.backoff);
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+        self.last_crash = Some(events::iso_now());
+        Some(status.to_string())
+    }
+
+    pub fn state(&self) -> CommanderState {
+        CommanderState {
+            pid: self.child.as_ref().map(Child::id),
+            restarts: self.restarts,
+            last_crash: self.last_crash.clone(),
+        }
+    }
+
+    /// Stop the commander, ours or not.
+    pub fn stop(&mut self) {
+        if let Ok(pid) = fs::read_to_string(paths::commander_pid_path()) {
+            if let Ok(pid) = pid.trim().parse::<i32>() {
+                let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
+            }
+        }
+        if let Some(mut child) = self.child.take() {
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while Instant::now() < deadline {
+                if !matches!(child.try_wait(), Ok(None)) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+}
+
+fn spawn() -> Option<Child> {
+    let pid_path = paths::commander_pid_path();
+    if let Ok(pid_str) = fs::read_to_string(&pid_path) {
+        if let Ok(pid) = pid_str.trim().parse::<i32>() {
+            if signal::kill(Pid::from_raw(pid), None).is_ok() {
+                eprintln!("commander already running (PID {pid}), not supervising it");
+                return None;
+            }
+        }
+    }
+
+    let log_path = paths::state_base_dir().join("commander.log");
+    let log_file = match fs::OpenOptions::new().create(true).append(true).open(&log_path) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("commander: cannot open log: {e}");
+            return None;
+        }
+    };
+    let stderr_file = match log_file.try_clone() {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("commander: cannot clone log fd: {e}");
+            return None;
+        }
+    };
+
+    match Command::new("drift-commander")
+        .stdout(log_file)
+        .stderr(stderr_file)
+        .stdin(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => {
+            eprintln!("commander spawned (PID {})", child.id());
+            Some(child)
+        }
+        Err(e) => {
+            eprintln!("commander: failed to spawn: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(script: &str) -> CommanderWatch {
+        let child = Command::new("sh").args(["-c", script]).spawn().unwrap();
+        CommanderWatch {
+            child: Some(child),
+            started: Instant::now(),
+            backoff: MIN_BACKOFF,
+            restart_at: None,
+            restarts: 0,
+            last_crash: None,
+        }
+    }
+
+    fn poll_until_exit(watch: &mut CommanderWatch) -> Option<String> {
+        for _ in 0..100 {
+            let crashed = watch.poll();
+            if watch.child.is_none() {
+                return crashed;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        panic!("child did not exit");
+    }
+
+    #[test]
+    fn crash_schedules_restart_with_backoff() {
+        let mut w = watch("exit 3");
+        assert_eq!(poll_until_exit(&mut w).as_deref(), Some("exit status: 3"));
+        assert!(w.restart_at.is_some());
+        assert_eq!(w.backoff, MIN_BACKOFF * 2);
+        assert!(w.state().last_crash.is_some());
+        assert_eq!(w.state().pid, None);
+    }
+
+    #[test]
+    fn clean_exit_is_not_restarted() {
+        let mut w = watch("exit 0");
+        assert_eq!(poll_until_exit(&mut w), None);
+        assert!(w.restart_at.is_none());
+        assert!(w.state().last_crash.is_none());
+    }
+}
@@ -1,44 +1,84 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::process::Stdio;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc;
-use std::time::{Duration, Instant};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::{fs, thread};
 
 use niri_ipc::{Event as NiriEvent, Window, Workspace};
-use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
-use nix::unistd::Pid;
+use nix::sys::signal::{SigSet, Signal};
 
 use drift_core::config;
 use drift_core::events::{self, Event};
 use drift_core::paths;
 #[cfg(any(feature = "drivers-claude", feature = "drivers-codex"))]
 use drift_core::driver::{AgentDriver, AgentHandle, AgentState};
-use crate::state::{DaemonState, FocusState, NiriWorkspace, WorkspaceProject};
+use crate::commander::CommanderWatch;
+use crate::metrics::Metrics;
+use crate::rate_limit::Verdict;
+use crate::shutdown::Shutdown;
+use crate::state::{DaemonState, FocusState, NiriWorkspace, TrackedWindow, WorkspaceProject};
 
 const STATE_WRITE_INTERVAL: Duration = Duration::from_secs(1);
+const EVENT_LOG_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const NIRI_RULES_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// How often the spool is checked for events written while the emit socket
+/// was unreachable (daemon starting, socket briefly gone)
+const SPOOL_REPLAY_INTERVAL: Duration = Duration::from_secs(5);
+/// How long after opening a window can still be claimed by a window rule
+const WINDOW_RULE_GRACE: Duration = Duration::from_secs(5);
+/// How often the focused project's time is written to the ledger
+const FOCUS_TIME_INTERVAL: Duration = Duration::from_secs(60);
+/// How often a running commander is checked for a crash
+const COMMANDER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Consecutive persist failures after which `drift.warning` escalates to error level
+const PERSIST_FAILURE_ESCALATION: u32 = 3;
 
 pub enum DaemonMsg {
     NiriEvent(NiriEvent),
     EmitEvent(Event),
+    /// The niri event stream (re)connected
+    NiriConnected,
+    /// The niri event stream broke, usually because the compositor exited
+    NiriDisconnected,
+    /// The stream is back after `down_for` without niri, with its full state
+    NiriReconnected {
+        down_for: Duration,
+        workspaces: Vec<Workspace>,
+        windows: Vec<Window>,
+    },
+    /// Full niri state fetched at `taken`, for reconciling the caches
+    NiriSnapshot {
+        taken: Instant,
+        workspaces: Vec<Workspace>,
+        windows: Vec<Window>,
+    },
+    /// No keyboard/mouse input for `defaults.focus_idle_minutes` (true),
+    /// or input again (false)
+    UserIdle(bool),
+    /// A project's repo after a background fetch (`defaults.git_poll_minutes`)
+    RepoStatus {
+        project: String,
+        status: drift_core::repo_status::RepoStatus,
+    },
+    /// SIGTERM or SIGINT arrived
+    Shutdown,
 }
 
-static SHUTDOWN: AtomicBool = AtomicBool::new(false);
-
-extern "C" fn handle_signal(_: libc::c_int) {
-    SHUTDOWN.store(true, Ordering::Relaxed);
-}
-
-fn install_signal_handlers() {
-    unsafe {
-        let action = SigAction::new(
-            SigHandler::Handler(handle_signal),
-            SaFlags::SA_RESTART,
-            SigSet::empty(),
-        );
-        signal::sigaction(Signal::SIGTERM, &action).expect("install SIGTERM handler");
-        signal::sigaction(Signal::SIGINT, &action).expect("install SIGINT handler");
-    }
+/// Block SIGTERM and SIGINT in every daemon thread and receive them on a
+/// thread of their own, which turns them into `DaemonMsg::Shutdown`.
+/// Children get the default mask back from `Command::spawn`.
+fn spawn_signal_thread(tx: mpsc::Sender<DaemonMsg>) -> anyhow::Result<()> {
+    let mut signals = SigSet::empty();
+    signals.add(Signal::SIGTERM);
+    signals.add(Signal::SIGINT);
+    signals.thread_block()?;
+    thread::Builder::new().name("signals".into()).spawn(move || {
+        if signals.wait().is_ok() {
+            let _ = tx.send(DaemonMsg::Shutdown);
+        }
+    })?;
+    Ok(())
 }
 
 struct DaemonInner {
@@ -46,12 +86,56 @@ struct DaemonInner {
     windows: HashMap<u64, Window>,
     workspace_to_project: HashMap<u64, String>,
     known_projects: HashSet<String>,
+    /// Compiled `[[window_rules]]` of every project
+    window_matchers: Vec<drift_core::window_rules::WindowMatcher>,
+    /// Windows opened recently enough to be claimed by a window rule
+    rule_candidates: HashMap<u64, Instant>,
+    /// Windows tied to a project's `[[windows]]` entry when they opened,
+    /// kept while they live even if they change their title
+    tracked_windows: HashMap<u64, TrackedWindow>,
     active_project: Option<String>,
     focused_workspace_id: Option<u64>,
     events: HashMap<String, VecDeque<Event>>,
     buffer_size: usize,
     terminal_name: String,
     global_persist_windows: bool,
+    webhooks: drift_core::config::WebhooksConfig,
+    sinks: crate::sinks::Sinks,
+    /// `[[hooks]]` runner; None until the daemon starts (and in unit tests)
+    hooks: Option<crate::hooks::Hooks>,
+    /// Append events to the on-disk event log and focus time to its
+    /// ledger (off in unit tests)
+    log_events: bool,
+    idle_suspend_after: Option<Duration>,
+    /// Stops or resumes a project's services: `supervisor::request_suspend`,
+    /// swapped for a recorder in unit tests
+    request_suspend: fn(&str, bool) -> anyhow::Result<()>,
+    /// `events.dedup_secs`; None when repeats aren't coalesced
+    dedup_window: Option<Duration>,
+    /// Open dedup windows by (project, type, title): when the event was let
+    /// through and how many repeats were folded into it since
+    dedup_seen: HashMap<(String, String, Option<String>), (Instant, u64)>,
+    /// `[events.limits]`; only applies to events from the emit socket
+    rate_limits: crate::rate_limit::RateLimits,
+    last_focused: HashMap<String, Instant>,
+    /// Project with focus and when its time was last credited
+    focus_since: Option<(String, Instant)>,
+    /// No input for a while (see `idle`); focus time stops accruing
+    user_idle: bool,
+    /// Commits each project's branch was behind when last reported
+    repo_behind: HashMap<String, u32>,
+    /// daemon.json as last written, so an idle daemon doesn't rewrite it
+    last_state_json: String,
+    suspended_projects: HashSet<String>,
+    /// Consecutive auto-save/auto-sync failures per (project, operation)
+    persist_failures: HashMap<(String, &'static str), u32>,
+    started: Instant,
+    /// When the last niri stream event arrived; snapshots older than this are stale
+    last_niri_event: Instant,
+    /// Projects that had a workspace when the niri stream last broke
+    niri_lost: Option<HashSet<String>>,
+    metrics: Arc<Metrics>,
+    commander: Option<CommanderWatch>,
     subscriber_tx: mpsc::Sender<Event>,
     #[cfg(feature = "dispatch")]
     dispatch_tx: mpsc::Sender<Event>,
@@ -60,23 +144,44 @@ struct DaemonInner {
 impl DaemonInner {
     #[cfg(feature = "dispatch")]
     fn new(subscriber_tx: mpsc::Sender<Event>, dispatch_tx: mpsc::Sender<Event>, buffer_size: usize, terminal_name: String, global_persist_windows: bool) -> Self {
-        let known_projects: HashSet<String> = drift_core::registry::list_projects()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|p| p.project.name)
-            .collect();
+        let projects = drift_core::registry::list_projects().unwrap_or_default();
+        let known_projects: HashSet<String> = projects.iter().map(|p| p.project.name.clone()).collect();
 
         Self {
             workspaces: HashMap::new(),
             windows: HashMap::new(),
             workspace_to_project: HashMap::new(),
             known_projects,
+            window_matchers: drift_core::window_rules::compile(&projects),
+            rule_candidates: HashMap::new(),
+            tracked_windows: HashMap::new(),
             active_project: None,
             focused_workspace_id: None,
             events: HashMap::new(),
             buffer_size,
             terminal_name,
             global_persist_windows,
+            webhooks: Default::default(),
+            sinks: Default::default(),
+            hooks: None,
+            log_events: true,
+            idle_suspend_after: None,
+            request_suspend: drift_core::supervisor::request_suspend,
+            dedup_window: None,
+            dedup_seen: HashMap::new(),
+            rate_limits: Default::default(),
+            last_focused: HashMap::new(),
+            focus_since: None,
+            user_idle: false,
+            repo_behind: HashMap::new(),
+            last_state_json: String::new(),
+            suspended_projects: HashSet::new(),
+            persist_failures: HashMap::new(),
+            started: Instant::now(),
+            last_niri_event: Instant::now(),
+            niri_lost: None,
+            metrics: Metrics::new(),
+            commander: None,
             subscriber_tx,
             dispatch_tx,
         }
@@ -84,23 +189,44 @@ impl DaemonInner {
 
     #[cfg(not(feature = "dispatch"))]
     fn new(subscriber_tx: mpsc::Sender<Event>, buffer_size: usize, terminal_name: String, global_persist_windows: bool) -> Self {
-        let known_projects: HashSet<String> = drift_core::registry::list_projects()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|p| p.project.name)
-            .collect();
+        let projects = drift_core::registry::list_projects().unwrap_or_default();
+        let known_projects: HashSet<String> = projects.iter().map(|p| p.project.name.clone()).collect();
 
         Self {
             workspaces: HashMap::new(),
             windows: HashMap::new(),
             workspace_to_project: HashMap::new(),
             known_projects,
+            window_matchers: drift_core::window_rules::compile(&projects),
+            rule_candidates: HashMap::new(),
+            tracked_windows: HashMap::new(),
             active_project: None,
             focused_workspace_id: None,
             events: HashMap::new(),
             buffer_size,
             terminal_name,
             global_persist_windows,
+            webhooks: Default::default(),
+            sinks: Default::default(),
+            hooks: None,
+            log_events: true,
+            idle_suspend_after: None,
+            request_suspend: drift_core::supervisor::request_suspend,
+            dedup_window: None,
+            dedup_seen: HashMap::new(),
+            rate_limits: Default::default(),
+            last_focused: HashMap::new(),
+            focus_since: None,
+            user_idle: false,
+            repo_behind: HashMap::new(),
+            last_state_json: String::new(),
+            suspended_projects: HashSet::new(),
+            persist_failures: HashMap::new(),
+            started: Instant::now(),
+            last_niri_event: Instant::now(),
+            niri_lost: None,
+            metrics: Metrics::new(),
+            commander: None,
             subscriber_tx,
         }
     }
@@ -112,6 +238,7 @@ impl DaemonInner {
 
                 self.workspaces = workspaces.into_iter().map(|ws| (ws.id, ws)).collect();
                 if let Ok(projects) = drift_core::registry::list_projects() {
+                    self.window_matchers = drift_core::window_rules::compile(&projects);
                     self.known_projects = projects.into_iter().map(|p| p.project.name).collect();
                 }
                 self.rebuild_workspace_project_map();
@@ -120,30 +247,10 @@ impl DaemonInner {
                 let new_projects: HashSet<String> = self.workspace_to_project.values().cloned().collect();
 
                 for project in new_projects.difference(&old_projects) {
-                    self.process_event(Event {
-                        event_type: "workspace.created".into(),
-                        project: project.clone(),
-                        source: "daemon".into(),
-                        ts: events::iso_now(),
-                        level: Some("info".into()),
-                        title: None,
-                        body: None,
-                        meta: None,
-                        priority: None,
-                    });
+                    self.process_event(Event::new("workspace.created", project.clone(), "daemon").level("info"));
                 }
                 for project in old_projects.difference(&new_projects) {
-                    self.process_event(Event {
-                        event_type: "workspace.destroyed".into(),
-                        project: project.clone(),
-                        source: "daemon".into(),
-                        ts: events::iso_now(),
-                        level: Some("info".into()),
-                        title: None,
-                        body: None,
-                        meta: None,
-                        priority: None,
-                    });
+                    self.process_event(Event::new("workspace.destroyed", project.clone(), "daemon").level("info"));
                 }
 
                 self.update_active_project();
@@ -153,37 +260,9 @@ impl DaemonInner {
                     if let Some(prev_id) = self.focused_workspace_id {
                         if prev_id != id {
                             if let Some(project) = self.workspace_to_project.get(&prev_id).cloned() {
-                                self.save_workspace_snapshot(&project, prev_id);
-
-                                let persist = drift_core::config::load_project_config(&project)
-                                    .ok()
-                                    .and_then(|cfg| cfg.persist_windows)
-                                    .unwrap_or(self.global_persist_windows);
-
-                                if !persist {
-                                    let running_windows: Vec<(String, Option<String>)> = self.windows.values()
-                                        .filter(|w| w.workspace_id == Some(prev_id))
-                                        .filter_map(|w| {
-                                            let app_id = w.app_id.clone()?;
-                                            Some((app_id, w.title.clone()))
-                                        })
-                                        .collect();
-                                    if let Err(e) = drift_core::sync::sync_windows_to_config(&project, &running_windows, &self.terminal_name) {
-                                        eprintln!("auto-sync windows for '{project}': {e}");
-                                    }
-                                }
+                                self.persist_workspace(&project, prev_id);
 
-                                self.process_event(Event {
-                                    event_type: "workspace.deactivated".into(),
-                                    project: project.clone(),
-                                    source: "daemon".into(),
-                                    ts: events::iso_now(),
-                                    level: Some("info".into()),
-                                    title: None,
-                                    body: None,
-                                    meta: None,
-                                    priority: None,
-                                });
+                                self.process_event(Event::new("workspace.deactivated", project.clone(), "daemon").level("info"));
                             }
                         }
                     }
@@ -214,29 +293,34 @@ impl DaemonInner {
                     self.update_active_project();
 
                     if let Some(project) = self.workspace_to_project.get(&id).cloned() {
-                        self.process_event(Event {
-                            event_type: "workspace.activated".into(),
-                            project: project.clone(),
-                            source: "daemon".into(),
-                            ts: events::iso_now(),
-                            level: Some("info".into()),
-                            title: None,
-                            body: None,
-                            meta: None,
-                            priority: None,
-                        });
+                        self.process_event(Event::new("workspace.activated", project.clone(), "daemon").level("info"));
                     }
                 }
             }
             NiriEvent::WindowsChanged { windows } => {
                 self.windows = windows.into_iter().map(|w| (w.id, w)).collect();
+                // Ids restart with niri; a tie only survives if the pid agrees
+                let windows = &self.windows;
+                self.tracked_windows.retain(|id, t| windows.get(id).is_some_and(|w| w.pid == t.pid));
+                let ids: Vec<u64> = self.windows.keys().copied().collect();
+                for id in ids {
+                    self.track_window(id);
+                }
             }
             NiriEvent::WindowOpenedOrChanged { window } => {
-                self.windows.insert(window.id, window);
+                let id = window.id;
+                if !self.windows.contains_key(&id) {
+                    self.rule_candidates.insert(id, Instant::now());
+                }
+                self.windows.insert(id, window);
+                self.track_window(id);
+                self.apply_window_rules(id);
             }
             NiriEvent::WindowClosed { id } => {
                 let ws_id = self.windows.get(&id).and_then(|w| w.workspace_id);
                 self.windows.remove(&id);
+                self.rule_candidates.remove(&id);
+                self.tracked_windows.remove(&id);
 
                 // Save snapshot AFTER removing so the closed window is excluded
                 if let Some(ws_id) = ws_id {
@@ -255,25 +339,18 @@ impl DaemonInner {
                     win.is_focused = Some(win.id) == id;
                 }
             }
-            NiriEvent::WindowUrgencyChanged { id, urgent } => {
-                if urgent {
-                    if let Some(win) = self.windows.get(&id) {
-                        if let Some(ws_id) = win.workspace_id {
-                            if let Some(project) = self.workspace_to_project.get(&ws_id).cloned() {
-                                let is_active = self.active_project.as_deref() == Some(&project);
-                                if !is_active {
-                                    self.process_event(Event {
-                                        event_type: "window.urgent".into(),
-                                        project,
-                                        source: "window".into(),
-                                        ts: events::iso_now(),
-                                        level: Some("warning".into()),
-                                        title: Some("Window needs attention".into()),
-                                        body: win.title.clone(),
-                                        meta: None,
-                                        priority: None,
-                                    });
-                                }
+            NiriEvent::WindowUrgencyChanged { id, urgent } if urgent => {
+                if let Some(win) = self.windows.get(&id) {
+                    if let Some(ws_id) = win.workspace_id {
+                        if let Some(project) = self.workspace_to_project.get(&ws_id).cloned() {
+                            let is_active = self.active_project.as_deref() == Some(&project);
+                            if !is_active {
+                                self.process_event(Event {
+                                    body: win.title.clone(),
+                                    ..Event::new("window.urgent", project, "window")
+                                        .level("warning")
+                                        .title("Window needs attention")
+                                });
                             }
                         }
                     }
@@ -283,6 +360,106 @@ impl DaemonInner {
         }
     }
 
+    /// Replace the caches with a fresh niri snapshot if they disagree, for
+    /// when the event stream dropped or skipped messages.
+    fn reconcile(&mut self, taken: Instant, workspaces: Vec<Workspace>, windows: Vec<Window>) {
+        // A stream event newer than the snapshot already holds fresher state
+        if self.last_niri_event > taken {
+            return;
+        }
+        let stale_workspaces = stale_workspace_count(&self.workspaces, &workspaces);
+        let stale_windows = stale_window_count(&self.windows, &windows);
+        let focused = workspaces.iter().find(|ws| ws.is_focused).map(|ws| ws.id);
+        if stale_workspaces == 0 && stale_windows == 0 && focused == self.focused_workspace_id {
+            return;
+        }
+
+        eprintln!("niri state drifted ({stale_workspaces} workspaces, {stale_windows} windows), resyncing");
+        self.handle_niri_event(NiriEvent::WindowsChanged { windows });
+        self.handle_niri_event(NiriEvent::WorkspacesChanged { workspaces });
+        self.focused_workspace_id = focused;
+        self.update_active_project();
+
+        self.process_event(Event::new("daemon.resynced", "", "daemon")
+            .level("warning")
+            .title("Daemon state resynced with niri")
+            .meta(serde_json::json!({
+                "stale_workspaces": stale_workspaces,
+                "stale_windows": stale_windows,
+            })));
+    }
+
+    /// The niri stream broke. The caches stay as they were, so that on
+    /// reconnect the projects that lost their workspace can be told apart.
+    fn niri_disconnected(&mut self) {
+        eprintln!("lost niri event stream");
+        self.niri_lost = Some(self.workspace_to_project.values().cloned().collect());
+        self.focused_workspace_id = None;
+        self.credit_focus(None);
+    }
+
+    /// niri is back, possibly as a new compositor with new workspace and
+    /// window ids: replace the caches wholesale and rebuild the project map.
+    fn niri_reconnected(&mut self, down_for: Duration, workspaces: Vec<Workspace>, windows: Vec<Window>) {
+        let before = self.niri_lost.take().unwrap_or_default();
+        let focused = workspaces.iter().find(|ws| ws.is_focused).map(|ws| ws.id);
+        self.last_niri_event = Instant::now();
+        self.handle_niri_event(NiriEvent::WindowsChanged { windows });
+        self.handle_niri_event(NiriEvent::WorkspacesChanged { workspaces });
+        self.focused_workspace_id = focused;
+        self.update_active_project();
+
+        let open: HashSet<String> = self.workspace_to_project.values().cloned().collect();
+        let mut projects: Vec<&String> = open.iter().collect();
+        projects.sort();
+        let mut lost: Vec<&String> = before.difference(&open).collect();
+        lost.sort();
+        eprintln!("niri reconnected after {}s, {} project workspaces, {} lost", down_for.as_secs(), open.len(), lost.len());
+
+        self.process_event(Event {
+            body: (!lost.is_empty()).then(|| {
+                let names: Vec<&str> = lost.iter().map(|p| p.as_str()).collect();
+                format!("Workspaces gone: {}", names.join(", "))
+            }),
+            ..Event::new("daemon.reconnected", "", "daemon")
+                .level(if lost.is_empty() { "info" } else { "warning" })
+                .title("Reconnected to niri")
+                .meta(serde_json::json!({
+                    "down_secs": down_for.as_secs(),
+                    "projects": projects,
+                    "lost": lost,
+                }))
+        });
+    }
+
+    /// Liveness signal for subscribers. Not buffered or logged.
+    fn send_heartbeat(&self) {
+        let _ = self.subscriber_tx.send(Event {
+            priority: Some("silent".into()),
+            ..Event::new("daemon.heartbeat", "", "daemon")
+                .meta(serde_json::json!({
+                    "uptime_secs": self.started.elapsed().as_secs(),
+                    "last_niri_event_secs": self.last_niri_event.elapsed().as_secs(),
+                    "workspaces": self.workspaces.len(),
+                    "windows": self.windows.len(),
+                }))
+        });
+    }
+
+    /// Restart a crashed commander; true when it had crashed.
+    fn check_commander(&mut self) -> bool {
+        let Some(status) = self.commander.as_mut().and_then(CommanderWatch::poll) else {
+            return false;
+        };
+        let restarts = self.commander.as_ref().map_or(0, |c| c.state().restarts);
+        self.process_event(Event::new("commander.crashed", "", "daemon")
+            .level("error")
+            .title("Commander crashed")
+            .body(format!("{status}; restarting"))
+            .meta(serde_json::json!({ "status": status, "restarts": restarts })));
+        true
+    }
+
     fn rebuild_workspace_project_map(&mut self) {
         self.workspace_to_project.clear();
         for ws in self.workspaces.values() {
@@ -321,47 +498,316 @@ impl DaemonInner {
 
         for (ws_id, name, window_count) in unmanaged {
             let _ = self.subscriber_tx.send(Event {
-                event_type: "workspace.unmanaged".into(),
-                project: String::new(),
-                source: "daemon".into(),
-                ts: events::iso_now(),
-                level: Some("info".into()),
-                title: Some(format!("Unmanaged workspace: {name}")),
-                body: None,
-                meta: Some(serde_json::json!({
-                    "workspace_id": ws_id,
-                    "workspace_name": name,
-                    "window_count": window_count,
-                })),
                 priority: Some("silent".into()),
+                ..Event::new("workspace.unmanaged", "", "daemon")
+                    .level("info")
+                    .title(format!("Unmanaged workspace: {name}"))
+                    .meta(serde_json::json!({
+                        "workspace_id": ws_id,
+                        "workspace_name": name,
+                        "window_count": window_count,
+                    }))
             });
         }
     }
 
+    /// Claim a newly opened window for the project whose `[[window_rules]]`
+    /// match it, moving it to the project's workspace if the rule says so.
+    /// Titles often arrive after the window opens, so it stays a candidate
+    /// for a few seconds.
+    fn apply_window_rules(&mut self, id: u64) {
+        let Some(opened) = self.rule_candidates.get(&id) else {
+            return;
+        };
+        if opened.elapsed() > WINDOW_RULE_GRACE {
+            self.rule_candidates.remove(&id);
+            return;
+        }
+        let Some(win) = self.windows.get(&id) else {
+            return;
+        };
+        let (app_id, title, current_ws) = (win.app_id.clone(), win.title.clone(), win.workspace_id);
+        // Already a project's: opened on its workspace or tied to a [[windows]] entry
+        let owned = current_ws.is_some_and(|ws| self.workspace_to_project.contains_key(&ws));
+        if owned || self.tracked_windows.contains_key(&id) {
+            self.rule_candidates.remove(&id);
+            return;
+        }
+        let Some(rule) = drift_core::window_rules::find(&self.window_matchers, app_id.as_deref(), title.as_deref()) else {
+            return;
+        };
+        let (project, wants_move) = (rule.project.clone(), rule.move_to_workspace);
+        self.rule_candidates.remove(&id);
+
+        let target = self
+            .workspace_to_project
+            .iter()
+            .find(|(_, p)| **p == project)
+            .map(|(ws, _)| *ws);
+        let moved = match target {
+            Some(ws) if wants_move && current_ws != Some(ws) => drift_core::niri::NiriClient::connect()
+                .and_then(|mut c| c.move_window_to_workspace(id, ws))
+                .map_err(|e| eprintln!("moving window {id} to '{project}' failed: {e}"))
+                .is_ok(),
+            _ => false,
+        };
+
+        self.process_event(Event {
+            priority: Some("silent".into()),
+            ..Event::new("window.claimed", project.clone(), "daemon")
+                .level("info")
+                .title(format!("Window '{}' matched a window rule", title.as_deref().or(app_id.as_deref()).unwrap_or("?")))
+                .meta(serde_json::json!({
+                    "window_id": id,
+                    "app_id": app_id,
+                    "title": title,
+                    "moved": moved,
+                }))
+        });
+    }
+
     fn update_active_project(&mut self) {
         self.active_project = self.focused_workspace_id
             .and_then(|id| self.workspace_to_project.get(&id))
             .cloned();
+        if self.focus_since.as_ref().map(|(p, _)| p) != self.active_project.as_ref() {
+            self.credit_focus(self.active_project.clone());
+        }
+        // Resume right away on focus rather than waiting for the next idle check
+        if self.active_project.as_ref().is_some_and(|p| self.suspended_projects.contains(p)) {
+            self.check_idle_projects();
+        }
+    }
+
+    /// Add the time since the last credit to the project that had focus,
+    /// then start timing `next`. A project keeping focus carries over the
+    /// sub-second remainder.
+    fn credit_focus(&mut self, next: Option<String>) {
+        self.credit_focus_until(Instant::now(), next);
+    }
+
+    fn credit_focus_until(&mut self, now: Instant, next: Option<String>) {
+        // Nobody at the keyboard: nothing to time
+        let next = next.filter(|_| !self.user_idle);
+        if let Some((project, since)) = self.focus_since.take() {
+            let secs = now.saturating_duration_since(since).as_secs();
+            if secs > 0 && self.log_events {
+                let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+                if let Err(e) = drift_core::focus_time::record(&project, secs, ts) {
+                    eprintln!("focus time for '{project}' not recorded: {e}");
+                }
+            }
+            if next.as_ref() == Some(&project) {
+                self.focus_since = Some((project, since + Duration::from_secs(secs)));
+                return;
+            }
+        }
+        self.focus_since = next.map(|p| (p, now));
+    }
+
+    /// Emit `git.behind_upstream` when a project that isn't focused falls
+    /// further behind than last reported. The focused project only updates
+    /// the count, so it isn't reported again once you switch away.
+    fn repo_status_changed(&mut self, project: String, status: drift_core::repo_status::RepoStatus) {
+        let reported = self.repo_behind.insert(project.clone(), status.behind).unwrap_or(0);
+        if status.behind <= reported || self.active_project.as_deref() == Some(project.as_str()) {
+            return;
+        }
+        let behind = status.behind;
+        self.process_event(Event::new("git.behind_upstream", project.clone(), "daemon")
+            .level("info")
+            .title(format!(
+                "'{project}' is {behind} commit{} behind upstream",
+                if behind == 1 { "" } else { "s" }
+            ))
+            .meta(serde_json::json!({
+                "branch": status.branch,
+                "behind": behind,
+                "ahead": status.ahead,
+            })));
+    }
+
+    /// The user went idle or came back. Idle is only reported after the
+    /// threshold has passed, so those minutes are taken back off.
+    fn user_idle_changed(&mut self, idle: bool, threshold: Duration) {
+        let now = Instant::now();
+        if idle {
+            self.user_idle = true;
+            let went_idle = now.checked_sub(threshold).unwrap_or(now);
+            if let Some((project, since)) = &self.focus_since {
+                // The periodic credit may already have counted idle minutes
+                let over = since.saturating_duration_since(went_idle).as_secs();
+                if over > 0 && self.log_events {
+                    let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+                    if let Err(e) = drift_core::focus_time::take_back(project, over, ts) {
+                        eprintln!("focus time for '{project}' not corrected: {e}");
+                    }
+                }
+            }
+            self.credit_focus_until(went_idle, None);
+        } else {
+            self.user_idle = false;
+            self.credit_focus(self.active_project.clone());
+        }
+    }
+
+    /// Suspend services of projects whose workspace has been unfocused for
+    /// `defaults.idle_suspend_minutes`, and resume the focused one.
+    fn check_idle_projects(&mut self) {
+        let Some(idle_after) = self.idle_suspend_after else {
+            return;
+        };
+        let now = Instant::now();
+        let open: HashSet<String> = self.workspace_to_project.values().cloned().collect();
+        self.last_focused.retain(|p, _| open.contains(p));
+        // A closed project's services would stay stopped, and it would
+        // never be resumed if its workspace came back
+        let closed: Vec<String> = self.suspended_projects.iter().filter(|p| !open.contains(*p)).cloned().collect();
+        for project in closed {
+            self.suspended_projects.remove(&project);
+            if let Err(e) = (self.request_suspend)(&project, false) {
+                eprintln!("resume '{project}' failed: {e}");
+            }
+        }
+
+        for project in open {
+            if self.active_project.as_ref() == Some(&project) {
+                self.last_focused.insert(project.clone(), now);
+                if self.suspended_projects.remove(&project) {
+                    if let Err(e) = (self.request_suspend)(&project, false) {
+                        eprintln!("resume '{project}' failed: {e}");
+                    }
+                }
+                continue;
+            }
+            let since = *self.last_focused.entry(project.clone()).or_insert(now);
+            if now.duration_since(since) >= idle_after
+                && !self.suspended_projects.contains(&project)
+                // Projects without services have no supervisor; that's not an error
+                && (self.request_suspend)(&project, true).is_ok()
+            {
+                self.suspended_projects.insert(project);
+            }
+        }
+    }
+
+    /// Tie a window to its `[[windows]]` entry the first time it shows a
+    /// drift title (or browser app_id). Later titles don't change the tie.
+    fn track_window(&mut self, id: u64) {
+        if self.tracked_windows.contains_key(&id) {
+            return;
+        }
+        let Some(window) = self.windows.get(&id) else {
+            return;
+        };
+        let identified = drift_core::workspace::identify_window(
+            window.app_id.as_deref(),
+            window.title.as_deref(),
+            self.known_projects.iter().map(String::as_str),
+        );
+        if let Some((project, name)) = identified {
+            let pid = window.pid;
+            self.tracked_windows.insert(id, TrackedWindow { window_id: id, project, name, pid });
+        }
+    }
+
+    /// Snapshot the workspace and, unless the project keeps its windows
+    /// config as written (`persist_windows`), add new windows to it.
+    fn persist_workspace(&mut self, project: &str, ws_id: u64) {
+        // An empty workspace (e.g. right after niri restarted) would
+        // overwrite the saved layout with nothing
+        if !self.windows.values().any(|w| w.workspace_id == Some(ws_id)) {
+            return;
+        }
+        self.save_workspace_snapshot(project, ws_id);
+
+        let persist = drift_core::config::load_project_config(project)
+            .ok()
+            .and_then(|cfg| cfg.persist_windows)
+            .unwrap_or(self.global_persist_windows);
+        if !persist {
+            let running_windows: Vec<(String, Option<String>)> = self.windows.values()
+                .filter(|w| w.workspace_id == Some(ws_id))
+                .filter_map(|w| {
+                    let app_id = w.app_id.clone()?;
+                    Some((app_id, w.title.clone()))
+                })
+                .collect();
+            let result = drift_core::sync::sync_windows_to_config(project, &running_windows, &self.terminal_name)
+                .map(|_| ());
+            self.report_persist_result(project, "auto-sync", result);
+        }
+    }
+
+    /// `defaults.auto_save_minutes`: persist every open project, so a
+    /// compositor crash loses at most one interval of layout changes even
+    /// in a workspace that never loses focus.
+    fn auto_save_workspaces(&mut self) {
+        for (ws_id, project) in self.workspaces_to_save() {
+            self.persist_workspace(&project, ws_id);
+        }
+    }
+
+    /// Project workspaces with windows on them, focused or not.
+    fn workspaces_to_save(&self) -> Vec<(u64, String)> {
+        let mut open: Vec<(u64, String)> = self.workspace_to_project.iter()
+            .filter(|(id, _)| self.windows.values().any(|w| w.workspace_id == Some(**id)))
+            .map(|(id, p)| (*id, p.clone()))
+            .collect();
+        open.sort();
+        open
     }
 
-    fn save_workspace_snapshot(&self, project: &str, ws_id: u64) {
-        let windows: Vec<drift_core::workspace::SavedWindow> = self.windows.values()
+    fn save_workspace_snapshot(&mut self, project: &str, ws_id: u64) {
+        let windows = self.snapshot_windows(project, ws_id);
+        let result = drift_core::workspace::write_snapshot(project, windows);
+        self.report_persist_result(project, "auto-save", result);
+    }
+
+    fn snapshot_windows(&self, project: &str, ws_id: u64) -> Vec<drift_core::workspace::SavedWindow> {
+        self.windows.values()
             .filter(|w| w.workspace_id == Some(ws_id))
             .map(|w| drift_core::workspace::SavedWindow {
                 app_id: w.app_id.clone(),
                 title: w.title.clone(),
-                config_name: drift_core::workspace::extract_config_name(
-                    w.title.as_deref(),
-                    project,
-                ),
+                config_name: match self.tracked_windows.get(&w.id) {
+                    Some(tracked) => Some(tracked.name.clone()),
+                    None => drift_core::workspace::extract_config_name(w.app_id.as_deref(), w.title.as_deref(), project),
+                },
                 width: Some(w.layout.tile_size.0),
                 height: Some(w.layout.tile_size.1),
                 column_index: w.layout.pos_in_scrolling_layout.map(|(col, _)| col),
+                tile_index: w.layout.pos_in_scrolling_layout.map(|(_, tile)| tile),
+                floating: w.is_floating,
+                focused: w.is_focused,
             })
-            .collect();
-        if let Err(e) = drift_core::workspace::write_snapshot(project, windows) {
-            eprintln!("auto-save workspace '{project}': {e}");
-        }
+            .collect()
+    }
+
+    /// Surface auto-save/auto-sync failures as `drift.warning` events. A
+    /// failure that keeps repeating is escalated to error level so it
+    /// notifies even when the project is in the background.
+    fn report_persist_result(&mut self, project: &str, operation: &'static str, result: anyhow::Result<()>) {
+        let key = (project.to_string(), operation);
+        let e = match result {
+            Ok(()) => {
+                self.persist_failures.remove(&key);
+                return;
+            }
+            Err(e) => e,
+        };
+        let failures = self.persist_failures.entry(key).or_insert(0);
+        *failures += 1;
+        let failures = *failures;
+        let level = if failures >= PERSIST_FAILURE_ESCALATION { "error" } else { "warning" };
+        self.process_event(Event::new("drift.warning", project, "daemon")
+            .level(level)
+            .title(format!("{operation} failed for '{project}'"))
+            .body(format!("{e:#}"))
+            .meta(serde_json::json!({
+                "operation": operation,
+                "consecutive_failures": failures,
+            })));
     }
 
     fn auto_close_project(&mut self, project_name: &str) {
@@ -371,23 +817,71 @@ impl DaemonInner {
             }
         }
 
-        drift_core::lifecycle::teardown_project(project_name);
+        drift_core::lifecycle::teardown_project(project_name, false);
 
         if let Ok(mut client) = drift_core::niri::NiriClient::connect() {
             let _ = client.unset_workspace_name(project_name);
         }
 
-        self.process_event(Event {
-            event_type: "drift.project.closed".into(),
-            project: project_name.to_string(),
-            source: "daemon".into(),
-            ts: events::iso_now(),
-            level: Some("info".into()),
-            title: Some(format!("Auto-closed project '{project_name}'")),
-            body: None,
-            meta: None,
-            priority: None,
-        });
+        self.process_event(Event::new("drift.project.closed", project_name, "daemon")
+            .level("info")
+            .title(format!("Auto-closed project '{project_name}'")));
+    }
+
+    /// Clean up after supervisors that died without shutting down. Their
+    /// pid and state files are removed, and services they left running are
+    /// stopped, or with `adopt` handed to a fresh supervisor.
+    fn reconcile_supervisors(&mut self, adopt: bool) {
+        let Ok(entries) = fs::read_dir(paths::state_base_dir()) else {
+            return;
+        };
+        // Stopped together at the end, so startup waits once for all of them
+        let mut stopping = Vec::new();
+        let mut cleared = Vec::new();
+        for entry in entries.flatten() {
+            if !entry.path().is_dir() {
+                continue;
+            }
+            let project = entry.file_name().to_string_lossy().into_owned();
+            let Some(leftovers) = drift_core::supervisor::find_leftovers(&project) else {
+                continue;
+            };
+            if leftovers.orphans.is_empty() {
+                if leftovers.stale_files {
+                    drift_core::supervisor::clear_stale_files(&project);
+                    eprintln!("removed stale supervisor files for '{project}'");
+                }
+                continue;
+            }
+
+            // The new supervisor picks the orphans up from services.json
+            let adopted = adopt
+                && config::load_project_config(&project).is_ok()
+                && match spawn_supervisor(&project) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        eprintln!("could not restart supervisor for '{project}': {e}");
+                        false
+                    }
+                };
+            if !adopted {
+                stopping.extend(leftovers.orphans.iter().cloned());
+                cleared.push(project.clone());
+            }
+
+            let action = if adopted { "adopted" } else { "killed" };
+            for orphan in leftovers.orphans {
+                self.process_event(Event::new("service.orphaned", project.clone(), orphan.service.clone())
+                    .level("warning")
+                    .title(format!("Service '{}' outlived its supervisor ({action})", orphan.service))
+                    .meta(serde_json::json!({ "pid": orphan.pid, "action": action })));
+            }
+        }
+
+        drift_core::supervisor::terminate_orphans(&stopping);
+        for project in cleared {
+            drift_core::supervisor::clear_stale_files(&project);
+        }
     }
 
     #[cfg(feature = "dispatch")]
@@ -452,6 +946,10 @@ impl DaemonInner {
             buffer.pop_front();
         }
 
+        if self.log_events {
+            let _ = events::append_to_log(&event);
+        }
+        self.metrics.record_event(&event.event_type);
         let _ = self.subscriber_tx.send(event.clone());
         #[cfg(feature = "dispatch")]
         let _ = self.dispatch_tx.send(event.clone());
@@ -459,21 +957,134 @@ impl DaemonInner {
         if matches!(priority, "critical" | "high" | "medium") {
             self.send_desktop_notification(&event);
         }
+        self.send_webhooks(&event);
+        self.sinks.publish(&event);
+        if let Some(hooks) = &mut self.hooks {
+            hooks.dispatch(&event);
+        }
 
         #[cfg(feature = "dispatch")]
         match event.event_type.as_str() {
             "task.running" | "task.completed" | "task.failed" | "task.needs_review"
-            | "task.queued" | "agent.completed" | "agent.error" | "service.crashed" => {
-                if !event.project.is_empty() {
-                    self.update_workspace_name(&event.project);
-                }
+            | "task.queued" | "agent.completed" | "agent.error" | "service.crashed"
+                if !event.project.is_empty() =>
+            {
+                self.update_workspace_name(&event.project);
             }
             _ => {}
         }
     }
 
+    /// Fold a repeat of a recently emitted event into the buffered original,
+    /// bumping `meta.count` there instead of notifying everyone again. True
+    /// when the event was absorbed. A window starts at the first occurrence,
+    /// so a steady stream still gets through once per window.
+    fn coalesce(&mut self, event: &Event, now: Instant) -> bool {
+        if self.dedup_window.is_none() {
+            return false;
+        }
+        self.close_dedup_windows(now);
+        let key = (event.project.clone(), event.event_type.clone(), event.title.clone());
+        let Some(&(opened, repeats)) = self.dedup_seen.get(&key) else {
+            self.dedup_seen.insert(key, (now, 0));
+            return false;
+        };
+        let original = self.events.get_mut(&event.project).and_then(|buffer| {
+            buffer
+                .iter_mut()
+                .rev()
+                .find(|e| e.event_type == event.event_type && e.title == event.title)
+        });
+        let Some(original) = original else {
+            // Pushed out of the buffer already; start a new window
+            self.dedup_seen.insert(key, (now, 0));
+            return false;
+        };
+        let meta = original.meta.get_or_insert_with(|| serde_json::json!({}));
+        let Some(meta) = meta.as_object_mut() else {
+            return false;
+        };
+        meta.insert("count".into(), (repeats + 2).into());
+        original.ts = event.ts.clone();
+        self.dedup_seen.insert(key, (opened, repeats + 1));
+        true
+    }
+
+    /// End dedup windows older than `events.dedup_secs`. A window that
+    /// absorbed repeats sends its original again, now carrying the final
+    /// `meta.count`, to subscribers and the event log, so they see how many
+    /// times it fired.
+    /// When the next dedup window with folded repeats closes.
+    fn next_dedup_close(&self) -> Option<Instant> {
+        let window = self.dedup_window?;
+        self.dedup_seen.values().filter(|(_, repeats)| *repeats > 0).map(|(opened, _)| *opened + window).min()
+    }
+
+    fn close_dedup_windows(&mut self, now: Instant) {
+        let Some(window) = self.dedup_window else {
+            return;
+        };
+        let mut closed = Vec::new();
+        self.dedup_seen.retain(|key, (opened, repeats)| {
+            let open = now.duration_since(*opened) < window;
+            if !open && *repeats > 0 {
+                closed.push(key.clone());
+            }
+            open
+        });
+        for (project, event_type, title) in closed {
+            let original = self.events.get(&project).and_then(|buffer| {
+                buffer.iter().rev().find(|e| e.event_type == event_type && e.title == title)
+            });
+            if let Some(original) = original {
+                if self.log_events {
+                    let _ = events::append_to_log(original);
+                }
+                let _ = self.subscriber_tx.send(original.clone());
+            }
+        }
+    }
+
+    /// Entry point for events from the emit socket and spool. Repeats and
+    /// rate limits only apply here: drift's own events always go through.
     fn handle_emit_event(&mut self, event: Event) {
-        self.process_event(event);
+        if self.coalesce(&event, Instant::now()) {
+            self.metrics.record_event(&event.event_type);
+            return;
+        }
+        self.report_rate_limit_recoveries();
+        match self.rate_limits.check(&event.source) {
+            Verdict::Allow => self.process_event(event),
+            Verdict::Drop { first: false } => {}
+            Verdict::Drop { first: true } => {
+                let limit = self.rate_limits.spec(&event.source).unwrap_or_default().to_string();
+                self.process_event(Event::new("events.rate_limited", event.project.clone(), "daemon")
+                    .level("warning")
+                    .title(format!("Dropping events from '{}' (limit {limit})", event.source))
+                    .meta(serde_json::json!({
+                        "source": event.source,
+                        "limit": limit,
+                    })));
+            }
+        }
+    }
+
+    /// One notice per source that was limited and has refilled, with how
+    /// many of its events were dropped in between.
+    fn report_rate_limit_recoveries(&mut self) {
+        for recovered in self.rate_limits.recovered() {
+            self.process_event(Event::new("events.rate_limit_ended", "", "daemon")
+                .level("info")
+                .title(format!(
+                    "Dropped {} events from '{}' (limit {})",
+                    recovered.dropped, recovered.source, recovered.spec
+                ))
+                .meta(serde_json::json!({
+                    "source": recovered.source,
+                    "limit": recovered.spec,
+                    "dropped": recovered.dropped,
+                })));
+        }
     }
 
     fn send_desktop_notification(&self, event: &Event) {
@@ -485,7 +1096,10 @@ impl DaemonInner {
             _ => return,
         };
         let title_text = event.title.as_deref().unwrap_or("");
-        let title = format!("[{}] {}", event.project, title_text);
+        let project = config::load_project_config(&event.project)
+            .map(|cfg| cfg.project.display_name())
+            .unwrap_or_else(|_| event.project.clone());
+        let title = format!("[{project}] {title_text}");
         let body = event.body.as_deref().unwrap_or("");
 
         let _ = std::process::Command::new("notify-send")
@@ -501,7 +1115,34 @@ impl DaemonInner {
             .spawn();
     }
 
-    fn write_state_to_disk(&self) {
+    fn send_webhooks(&self, event: &Event) {
+        let urls = self.webhooks.urls_for(&event.event_type, &event.project);
+        if urls.is_empty() {
+            return;
+        }
+        let Ok(body) = serde_json::to_string(event) else {
+            return;
+        };
+        // The body goes over stdin: an `@` at its start would make curl read a file
+        for url in urls {
+            let args = [
+                "-fsS",
+                "--max-time",
+                "5",
+                "-X",
+                "POST",
+                "-H",
+                "Content-Type: application/json",
+                "--data-binary",
+                "@-",
+                url,
+            ]
+            .map(String::from);
+            crate::sinks::spawn_reaped("curl", &args, Some(body.clone()), None);
+        }
+    }
+
+    fn write_state_to_disk(&mut self) {
         let state = DaemonState {
             pid: std::process::id(),
             active_project: self.active_project.clone(),
@@ -550,6 +1191,18 @@ impl DaemonInner {
                 active_project: self.active_project.clone(),
                 niri_workspace_id: self.focused_workspace_id,
             },
+            commander: self.commander.as_ref().map(CommanderWatch::state),
+            subscribers: self.metrics.subscriber_stats(),
+            tracked_windows: {
+                let mut tracked: Vec<TrackedWindow> = self.tracked_windows.values().cloned().collect();
+                tracked.sort_by_key(|w| w.window_id);
+                tracked
+            },
+            suspended_projects: {
+                let mut suspended: Vec<String> = self.suspended_projects.iter().cloned().collect();
+                suspended.sort();
+                suspended
+            },
         };
 
         let path = paths::daemon_state_path();
@@ -558,71 +1211,189 @@ impl DaemonInner {
         }
         let tmp = path.with_extension("json.tmp");
         if let Ok(json) = serde_json::to_string_pretty(&state) {
+            if json == self.last_state_json && path.exists() {
+                return;
+            }
             let _ = fs::write(&tmp, &json);
             let _ = fs::rename(&tmp, &path);
+            self.last_state_json = json;
         }
     }
 }
 
-fn spawn_commander() -> Option<u32> {
-    let pid_path = paths::commander_pid_path();
-    // Check if already running
-    if pid_path.exists() {
-        if let Ok(pid_str) = fs::read_to_string(&pid_path) {
-            if let Ok(pid) = pid_str.trim().parse::<i32>() {
-                if nix::sys::signal::kill(Pid::from_raw(pid), None).is_ok() {
-                    eprintln!("commander already running (PID {pid})");
-                    return Some(pid as u32);
-                }
-            }
+/// A periodic task in the main loop.
+struct Every {
+    interval: Duration,
+    last: Instant,
+}
+
+impl Every {
+    fn new(interval: Duration) -> Self {
+        Every { interval, last: Instant::now() }
+    }
+
+    /// Due on the first pass through the loop.
+    fn due_now(interval: Duration) -> Self {
+        Every { interval, last: Instant::now() - interval }
+    }
+
+    fn deadline(&self) -> Instant {
+        self.last + self.interval
+    }
+
+    /// Whether the task should run at `now`; restarts the interval if so.
+    fn due(&mut self, now: Instant) -> bool {
+        if now < self.deadline() {
+            return false;
         }
+        self.last = now;
+        true
     }
+}
+
+/// Workspaces added, removed or changed (name, output, focus) between the cache and a snapshot.
+fn stale_workspace_count(cached: &HashMap<u64, Workspace>, fresh: &[Workspace]) -> usize {
+    let changed = fresh
+        .iter()
+        .filter(|ws| {
+            cached.get(&ws.id).is_none_or(|c| {
+                c.name != ws.name
+                    || c.output != ws.output
+                    || c.is_active != ws.is_active
+                    || c.is_focused != ws.is_focused
+            })
+        })
+        .count();
+    let removed = cached.keys().filter(|id| !fresh.iter().any(|ws| ws.id == **id)).count();
+    changed + removed
+}
 
-    let log_path = paths::state_base_dir().join("commander.log");
-    let log_file = match fs::OpenOptions::new().create(true).append(true).open(&log_path) {
-        Ok(f) => f,
+/// Windows added, removed or changed (workspace, title, app id) between the cache and a snapshot.
+fn stale_window_count(cached: &HashMap<u64, Window>, fresh: &[Window]) -> usize {
+    let changed = fresh
+        .iter()
+        .filter(|w| {
+            cached.get(&w.id).is_none_or(|c| {
+                c.workspace_id != w.workspace_id || c.title != w.title || c.app_id != w.app_id
+            })
+        })
+        .count();
+    let removed = cached.keys().filter(|id| !fresh.iter().any(|w| w.id == **id)).count();
+    changed + removed
+}
+
+/// Reopen the projects recorded in the last session (`drift resume`).
+/// Runs as a child process so the daemon keeps handling niri events while
+/// workspaces are being created.
+fn spawn_resume() {
+    let drift_bin = match std::env::current_exe() {
+        Ok(p) => p,
         Err(e) => {
-            eprintln!("commander: cannot open log: {e}");
-            return None;
+            eprintln!("autostart: cannot determine drift binary: {e}");
+            return;
         }
     };
-    let stderr_file = match log_file.try_clone() {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("commander: cannot clone log fd: {e}");
-            return None;
-        }
+    let log_path = paths::state_base_dir().join("resume.log");
+    let (stdout, stderr) = match fs::OpenOptions::new().create(true).append(true).open(&log_path) {
+        Ok(f) => match f.try_clone() {
+            Ok(f2) => (Stdio::from(f), Stdio::from(f2)),
+            Err(_) => (Stdio::null(), Stdio::null()),
+        },
+        Err(_) => (Stdio::null(), Stdio::null()),
     };
 
-    match std::process::Command::new("drift-commander")
-        .stdout(log_file)
-        .stderr(stderr_file)
+    match std::process::Command::new(drift_bin)
+        .arg("resume")
+        .stdout(stdout)
+        .stderr(stderr)
         .stdin(Stdio::null())
         .spawn()
     {
-        Ok(child) => {
-            let pid = child.id();
-            eprintln!("commander spawned (PID {pid})");
-            Some(pid)
+        Ok(child) => eprintln!("autostart: resuming session (PID {})", child.id()),
+        Err(e) => eprintln!("autostart: failed to spawn drift resume: {e}"),
+    }
+}
+
+/// Start `drift _supervisor` for a project, logging to its supervisor.log.
+fn spawn_supervisor(project: &str) -> anyhow::Result<()> {
+    let drift_bin = std::env::current_exe()?;
+    let logs_dir = paths::logs_dir(project);
+    fs::create_dir_all(&logs_dir)?;
+    let log_file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(logs_dir.join("supervisor.log"))?;
+    let stderr_file = log_file.try_clone()?;
+    std::process::Command::new(drift_bin)
+        .args(["_supervisor", project])
+        .stdout(log_file)
+        .stderr(stderr_file)
+        .stdin(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+fn refresh_stale_niri_rules() {
+    if !drift_core::kdl::niri_rules_stale() {
+        return;
+    }
+    // Re-read the global config too: it may be what changed
+    let result = config::load_global_config().and_then(|global| {
+        let projects = drift_core::registry::list_projects()?;
+        drift_core::kdl::write_niri_rules(&projects, &global)
+    });
+    match result {
+        Ok(true) => eprintln!("regenerated {}", paths::niri_rules_path().display()),
+        Ok(false) => {}
+        Err(e) => eprintln!("niri rules regeneration failed: {e}"),
+    }
+}
+
+/// Returns whether there was anything to replay.
+fn replay_spooled_events(inner: &mut DaemonInner) -> bool {
+    let events = events::drain_spool();
+    if events.is_empty() {
+        return false;
+    }
+    eprintln!("replaying {} spooled event(s)", events.len());
+    for event in events {
+        inner.handle_emit_event(event);
+    }
+    true
+}
+
+fn prune_event_log(retention_days: u32) {
+    match events::prune_event_log(retention_days) {
+        Ok(removed) if !removed.is_empty() => {
+            eprintln!("pruned {} event log archive(s)", removed.len());
         }
-        Err(e) => {
-            eprintln!("commander: failed to spawn: {e}");
-            None
+        Ok(_) => {}
+        Err(e) => eprintln!("event log prune failed: {e}"),
+    }
+    match events::prune_attachments(retention_days) {
+        Ok(removed) if !removed.is_empty() => {
+            eprintln!("pruned {} event attachment(s)", removed.len());
         }
+        Ok(_) => {}
+        Err(e) => eprintln!("attachment prune failed: {e}"),
     }
 }
 
-fn stop_commander() {
-    let pid_path = paths::commander_pid_path();
-    if let Ok(pid_str) = fs::read_to_string(&pid_path) {
-        if let Ok(pid) = pid_str.trim().parse::<i32>() {
-            let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
+fn prune_trash(retention_days: u32) {
+    match drift_core::trash::prune(retention_days) {
+        Ok(removed) if !removed.is_empty() => {
+            eprintln!("pruned {} deleted project(s) from the trash", removed.len());
         }
+        Ok(_) => {}
+        Err(e) => eprintln!("trash prune failed: {e}"),
     }
 }
 
-pub fn run_daemon() -> anyhow::Result<()> {
-    install_signal_handlers();
+pub fn run_daemon(autostart: bool) -> anyhow::Result<()> {
+    let (msg_tx, msg_rx) = mpsc::channel::<DaemonMsg>();
+    // Before any other thread starts, so they all inherit the blocked mask
+    spawn_signal_thread(msg_tx.clone())?;
+    let shutdown = Shutdown::new();
 
     let global_config = config::load_global_config().unwrap_or_default();
     let commander_enabled = global_config.commander.enabled;
@@ -637,7 +1408,6 @@ pub fn run_daemon() -> anyhow::Result<()> {
     }
     fs::write(&pid_path, std::process::id().to_string())?;
 
-    let (msg_tx, msg_rx) = mpsc::channel::<DaemonMsg>();
     let (sub_tx, sub_rx) = mpsc::channel::<Event>();
     #[cfg(feature = "dispatch")]
     let (dispatch_tx, dispatch_rx) = mpsc::channel::<Event>();
@@ -647,30 +1417,96 @@ pub fn run_daemon() -> anyhow::Result<()> {
     let mut inner = DaemonInner::new(sub_tx, dispatch_tx, events_config.buffer_size, terminal_name, global_persist_windows);
     #[cfg(not(feature = "dispatch"))]
     let mut inner = DaemonInner::new(sub_tx, events_config.buffer_size, terminal_name, global_persist_windows);
+    inner.webhooks = global_config.webhooks.clone();
+    inner.sinks = crate::sinks::Sinks::from_config(&global_config.sinks);
+    inner.hooks = Some(crate::hooks::Hooks::new(global_config.hooks.clone(), msg_tx.clone()));
+    // Windows that retitled themselves before this daemon started, and
+    // projects it froze, are only known from the last one's state
+    if let Ok(previous) = drift_client::daemon_state() {
+        inner.tracked_windows = previous.tracked_windows.into_iter().map(|w| (w.window_id, w)).collect();
+        inner.suspended_projects = previous.suspended_projects.into_iter().collect();
+    }
+    inner.idle_suspend_after = global_config
+        .defaults
+        .idle_suspend_minutes
+        .map(|m| Duration::from_secs(m * 60));
+    inner.rate_limits = crate::rate_limit::RateLimits::from_config(&events_config.limits);
+    inner.dedup_window = (events_config.dedup_secs > 0).then(|| Duration::from_secs(events_config.dedup_secs));
+
+    let heartbeat_interval = (events_config.heartbeat_secs > 0)
+        .then(|| Duration::from_secs(events_config.heartbeat_secs));
+    // Workspace tracking only speaks niri's IPC; retrying its socket on
+    // Hyprland would just log a reconnect every few seconds
+    let track_niri = !matches!(drift_core::compositor::backend(), Ok(drift_core::compositor::Backend::Hyprland));
+    if !track_niri {
+        eprintln!(
+            "Hyprland session: workspace tracking needs niri, so focus time, idle suspend, auto-save and \
+             window sync are off; events, hooks, sinks and services work as usual"
+        );
+    }
+    let poll_thread = match heartbeat_interval.filter(|_| track_niri) {
+        Some(interval) => {
+            let (tx, shutdown) = (msg_tx.clone(), shutdown.clone());
+            Some(thread::Builder::new()
+                .name("state-poll".into())
+                .spawn(move || crate::event_stream::run_state_poll(tx, shutdown, interval))?)
+        }
+        None => None,
+    };
 
-    let tx_events = msg_tx.clone();
-    let event_thread = thread::Builder::new()
-        .name("event-stream".into())
-        .spawn(move || crate::event_stream::run_event_stream(tx_events, &SHUTDOWN))?;
+    let event_thread = if track_niri {
+        let (tx_events, event_shutdown) = (msg_tx.clone(), shutdown.clone());
+        Some(thread::Builder::new()
+            .name("event-stream".into())
+            .spawn(move || crate::event_stream::run_event_stream(tx_events, event_shutdown))?)
+    } else {
+        None
+    };
 
     #[cfg(any(feature = "drivers-claude", feature = "drivers-codex"))]
     let msg_tx_driver = msg_tx.clone();
 
-    let tx_emit = msg_tx;
+    let focus_idle_after = Duration::from_secs(u64::from(global_config.defaults.focus_idle_minutes) * 60);
+    let mut idle_watch = if focus_idle_after.is_zero() {
+        None
+    } else {
+        crate::idle::IdleWatch::start(focus_idle_after, msg_tx.clone())
+    };
+
+    // Not joined on shutdown: a fetch in flight can take up to
+    // `repo_status::FETCH_TIMEOUT`, and the thread holds nothing to flush
+    if let Some(minutes) = global_config.defaults.git_poll_minutes.filter(|m| *m > 0) {
+        let (tx, shutdown) = (msg_tx.clone(), shutdown.clone());
+        thread::Builder::new()
+            .name("git-poll".into())
+            .spawn(move || crate::git_poll::run_git_poll(tx, shutdown, Duration::from_secs(minutes * 60)))?;
+    }
+
+    let (tx_emit, emit_shutdown) = (msg_tx, shutdown.clone());
     let emit_thread = thread::Builder::new()
         .name("emit-listener".into())
-        .spawn(move || crate::emit_listener::run_emit_listener(tx_emit, &SHUTDOWN))?;
+        .spawn(move || crate::emit_listener::run_emit_listener(tx_emit, emit_shutdown))?;
 
     let replay_count = events_config.replay_on_subscribe;
+    let (sub_metrics, sub_shutdown) = (inner.metrics.clone(), shutdown.clone());
     let subscriber_thread = thread::Builder::new()
         .name("subscriber-manager".into())
-        .spawn(move || crate::subscriber::run_subscriber_manager(sub_rx, &SHUTDOWN, replay_count))?;
+        .spawn(move || crate::subscriber::run_subscriber_manager(sub_rx, sub_shutdown, replay_count, sub_metrics))?;
+
+    let metrics_thread = if global_config.metrics.listen.is_some() {
+        let (config, metrics, shutdown) = (global_config.metrics.clone(), inner.metrics.clone(), shutdown.clone());
+        Some(thread::Builder::new()
+            .name("metrics".into())
+            .spawn(move || crate::metrics::run_metrics_listener(config, metrics, shutdown))?)
+    } else {
+        None
+    };
 
     #[cfg(feature = "dispatch")]
     let dispatch_thread = if dispatch_enabled {
         Some(thread::Builder::new()
             .name("dispatch-watcher".into())
-            .spawn(move || run_dispatch_watcher(dispatch_rx, &SHUTDOWN))?)
+            .spawn(move || run_dispatch_watcher(dispatch_rx))?)
     } else {
         drop(dispatch_rx);
         None
@@ -682,53 +1518,185 @@ pub fn run_daemon() -> anyhow::Result<()> {
         if enabled_drivers.is_empty() {
             None
         } else {
-            let tx = msg_tx_driver;
+            let (tx, shutdown) = (msg_tx_driver, shutdown.clone());
             Some(thread::Builder::new()
                 .name("driver-poll".into())
-                .spawn(move || run_driver_poll(tx, &SHUTDOWN, enabled_drivers))?)
+                .spawn(move || run_driver_poll(tx, shutdown, enabled_drivers))?)
         }
     };
 
     if commander_enabled {
-        spawn_commander();
+        inner.commander = Some(CommanderWatch::start());
     }
 
     eprintln!("drift daemon started (PID {})", std::process::id());
+    inner.reconcile_supervisors(global_config.defaults.adopt_orphans);
+
+    // Events emitted while no daemon was listening, e.g. by a supervisor
+    // that outlived the last one
+    replay_spooled_events(&mut inner);
 
     let mut last_state_write = Instant::now();
     inner.write_state_to_disk();
-
-    while !SHUTDOWN.load(Ordering::Relaxed) {
-        match msg_rx.recv_timeout(Duration::from_millis(500)) {
+    // Set by anything that changes what daemon.json shows; the write
+    // follows at most STATE_WRITE_INTERVAL later
+    let mut state_dirty = false;
+
+    let retention_days = events_config.retention_days;
+    prune_event_log(retention_days);
+    let trash_retention_days = global_config.defaults.trash_retention_days;
+    prune_trash(trash_retention_days);
+    let mut prune = Every::new(EVENT_LOG_PRUNE_INTERVAL);
+    let mut spool_replay = Every::new(SPOOL_REPLAY_INTERVAL);
+    let mut idle_check = inner.idle_suspend_after.map(|_| Every::new(IDLE_CHECK_INTERVAL));
+    // Catches project configs edited by hand or by commands that don't regenerate
+    let mut rules_check = global_config.defaults.auto_niri_rules.then(|| Every::due_now(NIRI_RULES_CHECK_INTERVAL));
+    let mut heartbeat = heartbeat_interval.map(Every::new);
+    let mut focus_credit = Every::new(FOCUS_TIME_INTERVAL);
+    let mut auto_save = global_config
+        .defaults
+        .auto_save_minutes
+        .filter(|m| *m > 0)
+        .map(|m| Every::new(Duration::from_secs(m * 60)));
+    let mut commander_poll = Every::new(COMMANDER_POLL_INTERVAL);
+
+    loop {
+        // Sleep until the earliest pending deadline; with nothing pending
+        // only a message wakes the loop
+        let deadline = [
+            Some(prune.deadline()),
+            Some(spool_replay.deadline()),
+            idle_check.as_ref().map(Every::deadline),
+            rules_check.as_ref().map(Every::deadline),
+            heartbeat.as_ref().map(Every::deadline),
+            auto_save.as_ref().map(Every::deadline),
+            inner.focus_since.is_some().then(|| focus_credit.deadline()),
+            inner.commander.is_some().then(|| commander_poll.deadline()),
+            inner.next_dedup_close(),
+            inner.rate_limits.next_recovery(),
+            state_dirty.then(|| last_state_write + STATE_WRITE_INTERVAL),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let received = match deadline {
+            Some(deadline) => msg_rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => msg_rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+        };
+        // Every message can change what daemon.json shows
+        state_dirty |= received.is_ok();
+        match received {
             Ok(DaemonMsg::NiriEvent(event)) => {
+                inner.last_niri_event = Instant::now();
                 inner.handle_niri_event(event);
             }
             Ok(DaemonMsg::EmitEvent(event)) => {
                 inner.handle_emit_event(event);
             }
+            Ok(DaemonMsg::NiriConnected) => {
+                if autostart {
+                    spawn_resume();
+                }
+            }
+            Ok(DaemonMsg::NiriDisconnected) => {
+                inner.niri_disconnected();
+            }
+            Ok(DaemonMsg::NiriReconnected { down_for, workspaces, windows }) => {
+                inner.niri_reconnected(down_for, workspaces, windows);
+            }
+            Ok(DaemonMsg::NiriSnapshot { taken, workspaces, windows }) => {
+                inner.reconcile(taken, workspaces, windows);
+            }
+            Ok(DaemonMsg::UserIdle(idle)) => {
+                inner.user_idle_changed(idle, focus_idle_after);
+            }
+            Ok(DaemonMsg::RepoStatus { project, status }) => {
+                inner.repo_status_changed(project, status);
+            }
+            Ok(DaemonMsg::Shutdown) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
             Err(mpsc::RecvTimeoutError::Timeout) => {}
-            Err(mpsc::RecvTimeoutError::Disconnected) => break,
         }
 
-        if last_state_write.elapsed() >= STATE_WRITE_INTERVAL {
+        let now = Instant::now();
+        if prune.due(now) {
+            prune_event_log(retention_days);
+            prune_trash(trash_retention_days);
+        }
+
+        if spool_replay.due(now) && replay_spooled_events(&mut inner) {
+            state_dirty = true;
+        }
+
+        if idle_check.as_mut().is_some_and(|t| t.due(now)) {
+            inner.check_idle_projects();
+            state_dirty = true;
+        }
+
+        if focus_credit.due(now) {
+            inner.credit_focus(inner.active_project.clone());
+        }
+
+        if rules_check.as_mut().is_some_and(|t| t.due(now)) {
+            refresh_stale_niri_rules();
+        }
+
+        if auto_save.as_mut().is_some_and(|t| t.due(now)) {
+            inner.auto_save_workspaces();
+        }
+
+        if heartbeat.as_mut().is_some_and(|t| t.due(now)) {
+            inner.send_heartbeat();
+            // Subscriber counts in daemon.json change without a message
+            state_dirty = true;
+        }
+
+        inner.close_dedup_windows(now);
+        if inner.rate_limits.next_recovery().is_some_and(|at| at <= now) {
+            inner.report_rate_limit_recoveries();
+            state_dirty = true;
+        }
+        if inner.commander.is_some() && commander_poll.due(now) && inner.check_commander() {
+            state_dirty = true;
+        }
+
+        if state_dirty && last_state_write.elapsed() >= STATE_WRITE_INTERVAL {
             inner.write_state_to_disk();
             last_state_write = Instant::now();
+            state_dirty = false;
         }
     }
 
+    // Also reached when every sender is gone; either way, release the
+    // threads blocked in accept or sleeping until their next poll
+    shutdown.trigger();
+
+    inner.credit_focus(None);
     inner.write_state_to_disk();
     let _ = fs::remove_file(&pid_path);
 
-    if commander_enabled {
-        stop_commander();
+    if let Some(commander) = inner.commander.as_mut() {
+        commander.stop();
+    }
+    if let Some(watch) = idle_watch.as_mut() {
+        watch.stop();
     }
 
     eprintln!("drift daemon shutting down");
 
+    // Dropping `inner` closes the subscriber and dispatch channels
+    drop(inner);
     drop(msg_rx);
-    let _ = event_thread.join();
+    if let Some(t) = event_thread {
+        let _ = t.join();
+    }
     let _ = emit_thread.join();
     let _ = subscriber_thread.join();
+    if let Some(t) = poll_thread {
+        let _ = t.join();
+    }
+    if let Some(t) = metrics_thread {
+        let _ = t.join();
+    }
     #[cfg(feature = "dispatch")]
     if let Some(t) = dispatch_thread {
         let _ = t.join();
@@ -745,7 +1713,7 @@ pub fn run_daemon() -> anyhow::Result<()> {
 #[cfg(any(feature = "drivers-claude", feature = "drivers-codex"))]
 fn run_driver_poll(
     tx: mpsc::Sender<DaemonMsg>,
-    shutdown: &AtomicBool,
+    shutdown: Shutdown,
     enabled_drivers: Vec<String>,
 ) {
     let interval = Duration::from_millis(1500);
@@ -770,7 +1738,7 @@ fn run_driver_poll(
 
     eprintln!("driver-poll started ({} drivers)", drivers.len());
 
-    while !shutdown.load(Ordering::Relaxed) {
+    while !shutdown.is_triggered() {
         let projects = drift_core::registry::list_projects().unwrap_or_default();
         for proj in &projects {
             let cwd = match config::resolve_repo_path(&proj.project.repo) {
@@ -826,48 +1794,31 @@ fn run_driver_poll(
                     _ => "info",
                 };
 
-                let _ = tx.send(DaemonMsg::EmitEvent(Event {
-                    event_type: event_type.into(),
-                    project: proj.project.name.clone(),
-                    source: "driver".into(),
-                    ts: events::iso_now(),
-                    level: Some(level.into()),
-                    title: None,
-                    body: None,
-                    meta: Some(serde_json::json!({
+                let _ = tx.send(DaemonMsg::EmitEvent(Event::new(event_type, proj.project.name.clone(), "driver")
+                    .level(level)
+                    .meta(serde_json::json!({
                         "agent_name": svc.name,
                         "driver": driver_name,
-                    })),
-                    priority: None,
-                }));
+                    }))));
 
                 last_states.insert(key, state);
             }
         }
 
-        let mut slept = Duration::ZERO;
-        while slept < interval && !shutdown.load(Ordering::Relaxed) {
-            thread::sleep(Duration::from_millis(100));
-            slept += Duration::from_millis(100);
-        }
+        shutdown.sleep(interval);
     }
 
     eprintln!("driver-poll shutting down");
 }
 
 #[cfg(feature = "dispatch")]
-fn run_dispatch_watcher(rx: mpsc::Receiver<Event>, shutdown: &AtomicBool) {
+/// Runs until the daemon drops its end of the channel on shutdown.
+fn run_dispatch_watcher(rx: mpsc::Receiver<Event>) {
     let min_interval = Duration::from_secs(10);
     let max_tracked_projects = 100;
     let mut last_dispatch: HashMap<String, Instant> = HashMap::new();
 
-    while !shutdown.load(Ordering::Relaxed) {
-        let event = match rx.recv_timeout(Duration::from_millis(500)) {
-            Ok(e) => e,
-            Err(mpsc::RecvTimeoutError::Timeout) => continue,
-            Err(mpsc::RecvTimeoutError::Disconnected) => break,
-        };
-
+    for event in rx {
         match event.event_type.as_str() {
             "task.completed" | "task.queued" => {}
             _ => continue,
@@ -961,12 +1912,36 @@ mod tests {
             windows: HashMap::new(),
             workspace_to_project: HashMap::new(),
             known_projects: HashSet::new(),
+            window_matchers: Vec::new(),
+            rule_candidates: HashMap::new(),
+            tracked_windows: HashMap::new(),
             active_project: None,
             focused_workspace_id: None,
             events: HashMap::new(),
             buffer_size: 200,
             terminal_name: "ghostty".into(),
             global_persist_windows: false,
+            webhooks: Default::default(),
+            sinks: Default::default(),
+            hooks: None,
+            log_events: false,
+            idle_suspend_after: None,
+            request_suspend: drift_core::supervisor::request_suspend,
+            dedup_window: None,
+            dedup_seen: HashMap::new(),
+            rate_limits: Default::default(),
+            last_focused: HashMap::new(),
+            focus_since: None,
+            user_idle: false,
+            repo_behind: HashMap::new(),
+            last_state_json: String::new(),
+            suspended_projects: HashSet::new(),
+            persist_failures: HashMap::new(),
+            started: Instant::now(),
+            last_niri_event: Instant::now(),
+            niri_lost: None,
+            metrics: Metrics::new(),
+            commander: None,
             subscriber_tx: sub_tx,
             #[cfg(feature = "dispatch")]
             dispatch_tx,
@@ -975,18 +1950,102 @@ mod tests {
 
     fn test_event(project: &str, level: &str) -> Event {
         Event {
-            event_type: "test".into(),
-            project: project.into(),
-            source: "test".into(),
             ts: "2026-01-01T00:00:00Z".into(),
-            level: Some(level.into()),
-            title: None,
-            body: None,
-            meta: None,
-            priority: None,
+            ..Event::new("test", project, "test").level(level)
         }
     }
 
+    fn test_window(id: u64, workspace_id: u64, app_id: &str, title: &str) -> Window {
+        Window {
+            id,
+            title: Some(title.into()),
+            app_id: Some(app_id.into()),
+            pid: Some(100 + id as i32),
+            workspace_id: Some(workspace_id),
+            is_focused: false,
+            is_floating: false,
+            is_urgent: false,
+            layout: niri_ipc::WindowLayout {
+                pos_in_scrolling_layout: None,
+                tile_size: (0.0, 0.0),
+                window_size: (0, 0),
+                tile_pos_in_workspace_view: None,
+                window_offset_in_tile: (0.0, 0.0),
+            },
+            focus_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn window_rules_leave_owned_windows_alone() {
+        let mut inner = test_inner();
+        let mut db: config::ProjectConfig =
+            serde_json::from_value(serde_json::json!({ "project": { "name": "db", "repo": "/tmp" } })).unwrap();
+        db.window_rules = vec![config::WindowRule { app_id: Some("dbeaver".into()), ..Default::default() }];
+        inner.window_matchers = drift_core::window_rules::compile(&[db]);
+        inner.workspace_to_project.insert(1, "api".into());
+        inner.tracked_windows.insert(
+            3,
+            TrackedWindow { window_id: 3, project: "web".into(), name: "db".into(), pid: Some(103) },
+        );
+
+        // On a project's workspace, tied to a [[windows]] entry, and unowned
+        for (id, ws) in [(2, 1), (3, 9), (4, 9)] {
+            inner.handle_niri_event(NiriEvent::WindowOpenedOrChanged { window: test_window(id, ws, "dbeaver", "DBeaver") });
+        }
+
+        let claimed: Vec<_> = inner
+            .events
+            .values()
+            .flatten()
+            .filter(|e| e.event_type == "window.claimed")
+            .map(|e| e.meta.as_ref().unwrap()["window_id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(claimed, [4]);
+        assert!(inner.rule_candidates.is_empty());
+    }
+
+    #[test]
+    fn retitled_window_keeps_its_tie_until_its_pid_changes() {
+        let mut inner = test_inner();
+        inner.known_projects.insert("web".into());
+        let window = test_window(5, 1, "com.mitchellh.ghostty", "drift:web/editor");
+        inner.handle_niri_event(NiriEvent::WindowOpenedOrChanged { window: window.clone() });
+        let retitled = Window { title: Some("nvim src/main.rs".into()), ..window.clone() };
+        inner.handle_niri_event(NiriEvent::WindowOpenedOrChanged { window: retitled.clone() });
+        assert_eq!(inner.tracked_windows[&5].name, "editor");
+
+        // niri restarted and handed the id to another process
+        let reused = Window { pid: Some(999), ..retitled };
+        inner.handle_niri_event(NiriEvent::WindowsChanged { windows: vec![reused] });
+        assert!(inner.tracked_windows.is_empty());
+    }
+
+    #[test]
+    fn auto_save_covers_unfocused_workspaces_with_windows() {
+        let mut inner = test_inner();
+        inner.workspace_to_project.insert(1, "web".into());
+        inner.workspace_to_project.insert(2, "api".into());
+        inner.focused_workspace_id = Some(3);
+        inner.tracked_windows.insert(
+            5,
+            TrackedWindow { window_id: 5, project: "web".into(), name: "editor".into(), pid: Some(105) },
+        );
+        for window in [
+            test_window(5, 1, "com.mitchellh.ghostty", "nvim"),
+            test_window(6, 1, "firefox", "drift:web/docs"),
+            test_window(7, 3, "foot", "scratch"),
+        ] {
+            inner.windows.insert(window.id, window);
+        }
+
+        // api's workspace is empty, e.g. right after niri restarted
+        assert_eq!(inner.workspaces_to_save(), [(1, "web".to_string())]);
+        let mut names: Vec<_> = inner.snapshot_windows("web", 1).into_iter().map(|w| w.config_name).collect();
+        names.sort();
+        assert_eq!(names, [Some("docs".to_string()), Some("editor".to_string())]);
+    }
+
     #[test]
     fn priority_active_error_is_critical() {
         let mut inner = test_inner();
@@ -1063,6 +2122,231 @@ mod tests {
         assert_eq!(buffer[0].priority.as_deref(), Some("critical"));
     }
 
+    #[test]
+    fn idle_check_disabled_by_default() {
+        let mut inner = test_inner();
+        inner.workspace_to_project.insert(1, "proj".into());
+        inner.check_idle_projects();
+        assert!(inner.last_focused.is_empty());
+    }
+
+    #[test]
+    fn idle_check_tracks_focus_and_resumes_closed_projects() {
+        thread_local! {
+            static REQUESTS: std::cell::RefCell<Vec<(String, bool)>> = const { std::cell::RefCell::new(Vec::new()) };
+        }
+        fn record(project: &str, suspend: bool) -> anyhow::Result<()> {
+            REQUESTS.with(|r| r.borrow_mut().push((project.to_string(), suspend)));
+            Ok(())
+        }
+
+        let mut inner = test_inner();
+        inner.request_suspend = record;
+        inner.idle_suspend_after = Some(Duration::from_secs(600));
+        inner.workspace_to_project.insert(1, "proj".into());
+        inner.active_project = Some("proj".into());
+        inner.last_focused.insert("gone".into(), Instant::now());
+        inner.suspended_projects.insert("gone".into());
+
+        inner.check_idle_projects();
+        assert!(inner.last_focused.contains_key("proj"));
+        assert!(!inner.last_focused.contains_key("gone"));
+        assert!(inner.suspended_projects.is_empty());
+        assert_eq!(REQUESTS.with(|r| r.take()), [("gone".to_string(), false)]);
+    }
+
+    #[test]
+    fn focus_timer_follows_the_active_project() {
+        let mut inner = test_inner();
+        inner.workspace_to_project.insert(1, "proj".into());
+        inner.focused_workspace_id = Some(1);
+        inner.update_active_project();
+        let (project, started) = inner.focus_since.clone().unwrap();
+        assert_eq!(project, "proj");
+
+        // Same project: the running timer is kept
+        inner.credit_focus(Some("proj".into()));
+        assert_eq!(inner.focus_since.as_ref().unwrap().1, started);
+
+        inner.focused_workspace_id = None;
+        inner.update_active_project();
+        assert!(inner.focus_since.is_none());
+    }
+
+    #[test]
+    fn focus_timer_pauses_while_idle() {
+        let mut inner = test_inner();
+        inner.workspace_to_project.insert(1, "proj".into());
+        inner.focused_workspace_id = Some(1);
+        inner.update_active_project();
+
+        inner.user_idle_changed(true, Duration::from_secs(300));
+        assert!(inner.focus_since.is_none());
+        // Switching workspaces while away doesn't restart it
+        inner.update_active_project();
+        inner.credit_focus(Some("proj".into()));
+        assert!(inner.focus_since.is_none());
+
+        inner.user_idle_changed(false, Duration::from_secs(300));
+        assert_eq!(inner.focus_since.as_ref().unwrap().0, "proj");
+    }
+
+    #[test]
+    fn behind_upstream_reported_once_per_increase() {
+        let mut inner = test_inner();
+        inner.active_project = Some("api".into());
+        let behind = |n| drift_core::repo_status::RepoStatus { branch: Some("main".into()), behind: n, has_upstream: true, ..Default::default() };
+        let reported = |inner: &DaemonInner, project: &str| {
+            inner.events.get(project).map_or(0, |b| b.iter().filter(|e| e.event_type == "git.behind_upstream").count())
+        };
+
+        inner.repo_status_changed("web".into(), behind(2));
+        inner.repo_status_changed("web".into(), behind(2));
+        assert_eq!(reported(&inner, "web"), 1);
+        inner.repo_status_changed("web".into(), behind(3));
+        assert_eq!(reported(&inner, "web"), 2);
+
+        // The focused project isn't interrupted
+        inner.repo_status_changed("api".into(), behind(1));
+        assert_eq!(reported(&inner, "api"), 0);
+    }
+
+    #[test]
+    fn persist_failures_escalate_and_reset() {
+        let mut inner = test_inner();
+        for _ in 0..PERSIST_FAILURE_ESCALATION {
+            inner.report_persist_result("proj", "auto-save", Err(anyhow::anyhow!("disk full")));
+        }
+        let buffer = inner.events.get("proj").unwrap();
+        assert_eq!(buffer.len(), PERSIST_FAILURE_ESCALATION as usize);
+        assert!(buffer.iter().all(|e| e.event_type == "drift.warning"));
+        assert_eq!(buffer[0].level.as_deref(), Some("warning"));
+        assert_eq!(buffer[0].body.as_deref(), Some("disk full"));
+        assert_eq!(buffer.back().unwrap().level.as_deref(), Some("error"));
+
+        inner.report_persist_result("proj", "auto-save", Ok(()));
+        assert!(inner.persist_failures.is_empty());
+        assert_eq!(inner.events.get("proj").unwrap().len(), PERSIST_FAILURE_ESCALATION as usize);
+    }
+
+    #[test]
+    fn reconcile_without_drift_emits_nothing() {
+        let mut inner = test_inner();
+        inner.reconcile(Instant::now(), Vec::new(), Vec::new());
+        assert!(inner.events.is_empty());
+    }
+
+    #[test]
+    fn niri_restart_reports_lost_workspaces() {
+        let mut inner = test_inner();
+        inner.workspace_to_project.insert(1, "proj".into());
+        inner.focused_workspace_id = Some(1);
+        inner.niri_disconnected();
+        assert_eq!(inner.focused_workspace_id, None);
+
+        inner.niri_reconnected(Duration::from_secs(3), Vec::new(), Vec::new());
+        assert!(inner.niri_lost.is_none());
+        assert!(inner.workspace_to_project.is_empty());
+        let event = inner.events.get("").unwrap().back().unwrap();
+        assert_eq!(event.event_type, "daemon.reconnected");
+        assert_eq!(event.level.as_deref(), Some("warning"));
+        assert_eq!(event.meta.as_ref().unwrap()["lost"], serde_json::json!(["proj"]));
+        assert_eq!(inner.events.get("proj").unwrap()[0].event_type, "workspace.destroyed");
+    }
+
+    #[test]
+    fn heartbeat_goes_to_subscribers_only() {
+        let (sub_tx, sub_rx) = mpsc::channel();
+        let mut inner = test_inner();
+        inner.subscriber_tx = sub_tx;
+        inner.send_heartbeat();
+        let event = sub_rx.try_recv().unwrap();
+        assert_eq!(event.event_type, "daemon.heartbeat");
+        assert_eq!(event.priority.as_deref(), Some("silent"));
+        assert!(inner.events.is_empty());
+    }
+
+    #[test]
+    fn repeated_events_coalesce_within_window() {
+        let mut inner = test_inner();
+        inner.dedup_window = Some(Duration::from_secs(60));
+        for _ in 0..3 {
+            inner.handle_emit_event(test_event("proj", "error"));
+        }
+        let mut other = test_event("proj", "error");
+        other.title = Some("something else".into());
+        inner.handle_emit_event(other);
+
+        let buffer = inner.events.get("proj").unwrap();
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer[0].meta.as_ref().unwrap()["count"], 3);
+        assert!(buffer[1].meta.is_none());
+
+        inner.dedup_window = None;
+        inner.handle_emit_event(test_event("proj", "error"));
+        assert_eq!(inner.events.get("proj").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn closed_dedup_window_rebroadcasts_count() {
+        let (sub_tx, sub_rx) = mpsc::channel();
+        let mut inner = test_inner();
+        inner.subscriber_tx = sub_tx;
+        inner.dedup_window = Some(Duration::from_secs(60));
+        inner.handle_emit_event(test_event("proj", "error"));
+        // Nothing folded yet: no reason to wake up for this window
+        assert!(inner.next_dedup_close().is_none());
+        for _ in 0..2 {
+            inner.handle_emit_event(test_event("proj", "error"));
+        }
+        assert_eq!(sub_rx.try_iter().count(), 1);
+        let closes = inner.next_dedup_close().unwrap();
+        assert!(closes > Instant::now() + Duration::from_secs(59));
+
+        inner.close_dedup_windows(Instant::now() + Duration::from_secs(61));
+        let summary = sub_rx.try_recv().unwrap();
+        assert_eq!(summary.meta.unwrap()["count"], 3);
+        assert!(inner.dedup_seen.is_empty());
+    }
+
+    #[test]
+    fn periodic_task_restarts_its_interval_when_run() {
+        let mut task = Every::new(Duration::from_secs(10));
+        let start = task.last;
+        assert!(!task.due(start + Duration::from_secs(9)));
+        assert!(task.due(start + Duration::from_secs(12)));
+        assert_eq!(task.deadline(), start + Duration::from_secs(22));
+        assert!(Every::due_now(Duration::from_secs(10)).due(Instant::now()));
+    }
+
+    #[test]
+    fn internal_events_are_not_coalesced() {
+        let mut inner = test_inner();
+        inner.dedup_window = Some(Duration::from_secs(60));
+        for _ in 0..2 {
+            inner.process_event(Event {
+                event_type: "workspace.activated".into(),
+                source: "niri".into(),
+                ..test_event("proj", "info")
+            });
+        }
+        assert_eq!(inner.events["proj"].len(), 2);
+        assert!(inner.dedup_seen.is_empty());
+    }
+
+    #[test]
+    fn rate_limited_source_gets_one_notice() {
+        let mut inner = test_inner();
+        inner.rate_limits =
+            crate::rate_limit::RateLimits::from_config(&HashMap::from([("test".to_string(), "1/hour".to_string())]));
+        for _ in 0..4 {
+            inner.handle_emit_event(test_event("proj", "info"));
+        }
+        let types: Vec<_> = inner.events["proj"].iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["test", "events.rate_limited"]);
+        assert_eq!(inner.events["proj"][1].meta.as_ref().unwrap()["limit"], "1/hour");
+    }
+
     #[test]
     fn process_event_respects_buffer_size() {
         let mut inner = test_inner();
@@ -1,15 +1,28 @@
-use std::io::{BufRead, BufReader};
-use std::os::unix::net::UnixListener;
-use std::sync::atomic::{AtomicBool, Ordering};
+//! `emit.sock`: one thread per connection, so a long-lived emitter (an
+//! editor plugin, a watch loop) never holds up anyone else.
+
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::time::Duration;
 
-use drift_core::events::{self, Event};
+use serde_json::json;
+
+use drift_core::events;
 use drift_core::paths;
 
 use crate::daemon::DaemonMsg;
+use crate::shutdown::Shutdown;
+
+const REPLY_TIMEOUT: Duration = Duration::from_millis(100);
+/// A client that sends nothing for this long is disconnected.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+/// Connections past this are told to retry and closed.
+const MAX_CONNECTIONS: usize = 32;
 
-pub fn run_emit_listener(tx: Sender<DaemonMsg>, shutdown: &'static AtomicBool) {
+pub fn run_emit_listener(tx: Sender<DaemonMsg>, shutdown: Shutdown) {
     let sock_path = paths::emit_socket_path();
 
     if let Some(parent) = sock_path.parent() {
@@ -25,43 +38,187 @@ pub fn run_emit_listener(tx: Sender<DaemonMsg>, shutdown: &'static AtomicBool) {
         }
     };
 
-    listener.set_nonblocking(true).ok();
+    // accept blocks; on shutdown a connection of our own wakes it
+    let wake_path = sock_path.clone();
+    shutdown.on_shutdown(move || {
+        let _ = UnixStream::connect(&wake_path);
+    });
+    let next_id = Arc::new(AtomicU64::new(1));
+    let connections = Arc::new(AtomicUsize::new(0));
 
-    while !shutdown.load(Ordering::Relaxed) {
+    while !shutdown.is_triggered() {
         match listener.accept() {
+            Ok(_) if shutdown.is_triggered() => break,
             Ok((stream, _)) => {
-                let reader = BufReader::new(stream);
-                for line in reader.lines() {
-                    match line {
-                        Ok(line) if line.trim().is_empty() => continue,
-                        Ok(line) => match serde_json::from_str::<Event>(&line) {
-                            Ok(mut event) => {
-                                if event.ts.is_empty() {
-                                    event.ts = events::iso_now();
-                                }
-                                if event.level.is_none() {
-                                    event.level = Some("info".into());
-                                }
-                                if tx.send(DaemonMsg::EmitEvent(event)).is_err() {
-                                    let _ = std::fs::remove_file(&sock_path);
-                                    return;
-                                }
-                            }
-                            Err(e) => eprintln!("invalid event JSON: {e}"),
-                        },
-                        Err(_) => break,
-                    }
+                if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    let _ = stream.set_write_timeout(Some(REPLY_TIMEOUT));
+                    let reply = json!({ "ok": false, "error": "too many connections, retry later" });
+                    let _ = writeln!(&stream, "{reply}");
+                    continue;
+                }
+                let (tx, next_id, done, shutdown) =
+                    (tx.clone(), next_id.clone(), connections.clone(), shutdown.clone());
+                let spawned = std::thread::Builder::new().name("emit-conn".into()).spawn(move || {
+                    handle_connection(stream, &tx, &next_id, &shutdown);
+                    done.fetch_sub(1, Ordering::SeqCst);
+                });
+                if spawned.is_err() {
+                    connections.fetch_sub(1, Ordering::SeqCst);
                 }
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                std::thread::sleep(Duration::from_millis(100));
             }
             Err(e) => {
                 eprintln!("emit accept error: {e}");
-                std::thread::sleep(Duration::from_millis(100));
+                shutdown.sleep(Duration::from_millis(100));
             }
         }
     }
 
     let _ = std::fs::remove_file(&sock_path);
 }
+
+/// Read events from one client until it disconnects, sends an oversized
+/// line, goes quiet for `IDLE_TIMEOUT`, or the daemon is gone. No line may
+/// grow past `MAX_EVENT_BYTES`.
+fn handle_connection(stream: UnixStream, tx: &Sender<DaemonMsg>, next_id: &AtomicU64, shutdown: &Shutdown) {
+    // A client that connects and never writes must not hold a thread forever
+    if stream.set_read_timeout(Some(IDLE_TIMEOUT)).is_err() {
+        return;
+    }
+    // A client that never reads its replies must not stall its connection
+    let mut replies = stream
+        .try_clone()
+        .ok()
+        .filter(|s| s.set_write_timeout(Some(REPLY_TIMEOUT)).is_ok());
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+
+    while !shutdown.is_triggered() {
+        let limit = (events::MAX_EVENT_BYTES + 1).saturating_sub(line.len()) as u64;
+        match (&mut reader).take(limit).read_until(b'\n', &mut line) {
+            Ok(0) => {
+                // A last line without a trailing newline still counts
+                let text = String::from_utf8_lossy(&line).trim().to_string();
+                if !text.is_empty() {
+                    handle_line(&text, tx, next_id, &mut replies);
+                }
+                return;
+            }
+            Ok(_) if line.ends_with(b"\n") => {
+                let text = String::from_utf8_lossy(&line).trim().to_string();
+                line.clear();
+                if !text.is_empty() && !handle_line(&text, tx, next_id, &mut replies) {
+                    return;
+                }
+            }
+            Ok(_) if line.len() > events::MAX_EVENT_BYTES => {
+                let error = format!("event is over {} bytes, closing connection", events::MAX_EVENT_BYTES);
+                eprintln!("rejected event: {error}");
+                send_reply(&mut replies, &json!({ "ok": false, "error": error }));
+                return;
+            }
+            // Unterminated line at EOF; the next read returns 0
+            Ok(_) => continue,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => return,
+        }
+    }
+}
+
+/// Forward one line to the daemon and acknowledge it. False once the
+/// daemon is gone.
+fn handle_line(line: &str, tx: &Sender<DaemonMsg>, next_id: &AtomicU64, replies: &mut Option<UnixStream>) -> bool {
+    match events::parse_emitted(line) {
+        Ok(mut event) => {
+            if event.ts.is_empty() {
+                event.ts = events::iso_now();
+            }
+            if event.level.is_none() {
+                event.level = Some("info".into());
+            }
+            if tx.send(DaemonMsg::EmitEvent(event)).is_err() {
+                return false;
+            }
+            let id = next_id.fetch_add(1, Ordering::Relaxed);
+            send_reply(replies, &json!({ "ok": true, "id": id }));
+        }
+        Err(e) => {
+            eprintln!("rejected event: {e}");
+            send_reply(replies, &json!({ "ok": false, "error": e.to_string() }));
+        }
+    }
+    true
+}
+
+/// Acknowledge a line. Clients that don't read replies are unaffected; once
+/// a write fails (closed socket, full buffer) the connection gets no more.
+fn send_reply(stream: &mut Option<UnixStream>, reply: &serde_json::Value) {
+    if let Some(out) = stream.as_mut() {
+        if writeln!(out, "{reply}").is_err() {
+            *stream = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn replies(client: UnixStream) -> Vec<serde_json::Value> {
+        BufReader::new(client)
+            .lines()
+            .map(|l| serde_json::from_str(&l.unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn connection_forwards_events_and_acknowledges_each_line() {
+        let (server, client) = UnixStream::pair().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let next_id = AtomicU64::new(7);
+        let shutdown = Shutdown::new();
+
+        let lines = concat!(
+            r#"{"type":"build.failed","project":"web","source":"ci","ts":"2025-01-01T00:00:00Z"}"#,
+            "\nnot json\n",
+            r#"{"type":"build.started","project":"web","source":"ci","ts":"2025-01-01T00:00:01Z"}"#,
+        );
+        (&client).write_all(lines.as_bytes()).unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+        handle_connection(server, &tx, &next_id, &shutdown);
+
+        let types: Vec<String> = rx
+            .try_iter()
+            .map(|msg| match msg {
+                DaemonMsg::EmitEvent(event) => event.event_type,
+                _ => panic!("unexpected message"),
+            })
+            .collect();
+        assert_eq!(types, ["build.failed", "build.started"]);
+        let replies = replies(client);
+        assert_eq!(replies[0], json!({ "ok": true, "id": 7 }));
+        assert_eq!(replies[1]["ok"], false);
+        assert_eq!(replies[2], json!({ "ok": true, "id": 8 }));
+    }
+
+    #[test]
+    fn oversized_line_closes_connection() {
+        let (server, client) = UnixStream::pair().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Shutdown::new();
+
+        let writer = client.try_clone().unwrap();
+        let flood = std::thread::spawn(move || {
+            let _ = (&writer).write_all(&vec![b'x'; events::MAX_EVENT_BYTES + 10]);
+        });
+        handle_connection(server, &tx, &AtomicU64::new(1), &shutdown);
+        flood.join().unwrap();
+        let _ = client.shutdown(std::net::Shutdown::Write);
+
+        assert!(rx.try_recv().is_err());
+        let replies = replies(client);
+        assert_eq!(replies.len(), 1);
+        assert!(replies[0]["error"].as_str().unwrap().contains("closing connection"));
+    }
+}
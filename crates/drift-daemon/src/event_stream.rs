@@ -1,24 +1,34 @@
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use drift_core::niri::NiriClient;
 use niri_ipc::socket::Socket;
 use niri_ipc::{Request, Response};
 
 use crate::daemon::DaemonMsg;
+use crate::shutdown::Shutdown;
 
-pub fn run_event_stream(tx: Sender<DaemonMsg>, shutdown: &'static AtomicBool) {
-    while !shutdown.load(Ordering::Relaxed) {
-        match connect_and_stream(&tx, shutdown) {
+const MIN_RETRY: Duration = Duration::from_millis(500);
+const MAX_RETRY: Duration = Duration::from_secs(5);
+
+pub fn run_event_stream(tx: Sender<DaemonMsg>, shutdown: Shutdown) {
+    // Set while niri is away after a stream that was up broke
+    let mut lost: Option<Instant> = None;
+    let mut retry = MIN_RETRY;
+    while !shutdown.is_triggered() {
+        let attempt = Instant::now();
+        match connect_and_stream(&tx, &shutdown, &mut lost) {
             Ok(()) => break,
             Err(e) => {
-                eprintln!("event stream error: {e}, reconnecting in 5s");
-                for _ in 0..50 {
-                    if shutdown.load(Ordering::Relaxed) {
-                        return;
-                    }
-                    std::thread::sleep(Duration::from_millis(100));
+                if lost.is_some_and(|since| since >= attempt) {
+                    // A live stream just dropped; niri restarts fast, so retry quickly
+                    retry = MIN_RETRY;
+                }
+                eprintln!("event stream error: {e}, reconnecting in {}ms", retry.as_millis());
+                if shutdown.sleep(retry) {
+                    return;
                 }
+                retry = (retry * 2).min(MAX_RETRY);
             }
         }
     }
@@ -26,7 +36,8 @@ pub fn run_event_stream(tx: Sender<DaemonMsg>, shutdown: &'static AtomicBool) {
 
 fn connect_and_stream(
     tx: &Sender<DaemonMsg>,
-    shutdown: &'static AtomicBool,
+    shutdown: &Shutdown,
+    lost: &mut Option<Instant>,
 ) -> anyhow::Result<()> {
     let mut socket = Socket::connect()?;
 
@@ -37,10 +48,26 @@ fn connect_and_stream(
         Err(msg) => anyhow::bail!("niri error: {msg}"),
     }
 
+    // After a drop this may be a fresh compositor: hand the daemon the full
+    // state before any stream event so it can rebuild its caches
+    if let Some(since) = *lost {
+        let mut client = NiriClient::connect()?;
+        let workspaces = client.workspaces()?;
+        let windows = client.windows()?;
+        *lost = None;
+        let msg = DaemonMsg::NiriReconnected { down_for: since.elapsed(), workspaces, windows };
+        if tx.send(msg).is_err() {
+            return Ok(());
+        }
+    }
+    if tx.send(DaemonMsg::NiriConnected).is_err() {
+        return Ok(());
+    }
+
     let mut read_event = socket.read_events();
 
     loop {
-        if shutdown.load(Ordering::Relaxed) {
+        if shutdown.is_triggered() {
             return Ok(());
         }
 
@@ -51,8 +78,31 @@ fn connect_and_stream(
                 }
             }
             Err(e) => {
+                *lost = Some(Instant::now());
+                if tx.send(DaemonMsg::NiriDisconnected).is_err() {
+                    return Ok(());
+                }
                 return Err(e.into());
             }
         }
     }
 }
+
+/// Periodically fetch the full workspace and window lists so the daemon can
+/// correct caches that missed stream events.
+pub fn run_state_poll(tx: Sender<DaemonMsg>, shutdown: Shutdown, interval: Duration) {
+    while !shutdown.sleep(interval) {
+        let taken = Instant::now();
+        let snapshot = NiriClient::connect().and_then(|mut client| {
+            let workspaces = client.workspaces()?;
+            let windows = client.windows()?;
+            Ok((workspaces, windows))
+        });
+        // niri being down is already reported by the event stream
+        if let Ok((workspaces, windows)) = snapshot {
+            if tx.send(DaemonMsg::NiriSnapshot { taken, workspaces, windows }).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Background fetches of every project's repo (`defaults.git_poll_minutes`),
+//! reporting how far each branch is behind its upstream so the daemon can
+//! point out projects that fell behind while you were elsewhere.
+
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use drift_core::{config, registry, repo_status};
+
+use crate::daemon::DaemonMsg;
+use crate::shutdown::Shutdown;
+
+pub fn run_git_poll(tx: Sender<DaemonMsg>, shutdown: Shutdown, interval: Duration) {
+    while !shutdown.sleep(interval) {
+        for project in registry::list_projects().unwrap_or_default() {
+            if shutdown.is_triggered() {
+                return;
+            }
+            let Ok(repo) = config::resolve_repo_path(&project.project.repo) else {
+                continue;
+            };
+            if !repo.join(".git").exists() {
+                continue;
+            }
+            repo_status::fetch(&repo);
+            let Some(status) = repo_status::inspect(&repo).filter(|s| s.has_upstream) else {
+                continue;
+            };
+            if tx.send(DaemonMsg::RepoStatus { project: project.project.name, status }).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! `[[hooks]]`: shell commands the daemon runs for matching events, from
+//! the global config and from the event's project config. They run on a
+//! small worker pool with a timeout each; failures come back as
+//! `hook.failed` events.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use drift_core::config::{self, HookConfig};
+use drift_core::events::{self, matches_type_filter, Event};
+use drift_core::paths;
+
+use crate::daemon::DaemonMsg;
+
+/// Hooks running at once; more wait in the queue.
+const WORKERS: usize = 4;
+/// Hooks waiting for a worker; past this, new ones are dropped.
+const QUEUE: usize = 64;
+
+struct Job {
+    hook: HookConfig,
+    event: Event,
+    cwd: Option<PathBuf>,
+}
+
+pub struct Hooks {
+    global: Vec<HookConfig>,
+    jobs: SyncSender<Job>,
+    /// Each project's hooks, kept until its config file changes
+    projects: HashMap<String, ProjectHooks>,
+}
+
+struct ProjectHooks {
+    modified: Option<SystemTime>,
+    hooks: Vec<HookConfig>,
+    cwd: Option<PathBuf>,
+}
+
+impl Hooks {
+    pub fn new(global: Vec<HookConfig>, tx: Sender<DaemonMsg>) -> Self {
+        let (jobs, rx) = mpsc::sync_channel::<Job>(QUEUE);
+        let rx = Arc::new(Mutex::new(rx));
+        for i in 0..WORKERS {
+            let rx = Arc::clone(&rx);
+            let tx = tx.clone();
+            let _ = thread::Builder::new()
+                .name(format!("hook-{i}"))
+                .spawn(move || worker(rx, tx));
+        }
+        Self { global, jobs, projects: HashMap::new() }
+    }
+
+    /// Queue every hook matching `event`. Events about hooks never trigger
+    /// hooks, so a failing `*` hook can't feed itself.
+    pub fn dispatch(&mut self, event: &Event) {
+        if event.event_type.starts_with("hook.") {
+            return;
+        }
+        let mut jobs: Vec<Job> = matching(&self.global, event)
+            .map(|hook| Job { hook: hook.clone(), event: event.clone(), cwd: None })
+            .collect();
+        if let Some(project) = self.project_hooks(&event.project) {
+            jobs.extend(matching(&project.hooks, event).map(|hook| Job {
+                hook: hook.clone(),
+                event: event.clone(),
+                cwd: project.cwd.clone(),
+            }));
+        }
+        for job in jobs {
+            match self.jobs.try_send(job) {
+                Ok(()) => {}
+                Err(TrySendError::Full(job)) => {
+                    eprintln!("hooks: queue full, dropping '{}'", job.hook.command);
+                }
+                Err(TrySendError::Disconnected(_)) => return,
+            }
+        }
+    }
+
+    /// The project's hooks, re-read only when its config file's mtime
+    /// changed since the last event.
+    fn project_hooks(&mut self, project: &str) -> Option<&ProjectHooks> {
+        if project.is_empty() {
+            return None;
+        }
+        let modified = std::fs::metadata(paths::project_config_path(project))
+            .and_then(|m| m.modified())
+            .ok();
+        if self.projects.get(project).is_none_or(|cached| cached.modified != modified) {
+            let loaded = modified.and_then(|_| config::load_project_config(project).ok());
+            let cached = ProjectHooks {
+                modified,
+                cwd: loaded.as_ref().and_then(|c| config::resolve_repo_path(&c.project.repo).ok()),
+                hooks: loaded.map(|c| c.hooks).unwrap_or_default(),
+            };
+            self.projects.insert(project.to_string(), cached);
+        }
+        self.projects.get(project)
+    }
+}
+
+fn matching<'a>(hooks: &'a [HookConfig], event: &'a Event) -> impl Iterator<Item = &'a HookConfig> {
+    hooks.iter().filter(|hook| {
+        matches_type_filter(&event.event_type, &hook.event)
+            && hook.project.as_deref().is_none_or(|p| p == event.project)
+    })
+}
+
+fn worker(rx: Arc<Mutex<Receiver<Job>>>, tx: Sender<DaemonMsg>) {
+    loop {
+        let job = match rx.lock() {
+            Ok(rx) => match rx.recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            },
+            Err(_) => return,
+        };
+        if let Err(reason) = run(&job) {
+            eprintln!("hooks: '{}' failed: {reason}", job.hook.command);
+            let _ = tx.send(DaemonMsg::EmitEvent(failed_event(&job, &reason)));
+        }
+    }
+}
+
+/// Run one hook to completion or its timeout. The error is the reason it
+/// failed, for `hook.failed`.
+fn run(job: &Job) -> Result<(), String> {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", &job.hook.command])
+        .envs(event_env(&job.event))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        // Own process group, so a timeout also kills whatever it started
+        .process_group(0);
+    if let Some(cwd) = job.cwd.as_ref().filter(|c| c.is_dir()) {
+        cmd.current_dir(cwd);
+    }
+    let mut child = cmd.spawn().map_err(|e| format!("could not start: {e}"))?;
+    let started = Instant::now();
+    // A background process the hook left behind can hold stderr open, so
+    // the reader reports back over a channel that is waited on with a limit
+    let (stderr_tx, stderr_rx) = mpsc::channel();
+    if let Some(mut err) = child.stderr.take() {
+        thread::spawn(move || {
+            let mut text = String::new();
+            let _ = err.read_to_string(&mut text);
+            let _ = stderr_tx.send(text);
+        });
+    }
+
+    let timeout = Duration::from_secs(job.hook.timeout_secs);
+    let status = match drift_core::resources::wait_group_timeout(&mut child, timeout) {
+        Ok(Some(status)) => status,
+        Ok(None) => return Err(format!("timed out after {}s", job.hook.timeout_secs)),
+        Err(e) => return Err(e.to_string()),
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = stderr_rx
+        .recv_timeout(timeout.saturating_sub(started.elapsed()))
+        .unwrap_or_default();
+    match stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+        Some(line) => Err(format!("{status}: {}", line.trim())),
+        None => Err(status.to_string()),
+    }
+}
+
+fn event_env(event: &Event) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("DRIFT_EVENT_TYPE", event.event_type.clone()),
+        ("DRIFT_EVENT_PROJECT", event.project.clone()),
+        ("DRIFT_EVENT_SOURCE", event.source.clone()),
+        ("DRIFT_EVENT_TS", event.ts.clone()),
+        ("DRIFT_EVENT_LEVEL", event.level.clone().unwrap_or_default()),
+        ("DRIFT_EVENT_TITLE", event.title.clone().unwrap_or_default()),
+        ("DRIFT_EVENT_BODY", event.body.clone().unwrap_or_default()),
+        ("DRIFT_EVENT_PRIORITY", event.priority.clone().unwrap_or_default()),
+        ("DRIFT_EVENT_JSON", serde_json::to_string(event).unwrap_or_default()),
+    ];
+    if !event.project.is_empty() {
+        env.push(("DRIFT_PROJECT", event.project.clone()));
+    }
+    if let Some(id) = &event.correlation_id {
+        env.push((events::CORRELATION_ENV, id.clone()));
+    }
+    env
+}
+
+fn failed_event(job: &Job, reason: &str) -> Event {
+    Event {
+        correlation_id: job.event.correlation_id.clone(),
+        ..Event::new("hook.failed", job.event.project.clone(), "hooks")
+            .level("error")
+            .title(format!("Hook for {} failed", job.event.event_type))
+            .body(reason)
+            .meta(serde_json::json!({
+                "event": job.event.event_type,
+                "command": job.hook.command,
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(event: &str, command: &str) -> HookConfig {
+        HookConfig {
+            event: event.into(),
+            command: command.into(),
+            project: None,
+            timeout_secs: 5,
+        }
+    }
+
+    fn event(event_type: &str, project: &str) -> Event {
+        Event {
+            correlation_id: Some("run-1".into()),
+            ..Event::new(event_type, project, "test").level("error").title("cargo check")
+        }
+    }
+
+    fn job(hook: HookConfig, event: Event) -> Job {
+        Job { hook, event, cwd: None }
+    }
+
+    #[test]
+    fn hooks_match_type_and_project() {
+        let mut api_only = hook("service.*", "true");
+        api_only.project = Some("api".into());
+        let hooks = [hook("build.failed", "true"), api_only];
+        let count = |e: &Event| matching(&hooks, e).count();
+        assert_eq!(count(&event("build.failed", "web")), 1);
+        assert_eq!(count(&event("service.crashed", "api")), 1);
+        assert_eq!(count(&event("service.crashed", "web")), 0);
+        assert_eq!(count(&event("build.succeeded", "web")), 0);
+    }
+
+    #[test]
+    fn hook_sees_event_in_env() {
+        let job = job(
+            hook("build.failed", r#"test "$DRIFT_EVENT_TITLE/$DRIFT_PROJECT/$DRIFT_CORRELATION_ID" = "cargo check/web/run-1""#),
+            event("build.failed", "web"),
+        );
+        assert_eq!(run(&job), Ok(()));
+    }
+
+    #[test]
+    fn hook_failure_reports_stderr() {
+        let job = job(hook("*", "echo nope >&2; exit 2"), event("build.failed", "web"));
+        assert_eq!(run(&job), Err("exit status: 2: nope".into()));
+        let failed = failed_event(&job, "exit status: 2: nope");
+        assert_eq!(failed.event_type, "hook.failed");
+        assert_eq!(failed.correlation_id.as_deref(), Some("run-1"));
+    }
+
+    #[test]
+    fn leftover_background_process_does_not_hold_up_failure() {
+        let mut hook = hook("*", "sleep 10 & exit 2");
+        hook.timeout_secs = 1;
+        let started = Instant::now();
+        assert_eq!(run(&job(hook, event("build.failed", "web"))), Err("exit status: 2".into()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn hook_times_out() {
+        let mut slow = hook("*", "sleep 10");
+        slow.timeout_secs = 0;
+        let started = Instant::now();
+        assert_eq!(run(&job(slow, event("build.failed", "web"))), Err("timed out after 0s".into()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,133 @@
+//! Keyboard/mouse idle detection for focus time, through `swayidle`, which
+//! speaks the ext-idle-notify protocol for us. It prints a line when input
+//! stops for the timeout and another when it resumes.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::daemon::DaemonMsg;
+
+const MIN_RESTART: Duration = Duration::from_secs(1);
+const MAX_RESTART: Duration = Duration::from_secs(60);
+/// A swayidle that ran this long before exiting is restarted without backoff.
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+pub struct IdleWatch {
+    child: Arc<Mutex<Option<Child>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl IdleWatch {
+    /// Start swayidle with a `timeout` idle threshold, restarting it when
+    /// it exits. None when it isn't installed or can't reach the compositor.
+    pub fn start(timeout: Duration, tx: mpsc::Sender<DaemonMsg>) -> Option<IdleWatch> {
+        let child = match spawn_swayidle(timeout) {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("idle detection unavailable (swayidle: {e}); focus time includes idle time");
+                return None;
+            }
+        };
+        let child = Arc::new(Mutex::new(Some(child)));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (watched, watch_stopped) = (child.clone(), stopped.clone());
+        let watcher = thread::Builder::new()
+            .name("idle-watch".into())
+            .spawn(move || watch(timeout, tx, watched, watch_stopped));
+        if watcher.is_err() {
+            if let Some(mut child) = lock(&child).take() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            return None;
+        }
+        Some(IdleWatch { child, stopped })
+    }
+
+    /// Stop swayidle for good; the watcher thread ends with its output.
+    pub fn stop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Some(mut child) = lock(&self.child).take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+fn spawn_swayidle(timeout: Duration) -> std::io::Result<Child> {
+    Command::new("swayidle")
+        .arg("-w")
+        .arg("timeout")
+        .arg(timeout.as_secs().max(1).to_string())
+        .arg("echo idle")
+        .arg("resume")
+        .arg("echo active")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+fn lock(child: &Mutex<Option<Child>>) -> MutexGuard<'_, Option<Child>> {
+    child.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Relay swayidle's lines to the daemon. When it exits, reap it, report
+/// the user active again (nobody would report it otherwise), and start a
+/// new one with backoff.
+fn watch(timeout: Duration, tx: mpsc::Sender<DaemonMsg>, child: Arc<Mutex<Option<Child>>>, stopped: Arc<AtomicBool>) {
+    let mut backoff = MIN_RESTART;
+    loop {
+        let started = Instant::now();
+        let stdout = lock(&child).as_mut().and_then(|c| c.stdout.take());
+        let mut idle = false;
+        for line in stdout.into_iter().flat_map(|out| BufReader::new(out).lines()) {
+            let Ok(line) = line else { break };
+            idle = match line.trim() {
+                "idle" => true,
+                "active" => false,
+                _ => continue,
+            };
+            if tx.send(DaemonMsg::UserIdle(idle)).is_err() {
+                return;
+            }
+        }
+
+        if let Some(mut exited) = lock(&child).take() {
+            let _ = exited.wait();
+        }
+        if idle && tx.send(DaemonMsg::UserIdle(false)).is_err() {
+            return;
+        }
+        if started.elapsed() >= STABLE_RUN {
+            backoff = MIN_RESTART;
+        }
+
+        loop {
+            if stopped.load(Ordering::Relaxed) {
+                return;
+            }
+            eprintln!("swayidle exited; restarting it in {}s", backoff.as_secs());
+            thread::sleep(backoff);
+            backoff = (backoff * 2).min(MAX_RESTART);
+            match spawn_swayidle(timeout) {
+                Ok(mut restarted) => {
+                    let mut current = lock(&child);
+                    // stop() ran while we slept
+                    if stopped.load(Ordering::Relaxed) {
+                        let _ = restarted.kill();
+                        let _ = restarted.wait();
+                        return;
+                    }
+                    *current = Some(restarted);
+                    break;
+                }
+                Err(e) => eprintln!("could not restart swayidle: {e}"),
+            }
+        }
+    }
+}
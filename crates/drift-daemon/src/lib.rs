@@ -1,7 +1,16 @@
+pub mod commander;
 pub mod daemon;
 pub mod emit_listener;
 pub mod event_stream;
+pub mod git_poll;
+pub mod hooks;
+pub mod idle;
+pub mod metrics;
+pub mod rate_limit;
+pub mod sinks;
+pub mod shutdown;
 pub mod state;
 pub mod subscriber;
+pub mod web;
 
 pub use daemon::run_daemon;
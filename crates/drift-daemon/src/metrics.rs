@@ -0,0 +1,313 @@
+//! Minimal HTTP listener (`[metrics] listen`): Prometheus text exposition on
+//! `/metrics`, plus the read-only status page from [`crate::web`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use drift_core::config::MetricsConfig;
+use drift_core::supervisor::{self, ServiceStatus, ServicesState};
+
+use crate::shutdown::Shutdown;
+use crate::state::SubscriberStats;
+
+/// Counters fed by the daemon threads; service gauges are read from each
+/// project's services.json at scrape time.
+pub struct Metrics {
+    started: Instant,
+    events_by_type: Mutex<HashMap<String, u64>>,
+    subscribers: AtomicUsize,
+    /// Subscribers disconnected for falling too far behind
+    slow_subscribers: AtomicU64,
+    /// Events those subscribers never received
+    dropped_events: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            started: Instant::now(),
+            events_by_type: Mutex::new(HashMap::new()),
+            subscribers: AtomicUsize::new(0),
+            slow_subscribers: AtomicU64::new(0),
+            dropped_events: AtomicU64::new(0),
+        })
+    }
+
+    pub fn record_event(&self, event_type: &str) {
+        if let Ok(mut counts) = self.events_by_type.lock() {
+            *counts.entry(event_type.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn set_subscribers(&self, count: usize) {
+        self.subscribers.store(count, Ordering::Relaxed);
+    }
+
+    pub fn record_slow_subscriber(&self, dropped_events: usize) {
+        self.slow_subscribers.fetch_add(1, Ordering::Relaxed);
+        self.dropped_events.fetch_add(dropped_events as u64, Ordering::Relaxed);
+    }
+
+    pub fn subscriber_stats(&self) -> SubscriberStats {
+        SubscriberStats {
+            connected: self.subscribers.load(Ordering::Relaxed),
+            slow_disconnects: self.slow_subscribers.load(Ordering::Relaxed),
+            dropped_events: self.dropped_events.load(Ordering::Relaxed),
+        }
+    }
+
+    fn snapshot(&self) -> String {
+        let events = self.events_by_type.lock().map(|c| c.clone()).unwrap_or_default();
+        let states: Vec<ServicesState> = drift_core::registry::list_projects()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| supervisor::read_state(&p.project.name))
+            .collect();
+        render(
+            self.started.elapsed(),
+            &events,
+            &self.subscriber_stats(),
+            &states,
+        )
+    }
+}
+
+fn render(
+    uptime: Duration,
+    events: &HashMap<String, u64>,
+    subscribers: &SubscriberStats,
+    services: &[ServicesState],
+) -> String {
+    let mut out = String::new();
+
+    out.push_str("# HELP drift_uptime_seconds Seconds since the daemon started.\n");
+    out.push_str("# TYPE drift_uptime_seconds gauge\n");
+    let _ = writeln!(out, "drift_uptime_seconds {}", uptime.as_secs());
+
+    out.push_str("# HELP drift_subscribers Connected event stream subscribers.\n");
+    out.push_str("# TYPE drift_subscribers gauge\n");
+    let _ = writeln!(out, "drift_subscribers {}", subscribers.connected);
+
+    out.push_str("# HELP drift_subscriber_slow_disconnects_total Subscribers disconnected for not keeping up.\n");
+    out.push_str("# TYPE drift_subscriber_slow_disconnects_total counter\n");
+    let _ = writeln!(out, "drift_subscriber_slow_disconnects_total {}", subscribers.slow_disconnects);
+
+    out.push_str("# HELP drift_subscriber_dropped_events_total Events never delivered to disconnected slow subscribers.\n");
+    out.push_str("# TYPE drift_subscriber_dropped_events_total counter\n");
+    let _ = writeln!(out, "drift_subscriber_dropped_events_total {}", subscribers.dropped_events);
+
+    out.push_str("# HELP drift_events_total Events processed since the daemon started, by type.\n");
+    out.push_str("# TYPE drift_events_total counter\n");
+    let sorted: BTreeMap<&String, &u64> = events.iter().collect();
+    for (event_type, count) in sorted {
+        let _ = writeln!(out, "drift_events_total{{type=\"{}\"}} {count}", escape(event_type));
+    }
+
+    out.push_str("# HELP drift_services Supervised services by project and status.\n");
+    out.push_str("# TYPE drift_services gauge\n");
+    for state in services {
+        let project = escape(&state.project);
+        for (label, status) in [
+            ("running", ServiceStatus::Running),
+            ("crashed", ServiceStatus::Failed),
+            ("backoff", ServiceStatus::Backoff),
+            ("stopped", ServiceStatus::Stopped),
+            ("scheduled", ServiceStatus::Scheduled),
+        ] {
+            let count = state.services.iter().filter(|s| s.status == status).count();
+            let _ = writeln!(out, "drift_services{{project=\"{project}\",status=\"{label}\"}} {count}");
+        }
+    }
+
+    out.push_str("# HELP drift_service_restarts_total Restarts of a service since its supervisor started.\n");
+    out.push_str("# TYPE drift_service_restarts_total counter\n");
+    for state in services {
+        let project = escape(&state.project);
+        for svc in &state.services {
+            let _ = writeln!(
+                out,
+                "drift_service_restarts_total{{project=\"{project}\",service=\"{}\"}} {}",
+                escape(&svc.name),
+                svc.restart_count
+            );
+        }
+    }
+
+    out
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub fn run_metrics_listener(config: MetricsConfig, metrics: Arc<Metrics>, shutdown: Shutdown) {
+    let Some(listen) = config.listen else {
+        return;
+    };
+    let listener = match TcpListener::bind(&listen) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("failed to bind metrics listener on {listen}: {e}");
+            return;
+        }
+    };
+    eprintln!("metrics listening on http://{listen}/metrics");
+    // accept blocks; on shutdown a connection of our own wakes it
+    if let Ok(addr) = listener.local_addr() {
+        shutdown.on_shutdown(move || {
+            let _ = TcpStream::connect_timeout(&addr, Duration::from_millis(100));
+        });
+    }
+
+    while !shutdown.is_triggered() {
+        match listener.accept() {
+            Ok(_) if shutdown.is_triggered() => break,
+            Ok((stream, _)) => {
+                if let Err(e) = serve(stream, &metrics, config.token.as_deref(), &shutdown) {
+                    eprintln!("metrics request failed: {e}");
+                }
+            }
+            Err(e) => {
+                eprintln!("metrics accept error: {e}");
+                shutdown.sleep(Duration::from_millis(100));
+            }
+        }
+    }
+}
+
+fn serve(mut stream: TcpStream, metrics: &Metrics, token: Option<&str>, shutdown: &Shutdown) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(2)))?;
+
+    // Only the request line matters; the rest of the request is ignored
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let target = request.split_whitespace().nth(1).unwrap_or("");
+    let path = target.split('?').next().unwrap_or("");
+
+    let (status, content_type, body) = match path {
+        _ if token.is_some_and(|t| !authorized(&request, t)) => {
+            ("401 Unauthorized", "text/plain; charset=utf-8", "token required\n".to_string())
+        }
+        "/metrics" => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics.snapshot(),
+        ),
+        "/" => ("200 OK", "text/html; charset=utf-8", crate::web::INDEX_HTML.to_string()),
+        "/api/status" => ("200 OK", "application/json", crate::web::status_json()),
+        "/events" => {
+            crate::web::spawn_event_stream(stream, shutdown.clone());
+            return Ok(());
+        }
+        _ => ("404 Not Found", "text/plain; charset=utf-8", "not found\n".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+/// Whether the request carries `token` as `?token=` or `Authorization: Bearer`.
+fn authorized(request: &str, token: &str) -> bool {
+    let target = request.split_whitespace().nth(1).unwrap_or("");
+    let query = target.split_once('?').map_or("", |(_, q)| q);
+    let from_query = query.split('&').filter_map(|pair| pair.strip_prefix("token="));
+    let from_header = request.lines().skip(1).filter_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        let value = value.trim();
+        let scheme = value.get(..7)?;
+        (name.trim().eq_ignore_ascii_case("authorization") && scheme.eq_ignore_ascii_case("bearer "))
+            .then(|| value[7..].trim())
+    });
+    from_query.chain(from_header).any(|given| same_token(given, token))
+}
+
+/// Compare without returning early, so response times don't reveal how
+/// much of a guess was right.
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drift_core::supervisor::{ServiceState, StartReason};
+
+    fn service(name: &str, status: ServiceStatus, restart_count: u32) -> ServiceState {
+        ServiceState {
+            name: name.into(),
+            pid: None,
+            status,
+            restart_count,
+            started_at: None,
+            exit_code: None,
+            is_agent: false,
+            agent_type: None,
+            start_reason: StartReason::Initial,
+            previous_exit_code: None,
+            cpu_percent: None,
+            rss_bytes: None,
+            start_ticks: None,
+            scope_unit: None,
+            next_run: None,
+        }
+    }
+
+    #[test]
+    fn token_accepted_from_query_or_bearer_header() {
+        assert!(authorized("GET /metrics?token=s3cret HTTP/1.1\r\n\r\n", "s3cret"));
+        assert!(authorized("GET /events?x=1&token=s3cret HTTP/1.1\r\n\r\n", "s3cret"));
+        assert!(authorized("GET / HTTP/1.1\r\nHost: h\r\nauthorization: Bearer s3cret\r\n\r\n", "s3cret"));
+        assert!(!authorized("GET /metrics HTTP/1.1\r\n\r\n", "s3cret"));
+        assert!(!authorized("GET /metrics?token=s3cre HTTP/1.1\r\n\r\n", "s3cret"));
+        assert!(!authorized("GET / HTTP/1.1\r\nAuthorization: Basic s3cret\r\n\r\n", "s3cret"));
+    }
+
+    #[test]
+    fn renders_prometheus_text() {
+        let events: HashMap<String, u64> = [("service.crashed".to_string(), 2)].into_iter().collect();
+        let services = vec![ServicesState {
+            supervisor_pid: 1,
+            project: "myapp".into(),
+            services: vec![
+                service("api", ServiceStatus::Running, 3),
+                service("worker", ServiceStatus::Failed, 0),
+            ],
+            suspended: false,
+        }];
+        let subscribers = SubscriberStats { connected: 2, slow_disconnects: 1, dropped_events: 1001 };
+        let text = render(Duration::from_secs(42), &events, &subscribers, &services);
+
+        assert!(text.contains("drift_uptime_seconds 42\n"));
+        assert!(text.contains("drift_subscribers 2\n"));
+        assert!(text.contains("drift_subscriber_slow_disconnects_total 1\n"));
+        assert!(text.contains("drift_subscriber_dropped_events_total 1001\n"));
+        assert!(text.contains("drift_events_total{type=\"service.crashed\"} 2\n"));
+        assert!(text.contains("drift_services{project=\"myapp\",status=\"running\"} 1\n"));
+        assert!(text.contains("drift_services{project=\"myapp\",status=\"crashed\"} 1\n"));
+        assert!(text.contains("drift_service_restarts_total{project=\"myapp\",service=\"api\"} 3\n"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape("a\"b\\c"), "a\\\"b\\\\c");
+    }
+
+    #[test]
+    fn record_event_counts_by_type() {
+        let metrics = Metrics::new();
+        metrics.record_event("build.failed");
+        metrics.record_event("build.failed");
+        assert_eq!(metrics.events_by_type.lock().unwrap()["build.failed"], 2);
+    }
+}
@@ -0,0 +1,171 @@
+//! Per-source limits on emitted events (`[events.limits]`), so one
+//! misbehaving script can't drown out real alerts. Each source gets a token
+//! bucket that holds its whole allowance and refills evenly over the period.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use drift_core::events;
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Allow,
+    /// Over the limit. `first` is set for the first event dropped since the
+    /// source was last within it, which is when to tell the user.
+    Drop { first: bool },
+}
+
+/// A source whose bucket filled back up after it was limited.
+#[derive(Debug, PartialEq)]
+pub struct Recovered {
+    pub source: String,
+    pub spec: String,
+    /// Events dropped while it was limited
+    pub dropped: u64,
+}
+
+struct Bucket {
+    capacity: f64,
+    per_sec: f64,
+    tokens: f64,
+    refilled: Instant,
+    /// Set from the first drop until the bucket is full again, so a source
+    /// hovering at its limit gets one notice rather than one per refill
+    limited: bool,
+    dropped: u64,
+    /// What the user configured, for the notice
+    spec: String,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.per_sec).min(self.capacity);
+        self.refilled = now;
+    }
+}
+
+#[derive(Default)]
+pub struct RateLimits {
+    buckets: HashMap<String, Bucket>,
+}
+
+impl RateLimits {
+    /// Limits that don't parse are skipped; `drift config validate` reports them.
+    pub fn from_config(limits: &HashMap<String, String>) -> Self {
+        let now = Instant::now();
+        let buckets = limits
+            .iter()
+            .filter_map(|(source, spec)| {
+                let (count, period) = events::parse_rate_limit(spec)?;
+                let capacity = f64::from(count);
+                let bucket = Bucket {
+                    capacity,
+                    per_sec: capacity / period.as_secs_f64(),
+                    tokens: capacity,
+                    refilled: now,
+                    limited: false,
+                    dropped: 0,
+                    spec: spec.clone(),
+                };
+                Some((source.clone(), bucket))
+            })
+            .collect();
+        Self { buckets }
+    }
+
+    pub fn check(&mut self, source: &str) -> Verdict {
+        self.check_at(source, Instant::now())
+    }
+
+    fn check_at(&mut self, source: &str, now: Instant) -> Verdict {
+        let Some(bucket) = self.buckets.get_mut(source) else {
+            return Verdict::Allow;
+        };
+        bucket.refill(now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Verdict::Allow;
+        }
+        let first = !bucket.limited;
+        bucket.limited = true;
+        bucket.dropped += 1;
+        Verdict::Drop { first }
+    }
+
+    /// Sources that were limited and have since refilled to their whole
+    /// allowance, with how many events they lost. Each is reported once.
+    pub fn recovered(&mut self) -> Vec<Recovered> {
+        self.recovered_at(Instant::now())
+    }
+
+    fn recovered_at(&mut self, now: Instant) -> Vec<Recovered> {
+        let mut recovered = Vec::new();
+        for (source, bucket) in &mut self.buckets {
+            if !bucket.limited {
+                continue;
+            }
+            bucket.refill(now);
+            if bucket.tokens >= bucket.capacity {
+                bucket.limited = false;
+                recovered.push(Recovered {
+                    source: source.clone(),
+                    spec: bucket.spec.clone(),
+                    dropped: std::mem::take(&mut bucket.dropped),
+                });
+            }
+        }
+        recovered
+    }
+
+    /// When the next limited source will have refilled, for the daemon to
+    /// wake up and report it.
+    pub fn next_recovery(&self) -> Option<Instant> {
+        self.buckets
+            .values()
+            .filter(|b| b.limited)
+            // A millisecond late, so rounding can't leave the bucket a hair short
+            .map(|b| b.refilled + Duration::from_secs_f64((b.capacity - b.tokens).max(0.0) / b.per_sec + 0.001))
+            .min()
+    }
+
+    /// The configured limit for `source`, e.g. "10/min".
+    pub fn spec(&self, source: &str) -> Option<&str> {
+        self.buckets.get(source).map(|b| b.spec.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_drops_excess_and_refills() {
+        let mut limits = RateLimits::from_config(&HashMap::from([("ci".to_string(), "2/min".to_string())]));
+        let start = Instant::now();
+        assert_eq!(limits.check_at("ci", start), Verdict::Allow);
+        assert_eq!(limits.check_at("ci", start), Verdict::Allow);
+        assert_eq!(limits.check_at("ci", start), Verdict::Drop { first: true });
+        assert_eq!(limits.check_at("ci", start), Verdict::Drop { first: false });
+        // Unlimited sources pass
+        assert_eq!(limits.check_at("editor", start), Verdict::Allow);
+
+        // One token back after half the period, but still limited
+        let later = start + Duration::from_secs(30);
+        assert!(limits.recovered_at(later).is_empty());
+        let due = limits.next_recovery().unwrap();
+        assert!(due > later && due <= later + Duration::from_secs(31));
+        assert_eq!(limits.check_at("ci", later), Verdict::Allow);
+        assert_eq!(limits.check_at("ci", later), Verdict::Drop { first: false });
+
+        // Full again: reported once with everything dropped
+        let full = later + Duration::from_secs(60);
+        let recovered = limits.recovered_at(full);
+        assert_eq!(
+            recovered,
+            [Recovered { source: "ci".into(), spec: "2/min".into(), dropped: 3 }]
+        );
+        assert!(limits.recovered_at(full).is_empty());
+        assert_eq!(limits.check_at("ci", full), Verdict::Allow);
+    }
+}
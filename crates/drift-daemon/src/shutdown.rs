@@ -0,0 +1,106 @@
+//! Lets daemon threads block instead of polling for shutdown. Each thread
+//! gets a clone of one [`Shutdown`]: timed waits sleep on its condvar and
+//! blocking accepts register a waker, and the main loop releases both with
+//! [`Shutdown::trigger`] when it stops.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+type Waker = Box<dyn Fn() + Send>;
+
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    triggered: AtomicBool,
+    lock: Mutex<()>,
+    condvar: Condvar,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.inner.triggered.load(Ordering::Relaxed)
+    }
+
+    /// Sleep for `duration` unless the daemon shuts down first. True when
+    /// it is shutting down.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        let mut guard = self.inner.lock.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if self.is_triggered() {
+                return true;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            guard = match self.inner.condvar.wait_timeout(guard, deadline - now) {
+                Ok((guard, _)) => guard,
+                Err(e) => e.into_inner().0,
+            };
+        }
+    }
+
+    /// Run `waker` on shutdown, e.g. to connect to a listener blocked in
+    /// `accept`. Runs it right away if shutdown has already been triggered.
+    pub fn on_shutdown(&self, waker: impl Fn() + Send + 'static) {
+        let mut wakers = self.inner.wakers.lock().unwrap_or_else(|e| e.into_inner());
+        if self.is_triggered() {
+            waker();
+        } else {
+            wakers.push(Box::new(waker));
+        }
+    }
+
+    /// Mark the daemon as stopping and wake every sleeping or blocked thread.
+    pub fn trigger(&self) {
+        let wakers = self.inner.wakers.lock().unwrap_or_else(|e| e.into_inner());
+        {
+            let _guard = self.inner.lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.inner.triggered.store(true, Ordering::Relaxed);
+        }
+        self.inner.condvar.notify_all();
+        for waker in wakers.iter() {
+            waker();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_returns_early_when_shutting_down() {
+        let shutdown = Shutdown::new();
+        assert!(!shutdown.sleep(Duration::from_millis(10)));
+
+        let waiter = shutdown.clone();
+        let sleeper = std::thread::spawn(move || waiter.sleep(Duration::from_secs(60)));
+        shutdown.trigger();
+        assert!(sleeper.join().unwrap());
+        assert!(shutdown.sleep(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn wakers_run_once_triggered() {
+        let shutdown = Shutdown::new();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let early = tx.clone();
+        shutdown.on_shutdown(move || early.send("early").unwrap());
+        assert!(rx.try_recv().is_err());
+        shutdown.trigger();
+        shutdown.on_shutdown(move || tx.send("late").unwrap());
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["early", "late"]);
+    }
+}
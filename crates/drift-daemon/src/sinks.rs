@@ -0,0 +1,293 @@
+//! External services events are published to (`[[sinks]]`): ntfy topics for
+//! phone pushes and MQTT for home automation.
+
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use drift_core::config::{SinkConfig, SinkKind};
+use drift_core::events::{self, Event};
+use drift_core::paths;
+
+/// A destination for events. Publishing is fire-and-forget: a sink that is
+/// down must never hold up the daemon.
+pub trait Sink {
+    fn publish(&self, event: &Event);
+}
+
+/// Configured sinks with the filter each one applies.
+#[derive(Default)]
+pub struct Sinks {
+    routes: Vec<(SinkConfig, Box<dyn Sink>)>,
+}
+
+impl Sinks {
+    pub fn from_config(configs: &[SinkConfig]) -> Self {
+        let routes = configs
+            .iter()
+            .map(|config| {
+                let sink: Box<dyn Sink> = match config.kind {
+                    SinkKind::Ntfy => Box::new(NtfySink::new(config)),
+                    SinkKind::Mqtt => Box::new(MqttSink::new(config)),
+                };
+                (config.clone(), sink)
+            })
+            .collect();
+        Self { routes }
+    }
+
+    pub fn publish(&self, event: &Event) {
+        for (config, sink) in &self.routes {
+            if accepts(config, event) {
+                sink.publish(event);
+            }
+        }
+    }
+}
+
+fn accepts(config: &SinkConfig, event: &Event) -> bool {
+    let min = config.min_priority.as_deref().map_or(0, events::priority_rank);
+    let priority = event.priority.as_deref().map_or(0, events::priority_rank);
+    priority >= min
+        && config.project.as_deref().is_none_or(|p| p == event.project)
+        && (config.events.is_empty()
+            || config.events.iter().any(|f| events::matches_type_filter(&event.event_type, f)))
+}
+
+/// Posts to `<server>/<topic>` with curl, using the event title and body as
+/// the notification and mapping the drift priority onto ntfy's 1-5 scale.
+struct NtfySink {
+    url: String,
+    token: Option<String>,
+}
+
+impl NtfySink {
+    fn new(config: &SinkConfig) -> Self {
+        let server = config.server.as_deref().unwrap_or("https://ntfy.sh");
+        Self {
+            url: format!("{}/{}", server.trim_end_matches('/'), config.topic),
+            token: config.token.clone(),
+        }
+    }
+
+    /// curl arguments. The body is read from stdin (`@-`) so a body that
+    /// starts with `@` is sent as text, not read as a file name.
+    fn args(&self, event: &Event) -> Vec<String> {
+        let title = match &event.title {
+            Some(title) => format!("[{}] {title}", event.project),
+            None => format!("[{}] {}", event.project, event.event_type),
+        };
+        let priority = event.priority.as_deref().map_or(0, events::priority_rank) + 1;
+
+        vec![
+            "-fsS".into(),
+            "--max-time".into(),
+            "5".into(),
+            "-H".into(),
+            format!("Title: {title}"),
+            "-H".into(),
+            format!("Priority: {priority}"),
+            "-H".into(),
+            format!("Tags: {}", event.event_type),
+            "--data-binary".into(),
+            "@-".into(),
+            self.url.clone(),
+        ]
+    }
+
+    fn body(event: &Event) -> String {
+        event
+            .body
+            .clone()
+            .or_else(|| event.title.clone())
+            .unwrap_or_else(|| event.event_type.clone())
+    }
+
+    /// curl config carrying the access token, which would be visible to
+    /// every user in `ps` as an argument.
+    fn auth_config(&self) -> Option<String> {
+        let token = self.token.as_ref()?;
+        let escaped = token.replace('\\', "\\\\").replace('"', "\\\"");
+        Some(format!("header = \"Authorization: Bearer {escaped}\"\n"))
+    }
+}
+
+impl Sink for NtfySink {
+    fn publish(&self, event: &Event) {
+        let mut args = self.args(event);
+        let config = match self.auth_config() {
+            Some(contents) => match write_private_file("ntfy", &contents) {
+                Some(path) => {
+                    args.insert(0, path.to_string_lossy().into());
+                    args.insert(0, "--config".into());
+                    Some(path)
+                }
+                // Sending without the token would only be rejected
+                None => return,
+            },
+            None => None,
+        };
+        spawn_reaped("curl", &args, Some(Self::body(event)), config);
+    }
+}
+
+/// Publishes the event JSON to `<topic>/<project>/<type>` with mosquitto_pub.
+struct MqttSink {
+    host: String,
+    port: Option<String>,
+    topic: String,
+}
+
+impl MqttSink {
+    fn new(config: &SinkConfig) -> Self {
+        let server = config.server.as_deref().unwrap_or("localhost");
+        let (host, port) = match server.rsplit_once(':') {
+            Some((host, port)) => (host.to_string(), Some(port.to_string())),
+            None => (server.to_string(), None),
+        };
+        Self {
+            host,
+            port,
+            topic: config.topic.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn args(&self, event: &Event) -> Vec<String> {
+        let mut args = vec!["-h".to_string(), self.host.clone()];
+        if let Some(port) = &self.port {
+            args.push("-p".into());
+            args.push(port.clone());
+        }
+        args.push("-t".into());
+        args.push(format!("{}/{}/{}", self.topic, event.project, event.event_type));
+        // Message on stdin (-s), not in argv where `ps` shows event bodies
+        args.push("-s".into());
+        args
+    }
+}
+
+impl Sink for MqttSink {
+    fn publish(&self, event: &Event) {
+        if let Ok(payload) = serde_json::to_string(event) {
+            spawn_reaped("mosquitto_pub", &self.args(event), Some(payload), None);
+        }
+    }
+}
+
+/// Run `program` in the background with `input` on its stdin. A thread
+/// feeds it and waits for it, so finished children don't pile up as
+/// zombies, then removes `cleanup`.
+pub(crate) fn spawn_reaped(program: &str, args: &[String], input: Option<String>, cleanup: Option<PathBuf>) {
+    let child = Command::new(program)
+        .args(args)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        if let Some(path) = cleanup {
+            let _ = fs::remove_file(path);
+        }
+        return;
+    };
+    let _ = std::thread::Builder::new().name(format!("reap-{program}")).spawn(move || {
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+        let _ = child.wait();
+        if let Some(path) = cleanup {
+            let _ = fs::remove_file(path);
+        }
+    });
+}
+
+/// Write `contents` to a file only the user can read, in the runtime dir
+/// next to the daemon's sockets.
+fn write_private_file(prefix: &str, contents: &str) -> Option<PathBuf> {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let dir = paths::emit_socket_path().parent()?.to_path_buf();
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let path = dir.join(format!(".{prefix}-{}-{n}", std::process::id()));
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)
+        .ok()?;
+    if file.write_all(contents.as_bytes()).is_err() {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink(kind: SinkKind, topic: &str) -> SinkConfig {
+        SinkConfig {
+            kind,
+            topic: topic.into(),
+            server: None,
+            min_priority: None,
+            events: Vec::new(),
+            project: None,
+            token: None,
+        }
+    }
+
+    fn event(event_type: &str, priority: &str) -> Event {
+        Event {
+            priority: Some(priority.into()),
+            ..Event::new(event_type, "myapp", "test").level("error").title("Build failed")
+        }
+    }
+
+    #[test]
+    fn accepts_by_priority_type_and_project() {
+        let mut config = sink(SinkKind::Ntfy, "drift-alerts");
+        config.min_priority = Some("high".into());
+        assert!(accepts(&config, &event("build.failed", "high")));
+        assert!(accepts(&config, &event("build.failed", "critical")));
+        assert!(!accepts(&config, &event("build.failed", "medium")));
+
+        config.events = vec!["build.*".into()];
+        assert!(!accepts(&config, &event("service.crashed", "critical")));
+
+        config.project = Some("other".into());
+        assert!(!accepts(&config, &event("build.failed", "critical")));
+    }
+
+    #[test]
+    fn ntfy_request() {
+        let mut config = sink(SinkKind::Ntfy, "drift-alerts");
+        config.server = Some("https://ntfy.example.com/".into());
+        config.token = Some("tk_abc".into());
+        let ntfy = NtfySink::new(&config);
+        let mut event = event("build.failed", "critical");
+        let args = ntfy.args(&event);
+        assert_eq!(args.last().unwrap(), "https://ntfy.example.com/drift-alerts");
+        assert!(args.contains(&"Title: [myapp] Build failed".to_string()));
+        assert!(args.contains(&"Priority: 5".to_string()));
+        // Neither the token nor the body is passed as an argument
+        assert!(!args.iter().any(|a| a.contains("tk_abc")));
+        assert!(args.windows(2).any(|w| w == ["--data-binary", "@-"]));
+        assert_eq!(ntfy.auth_config().unwrap(), "header = \"Authorization: Bearer tk_abc\"\n");
+
+        event.body = Some("@~/.ssh/id_ed25519".into());
+        assert_eq!(NtfySink::body(&event), "@~/.ssh/id_ed25519");
+    }
+
+    #[test]
+    fn mqtt_topic_and_broker() {
+        let mut config = sink(SinkKind::Mqtt, "drift/");
+        config.server = Some("broker.lan:1884".into());
+        let args = MqttSink::new(&config).args(&event("build.failed", "high"));
+        assert_eq!(args, ["-h", "broker.lan", "-p", "1884", "-t", "drift/myapp/build.failed", "-s"]);
+    }
+}
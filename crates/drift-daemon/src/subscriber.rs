@@ -1,18 +1,176 @@
+//! Broadcasts events to `subscribe.sock` clients. A client may send one
+//! JSON subscription line right after connecting, e.g.
+//! `{"projects":["myapp"],"types":["agent.*"],"min_priority":"medium"}`,
+//! and then only receives matching events; clients that send nothing get
+//! everything.
+
 use std::collections::VecDeque;
-use std::io::Write;
+use std::io::{ErrorKind, Read, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc;
-use std::time::Duration;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 use std::{fs, thread};
 
+use drift_client::Subscription;
 use drift_core::events::Event;
 use drift_core::paths;
 
+use crate::metrics::Metrics;
+use crate::shutdown::Shutdown;
+
+/// How long a new client has to send its subscription line before it is
+/// treated as subscribing to everything.
+const SUBSCRIBE_GRACE: Duration = Duration::from_millis(250);
+const MAX_SUBSCRIPTION_BYTES: usize = 4096;
+/// Undelivered events a subscriber may fall behind by (on top of its
+/// replay) before it is disconnected.
+const MAX_QUEUED_EVENTS: usize = 1000;
+/// How soon to retry writing to a client whose socket buffer was full.
+const FLUSH_RETRY: Duration = Duration::from_millis(50);
+
+struct Subscriber {
+    stream: UnixStream,
+    subscription: Subscription,
+    /// Set until the client has sent its subscription line or the grace
+    /// period is over; events are held back meanwhile.
+    pending: Option<Pending>,
+    /// Serialized events not yet written; `written` bytes of the front one
+    /// are already out.
+    queue: VecDeque<Vec<u8>>,
+    written: usize,
+    /// Most events `queue` may hold before the client counts as too slow
+    limit: usize,
+}
+
+struct Pending {
+    since: Instant,
+    line: Vec<u8>,
+    held: Vec<Event>,
+}
+
+impl Subscriber {
+    /// A client that just connected; `held` is the replay it gets once
+    /// subscribed. The stream stays non-blocking for its whole life.
+    fn new(stream: UnixStream, held: Vec<Event>) -> Self {
+        let limit = MAX_QUEUED_EVENTS + held.len();
+        Self {
+            stream,
+            subscription: Subscription::default(),
+            pending: Some(Pending { since: Instant::now(), line: Vec::new(), held }),
+            queue: VecDeque::new(),
+            written: 0,
+            limit,
+        }
+    }
+
+    /// Queue one event if the client wants it, or hold it back while the
+    /// subscription is pending. False once the queue is over its limit.
+    fn enqueue(&mut self, event: &Event) -> bool {
+        if let Some(pending) = &mut self.pending {
+            pending.held.push(event.clone());
+            return pending.held.len() <= self.limit;
+        }
+        if !self.subscription.matches(event) {
+            return true;
+        }
+        if let Ok(mut json) = serde_json::to_vec(event) {
+            json.push(b'\n');
+            self.queue.push_back(json);
+        }
+        self.queue.len() <= self.limit
+    }
+
+    /// Write as much of the queue as the socket takes without blocking.
+    /// False once the client is gone.
+    fn flush(&mut self) -> bool {
+        while let Some(front) = self.queue.front() {
+            match (&self.stream).write(&front[self.written..]) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.written += n;
+                    if self.written == front.len() {
+                        self.queue.pop_front();
+                        self.written = 0;
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+
+    /// Read whatever the client has sent so far and, once the subscription
+    /// line is complete (or the client is done or out of time), queue the
+    /// held events through it. False once the client is gone.
+    fn poll_pending(&mut self) -> bool {
+        let Some(pending) = &mut self.pending else {
+            return true;
+        };
+        let mut buf = [0u8; 512];
+        let mut done = pending.since.elapsed() >= SUBSCRIBE_GRACE;
+        loop {
+            match (&self.stream).read(&mut buf) {
+                // The client closed its write half: it will never subscribe
+                Ok(0) => {
+                    done = true;
+                    break;
+                }
+                Ok(n) => pending.line.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            }
+            if pending.line.contains(&b'\n') || pending.line.len() > MAX_SUBSCRIPTION_BYTES {
+                break;
+            }
+        }
+
+        if let Some(end) = pending.line.iter().position(|&b| b == b'\n') {
+            let line = String::from_utf8_lossy(&pending.line[..end]).trim().to_string();
+            if !line.is_empty() {
+                match Subscription::parse(&line) {
+                    Ok(subscription) => self.subscription = subscription,
+                    Err(e) => {
+                        let reply = serde_json::json!({ "ok": false, "error": format!("invalid subscription: {e}") });
+                        let _ = writeln!(&self.stream, "{reply}");
+                        return false;
+                    }
+                }
+            }
+            done = true;
+        } else if pending.line.len() > MAX_SUBSCRIPTION_BYTES {
+            let reply = serde_json::json!({ "ok": false, "error": "subscription line too long" });
+            let _ = writeln!(&self.stream, "{reply}");
+            return false;
+        }
+        if !done {
+            return true;
+        }
+
+        let held = std::mem::take(&mut pending.held);
+        self.pending = None;
+        for event in &held {
+            self.enqueue(event);
+        }
+        true
+    }
+}
+
+/// What wakes the manager: an event to fan out, a client that connected,
+/// or daemon shutdown.
+enum Input {
+    Event(Box<Event>),
+    Connected(UnixStream),
+    Shutdown,
+}
+
 pub fn run_subscriber_manager(
     rx: mpsc::Receiver<Event>,
-    shutdown: &'static AtomicBool,
+    shutdown: Shutdown,
     replay_count: usize,
+    metrics: Arc<Metrics>,
 ) {
     let sock_path = paths::subscribe_socket_path();
     if let Some(parent) = sock_path.parent() {
@@ -27,60 +185,160 @@ pub fn run_subscriber_manager(
             return;
         }
     };
-    if let Err(e) = listener.set_nonblocking(true) {
-        eprintln!("failed to set subscribe socket nonblocking: {e}");
+
+    // Events and new clients both arrive on one channel, so the manager
+    // sleeps until there is something to do
+    let (input_tx, input) = mpsc::channel::<Input>();
+    let accept_tx = input_tx.clone();
+    let accept_shutdown = shutdown.clone();
+    let spawned = thread::Builder::new().name("subscribe-accept".into()).spawn(move || {
+        for stream in listener.incoming() {
+            if accept_shutdown.is_triggered() {
+                break;
+            }
+            if let Ok(stream) = stream {
+                if accept_tx.send(Input::Connected(stream)).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    if let Err(e) = spawned {
+        eprintln!("failed to start subscribe socket listener: {e}");
         let _ = fs::remove_file(&sock_path);
         return;
     }
-
-    let mut subscribers: Vec<UnixStream> = Vec::new();
-    let mut replay_buffer: VecDeque<Event> = VecDeque::with_capacity(replay_count + 1);
-
-    while !shutdown.load(Ordering::Relaxed) {
-        loop {
-            match listener.accept() {
-                Ok((stream, _)) => {
-                    let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
-                    let mut alive = true;
-                    for event in &replay_buffer {
-                        if let Ok(json) = serde_json::to_string(event) {
-                            if writeln!(&stream, "{json}").is_err() {
-                                alive = false;
-                                break;
-                            }
-                        }
-                    }
-                    if alive {
-                        subscribers.push(stream);
-                    }
-                }
-                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
-                Err(_) => break,
+    let forward_tx = input_tx.clone();
+    let spawned = thread::Builder::new().name("subscribe-forward".into()).spawn(move || {
+        for event in rx {
+            if forward_tx.send(Input::Event(Box::new(event))).is_err() {
+                return;
             }
         }
+        let _ = forward_tx.send(Input::Shutdown);
+    });
+    if let Err(e) = spawned {
+        eprintln!("failed to start subscriber fan-out: {e}");
+        let _ = fs::remove_file(&sock_path);
+        return;
+    }
+    let wake_path = sock_path.clone();
+    shutdown.on_shutdown(move || {
+        let _ = input_tx.send(Input::Shutdown);
+        let _ = UnixStream::connect(&wake_path);
+    });
 
-        loop {
-            match rx.try_recv() {
-                Ok(event) => {
-                    replay_buffer.push_back(event.clone());
+    let mut subscribers: Vec<Subscriber> = Vec::new();
+    let mut replay_buffer: VecDeque<Event> = VecDeque::with_capacity(replay_count + 1);
+
+    while !shutdown.is_triggered() {
+        let next = match next_wakeup(&subscribers) {
+            Some(timeout) => input.recv_timeout(timeout).ok(),
+            None => input.recv().ok(),
+        };
+        for msg in next.into_iter().chain(std::iter::from_fn(|| input.try_recv().ok())) {
+            match msg {
+                Input::Event(event) => {
+                    replay_buffer.push_back((*event).clone());
                     if replay_buffer.len() > replay_count {
                         replay_buffer.pop_front();
                     }
 
-                    if let Ok(json) = serde_json::to_string(&event) {
-                        subscribers.retain(|stream| writeln!(&*stream, "{json}").is_ok());
+                    subscribers.retain_mut(|sub| {
+                        if sub.enqueue(&event) {
+                            return true;
+                        }
+                        let dropped = sub.queue.len() + sub.pending.as_ref().map_or(0, |p| p.held.len());
+                        eprintln!("subscriber too slow, disconnecting ({dropped} undelivered events)");
+                        metrics.record_slow_subscriber(dropped);
+                        false
+                    });
+                }
+                Input::Connected(stream) => {
+                    // Writes never block: a client that stops reading only
+                    // grows its own queue
+                    if stream.set_nonblocking(true).is_ok() {
+                        subscribers.push(Subscriber::new(stream, replay_buffer.iter().cloned().collect()));
                     }
                 }
-                Err(mpsc::TryRecvError::Empty) => break,
-                Err(mpsc::TryRecvError::Disconnected) => {
+                Input::Shutdown => {
                     let _ = fs::remove_file(&sock_path);
                     return;
                 }
             }
         }
 
-        thread::sleep(Duration::from_millis(50));
+        subscribers.retain_mut(|sub| sub.poll_pending() && sub.flush());
+        metrics.set_subscribers(subscribers.len());
     }
 
     let _ = fs::remove_file(&sock_path);
 }
+
+/// How long the manager may sleep with no input: until the first pending
+/// subscription times out, or briefly while a client's socket is full.
+/// None when only new input can change anything.
+fn next_wakeup(subscribers: &[Subscriber]) -> Option<Duration> {
+    subscribers
+        .iter()
+        .filter_map(|sub| match &sub.pending {
+            Some(pending) => Some(SUBSCRIBE_GRACE.saturating_sub(pending.since.elapsed())),
+            None => (!sub.queue.is_empty()).then_some(FLUSH_RETRY),
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    fn event(project: &str, event_type: &str, priority: Option<&str>) -> Event {
+        Event { priority: priority.map(String::from), ..Event::new(event_type, project, "test") }
+    }
+
+    #[test]
+    fn held_events_flushed_through_subscription() {
+        let (server, client) = UnixStream::pair().unwrap();
+        server.set_nonblocking(true).unwrap();
+        let mut sub = Subscriber::new(server, vec![event("web", "agent.completed", None), event("api", "agent.completed", None)]);
+
+        // Nothing sent yet and still within the grace period
+        assert!(sub.poll_pending());
+        assert!(sub.pending.is_some());
+
+        writeln!(&client, r#"{{"projects":["web"]}}"#).unwrap();
+        assert!(sub.poll_pending());
+        assert!(sub.pending.is_none());
+        assert!(sub.enqueue(&event("api", "service.crashed", None)));
+        assert!(sub.enqueue(&event("web", "service.crashed", None)));
+        assert!(sub.flush());
+        assert!(sub.queue.is_empty());
+        drop(sub);
+
+        let types: Vec<String> = BufReader::new(client)
+            .lines()
+            .map(|l| serde_json::from_str::<Event>(&l.unwrap()).unwrap().event_type)
+            .collect();
+        assert_eq!(types, ["agent.completed", "service.crashed"]);
+    }
+
+    #[test]
+    fn subscriber_that_stops_reading_overflows_its_queue() {
+        let (server, _client) = UnixStream::pair().unwrap();
+        server.set_nonblocking(true).unwrap();
+        let mut sub = Subscriber::new(server, Vec::new());
+        sub.pending = None;
+
+        let mut crashed = event("web", "service.crashed", None);
+        crashed.body = Some("x".repeat(1024));
+        let mut sent = 0;
+        while sub.enqueue(&crashed) {
+            // Writes never block, however full the socket buffer is
+            assert!(sub.flush());
+            sent += 1;
+            assert!(sent < 100_000, "queue never filled up");
+        }
+        assert_eq!(sub.queue.len(), MAX_QUEUED_EVENTS + 1);
+    }
+}
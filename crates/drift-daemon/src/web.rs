@@ -0,0 +1,188 @@
+//! Read-only status page served next to `/metrics`: project and service
+//! status as JSON, and the live event stream as server-sent events.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use drift_core::supervisor::{self, ServicesState};
+use drift_core::{paths, registry};
+
+use crate::shutdown::Shutdown;
+use crate::state::DaemonState;
+
+pub const INDEX_HTML: &str = include_str!("web.html");
+
+/// Concurrent `/events` streams; each holds a thread and a subscription.
+const MAX_STREAMS: usize = 8;
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+static STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+/// `/api/status`: every project with its workspace and services.
+pub fn status_json() -> String {
+    let state: Option<DaemonState> = std::fs::read_to_string(paths::daemon_state_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok());
+    let projects: Vec<String> = registry::list_projects()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| p.project.name)
+        .collect();
+    let services: Vec<ServicesState> = projects.iter().filter_map(|p| supervisor::read_state(p)).collect();
+    build_status(state.as_ref(), &projects, &services).to_string()
+}
+
+fn build_status(state: Option<&DaemonState>, projects: &[String], services: &[ServicesState]) -> Value {
+    let projects: Vec<Value> = projects
+        .iter()
+        .map(|name| {
+            let workspace = state.and_then(|s| s.workspace_projects.iter().find(|w| &w.project == name));
+            let services: Vec<Value> = services
+                .iter()
+                .find(|s| &s.project == name)
+                .map(|s| {
+                    s.services
+                        .iter()
+                        .map(|svc| {
+                            json!({
+                                "name": svc.name,
+                                "status": svc.status,
+                                "restarts": svc.restart_count,
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            json!({
+                "name": name,
+                "open": workspace.is_some(),
+                "focused": workspace.is_some_and(|w| w.is_focused),
+                "windows": workspace.map_or(0, |w| w.window_count),
+                "services": services,
+            })
+        })
+        .collect();
+    json!({
+        "daemon": state.is_some(),
+        "active_project": state.and_then(|s| s.active_project.clone()),
+        "projects": projects,
+    })
+}
+
+/// Answer `/events` on its own thread: relay the subscribe socket (including
+/// its replay of recent events) as server-sent events until either side
+/// closes or the daemon shuts down.
+pub fn spawn_event_stream(stream: TcpStream, shutdown: Shutdown) {
+    if STREAMS.fetch_add(1, Ordering::SeqCst) >= MAX_STREAMS {
+        STREAMS.fetch_sub(1, Ordering::SeqCst);
+        let mut stream = stream;
+        let _ = write!(
+            stream,
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+        return;
+    }
+    let spawned = std::thread::Builder::new().name("web-events".into()).spawn(move || {
+        if let Err(e) = relay_events(stream, &shutdown) {
+            eprintln!("web event stream ended: {e}");
+        }
+        STREAMS.fetch_sub(1, Ordering::SeqCst);
+    });
+    if spawned.is_err() {
+        STREAMS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn relay_events(mut stream: TcpStream, shutdown: &Shutdown) -> std::io::Result<()> {
+    let subscription = UnixStream::connect(paths::subscribe_socket_path())?;
+    subscription.set_read_timeout(Some(Duration::from_secs(1)))?;
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n"
+    )?;
+
+    let mut reader = BufReader::new(subscription);
+    let mut line = String::new();
+    let mut last_write = Instant::now();
+    while !shutdown.is_triggered() {
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                // Partial lines stay buffered across read timeouts
+                if line.ends_with('\n') {
+                    write!(stream, "data: {}\n\n", line.trim_end())?;
+                    last_write = Instant::now();
+                    line.clear();
+                }
+            }
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+        // Comments keep proxies from timing out and reveal a closed browser tab
+        if last_write.elapsed() >= KEEPALIVE {
+            stream.write_all(b": keepalive\n\n")?;
+            last_write = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::WorkspaceProject;
+    use drift_core::supervisor::{ServiceState, ServiceStatus, StartReason};
+
+    #[test]
+    fn status_joins_workspaces_and_services() {
+        let state = DaemonState {
+            pid: 1,
+            active_project: Some("myapp".into()),
+            workspace_projects: vec![WorkspaceProject {
+                workspace_id: 3,
+                workspace_name: "myapp".into(),
+                project: "myapp".into(),
+                is_active: true,
+                is_focused: true,
+                window_count: 2,
+            }],
+            ..Default::default()
+        };
+        let services = vec![ServicesState {
+            supervisor_pid: 2,
+            project: "myapp".into(),
+            services: vec![ServiceState {
+                name: "api".into(),
+                pid: Some(10),
+                status: ServiceStatus::Running,
+                restart_count: 1,
+                started_at: None,
+                exit_code: None,
+                is_agent: false,
+                agent_type: None,
+                start_reason: StartReason::Initial,
+                previous_exit_code: None,
+                cpu_percent: None,
+                rss_bytes: None,
+                start_ticks: None,
+                scope_unit: None,
+                next_run: None,
+            }],
+            suspended: false,
+        }];
+        let projects = vec!["myapp".to_string(), "notes".to_string()];
+
+        let status = build_status(Some(&state), &projects, &services);
+        assert_eq!(status["active_project"], "myapp");
+        assert_eq!(status["projects"][0]["open"], true);
+        assert_eq!(status["projects"][0]["windows"], 2);
+        assert_eq!(status["projects"][0]["services"][0]["restarts"], 1);
+        assert_eq!(status["projects"][1]["open"], false);
+        assert_eq!(status["projects"][1]["services"], json!([]));
+    }
+}
@@ -113,6 +113,10 @@ QtObject {
         return root.textDim;
     }
 
+    function projectLabel(p) {
+        return p.icon ? p.icon + " " + p.name : p.name;
+    }
+
     property var closedProjectList: {
         var openNames = {};
         var ws = root.driftState ? (root.driftState.workspaces || []) : [];
@@ -324,7 +328,7 @@ QtObject {
                                                 }
 
                                                 Text {
-                                                    text: wsDelegate.hasProject ? wsDelegate.ws.project.name : wsDelegate.displayName
+                                                    text: wsDelegate.hasProject ? root.projectLabel(wsDelegate.ws.project) : wsDelegate.displayName
                                                     font.family: "JetBrainsMono Nerd Font"
                                                     font.pixelSize: 13
                                                     color: wsDelegate.ws.is_focused ? root.textColor : root.textDim
@@ -465,7 +469,7 @@ QtObject {
                                                 }
 
                                                 Text {
-                                                    text: wsDelegate.hasProject ? wsDelegate.ws.project.name : wsDelegate.displayName
+                                                    text: wsDelegate.hasProject ? root.projectLabel(wsDelegate.ws.project) : wsDelegate.displayName
                                                     font.family: "JetBrainsMono Nerd Font"
                                                     font.pixelSize: wsDelegate.hasProject ? 14 : 13
                                                     font.bold: wsDelegate.hasProject
@@ -1010,7 +1014,7 @@ QtObject {
                                         }
 
                                         Text {
-                                            text: closedDelegate.modelData.name
+                                            text: root.projectLabel(closedDelegate.modelData)
                                             font.family: "JetBrainsMono Nerd Font"
                                             font.pixelSize: 12
                                             color: root.textDim
@@ -109,6 +109,12 @@ in
       description = "Run the drift daemon as a systemd user service.";
     };
 
+    daemon.autostart = lib.mkOption {
+      type = lib.types.bool;
+      default = false;
+      description = "Reopen the last session's projects when niri comes up (drift daemon --autostart).";
+    };
+
     commander = {
       enable = lib.mkOption {
         type = lib.types.bool;
@@ -223,7 +229,7 @@ in
         After = [ "graphical-session.target" ];
       };
       Service = {
-        ExecStart = "${cfg.package}/bin/drift daemon";
+        ExecStart = "${cfg.package}/bin/drift daemon" + lib.optionalString cfg.daemon.autostart " --autostart";
         Restart = "on-failure";
         RestartSec = 3;
       };